@@ -79,6 +79,7 @@ impl Into<indradb::Identifier> for Identifier {
 pub enum EdgeDirection {
     Outbound,
     Inbound,
+    Both,
 }
 
 impl Into<indradb::EdgeDirection> for EdgeDirection {
@@ -86,6 +87,22 @@ impl Into<indradb::EdgeDirection> for EdgeDirection {
         match self {
             EdgeDirection::Outbound => indradb::EdgeDirection::Outbound,
             EdgeDirection::Inbound => indradb::EdgeDirection::Inbound,
+            EdgeDirection::Both => indradb::EdgeDirection::Both,
+        }
+    }
+}
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+pub enum RangeDirection {
+    Ascending,
+    Descending,
+}
+
+impl Into<indradb::RangeDirection> for RangeDirection {
+    fn into(self) -> indradb::RangeDirection {
+        match self {
+            RangeDirection::Ascending => indradb::RangeDirection::Ascending,
+            RangeDirection::Descending => indradb::RangeDirection::Descending,
         }
     }
 }
@@ -140,16 +157,22 @@ impl Into<indradb::Query> for Query {
 #[derive(Arbitrary, Clone, Debug, PartialEq)]
 pub struct RangeVertexQuery {
     pub limit: u32,
-    pub t: Option<Identifier>,
+    pub t_filter: Vec<Identifier>,
     pub start_id: Option<Uuid>,
+    pub property_filter: Option<(Identifier, Json)>,
+    pub direction: RangeDirection,
 }
 
 impl Into<indradb::RangeVertexQuery> for RangeVertexQuery {
     fn into(self) -> indradb::RangeVertexQuery {
         indradb::RangeVertexQuery {
             limit: self.limit,
-            t: self.t.map(|t| t.into()),
+            t_filter: self.t_filter.into_iter().map(|t| t.into()).collect(),
             start_id: self.start_id.map(|id| id.into()),
+            property_filter: self
+                .property_filter
+                .map(|(name, value)| (name.into(), indradb::Json::new(value.into()))),
+            direction: self.direction.into(),
         }
     }
 }
@@ -174,9 +197,7 @@ pub struct VertexWithPropertyPresenceQuery {
 
 impl Into<indradb::VertexWithPropertyPresenceQuery> for VertexWithPropertyPresenceQuery {
     fn into(self) -> indradb::VertexWithPropertyPresenceQuery {
-        indradb::VertexWithPropertyPresenceQuery {
-            name: self.name.into(),
-        }
+        indradb::VertexWithPropertyPresenceQuery { name: self.name.into() }
     }
 }
 
@@ -215,9 +236,7 @@ pub struct EdgeWithPropertyPresenceQuery {
 
 impl Into<indradb::EdgeWithPropertyPresenceQuery> for EdgeWithPropertyPresenceQuery {
     fn into(self) -> indradb::EdgeWithPropertyPresenceQuery {
-        indradb::EdgeWithPropertyPresenceQuery {
-            name: self.name.into(),
-        }
+        indradb::EdgeWithPropertyPresenceQuery { name: self.name.into() }
     }
 }
 
@@ -241,7 +260,7 @@ pub struct PipeQuery {
     pub inner: Box<Query>,
     pub direction: EdgeDirection,
     pub limit: u32,
-    pub t: Option<Identifier>,
+    pub t_filter: Vec<Identifier>,
 }
 
 impl Into<indradb::PipeQuery> for PipeQuery {
@@ -250,7 +269,7 @@ impl Into<indradb::PipeQuery> for PipeQuery {
             inner: Box::new((*self.inner).into()),
             direction: self.direction.into(),
             limit: self.limit,
-            t: self.t.map(|t| t.into()),
+            t_filter: self.t_filter.into_iter().map(|t| t.into()).collect(),
         }
     }
 }
@@ -526,7 +545,8 @@ macro_rules! cmp {
 fuzz_target!(|ops: Vec<Op>| {
     let d1 = MemoryDatastore::new_db();
     let rocksdb_dir = tempdir().unwrap();
-    let d2 = RocksdbDatastore::new_db_with_options(rocksdb_dir.path(), &RocksdbDatastore::get_options(Some(1))).unwrap();
+    let d2 =
+        RocksdbDatastore::new_db_with_options(rocksdb_dir.path(), &RocksdbDatastore::get_options(Some(1))).unwrap();
 
     for op in ops {
         match op {
@@ -0,0 +1,698 @@
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+use crate::database::{
+    add_label_in_txn, bulk_delete_in_txn, compare_and_set_property_in_txn, copy_vertex_properties_in_txn,
+    delete_all_properties_in_txn, delete_in_txn, get_batch_in_txn, get_in_txn, get_one_in_txn,
+    group_count_by_property_in_txn, increment_property_in_txn, merge_properties_in_txn, remove_label_in_txn,
+    set_properties_batch_in_txn, set_properties_in_txn, Transaction,
+};
+use crate::errors::{Error, Result};
+use crate::models::{
+    BulkDeleteItem, BulkInsertItem, Cursor, DatastoreStats, Edge, EdgeDirection, Identifier, Json, JsonType,
+    Neighborhood, Query, QueryOutputValue, QueryPlan, RangeVertexQuery, Vertex,
+};
+use crate::util;
+use uuid::Uuid;
+
+/// Specifies a datastore whose transactions can be acquired without blocking
+/// the calling thread. This is the async counterpart to `Datastore`.
+///
+/// Only acquiring a transaction is async - e.g. waiting on a lock held by
+/// another transaction. Once acquired, transactions are the same as those
+/// used by `Datastore`.
+pub trait AsyncDatastore {
+    /// The datastore transaction type.
+    type Transaction<'a>: Transaction<'a>
+    where
+        Self: 'a;
+    /// Creates a new transaction. Its changes should be committed with
+    /// `Transaction::commit`, since datastores that buffer changes discard
+    /// them when an uncommitted transaction is dropped.
+    fn transaction(&self) -> impl Future<Output = Self::Transaction<'_>> + Send;
+    /// Creates a new read-only transaction. Any method on it that would
+    /// mutate the graph returns `Error::ReadOnly` instead.
+    fn readonly_transaction(&self) -> impl Future<Output = Self::Transaction<'_>> + Send;
+    /// Generates the ID of a vertex created from just a type. By default,
+    /// this generates a UUID v1.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the new vertex.
+    fn generate_vertex_id(&self, _t: Identifier) -> Uuid {
+        util::generate_uuid_v1()
+    }
+}
+
+/// The IndraDB database, for use from async code.
+///
+/// This is the async counterpart to `Database`, and shares its logic.
+/// Methods return futures that wait for a transaction without blocking the
+/// async runtime, which makes it suitable for embedding IndraDB in an async
+/// service without wrapping every call in `spawn_blocking`.
+pub struct AsyncDatabase<D: AsyncDatastore> {
+    pub datastore: D,
+}
+
+impl<D: AsyncDatastore> AsyncDatabase<D> {
+    /// Creates a new database.
+    ///
+    /// # Arguments
+    /// * `datastore`: The underlying datastore to use.
+    pub fn new(datastore: D) -> AsyncDatabase<D> {
+        Self { datastore }
+    }
+
+    /// Syncs persisted content. What this guarantees depends on the
+    /// datastore; see `Database::sync`.
+    pub async fn sync(&self) -> Result<()> {
+        let txn = self.datastore.transaction().await;
+        txn.sync()
+    }
+
+    /// Compacts the datastore's storage, e.g. to clear out what's left behind
+    /// by large deletes. Reads can continue while this runs, but it can be
+    /// expensive, so it's intended for off-peak maintenance. Depending on the
+    /// datastore implementation, this may be a no-op.
+    pub async fn compact(&self) -> Result<()> {
+        let txn = self.datastore.transaction().await;
+        txn.compact()
+    }
+
+    /// Creates a new vertex. Returns whether the vertex was successfully
+    /// created - if this is false, it's because a vertex with the same UUID
+    /// already exists.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create.
+    pub async fn create_vertex(&self, vertex: &Vertex) -> Result<bool> {
+        let mut txn = self.datastore.transaction().await;
+        let created = txn.create_vertex(vertex)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new vertex with just a type specification. Returns the new
+    /// vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    pub async fn create_vertex_from_type(&self, t: Identifier) -> Result<Uuid> {
+        let v = Vertex::with_id(self.datastore.generate_vertex_id(t), t);
+
+        if !self.create_vertex(&v).await? {
+            Err(Error::UuidTaken)
+        } else {
+            Ok(v.id)
+        }
+    }
+
+    /// Creates a new vertex of a given type along with its initial
+    /// properties, in one transaction. Returns the new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    /// * `props`: The property names and values.
+    pub async fn create_vertex_with_properties(&self, t: Identifier, props: Vec<(Identifier, Json)>) -> Result<Uuid> {
+        let v = Vertex::with_id(self.datastore.generate_vertex_id(t), t);
+        let mut txn = self.datastore.transaction().await;
+        if !txn.create_vertex_with_properties(&v, props)? {
+            return Err(Error::UuidTaken);
+        }
+        txn.commit()?;
+        Ok(v.id)
+    }
+
+    /// Creates a new vertex of a given type along with its labels, in one
+    /// transaction. Returns the new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    /// * `labels`: The labels.
+    pub async fn create_vertex_with_labels(&self, t: Identifier, labels: BTreeSet<Identifier>) -> Result<Uuid> {
+        let v = Vertex::with_id(self.datastore.generate_vertex_id(t), t);
+        let mut txn = self.datastore.transaction().await;
+        if !txn.create_vertex_with_labels(&v, labels)? {
+            return Err(Error::UuidTaken);
+        }
+        txn.commit()?;
+        Ok(v.id)
+    }
+
+    /// Atomically creates a vertex along with its properties if it doesn't
+    /// exist, or sets the properties on it if it does. Returns whether the
+    /// vertex was created. An existing vertex keeps its type, and its other
+    /// properties.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create or update.
+    /// * `props`: The property names and values.
+    pub async fn upsert_vertex(&self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        let mut txn = self.datastore.transaction().await;
+        let created = txn.upsert_vertex(vertex, props)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new edge. Returns whether the edge was successfully
+    /// created - if this is false, it's because one of the specified vertices
+    /// is missing.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    pub async fn create_edge(&self, edge: &Edge) -> Result<bool> {
+        let mut txn = self.datastore.transaction().await;
+        let created = txn.create_edge(edge)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new edge that expires after `ttl`. Returns whether the edge
+    /// was successfully created.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    /// * `ttl`: How long the edge should live for.
+    pub async fn create_edge_with_ttl(&self, edge: &Edge, ttl: Duration) -> Result<bool> {
+        let mut txn = self.datastore.transaction().await;
+        let created = txn.create_edge_with_ttl(edge, ttl)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new edge, but only if it doesn't already exist. Returns
+    /// whether the edge was newly created.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    pub async fn create_edge_if_absent(&self, edge: &Edge) -> Result<bool> {
+        let mut txn = self.datastore.transaction().await;
+        let created = txn.create_edge_if_absent(edge)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates an edge along with its reverse, in one transaction, e.g. to
+    /// model an undirected relationship. Returns whether the edges were
+    /// successfully created - if this is false, it's because one of the
+    /// specified vertices is missing.
+    ///
+    /// # Arguments
+    /// * `edge`: One of the two edges to create.
+    pub async fn create_symmetric_edge(&self, edge: &Edge) -> Result<bool> {
+        let mut txn = self.datastore.transaction().await;
+        let created = txn.create_symmetric_edge(edge)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Deletes an edge along with its reverse, in one transaction. Returns
+    /// the number of edges deleted.
+    ///
+    /// # Arguments
+    /// * `edge`: One of the two edges to delete.
+    pub async fn delete_symmetric_edge(&self, edge: &Edge) -> Result<u64> {
+        let mut txn = self.datastore.transaction().await;
+        let count = txn.delete_symmetric_edge(edge)?;
+        txn.commit()?;
+        Ok(count)
+    }
+
+    /// Gets the number of vertices of a given type.
+    ///
+    /// # Arguments
+    /// * `t`: The vertex type to count.
+    pub async fn get_vertex_count_by_type(&self, t: Identifier) -> Result<u64> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.vertex_count_by_type(t)
+    }
+
+    /// Gets the distinct names of vertex properties currently in use, in
+    /// sorted order.
+    pub async fn get_vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.vertex_property_names()
+    }
+
+    /// Gets the distinct names of edge properties currently in use, in
+    /// sorted order.
+    pub async fn get_edge_property_names(&self) -> Result<Vec<Identifier>> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.edge_property_names()
+    }
+
+    /// Gets the names of indexed properties, including paths to nested
+    /// fields, in sorted order.
+    pub async fn get_indexed_properties(&self) -> Result<Vec<Identifier>> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.indexed_properties()
+    }
+
+    /// Gets the registered property schemas, as pairs of property name and
+    /// expected value type, in sorted order of name.
+    pub async fn get_property_schemas(&self) -> Result<Vec<(Identifier, JsonType)>> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.property_schemas()
+    }
+
+    /// Gets statistics about the datastore, e.g. for monitoring. This is
+    /// cheaper than counting with queries, but some datastores estimate the
+    /// counts.
+    pub async fn get_stats(&self) -> Result<DatastoreStats> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.stats()
+    }
+
+    /// Gets values specified by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn get<Q: Into<Query>>(&self, q: Q) -> Result<Vec<QueryOutputValue>> {
+        let q = q.into();
+        let txn = self.datastore.readonly_transaction().await;
+        get_in_txn(&txn, q)
+    }
+
+    /// Describes how a query would run without running it, e.g. to check
+    /// whether it uses a property index.
+    ///
+    /// # Arguments
+    /// * `q`: The query to describe.
+    pub async fn explain<Q: Into<Query>>(&self, q: Q) -> Result<QueryPlan> {
+        let q = q.into();
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).explain(q) }
+    }
+
+    /// Gets values specified by several queries, all from one transaction.
+    /// The values of each query are returned in the same position as the
+    /// query. If any query fails, the whole batch fails.
+    ///
+    /// # Arguments
+    /// * `qs`: The queries to run.
+    pub async fn get_batch(&self, qs: Vec<Query>) -> Result<Vec<Vec<QueryOutputValue>>> {
+        let txn = self.datastore.readonly_transaction().await;
+        get_batch_in_txn(&txn, qs)
+    }
+
+    /// Gets the first value specified by a query, or `None` if there are no
+    /// values. Where possible, the query stops as soon as a value is found,
+    /// rather than getting every value. Counts are returned as-is.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn get_one<Q: Into<Query>>(&self, q: Q) -> Result<Option<QueryOutputValue>> {
+        let q = q.into();
+        let txn = self.datastore.readonly_transaction().await;
+        get_one_in_txn(&txn, q)
+    }
+
+    /// Gets a page of vertices from a range query, along with a cursor for
+    /// getting the next page. See `Database::get_vertex_page`.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run. Its limit is the page size.
+    /// * `cursor`: Where to continue from, or `None` for the first page.
+    pub async fn get_vertex_page(
+        &self,
+        q: RangeVertexQuery,
+        cursor: Option<&Cursor>,
+    ) -> Result<(Vec<Vertex>, Option<Cursor>)> {
+        let q = match cursor {
+            Some(cursor) => cursor.apply(q)?,
+            None => q,
+        };
+        let vertices = util::extract_vertices(self.get(q.clone()).await?).unwrap_or_default();
+        let next = Cursor::after(&q, &vertices);
+        Ok((vertices, next))
+    }
+
+    /// Gets a vertex if it exists, or `None` otherwise.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex to get.
+    pub async fn get_vertex(&self, id: Uuid) -> Result<Option<Vertex>> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).get_vertex(id) }
+    }
+
+    /// Checks whether a vertex exists.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex to check.
+    pub async fn contains_vertex(&self, id: Uuid) -> Result<bool> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).contains_vertex(id) }
+    }
+
+    /// Gets the labels of a vertex, which are empty if the vertex doesn't
+    /// exist.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    pub async fn get_vertex_labels(&self, id: Uuid) -> Result<BTreeSet<Identifier>> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.vertex_labels(id)
+    }
+
+    /// Gets when a vertex was created, or `None` if it doesn't exist or was
+    /// created before its datastore tracked creation times.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    pub async fn get_vertex_created_at(&self, id: Uuid) -> Result<Option<SystemTime>> {
+        let txn = self.datastore.readonly_transaction().await;
+        txn.vertex_created_at(id)
+    }
+
+    /// Gets an edge if it exists, or `None` otherwise.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to get.
+    pub async fn get_edge(&self, edge: &Edge) -> Result<Option<Edge>> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).get_edge(edge) }
+    }
+
+    /// Checks whether an edge exists.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to check.
+    pub async fn contains_edge(&self, edge: &Edge) -> Result<bool> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).contains_edge(edge) }
+    }
+
+    /// Counts the vertices or edges returned by a query, grouped by the value
+    /// of a property. Returns each distinct value with its count, most common
+    /// first.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name to group by.
+    pub async fn group_count_by_property<Q: Into<Query>>(&self, q: Q, name: Identifier) -> Result<Vec<(Json, u64)>> {
+        let q = q.into();
+        let txn = self.datastore.readonly_transaction().await;
+        group_count_by_property_in_txn(&txn, q, name)
+    }
+
+    /// Deletes values specified by a query. Returns the number of vertices,
+    /// edges or properties deleted.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn delete<Q: Into<Query>>(&self, q: Q) -> Result<u64> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        let count = delete_in_txn(&mut txn, q)?;
+        txn.commit()?;
+        Ok(count)
+    }
+
+    /// Deletes every property of the vertices or edges specified by a query,
+    /// in one transaction. Returns the number of properties deleted.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn delete_all_properties<Q: Into<Query>>(&self, q: Q) -> Result<u64> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        let count = delete_all_properties_in_txn(&mut txn, q)?;
+        txn.commit()?;
+        Ok(count)
+    }
+
+    /// Sets properties.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `value`: The property value.
+    pub async fn set_properties<Q: Into<Query>>(&self, q: Q, name: Identifier, value: &Json) -> Result<()> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        set_properties_in_txn(&mut txn, q, name, value)?;
+        txn.commit()
+    }
+
+    /// Adds a label to the vertices returned by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `label`: The label.
+    pub async fn add_label<Q: Into<Query>>(&self, q: Q, label: Identifier) -> Result<()> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        add_label_in_txn(&mut txn, q, label)?;
+        txn.commit()
+    }
+
+    /// Removes a label from the vertices returned by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `label`: The label.
+    pub async fn remove_label<Q: Into<Query>>(&self, q: Q, label: Identifier) -> Result<()> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        remove_label_in_txn(&mut txn, q, label)?;
+        txn.commit()
+    }
+
+    /// Sets several properties in one transaction. If a name appears more
+    /// than once, the last value wins.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `props`: The property names and values.
+    pub async fn set_properties_batch<Q: Into<Query>>(&self, q: Q, props: Vec<(Identifier, Json)>) -> Result<()> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        set_properties_batch_in_txn(&mut txn, q, props)?;
+        txn.commit()
+    }
+
+    /// Atomically adds `delta` to a numeric vertex property, treating missing
+    /// properties as 0.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `delta`: The amount to add.
+    pub async fn increment_property<Q: Into<Query>>(&self, q: Q, name: Identifier, delta: f64) -> Result<()> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        increment_property_in_txn(&mut txn, q, name, delta)?;
+        txn.commit()
+    }
+
+    /// Atomically sets a vertex property to a new value, but only if its
+    /// current value is equal to an expected one. Returns whether the
+    /// property was set.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `expected`: The expected current value, or `None` if the property
+    ///   is expected to be missing.
+    /// * `new`: The value to set.
+    pub async fn compare_and_set_property<Q: Into<Query>>(
+        &self,
+        q: Q,
+        name: Identifier,
+        expected: Option<&Json>,
+        new: &Json,
+    ) -> Result<bool> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        let swapped = compare_and_set_property_in_txn(&mut txn, q, name, expected, new)?;
+        txn.commit()?;
+        Ok(swapped)
+    }
+
+    /// Atomically deep-merges a JSON object into an object vertex property,
+    /// creating the property if it's missing.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `patch`: The object to merge in.
+    pub async fn merge_properties<Q: Into<Query>>(&self, q: Q, name: Identifier, patch: &Json) -> Result<()> {
+        let q = q.into();
+        let mut txn = self.datastore.transaction().await;
+        merge_properties_in_txn(&mut txn, q, name, patch)?;
+        txn.commit()
+    }
+
+    /// Copies all of the properties of one vertex to another, in one
+    /// transaction. Returns whether both vertices exist - if this is false,
+    /// nothing is copied.
+    ///
+    /// # Arguments
+    /// * `from`: The ID of the vertex to copy the properties from.
+    /// * `to`: The ID of the vertex to copy the properties to.
+    /// * `overwrite`: Whether to overwrite properties the target already
+    ///   has. If false, those are left as is.
+    pub async fn copy_vertex_properties(&self, from: Uuid, to: Uuid, overwrite: bool) -> Result<bool> {
+        let mut txn = self.datastore.transaction().await;
+        let copied = copy_vertex_properties_in_txn(&mut txn, from, to, overwrite)?;
+        txn.commit()?;
+        Ok(copied)
+    }
+
+    /// Bulk inserts many vertices, edges, and/or properties.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    pub async fn bulk_insert(&self, items: Vec<BulkInsertItem>) -> Result<()> {
+        let mut txn = self.datastore.transaction().await;
+        txn.bulk_insert(items)?;
+        txn.commit()
+    }
+
+    /// Bulk inserts many vertices, edges, and/or properties, after checking
+    /// that the vertices of each edge exist, either already or earlier in
+    /// `items`. This is slower than `bulk_insert`.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    ///
+    /// # Errors
+    /// Returns `Error::DanglingEdge` for the first edge with a vertex that
+    /// doesn't exist, in which case nothing is inserted.
+    pub async fn bulk_insert_checked(&self, items: Vec<BulkInsertItem>) -> Result<()> {
+        let mut txn = self.datastore.transaction().await;
+        txn.bulk_insert_checked(items)?;
+        txn.commit()
+    }
+
+    /// Bulk deletes many vertices, edges, and/or properties. Items that
+    /// don't exist are ignored.
+    ///
+    /// # Arguments
+    /// * `items`: The items to delete.
+    pub async fn bulk_delete(&self, items: Vec<BulkDeleteItem>) -> Result<()> {
+        let mut txn = self.datastore.transaction().await;
+        bulk_delete_in_txn(&mut txn, items)?;
+        txn.commit()
+    }
+
+    /// Finds the shortest path of edges from one vertex to another. Returns
+    /// `None` if there is no path within `max_depth` hops.
+    ///
+    /// # Arguments
+    /// * `from`: The ID of the vertex to start from.
+    /// * `to`: The ID of the vertex to end at.
+    /// * `max_depth`: The maximum number of edges in the path.
+    /// * `t`: Only follow edges of this type, if set.
+    /// * `direction`: Only follow edges in this direction, if set.
+    pub async fn shortest_path(
+        &self,
+        from: Uuid,
+        to: Uuid,
+        max_depth: u32,
+        t: Option<Identifier>,
+        direction: Option<EdgeDirection>,
+    ) -> Result<Option<Vec<Edge>>> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).shortest_path(from, to, max_depth, t, direction) }
+    }
+
+    /// Gets a vertex along with its outbound and inbound edges, or `None` if
+    /// the vertex doesn't exist, all from a single transaction.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only get edges of this type, if set.
+    /// * `limit`: The maximum number of edges to get in each direction.
+    pub async fn get_vertex_neighborhood(
+        &self,
+        id: Uuid,
+        t_filter: Option<Identifier>,
+        limit: u32,
+    ) -> Result<Option<Neighborhood>> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).vertex_neighborhood(id, t_filter, limit) }
+    }
+
+    /// Gets the number of outbound and inbound edges of a vertex, as
+    /// `(outbound, inbound)`, from a single transaction.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only count edges of this type, if set.
+    pub async fn get_edge_counts(&self, id: Uuid, t_filter: Option<Identifier>) -> Result<(u64, u64)> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).edge_counts(id, t_filter) }
+    }
+
+    /// Gets the vertices with the most edges in a given direction, along
+    /// with their degree. Results are sorted by degree descending, with ties
+    /// broken by ID.
+    ///
+    /// # Arguments
+    /// * `direction`: Which edges to count - outbound, inbound, or both.
+    /// * `limit`: The maximum number of vertices to return.
+    /// * `t_filter`: Only count edges of this type, if set.
+    pub async fn top_vertices_by_degree(
+        &self,
+        direction: EdgeDirection,
+        limit: usize,
+        t_filter: Option<Identifier>,
+    ) -> Result<Vec<(Uuid, u64)>> {
+        let txn = self.datastore.readonly_transaction().await;
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).top_vertices_by_degree(direction, limit, t_filter) }
+    }
+
+    /// Enables indexing on a specified property, or a nested field of one.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to index.
+    pub async fn index_property(&self, name: Identifier) -> Result<()> {
+        let mut txn = self.datastore.transaction().await;
+        txn.index_property(name)?;
+        txn.commit()
+    }
+
+    /// Drops the index on a specified property. This is a no-op if the
+    /// property isn't indexed.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to stop
+    ///   indexing.
+    pub async fn drop_index(&self, name: Identifier) -> Result<()> {
+        let mut txn = self.datastore.transaction().await;
+        txn.drop_index(name)?;
+        txn.commit()
+    }
+
+    /// Registers the type expected of a property's values, so that setting
+    /// the property to a value of any other type fails with
+    /// `Error::SchemaViolation`.
+    ///
+    /// # Arguments
+    /// * `name`: The property name.
+    /// * `expected`: The type expected of the property's values.
+    pub async fn register_property_schema(&self, name: Identifier, expected: JsonType) -> Result<()> {
+        let mut txn = self.datastore.transaction().await;
+        txn.register_property_schema(name, expected)?;
+        txn.commit()
+    }
+
+    /// Removes all vertices, edges, properties, indexes and property schemas,
+    /// returning the database to an empty state.
+    pub async fn clear(&self) -> Result<()> {
+        let mut txn = self.datastore.transaction().await;
+        txn.clear()?;
+        txn.commit()
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    ijson, AllEdgeQuery, BulkInsertItem, CountQueryExt, Database, Datastore, Edge, Error, Identifier, Query,
-    SpecificEdgeQuery, SpecificVertexQuery, Vertex,
+    ijson, AllEdgeQuery, BulkInsertItem, CountQueryExt, Database, Datastore, Edge, Error, Identifier, Query, QueryExt,
+    SpecificEdgeQuery, SpecificVertexQuery, Transaction, Vertex,
 };
 
 use test::Bencher;
@@ -166,6 +166,20 @@ pub fn bench_get_edge_count<D: Datastore>(b: &mut Bencher, db: &mut Database<D>)
     Ok(())
 }
 
+const TRANSACTION_CREATE_COUNT: usize = 100;
+
+pub fn bench_create_vertices_in_transaction<D: Datastore>(b: &mut Bencher, db: &mut Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("bench_create_vertices_in_transaction")?;
+    b.iter(|| {
+        let mut txn = db.datastore.transaction();
+        for _ in 0..TRANSACTION_CREATE_COUNT {
+            txn.create_vertex(&Vertex::new(t)).unwrap();
+        }
+        txn.commit().unwrap();
+    });
+    Ok(())
+}
+
 const BULK_INSERT_COUNT: usize = 100;
 
 pub fn bench_bulk_insert<D: Datastore>(b: &mut Bencher, db: &mut Database<D>) -> Result<(), Error> {
@@ -200,3 +214,51 @@ pub fn bench_bulk_insert<D: Datastore>(b: &mut Bencher, db: &mut Database<D>) ->
 
     Ok(())
 }
+
+const PIPE_EDGES_PER_VERTEX: usize = 10;
+
+// Pipes from a batch of vertices to their edges in both directions. Each
+// vertex has the same number of edges, so comparing batch sizes shows how
+// the query scales with the number of edges.
+fn bench_pipe_from_vertices<D: Datastore>(
+    b: &mut Bencher,
+    db: &mut Database<D>,
+    t: Identifier,
+    vertex_count: usize,
+) -> Result<(), Error> {
+    let mut vertices = Vec::with_capacity(vertex_count);
+    for _ in 0..vertex_count {
+        vertices.push(Vertex::new(t));
+    }
+
+    let mut items = Vec::with_capacity(vertex_count * (PIPE_EDGES_PER_VERTEX + 1));
+    for vertex in &vertices {
+        items.push(BulkInsertItem::Vertex(vertex.clone()));
+    }
+    for (i, vertex) in vertices.iter().enumerate() {
+        for j in 1..=PIPE_EDGES_PER_VERTEX {
+            let other = &vertices[(i + j) % vertex_count];
+            items.push(BulkInsertItem::Edge(Edge::new(vertex.id, t, other.id)));
+        }
+    }
+    db.bulk_insert(items)?;
+
+    let ids = vertices.iter().map(|v| v.id).collect();
+    let q: Query = SpecificVertexQuery::new(ids).both()?.into();
+
+    b.iter(|| {
+        db.get(q.clone()).unwrap();
+    });
+
+    Ok(())
+}
+
+pub fn bench_pipe_from_50_vertices<D: Datastore>(b: &mut Bencher, db: &mut Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("bench_pipe_from_50_vertices")?;
+    bench_pipe_from_vertices(b, db, t, 50)
+}
+
+pub fn bench_pipe_from_500_vertices<D: Datastore>(b: &mut Bencher, db: &mut Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("bench_pipe_from_500_vertices")?;
+    bench_pipe_from_vertices(b, db, t, 500)
+}
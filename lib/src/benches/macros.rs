@@ -17,10 +17,13 @@ macro_rules! define_bench {
 macro_rules! full_bench_impl {
     ($code:expr) => {
         define_bench!(bench_create_vertex, $code);
+        define_bench!(bench_create_vertices_in_transaction, $code);
         define_bench!(bench_get_vertices, $code);
         define_bench!(bench_create_edge, $code);
         define_bench!(bench_get_edges, $code);
         define_bench!(bench_get_edge_count, $code);
         define_bench!(bench_bulk_insert, $code);
+        define_bench!(bench_pipe_from_50_vertices, $code);
+        define_bench!(bench_pipe_from_500_vertices, $code);
     };
 }
@@ -1,9 +1,14 @@
 use crate::errors::{Error, Result};
 use crate::models::{
-    BulkInsertItem, Edge, EdgeDirection, EdgeProperties, Identifier, Json, NamedProperty, Query, QueryOutputValue,
-    Vertex, VertexProperties,
+    BulkDeleteItem, BulkInsertItem, CanonicalJson, ChangeFilter, Cursor, DatastoreStats, Edge, EdgeDirection,
+    EdgeProperties, Identifier, Json, JsonType, NamedProperty, Neighborhood, PlanAccess, Query, QueryOutputValue,
+    QueryPlan, RangeDirection, RangeVertexQuery, SpecificVertexQuery, Vertex, VertexProperties,
 };
-use std::collections::HashSet;
+use crate::util;
+use crate::watch::Watcher;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::{Duration, SystemTime};
 use std::vec::Vec;
 use uuid::Uuid;
 
@@ -24,16 +29,102 @@ pub type DynIter<'a, T> = Box<dyn Iterator<Item = Result<T>> + 'a>;
 pub trait Transaction<'a> {
     /// Gets the number of vertices.
     fn vertex_count(&self) -> u64;
+    /// Gets the number of vertices of a given type. By default, this errors
+    /// out, but this can be overridden in datastores that keep per-type
+    /// counts.
+    ///
+    /// # Arguments
+    /// * `t`: The vertex type to count.
+    fn vertex_count_by_type(&self, _t: Identifier) -> Result<u64> {
+        Err(Error::Unsupported)
+    }
+    /// Gets the distinct names of vertex properties currently in use, in
+    /// sorted order. By default, this errors out, but this can be overridden
+    /// in datastores that support it.
+    fn vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        Err(Error::Unsupported)
+    }
+    /// Gets the distinct names of edge properties currently in use, in
+    /// sorted order. By default, this errors out, but this can be overridden
+    /// in datastores that support it.
+    fn edge_property_names(&self) -> Result<Vec<Identifier>> {
+        Err(Error::Unsupported)
+    }
+    /// Gets the names of indexed properties, including paths to nested
+    /// fields, in sorted order. By default, this errors out, but this can be
+    /// overridden in datastores that support it.
+    fn indexed_properties(&self) -> Result<Vec<Identifier>> {
+        Err(Error::Unsupported)
+    }
+    /// Gets the registered property schemas, as pairs of property name and
+    /// expected value type, in sorted order of name. By default, this errors
+    /// out, but this can be overridden in datastores that support property
+    /// schemas.
+    fn property_schemas(&self) -> Result<Vec<(Identifier, JsonType)>> {
+        Err(Error::Unsupported)
+    }
+    /// Gets statistics about the datastore. By default, this counts vertices
+    /// and edges with `vertex_count` and `edge_count`, and doesn't report a
+    /// size.
+    fn stats(&self) -> Result<DatastoreStats> {
+        let index_count = match self.indexed_properties() {
+            Ok(names) => names.len() as u64,
+            Err(Error::Unsupported) => 0,
+            Err(err) => return Err(err),
+        };
+        Ok(DatastoreStats {
+            vertex_count: self.vertex_count(),
+            edge_count: self.edge_count(),
+            index_count,
+            size_bytes: None,
+        })
+    }
     /// Returns all vertices.
     fn all_vertices(&'a self) -> Result<DynIter<'a, Vertex>>;
     /// Returns all vertices with `id >= offset`.
     ///
     /// # Arguments
     /// * `offset` - Only fetch vertices with an offset greater than or equal
-    /// to this value.
+    ///   to this value.
     fn range_vertices(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>>;
+    /// Returns all vertices with `id <= offset`, in descending order of ID.
+    /// By default, this collects the matching vertices from `all_vertices`
+    /// and reverses them, but this can be overridden in datastores that can
+    /// iterate over vertices in reverse.
+    ///
+    /// # Arguments
+    /// * `offset` - Only fetch vertices with an ID less than or equal to
+    ///   this value.
+    fn range_vertices_descending(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        let mut vertices = Vec::new();
+        for vertex in self.all_vertices()? {
+            let vertex = vertex?;
+            if vertex.id > offset {
+                break;
+            }
+            vertices.push(vertex);
+        }
+        vertices.reverse();
+        Ok(Box::new(vertices.into_iter().map(Ok)))
+    }
     /// Gets a specific set of vertices with the given IDs.
     fn specific_vertices(&'a self, ids: Vec<Uuid>) -> Result<DynIter<'a, Vertex>>;
+    /// Gets a vertex if it exists, or `None` otherwise. By default, this
+    /// looks the vertex up via `specific_vertices`.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the vertex to get.
+    fn get_vertex(&'a self, id: Uuid) -> Result<Option<Vertex>> {
+        self.specific_vertices(vec![id])?.next().transpose()
+    }
+    /// Checks whether a vertex exists. By default, this gets the vertex via
+    /// `get_vertex`, but datastores can override this to avoid reading it.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the vertex to check.
+    fn contains_vertex(&'a self, id: Uuid) -> Result<bool> {
+        Ok(self.get_vertex(id)?.is_some())
+    }
     /// Get all vertices with a given property.
     ///
     /// # Arguments
@@ -45,6 +136,108 @@ pub trait Transaction<'a> {
     /// * `name` - The property name.
     /// * `value` - The property value.
     fn vertex_ids_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Uuid>>>;
+    /// Gets the labels of a vertex, which are empty if the vertex doesn't
+    /// exist. By default, this errors out, but this can be overridden in
+    /// datastores that support labels.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the vertex.
+    fn vertex_labels(&self, _id: Uuid) -> Result<BTreeSet<Identifier>> {
+        Err(Error::Unsupported)
+    }
+    /// Gets the IDs of all vertices with a given label. Datastores that
+    /// support labels should index them, so that this doesn't scan all
+    /// vertices. By default, this errors out.
+    ///
+    /// # Arguments
+    /// * `label` - The label.
+    fn vertex_ids_with_label(&'a self, _label: Identifier) -> Result<DynIter<'a, Uuid>> {
+        Err(Error::Unsupported)
+    }
+    /// Gets when a vertex was created, or `None` if the vertex doesn't exist
+    /// or was created before the datastore tracked creation times. Creation
+    /// times are set by the datastore when a vertex is created. By default,
+    /// this errors out, but this can be overridden in datastores that
+    /// support creation times.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the vertex.
+    fn vertex_created_at(&self, _id: Uuid) -> Result<Option<SystemTime>> {
+        Err(Error::Unsupported)
+    }
+    /// Gets the IDs of vertices created in a time range, oldest first.
+    /// Datastores that support creation times should index them, so that
+    /// this doesn't scan all vertices. By default, this errors out.
+    ///
+    /// # Arguments
+    /// * `low` - If set, the earliest creation time, inclusive.
+    /// * `high` - If set, the latest creation time, exclusive.
+    fn vertex_ids_created_in_range(
+        &'a self,
+        _low: Option<SystemTime>,
+        _high: Option<SystemTime>,
+    ) -> Result<DynIter<'a, Uuid>> {
+        Err(Error::Unsupported)
+    }
+
+    /// Lazily gets the vertices specified by a query. Unlike `Database::get`,
+    /// vertices are yielded as the underlying datastore iterates over them
+    /// rather than being collected up front, which keeps memory usage down
+    /// for large result sets. Only the vertex-producing root queries are
+    /// streamed; for anything else, the query is run in full and its output
+    /// is yielded.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    ///
+    /// # Errors
+    /// Returns `Error::OperationOnQuery` if the query doesn't output
+    /// vertices.
+    fn stream_vertices(&'a self, q: Query) -> Result<DynIter<'a, Vertex>> {
+        match q {
+            Query::AllVertex => self.all_vertices(),
+            Query::RangeVertex(ref q) => unsafe { range_vertices(self as *const Self, q) },
+            Query::SpecificVertex(q) => self.specific_vertices(q.ids),
+            q => {
+                if !matches!(q.output_type(), Ok(QueryOutputValue::Vertices(_))) {
+                    return Err(Error::OperationOnQuery);
+                }
+                let mut output = Vec::with_capacity(q.output_len());
+                unsafe {
+                    query(self as *const Self, &q, &mut output)?;
+                }
+                match output.pop() {
+                    Some(QueryOutputValue::Vertices(vertices)) => Ok(Box::new(vertices.into_iter().map(Ok))),
+                    _ => Err(Error::OperationOnQuery),
+                }
+            }
+        }
+    }
+
+    /// Gets values specified by a query, within this transaction. This runs
+    /// the query the same way `Database::get` does, e.g. for callers that
+    /// only have a transaction to work with.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get(&'a self, q: Query) -> Result<Vec<QueryOutputValue>> {
+        let mut output = Vec::with_capacity(q.output_len());
+        unsafe {
+            query(self as *const Self, &q, &mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// Describes how a query would run without running it, e.g. to check
+    /// whether it uses a property index. Estimating the number of results
+    /// may read indexes, but nothing is written. By default, this describes
+    /// how the query runs via `Database::get`.
+    ///
+    /// # Arguments
+    /// * `q`: The query to describe.
+    fn explain(&'a self, q: Query) -> Result<QueryPlan> {
+        unsafe { explain(self as *const Self, &q) }
+    }
 
     /// Gets the number of edges.
     fn edge_count(&self) -> u64;
@@ -62,11 +255,64 @@ pub trait Transaction<'a> {
     /// # Arguments
     /// * `offset` - Only fetch edges greater than or equal to this value.
     fn range_reversed_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>>;
+    /// Returns the edges of many vertices, as if by a range scan from each
+    /// vertex that stops after its last edge. Edges are returned vertex by
+    /// vertex, in the order of `roots`. By default, this calls `range_edges`
+    /// or `range_reversed_edges` per vertex, but datastores can override
+    /// this to reuse one cursor for all of them.
+    ///
+    /// # Arguments
+    /// * `roots` - The vertex IDs, each with an optional edge type to only
+    ///   return edges of.
+    /// * `reversed` - Whether to return reversed edges, as with
+    ///   `range_reversed_edges`.
+    fn range_edges_for_vertices(
+        &'a self,
+        roots: Vec<(Uuid, Option<Identifier>)>,
+        reversed: bool,
+    ) -> Result<DynIter<'a, Edge>> {
+        let mut iters = Vec::with_capacity(roots.len());
+
+        for (id, t) in roots {
+            let lower_bound = Edge::new(id, t.unwrap_or_default(), Uuid::default());
+
+            let iter = if reversed {
+                self.range_reversed_edges(lower_bound)?
+            } else {
+                self.range_edges(lower_bound)?
+            };
+
+            let iter: DynIter<'a, Edge> = Box::new(iter.take_while(move |r| match r {
+                Ok(e) => e.outbound_id == id && (t.is_none() || t == Some(e.t)),
+                Err(_) => true,
+            }));
+
+            iters.push(iter);
+        }
+
+        Ok(Box::new(iters.into_iter().flatten()))
+    }
     /// Gets a specific set of edges.
     ///
     /// # Arguments
     /// * `edges` - The edges to get.
     fn specific_edges(&'a self, edges: Vec<Edge>) -> Result<DynIter<'a, Edge>>;
+    /// Gets an edge if it exists, or `None` otherwise. By default, this looks
+    /// the edge up via `specific_edges`.
+    ///
+    /// # Arguments
+    /// * `edge` - The edge to get.
+    fn get_edge(&'a self, edge: &Edge) -> Result<Option<Edge>> {
+        self.specific_edges(vec![edge.clone()])?.next().transpose()
+    }
+    /// Checks whether an edge exists. By default, this gets the edge via
+    /// `get_edge`, but datastores can override this to avoid reading it.
+    ///
+    /// # Arguments
+    /// * `edge` - The edge to check.
+    fn contains_edge(&'a self, edge: &Edge) -> Result<bool> {
+        Ok(self.get_edge(edge)?.is_some())
+    }
     /// Get all edges with a given property.
     ///
     /// # Arguments
@@ -103,16 +349,18 @@ pub trait Transaction<'a> {
     /// * `edge` - The edge.
     fn all_edge_properties_for_edge(&'a self, edge: &Edge) -> Result<DynIter<'a, (Identifier, Json)>>;
 
-    /// Deletes the given vertices.
+    /// Deletes the given vertices. Returns the number of vertices deleted,
+    /// which excludes any that didn't exist.
     ///
     /// # Arguments
     /// * `vertices` - The vertices to delete.
-    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<()>;
-    /// Deletes the given edges.
+    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<u64>;
+    /// Deletes the given edges. Returns the number of edges deleted, which
+    /// excludes any that didn't exist.
     ///
     /// # Arguments
     /// * `edges` - The edges to delete.
-    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<()>;
+    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<u64>;
     /// Deletes the given vertex properties.
     ///
     /// # Arguments
@@ -130,6 +378,37 @@ pub trait Transaction<'a> {
         Err(Error::Unsupported)
     }
 
+    /// Compacts the datastore's storage, e.g. to clear out what's left behind
+    /// by large deletes. This can be expensive, so it's intended for off-peak
+    /// maintenance. By default, this is a no-op, for datastores that don't
+    /// need compacting.
+    fn compact(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Commits the transaction's changes. In datastores that buffer changes,
+    /// nothing is visible to other transactions until this is called, and
+    /// transactions that are dropped without being committed are rolled
+    /// back. By default, this is a no-op, for datastores that apply changes
+    /// as they're made.
+    fn commit(self) -> Result<()>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
+    /// Rolls back the transaction, discarding its changes. This is the same
+    /// as dropping the transaction without committing it, but is explicit.
+    /// By default, this errors out, but this can be overridden in datastores
+    /// that buffer changes until commit, or can otherwise undo them.
+    fn rollback(self) -> Result<()>
+    where
+        Self: Sized,
+    {
+        Err(Error::Unsupported)
+    }
+
     /// Creates a new vertex. Returns whether the vertex was successfully
     /// created - if this is false, it's because a vertex with the same UUID
     /// already exists.
@@ -137,6 +416,64 @@ pub trait Transaction<'a> {
     /// # Arguments
     /// * `vertex`: The vertex to create.
     fn create_vertex(&mut self, vertex: &Vertex) -> Result<bool>;
+    /// Creates a new vertex along with its initial properties. Returns
+    /// whether the vertex was successfully created - if this is false, it's
+    /// because a vertex with the same UUID already exists, and no properties
+    /// are set. If a name appears more than once, the last value wins.
+    ///
+    /// By default, this creates the vertex and then sets the properties via
+    /// `set_vertex_properties_batch`, which is only atomic for datastores
+    /// that can roll back a failed transaction. Others should override this.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create.
+    /// * `props`: The property names and values.
+    fn create_vertex_with_properties(&mut self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        if !self.create_vertex(vertex)? {
+            return Ok(false);
+        }
+        self.set_vertex_properties_batch(vec![vertex.id], props)?;
+        Ok(true)
+    }
+    /// Creates a new vertex along with its labels. Returns whether the
+    /// vertex was successfully created - if this is false, it's because a
+    /// vertex with the same UUID already exists, and no labels are added.
+    ///
+    /// By default, this creates the vertex and then adds the labels one at a
+    /// time via `add_vertex_labels`.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create.
+    /// * `labels`: The labels.
+    fn create_vertex_with_labels(&mut self, vertex: &Vertex, labels: BTreeSet<Identifier>) -> Result<bool> {
+        if !self.create_vertex(vertex)? {
+            return Ok(false);
+        }
+        for label in labels {
+            self.add_vertex_labels(vec![vertex.id], label)?;
+        }
+        Ok(true)
+    }
+    /// Creates a vertex along with its properties if it doesn't exist, or
+    /// sets the properties on it if it does. Returns whether the vertex was
+    /// created. An existing vertex keeps its type, and its other properties.
+    /// If a name appears more than once, the last value wins.
+    ///
+    /// By default, this tries `create_vertex_with_properties`, and falls back
+    /// to `set_vertex_properties_batch`. That's atomic for datastores whose
+    /// write transactions exclude each other, but others should override
+    /// this, so that concurrent upserts of a vertex can't interleave.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create or update.
+    /// * `props`: The property names and values.
+    fn upsert_vertex(&mut self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        if self.create_vertex_with_properties(vertex, props.clone())? {
+            return Ok(true);
+        }
+        self.set_vertex_properties_batch(vec![vertex.id], props)?;
+        Ok(false)
+    }
     /// Creates a new edge. Returns whether the edge was successfully
     /// created - if this is false, it's because one of the specified vertices
     /// is missing.
@@ -145,9 +482,71 @@ pub trait Transaction<'a> {
     /// * `edge`: The edge to create.
     fn create_edge(&mut self, edge: &Edge) -> Result<bool>;
 
+    /// Creates a new edge that expires after `ttl`. Once expired, the edge is
+    /// no longer returned by queries or included in counts, and the datastore
+    /// eventually deletes it. Returns whether the edge was successfully
+    /// created - if this is false, it's because one of the specified vertices
+    /// is missing. By default, this errors out, but this can be overridden in
+    /// datastores that support expiring edges.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    /// * `ttl`: How long the edge should live for.
+    fn create_edge_with_ttl(&mut self, _edge: &Edge, _ttl: Duration) -> Result<bool> {
+        Err(Error::Unsupported)
+    }
+
+    /// Creates a new edge, but only if it doesn't already exist. Returns
+    /// whether the edge was newly created - if this is false, it's because
+    /// either the edge already exists, or one of the specified vertices is
+    /// missing. An existing edge is left untouched. The check and insert are
+    /// atomic, so concurrent callers creating the same edge won't both get
+    /// `true`. By default, this errors out, but this can be overridden in
+    /// datastores that support it.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    fn create_edge_if_absent(&mut self, _edge: &Edge) -> Result<bool> {
+        Err(Error::Unsupported)
+    }
+    /// Creates an edge along with its reverse, e.g. to model an undirected
+    /// relationship. Queries still see them as two directed edges. Returns
+    /// whether the edges were successfully created - if this is false, it's
+    /// because one of the specified vertices is missing, and neither edge is
+    /// created.
+    ///
+    /// By default, this creates the edges one at a time via `create_edge`,
+    /// which is only atomic for datastores that can roll back a failed
+    /// transaction. Others should override this.
+    ///
+    /// # Arguments
+    /// * `edge`: One of the two edges to create.
+    fn create_symmetric_edge(&mut self, edge: &Edge) -> Result<bool> {
+        if !self.create_edge(edge)? {
+            return Ok(false);
+        }
+        self.create_edge(&edge.reversed())
+    }
+    /// Deletes an edge along with its reverse. Returns the number of edges
+    /// deleted. By default, this deletes both via `delete_edges`.
+    ///
+    /// # Arguments
+    /// * `edge`: One of the two edges to delete.
+    fn delete_symmetric_edge(&mut self, edge: &Edge) -> Result<u64> {
+        let reversed = edge.reversed();
+        if reversed == *edge {
+            self.delete_edges(vec![reversed])
+        } else {
+            self.delete_edges(vec![edge.clone(), reversed])
+        }
+    }
+
     /// Bulk inserts many vertices, edges, and/or properties. By default, this
     /// makes the underlying calls to insert the values, but can be overridden
-    /// to offer a more efficient implementation.
+    /// to offer a more efficient implementation, e.g. the rocksdb datastore
+    /// writes all the items in one write batch. Implementations don't have
+    /// to check that the vertices of inserted edges exist - see
+    /// `bulk_insert_checked` for that.
     ///
     /// # Arguments
     /// * `items`: The items to insert.
@@ -172,212 +571,1838 @@ pub trait Transaction<'a> {
         Ok(())
     }
 
-    /// Enables indexing on a specified property. When indexing is enabled on a
-    /// property, it's possible to query on its presence and values.
+    /// Bulk inserts many vertices, edges, and/or properties, like
+    /// `bulk_insert`, but first checks that the vertices of each edge exist,
+    /// either in the datastore or earlier in `items`. By default, this checks
+    /// the items in order and then calls `bulk_insert`, so nothing is
+    /// inserted if the check fails.
     ///
     /// # Arguments
-    /// * `name`: The name of the property to index.
-    fn index_property(&mut self, name: Identifier) -> Result<()>;
+    /// * `items`: The items to insert.
+    ///
+    /// # Errors
+    /// Returns `Error::DanglingEdge` for the first edge with a vertex that
+    /// doesn't exist.
+    fn bulk_insert_checked(&mut self, items: Vec<BulkInsertItem>) -> Result<()>
+    where
+        Self: 'a,
+    {
+        let txn = self as *const Self;
+        // Vertices known to exist, so that each is only looked up once
+        let mut existing = HashSet::new();
 
-    /// Sets vertex properties.
+        for item in &items {
+            match item {
+                BulkInsertItem::Vertex(vertex) => {
+                    existing.insert(vertex.id);
+                }
+                BulkInsertItem::Edge(edge) => {
+                    for id in [edge.outbound_id, edge.inbound_id] {
+                        if existing.contains(&id) {
+                            continue;
+                        }
+                        if unsafe { (*txn).specific_vertices(vec![id])? }
+                            .next()
+                            .transpose()?
+                            .is_none()
+                        {
+                            return Err(Error::DanglingEdge { key: edge.clone() });
+                        }
+                        existing.insert(id);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.bulk_insert(items)
+    }
+
+    /// Finds the shortest path of edges from one vertex to another. Returns
+    /// `None` if there is no path within `max_depth` hops. By default, this
+    /// runs a bidirectional breadth-first search over the edge ranges,
+    /// searching from both ends and expanding whichever has the smaller
+    /// frontier, but can be overridden to offer a more efficient
+    /// implementation.
     ///
-    /// # Arguments
-    /// * `vertices`: The vertices to set the properties on.
-    /// * `name`: The property name.
-    /// * `value`: The property value.
-    fn set_vertex_properties(&mut self, vertices: Vec<Uuid>, name: Identifier, value: &Json) -> Result<()>;
-    /// Sets edge properties.
+    /// Edges in the returned path are in their stored orientation, even when
+    /// traversed inbound.
     ///
     /// # Arguments
-    /// * `edges`: The edges to set the properties on.
-    /// * `name`: The property name.
-    /// * `value`: The property value.
-    fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()>;
-}
+    /// * `from`: The ID of the vertex to start from.
+    /// * `to`: The ID of the vertex to end at.
+    /// * `max_depth`: The maximum number of edges in the path.
+    /// * `t`: Only follow edges of this type, if set.
+    /// * `direction`: Only follow edges in this direction, if set. Otherwise
+    ///   edges are followed in both directions.
+    fn shortest_path(
+        &'a self,
+        from: Uuid,
+        to: Uuid,
+        max_depth: u32,
+        t: Option<Identifier>,
+        direction: Option<EdgeDirection>,
+    ) -> Result<Option<Vec<Edge>>> {
+        if from == to {
+            return Ok(Some(Vec::new()));
+        }
 
-/// Specifies a datastore, which provides datastore transaction
-/// implementations to the database.
-pub trait Datastore {
-    /// The datastore transaction type.
-    type Transaction<'a>: Transaction<'a>
-    where
-        Self: 'a;
-    /// Creates a new transaction.
-    fn transaction(&self) -> Self::Transaction<'_>;
-}
+        let directions = match direction {
+            Some(EdgeDirection::Both) | None => vec![EdgeDirection::Outbound, EdgeDirection::Inbound],
+            Some(direction) => vec![direction],
+        };
+        // The search from `to` walks the path backwards, so follows edges
+        // the other way
+        let reversed_directions: Vec<EdgeDirection> = directions
+            .iter()
+            .map(|direction| match direction {
+                EdgeDirection::Outbound => EdgeDirection::Inbound,
+                _ => EdgeDirection::Outbound,
+            })
+            .collect();
 
-/// The IndraDB database.
-///
-/// This contains all of the logic shared across implementations, e.g. query
-/// handling. Underlying it (as a generic argument) are datastores, which
-/// contain implementation-specific logic.
-///
-/// As an IndraDB end-user, you should interact with this rather than
-/// datastores.
-pub struct Database<D: Datastore> {
-    pub datastore: D,
-}
+        // Gets the neighbors of a vertex in a direction, along with the edge
+        // to each of them
+        let neighbors = |id: Uuid, direction: EdgeDirection| -> Result<Vec<(Uuid, Edge)>> {
+            let lower_bound = Edge::new(id, t.unwrap_or_default(), Uuid::default());
+            let iter = if direction == EdgeDirection::Outbound {
+                self.range_edges(lower_bound)?
+            } else {
+                self.range_reversed_edges(lower_bound)?
+            };
 
-impl<D: Datastore> Database<D> {
-    /// Creates a new database.
-    ///
-    /// # Arguments
-    /// * `datastore`: The underlying datastore to use.
-    pub fn new(datastore: D) -> Database<D> {
-        Self { datastore }
-    }
+            let mut neighbors = Vec::new();
+            for result in iter {
+                let edge = result?;
+                if edge.outbound_id != id || t.is_some_and(|t| edge.t != t) {
+                    break;
+                }
+                let neighbor_id = edge.inbound_id;
+                let edge = if direction == EdgeDirection::Outbound {
+                    edge
+                } else {
+                    edge.reversed()
+                };
+                neighbors.push((neighbor_id, edge));
+            }
+            Ok(neighbors)
+        };
 
-    /// Syncs persisted content. Depending on the datastore implementation,
-    /// this has different meanings - including potentially being a no-op.
-    pub fn sync(&self) -> Result<()> {
-        let txn = self.datastore.transaction();
-        txn.sync()
-    }
+        // Follows the edges used to reach a vertex back to where its search
+        // started
+        let walk = |parents: &HashMap<Uuid, Option<Edge>>, mut cur: Uuid| -> Vec<Edge> {
+            let mut path = Vec::new();
+            while let Some(Some(edge)) = parents.get(&cur) {
+                cur = if edge.inbound_id == cur {
+                    edge.outbound_id
+                } else {
+                    edge.inbound_id
+                };
+                path.push(edge.clone());
+            }
+            path
+        };
 
-    /// Creates a new vertex. Returns whether the vertex was successfully
-    /// created - if this is false, it's because a vertex with the same UUID
-    /// already exists.
-    ///
-    /// # Arguments
-    /// * `vertex`: The vertex to create.
-    pub fn create_vertex(&self, vertex: &Vertex) -> Result<bool> {
-        let mut txn = self.datastore.transaction();
-        txn.create_vertex(vertex)
+        // Maps each vertex visited from either end to the edge used to reach
+        // it. Whole levels are expanded at a time, so the first vertex
+        // visited from both ends is on a shortest path.
+        let mut forward: HashMap<Uuid, Option<Edge>> = HashMap::from([(from, None)]);
+        let mut backward: HashMap<Uuid, Option<Edge>> = HashMap::from([(to, None)]);
+        let mut forward_frontier = vec![from];
+        let mut backward_frontier = vec![to];
+        let mut depth = 0;
+
+        while depth < max_depth && !forward_frontier.is_empty() && !backward_frontier.is_empty() {
+            let (frontier, visited, other, directions) = if forward_frontier.len() <= backward_frontier.len() {
+                (&mut forward_frontier, &mut forward, &backward, &directions)
+            } else {
+                (&mut backward_frontier, &mut backward, &forward, &reversed_directions)
+            };
+            let mut next_frontier = Vec::new();
+
+            for id in frontier.drain(..) {
+                for direction in directions {
+                    for (neighbor_id, edge) in neighbors(id, *direction)? {
+                        if visited.contains_key(&neighbor_id) {
+                            continue;
+                        }
+                        visited.insert(neighbor_id, Some(edge));
+
+                        if other.contains_key(&neighbor_id) {
+                            let mut path = walk(&forward, neighbor_id);
+                            path.reverse();
+                            path.extend(walk(&backward, neighbor_id));
+                            return Ok(Some(path));
+                        }
+
+                        next_frontier.push(neighbor_id);
+                    }
+                }
+            }
+
+            *frontier = next_frontier;
+            depth += 1;
+        }
+
+        Ok(None)
     }
 
-    /// Creates a new vertex with just a type specification. As opposed to
-    /// `create_vertex`, this is used when you do not want to manually specify
-    /// the vertex's UUID. Returns the new vertex's UUID.
+    /// Gets a vertex along with its outbound and inbound edges, or `None` if
+    /// the vertex doesn't exist. Since this is read from a single
+    /// transaction, the edges are consistent with the vertex.
     ///
     /// # Arguments
-    /// * `t`: The type of the vertex to create.
-    pub fn create_vertex_from_type(&self, t: Identifier) -> Result<Uuid> {
-        let v = Vertex::new(t);
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only get edges of this type, if set.
+    /// * `limit`: The maximum number of edges to get in each direction.
+    fn vertex_neighborhood(
+        &'a self,
+        id: Uuid,
+        t_filter: Option<Identifier>,
+        limit: u32,
+    ) -> Result<Option<Neighborhood>> {
+        let vertex = match self.get_vertex(id)? {
+            Some(vertex) => vertex,
+            None => return Ok(None),
+        };
 
-        if !self.create_vertex(&v)? {
-            Err(Error::UuidTaken)
-        } else {
-            Ok(v.id)
+        let mut neighborhood = Neighborhood {
+            vertex,
+            outbound: Vec::new(),
+            inbound: Vec::new(),
+        };
+
+        for direction in [EdgeDirection::Outbound, EdgeDirection::Inbound] {
+            let lower_bound = Edge::new(id, t_filter.unwrap_or_default(), Uuid::default());
+            let (iter, edges) = if direction == EdgeDirection::Outbound {
+                (self.range_edges(lower_bound)?, &mut neighborhood.outbound)
+            } else {
+                (self.range_reversed_edges(lower_bound)?, &mut neighborhood.inbound)
+            };
+
+            for result in iter {
+                if edges.len() >= limit as usize {
+                    break;
+                }
+                let edge = result?;
+                if edge.outbound_id != id || t_filter.is_some_and(|t| edge.t != t) {
+                    break;
+                }
+                edges.push(if direction == EdgeDirection::Outbound {
+                    edge
+                } else {
+                    edge.reversed()
+                });
+            }
         }
+
+        Ok(Some(neighborhood))
     }
 
-    /// Creates a new edge. Returns whether the edge was successfully
-    /// created - if this is false, it's because one of the specified vertices
-    /// is missing.
+    /// Gets the number of outbound and inbound edges of a vertex, as
+    /// `(outbound, inbound)`. A self-loop counts in both directions. Counts
+    /// are zero if the vertex doesn't exist.
     ///
     /// # Arguments
-    /// * `edge`: The edge to create.
-    pub fn create_edge(&self, edge: &Edge) -> Result<bool> {
-        let mut txn = self.datastore.transaction();
-        txn.create_edge(edge)
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only count edges of this type, if set.
+    fn edge_counts(&'a self, id: Uuid, t_filter: Option<Identifier>) -> Result<(u64, u64)> {
+        let mut outbound = 0;
+        let mut inbound = 0;
+
+        for direction in [EdgeDirection::Outbound, EdgeDirection::Inbound] {
+            let lower_bound = Edge::new(id, t_filter.unwrap_or_default(), Uuid::default());
+            let (iter, count) = if direction == EdgeDirection::Outbound {
+                (self.range_edges(lower_bound)?, &mut outbound)
+            } else {
+                (self.range_reversed_edges(lower_bound)?, &mut inbound)
+            };
+
+            for result in iter {
+                let edge = result?;
+                if edge.outbound_id != id || t_filter.is_some_and(|t| edge.t != t) {
+                    break;
+                }
+                *count += 1;
+            }
+        }
+
+        Ok((outbound, inbound))
     }
 
-    /// Gets values specified by a query.
+    /// Gets the vertices with the most edges, along with their degree - the
+    /// number of edges they have in the given direction. Results are sorted
+    /// by degree descending, with ties broken by ID. Vertices without any
+    /// matching edges aren't included. By default, this scans every edge,
+    /// but can be overridden in datastores that keep per-vertex edge counts.
+    ///
+    /// # Arguments
+    /// * `direction`: Which edges to count - outbound, inbound, or both. With
+    ///   both, a self-loop counts once.
+    /// * `limit`: The maximum number of vertices to return.
+    /// * `t_filter`: Only count edges of this type, if set.
+    fn top_vertices_by_degree(
+        &'a self,
+        direction: EdgeDirection,
+        limit: usize,
+        t_filter: Option<Identifier>,
+    ) -> Result<Vec<(Uuid, u64)>> {
+        let mut degrees: HashMap<Uuid, u64> = HashMap::new();
+        for edge in self.all_edges()? {
+            let edge = edge?;
+            if t_filter.is_some_and(|t| edge.t != t) {
+                continue;
+            }
+            if direction != EdgeDirection::Inbound {
+                *degrees.entry(edge.outbound_id).or_insert(0) += 1;
+            }
+            if direction == EdgeDirection::Inbound
+                || (direction == EdgeDirection::Both && edge.inbound_id != edge.outbound_id)
+            {
+                *degrees.entry(edge.inbound_id).or_insert(0) += 1;
+            }
+        }
+
+        let mut degrees: Vec<(Uuid, u64)> = degrees.into_iter().collect();
+        degrees.sort_unstable_by_key(|(id, degree)| (Reverse(*degree), *id));
+        degrees.truncate(limit);
+        Ok(degrees)
+    }
+
+    /// Enables indexing on a specified property. When indexing is enabled on a
+    /// property, it's possible to query on its presence and values.
+    /// Datastores that don't support indexing nested fields should return
+    /// `Error::Unsupported` for paths (see `Identifier::new_path`).
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to index.
+    fn index_property(&mut self, name: Identifier) -> Result<()>;
+
+    /// Drops the index on a specified property, if there is one. Afterwards,
+    /// queries on the property's presence and values return
+    /// `Error::NotIndexed`, as with any other unindexed property. By default,
+    /// this errors out, but this can be overridden in datastores that support
+    /// it.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to stop
+    ///   indexing.
+    fn drop_index(&mut self, _name: Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    /// Registers the type expected of a property's values. Afterwards,
+    /// setting the property on a vertex or edge to a value of any other type
+    /// fails with `Error::SchemaViolation`. Registering a property again
+    /// replaces its expected type. Values that were set beforehand aren't
+    /// checked. By default, this errors out, but this can be overridden in
+    /// datastores that support property schemas.
+    ///
+    /// # Arguments
+    /// * `name`: The property name.
+    /// * `expected`: The type expected of the property's values.
+    fn register_property_schema(&mut self, _name: Identifier, _expected: JsonType) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    /// Removes all vertices, edges, properties and indexes, returning the
+    /// datastore to an empty state. By default, this deletes every edge and
+    /// vertex, along with their properties, and then drops every index.
+    /// Datastores that support property schemas should remove those too.
+    fn clear(&mut self) -> Result<()>
+    where
+        Self: 'a,
+    {
+        let txn = self as *const Self;
+        let edges = unsafe { (*txn).all_edges()?.collect::<Result<Vec<Edge>>>()? };
+        self.delete_edges(edges)?;
+        let vertices = unsafe { (*txn).all_vertices()?.collect::<Result<Vec<Vertex>>>()? };
+        self.delete_vertices(vertices)?;
+        match self.indexed_properties() {
+            Ok(names) => {
+                for name in names {
+                    self.drop_index(name)?;
+                }
+                Ok(())
+            }
+            Err(Error::Unsupported) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Sets vertex properties.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to set the properties on.
+    /// * `name`: The property name.
+    /// * `value`: The property value.
+    fn set_vertex_properties(&mut self, vertices: Vec<Uuid>, name: Identifier, value: &Json) -> Result<()>;
+    /// Sets edge properties.
+    ///
+    /// # Arguments
+    /// * `edges`: The edges to set the properties on.
+    /// * `name`: The property name.
+    /// * `value`: The property value.
+    fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()>;
+    /// Sets several properties on each of the vertices. If a name appears
+    /// more than once, the last value wins. By default, this sets the
+    /// properties one name at a time via `set_vertex_properties`.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to set the properties on.
+    /// * `props`: The property names and values.
+    fn set_vertex_properties_batch(&mut self, vertices: Vec<Uuid>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        for (name, value) in props {
+            self.set_vertex_properties(vertices.clone(), name, &value)?;
+        }
+        Ok(())
+    }
+    /// Sets several properties on each of the edges. If a name appears more
+    /// than once, the last value wins. By default, this sets the properties
+    /// one name at a time via `set_edge_properties`.
+    ///
+    /// # Arguments
+    /// * `edges`: The edges to set the properties on.
+    /// * `props`: The property names and values.
+    fn set_edge_properties_batch(&mut self, edges: Vec<Edge>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        for (name, value) in props {
+            self.set_edge_properties(edges.clone(), name, &value)?;
+        }
+        Ok(())
+    }
+    /// Adds a label to vertices. Vertices that already have the label, or
+    /// don't exist, are skipped. By default, this errors out, but this can
+    /// be overridden in datastores that support labels.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to add the label to.
+    /// * `label`: The label.
+    fn add_vertex_labels(&mut self, _vertices: Vec<Uuid>, _label: Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+    /// Removes a label from vertices. Vertices that don't have the label are
+    /// skipped. By default, this errors out, but this can be overridden in
+    /// datastores that support labels.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to remove the label from.
+    /// * `label`: The label.
+    fn remove_vertex_labels(&mut self, _vertices: Vec<Uuid>, _label: Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    /// Adds `delta` to numeric vertex properties, treating missing properties
+    /// as 0. Either all of the properties are updated, or none of them are.
+    ///
+    /// By default, this reads the current values and then sets the new ones.
+    /// That's atomic for datastores whose transactions hold an exclusive lock,
+    /// but others should override this so that concurrent increments don't
+    /// lose updates.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to increment the properties on.
+    /// * `name`: The property name.
+    /// * `delta`: The amount to add.
+    ///
+    /// # Errors
+    /// Returns `Error::NotNumeric` if any of the existing values isn't a
+    /// number.
+    fn increment_vertex_properties(&mut self, vertices: Vec<Vertex>, name: Identifier, delta: f64) -> Result<()> {
+        let mut values = Vec::with_capacity(vertices.len());
+        for vertex in &vertices {
+            let value = self.vertex_property(vertex, name)?;
+            values.push(util::increment_json(value.as_ref(), delta)?);
+        }
+        for (vertex, value) in vertices.into_iter().zip(values) {
+            self.set_vertex_properties(vec![vertex.id], name, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Deep-merges a JSON object into object vertex properties, creating
+    /// missing properties. Either all of the properties are updated, or none
+    /// of them are.
+    ///
+    /// By default, this reads the current values and then sets the new ones.
+    /// That's atomic for datastores whose transactions hold an exclusive lock,
+    /// but others should override this so that concurrent merges don't lose
+    /// updates.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to merge the properties on.
+    /// * `name`: The property name.
+    /// * `patch`: The object to merge in.
+    ///
+    /// # Errors
+    /// Returns `Error::NotObject` if the patch or any of the existing values
+    /// isn't an object.
+    fn merge_vertex_properties(&mut self, vertices: Vec<Vertex>, name: Identifier, patch: &Json) -> Result<()> {
+        let mut values = Vec::with_capacity(vertices.len());
+        for vertex in &vertices {
+            let value = self.vertex_property(vertex, name)?;
+            values.push(util::merge_json(value.as_ref(), patch)?);
+        }
+        for (vertex, value) in vertices.into_iter().zip(values) {
+            self.set_vertex_properties(vec![vertex.id], name, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Sets vertex properties to a new value, but only if all of their
+    /// current values are equal to an expected one. Returns whether the
+    /// properties were set, which they aren't if there are no vertices.
+    ///
+    /// By default, this reads the current values and then sets the new ones.
+    /// That's atomic for datastores whose transactions hold an exclusive lock,
+    /// but others should override this so that a concurrent write can't land
+    /// between the comparison and the set.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to set the properties on.
+    /// * `name`: The property name.
+    /// * `expected`: The expected current value, or `None` if the property
+    ///   is expected to be missing.
+    /// * `new`: The value to set.
+    fn compare_and_set_vertex_properties(
+        &mut self,
+        vertices: Vec<Vertex>,
+        name: Identifier,
+        expected: Option<&Json>,
+        new: &Json,
+    ) -> Result<bool> {
+        if vertices.is_empty() {
+            return Ok(false);
+        }
+        for vertex in &vertices {
+            if self.vertex_property(vertex, name)?.as_ref() != expected {
+                return Ok(false);
+            }
+        }
+        self.set_vertex_properties(vertices.into_iter().map(|v| v.id).collect(), name, new)?;
+        Ok(true)
+    }
+
+    /// Copies all of the properties of one vertex to another. By default,
+    /// this reads the properties and then sets them on the target via
+    /// `set_vertex_properties_batch`.
+    ///
+    /// # Arguments
+    /// * `from`: The vertex to copy the properties from.
+    /// * `to`: The vertex to copy the properties to.
+    /// * `overwrite`: Whether to overwrite properties the target already
+    ///   has. If false, those are left as is.
+    fn copy_vertex_properties(&mut self, from: &Vertex, to: &Vertex, overwrite: bool) -> Result<()>
+    where
+        Self: 'a,
+    {
+        let txn = self as *const Self;
+        let mut props = unsafe { (*txn).all_vertex_properties_for_vertex(from)? }.collect::<Result<Vec<_>>>()?;
+        if !overwrite {
+            let mut missing = Vec::with_capacity(props.len());
+            for (name, value) in props {
+                if self.vertex_property(to, name)?.is_none() {
+                    missing.push((name, value));
+                }
+            }
+            props = missing;
+        }
+        if props.is_empty() {
+            return Ok(());
+        }
+        self.set_vertex_properties_batch(vec![to.id], props)
+    }
+
+    /// Deletes every property of the given vertices. Returns the number of
+    /// properties deleted. By default, this reads the property names of each
+    /// vertex and then deletes them via `delete_vertex_properties`.
+    ///
+    /// # Arguments
+    /// * `vertices`: The vertices to delete the properties of.
+    fn delete_all_vertex_properties(&mut self, vertices: Vec<Vertex>) -> Result<u64>
+    where
+        Self: 'a,
+    {
+        let txn = self as *const Self;
+        let mut props = Vec::new();
+        for vertex in &vertices {
+            for item in unsafe { (*txn).all_vertex_properties_for_vertex(vertex)? } {
+                let (name, _) = item?;
+                props.push((vertex.id, name));
+            }
+        }
+        let count = props.len() as u64;
+        self.delete_vertex_properties(props)?;
+        Ok(count)
+    }
+
+    /// Deletes every property of the given edges. Returns the number of
+    /// properties deleted. By default, this reads the property names of each
+    /// edge and then deletes them via `delete_edge_properties`.
+    ///
+    /// # Arguments
+    /// * `edges`: The edges to delete the properties of.
+    fn delete_all_edge_properties(&mut self, edges: Vec<Edge>) -> Result<u64>
+    where
+        Self: 'a,
+    {
+        let txn = self as *const Self;
+        let mut props = Vec::new();
+        for edge in &edges {
+            for item in unsafe { (*txn).all_edge_properties_for_edge(edge)? } {
+                let (name, _) = item?;
+                props.push((edge.clone(), name));
+            }
+        }
+        let count = props.len() as u64;
+        self.delete_edge_properties(props)?;
+        Ok(count)
+    }
+}
+
+/// Specifies a datastore, which provides datastore transaction
+/// implementations to the database.
+pub trait Datastore {
+    /// The datastore transaction type.
+    type Transaction<'a>: Transaction<'a>
+    where
+        Self: 'a;
+    /// Creates a new transaction. Its changes should be committed with
+    /// `Transaction::commit`, since datastores that buffer changes discard
+    /// them when an uncommitted transaction is dropped.
+    fn transaction(&self) -> Self::Transaction<'_>;
+    /// Creates a new read-only transaction. Any method on it that would
+    /// mutate the graph returns `Error::ReadOnly` instead. By default, this
+    /// returns a writable transaction, so that datastores that predate
+    /// read-only transactions keep working, but should be overridden.
+    fn readonly_transaction(&self) -> Self::Transaction<'_> {
+        self.transaction()
+    }
+    /// Watches for changes to the graph. By default, this errors out, but
+    /// this can be overridden in datastores that support it.
+    ///
+    /// # Arguments
+    /// * `filter`: Which changes to watch for.
+    fn watch(&self, _filter: ChangeFilter) -> Result<Watcher> {
+        Err(Error::Unsupported)
+    }
+    /// Generates the ID of a vertex created from just a type. By default,
+    /// this generates a UUID v1, but this can be overridden in datastores
+    /// that let users pick a `VertexIdGenerator`.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the new vertex.
+    fn generate_vertex_id(&self, _t: Identifier) -> Uuid {
+        util::generate_uuid_v1()
+    }
+    /// Runs `f` in a new transaction and commits it, retrying with a fresh
+    /// transaction whenever `f` or the commit fails with
+    /// `Error::SerializationConflict`. Any other error is returned as is,
+    /// as is the last conflict once `max_attempts` have been made.
+    ///
+    /// Since `f` may be run more than once, it shouldn't have side effects
+    /// outside of the transaction it's given - e.g. it shouldn't send
+    /// messages or update in-memory state - or those may be applied
+    /// multiple times, or for an attempt that was rolled back.
+    ///
+    /// # Arguments
+    /// * `max_attempts`: The maximum number of times to run `f`. It's always
+    ///   run at least once.
+    /// * `f`: The operation to run in each transaction.
+    fn with_transaction_retry<'a, T, F>(&'a self, max_attempts: u32, f: F) -> Result<T>
+    where
+        F: Fn(&mut Self::Transaction<'a>) -> Result<T>,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let mut txn = self.transaction();
+            let result = f(&mut txn).and_then(|value| txn.commit().map(|_| value));
+            match result {
+                Err(Error::SerializationConflict) if attempts < max_attempts => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+/// The IndraDB database.
+///
+/// This contains all of the logic shared across implementations, e.g. query
+/// handling. Underlying it (as a generic argument) are datastores, which
+/// contain implementation-specific logic.
+///
+/// As an IndraDB end-user, you should interact with this rather than
+/// datastores.
+pub struct Database<D: Datastore> {
+    pub datastore: D,
+}
+
+impl<D: Datastore> Database<D> {
+    /// Creates a new database.
+    ///
+    /// # Arguments
+    /// * `datastore`: The underlying datastore to use.
+    pub fn new(datastore: D) -> Database<D> {
+        Self { datastore }
+    }
+
+    /// Syncs persisted content. What this guarantees depends on the
+    /// datastore:
+    /// * Rocksdb: every write made before the call is durable on disk once it
+    ///   returns, since the write-ahead log is synced and memtables are
+    ///   flushed. Expired edges are also reaped.
+    /// * Memory: if the datastore was created with a persistence path, the
+    ///   whole datastore is written there. Otherwise, this is a no-op.
+    /// * Sqlite: a no-op, since writes are durable as soon as they commit.
+    pub fn sync(&self) -> Result<()> {
+        let txn = self.datastore.transaction();
+        txn.sync()?;
+        // Commit anything the sync wrote, such as reaped expired edges
+        txn.commit()
+    }
+
+    /// Compacts the datastore's storage, e.g. to clear out what's left behind
+    /// by large deletes. Reads can continue while this runs, but it can be
+    /// expensive, so it's intended for off-peak maintenance. Depending on the
+    /// datastore implementation, this may be a no-op.
+    pub fn compact(&self) -> Result<()> {
+        let txn = self.datastore.transaction();
+        txn.compact()
+    }
+
+    /// Creates a new vertex. Returns whether the vertex was successfully
+    /// created - if this is false, it's because a vertex with the same UUID
+    /// already exists.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create.
+    pub fn create_vertex(&self, vertex: &Vertex) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let created = txn.create_vertex(vertex)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new vertex with just a type specification. As opposed to
+    /// `create_vertex`, this is used when you do not want to manually specify
+    /// the vertex's UUID. Returns the new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    pub fn create_vertex_from_type(&self, t: Identifier) -> Result<Uuid> {
+        let v = Vertex::with_id(self.datastore.generate_vertex_id(t), t);
+
+        if !self.create_vertex(&v)? {
+            Err(Error::UuidTaken)
+        } else {
+            Ok(v.id)
+        }
+    }
+
+    /// Creates a new vertex of a given type along with its initial
+    /// properties, in one transaction. Returns the new vertex's UUID. If a
+    /// name appears more than once, the last value wins.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    /// * `props`: The property names and values.
+    pub fn create_vertex_with_properties(&self, t: Identifier, props: Vec<(Identifier, Json)>) -> Result<Uuid> {
+        let v = Vertex::with_id(self.datastore.generate_vertex_id(t), t);
+        let mut txn = self.datastore.transaction();
+        if !txn.create_vertex_with_properties(&v, props)? {
+            return Err(Error::UuidTaken);
+        }
+        txn.commit()?;
+        Ok(v.id)
+    }
+
+    /// Creates a new vertex of a given type along with its labels, in one
+    /// transaction. Returns the new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    /// * `labels`: The labels.
+    pub fn create_vertex_with_labels(&self, t: Identifier, labels: BTreeSet<Identifier>) -> Result<Uuid> {
+        let v = Vertex::with_id(self.datastore.generate_vertex_id(t), t);
+        let mut txn = self.datastore.transaction();
+        if !txn.create_vertex_with_labels(&v, labels)? {
+            return Err(Error::UuidTaken);
+        }
+        txn.commit()?;
+        Ok(v.id)
+    }
+
+    /// Atomically creates a vertex along with its properties if it doesn't
+    /// exist, or sets the properties on it if it does. Returns whether the
+    /// vertex was created. An existing vertex keeps its type, and its other
+    /// properties.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create or update.
+    /// * `props`: The property names and values.
+    pub fn upsert_vertex(&self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let created = txn.upsert_vertex(vertex, props)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new edge. Returns whether the edge was successfully
+    /// created - if this is false, it's because one of the specified vertices
+    /// is missing.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    pub fn create_edge(&self, edge: &Edge) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let created = txn.create_edge(edge)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new edge that expires after `ttl`. Returns whether the edge
+    /// was successfully created - if this is false, it's because one of the
+    /// specified vertices is missing.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    /// * `ttl`: How long the edge should live for.
+    pub fn create_edge_with_ttl(&self, edge: &Edge, ttl: Duration) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let created = txn.create_edge_with_ttl(edge, ttl)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates a new edge, but only if it doesn't already exist. Returns
+    /// whether the edge was newly created - if this is false, it's because
+    /// either the edge already exists, or one of the specified vertices is
+    /// missing.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    pub fn create_edge_if_absent(&self, edge: &Edge) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let created = txn.create_edge_if_absent(edge)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Creates an edge along with its reverse, in one transaction, e.g. to
+    /// model an undirected relationship. Returns whether the edges were
+    /// successfully created - if this is false, it's because one of the
+    /// specified vertices is missing.
+    ///
+    /// # Arguments
+    /// * `edge`: One of the two edges to create.
+    pub fn create_symmetric_edge(&self, edge: &Edge) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let created = txn.create_symmetric_edge(edge)?;
+        txn.commit()?;
+        Ok(created)
+    }
+
+    /// Deletes an edge along with its reverse, in one transaction. Returns
+    /// the number of edges deleted.
+    ///
+    /// # Arguments
+    /// * `edge`: One of the two edges to delete.
+    pub fn delete_symmetric_edge(&self, edge: &Edge) -> Result<u64> {
+        let mut txn = self.datastore.transaction();
+        let count = txn.delete_symmetric_edge(edge)?;
+        txn.commit()?;
+        Ok(count)
+    }
+
+    /// Gets the number of vertices of a given type.
+    ///
+    /// # Arguments
+    /// * `t`: The vertex type to count.
+    pub fn get_vertex_count_by_type(&self, t: Identifier) -> Result<u64> {
+        let txn = self.datastore.readonly_transaction();
+        txn.vertex_count_by_type(t)
+    }
+
+    /// Gets the distinct names of vertex properties currently in use, in
+    /// sorted order.
+    pub fn get_vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        let txn = self.datastore.readonly_transaction();
+        txn.vertex_property_names()
+    }
+
+    /// Gets the distinct names of edge properties currently in use, in
+    /// sorted order.
+    pub fn get_edge_property_names(&self) -> Result<Vec<Identifier>> {
+        let txn = self.datastore.readonly_transaction();
+        txn.edge_property_names()
+    }
+
+    /// Gets the names of indexed properties, including paths to nested
+    /// fields, in sorted order.
+    pub fn get_indexed_properties(&self) -> Result<Vec<Identifier>> {
+        let txn = self.datastore.readonly_transaction();
+        txn.indexed_properties()
+    }
+
+    /// Gets the registered property schemas, as pairs of property name and
+    /// expected value type, in sorted order of name.
+    pub fn get_property_schemas(&self) -> Result<Vec<(Identifier, JsonType)>> {
+        let txn = self.datastore.readonly_transaction();
+        txn.property_schemas()
+    }
+
+    /// Gets statistics about the datastore, e.g. for monitoring. This is
+    /// cheaper than counting with queries, but some datastores estimate the
+    /// counts.
+    pub fn get_stats(&self) -> Result<DatastoreStats> {
+        let txn = self.datastore.readonly_transaction();
+        txn.stats()
+    }
+
+    /// Watches for changes to the graph, returning a watcher that receives
+    /// events as mutations are committed. Delivery is best-effort; see
+    /// `ChangeBroadcaster` for details.
+    ///
+    /// # Arguments
+    /// * `filter`: Which changes to watch for.
+    pub fn watch(&self, filter: ChangeFilter) -> Result<Watcher> {
+        self.datastore.watch(filter)
+    }
+
+    /// Gets values specified by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub fn get<Q: Into<Query>>(&self, q: Q) -> Result<Vec<QueryOutputValue>> {
+        let txn = self.datastore.readonly_transaction();
+        get_in_txn(&txn, q.into())
+    }
+
+    /// Describes how a query would run without running it, e.g. to check
+    /// whether it uses a property index.
+    ///
+    /// # Arguments
+    /// * `q`: The query to describe.
+    pub fn explain<Q: Into<Query>>(&self, q: Q) -> Result<QueryPlan> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).explain(q.into()) }
+    }
+
+    /// Streams the vertices specified by a query to a callback, one at a
+    /// time, without collecting them first. See
+    /// `Transaction::stream_vertices` for which queries are streamed.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `f`: Called with each vertex. Returning an error stops the stream
+    ///   and returns the error.
+    ///
+    /// # Errors
+    /// Returns `Error::OperationOnQuery` if the query doesn't output
+    /// vertices.
+    pub fn stream_vertices<Q, F>(&self, q: Q, mut f: F) -> Result<()>
+    where
+        Q: Into<Query>,
+        F: FnMut(Vertex) -> Result<()>,
+    {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        for vertex in unsafe { (*txn).stream_vertices(q.into())? } {
+            f(vertex?)?;
+        }
+        Ok(())
+    }
+
+    /// Gets values specified by several queries, all from one transaction.
+    /// The values of each query are returned in the same position as the
+    /// query. If any query fails, the whole batch fails.
+    ///
+    /// # Arguments
+    /// * `qs`: The queries to run.
+    pub fn get_batch(&self, qs: Vec<Query>) -> Result<Vec<Vec<QueryOutputValue>>> {
+        let txn = self.datastore.readonly_transaction();
+        get_batch_in_txn(&txn, qs)
+    }
+
+    /// Gets the first value specified by a query, or `None` if there are no
+    /// values. Where possible, the query stops as soon as a value is found,
+    /// rather than getting every value. Counts are returned as-is.
     ///
     /// # Arguments
     /// * `q`: The query to run.
-    pub fn get<Q: Into<Query>>(&self, q: Q) -> Result<Vec<QueryOutputValue>> {
-        let q = q.into();
-        let txn = self.datastore.transaction();
-        let mut output = Vec::with_capacity(q.output_len());
-        unsafe {
-            query(&txn as *const D::Transaction<'_>, &q, &mut output)?;
+    pub fn get_one<Q: Into<Query>>(&self, q: Q) -> Result<Option<QueryOutputValue>> {
+        let txn = self.datastore.readonly_transaction();
+        get_one_in_txn(&txn, q.into())
+    }
+
+    /// Gets a page of vertices from a range query, along with a cursor for
+    /// getting the next page, or `None` if there are no more vertices. The
+    /// cursor is opaque, so it can be handed to clients in place of vertex
+    /// IDs.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run. Its limit is the page size.
+    /// * `cursor`: Where to continue from, or `None` for the first page.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidCursor` if the cursor came from a different
+    /// query.
+    pub fn get_vertex_page(
+        &self,
+        q: RangeVertexQuery,
+        cursor: Option<&Cursor>,
+    ) -> Result<(Vec<Vertex>, Option<Cursor>)> {
+        let q = match cursor {
+            Some(cursor) => cursor.apply(q)?,
+            None => q,
+        };
+        let vertices = util::extract_vertices(self.get(q.clone())?).unwrap_or_default();
+        let next = Cursor::after(&q, &vertices);
+        Ok((vertices, next))
+    }
+
+    /// Gets a vertex if it exists, or `None` otherwise. This is a shortcut
+    /// for getting a `SpecificVertexQuery` of a single vertex.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex to get.
+    pub fn get_vertex(&self, id: Uuid) -> Result<Option<Vertex>> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).get_vertex(id) }
+    }
+
+    /// Checks whether a vertex exists.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex to check.
+    pub fn contains_vertex(&self, id: Uuid) -> Result<bool> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).contains_vertex(id) }
+    }
+
+    /// Gets the labels of a vertex, which are empty if the vertex doesn't
+    /// exist.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    pub fn get_vertex_labels(&self, id: Uuid) -> Result<BTreeSet<Identifier>> {
+        let txn = self.datastore.readonly_transaction();
+        txn.vertex_labels(id)
+    }
+
+    /// Gets when a vertex was created, or `None` if it doesn't exist or was
+    /// created before its datastore tracked creation times.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    pub fn get_vertex_created_at(&self, id: Uuid) -> Result<Option<SystemTime>> {
+        let txn = self.datastore.readonly_transaction();
+        txn.vertex_created_at(id)
+    }
+
+    /// Gets an edge if it exists, or `None` otherwise. This is a shortcut
+    /// for getting a `SpecificEdgeQuery` of a single edge.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to get.
+    pub fn get_edge(&self, edge: &Edge) -> Result<Option<Edge>> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).get_edge(edge) }
+    }
+
+    /// Checks whether an edge exists.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to check.
+    pub fn contains_edge(&self, edge: &Edge) -> Result<bool> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).contains_edge(edge) }
+    }
+
+    /// Counts the vertices or edges returned by a query, grouped by the value
+    /// of a property. Returns each distinct value with its count, most common
+    /// first. Values are grouped by their canonical form (see
+    /// `CanonicalJson`), so e.g. `3` and `3.0` are counted together, and are
+    /// returned in that form. Vertices or edges without the property aren't
+    /// counted.
+    ///
+    /// Every result of the query is looked up, so on large graphs, bound the
+    /// work by putting a limit on the query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name to group by.
+    pub fn group_count_by_property<Q: Into<Query>>(&self, q: Q, name: Identifier) -> Result<Vec<(Json, u64)>> {
+        let txn = self.datastore.readonly_transaction();
+        group_count_by_property_in_txn(&txn, q.into(), name)
+    }
+
+    /// Deletes values specified by a query. Returns the number of vertices,
+    /// edges or properties deleted.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub fn delete<Q: Into<Query>>(&self, q: Q) -> Result<u64> {
+        let mut txn = self.datastore.transaction();
+        let count = delete_in_txn(&mut txn, q.into())?;
+        txn.commit()?;
+        Ok(count)
+    }
+
+    /// Deletes every property of the vertices or edges specified by a query,
+    /// in one transaction. Returns the number of properties deleted.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub fn delete_all_properties<Q: Into<Query>>(&self, q: Q) -> Result<u64> {
+        let mut txn = self.datastore.transaction();
+        let count = delete_all_properties_in_txn(&mut txn, q.into())?;
+        txn.commit()?;
+        Ok(count)
+    }
+
+    /// Sets properties.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `value`: The property value.
+    pub fn set_properties<Q: Into<Query>>(&self, q: Q, name: Identifier, value: &Json) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        set_properties_in_txn(&mut txn, q.into(), name, value)?;
+        txn.commit()
+    }
+
+    /// Adds a label to the vertices returned by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `label`: The label.
+    pub fn add_label<Q: Into<Query>>(&self, q: Q, label: Identifier) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        add_label_in_txn(&mut txn, q.into(), label)?;
+        txn.commit()
+    }
+
+    /// Removes a label from the vertices returned by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `label`: The label.
+    pub fn remove_label<Q: Into<Query>>(&self, q: Q, label: Identifier) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        remove_label_in_txn(&mut txn, q.into(), label)?;
+        txn.commit()
+    }
+
+    /// Sets several properties in one transaction. If a name appears more
+    /// than once, the last value wins.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `props`: The property names and values.
+    pub fn set_properties_batch<Q: Into<Query>>(&self, q: Q, props: Vec<(Identifier, Json)>) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        set_properties_batch_in_txn(&mut txn, q.into(), props)?;
+        txn.commit()
+    }
+
+    /// Atomically adds `delta` to a numeric vertex property, treating missing
+    /// properties as 0.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `delta`: The amount to add.
+    ///
+    /// # Errors
+    /// Returns `Error::NotNumeric` if any of the existing values isn't a
+    /// number, in which case no values are changed.
+    pub fn increment_property<Q: Into<Query>>(&self, q: Q, name: Identifier, delta: f64) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        increment_property_in_txn(&mut txn, q.into(), name, delta)?;
+        txn.commit()
+    }
+
+    /// Atomically sets a vertex property to a new value, but only if its
+    /// current value is equal to an expected one, e.g. for optimistic
+    /// concurrency control. Returns whether the property was set. If the
+    /// query returns multiple vertices, either all of them are set or none
+    /// are.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `expected`: The expected current value, or `None` if the property
+    ///   is expected to be missing.
+    /// * `new`: The value to set.
+    pub fn compare_and_set_property<Q: Into<Query>>(
+        &self,
+        q: Q,
+        name: Identifier,
+        expected: Option<&Json>,
+        new: &Json,
+    ) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let swapped = compare_and_set_property_in_txn(&mut txn, q.into(), name, expected, new)?;
+        txn.commit()?;
+        Ok(swapped)
+    }
+
+    /// Atomically deep-merges a JSON object into an object vertex property,
+    /// creating the property if it's missing. Nested objects are merged
+    /// recursively, while other values in the patch overwrite existing ones.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `patch`: The object to merge in.
+    ///
+    /// # Errors
+    /// Returns `Error::NotObject` if the patch or any of the existing values
+    /// isn't an object, in which case no values are changed.
+    pub fn merge_properties<Q: Into<Query>>(&self, q: Q, name: Identifier, patch: &Json) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        merge_properties_in_txn(&mut txn, q.into(), name, patch)?;
+        txn.commit()
+    }
+
+    /// Copies all of the properties of one vertex to another, in one
+    /// transaction. Returns whether both vertices exist - if this is false,
+    /// nothing is copied.
+    ///
+    /// # Arguments
+    /// * `from`: The ID of the vertex to copy the properties from.
+    /// * `to`: The ID of the vertex to copy the properties to.
+    /// * `overwrite`: Whether to overwrite properties the target already
+    ///   has. If false, those are left as is.
+    pub fn copy_vertex_properties(&self, from: Uuid, to: Uuid, overwrite: bool) -> Result<bool> {
+        let mut txn = self.datastore.transaction();
+        let copied = copy_vertex_properties_in_txn(&mut txn, from, to, overwrite)?;
+        txn.commit()?;
+        Ok(copied)
+    }
+
+    /// Bulk inserts many vertices, edges, and/or properties, in one
+    /// transaction.
+    ///
+    /// As with bulk inserts over gRPC, datastores have discretion on how to
+    /// approach safeguard vs performance tradeoffs. In particular, they might
+    /// not ensure that the relevant vertices exist before inserting an edge;
+    /// use `bulk_insert_checked` for that.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    pub fn bulk_insert(&self, items: Vec<BulkInsertItem>) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        txn.bulk_insert(items)?;
+        txn.commit()
+    }
+
+    /// Bulk inserts many vertices, edges, and/or properties, after checking
+    /// that the vertices of each edge exist, either already or earlier in
+    /// `items`. This is slower than `bulk_insert`.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    ///
+    /// # Errors
+    /// Returns `Error::DanglingEdge` for the first edge with a vertex that
+    /// doesn't exist, in which case nothing is inserted.
+    pub fn bulk_insert_checked(&self, items: Vec<BulkInsertItem>) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        txn.bulk_insert_checked(items)?;
+        txn.commit()
+    }
+
+    /// Bulk deletes many vertices, edges, and/or properties. Items that
+    /// don't exist are ignored.
+    ///
+    /// # Arguments
+    /// * `items`: The items to delete.
+    pub fn bulk_delete(&self, items: Vec<BulkDeleteItem>) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        bulk_delete_in_txn(&mut txn, items)?;
+        txn.commit()
+    }
+
+    /// Finds the shortest path of edges from one vertex to another. Returns
+    /// `None` if there is no path within `max_depth` hops.
+    ///
+    /// # Arguments
+    /// * `from`: The ID of the vertex to start from.
+    /// * `to`: The ID of the vertex to end at.
+    /// * `max_depth`: The maximum number of edges in the path.
+    /// * `t`: Only follow edges of this type, if set.
+    /// * `direction`: Only follow edges in this direction, if set. Otherwise
+    ///   edges are followed in both directions.
+    pub fn shortest_path(
+        &self,
+        from: Uuid,
+        to: Uuid,
+        max_depth: u32,
+        t: Option<Identifier>,
+        direction: Option<EdgeDirection>,
+    ) -> Result<Option<Vec<Edge>>> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).shortest_path(from, to, max_depth, t, direction) }
+    }
+
+    /// Gets a vertex along with its outbound and inbound edges, or `None` if
+    /// the vertex doesn't exist, all from a single transaction.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only get edges of this type, if set.
+    /// * `limit`: The maximum number of edges to get in each direction.
+    pub fn get_vertex_neighborhood(
+        &self,
+        id: Uuid,
+        t_filter: Option<Identifier>,
+        limit: u32,
+    ) -> Result<Option<Neighborhood>> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).vertex_neighborhood(id, t_filter, limit) }
+    }
+
+    /// Gets the number of outbound and inbound edges of a vertex, as
+    /// `(outbound, inbound)`, from a single transaction.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only count edges of this type, if set.
+    pub fn get_edge_counts(&self, id: Uuid, t_filter: Option<Identifier>) -> Result<(u64, u64)> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).edge_counts(id, t_filter) }
+    }
+
+    /// Gets the vertices with the most edges in a given direction, along
+    /// with their degree. Results are sorted by degree descending, with ties
+    /// broken by ID.
+    ///
+    /// # Arguments
+    /// * `direction`: Which edges to count - outbound, inbound, or both.
+    /// * `limit`: The maximum number of vertices to return.
+    /// * `t_filter`: Only count edges of this type, if set.
+    pub fn top_vertices_by_degree(
+        &self,
+        direction: EdgeDirection,
+        limit: usize,
+        t_filter: Option<Identifier>,
+    ) -> Result<Vec<(Uuid, u64)>> {
+        let txn = self.datastore.readonly_transaction();
+        let txn = &txn as *const D::Transaction<'_>;
+        unsafe { (*txn).top_vertices_by_degree(direction, limit, t_filter) }
+    }
+
+    /// Enables indexing on a specified property. When indexing is enabled on a
+    /// property, it's possible to query on its presence and values. A nested
+    /// field of a property can be indexed by passing a path created with
+    /// `Identifier::new_path`; properties that don't have the field aren't
+    /// indexed.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to index.
+    pub fn index_property(&self, name: Identifier) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        txn.index_property(name)?;
+        txn.commit()
+    }
+
+    /// Drops the index on a specified property. This is a no-op if the
+    /// property isn't indexed.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to stop
+    ///   indexing.
+    pub fn drop_index(&self, name: Identifier) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        txn.drop_index(name)?;
+        txn.commit()
+    }
+
+    /// Registers the type expected of a property's values, so that setting
+    /// the property to a value of any other type fails with
+    /// `Error::SchemaViolation`. Properties without a registered schema can
+    /// be set to anything.
+    ///
+    /// # Arguments
+    /// * `name`: The property name.
+    /// * `expected`: The type expected of the property's values.
+    pub fn register_property_schema(&self, name: Identifier, expected: JsonType) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        txn.register_property_schema(name, expected)?;
+        txn.commit()
+    }
+
+    /// Removes all vertices, edges, properties, indexes and property schemas,
+    /// returning the database to an empty state.
+    pub fn clear(&self) -> Result<()> {
+        let mut txn = self.datastore.transaction();
+        txn.clear()?;
+        txn.commit()
+    }
+}
+
+// The bodies of the query-based `Database` methods, shared with the async
+// database so that both behave identically once a transaction is acquired.
+
+/// Gets values specified by a query in a given transaction, e.g. to pick
+/// whether it's read-only.
+///
+/// # Arguments
+/// * `txn`: The transaction to run the query in.
+/// * `q`: The query to run.
+pub fn get_in_txn<'a, T: Transaction<'a> + 'a>(txn: &T, q: Query) -> Result<Vec<QueryOutputValue>> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(txn as *const T, &q, &mut output)?;
+    }
+    Ok(output)
+}
+
+/// Gets values specified by several queries in a given transaction.
+///
+/// # Arguments
+/// * `txn`: The transaction to run the queries in.
+/// * `qs`: The queries to run.
+pub fn get_batch_in_txn<'a, T: Transaction<'a> + 'a>(txn: &T, qs: Vec<Query>) -> Result<Vec<Vec<QueryOutputValue>>> {
+    qs.into_iter().map(|q| get_in_txn(txn, q)).collect()
+}
+
+pub(crate) fn get_one_in_txn<'a, T: Transaction<'a> + 'a>(txn: &T, q: Query) -> Result<Option<QueryOutputValue>> {
+    Ok(util::extract_first(get_in_txn(txn, q.first())?))
+}
+
+pub(crate) fn group_count_by_property_in_txn<'a, T: Transaction<'a> + 'a>(
+    txn: &T,
+    q: Query,
+    name: Identifier,
+) -> Result<Vec<(Json, u64)>> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(txn as *const T, &q, &mut output)?;
+    }
+
+    // Values are grouped by their canonical form, so that e.g. `3` and `3.0`
+    // are counted together
+    let mut counts: HashMap<CanonicalJson, u64> = HashMap::new();
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => {
+            for vertex in vertices {
+                if let Some(value) = txn.vertex_property(&vertex, name)? {
+                    *counts.entry(value.canonical()).or_insert(0) += 1;
+                }
+            }
+        }
+        QueryOutputValue::Edges(edges) => {
+            for edge in edges {
+                if let Some(value) = txn.edge_property(&edge, name)? {
+                    *counts.entry(value.canonical()).or_insert(0) += 1;
+                }
+            }
+        }
+        _ => return Err(Error::OperationOnQuery),
+    }
+
+    // JSON values are only partially ordered, so ties are broken by their
+    // serialized form to keep the order stable
+    let mut counts: Vec<(Json, u64)> = counts
+        .into_iter()
+        .map(|(value, count)| (value.into_json(), count))
+        .collect();
+    counts.sort_by_cached_key(|(value, count)| (Reverse(*count), value.to_string()));
+    Ok(counts)
+}
+
+pub(crate) fn delete_in_txn<'a, T: Transaction<'a> + 'a>(txn: &mut T, q: Query) -> Result<u64> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(&*txn as *const T, &q, &mut output)?;
+    }
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => txn.delete_vertices(vertices),
+        QueryOutputValue::Edges(edges) => txn.delete_edges(edges),
+        QueryOutputValue::VertexProperties(vertex_properties) => {
+            // Properties come from the query output, so they all exist
+            let props: Vec<(Uuid, Identifier)> = vertex_properties
+                .into_iter()
+                .flat_map(|vps| {
+                    let iter = vps.props.iter().map(move |vp| (vps.vertex.id, vp.name));
+                    iter.collect::<Vec<(Uuid, Identifier)>>()
+                })
+                .collect();
+            let count = props.len() as u64;
+            txn.delete_vertex_properties(props)?;
+            Ok(count)
+        }
+        QueryOutputValue::EdgeProperties(edge_properties) => {
+            let props: Vec<(Edge, Identifier)> = edge_properties
+                .into_iter()
+                .flat_map(|eps| {
+                    let iter = eps.props.iter().map(move |ep| (eps.edge.clone(), ep.name));
+                    iter.collect::<Vec<(Edge, Identifier)>>()
+                })
+                .collect();
+            let count = props.len() as u64;
+            txn.delete_edge_properties(props)?;
+            Ok(count)
+        }
+        QueryOutputValue::Count(_) => Err(Error::OperationOnQuery),
+    }
+}
+
+pub(crate) fn delete_all_properties_in_txn<'a, T: Transaction<'a> + 'a>(txn: &mut T, q: Query) -> Result<u64> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(&*txn as *const T, &q, &mut output)?;
+    }
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => txn.delete_all_vertex_properties(vertices),
+        QueryOutputValue::Edges(edges) => txn.delete_all_edge_properties(edges),
+        _ => Err(Error::OperationOnQuery),
+    }
+}
+
+pub(crate) fn set_properties_in_txn<'a, T: Transaction<'a> + 'a>(
+    txn: &mut T,
+    q: Query,
+    name: Identifier,
+    value: &Json,
+) -> Result<()> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(&*txn as *const T, &q, &mut output)?;
+    }
+
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => {
+            txn.set_vertex_properties(vertices.into_iter().map(|v| v.id).collect(), name, value)?;
+        }
+        QueryOutputValue::Edges(edges) => {
+            txn.set_edge_properties(edges, name, value)?;
+        }
+        _ => return Err(Error::OperationOnQuery),
+    }
+    Ok(())
+}
+
+pub(crate) fn set_properties_batch_in_txn<'a, T: Transaction<'a> + 'a>(
+    txn: &mut T,
+    q: Query,
+    props: Vec<(Identifier, Json)>,
+) -> Result<()> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(&*txn as *const T, &q, &mut output)?;
+    }
+
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => {
+            txn.set_vertex_properties_batch(vertices.into_iter().map(|v| v.id).collect(), props)?;
+        }
+        QueryOutputValue::Edges(edges) => {
+            txn.set_edge_properties_batch(edges, props)?;
+        }
+        _ => return Err(Error::OperationOnQuery),
+    }
+    Ok(())
+}
+
+pub(crate) fn copy_vertex_properties_in_txn<'a, T: Transaction<'a> + 'a>(
+    txn: &mut T,
+    from: Uuid,
+    to: Uuid,
+    overwrite: bool,
+) -> Result<bool> {
+    let (from, to) = unsafe {
+        let txn = &*txn as *const T;
+        match ((*txn).get_vertex(from)?, (*txn).get_vertex(to)?) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Ok(false),
+        }
+    };
+    txn.copy_vertex_properties(&from, &to, overwrite)?;
+    Ok(true)
+}
+
+pub(crate) fn increment_property_in_txn<'a, T: Transaction<'a> + 'a>(
+    txn: &mut T,
+    q: Query,
+    name: Identifier,
+    delta: f64,
+) -> Result<()> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(&*txn as *const T, &q, &mut output)?;
+    }
+
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => {
+            txn.increment_vertex_properties(vertices, name, delta)?;
+        }
+        _ => return Err(Error::OperationOnQuery),
+    }
+    Ok(())
+}
+
+pub(crate) fn merge_properties_in_txn<'a, T: Transaction<'a> + 'a>(
+    txn: &mut T,
+    q: Query,
+    name: Identifier,
+    patch: &Json,
+) -> Result<()> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(&*txn as *const T, &q, &mut output)?;
+    }
+
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => {
+            txn.merge_vertex_properties(vertices, name, patch)?;
+        }
+        _ => return Err(Error::OperationOnQuery),
+    }
+    Ok(())
+}
+
+pub(crate) fn compare_and_set_property_in_txn<'a, T: Transaction<'a> + 'a>(
+    txn: &mut T,
+    q: Query,
+    name: Identifier,
+    expected: Option<&Json>,
+    new: &Json,
+) -> Result<bool> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(&*txn as *const T, &q, &mut output)?;
+    }
+
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => txn.compare_and_set_vertex_properties(vertices, name, expected, new),
+        _ => Err(Error::OperationOnQuery),
+    }
+}
+
+// Gets the IDs of the vertices returned by a query, for the label methods.
+fn query_vertex_ids<'a, T: Transaction<'a> + 'a>(txn: &T, q: Query) -> Result<Vec<Uuid>> {
+    let mut output = Vec::with_capacity(q.output_len());
+    unsafe {
+        query(txn as *const T, &q, &mut output)?;
+    }
+
+    match output.pop().unwrap() {
+        QueryOutputValue::Vertices(vertices) => Ok(vertices.into_iter().map(|v| v.id).collect()),
+        _ => Err(Error::OperationOnQuery),
+    }
+}
+
+pub(crate) fn add_label_in_txn<'a, T: Transaction<'a> + 'a>(txn: &mut T, q: Query, label: Identifier) -> Result<()> {
+    let vertex_ids = query_vertex_ids(txn, q)?;
+    txn.add_vertex_labels(vertex_ids, label)
+}
+
+pub(crate) fn remove_label_in_txn<'a, T: Transaction<'a> + 'a>(txn: &mut T, q: Query, label: Identifier) -> Result<()> {
+    let vertex_ids = query_vertex_ids(txn, q)?;
+    txn.remove_vertex_labels(vertex_ids, label)
+}
+
+pub(crate) fn bulk_delete_in_txn<'a, T: Transaction<'a> + 'a>(txn: &mut T, items: Vec<BulkDeleteItem>) -> Result<()> {
+    let mut vertex_ids = Vec::new();
+    let mut edges = Vec::new();
+    let mut vertex_properties = Vec::new();
+    let mut edge_properties = Vec::new();
+
+    for item in items {
+        match item {
+            BulkDeleteItem::Vertex(id) => vertex_ids.push(id),
+            BulkDeleteItem::Edge(edge) => edges.push(edge),
+            BulkDeleteItem::Properties(q, name) => {
+                let mut output = Vec::with_capacity(q.output_len());
+                unsafe {
+                    query(&*txn as *const T, &q, &mut output)?;
+                }
+                match output.pop().unwrap() {
+                    QueryOutputValue::Vertices(vertices) => {
+                        vertex_properties.extend(vertices.into_iter().map(|v| (v.id, name)));
+                    }
+                    QueryOutputValue::Edges(query_edges) => {
+                        edge_properties.extend(query_edges.into_iter().map(|e| (e, name)));
+                    }
+                    _ => return Err(Error::OperationOnQuery),
+                }
+            }
         }
-        Ok(output)
     }
 
-    /// Deletes values specified by a query.
-    ///
-    /// # Arguments
-    /// * `q`: The query to run.
-    pub fn delete<Q: Into<Query>>(&self, q: Q) -> Result<()> {
-        let q = q.into();
-        let mut txn = self.datastore.transaction();
+    let vertices = if vertex_ids.is_empty() {
+        Vec::new()
+    } else {
+        let q: Query = SpecificVertexQuery::new(vertex_ids).into();
         let mut output = Vec::with_capacity(q.output_len());
         unsafe {
-            query(&txn as *const D::Transaction<'_>, &q, &mut output)?;
+            query(&*txn as *const T, &q, &mut output)?;
         }
         match output.pop().unwrap() {
-            QueryOutputValue::Vertices(vertices) => {
-                txn.delete_vertices(vertices)?;
-            }
-            QueryOutputValue::Edges(edges) => {
-                txn.delete_edges(edges)?;
-            }
-            QueryOutputValue::VertexProperties(vertex_properties) => {
-                txn.delete_vertex_properties(
-                    vertex_properties
-                        .into_iter()
-                        .flat_map(|vps| {
-                            let iter = vps.props.iter().map(move |vp| (vps.vertex.id, vp.name));
-                            iter.collect::<Vec<(Uuid, Identifier)>>()
-                        })
-                        .collect(),
-                )?;
-            }
-            QueryOutputValue::EdgeProperties(edge_properties) => {
-                txn.delete_edge_properties(
-                    edge_properties
-                        .into_iter()
-                        .flat_map(|eps| {
-                            let iter = eps.props.iter().map(move |ep| (eps.edge.clone(), ep.name));
-                            iter.collect::<Vec<(Edge, Identifier)>>()
-                        })
-                        .collect(),
-                )?;
-            }
-            QueryOutputValue::Count(_) => return Err(Error::OperationOnQuery),
+            QueryOutputValue::Vertices(vertices) => vertices,
+            _ => unreachable!(),
         }
-        Ok(())
+    };
+
+    txn.delete_vertex_properties(vertex_properties)?;
+    txn.delete_edge_properties(edge_properties)?;
+    txn.delete_edges(edges)?;
+    txn.delete_vertices(vertices)?;
+    Ok(())
+}
+
+/// Gets the edges of many vertices in a given direction. For the inbound
+/// direction, edges are returned reversed, so that `outbound_id` is always
+/// the ID of one of the vertices. For both directions, outbound edges of
+/// all the vertices are returned before inbound ones, and self-loops are
+/// only returned once.
+///
+/// This does one range scan per vertex and edge type, via
+/// `range_edges_for_vertices`, so it takes O(k log n + m) for k scans over
+/// n edges returning m of them.
+unsafe fn vertices_edges<'a, T: Transaction<'a> + ?Sized + 'a>(
+    txn: *const T,
+    ids: &[Uuid],
+    direction: EdgeDirection,
+    t_filter: &[Identifier],
+) -> Result<DynIter<'a, Edge>> {
+    if direction == EdgeDirection::Both {
+        let outbound = vertices_edges(txn, ids, EdgeDirection::Outbound, t_filter)?;
+        let inbound = vertices_edges(txn, ids, EdgeDirection::Inbound, t_filter)?;
+        // Self-loops were already returned as outbound edges
+        let inbound = inbound.filter(move |r| match r {
+            Ok(e) => e.inbound_id != e.outbound_id,
+            Err(_) => true,
+        });
+        return Ok(Box::new(outbound.chain(inbound)));
     }
 
-    /// Sets properties.
-    ///
-    /// # Arguments
-    /// * `q`: The query to run.
-    /// * `name`: The property name.
-    /// * `value`: The property value.
-    pub fn set_properties<Q: Into<Query>>(&self, q: Q, name: Identifier, value: &Json) -> Result<()> {
-        let q = q.into();
-        let mut txn = self.datastore.transaction();
-        let mut output = Vec::with_capacity(q.output_len());
-        unsafe {
-            query(&txn as *const D::Transaction<'_>, &q, &mut output)?;
+    // Each edge type is a contiguous range, so do a range scan per type
+    let mut t_filter: Vec<Option<Identifier>> = t_filter.iter().map(|t| Some(*t)).collect();
+    t_filter.sort();
+    t_filter.dedup();
+    if t_filter.is_empty() {
+        t_filter.push(None);
+    }
+
+    let mut roots = Vec::with_capacity(ids.len() * t_filter.len());
+    for id in ids {
+        roots.extend(t_filter.iter().map(|t| (*id, *t)));
+    }
+
+    (*txn).range_edges_for_vertices(roots, direction == EdgeDirection::Inbound)
+}
+
+/// Gets the edges of a vertex in a given direction, like `vertices_edges`.
+unsafe fn vertex_edges<'a, T: Transaction<'a> + ?Sized + 'a>(
+    txn: *const T,
+    id: Uuid,
+    direction: EdgeDirection,
+    t_filter: &[Identifier],
+) -> Result<DynIter<'a, Edge>> {
+    vertices_edges(txn, &[id], direction, t_filter)
+}
+
+/// Like `vertices_edges`, but returns edges as they're stored, rather than
+/// reversing inbound edges.
+unsafe fn stored_vertices_edges<'a, T: Transaction<'a> + ?Sized + 'a>(
+    txn: *const T,
+    ids: &[Uuid],
+    direction: EdgeDirection,
+    t_filter: &[Identifier],
+) -> Result<DynIter<'a, Edge>> {
+    match direction {
+        EdgeDirection::Outbound => vertices_edges(txn, ids, direction, t_filter),
+        EdgeDirection::Inbound => {
+            let iter = vertices_edges(txn, ids, direction, t_filter)?;
+            Ok(Box::new(iter.map(|r| Ok(r?.reversed()))))
         }
+        EdgeDirection::Both => {
+            let outbound = stored_vertices_edges(txn, ids, EdgeDirection::Outbound, t_filter)?;
+            let inbound = stored_vertices_edges(txn, ids, EdgeDirection::Inbound, t_filter)?;
+            // Self-loops were already returned as outbound edges
+            let inbound = inbound.filter(move |r| match r {
+                Ok(e) => e.outbound_id != e.inbound_id,
+                Err(_) => true,
+            });
+            Ok(Box::new(outbound.chain(inbound)))
+        }
+    }
+}
 
-        match output.pop().unwrap() {
-            QueryOutputValue::Vertices(vertices) => {
-                txn.set_vertex_properties(vertices.into_iter().map(|v| v.id).collect(), name, value)?;
-            }
-            QueryOutputValue::Edges(edges) => {
-                txn.set_edge_properties(edges, name, value)?;
+/// Gets the vertices specified by a range query. The iterator is lazy, other
+/// than for candidates found via a property index, which are sorted first.
+unsafe fn range_vertices<'a, T: Transaction<'a> + ?Sized + 'a>(
+    txn: *const T,
+    q: &RangeVertexQuery,
+) -> Result<DynIter<'a, Vertex>> {
+    let indexed_ids = match q.property_filter {
+        Some((name, ref value)) => (*txn).vertex_ids_with_property_value(name, value)?,
+        None => None,
+    };
+
+    let mut iter: DynIter<Vertex> = if let Some(indexed_ids) = indexed_ids {
+        // Use the property index to find candidates, sorted so the results
+        // are consistent with the unindexed range
+        let mut ids = indexed_ids.collect::<Result<Vec<Uuid>>>()?;
+        match (q.direction, q.start_id) {
+            (RangeDirection::Ascending, Some(start_id)) => ids.retain(|id| id >= &start_id),
+            (RangeDirection::Descending, Some(start_id)) => ids.retain(|id| id <= &start_id),
+            (_, None) => {}
+        }
+        ids.sort();
+        if q.direction == RangeDirection::Descending {
+            ids.reverse();
+        }
+        (*txn).specific_vertices(ids)?
+    } else {
+        let mut iter = match (q.direction, q.start_id) {
+            (RangeDirection::Ascending, Some(start_id)) => (*txn).range_vertices(start_id)?,
+            (RangeDirection::Ascending, None) => (*txn).all_vertices()?,
+            (RangeDirection::Descending, start_id) => {
+                (*txn).range_vertices_descending(start_id.unwrap_or(Uuid::max()))?
             }
-            _ => return Err(Error::OperationOnQuery),
+        };
+
+        if let Some((name, value)) = q.property_filter.clone() {
+            iter = Box::new(iter.filter_map(move |r| match r {
+                Ok(v) => match (*txn).vertex_property(&v, name) {
+                    Ok(Some(ref v_value)) if v_value == &value => Some(Ok(v)),
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                },
+                Err(err) => Some(Err(err)),
+            }));
         }
-        Ok(())
-    }
 
-    /// Bulk inserts many vertices, edges, and/or properties.
-    ///
-    /// # Arguments
-    /// * `items`: The items to insert.
-    pub fn bulk_insert(&self, items: Vec<BulkInsertItem>) -> Result<()> {
-        let mut txn = self.datastore.transaction();
-        txn.bulk_insert(items)
-    }
+        iter
+    };
 
-    /// Enables indexing on a specified property. When indexing is enabled on a
-    /// property, it's possible to query on its presence and values.
-    ///
-    /// # Arguments
-    /// * `name`: The name of the property to index.
-    pub fn index_property(&self, name: Identifier) -> Result<()> {
-        let mut txn = self.datastore.transaction();
-        txn.index_property(name)
+    if !q.t_filter.is_empty() {
+        let t_filter = q.t_filter.clone();
+        iter = Box::new(iter.filter(move |r| match r {
+            Ok(v) => t_filter.contains(&v.t),
+            Err(_) => true,
+        }));
     }
+
+    Ok(Box::new(iter.take(q.limit as usize)))
 }
 
-unsafe fn query<'a, T: Transaction<'a> + 'a>(
+unsafe fn query<'a, T: Transaction<'a> + ?Sized + 'a>(
     txn: *const T,
     q: &Query,
     output: &mut Vec<QueryOutputValue>,
@@ -388,20 +2413,7 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
             QueryOutputValue::Vertices(iter.collect::<Result<Vec<Vertex>>>()?)
         }
         Query::RangeVertex(ref q) => {
-            let mut iter: DynIter<Vertex> = if let Some(start_id) = q.start_id {
-                (*txn).range_vertices(start_id)?
-            } else {
-                (*txn).all_vertices()?
-            };
-
-            if let Some(ref t) = q.t {
-                iter = Box::new(iter.filter(move |r| match r {
-                    Ok(v) => &v.t == t,
-                    Err(_) => true,
-                }));
-            }
-
-            iter = Box::new(iter.take(q.limit as usize));
+            let iter = range_vertices(txn, q)?;
             QueryOutputValue::Vertices(iter.collect::<Result<Vec<Vertex>>>()?)
         }
         Query::SpecificVertex(ref q) => {
@@ -417,13 +2429,22 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
                     let iter: Box<dyn Iterator<Item = Uuid>> = match q.direction {
                         EdgeDirection::Outbound => Box::new(piped_edges.iter().map(|e| e.outbound_id)),
                         EdgeDirection::Inbound => Box::new(piped_edges.iter().map(|e| e.inbound_id)),
+                        EdgeDirection::Both => {
+                            let mut seen = HashSet::new();
+                            Box::new(
+                                piped_edges
+                                    .iter()
+                                    .flat_map(|e| [e.outbound_id, e.inbound_id])
+                                    .filter(move |id| seen.insert(*id)),
+                            )
+                        }
                     };
 
                     let mut iter: DynIter<Vertex> = (*txn).specific_vertices(iter.collect())?;
 
-                    if let Some(ref t) = q.t {
+                    if !q.t_filter.is_empty() {
                         iter = Box::new(iter.filter(move |r| match r {
-                            Ok(v) => &v.t == t,
+                            Ok(v) => q.t_filter.contains(&v.t),
                             Err(_) => true,
                         }));
                     }
@@ -434,44 +2455,22 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
                 }
                 QueryOutputValue::Vertices(ref piped_vertices) => {
                     let mut edges = Vec::new();
+                    // An edge between two piped vertices is found from both
+                    // of them when piping in both directions
+                    let mut seen = HashSet::new();
+                    let ids: Vec<Uuid> = piped_vertices.iter().map(|v| v.id).collect();
 
-                    for vertex in piped_vertices {
-                        let lower_bound = match &q.t {
-                            Some(t) => Edge::new(vertex.id, *t, Uuid::default()),
-                            None => Edge::new(vertex.id, Identifier::default(), Uuid::default()),
-                        };
-
-                        let mut iter = if q.direction == EdgeDirection::Outbound {
-                            (*txn).range_edges(lower_bound)?
-                        } else {
-                            (*txn).range_reversed_edges(lower_bound)?
-                        };
-
-                        iter = Box::new(iter.take_while(move |r| match r {
-                            Ok(e) => e.outbound_id == vertex.id,
-                            Err(_) => true,
-                        }));
-
-                        if let Some(ref t) = q.t {
-                            iter = Box::new(iter.filter(move |r| match r {
-                                Ok(e) => &e.t == t,
-                                Err(_) => true,
-                            }));
-                        }
-
-                        if q.direction == EdgeDirection::Inbound {
-                            iter = Box::new(iter.map(move |r| Ok(r?.reversed())));
-                        }
-
-                        iter = Box::new(iter.take((q.limit as usize) - edges.len()));
-
-                        for result in iter {
-                            edges.push(result?);
-                        }
-
+                    // Scan the edges of all the piped vertices in one pass,
+                    // rather than running a query per vertex
+                    for result in stored_vertices_edges(txn, &ids, q.direction, &q.t_filter)? {
                         if edges.len() >= (q.limit as usize) {
                             break;
                         }
+                        let edge = result?;
+                        if q.direction == EdgeDirection::Both && !seen.insert(edge.clone()) {
+                            continue;
+                        }
+                        edges.push(edge);
                     }
 
                     QueryOutputValue::Edges(edges)
@@ -563,6 +2562,19 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
                 return Err(Error::NotIndexed);
             }
         }
+        Query::VertexWithLabel(ref q) => {
+            let ids = (*txn).vertex_ids_with_label(q.label)?.collect::<Result<Vec<Uuid>>>()?;
+            let iter = (*txn).specific_vertices(ids)?;
+            QueryOutputValue::Vertices(iter.collect::<Result<Vec<Vertex>>>()?)
+        }
+        Query::CreatedRangeVertex(ref q) => {
+            let ids = (*txn)
+                .vertex_ids_created_in_range(q.low, q.high)?
+                .take(q.limit as usize)
+                .collect::<Result<Vec<Uuid>>>()?;
+            let iter = (*txn).specific_vertices(ids)?;
+            QueryOutputValue::Vertices(iter.collect::<Result<Vec<Vertex>>>()?)
+        }
         Query::EdgeWithPropertyPresence(ref q) => {
             if let Some(iter) = (*txn).edges_with_property(q.name)? {
                 QueryOutputValue::Edges(iter.collect::<Result<Vec<Edge>>>()?)
@@ -577,33 +2589,65 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
                 return Err(Error::NotIndexed);
             }
         }
+        Query::WithPropertyValue(ref q) => {
+            let vertex_ids = match (*txn).vertex_ids_with_property_value(q.name, &q.value)? {
+                Some(iter) => iter.collect::<Result<Vec<Uuid>>>()?,
+                None => return Err(Error::NotIndexed),
+            };
+            let edges = match (*txn).edges_with_property_value(q.name, &q.value)? {
+                Some(iter) => iter.collect::<Result<Vec<Edge>>>()?,
+                None => return Err(Error::NotIndexed),
+            };
+            let iter = (*txn).specific_vertices(vertex_ids)?;
+            output.push(QueryOutputValue::Vertices(iter.collect::<Result<Vec<Vertex>>>()?));
+            QueryOutputValue::Edges(edges)
+        }
         Query::PipeWithPropertyPresence(ref q) => {
             query(txn, &q.inner, output)?;
             let piped_values = output.pop().unwrap();
 
+            // Uses the property index if there is one, and otherwise looks up
+            // the property of each value, following the fields of paths
+            let (property_name, fields) = q.name.split_path();
             let values = match piped_values {
-                QueryOutputValue::Edges(ref piped_edges) => {
-                    let edges_with_property = match (*txn).edges_with_property(q.name)? {
-                        Some(iter) => iter.collect::<Result<HashSet<Edge>>>()?,
-                        None => return Err(Error::NotIndexed),
-                    };
-                    let iter = piped_edges.iter().filter(move |e| {
-                        let contains = edges_with_property.contains(e);
-                        (q.exists && contains) || (!q.exists && !contains)
-                    });
-                    QueryOutputValue::Edges(iter.cloned().collect())
-                }
-                QueryOutputValue::Vertices(ref piped_vertices) => {
-                    let vertices_with_property = match (*txn).vertex_ids_with_property(q.name)? {
-                        Some(iter) => iter.collect::<Result<HashSet<Uuid>>>()?,
-                        None => return Err(Error::NotIndexed),
-                    };
-                    let iter = piped_vertices.iter().filter(move |v| {
-                        let contains = vertices_with_property.contains(&v.id);
-                        (q.exists && contains) || (!q.exists && !contains)
-                    });
-                    QueryOutputValue::Vertices(iter.cloned().collect())
-                }
+                QueryOutputValue::Edges(ref piped_edges) => match (*txn).edges_with_property(q.name)? {
+                    Some(iter) => {
+                        let edges_with_property = iter.collect::<Result<HashSet<Edge>>>()?;
+                        let iter = piped_edges
+                            .iter()
+                            .filter(move |e| edges_with_property.contains(e) == q.exists);
+                        QueryOutputValue::Edges(iter.cloned().collect())
+                    }
+                    None => {
+                        let mut edges = Vec::new();
+                        for edge in piped_edges {
+                            let value = (*txn).edge_property(edge, property_name)?;
+                            if value.and_then(|value| util::json_at_path(&value, &fields)).is_some() == q.exists {
+                                edges.push(edge.clone());
+                            }
+                        }
+                        QueryOutputValue::Edges(edges)
+                    }
+                },
+                QueryOutputValue::Vertices(ref piped_vertices) => match (*txn).vertex_ids_with_property(q.name)? {
+                    Some(iter) => {
+                        let vertices_with_property = iter.collect::<Result<HashSet<Uuid>>>()?;
+                        let iter = piped_vertices
+                            .iter()
+                            .filter(move |v| vertices_with_property.contains(&v.id) == q.exists);
+                        QueryOutputValue::Vertices(iter.cloned().collect())
+                    }
+                    None => {
+                        let mut vertices = Vec::new();
+                        for vertex in piped_vertices {
+                            let value = (*txn).vertex_property(vertex, property_name)?;
+                            if value.and_then(|value| util::json_at_path(&value, &fields)).is_some() == q.exists {
+                                vertices.push(vertex.clone());
+                            }
+                        }
+                        QueryOutputValue::Vertices(vertices)
+                    }
+                },
                 _ => {
                     return Err(Error::OperationOnQuery);
                 }
@@ -655,10 +2699,151 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
 
             values
         }
+        Query::PipeWithPropertyPredicate(ref q) => {
+            // Compile the predicate before running the inner query, so that
+            // an invalid regex fails fast
+            let matcher = q.predicate.matcher()?;
+            query(txn, &q.inner, output)?;
+            let piped_values = output.pop().unwrap();
+
+            let values = match piped_values {
+                QueryOutputValue::Edges(ref piped_edges) => {
+                    let mut edges = Vec::new();
+                    for edge in piped_edges {
+                        if edges.len() >= q.limit as usize {
+                            break;
+                        }
+                        if let Some(value) = (*txn).edge_property(edge, q.name)? {
+                            if matcher.matches(&value) {
+                                edges.push(edge.clone());
+                            }
+                        }
+                    }
+                    QueryOutputValue::Edges(edges)
+                }
+                QueryOutputValue::Vertices(ref piped_vertices) => {
+                    let mut vertices = Vec::new();
+                    for vertex in piped_vertices {
+                        if vertices.len() >= q.limit as usize {
+                            break;
+                        }
+                        if let Some(value) = (*txn).vertex_property(vertex, q.name)? {
+                            if matcher.matches(&value) {
+                                vertices.push(vertex.clone());
+                            }
+                        }
+                    }
+                    QueryOutputValue::Vertices(vertices)
+                }
+                _ => {
+                    return Err(Error::OperationOnQuery);
+                }
+            };
+
+            if let Query::Include(_) = *q.inner {
+                // keep the value exported
+                output.push(piped_values);
+            }
+
+            values
+        }
+        Query::TraversalVertex(ref q) => {
+            query(txn, &q.inner, output)?;
+            let piped_values = output.pop().unwrap();
+
+            let values = match piped_values {
+                QueryOutputValue::Vertices(ref piped_vertices) => {
+                    let limit = q.limit as usize;
+                    let mut visited = HashSet::new();
+                    let mut vertices = Vec::new();
+                    let mut frontier = Vec::new();
+
+                    for vertex in piped_vertices {
+                        if vertices.len() >= limit {
+                            break;
+                        }
+                        if visited.insert(vertex.id) {
+                            frontier.push(vertex.id);
+                            vertices.push(vertex.clone());
+                        }
+                    }
+
+                    let mut depth = 0;
+
+                    while depth < q.max_depth && !frontier.is_empty() && vertices.len() < limit {
+                        let mut next_ids = Vec::new();
+
+                        for id in frontier {
+                            for result in vertex_edges(txn, id, q.direction, &q.t_filter)? {
+                                let edge = result?;
+                                if visited.insert(edge.inbound_id) {
+                                    next_ids.push(edge.inbound_id);
+                                }
+                            }
+                        }
+
+                        frontier = Vec::with_capacity(next_ids.len());
+
+                        for result in (*txn).specific_vertices(next_ids)? {
+                            if vertices.len() >= limit {
+                                break;
+                            }
+                            let vertex = result?;
+                            frontier.push(vertex.id);
+                            vertices.push(vertex);
+                        }
+
+                        depth += 1;
+                    }
+
+                    QueryOutputValue::Vertices(vertices)
+                }
+                _ => {
+                    return Err(Error::OperationOnQuery);
+                }
+            };
+
+            if let Query::Include(_) = *q.inner {
+                // keep the value exported
+                output.push(piped_values);
+            }
+
+            values
+        }
         Query::AllEdge => {
             let iter = (*txn).all_edges()?;
             QueryOutputValue::Edges(iter.collect::<Result<Vec<Edge>>>()?)
         }
+        Query::RangeEdge(ref q) => {
+            // Start from the first edge of the vertex, or from the edge to
+            // continue after if that's later
+            let first = Edge::new(
+                q.outbound_id.unwrap_or_default(),
+                Identifier::default(),
+                Uuid::default(),
+            );
+            let offset = match q.after {
+                Some(ref after) if *after > first => after.clone(),
+                _ => first,
+            };
+
+            let after = q.after.clone();
+            let outbound_id = q.outbound_id;
+            let t_filter = &q.t_filter;
+            let iter = (*txn)
+                .range_edges(offset)?
+                .skip_while(move |r| matches!((r, &after), (Ok(e), Some(after)) if e == after))
+                .take_while(move |r| match (r, outbound_id) {
+                    (Ok(e), Some(id)) => e.outbound_id == id,
+                    _ => true,
+                })
+                .filter(move |r| match r {
+                    Ok(e) => t_filter.is_empty() || t_filter.contains(&e.t),
+                    Err(_) => true,
+                })
+                .take(q.limit as usize);
+            QueryOutputValue::Edges(iter.collect::<Result<Vec<Edge>>>()?)
+        }
         Query::SpecificEdge(ref q) => {
             let iter = (*txn).specific_edges(q.edges.clone())?;
             QueryOutputValue::Edges(iter.collect::<Result<Vec<Edge>>>()?)
@@ -672,6 +2857,33 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
                 // These paths are optimized
                 Query::AllVertex => (*txn).vertex_count(),
                 Query::AllEdge => (*txn).edge_count(),
+                // Edges piped from vertices are counted without collecting
+                // them, other than in both directions, where they need to be
+                // deduplicated
+                Query::Pipe(ref q)
+                    if q.direction != EdgeDirection::Both
+                        && matches!(q.inner.output_type(), Ok(QueryOutputValue::Vertices(_))) =>
+                {
+                    query(txn, &q.inner, output)?;
+                    let piped_values = output.pop().unwrap();
+                    let limit = q.limit as usize;
+                    let mut count = 0;
+
+                    if let QueryOutputValue::Vertices(ref piped_vertices) = piped_values {
+                        let ids: Vec<Uuid> = piped_vertices.iter().map(|v| v.id).collect();
+                        for result in vertices_edges(txn, &ids, q.direction, &q.t_filter)?.take(limit) {
+                            result?;
+                            count += 1;
+                        }
+                    }
+
+                    if let Query::Include(_) = *q.inner {
+                        // keep the value exported
+                        output.push(piped_values);
+                    }
+
+                    count as u64
+                }
                 q => {
                     query(txn, q, output)?;
                     let piped_values = output.pop().unwrap();
@@ -696,3 +2908,181 @@ unsafe fn query<'a, T: Transaction<'a> + 'a>(
     output.push(value);
     Ok(())
 }
+
+// Counts the results of an iterator, e.g. over the IDs in an index.
+fn count_results<T>(iter: impl Iterator<Item = Result<T>>) -> Result<u64> {
+    let mut count = 0;
+    for result in iter {
+        result?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+// Describes a stage that looks up a property index, given the number of
+// results in the index, or `None` if the property isn't indexed.
+fn index_plan(stage: &str, name: Identifier, count: Option<u64>) -> QueryPlan {
+    match count {
+        Some(count) => QueryPlan::new(stage, PlanAccess::PropertyIndex, Some(count)).property(name),
+        None => QueryPlan::new(stage, PlanAccess::MissingIndex, None).property(name),
+    }
+}
+
+// Describes a pipe stage that filters its input via a property index.
+fn filter_plan(stage: &str, name: Identifier, indexed: bool, inner: QueryPlan) -> QueryPlan {
+    let access = if indexed {
+        PlanAccess::PropertyIndex
+    } else {
+        PlanAccess::MissingIndex
+    };
+    QueryPlan::new(stage, access, inner.estimated_count)
+        .property(name)
+        .inner(inner)
+}
+
+/// Describes how `query` would run a query, without running it.
+unsafe fn explain<'a, T: Transaction<'a> + ?Sized + 'a>(txn: *const T, q: &Query) -> Result<QueryPlan> {
+    let plan = match q {
+        Query::AllVertex => QueryPlan::new("AllVertex", PlanAccess::FullScan, Some((*txn).vertex_count())),
+        Query::RangeVertex(ref q) => {
+            let limit = u64::from(q.limit);
+            let scan_count = (*txn).vertex_count().min(limit);
+            match q.property_filter {
+                Some((name, ref value)) => match (*txn).vertex_ids_with_property_value(name, value)? {
+                    Some(iter) => {
+                        let count = count_results(iter)?.min(limit);
+                        QueryPlan::new("RangeVertex", PlanAccess::PropertyIndex, Some(count)).property(name)
+                    }
+                    None => QueryPlan::new("RangeVertex", PlanAccess::RangeScan, Some(scan_count)).property(name),
+                },
+                None => QueryPlan::new("RangeVertex", PlanAccess::RangeScan, Some(scan_count)),
+            }
+        }
+        Query::SpecificVertex(ref q) => QueryPlan::new("SpecificVertex", PlanAccess::Lookup, Some(q.ids.len() as u64)),
+        Query::VertexWithPropertyPresence(ref q) => {
+            let count = (*txn)
+                .vertex_ids_with_property(q.name)?
+                .map(count_results)
+                .transpose()?;
+            index_plan("VertexWithPropertyPresence", q.name, count)
+        }
+        Query::VertexWithPropertyValue(ref q) => {
+            let count = (*txn)
+                .vertex_ids_with_property_value(q.name, &q.value)?
+                .map(count_results)
+                .transpose()?;
+            index_plan("VertexWithPropertyValue", q.name, count)
+        }
+        Query::VertexWithLabel(ref q) => {
+            let count = count_results((*txn).vertex_ids_with_label(q.label)?)?;
+            QueryPlan::new("VertexWithLabel", PlanAccess::Index, Some(count))
+        }
+        Query::CreatedRangeVertex(ref q) => {
+            let iter = (*txn).vertex_ids_created_in_range(q.low, q.high)?;
+            let count = count_results(iter.take(q.limit as usize))?;
+            QueryPlan::new("CreatedRangeVertex", PlanAccess::Index, Some(count))
+        }
+        Query::AllEdge => QueryPlan::new("AllEdge", PlanAccess::FullScan, Some((*txn).edge_count())),
+        Query::RangeEdge(ref q) => {
+            let count = (*txn).edge_count().min(u64::from(q.limit));
+            QueryPlan::new("RangeEdge", PlanAccess::RangeScan, Some(count))
+        }
+        Query::SpecificEdge(ref q) => QueryPlan::new("SpecificEdge", PlanAccess::Lookup, Some(q.edges.len() as u64)),
+        Query::EdgeWithPropertyPresence(ref q) => {
+            let count = (*txn).edges_with_property(q.name)?.map(count_results).transpose()?;
+            index_plan("EdgeWithPropertyPresence", q.name, count)
+        }
+        Query::EdgeWithPropertyValue(ref q) => {
+            let count = (*txn)
+                .edges_with_property_value(q.name, &q.value)?
+                .map(count_results)
+                .transpose()?;
+            index_plan("EdgeWithPropertyValue", q.name, count)
+        }
+        Query::WithPropertyValue(ref q) => {
+            let vertex_count = (*txn)
+                .vertex_ids_with_property_value(q.name, &q.value)?
+                .map(count_results)
+                .transpose()?;
+            let edge_count = (*txn)
+                .edges_with_property_value(q.name, &q.value)?
+                .map(count_results)
+                .transpose()?;
+            let count = vertex_count.zip(edge_count).map(|(v, e)| v + e);
+            index_plan("WithPropertyValue", q.name, count)
+        }
+        Query::Pipe(ref q) => {
+            let inner = explain(txn, &q.inner)?;
+            // Each piped edge leads to at most one vertex, or two in both
+            // directions, whereas vertices can have any number of edges
+            let estimated_count = match q.inner.output_type() {
+                Ok(QueryOutputValue::Edges(_)) => {
+                    let per_edge = if q.direction == EdgeDirection::Both { 2 } else { 1 };
+                    inner
+                        .estimated_count
+                        .map(|count| count.saturating_mul(per_edge).min(u64::from(q.limit)))
+                }
+                _ => None,
+            };
+            QueryPlan::new("Pipe", PlanAccess::Pipe, estimated_count).inner(inner)
+        }
+        Query::PipeProperty(ref q) => {
+            let inner = explain(txn, &q.inner)?;
+            let plan = QueryPlan::new("PipeProperty", PlanAccess::Pipe, inner.estimated_count);
+            match q.name {
+                Some(name) => plan.property(name).inner(inner),
+                None => plan.inner(inner),
+            }
+        }
+        Query::PipeWithPropertyPresence(ref q) => {
+            let inner = explain(txn, &q.inner)?;
+            let indexed = match q.inner.output_type() {
+                Ok(QueryOutputValue::Edges(_)) => (*txn).edges_with_property(q.name)?.is_some(),
+                _ => (*txn).vertex_ids_with_property(q.name)?.is_some(),
+            };
+            if indexed {
+                filter_plan("PipeWithPropertyPresence", q.name, indexed, inner)
+            } else {
+                // Without an index, the property of each value is looked up
+                QueryPlan::new("PipeWithPropertyPresence", PlanAccess::Pipe, inner.estimated_count)
+                    .property(q.name)
+                    .inner(inner)
+            }
+        }
+        Query::PipeWithPropertyValue(ref q) => {
+            let inner = explain(txn, &q.inner)?;
+            let indexed = match q.inner.output_type() {
+                Ok(QueryOutputValue::Edges(_)) => (*txn).edges_with_property_value(q.name, &q.value)?.is_some(),
+                _ => (*txn).vertex_ids_with_property_value(q.name, &q.value)?.is_some(),
+            };
+            filter_plan("PipeWithPropertyValue", q.name, indexed, inner)
+        }
+        Query::PipeWithPropertyPredicate(ref q) => {
+            let inner = explain(txn, &q.inner)?;
+            let count = match inner.estimated_count {
+                Some(count) => Some(count.min(u64::from(q.limit))),
+                None if q.limit < u32::MAX => Some(u64::from(q.limit)),
+                None => None,
+            };
+            QueryPlan::new("PipeWithPropertyPredicate", PlanAccess::Pipe, count)
+                .property(q.name)
+                .inner(inner)
+        }
+        Query::TraversalVertex(ref q) => {
+            let inner = explain(txn, &q.inner)?;
+            let count = (*txn).vertex_count().min(u64::from(q.limit));
+            QueryPlan::new("TraversalVertex", PlanAccess::Pipe, Some(count)).inner(inner)
+        }
+        Query::Include(ref q) => {
+            let inner = explain(txn, &q.inner)?;
+            QueryPlan::new("Include", PlanAccess::Pipe, inner.estimated_count).inner(inner)
+        }
+        Query::Count(ref q) => match *q.inner {
+            // These are counted without running the inner query
+            Query::AllVertex | Query::AllEdge => QueryPlan::new("Count", PlanAccess::StoredCount, Some(1)),
+            ref inner => QueryPlan::new("Count", PlanAccess::Pipe, Some(1)).inner(explain(txn, inner)?),
+        },
+    };
+
+    Ok(plan)
+}
@@ -2,11 +2,15 @@ use std::error::Error as StdError;
 use std::fmt;
 use std::result::Result as StdResult;
 
-#[cfg(feature = "rocksdb-datastore")]
+use crate::{Edge, JsonType};
+
 use bincode::Error as BincodeError;
+use regex::Error as RegexError;
 use rmp_serde::encode::Error as RmpEncodeError;
 #[cfg(feature = "rocksdb-datastore")]
 use rocksdb::Error as RocksDbError;
+#[cfg(feature = "sqlite-datastore")]
+use rusqlite::Error as SqliteError;
 use serde_json::Error as JsonError;
 
 /// An error triggered by the datastore.
@@ -31,6 +35,43 @@ pub enum Error {
     /// The operation cannot work with the given query, based off it's output
     /// type (e.g. attempting to delete using a query that outputs a count.)
     OperationOnQuery,
+
+    /// A numeric operation was attempted on a property value that isn't a
+    /// number.
+    NotNumeric,
+
+    /// An object operation was attempted on a property value that isn't a
+    /// JSON object.
+    NotObject,
+
+    /// A write was attempted in a read-only transaction.
+    ReadOnly,
+
+    /// A write would exceed a limit configured on the datastore, e.g. on the
+    /// number of vertices.
+    LimitExceeded,
+
+    /// The pattern of a regex property predicate is invalid.
+    InvalidRegex(RegexError),
+
+    /// A property value doesn't match the type registered for the property
+    /// in its schema.
+    SchemaViolation,
+
+    /// The transaction conflicted with a concurrent one, and can be retried -
+    /// see `Datastore::with_transaction_retry`.
+    SerializationConflict,
+
+    /// A pagination cursor is malformed, or came from a different query -
+    /// see `Cursor`.
+    InvalidCursor,
+
+    /// An edge was inserted via `Transaction::bulk_insert_checked`, but one
+    /// of its vertices doesn't exist.
+    DanglingEdge {
+        /// The edge.
+        key: Edge,
+    },
 }
 
 impl StdError for Error {
@@ -38,6 +79,7 @@ impl StdError for Error {
         match *self {
             Error::Datastore(ref err) => Some(&**err),
             Error::Invalid(ref err) => Some(err),
+            Error::InvalidRegex(ref err) => Some(err),
             _ => None,
         }
     }
@@ -52,6 +94,15 @@ impl fmt::Display for Error {
             Error::Unsupported => write!(f, "functionality not supported"),
             Error::Invalid(ref err) => write!(f, "{err}"),
             Error::OperationOnQuery => write!(f, "the operation cannot work with the given query"),
+            Error::NotNumeric => write!(f, "the property value is not numeric"),
+            Error::NotObject => write!(f, "the property value is not an object"),
+            Error::ReadOnly => write!(f, "the transaction is read-only"),
+            Error::LimitExceeded => write!(f, "a datastore limit was exceeded"),
+            Error::InvalidRegex(ref err) => write!(f, "invalid regex: {err}"),
+            Error::SchemaViolation => write!(f, "the property value does not match its schema"),
+            Error::SerializationConflict => write!(f, "the transaction conflicted with a concurrent transaction"),
+            Error::InvalidCursor => write!(f, "invalid cursor"),
+            Error::DanglingEdge { ref key } => write!(f, "a vertex of the edge {key:?} does not exist"),
         }
     }
 }
@@ -62,7 +113,6 @@ impl From<JsonError> for Error {
     }
 }
 
-#[cfg(feature = "rocksdb-datastore")]
 impl From<BincodeError> for Error {
     fn from(err: BincodeError) -> Self {
         Error::Datastore(Box::new(err))
@@ -82,6 +132,13 @@ impl From<RocksDbError> for Error {
     }
 }
 
+#[cfg(feature = "sqlite-datastore")]
+impl From<SqliteError> for Error {
+    fn from(err: SqliteError) -> Self {
+        Error::Datastore(Box::new(err))
+    }
+}
+
 impl From<ValidationError> for Error {
     fn from(err: ValidationError) -> Self {
         Error::Invalid(err)
@@ -121,3 +178,80 @@ impl fmt::Display for ValidationError {
 
 /// A result that might be a `ValidationError`.
 pub type ValidationResult<T> = StdResult<T, ValidationError>;
+
+/// Why an identifier is invalid - see `Identifier::try_new`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IdentifierError {
+    /// The identifier is longer than the maximum length.
+    TooLong {
+        /// The length of the identifier, in bytes.
+        len: usize,
+        /// The maximum length, in bytes.
+        max: usize,
+    },
+    /// The identifier contains a character other than a letter, number,
+    /// dash or underscore.
+    InvalidCharacter {
+        /// The invalid character.
+        ch: char,
+        /// The position of the character in the identifier, counted in
+        /// characters rather than bytes.
+        index: usize,
+    },
+    /// A part of a path identifier is empty, e.g. in `addr..city`.
+    Empty,
+}
+
+impl StdError for IdentifierError {}
+
+impl fmt::Display for IdentifierError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IdentifierError::TooLong { len, max } => {
+                write!(f, "identifier is {len} bytes long, but can be at most {max}")
+            }
+            IdentifierError::InvalidCharacter { ch, index } => {
+                write!(f, "identifier has an invalid character {ch:?} at position {index}")
+            }
+            IdentifierError::Empty => write!(f, "identifier has an empty path part"),
+        }
+    }
+}
+
+impl From<IdentifierError> for ValidationError {
+    fn from(err: IdentifierError) -> Self {
+        match err {
+            IdentifierError::TooLong { .. } => ValidationError::ValueTooLong,
+            _ => ValidationError::InvalidValue,
+        }
+    }
+}
+
+impl From<IdentifierError> for Error {
+    fn from(err: IdentifierError) -> Self {
+        Error::Invalid(err.into())
+    }
+}
+
+/// The error returned when converting a `Json` value into a Rust type fails,
+/// because the value is of a different type - e.g. converting a JSON number
+/// into a `String`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct JsonTypeError {
+    /// The type the conversion expected.
+    pub expected: JsonType,
+    /// The type of the value.
+    pub actual: JsonType,
+}
+
+impl StdError for JsonTypeError {}
+
+impl fmt::Display for JsonTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a JSON {:?}, but got a JSON {:?}",
+            self.expected, self.actual
+        )
+    }
+}
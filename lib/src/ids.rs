@@ -0,0 +1,97 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::util::generate_uuid_v1;
+use crate::Identifier;
+
+use uuid::{Builder, Uuid};
+
+// The increment of the splitmix64 generator.
+const SPLITMIX64_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Generates the IDs of vertices that are created from just a type, e.g. via
+/// `Database::create_vertex_from_type`.
+///
+/// Generated IDs don't have to be unique: if one is already taken, creating
+/// the vertex fails with `Error::UuidTaken` rather than overwriting the
+/// existing vertex. To derive IDs from a natural key instead, e.g. with UUID
+/// v5 for idempotent ingestion, create vertices via `Database::create_vertex`
+/// with `Vertex::with_id`.
+pub trait VertexIdGenerator: Debug + Send + Sync {
+    /// Generates an ID for a new vertex.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the new vertex.
+    fn generate(&self, t: Identifier) -> Uuid;
+}
+
+/// Generates UUID v1 vertex IDs, which are trivially guessable, but index
+/// better than random ones. This is the default generator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UuidV1Generator;
+
+impl VertexIdGenerator for UuidV1Generator {
+    fn generate(&self, _t: Identifier) -> Uuid {
+        generate_uuid_v1()
+    }
+}
+
+/// Generates random-looking UUID v4 vertex IDs from a seed, so that the same
+/// seed always produces the same sequence of IDs. This is meant for
+/// reproducible tests; the IDs are trivially guessable from the seed.
+#[derive(Debug)]
+pub struct SeededIdGenerator {
+    state: AtomicU64,
+}
+
+impl SeededIdGenerator {
+    /// Creates a new seeded generator.
+    ///
+    /// # Arguments
+    /// * `seed`: The seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    // Gets the next value of a splitmix64 sequence.
+    fn next_u64(&self) -> u64 {
+        let mut z = self
+            .state
+            .fetch_add(SPLITMIX64_GAMMA, Ordering::Relaxed)
+            .wrapping_add(SPLITMIX64_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl VertexIdGenerator for SeededIdGenerator {
+    fn generate(&self, _t: Identifier) -> Uuid {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_be_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_be_bytes());
+        Builder::from_random_bytes(bytes).into_uuid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SeededIdGenerator, VertexIdGenerator};
+    use crate::Identifier;
+    use uuid::{Uuid, Version};
+
+    fn generate(generator: &SeededIdGenerator) -> Vec<Uuid> {
+        (0..3).map(|_| generator.generate(Identifier::default())).collect()
+    }
+
+    #[test]
+    fn should_generate_seeded_ids() {
+        let ids = generate(&SeededIdGenerator::new(42));
+        assert_eq!(ids, generate(&SeededIdGenerator::new(42)));
+        assert_ne!(ids, generate(&SeededIdGenerator::new(43)));
+        assert_ne!(ids[0], ids[1]);
+        assert!(ids.iter().all(|id| id.get_version() == Some(Version::Random)));
+    }
+}
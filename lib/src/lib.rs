@@ -21,17 +21,35 @@ pub mod benches;
 
 mod database;
 mod errors;
+mod ids;
 mod memory;
 mod models;
+mod observe;
 pub mod util;
+mod watch;
 
 pub use crate::database::*;
 pub use crate::errors::*;
+pub use crate::ids::*;
 pub use crate::memory::*;
 pub use crate::models::*;
+pub use crate::observe::*;
+pub use crate::watch::*;
+
+#[cfg(feature = "async")]
+mod async_database;
+
+#[cfg(feature = "async")]
+pub use crate::async_database::{AsyncDatabase, AsyncDatastore};
 
 #[cfg(feature = "rocksdb-datastore")]
 mod rdb;
 
 #[cfg(feature = "rocksdb-datastore")]
-pub use crate::rdb::RocksdbDatastore;
+pub use crate::rdb::{RocksdbConfig, RocksdbDatastore};
+
+#[cfg(feature = "sqlite-datastore")]
+mod sqlite;
+
+#[cfg(feature = "sqlite-datastore")]
+pub use crate::sqlite::SqliteDatastore;
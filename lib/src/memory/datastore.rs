@@ -1,30 +1,213 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
-use std::path::PathBuf;
+use std::io::{BufReader, BufWriter, Error as IoError, ErrorKind as IoErrorKind, Read, Write};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
 use std::result::Result as StdResult;
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::{Arc, Once};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
 use crate::errors::{Error, Result};
 use crate::util;
-use crate::{Database, Datastore, DynIter, Edge, Identifier, Json, Transaction, Vertex};
+#[cfg(feature = "async")]
+use crate::{AsyncDatabase, AsyncDatastore};
+use crate::{
+    ChangeBroadcaster, ChangeEvent, ChangeFilter, Database, Datastore, DatastoreStats, DynIter, Edge, Identifier, Json,
+    JsonType, SeededIdGenerator, Transaction, UuidV1Generator, Vertex, VertexIdGenerator, Watcher,
+};
 
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use rmp_serde::decode::Error as RmpDecodeError;
 use serde::{Deserialize, Serialize};
 use tempfile::NamedTempFile;
 use uuid::Uuid;
 
-#[derive(Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[cfg(feature = "async")]
+use std::sync::TryLockError;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "async")]
+use tokio::sync::Notify;
+
+// Snapshots written by `MemoryDatastore::save_to_path` start with these magic
+// bytes, followed by the format version. Bump the version whenever
+// `InternalMemory` changes in a way that breaks deserialization.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"INDRAMEM";
+const SNAPSHOT_VERSION: u32 = 1;
+
+// How often expired edges are reaped in the background.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+fn invalid_snapshot(message: String) -> Error {
+    Error::Datastore(Box::new(IoError::new(IoErrorKind::InvalidData, message)))
+}
+
+// The lock guarding the datastore. It's a standard lock, so that synchronous
+// transactions can be created from any context, including from within an
+// async runtime. With the `async` feature, async transactions wait on it
+// without blocking the runtime, by trying to take it and otherwise waiting
+// until a guard is released.
+#[derive(Debug)]
+struct InternalLock {
+    lock: RwLock<InternalMemory>,
+    #[cfg(feature = "async")]
+    released: Notify,
+}
+
+impl InternalLock {
+    fn new(internal: InternalMemory) -> Self {
+        Self {
+            lock: RwLock::new(internal),
+            #[cfg(feature = "async")]
+            released: Notify::new(),
+        }
+    }
+
+    fn read(&self) -> InternalReadGuard<'_> {
+        InternalReadGuard {
+            guard: Some(self.lock.read().unwrap()),
+            lock: self,
+        }
+    }
+
+    fn write(&self) -> InternalWriteGuard<'_> {
+        InternalWriteGuard {
+            guard: Some(self.lock.write().unwrap()),
+            lock: self,
+        }
+    }
+
+    #[cfg(feature = "async")]
+    async fn read_async(&self) -> InternalReadGuard<'_> {
+        loop {
+            // Created before trying the lock, so that a release in between
+            // isn't missed
+            let released = self.released.notified();
+            match self.lock.try_read() {
+                Ok(guard) => {
+                    return InternalReadGuard {
+                        guard: Some(guard),
+                        lock: self,
+                    }
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Poisoned(err)) => panic!("{err}"),
+            }
+            released.await;
+        }
+    }
+
+    #[cfg(feature = "async")]
+    async fn write_async(&self) -> InternalWriteGuard<'_> {
+        loop {
+            let released = self.released.notified();
+            match self.lock.try_write() {
+                Ok(guard) => {
+                    return InternalWriteGuard {
+                        guard: Some(guard),
+                        lock: self,
+                    }
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Poisoned(err)) => panic!("{err}"),
+            }
+            released.await;
+        }
+    }
+
+    // Wakes async transactions waiting on the lock. This is called after a
+    // guard is released, so that they can take it.
+    fn notify_released(&self) {
+        #[cfg(feature = "async")]
+        self.released.notify_waiters();
+    }
+}
+
+struct InternalReadGuard<'a> {
+    // Only `None` while being dropped
+    guard: Option<RwLockReadGuard<'a, InternalMemory>>,
+    lock: &'a InternalLock,
+}
+
+impl<'a> Deref for InternalReadGuard<'a> {
+    type Target = InternalMemory;
+
+    fn deref(&self) -> &InternalMemory {
+        self.guard.as_ref().unwrap()
+    }
+}
+
+impl<'a> Drop for InternalReadGuard<'a> {
+    fn drop(&mut self) {
+        self.guard.take();
+        self.lock.notify_released();
+    }
+}
+
+struct InternalWriteGuard<'a> {
+    // Only `None` while being dropped
+    guard: Option<RwLockWriteGuard<'a, InternalMemory>>,
+    lock: &'a InternalLock,
+}
+
+impl<'a> Deref for InternalWriteGuard<'a> {
+    type Target = InternalMemory;
+
+    fn deref(&self) -> &InternalMemory {
+        self.guard.as_ref().unwrap()
+    }
+}
+
+impl<'a> DerefMut for InternalWriteGuard<'a> {
+    fn deref_mut(&mut self) -> &mut InternalMemory {
+        self.guard.as_mut().unwrap()
+    }
+}
+
+impl<'a> Drop for InternalWriteGuard<'a> {
+    fn drop(&mut self) {
+        self.guard.take();
+        self.lock.notify_released();
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 enum IndexedPropertyMember {
     Vertex(Uuid),
     Edge(Edge),
 }
 
+// A change made by a write transaction, recorded so that it can be undone if
+// the transaction is rolled back.
+#[derive(Clone, Debug)]
+enum Undo {
+    VertexCreated(Uuid),
+    VertexDeleted(Uuid, Identifier),
+    EdgeCreated(Edge),
+    EdgeDeleted(Edge),
+    // Property and expiration changes record the previous value
+    VertexProperty((Uuid, Identifier), Option<Json>),
+    EdgeProperty((Edge, Identifier), Option<Json>),
+    EdgeExpiration(Edge, Option<SystemTime>),
+    VertexLabelAdded(Uuid, Identifier),
+    VertexLabelRemoved(Uuid, Identifier),
+    // Records the previous creation time, if any
+    VertexCreatedAt(Uuid, Option<SystemTime>),
+    IndexCreated(Identifier),
+    IndexDropped(Identifier),
+    // Records the previously registered type, if any
+    PropertySchema(Identifier, Option<JsonType>),
+    // Everything that was cleared
+    Cleared(Box<InternalMemory>),
+}
+
 // All of the data is actually stored in this struct, which is stored
 // internally to the datastore itself. This way, we can wrap a mutex around
 // the entire datastore, rather than on a per-data structure basis, as the
 // latter approach would risk deadlocking without extreme care.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 struct InternalMemory {
     vertices: BTreeMap<Uuid, Identifier>,
     edges: BTreeSet<Edge>,
@@ -32,11 +215,320 @@ struct InternalMemory {
     vertex_properties: BTreeMap<(Uuid, Identifier), Json>,
     edge_properties: BTreeMap<(Edge, Identifier), Json>,
     property_values: HashMap<Identifier, HashMap<Json, HashSet<IndexedPropertyMember>>>,
+    #[serde(default)]
+    edge_expirations: HashMap<Edge, SystemTime>,
+    #[serde(default)]
+    property_schemas: HashMap<Identifier, JsonType>,
+    // Each vertex's labels, and the same pairs keyed by label first, so
+    // that vertices can be looked up by label
+    #[serde(default)]
+    vertex_labels: BTreeSet<(Uuid, Identifier)>,
+    #[serde(default)]
+    label_vertices: BTreeSet<(Identifier, Uuid)>,
+    // When each vertex was created, and the same pairs keyed by time first,
+    // so that vertices can be looked up by creation time
+    #[serde(default)]
+    vertex_created_at: HashMap<Uuid, SystemTime>,
+    #[serde(default)]
+    created_vertices: BTreeSet<(SystemTime, Uuid)>,
+    // Derived from `vertices`, so it's rebuilt on load rather than persisted
+    #[serde(skip)]
+    vertex_type_counts: HashMap<Identifier, u64>,
+    // Only set while a write transaction is open
+    #[serde(skip)]
+    undo_log: Option<Vec<Undo>>,
+}
+
+impl InternalMemory {
+    fn record(&mut self, undo: Undo) {
+        if let Some(ref mut undo_log) = self.undo_log {
+            undo_log.push(undo);
+        }
+    }
+
+    // Undoes everything in the undo log. Only the set of indexed properties
+    // is tracked in the log, so the indexes themselves are rebuilt
+    // afterwards, as are the vertex type counts.
+    fn roll_back(&mut self) {
+        let undo_log = match self.undo_log.take() {
+            Some(undo_log) if !undo_log.is_empty() => undo_log,
+            _ => return,
+        };
+
+        for undo in undo_log.into_iter().rev() {
+            match undo {
+                Undo::VertexCreated(id) => {
+                    self.vertices.remove(&id);
+                }
+                Undo::VertexDeleted(id, t) => {
+                    self.vertices.insert(id, t);
+                }
+                Undo::EdgeCreated(edge) => {
+                    self.reversed_edges.remove(&edge.reversed());
+                    self.edges.remove(&edge);
+                }
+                Undo::EdgeDeleted(edge) => {
+                    self.reversed_edges.insert(edge.reversed());
+                    self.edges.insert(edge);
+                }
+                Undo::VertexProperty(key, Some(value)) => {
+                    self.vertex_properties.insert(key, value);
+                }
+                Undo::VertexProperty(key, None) => {
+                    self.vertex_properties.remove(&key);
+                }
+                Undo::EdgeProperty(key, Some(value)) => {
+                    self.edge_properties.insert(key, value);
+                }
+                Undo::EdgeProperty(key, None) => {
+                    self.edge_properties.remove(&key);
+                }
+                Undo::EdgeExpiration(edge, Some(expires_at)) => {
+                    self.edge_expirations.insert(edge, expires_at);
+                }
+                Undo::EdgeExpiration(edge, None) => {
+                    self.edge_expirations.remove(&edge);
+                }
+                Undo::VertexLabelAdded(id, label) => {
+                    self.vertex_labels.remove(&(id, label));
+                    self.label_vertices.remove(&(label, id));
+                }
+                Undo::VertexLabelRemoved(id, label) => {
+                    self.vertex_labels.insert((id, label));
+                    self.label_vertices.insert((label, id));
+                }
+                Undo::VertexCreatedAt(id, previous) => {
+                    if let Some(created_at) = self.vertex_created_at.remove(&id) {
+                        self.created_vertices.remove(&(created_at, id));
+                    }
+                    if let Some(created_at) = previous {
+                        self.vertex_created_at.insert(id, created_at);
+                        self.created_vertices.insert((created_at, id));
+                    }
+                }
+                Undo::IndexCreated(name) => {
+                    self.property_values.remove(&name);
+                }
+                Undo::IndexDropped(name) => {
+                    self.property_values.insert(name, HashMap::new());
+                }
+                Undo::PropertySchema(name, Some(expected)) => {
+                    self.property_schemas.insert(name, expected);
+                }
+                Undo::PropertySchema(name, None) => {
+                    self.property_schemas.remove(&name);
+                }
+                Undo::Cleared(cleared) => {
+                    *self = *cleared;
+                }
+            }
+        }
+
+        let index_names: Vec<Identifier> = self.property_values.keys().copied().collect();
+        for name in index_names {
+            let property_container = self.build_index(name);
+            self.property_values.insert(name, property_container);
+        }
+        self.recount_vertex_types();
+    }
+
+    fn build_index(&self, name: Identifier) -> HashMap<Json, HashSet<IndexedPropertyMember>> {
+        // For paths, the value is nested in the property at the root of the path
+        let (property_name, fields) = name.split_path();
+        let mut property_container: HashMap<Json, HashSet<IndexedPropertyMember>> = HashMap::new();
+        for id in self.vertices.keys() {
+            if let Some(value) = self.vertex_properties.get(&(*id, property_name)) {
+                if let Some(value) = util::json_at_path(value, &fields) {
+                    property_container
+                        .entry(value)
+                        .or_default()
+                        .insert(IndexedPropertyMember::Vertex(*id));
+                }
+            }
+        }
+        for edge in self.edges.iter() {
+            if let Some(value) = self.edge_properties.get(&(edge.clone(), property_name)) {
+                if let Some(value) = util::json_at_path(value, &fields) {
+                    property_container
+                        .entry(value)
+                        .or_default()
+                        .insert(IndexedPropertyMember::Edge(edge.clone()));
+                }
+            }
+        }
+        property_container
+    }
+
+    // Estimates the memory used from the number of entries. The contents of
+    // property values and indexes aren't counted, since that would mean
+    // walking all of them.
+    fn estimated_size(&self) -> u64 {
+        let size = self.vertices.len() * mem::size_of::<(Uuid, Identifier)>()
+            + (self.edges.len() + self.reversed_edges.len()) * mem::size_of::<Edge>()
+            + self.vertex_properties.len() * mem::size_of::<((Uuid, Identifier), Json)>()
+            + self.edge_properties.len() * mem::size_of::<((Edge, Identifier), Json)>()
+            + self.edge_expirations.len() * mem::size_of::<(Edge, SystemTime)>()
+            + self.vertex_labels.len() * 2 * mem::size_of::<(Uuid, Identifier)>()
+            + self.vertex_created_at.len() * 2 * mem::size_of::<(Uuid, SystemTime)>();
+        size as u64
+    }
+
+    fn recount_vertex_types(&mut self) {
+        self.vertex_type_counts.clear();
+        for t in self.vertices.values() {
+            *self.vertex_type_counts.entry(*t).or_insert(0) += 1;
+        }
+    }
+
+    fn uncount_vertex_type(&mut self, t: Identifier) {
+        if let Some(count) = self.vertex_type_counts.get_mut(&t) {
+            *count -= 1;
+            if *count == 0 {
+                self.vertex_type_counts.remove(&t);
+            }
+        }
+    }
+
+    fn remove_vertex_label(&mut self, id: Uuid, label: Identifier) {
+        if self.vertex_labels.remove(&(id, label)) {
+            self.label_vertices.remove(&(label, id));
+            self.record(Undo::VertexLabelRemoved(id, label));
+        }
+    }
+
+    fn is_live_edge(&self, edge: &Edge, now: SystemTime) -> bool {
+        match self.edge_expirations.get(edge) {
+            Some(expires_at) => *expires_at > now,
+            None => true,
+        }
+    }
+
+    // Returns the edges that existed, and so were actually deleted
+    fn delete_edges(&mut self, edges: Vec<Edge>) -> Vec<Edge> {
+        let mut deleted = Vec::new();
+        for edge in edges {
+            if self.edges.remove(&edge) {
+                self.record(Undo::EdgeDeleted(edge.clone()));
+                deleted.push(edge.clone());
+            }
+            self.reversed_edges.remove(&edge.reversed());
+            if let Some(expires_at) = self.edge_expirations.remove(&edge) {
+                self.record(Undo::EdgeExpiration(edge.clone(), Some(expires_at)));
+            }
+
+            let mut deletable_edge_properties: Vec<(Edge, Identifier)> = Vec::new();
+            for (property_key, _) in self.edge_properties.range((edge.clone(), Identifier::default())..) {
+                let (property_edge, _) = property_key;
+
+                if &edge != property_edge {
+                    break;
+                }
+
+                deletable_edge_properties.push(property_key.clone());
+            }
+            self.delete_edge_properties(deletable_edge_properties);
+        }
+        deleted
+    }
+
+    fn delete_edge_properties(&mut self, props: Vec<(Edge, Identifier)>) {
+        for prop in props {
+            if let Some(property_value) = self.edge_properties.remove(&prop) {
+                self.record(Undo::EdgeProperty(prop.clone(), Some(property_value.clone())));
+                let (property_edge, property_name) = prop;
+                for (index_name, index_value) in
+                    util::indexed_values(self.property_values.keys(), property_name, &property_value)
+                {
+                    let removed = self
+                        .property_values
+                        .get_mut(&index_name)
+                        .unwrap()
+                        .get_mut(&index_value)
+                        .unwrap()
+                        .remove(&IndexedPropertyMember::Edge(property_edge.clone()));
+                    debug_assert!(removed);
+                }
+            }
+        }
+    }
+
+    fn delete_expired_edges(&mut self) {
+        let now = SystemTime::now();
+        let expired_edges = self
+            .edge_expirations
+            .iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|(edge, _)| edge.clone())
+            .collect();
+        self.delete_edges(expired_edges);
+    }
+}
+
+// Read-only transactions only take a read lock, so that they can run
+// concurrently with each other.
+enum InternalMemoryGuard<'a> {
+    Read(InternalReadGuard<'a>),
+    Write(InternalWriteGuard<'a>),
+}
+
+impl<'a> Deref for InternalMemoryGuard<'a> {
+    type Target = InternalMemory;
+
+    fn deref(&self) -> &InternalMemory {
+        match self {
+            InternalMemoryGuard::Read(guard) => guard,
+            InternalMemoryGuard::Write(guard) => guard,
+        }
+    }
+}
+
+impl<'a> DerefMut for InternalMemoryGuard<'a> {
+    fn deref_mut(&mut self) -> &mut InternalMemory {
+        match self {
+            // Mutating methods check `MemoryTransaction::check_writable`
+            // before touching the guard, so this should never happen.
+            InternalMemoryGuard::Read(_) => panic!("attempted to mutate a read-only memory transaction"),
+            InternalMemoryGuard::Write(guard) => guard,
+        }
+    }
 }
 
 pub struct MemoryTransaction<'a> {
-    internal: MutexGuard<'a, InternalMemory>,
-    path: Option<PathBuf>,
+    internal: InternalMemoryGuard<'a>,
+    datastore: &'a MemoryDatastore,
+    // Change events to publish once the transaction is committed, or `None`
+    // if nobody was watching when it started
+    events: Option<Vec<ChangeEvent>>,
+}
+
+impl<'a> MemoryTransaction<'a> {
+    fn new(mut internal: InternalMemoryGuard<'a>, datastore: &'a MemoryDatastore) -> Self {
+        if let InternalMemoryGuard::Write(ref mut guard) = internal {
+            guard.undo_log = Some(Vec::new());
+        }
+        let events = match internal {
+            InternalMemoryGuard::Write(_) if datastore.changes.has_subscribers() => Some(Vec::new()),
+            _ => None,
+        };
+        Self {
+            internal,
+            datastore,
+            events,
+        }
+    }
+
+    fn emit(&mut self, event: ChangeEvent) {
+        if let Some(ref mut events) = self.events {
+            events.push(event);
+        }
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        match self.internal {
+            InternalMemoryGuard::Read(_) => Err(Error::ReadOnly),
+            InternalMemoryGuard::Write(_) => Ok(()),
+        }
+    }
 }
 
 impl<'a> Transaction<'a> for MemoryTransaction<'a> {
@@ -44,6 +536,46 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         self.internal.vertices.len() as u64
     }
 
+    fn vertex_count_by_type(&self, t: Identifier) -> Result<u64> {
+        Ok(self.internal.vertex_type_counts.get(&t).copied().unwrap_or(0))
+    }
+
+    fn vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        let names: BTreeSet<Identifier> = self.internal.vertex_properties.keys().map(|(_, name)| *name).collect();
+        Ok(names.into_iter().collect())
+    }
+
+    fn edge_property_names(&self) -> Result<Vec<Identifier>> {
+        let names: BTreeSet<Identifier> = self.internal.edge_properties.keys().map(|(_, name)| *name).collect();
+        Ok(names.into_iter().collect())
+    }
+
+    fn indexed_properties(&self) -> Result<Vec<Identifier>> {
+        let mut names: Vec<Identifier> = self.internal.property_values.keys().copied().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn property_schemas(&self) -> Result<Vec<(Identifier, JsonType)>> {
+        let mut schemas: Vec<(Identifier, JsonType)> = self
+            .internal
+            .property_schemas
+            .iter()
+            .map(|(name, expected)| (*name, *expected))
+            .collect();
+        schemas.sort_by_key(|(name, _)| *name);
+        Ok(schemas)
+    }
+
+    fn stats(&self) -> Result<DatastoreStats> {
+        Ok(DatastoreStats {
+            vertex_count: self.vertex_count(),
+            edge_count: self.edge_count(),
+            index_count: self.internal.property_values.len() as u64,
+            size_bytes: Some(self.internal.estimated_size()),
+        })
+    }
+
     fn all_vertices(&'a self) -> Result<DynIter<'a, Vertex>> {
         let iter = self
             .internal
@@ -62,6 +594,16 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         Ok(Box::new(iter))
     }
 
+    fn range_vertices_descending(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        let iter = self
+            .internal
+            .vertices
+            .range(..=offset)
+            .rev()
+            .map(|(id, t)| Ok(Vertex::with_id(*id, *t)));
+        Ok(Box::new(iter))
+    }
+
     fn specific_vertices(&'a self, ids: Vec<Uuid>) -> Result<DynIter<'a, Vertex>> {
         let iter = ids.into_iter().filter_map(move |id| {
             self.internal
@@ -72,6 +614,54 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         Ok(Box::new(iter))
     }
 
+    fn get_vertex(&'a self, id: Uuid) -> Result<Option<Vertex>> {
+        Ok(self.internal.vertices.get(&id).map(|t| Vertex::with_id(id, *t)))
+    }
+
+    fn contains_vertex(&'a self, id: Uuid) -> Result<bool> {
+        Ok(self.internal.vertices.contains_key(&id))
+    }
+
+    fn vertex_labels(&self, id: Uuid) -> Result<BTreeSet<Identifier>> {
+        let labels = self
+            .internal
+            .vertex_labels
+            .range((id, Identifier::default())..)
+            .take_while(|(label_id, _)| *label_id == id)
+            .map(|(_, label)| *label)
+            .collect();
+        Ok(labels)
+    }
+
+    fn vertex_ids_with_label(&'a self, label: Identifier) -> Result<DynIter<'a, Uuid>> {
+        let iter = self
+            .internal
+            .label_vertices
+            .range((label, Uuid::nil())..)
+            .take_while(move |(vertex_label, _)| *vertex_label == label)
+            .map(|(_, id)| Ok(*id));
+        Ok(Box::new(iter))
+    }
+
+    fn vertex_created_at(&self, id: Uuid) -> Result<Option<SystemTime>> {
+        Ok(self.internal.vertex_created_at.get(&id).copied())
+    }
+
+    fn vertex_ids_created_in_range(
+        &'a self,
+        low: Option<SystemTime>,
+        high: Option<SystemTime>,
+    ) -> Result<DynIter<'a, Uuid>> {
+        let low = (low.unwrap_or(SystemTime::UNIX_EPOCH), Uuid::nil());
+        let iter = self
+            .internal
+            .created_vertices
+            .range(low..)
+            .take_while(move |(created_at, _)| high.is_none_or(|high| *created_at < high))
+            .map(|(_, id)| Ok(*id));
+        Ok(Box::new(iter))
+    }
+
     fn vertex_ids_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Uuid>>> {
         if let Some(container) = self.internal.property_values.get(&name) {
             let mut vertex_ids = HashSet::<Uuid>::default();
@@ -106,39 +696,72 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
     }
 
     fn edge_count(&self) -> u64 {
-        self.internal.edges.len() as u64
+        let now = SystemTime::now();
+        let expired_count = self
+            .internal
+            .edge_expirations
+            .values()
+            .filter(|expires_at| **expires_at <= now)
+            .count();
+        (self.internal.edges.len() - expired_count) as u64
     }
 
     fn all_edges(&'a self) -> Result<DynIter<'a, Edge>> {
-        let iter = self.internal.edges.iter().map(|e| Ok(e.clone()));
+        let now = SystemTime::now();
+        let iter = self
+            .internal
+            .edges
+            .iter()
+            .filter(move |e| self.internal.is_live_edge(e, now))
+            .map(|e| Ok(e.clone()));
         Ok(Box::new(iter))
     }
 
     fn range_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>> {
-        let iter = self.internal.edges.range(offset..).map(|e| Ok(e.clone()));
+        let now = SystemTime::now();
+        let iter = self
+            .internal
+            .edges
+            .range(offset..)
+            .filter(move |e| self.internal.is_live_edge(e, now))
+            .map(|e| Ok(e.clone()));
         Ok(Box::new(iter))
     }
 
     fn range_reversed_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>> {
-        let iter = self.internal.reversed_edges.range(offset..).map(|e| Ok(e.clone()));
+        let now = SystemTime::now();
+        let iter = self
+            .internal
+            .reversed_edges
+            .range(offset..)
+            .filter(move |e| self.internal.is_live_edge(&e.reversed(), now))
+            .map(|e| Ok(e.clone()));
         Ok(Box::new(iter))
     }
 
     fn specific_edges(&'a self, edges: Vec<Edge>) -> Result<DynIter<'a, Edge>> {
+        let now = SystemTime::now();
         let iter = edges
             .into_iter()
-            .filter(move |edge| self.internal.edges.contains(edge))
+            .filter(move |edge| self.internal.edges.contains(edge) && self.internal.is_live_edge(edge, now))
             .map(Ok);
         Ok(Box::new(iter))
     }
 
+    fn contains_edge(&'a self, edge: &Edge) -> Result<bool> {
+        Ok(self.internal.edges.contains(edge) && self.internal.is_live_edge(edge, SystemTime::now()))
+    }
+
     fn edges_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Edge>>> {
         if let Some(container) = self.internal.property_values.get(&name) {
+            let now = SystemTime::now();
             let mut edges = HashSet::<Edge>::default();
             for sub_container in container.values() {
                 for member in sub_container {
                     if let IndexedPropertyMember::Edge(edge) = member {
-                        edges.insert(edge.clone());
+                        if self.internal.is_live_edge(edge, now) {
+                            edges.insert(edge.clone());
+                        }
                     }
                 }
             }
@@ -151,8 +774,13 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
     fn edges_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Edge>>> {
         if let Some(container) = self.internal.property_values.get(&name) {
             if let Some(sub_container) = container.get(value) {
+                let now = SystemTime::now();
                 let iter = Box::new(sub_container.iter().filter_map(move |member| match member {
-                    IndexedPropertyMember::Edge(edge) if self.internal.edges.contains(edge) => Some(edge),
+                    IndexedPropertyMember::Edge(edge)
+                        if self.internal.edges.contains(edge) && self.internal.is_live_edge(edge, now) =>
+                    {
+                        Some(edge)
+                    }
                     _ => None,
                 }));
                 Ok(Some(Box::new(iter.map(|e| Ok(e.clone())))))
@@ -203,9 +831,20 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         Ok(Box::new(edge_properties.into_iter().map(Ok)))
     }
 
-    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<()> {
+    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<u64> {
+        self.check_writable()?;
+        let mut count = 0;
         for vertex in vertices {
-            self.internal.vertices.remove(&vertex.id);
+            if let Some(t) = self.internal.vertices.remove(&vertex.id) {
+                self.internal.record(Undo::VertexDeleted(vertex.id, t));
+                self.internal.uncount_vertex_type(t);
+                self.emit(ChangeEvent::VertexDeleted(Vertex::with_id(vertex.id, t)));
+                count += 1;
+            }
+            if let Some(created_at) = self.internal.vertex_created_at.remove(&vertex.id) {
+                self.internal.created_vertices.remove(&(created_at, vertex.id));
+                self.internal.record(Undo::VertexCreatedAt(vertex.id, Some(created_at)));
+            }
 
             let mut deletable_vertex_properties: Vec<(Uuid, Identifier)> = Vec::new();
             for (property_key, _) in self
@@ -223,6 +862,17 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
             }
             self.delete_vertex_properties(deletable_vertex_properties)?;
 
+            let labels: Vec<Identifier> = self
+                .internal
+                .vertex_labels
+                .range((vertex.id, Identifier::default())..)
+                .take_while(|(id, _)| *id == vertex.id)
+                .map(|(_, label)| *label)
+                .collect();
+            for label in labels {
+                self.internal.remove_vertex_label(vertex.id, label);
+            }
+
             let mut deletable_edges: Vec<Edge> = Vec::new();
             for edge in self.internal.edges.iter() {
                 if edge.outbound_id == vertex.id || edge.inbound_id == vertex.id {
@@ -231,42 +881,38 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
             }
             self.delete_edges(deletable_edges)?;
         }
-        Ok(())
+        Ok(count)
     }
 
-    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<()> {
-        for edge in edges {
-            self.internal.edges.remove(&edge);
-            self.internal.reversed_edges.remove(&edge.reversed());
-
-            let mut deletable_edge_properties: Vec<(Edge, Identifier)> = Vec::new();
-            for (property_key, _) in self
-                .internal
-                .edge_properties
-                .range((edge.clone(), Identifier::default())..)
-            {
-                let (property_edge, _) = property_key;
-
-                if &edge != property_edge {
-                    break;
-                }
-
-                deletable_edge_properties.push(property_key.clone());
-            }
-            self.delete_edge_properties(deletable_edge_properties)?;
+    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<u64> {
+        self.check_writable()?;
+        let deleted = self.internal.delete_edges(edges);
+        let count = deleted.len() as u64;
+        for edge in deleted {
+            self.emit(ChangeEvent::EdgeDeleted(edge));
         }
-        Ok(())
+        Ok(count)
     }
 
     fn delete_vertex_properties(&mut self, props: Vec<(Uuid, Identifier)>) -> Result<()> {
+        self.check_writable()?;
         for prop in props {
             if let Some(property_value) = self.internal.vertex_properties.remove(&prop) {
+                self.internal
+                    .record(Undo::VertexProperty(prop, Some(property_value.clone())));
                 let (property_vertex_id, property_name) = prop;
-                if let Some(property_container) = self.internal.property_values.get_mut(&property_name) {
-                    debug_assert!(property_container
-                        .get_mut(&property_value)
+                for (index_name, index_value) in
+                    util::indexed_values(self.internal.property_values.keys(), property_name, &property_value)
+                {
+                    let removed = self
+                        .internal
+                        .property_values
+                        .get_mut(&index_name)
+                        .unwrap()
+                        .get_mut(&index_value)
                         .unwrap()
-                        .remove(&IndexedPropertyMember::Vertex(property_vertex_id)));
+                        .remove(&IndexedPropertyMember::Vertex(property_vertex_id));
+                    debug_assert!(removed);
                 }
             }
         }
@@ -274,22 +920,13 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
     }
 
     fn delete_edge_properties(&mut self, props: Vec<(Edge, Identifier)>) -> Result<()> {
-        for prop in props {
-            if let Some(property_value) = self.internal.edge_properties.remove(&prop) {
-                let (property_edge, property_name) = prop;
-                if let Some(property_container) = self.internal.property_values.get_mut(&property_name) {
-                    debug_assert!(property_container
-                        .get_mut(&property_value)
-                        .unwrap()
-                        .remove(&IndexedPropertyMember::Edge(property_edge)));
-                }
-            }
-        }
+        self.check_writable()?;
+        self.internal.delete_edge_properties(props);
         Ok(())
     }
 
     fn sync(&self) -> Result<()> {
-        if let Some(ref persist_path) = self.path {
+        if let Some(ref persist_path) = self.datastore.path {
             let temp_path = NamedTempFile::new().map_err(|err| Error::Datastore(Box::new(err)))?;
             {
                 let mut buf = BufWriter::new(temp_path.as_file());
@@ -302,7 +939,33 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         Ok(())
     }
 
+    fn commit(mut self) -> Result<()> {
+        // Changes are made in place under the write lock, so committing just
+        // means forgetting how to undo them
+        if let InternalMemoryGuard::Write(ref mut internal) = self.internal {
+            internal.undo_log = None;
+        }
+        if let Some(events) = self.events.take() {
+            if !events.is_empty() {
+                self.datastore.changes.publish(events);
+            }
+        }
+        Ok(())
+    }
+
+    fn rollback(self) -> Result<()> {
+        // Dropping an uncommitted transaction rolls it back
+        Ok(())
+    }
+
     fn create_vertex(&mut self, vertex: &Vertex) -> Result<bool> {
+        self.check_writable()?;
+        // The write lock is held, so the count can't change under us
+        if let Some(max_vertices) = self.datastore.limits.max_vertices {
+            if !self.internal.vertices.contains_key(&vertex.id) && self.internal.vertices.len() as u64 >= max_vertices {
+                return Err(Error::LimitExceeded);
+            }
+        }
         let mut inserted = false;
 
         self.internal.vertices.entry(vertex.id).or_insert_with(|| {
@@ -310,43 +973,74 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
             vertex.t
         });
 
+        if inserted {
+            let created_at = SystemTime::now();
+            self.internal.vertex_created_at.insert(vertex.id, created_at);
+            self.internal.created_vertices.insert((created_at, vertex.id));
+            self.internal.record(Undo::VertexCreated(vertex.id));
+            self.internal.record(Undo::VertexCreatedAt(vertex.id, None));
+            *self.internal.vertex_type_counts.entry(vertex.t).or_insert(0) += 1;
+            self.emit(ChangeEvent::VertexCreated(vertex.clone()));
+        }
         Ok(inserted)
     }
 
     fn create_edge(&mut self, edge: &Edge) -> Result<bool> {
+        self.check_writable()?;
         if !self.internal.vertices.contains_key(&edge.outbound_id)
             || !self.internal.vertices.contains_key(&edge.inbound_id)
         {
             return Ok(false);
         }
+        if let Some(max_edges) = self.datastore.limits.max_edges {
+            if !self.internal.edges.contains(edge) && self.internal.edges.len() as u64 >= max_edges {
+                return Err(Error::LimitExceeded);
+            }
+        }
 
-        self.internal.edges.insert(edge.clone());
+        if self.internal.edges.insert(edge.clone()) {
+            self.internal.record(Undo::EdgeCreated(edge.clone()));
+            self.emit(ChangeEvent::EdgeCreated(edge.clone()));
+        }
         self.internal.reversed_edges.insert(edge.reversed());
+        if let Some(expires_at) = self.internal.edge_expirations.remove(edge) {
+            self.internal
+                .record(Undo::EdgeExpiration(edge.clone(), Some(expires_at)));
+        }
         Ok(true)
     }
 
-    fn index_property(&mut self, name: Identifier) -> Result<()> {
-        let mut property_container: HashMap<Json, HashSet<IndexedPropertyMember>> = HashMap::new();
-        for id in self.internal.vertices.keys() {
-            if let Some(value) = self.internal.vertex_properties.get(&(*id, name)) {
-                property_container
-                    .entry(value.clone())
-                    .or_insert_with(HashSet::new)
-                    .insert(IndexedPropertyMember::Vertex(*id));
-            }
+    fn create_edge_with_ttl(&mut self, edge: &Edge, ttl: Duration) -> Result<bool> {
+        if !self.create_edge(edge)? {
+            return Ok(false);
         }
-        for edge in self.internal.edges.iter() {
-            if let Some(value) = self.internal.edge_properties.get(&(edge.clone(), name)) {
-                property_container
-                    .entry(value.clone())
-                    .or_insert_with(HashSet::new)
-                    .insert(IndexedPropertyMember::Edge(edge.clone()));
-            }
+        let expires_at = SystemTime::now() + ttl;
+        let previous = self.internal.edge_expirations.insert(edge.clone(), expires_at);
+        self.internal.record(Undo::EdgeExpiration(edge.clone(), previous));
+        self.datastore.start_sweeper();
+        Ok(true)
+    }
+
+    fn create_edge_if_absent(&mut self, edge: &Edge) -> Result<bool> {
+        // The write lock is held for the whole transaction, so the check and
+        // insert below can't interleave with another writer
+        self.check_writable()?;
+        if self.internal.edges.contains(edge) && self.internal.is_live_edge(edge, SystemTime::now()) {
+            return Ok(false);
+        }
+        self.create_edge(edge)
+    }
+
+    fn index_property(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
+        let property_container = self.internal.build_index(name);
+        if !self.internal.property_values.contains_key(&name) {
+            self.internal.record(Undo::IndexCreated(name));
         }
 
-        let existing_property_container = self.internal.property_values.entry(name).or_insert_with(HashMap::new);
+        let existing_property_container = self.internal.property_values.entry(name).or_default();
         for (value, members) in property_container.into_iter() {
-            let existing_members = existing_property_container.entry(value).or_insert_with(HashSet::new);
+            let existing_members = existing_property_container.entry(value).or_default();
             for member in members {
                 existing_members.insert(member);
             }
@@ -355,7 +1049,43 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         Ok(())
     }
 
+    fn drop_index(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
+        if self.internal.property_values.remove(&name).is_some() {
+            self.internal.record(Undo::IndexDropped(name));
+        }
+        Ok(())
+    }
+
+    fn register_property_schema(&mut self, name: Identifier, expected: JsonType) -> Result<()> {
+        self.check_writable()?;
+        let previous = self.internal.property_schemas.insert(name, expected);
+        self.internal.record(Undo::PropertySchema(name, previous));
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.check_writable()?;
+        let undo_log = self.internal.undo_log.take();
+        let cleared = mem::take(&mut *self.internal);
+        self.internal.undo_log = undo_log;
+
+        if self.events.is_some() {
+            for edge in cleared.edges.iter() {
+                self.emit(ChangeEvent::EdgeDeleted(edge.clone()));
+            }
+            for (id, t) in cleared.vertices.iter() {
+                self.emit(ChangeEvent::VertexDeleted(Vertex::with_id(*id, *t)));
+            }
+        }
+
+        self.internal.record(Undo::Cleared(Box::new(cleared)));
+        Ok(())
+    }
+
     fn set_vertex_properties(&mut self, vertex_ids: Vec<Uuid>, name: Identifier, value: &Json) -> Result<()> {
+        self.check_writable()?;
+        util::check_property_schema(&self.internal.property_schemas, name, value)?;
         let mut deletable_vertex_properties = Vec::new();
         for vertex_id in &vertex_ids {
             deletable_vertex_properties.push((*vertex_id, name));
@@ -363,22 +1093,58 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         self.delete_vertex_properties(deletable_vertex_properties)?;
 
         for vertex_id in &vertex_ids {
-            self.internal
+            let previous = self
+                .internal
                 .vertex_properties
                 .insert((*vertex_id, name), value.clone());
+            self.internal.record(Undo::VertexProperty((*vertex_id, name), previous));
+            if let Some(t) = self.internal.vertices.get(vertex_id).copied() {
+                self.emit(ChangeEvent::VertexPropertySet(
+                    Vertex::with_id(*vertex_id, t),
+                    name,
+                    value.clone(),
+                ));
+            }
+        }
+
+        for (index_name, index_value) in util::indexed_values(self.internal.property_values.keys(), name, value) {
+            let property_container = self
+                .internal
+                .property_values
+                .get_mut(&index_name)
+                .unwrap()
+                .entry(index_value)
+                .or_default();
+            for vertex_id in &vertex_ids {
+                property_container.insert(IndexedPropertyMember::Vertex(*vertex_id));
+            }
         }
 
-        if let Some(property_container) = self.internal.property_values.get_mut(&name) {
-            let property_container = property_container.entry(value.clone()).or_insert_with(HashSet::new);
-            for vertex_id in vertex_ids.into_iter() {
-                property_container.insert(IndexedPropertyMember::Vertex(vertex_id));
+        Ok(())
+    }
+
+    fn add_vertex_labels(&mut self, vertices: Vec<Uuid>, label: Identifier) -> Result<()> {
+        self.check_writable()?;
+        for id in vertices {
+            if self.internal.vertices.contains_key(&id) && self.internal.vertex_labels.insert((id, label)) {
+                self.internal.label_vertices.insert((label, id));
+                self.internal.record(Undo::VertexLabelAdded(id, label));
             }
         }
+        Ok(())
+    }
 
+    fn remove_vertex_labels(&mut self, vertices: Vec<Uuid>, label: Identifier) -> Result<()> {
+        self.check_writable()?;
+        for id in vertices {
+            self.internal.remove_vertex_label(id, label);
+        }
         Ok(())
     }
 
     fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()> {
+        self.check_writable()?;
+        util::check_property_schema(&self.internal.property_schemas, name, value)?;
         let mut deletable_edge_properties = Vec::new();
         for edge in &edges {
             deletable_edge_properties.push((edge.clone(), name));
@@ -386,15 +1152,26 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
         self.delete_edge_properties(deletable_edge_properties)?;
 
         for edge in &edges {
-            self.internal
+            let previous = self
+                .internal
                 .edge_properties
                 .insert((edge.clone(), name), value.clone());
+            self.internal.record(Undo::EdgeProperty((edge.clone(), name), previous));
+            if self.internal.edges.contains(edge) {
+                self.emit(ChangeEvent::EdgePropertySet(edge.clone(), name, value.clone()));
+            }
         }
 
-        if let Some(property_container) = self.internal.property_values.get_mut(&name) {
-            let property_container = property_container.entry(value.clone()).or_insert_with(HashSet::new);
-            for edge in edges.into_iter() {
-                property_container.insert(IndexedPropertyMember::Edge(edge));
+        for (index_name, index_value) in util::indexed_values(self.internal.property_values.keys(), name, value) {
+            let property_container = self
+                .internal
+                .property_values
+                .get_mut(&index_name)
+                .unwrap()
+                .entry(index_value)
+                .or_default();
+            for edge in &edges {
+                property_container.insert(IndexedPropertyMember::Edge(edge.clone()));
             }
         }
 
@@ -402,20 +1179,217 @@ impl<'a> Transaction<'a> for MemoryTransaction<'a> {
     }
 }
 
+// The contents of a snapshot written by `MemoryDatastore::save_to_path`.
+// Snapshots are encoded with bincode, which can't deserialize JSON values
+// since it doesn't encode their types, so property values are stored as JSON
+// strings. Indexes and the other derived data are rebuilt on load.
+#[derive(Serialize, Deserialize)]
+struct Snapshot<'a> {
+    vertices: Cow<'a, BTreeMap<Uuid, Identifier>>,
+    edges: Cow<'a, BTreeSet<Edge>>,
+    vertex_properties: Vec<((Uuid, Identifier), String)>,
+    edge_properties: Vec<((Edge, Identifier), String)>,
+    indexed_properties: Vec<Identifier>,
+    edge_expirations: Cow<'a, HashMap<Edge, SystemTime>>,
+    property_schemas: Cow<'a, HashMap<Identifier, JsonType>>,
+    vertex_labels: Cow<'a, BTreeSet<(Uuid, Identifier)>>,
+    vertex_created_at: Cow<'a, HashMap<Uuid, SystemTime>>,
+}
+
+impl<'a> Snapshot<'a> {
+    fn new(internal: &'a InternalMemory) -> Self {
+        Self {
+            vertices: Cow::Borrowed(&internal.vertices),
+            edges: Cow::Borrowed(&internal.edges),
+            vertex_properties: internal
+                .vertex_properties
+                .iter()
+                .map(|(key, value)| (*key, value.0.to_string()))
+                .collect(),
+            edge_properties: internal
+                .edge_properties
+                .iter()
+                .map(|(key, value)| (key.clone(), value.0.to_string()))
+                .collect(),
+            indexed_properties: internal.property_values.keys().copied().collect(),
+            edge_expirations: Cow::Borrowed(&internal.edge_expirations),
+            property_schemas: Cow::Borrowed(&internal.property_schemas),
+            vertex_labels: Cow::Borrowed(&internal.vertex_labels),
+            vertex_created_at: Cow::Borrowed(&internal.vertex_created_at),
+        }
+    }
+
+    fn into_internal(self) -> Result<InternalMemory> {
+        let mut internal = InternalMemory {
+            reversed_edges: self.edges.iter().map(Edge::reversed).collect(),
+            vertices: self.vertices.into_owned(),
+            edges: self.edges.into_owned(),
+            vertex_properties: self
+                .vertex_properties
+                .into_iter()
+                .map(|(key, value)| Ok((key, value.parse()?)))
+                .collect::<Result<_>>()?,
+            edge_properties: self
+                .edge_properties
+                .into_iter()
+                .map(|(key, value)| Ok((key, value.parse()?)))
+                .collect::<Result<_>>()?,
+            edge_expirations: self.edge_expirations.into_owned(),
+            property_schemas: self.property_schemas.into_owned(),
+            label_vertices: self.vertex_labels.iter().map(|(id, label)| (*label, *id)).collect(),
+            vertex_labels: self.vertex_labels.into_owned(),
+            created_vertices: self
+                .vertex_created_at
+                .iter()
+                .map(|(id, created_at)| (*created_at, *id))
+                .collect(),
+            vertex_created_at: self.vertex_created_at.into_owned(),
+            ..InternalMemory::default()
+        };
+        for name in self.indexed_properties {
+            let property_container = internal.build_index(name);
+            internal.property_values.insert(name, property_container);
+        }
+        Ok(internal)
+    }
+}
+
+impl<'a> Drop for MemoryTransaction<'a> {
+    fn drop(&mut self) {
+        // Committing clears the undo log, so this only undoes the changes of
+        // transactions that were rolled back or dropped. Their change events
+        // are discarded along with them.
+        if let InternalMemoryGuard::Write(ref mut internal) = self.internal {
+            internal.roll_back();
+        }
+    }
+}
+
+/// Limits on the size of an in-memory datastore, so that runaway inserts
+/// fail fast with `Error::LimitExceeded` rather than exhausting memory.
+/// Deleting vertices or edges frees capacity back up.
+///
+/// A bulk insert that goes over a limit fails as a whole. It stops at the
+/// first item over the limit, and since `Database::bulk_insert` returns
+/// before committing, the items inserted before it are rolled back too.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryLimits {
+    /// The maximum number of vertices, or `None` for no limit.
+    pub max_vertices: Option<u64>,
+    /// The maximum number of edges, or `None` for no limit.
+    pub max_edges: Option<u64>,
+}
+
 /// An in-memory datastore.
+///
+/// Write transactions hold an exclusive lock for as long as they're open.
+/// Changes made through one are undone if it's rolled back, or dropped
+/// without being committed.
 #[derive(Debug, Clone)]
 pub struct MemoryDatastore {
-    internal: Arc<Mutex<InternalMemory>>,
+    internal: Arc<InternalLock>,
     path: Option<PathBuf>,
+    sweeper: Arc<Once>,
+    limits: MemoryLimits,
+    changes: Arc<ChangeBroadcaster>,
+    vertex_id_generator: Arc<dyn VertexIdGenerator>,
 }
 
 impl MemoryDatastore {
+    fn new(mut internal: InternalMemory, path: Option<PathBuf>) -> MemoryDatastore {
+        internal.recount_vertex_types();
+        MemoryDatastore {
+            internal: Arc::new(InternalLock::new(internal)),
+            path,
+            sweeper: Arc::new(Once::new()),
+            limits: MemoryLimits::default(),
+            changes: Arc::new(ChangeBroadcaster::default()),
+            vertex_id_generator: Arc::new(UuidV1Generator),
+        }
+    }
+
+    /// Sets how the IDs of vertices created from just a type are generated.
+    /// By default, they're UUID v1s.
+    ///
+    /// # Arguments
+    /// * `generator`: The vertex ID generator.
+    pub fn set_vertex_id_generator<G: VertexIdGenerator + 'static>(&mut self, generator: G) {
+        self.vertex_id_generator = Arc::new(generator);
+    }
+
+    /// Forks the datastore, returning an independent copy of it - e.g. to run
+    /// a scenario in a test, and then throw it away. The copy is taken under
+    /// the datastore's lock, so it's consistent even if there are concurrent
+    /// writers. Changes to either datastore aren't visible in the other, nor
+    /// to the other's watchers.
+    ///
+    /// The fork keeps the original's limits, but isn't tied to its persisted
+    /// image, so calls to sync won't write to it. The vertex ID generator is
+    /// shared, so a seeded generator's sequence is split between the two.
+    pub fn fork(&self) -> MemoryDatastore {
+        let internal = self.internal.read().clone();
+        let has_expirations = !internal.edge_expirations.is_empty();
+        let datastore = MemoryDatastore {
+            internal: Arc::new(InternalLock::new(internal)),
+            path: None,
+            sweeper: Arc::new(Once::new()),
+            limits: self.limits,
+            changes: Arc::new(ChangeBroadcaster::default()),
+            vertex_id_generator: self.vertex_id_generator.clone(),
+        };
+        if has_expirations {
+            datastore.start_sweeper();
+        }
+        datastore
+    }
+
+    // Starts a background thread that periodically reaps expired edges. The
+    // thread exits once the datastore is dropped.
+    fn start_sweeper(&self) {
+        self.sweeper.call_once(|| {
+            let internal = Arc::downgrade(&self.internal);
+            thread::spawn(move || loop {
+                thread::sleep(SWEEP_INTERVAL);
+                match internal.upgrade() {
+                    Some(internal) => internal.write().delete_expired_edges(),
+                    None => return,
+                }
+            });
+        });
+    }
+
     /// Creates a new in-memory database with no persistence.
     pub fn new_db() -> Database<MemoryDatastore> {
-        Database::new(MemoryDatastore {
-            internal: Arc::new(Mutex::new(InternalMemory::default())),
-            path: None,
-        })
+        Database::new(MemoryDatastore::new(InternalMemory::default(), None))
+    }
+
+    /// Creates a new in-memory database with no persistence, and limits on
+    /// its size.
+    ///
+    /// # Arguments
+    /// * `limits`: The size limits.
+    pub fn new_db_with_limits(limits: MemoryLimits) -> Database<MemoryDatastore> {
+        let mut datastore = MemoryDatastore::new(InternalMemory::default(), None);
+        datastore.limits = limits;
+        Database::new(datastore)
+    }
+
+    /// Creates a new in-memory database with no persistence, whose vertex IDs
+    /// are generated from a seed. This makes tests reproducible - see
+    /// `SeededIdGenerator`.
+    ///
+    /// # Arguments
+    /// * `seed`: The seed for vertex IDs.
+    pub fn new_seeded_db(seed: u64) -> Database<MemoryDatastore> {
+        let mut datastore = MemoryDatastore::new(InternalMemory::default(), None);
+        datastore.set_vertex_id_generator(SeededIdGenerator::new(seed));
+        Database::new(datastore)
+    }
+
+    /// Creates a new in-memory async database with no persistence.
+    #[cfg(feature = "async")]
+    pub fn new_async_db() -> AsyncDatabase<MemoryDatastore> {
+        AsyncDatabase::new(MemoryDatastore::new(InternalMemory::default(), None))
     }
 
     /// Reads a persisted image from disk. Calls to sync will overwrite the
@@ -428,10 +1402,7 @@ impl MemoryDatastore {
         let f = File::open(&path).map_err(RmpDecodeError::InvalidDataRead)?;
         let buf = BufReader::new(f);
         let internal: InternalMemory = rmp_serde::from_read(buf)?;
-        Ok(Database::new(MemoryDatastore {
-            internal: Arc::new(Mutex::new(internal)),
-            path: Some(path),
-        }))
+        Ok(Database::new(MemoryDatastore::new(internal, Some(path))))
     }
 
     /// Creates a new datastore. Calls to sync will overwrite the file at the
@@ -441,19 +1412,101 @@ impl MemoryDatastore {
     /// # Arguments
     /// * `path`: The path to the persisted image.
     pub fn create_msgpack_db<P: Into<PathBuf>>(path: P) -> Database<MemoryDatastore> {
-        Database::new(MemoryDatastore {
-            internal: Arc::new(Mutex::new(InternalMemory::default())),
-            path: Some(path.into()),
-        })
+        Database::new(MemoryDatastore::new(InternalMemory::default(), Some(path.into())))
+    }
+
+    /// Saves a snapshot of the entire datastore, including property indexes,
+    /// to a file. The snapshot is taken under the datastore's lock, so it's
+    /// consistent even if there are concurrent writers.
+    ///
+    /// # Arguments
+    /// * `path`: The path to save the snapshot to. Any existing file is
+    ///   replaced atomically.
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let temp_path = NamedTempFile::new_in(dir).map_err(|err| Error::Datastore(Box::new(err)))?;
+        {
+            let internal = self.internal.read();
+            let mut buf = BufWriter::new(temp_path.as_file());
+            buf.write_all(SNAPSHOT_MAGIC)
+                .and_then(|_| buf.write_u32::<BigEndian>(SNAPSHOT_VERSION))
+                .map_err(|err| Error::Datastore(Box::new(err)))?;
+            bincode::serialize_into(&mut buf, &Snapshot::new(&internal))?;
+            buf.flush().map_err(|err| Error::Datastore(Box::new(err)))?;
+        }
+        temp_path.persist(path).map_err(|err| Error::Datastore(Box::new(err)))?;
+        Ok(())
+    }
+
+    /// Loads a datastore from a snapshot written by `save_to_path`. The
+    /// returned datastore isn't tied to the file, so calls to sync won't
+    /// write to it.
+    ///
+    /// # Arguments
+    /// * `path`: The path to the snapshot.
+    ///
+    /// # Errors
+    /// Returns an error if the file isn't a snapshot, or was written by an
+    /// incompatible version of IndraDB.
+    pub fn load_from_path(path: &Path) -> Result<MemoryDatastore> {
+        let f = File::open(path).map_err(|err| Error::Datastore(Box::new(err)))?;
+        let mut buf = BufReader::new(f);
+
+        let mut magic = [0; SNAPSHOT_MAGIC.len()];
+        if buf.read_exact(&mut magic).is_err() || &magic != SNAPSHOT_MAGIC {
+            return Err(invalid_snapshot(format!(
+                "{} is not a memory datastore snapshot",
+                path.display()
+            )));
+        }
+        let version = buf
+            .read_u32::<BigEndian>()
+            .map_err(|err| Error::Datastore(Box::new(err)))?;
+        if version != SNAPSHOT_VERSION {
+            return Err(invalid_snapshot(format!(
+                "unsupported memory datastore snapshot version {version} (expected {SNAPSHOT_VERSION})"
+            )));
+        }
+
+        let snapshot: Snapshot = bincode::deserialize_from(buf)?;
+        Ok(MemoryDatastore::new(snapshot.into_internal()?, None))
     }
 }
 
 impl Datastore for MemoryDatastore {
     type Transaction<'a> = MemoryTransaction<'a>;
     fn transaction(&'_ self) -> Self::Transaction<'_> {
-        MemoryTransaction {
-            internal: self.internal.lock().unwrap(),
-            path: self.path.clone(),
-        }
+        MemoryTransaction::new(InternalMemoryGuard::Write(self.internal.write()), self)
+    }
+
+    fn readonly_transaction(&'_ self) -> Self::Transaction<'_> {
+        MemoryTransaction::new(InternalMemoryGuard::Read(self.internal.read()), self)
+    }
+
+    fn watch(&self, filter: ChangeFilter) -> Result<Watcher> {
+        Ok(self.changes.subscribe(filter))
+    }
+
+    fn generate_vertex_id(&self, t: Identifier) -> Uuid {
+        self.vertex_id_generator.generate(t)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncDatastore for MemoryDatastore {
+    type Transaction<'a> = MemoryTransaction<'a>;
+    async fn transaction(&self) -> MemoryTransaction<'_> {
+        MemoryTransaction::new(InternalMemoryGuard::Write(self.internal.write_async().await), self)
+    }
+
+    async fn readonly_transaction(&self) -> MemoryTransaction<'_> {
+        MemoryTransaction::new(InternalMemoryGuard::Read(self.internal.read_async().await), self)
+    }
+
+    fn generate_vertex_id(&self, t: Identifier) -> Uuid {
+        self.vertex_id_generator.generate(t)
     }
 }
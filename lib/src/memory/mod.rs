@@ -5,7 +5,7 @@
 
 mod datastore;
 
-pub use datastore::MemoryDatastore;
+pub use datastore::{MemoryDatastore, MemoryLimits};
 
 #[cfg(feature = "bench-suite")]
 full_bench_impl!(MemoryDatastore::new_db());
@@ -13,14 +13,76 @@ full_bench_impl!(MemoryDatastore::new_db());
 #[cfg(feature = "test-suite")]
 #[cfg(test)]
 mod tests {
-    use super::MemoryDatastore;
+    use super::{MemoryDatastore, MemoryLimits};
     use crate::util::{extract_count, extract_vertices};
-    use crate::{ijson, AllVertexQuery, CountQueryExt, Database, Identifier, SpecificVertexQuery};
+    use crate::{
+        ijson, AllEdgeQuery, AllVertexQuery, BulkInsertItem, ChangeEvent, ChangeFilter, CountQueryExt, Database,
+        Datastore, Edge, Error, Identifier, SpecificEdgeQuery, SpecificVertexQuery, Transaction, Vertex,
+        VertexIdGenerator, VertexWithPropertyValueQuery, DEFAULT_WATCH_CAPACITY,
+    };
+    use std::fs;
+    use std::time::Duration;
 
     use tempfile::NamedTempFile;
     use uuid::Uuid;
 
-    full_test_impl!(MemoryDatastore::new_db());
+    // Seeds vertex IDs if `INDRADB_TEST_SEED` is set
+    fn new_test_db() -> Database<MemoryDatastore> {
+        match crate::tests::test_seed() {
+            Some(seed) => MemoryDatastore::new_seeded_db(seed),
+            None => MemoryDatastore::new_db(),
+        }
+    }
+
+    full_test_impl!(new_test_db());
+    define_test!(should_not_get_expired_edges, new_test_db());
+    define_test!(should_not_create_an_invalid_edge_with_ttl, new_test_db());
+    define_test!(should_index_property_path, new_test_db());
+    define_test!(should_validate_property_schemas, new_test_db());
+    define_test!(should_add_and_remove_vertex_labels, new_test_db());
+    define_test!(should_get_vertices_by_creation_time, new_test_db());
+
+    // Hands out sequential IDs, starting from 1.
+    #[derive(Debug, Default)]
+    struct SequentialIdGenerator(std::sync::atomic::AtomicU64);
+
+    impl VertexIdGenerator for SequentialIdGenerator {
+        fn generate(&self, _t: Identifier) -> Uuid {
+            Uuid::from_u128(self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed) as u128 + 1)
+        }
+    }
+
+    #[test]
+    fn should_generate_seeded_vertex_ids() {
+        let t = Identifier::new("test_vertex_type").unwrap();
+        let ids = |db: Database<MemoryDatastore>| {
+            (0..3)
+                .map(|_| db.create_vertex_from_type(t).unwrap())
+                .collect::<Vec<Uuid>>()
+        };
+        assert_eq!(
+            ids(MemoryDatastore::new_seeded_db(7)),
+            ids(MemoryDatastore::new_seeded_db(7))
+        );
+    }
+
+    #[test]
+    fn should_generate_vertex_ids() {
+        let mut db = MemoryDatastore::new_db();
+        db.datastore.set_vertex_id_generator(SequentialIdGenerator::default());
+        let t = Identifier::new("test_vertex_type").unwrap();
+        assert_eq!(db.create_vertex_from_type(t).unwrap(), Uuid::from_u128(1));
+        assert_eq!(
+            db.create_vertex_with_properties(t, vec![(Identifier::default(), ijson!(true))])
+                .unwrap(),
+            Uuid::from_u128(2)
+        );
+
+        // Generated IDs that are already taken are rejected
+        db.datastore.set_vertex_id_generator(SequentialIdGenerator::default());
+        assert!(matches!(db.create_vertex_from_type(t), Err(Error::UuidTaken)));
+        assert_eq!(db.get_vertex_count_by_type(t).unwrap(), 2);
+    }
 
     fn create_vertex_with_property(db: &Database<MemoryDatastore>) -> Uuid {
         let id = db.create_vertex_from_type(Identifier::default()).unwrap();
@@ -46,4 +108,380 @@ mod tests {
         let db = MemoryDatastore::read_msgpack_db(path.path()).unwrap();
         expect_vertex(&db, id);
     }
+
+    #[test]
+    fn should_save_and_load_snapshot() {
+        let db = MemoryDatastore::new_db();
+        let id = create_vertex_with_property(&db);
+        db.index_property(Identifier::default()).unwrap();
+        let path = NamedTempFile::new().unwrap();
+        db.datastore.save_to_path(path.path()).unwrap();
+
+        let db = Database::new(MemoryDatastore::load_from_path(path.path()).unwrap());
+        expect_vertex(&db, id);
+        // Indexes should be restored too
+        let q = VertexWithPropertyValueQuery::new(Identifier::default(), ijson!(true));
+        let vertices = extract_vertices(db.get(q).unwrap()).unwrap();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].id, id);
+    }
+
+    #[test]
+    fn should_not_load_invalid_snapshot() {
+        let path = NamedTempFile::new().unwrap();
+        let db = MemoryDatastore::create_msgpack_db(path.path());
+        create_vertex_with_property(&db);
+        db.sync().unwrap();
+        // A msgpack image isn't a snapshot
+        assert!(matches!(
+            MemoryDatastore::load_from_path(path.path()),
+            Err(Error::Datastore(_))
+        ));
+    }
+
+    #[test]
+    fn should_not_load_incompatible_snapshot() {
+        let db = MemoryDatastore::new_db();
+        create_vertex_with_property(&db);
+        let path = NamedTempFile::new().unwrap();
+        db.datastore.save_to_path(path.path()).unwrap();
+
+        // Bump the format version
+        let mut contents = fs::read(path.path()).unwrap();
+        contents[11] += 1;
+        fs::write(path.path(), contents).unwrap();
+
+        match MemoryDatastore::load_from_path(path.path()) {
+            Err(err) => assert!(err
+                .to_string()
+                .contains("unsupported memory datastore snapshot version 2")),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn should_enforce_limits() {
+        let db = MemoryDatastore::new_db_with_limits(MemoryLimits {
+            max_vertices: Some(2),
+            max_edges: Some(1),
+        });
+        let t = Identifier::default();
+        let first = Vertex::new(t);
+        let second = Vertex::new(t);
+        assert!(db.create_vertex(&first).unwrap());
+        assert!(db.create_vertex(&second).unwrap());
+        assert!(matches!(db.create_vertex_from_type(t), Err(Error::LimitExceeded)));
+        // Existing vertices don't take up more capacity
+        assert!(!db.create_vertex(&first).unwrap());
+        assert!(matches!(
+            db.bulk_insert(vec![BulkInsertItem::Vertex(Vertex::new(t))]),
+            Err(Error::LimitExceeded)
+        ));
+
+        let edge = Edge::new(first.id, t, second.id);
+        assert!(db.create_edge(&edge).unwrap());
+        assert!(db.create_edge(&edge).unwrap());
+        assert!(matches!(
+            db.create_edge(&Edge::new(second.id, t, first.id)),
+            Err(Error::LimitExceeded)
+        ));
+
+        // Deletes should free capacity back up
+        db.delete(SpecificEdgeQuery::single(edge)).unwrap();
+        assert!(db.create_edge(&Edge::new(second.id, t, first.id)).unwrap());
+        db.delete(SpecificVertexQuery::single(first.id)).unwrap();
+        db.create_vertex_from_type(t).unwrap();
+    }
+
+    #[test]
+    fn should_watch_changes() {
+        let db = MemoryDatastore::new_db();
+        let vertex_t = Identifier::new("vertex_type").unwrap();
+        let edge_t = Identifier::new("edge_type").unwrap();
+        let other_t = Identifier::new("other_type").unwrap();
+        let watcher = db.watch(ChangeFilter::default()).unwrap();
+        let filtered_watcher = db
+            .watch(ChangeFilter {
+                vertex_types: vec![vertex_t],
+                edge_types: vec![other_t],
+            })
+            .unwrap();
+
+        let outbound = Vertex::new(vertex_t);
+        let inbound = Vertex::new(other_t);
+        let edge = Edge::new(outbound.id, edge_t, inbound.id);
+        let name = Identifier::new("name").unwrap();
+        db.create_vertex(&outbound).unwrap();
+        db.create_vertex(&inbound).unwrap();
+        // Recreating existing items isn't a change
+        db.create_vertex(&outbound).unwrap();
+        db.create_edge(&edge).unwrap();
+        db.create_edge(&edge).unwrap();
+        db.set_properties(SpecificVertexQuery::single(outbound.id), name, &ijson!(1))
+            .unwrap();
+        db.set_properties(SpecificEdgeQuery::single(edge.clone()), name, &ijson!(2))
+            .unwrap();
+        db.delete(SpecificVertexQuery::single(outbound.id)).unwrap();
+
+        let events: Vec<ChangeEvent> = std::iter::from_fn(|| watcher.try_recv()).collect();
+        assert_eq!(
+            events,
+            vec![
+                ChangeEvent::VertexCreated(outbound.clone()),
+                ChangeEvent::VertexCreated(inbound),
+                ChangeEvent::EdgeCreated(edge.clone()),
+                ChangeEvent::VertexPropertySet(outbound.clone(), name, ijson!(1)),
+                ChangeEvent::EdgePropertySet(edge.clone(), name, ijson!(2)),
+                ChangeEvent::VertexDeleted(outbound.clone()),
+                ChangeEvent::EdgeDeleted(edge),
+            ]
+        );
+
+        let events: Vec<ChangeEvent> = std::iter::from_fn(|| filtered_watcher.try_recv()).collect();
+        assert_eq!(
+            events,
+            vec![
+                ChangeEvent::VertexCreated(outbound.clone()),
+                ChangeEvent::VertexPropertySet(outbound.clone(), name, ijson!(1)),
+                ChangeEvent::VertexDeleted(outbound),
+            ]
+        );
+        assert_eq!(watcher.dropped_count(), 0);
+    }
+
+    #[test]
+    fn should_drop_changes_for_slow_watchers() {
+        let db = MemoryDatastore::new_db();
+        let watcher = db.watch(ChangeFilter::default()).unwrap();
+        for _ in 0..(DEFAULT_WATCH_CAPACITY + 10) {
+            db.create_vertex_from_type(Identifier::default()).unwrap();
+        }
+        assert_eq!(watcher.dropped_count(), 10);
+        assert_eq!(
+            std::iter::from_fn(|| watcher.try_recv()).count(),
+            DEFAULT_WATCH_CAPACITY
+        );
+
+        // Once caught up, the watcher gets events again
+        db.create_vertex_from_type(Identifier::default()).unwrap();
+        assert!(watcher.try_recv().is_some());
+    }
+
+    #[test]
+    fn should_commit_transaction() {
+        let db = MemoryDatastore::new_db();
+        let watcher = db.watch(ChangeFilter::default()).unwrap();
+        let outbound = Vertex::new(Identifier::default());
+        let inbound = Vertex::new(Identifier::default());
+        let edge = Edge::new(outbound.id, Identifier::default(), inbound.id);
+
+        let mut txn = db.datastore.transaction();
+        assert!(txn.create_vertex(&outbound).unwrap());
+        assert!(txn.create_vertex(&inbound).unwrap());
+        assert!(txn.create_edge(&edge).unwrap());
+        // Events aren't published until the transaction is committed
+        assert!(watcher.try_recv().is_none());
+        txn.commit().unwrap();
+
+        assert_eq!(extract_count(db.get(AllVertexQuery.count().unwrap()).unwrap()), Some(2));
+        assert_eq!(extract_count(db.get(AllEdgeQuery.count().unwrap()).unwrap()), Some(1));
+        assert_eq!(std::iter::from_fn(|| watcher.try_recv()).count(), 3);
+    }
+
+    #[test]
+    fn should_roll_back_transaction() {
+        let db = MemoryDatastore::new_db();
+        let name = Identifier::new("name").unwrap();
+        db.index_property(name).unwrap();
+        let id = create_vertex_with_property(&db);
+        db.set_properties(SpecificVertexQuery::single(id), name, &ijson!("alice"))
+            .unwrap();
+        let watcher = db.watch(ChangeFilter::default()).unwrap();
+
+        let mut txn = db.datastore.transaction();
+        let other = Vertex::new(Identifier::default());
+        let edge = Edge::new(id, Identifier::default(), other.id);
+        assert!(txn.create_vertex(&other).unwrap());
+        assert!(txn.create_edge_with_ttl(&edge, Duration::from_secs(60)).unwrap());
+        txn.set_vertex_properties(vec![id, other.id], name, &ijson!("bob"))
+            .unwrap();
+        txn.delete_vertices(vec![Vertex::with_id(id, Identifier::default())])
+            .unwrap();
+        txn.drop_index(name).unwrap();
+        txn.rollback().unwrap();
+
+        expect_vertex(&db, id);
+        assert_eq!(db.get_vertex_count_by_type(Identifier::default()).unwrap(), 1);
+        assert_eq!(extract_count(db.get(AllEdgeQuery.count().unwrap()).unwrap()), Some(0));
+        assert_eq!(db.get_indexed_properties().unwrap(), vec![name]);
+        let vertices = extract_vertices(
+            db.get(VertexWithPropertyValueQuery::new(name, ijson!("alice")))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vertices, vec![Vertex::with_id(id, Identifier::default())]);
+        let vertices =
+            extract_vertices(db.get(VertexWithPropertyValueQuery::new(name, ijson!("bob"))).unwrap()).unwrap();
+        assert_eq!(vertices, vec![]);
+
+        // Dropping an uncommitted transaction rolls it back too
+        {
+            let mut txn = db.datastore.transaction();
+            assert!(txn.create_vertex(&other).unwrap());
+        }
+        expect_vertex(&db, id);
+        assert!(watcher.try_recv().is_none());
+    }
+
+    #[test]
+    fn should_retry_serialization_conflicts() {
+        let db = MemoryDatastore::new_db();
+        let attempts = std::cell::Cell::new(0);
+
+        // Conflicting attempts are rolled back, and the last one commits
+        let id = db
+            .datastore
+            .with_transaction_retry(3, |txn| {
+                attempts.set(attempts.get() + 1);
+                let vertex = Vertex::new(Identifier::default());
+                txn.create_vertex(&vertex)?;
+                if attempts.get() < 3 {
+                    Err(Error::SerializationConflict)
+                } else {
+                    Ok(vertex.id)
+                }
+            })
+            .unwrap();
+        assert_eq!(attempts.get(), 3);
+        let vertices = extract_vertices(db.get(AllVertexQuery).unwrap()).unwrap();
+        assert_eq!(vertices, vec![Vertex::with_id(id, Identifier::default())]);
+
+        // The last conflict is returned once out of attempts
+        attempts.set(0);
+        let result = db.datastore.with_transaction_retry(2, |_| -> Result<(), Error> {
+            attempts.set(attempts.get() + 1);
+            Err(Error::SerializationConflict)
+        });
+        assert!(matches!(result, Err(Error::SerializationConflict)));
+        assert_eq!(attempts.get(), 2);
+
+        // Other errors aren't retried
+        attempts.set(0);
+        let result = db.datastore.with_transaction_retry(2, |_| -> Result<(), Error> {
+            attempts.set(attempts.get() + 1);
+            Err(Error::Unsupported)
+        });
+        assert!(matches!(result, Err(Error::Unsupported)));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn should_get_exact_stats() {
+        let db = MemoryDatastore::new_db();
+        let empty_size = db.get_stats().unwrap().size_bytes.unwrap();
+        let id = create_vertex_with_property(&db);
+        let other_id = db.create_vertex_from_type(Identifier::default()).unwrap();
+        db.create_edge(&Edge::new(id, Identifier::default(), other_id)).unwrap();
+
+        let stats = db.get_stats().unwrap();
+        assert_eq!(stats.vertex_count, 2);
+        assert_eq!(stats.edge_count, 1);
+        assert!(stats.size_bytes.unwrap() > empty_size);
+    }
+
+    #[test]
+    fn should_fork() {
+        let db = MemoryDatastore::new_db();
+        let name = Identifier::new("name").unwrap();
+        db.index_property(name).unwrap();
+        let id = create_vertex_with_property(&db);
+        db.set_properties(SpecificVertexQuery::single(id), name, &ijson!("alice"))
+            .unwrap();
+
+        let fork = Database::new(db.datastore.fork());
+        expect_vertex(&fork, id);
+        fork.set_properties(SpecificVertexQuery::single(id), name, &ijson!("bob"))
+            .unwrap();
+        let other_id = fork.create_vertex_from_type(Identifier::default()).unwrap();
+        assert_eq!(fork.get_vertex_count_by_type(Identifier::default()).unwrap(), 2);
+
+        let get_named = |db: &Database<MemoryDatastore>, value| {
+            extract_vertices(db.get(VertexWithPropertyValueQuery::new(name, value)).unwrap()).unwrap()
+        };
+        assert!(get_named(&fork, ijson!("alice")).is_empty());
+        assert_eq!(get_named(&fork, ijson!("bob")).len(), 1);
+
+        // The original is untouched
+        expect_vertex(&db, id);
+        assert_eq!(db.get_vertex(other_id).unwrap(), None);
+        assert_eq!(get_named(&db, ijson!("alice")).len(), 1);
+        assert!(get_named(&db, ijson!("bob")).is_empty());
+    }
+
+    #[test]
+    fn should_roll_back_clear() {
+        let db = MemoryDatastore::new_db();
+        let name = Identifier::new("name").unwrap();
+        db.index_property(name).unwrap();
+        let id = create_vertex_with_property(&db);
+        db.set_properties(SpecificVertexQuery::single(id), name, &ijson!("alice"))
+            .unwrap();
+
+        let mut txn = db.datastore.transaction();
+        let other = Vertex::new(Identifier::default());
+        assert!(txn.create_vertex(&other).unwrap());
+        txn.clear().unwrap();
+        assert_eq!(txn.vertex_count(), 0);
+        txn.rollback().unwrap();
+
+        expect_vertex(&db, id);
+        assert_eq!(db.get_vertex_count_by_type(Identifier::default()).unwrap(), 1);
+        assert_eq!(db.get_indexed_properties().unwrap(), vec![name]);
+        let vertices = extract_vertices(
+            db.get(VertexWithPropertyValueQuery::new(name, ijson!("alice")))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vertices, vec![Vertex::with_id(id, Identifier::default())]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn should_use_async_db() {
+        let db = MemoryDatastore::new_async_db();
+        let id = db.create_vertex_from_type(Identifier::default()).await.unwrap();
+        db.set_properties(SpecificVertexQuery::single(id), Identifier::default(), &ijson!(true))
+            .await
+            .unwrap();
+        let q = VertexWithPropertyValueQuery::new(Identifier::default(), ijson!(true));
+        assert!(matches!(db.get(q.clone()).await, Err(Error::NotIndexed)));
+        db.index_property(Identifier::default()).await.unwrap();
+        let vertices = extract_vertices(db.get(q).await.unwrap()).unwrap();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].id, id);
+        assert_eq!(db.delete(SpecificVertexQuery::single(id)).await.unwrap(), 1);
+        assert_eq!(
+            extract_count(db.get(AllVertexQuery.count().unwrap()).await.unwrap()),
+            Some(0)
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn should_mix_sync_and_async_transactions() {
+        let db = MemoryDatastore::new_async_db();
+        let sync_db = Database::new(db.datastore.clone());
+
+        // Synchronous transactions can be used from within the runtime
+        let id = sync_db.create_vertex_from_type(Identifier::default()).unwrap();
+
+        // Async transactions wait for synchronous ones without blocking it
+        let txn = sync_db.datastore.transaction();
+        let waiting = tokio::spawn(async move { db.get_vertex(id).await });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        drop(txn);
+        let vertex = waiting.await.unwrap().unwrap();
+        assert_eq!(vertex.map(|v| v.id), Some(id));
+    }
 }
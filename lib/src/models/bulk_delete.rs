@@ -0,0 +1,14 @@
+use crate::{Edge, Identifier, Query};
+
+use uuid::Uuid;
+
+/// An item to delete, as part of a bulk delete request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BulkDeleteItem {
+    /// The ID of a vertex to delete.
+    Vertex(Uuid),
+    /// An edge to delete.
+    Edge(Edge),
+    /// A property to delete from every vertex or edge returned by a query.
+    Properties(Query, Identifier),
+}
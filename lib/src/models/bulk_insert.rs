@@ -5,7 +5,10 @@ use uuid::Uuid;
 /// An item to insert, as part of a bulk insert request.
 #[derive(Clone, Debug, PartialEq)]
 pub enum BulkInsertItem {
-    /// A vertex to insert.
+    /// A vertex to insert. Its ID is chosen before the insert - e.g. by
+    /// `Vertex::new` - rather than by the datastore, so edges and
+    /// properties referencing it can be built from `vertex.id` and included
+    /// in the same bulk insert, without querying for the vertex first.
     Vertex(Vertex),
     /// An edge to insert.
     Edge(Edge),
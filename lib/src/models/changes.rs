@@ -0,0 +1,51 @@
+use crate::{Edge, Identifier, Json, Vertex};
+
+/// A mutation to the graph, as emitted to watchers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeEvent {
+    /// A vertex was created.
+    VertexCreated(Vertex),
+    /// A vertex was deleted.
+    VertexDeleted(Vertex),
+    /// An edge was created.
+    EdgeCreated(Edge),
+    /// An edge was deleted, either directly or because one of its vertices
+    /// was. Edges that expire after a TTL don't emit this.
+    EdgeDeleted(Edge),
+    /// A property was set on a vertex.
+    VertexPropertySet(Vertex, Identifier, Json),
+    /// A property was set on an edge.
+    EdgePropertySet(Edge, Identifier, Json),
+}
+
+/// Filters the change events a watcher receives. Vertex events, including
+/// vertex properties being set, are filtered by vertex type; edge events by
+/// edge type. An empty list of types lets all events of that kind through.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChangeFilter {
+    /// The vertex types to receive events for.
+    pub vertex_types: Vec<Identifier>,
+    /// The edge types to receive events for.
+    pub edge_types: Vec<Identifier>,
+}
+
+impl ChangeFilter {
+    /// Returns whether an event passes the filter.
+    ///
+    /// # Arguments
+    /// * `event`: The event to check.
+    pub fn matches(&self, event: &ChangeEvent) -> bool {
+        match event {
+            ChangeEvent::VertexCreated(vertex)
+            | ChangeEvent::VertexDeleted(vertex)
+            | ChangeEvent::VertexPropertySet(vertex, _, _) => {
+                self.vertex_types.is_empty() || self.vertex_types.contains(&vertex.t)
+            }
+            ChangeEvent::EdgeCreated(edge)
+            | ChangeEvent::EdgeDeleted(edge)
+            | ChangeEvent::EdgePropertySet(edge, _, _) => {
+                self.edge_types.is_empty() || self.edge_types.contains(&edge.t)
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{RangeDirection, RangeVertexQuery, Vertex};
+use crate::errors::{Error, Result};
+
+use uuid::Uuid;
+
+// The parameters of 64-bit FNV-1a, which hashes the shape of a query the same
+// regardless of the platform or Rust version.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+// Hashes everything about a query that determines which results it returns,
+// other than where it starts and how many results it returns.
+fn query_shape(q: &RangeVertexQuery) -> u64 {
+    let mut t_filter = q.t_filter.clone();
+    t_filter.sort();
+    t_filter.dedup();
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for t in t_filter {
+        // Identifiers can't contain a nul, so this separates them unambiguously
+        hash = fnv1a(hash, t.as_bytes());
+        hash = fnv1a(hash, &[0]);
+    }
+    if let Some((ref name, ref value)) = q.property_filter {
+        hash = fnv1a(hash, &[1]);
+        hash = fnv1a(hash, name.as_bytes());
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, value.to_string().as_bytes());
+    }
+    let direction = match q.direction {
+        RangeDirection::Ascending => 0,
+        RangeDirection::Descending => 1,
+    };
+    fnv1a(hash, &[2, direction])
+}
+
+/// An opaque position in the results of a `RangeVertexQuery`, for paginating
+/// through them without exposing vertex IDs, e.g. in the pagination tokens
+/// of an API. See `Database::get_vertex_page`.
+///
+/// A cursor is formatted as a string via `Display`, and parsed back via
+/// `FromStr`. It can only be used to continue the query it came from, other
+/// than its limit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Cursor {
+    last_id: Uuid,
+    shape: u64,
+}
+
+impl Cursor {
+    /// Gets the cursor for continuing after a page of results, or `None` if
+    /// there are no more results. More results may exist when the page is
+    /// full, so the last page can be empty.
+    ///
+    /// # Arguments
+    /// * `q`: The query that produced the page.
+    /// * `page`: The vertices returned by the query.
+    pub fn after(q: &RangeVertexQuery, page: &[Vertex]) -> Option<Self> {
+        if page.len() < q.limit as usize {
+            return None;
+        }
+        Self::continuing(q, page.last()?)
+    }
+
+    /// Gets the cursor for continuing after a vertex, however many results
+    /// the page had - e.g. for a page that was cut short before reaching
+    /// the query's limit. Returns `None` if there can't be any more results.
+    ///
+    /// # Arguments
+    /// * `q`: The query that produced the page.
+    /// * `last`: The last vertex of the page.
+    pub fn continuing(q: &RangeVertexQuery, last: &Vertex) -> Option<Self> {
+        let last_id = last.id;
+        // There's nothing beyond the ends of the ID space
+        let exhausted = match q.direction {
+            RangeDirection::Ascending => last_id == Uuid::max(),
+            RangeDirection::Descending => last_id.is_nil(),
+        };
+        if exhausted {
+            None
+        } else {
+            Some(Self {
+                last_id,
+                shape: query_shape(q),
+            })
+        }
+    }
+
+    /// Continues a query from this cursor.
+    ///
+    /// # Arguments
+    /// * `q`: The query to continue.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidCursor` if the cursor came from a different
+    /// query.
+    pub fn apply(&self, q: RangeVertexQuery) -> Result<RangeVertexQuery> {
+        if self.shape != query_shape(&q) {
+            return Err(Error::InvalidCursor);
+        }
+        let start_id = match q.direction {
+            RangeDirection::Ascending => Uuid::from_u128(self.last_id.as_u128() + 1),
+            RangeDirection::Descending => Uuid::from_u128(self.last_id.as_u128() - 1),
+        };
+        Ok(q.start_id(start_id))
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:032x}{:016x}", self.last_id.as_u128(), self.shape)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.len() != 48 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(Error::InvalidCursor);
+        }
+        let last_id = u128::from_str_radix(&s[..32], 16).map_err(|_| Error::InvalidCursor)?;
+        let shape = u64::from_str_radix(&s[32..], 16).map_err(|_| Error::InvalidCursor)?;
+        Ok(Self {
+            last_id: Uuid::from_u128(last_id),
+            shape,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cursor;
+    use crate::{Error, Identifier, RangeVertexQuery, Vertex};
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    #[test]
+    fn should_round_trip_cursor() {
+        let q = RangeVertexQuery::new().limit(1);
+        let vertex = Vertex::with_id(Uuid::from_u128(7), Identifier::new("foo").unwrap());
+        let cursor = Cursor::after(&q, &[vertex]).unwrap();
+        assert_eq!(Cursor::from_str(&cursor.to_string()).unwrap(), cursor);
+        assert_eq!(cursor.apply(q.clone()).unwrap().start_id, Some(Uuid::from_u128(8)));
+        assert!(matches!(
+            cursor.apply(q.t(Identifier::new("foo").unwrap())),
+            Err(Error::InvalidCursor)
+        ));
+        assert!(matches!(Cursor::from_str("nope"), Err(Error::InvalidCursor)));
+    }
+
+    #[test]
+    fn should_not_get_cursor_after_last_page() {
+        let q = RangeVertexQuery::new().limit(2);
+        let vertex = Vertex::with_id(Uuid::from_u128(7), Identifier::new("foo").unwrap());
+        assert_eq!(Cursor::after(&q, &[vertex]), None);
+        let vertex = Vertex::with_id(Uuid::max(), Identifier::new("foo").unwrap());
+        assert_eq!(Cursor::after(&q.limit(1), &[vertex]), None);
+    }
+
+    #[test]
+    fn should_continue_short_page() {
+        let q = RangeVertexQuery::new().limit(2);
+        let vertex = Vertex::with_id(Uuid::from_u128(7), Identifier::new("foo").unwrap());
+        let cursor = Cursor::continuing(&q, &vertex).unwrap();
+        assert_eq!(cursor.apply(q.clone()).unwrap().start_id, Some(Uuid::from_u128(8)));
+        let vertex = Vertex::with_id(Uuid::max(), Identifier::new("foo").unwrap());
+        assert_eq!(Cursor::continuing(&q, &vertex), None);
+    }
+}
@@ -8,6 +8,19 @@ use uuid::Uuid;
 /// Edges are how you would represent a verb or a relationship in the
 /// datastore. An example might be "liked" or "reviewed". Edges are typed and
 /// directed.
+///
+/// Edges don't record when they were created or updated, so there's no
+/// querying by time range or ordering by recency. If you need that, store
+/// the time in an edge property.
+///
+/// An edge is just its `(outbound_id, t, inbound_id)` key, with no other
+/// fields to load, so queries that output edges are already as light as
+/// reading only the keys.
+///
+/// Similarly, edges don't have a weight, since an edge is identified by its
+/// endpoints and type alone. Store a weight in an edge property instead, and
+/// filter on it with a `PropertyPredicate`, e.g. `GreaterOrEqual`. The filter
+/// runs in the datastore, including when querying via the gRPC server.
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct Edge {
     /// The id of the outbound vertex.
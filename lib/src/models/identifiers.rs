@@ -1,16 +1,71 @@
+use std::cmp::Ordering;
 use std::convert::TryFrom;
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
-use crate::errors::{ValidationError, ValidationResult};
+use crate::errors::{IdentifierError, ValidationError, ValidationResult};
 
 use internment::Intern;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-/// A string that must be less than 256 characters long, and can only contain
-/// letters, numbers, dashes and underscores. This is used for vertex and edge
+/// The default maximum length of an identifier, in bytes.
+pub const DEFAULT_MAX_IDENTIFIER_LENGTH: usize = 255;
+
+/// The highest that the maximum length of an identifier can be set to, in
+/// bytes. This is limited by how the RocksDB datastore encodes identifiers
+/// in its keys.
+pub const MAX_IDENTIFIER_LENGTH_CEILING: usize = u16::MAX as usize;
+
+static MAX_LEN: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_IDENTIFIER_LENGTH);
+
+/// Gets the maximum length of an identifier, in bytes.
+pub fn max_identifier_length() -> usize {
+    MAX_LEN.load(AtomicOrdering::Relaxed)
+}
+
+/// Sets the maximum length of an identifier for the whole process, e.g. to
+/// allow long namespaced type names. This only affects validation of new
+/// identifiers; identifiers that have already been created or persisted
+/// remain valid. When using the gRPC server, it should be set in both the
+/// client and server processes.
+///
+/// # Arguments
+/// * `len`: The maximum length, in bytes.
+///
+/// # Errors
+/// Returns `ValidationError::ValueTooLong` if `len` is above
+/// `MAX_IDENTIFIER_LENGTH_CEILING`.
+pub fn set_max_identifier_length(len: usize) -> ValidationResult<()> {
+    if len > MAX_IDENTIFIER_LENGTH_CEILING {
+        return Err(ValidationError::ValueTooLong);
+    }
+    MAX_LEN.store(len, AtomicOrdering::Relaxed);
+    Ok(())
+}
+
+fn is_valid_char(c: char) -> bool {
+    c == '-' || c == '_' || c.is_alphanumeric()
+}
+
+fn check_len(s: &str) -> Result<(), IdentifierError> {
+    let max = max_identifier_length();
+    if s.len() > max {
+        Err(IdentifierError::TooLong { len: s.len(), max })
+    } else {
+        Ok(())
+    }
+}
+
+/// A string that must be at most 255 bytes long (see
+/// `set_max_identifier_length`), and can only contain letters, numbers,
+/// dashes and underscores. This is used for vertex and edge
 /// types, as well as property names.
-#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash, Ord, PartialOrd)]
+///
+/// Identifiers are ordered by a lexicographic comparison of their bytes, the
+/// same as `str`. This ordering is guaranteed to be stable, and doesn't
+/// depend on when or where identifiers were created.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
 pub struct Identifier(pub(crate) Intern<String>);
 
 impl Identifier {
@@ -20,18 +75,73 @@ impl Identifier {
     /// * `s`: The identifier value.
     ///
     /// # Errors
-    /// Returns a `ValidationError` if the identifier is longer than 255
-    /// characters, or has invalid characters.
+    /// Returns a `ValidationError` if the identifier is longer than the
+    /// maximum length, or has invalid characters. Use `try_new` to find out
+    /// which.
     pub fn new<S: Into<String>>(s: S) -> ValidationResult<Self> {
+        Ok(Self::try_new(s)?)
+    }
+
+    /// Constructs a new identifier, reporting specifically why it's invalid
+    /// if it is, e.g. to show to end users.
+    ///
+    /// # Arguments
+    /// * `s`: The identifier value.
+    ///
+    /// # Errors
+    /// Returns `IdentifierError::TooLong` if the identifier is longer than
+    /// the maximum length, or `IdentifierError::InvalidCharacter` with the first
+    /// invalid character.
+    pub fn try_new<S: Into<String>>(s: S) -> Result<Self, IdentifierError> {
         let s = s.into();
+        check_len(&s)?;
+        if let Some((index, ch)) = s.chars().enumerate().find(|(_, c)| !is_valid_char(*c)) {
+            return Err(IdentifierError::InvalidCharacter { ch, index });
+        }
+        Ok(Self(Intern::new(s)))
+    }
+
+    /// Constructs a new identifier that refers to a nested field of a
+    /// property, for indexing and querying. The path is dotted, e.g.
+    /// `addr.city` refers to the `city` field of the `addr` property. Each
+    /// part of the path must be a valid identifier.
+    ///
+    /// Once indexed, a path can be used wherever a property name is queried.
+    /// Properties whose value isn't an object, or that don't have a field
+    /// along the path, aren't indexed under it.
+    ///
+    /// # Arguments
+    /// * `s`: The dotted path.
+    ///
+    /// # Errors
+    /// Returns a `ValidationError` if the path is longer than the maximum
+    /// length, or any part of it is empty or has invalid characters.
+    /// Use `try_new_path` to find out which.
+    pub fn new_path<S: Into<String>>(s: S) -> ValidationResult<Self> {
+        Ok(Self::try_new_path(s)?)
+    }
 
-        if s.len() > 255 {
-            Err(ValidationError::ValueTooLong)
-        } else if !s.chars().all(|c| c == '-' || c == '_' || c.is_alphanumeric()) {
-            Err(ValidationError::InvalidValue)
-        } else {
-            Ok(Self(Intern::new(s)))
+    /// Constructs a new identifier that refers to a nested field of a
+    /// property, reporting specifically why it's invalid if it is. See
+    /// `new_path`.
+    ///
+    /// # Arguments
+    /// * `s`: The dotted path.
+    ///
+    /// # Errors
+    /// Returns `IdentifierError::TooLong` if the path is longer than the
+    /// maximum length, `IdentifierError::Empty` if any part of it is empty, or
+    /// `IdentifierError::InvalidCharacter` with the first invalid character.
+    pub fn try_new_path<S: Into<String>>(s: S) -> Result<Self, IdentifierError> {
+        let s = s.into();
+        check_len(&s)?;
+        if s.split('.').any(|part| part.is_empty()) {
+            return Err(IdentifierError::Empty);
+        }
+        if let Some((index, ch)) = s.chars().enumerate().find(|(_, c)| *c != '.' && !is_valid_char(*c)) {
+            return Err(IdentifierError::InvalidCharacter { ch, index });
         }
+        Ok(Self(Intern::new(s)))
     }
 
     /// Constructs a new identifier, without any checks that it is valid.
@@ -50,6 +160,18 @@ impl Identifier {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Splits a path identifier (see `new_path`) into the property name and
+    /// the fields nested under it. Identifiers that aren't paths have no
+    /// fields.
+    pub(crate) fn split_path(&self) -> (Identifier, Vec<&str>) {
+        if !self.0.contains('.') {
+            return (*self, Vec::new());
+        }
+        let mut parts = self.0.split('.');
+        let name = unsafe { Identifier::new_unchecked(parts.next().unwrap()) };
+        (name, parts.collect())
+    }
 }
 
 impl Default for Identifier {
@@ -58,6 +180,18 @@ impl Default for Identifier {
     }
 }
 
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Deref for Identifier {
     type Target = String;
     fn deref(&self) -> &Self::Target {
@@ -103,7 +237,8 @@ impl<'de> Deserialize<'de> for Identifier {
 
 #[cfg(test)]
 mod tests {
-    use super::Identifier;
+    use super::{max_identifier_length, set_max_identifier_length, Identifier, MAX_IDENTIFIER_LENGTH_CEILING};
+    use crate::errors::IdentifierError;
     use std::str::FromStr;
 
     #[test]
@@ -114,6 +249,51 @@ mod tests {
         assert!(Identifier::new("$").is_err());
     }
 
+    #[test]
+    fn should_create_path() {
+        let path = Identifier::new_path("addr.city").unwrap();
+        assert_eq!(path.as_str(), "addr.city");
+        assert_eq!(path.split_path(), (Identifier::new("addr").unwrap(), vec!["city"]));
+        let id = Identifier::new_path("addr").unwrap();
+        assert_eq!(id.split_path(), (id, vec![]));
+        assert!(Identifier::new("addr.city").is_err());
+        assert!(Identifier::new_path("addr..city").is_err());
+        assert!(Identifier::new_path("addr.$").is_err());
+    }
+
+    #[test]
+    fn should_report_why_identifiers_are_invalid() {
+        let long_t = (0..256).map(|_| "X").collect::<String>();
+        assert_eq!(
+            Identifier::try_new(long_t.clone()),
+            Err(IdentifierError::TooLong { len: 256, max: 255 })
+        );
+        assert_eq!(
+            Identifier::try_new("ä$b"),
+            Err(IdentifierError::InvalidCharacter { ch: '$', index: 1 })
+        );
+        assert_eq!(
+            Identifier::try_new("addr.city"),
+            Err(IdentifierError::InvalidCharacter { ch: '.', index: 4 })
+        );
+        assert_eq!(
+            Identifier::try_new_path(long_t),
+            Err(IdentifierError::TooLong { len: 256, max: 255 })
+        );
+        assert_eq!(Identifier::try_new_path("addr..city"), Err(IdentifierError::Empty));
+        assert_eq!(
+            Identifier::try_new_path("addr.$"),
+            Err(IdentifierError::InvalidCharacter { ch: '$', index: 5 })
+        );
+        assert_eq!(Identifier::try_new_path("addr.city").unwrap().as_str(), "addr.city");
+    }
+
+    #[test]
+    fn should_not_set_max_length_above_ceiling() {
+        assert!(set_max_identifier_length(MAX_IDENTIFIER_LENGTH_CEILING + 1).is_err());
+        assert_eq!(max_identifier_length(), 255);
+    }
+
     #[test]
     fn should_create_unchecked() {
         unsafe {
@@ -130,6 +310,19 @@ mod tests {
         assert!(Identifier::try_from("$".to_string()).is_err());
     }
 
+    #[test]
+    fn should_order_like_str() {
+        // Intern in a different order than the sorted one
+        let values = ["b", "B", "a_1", "a-1", "a", "10", "9", "ä", "z"];
+        let mut ids: Vec<Identifier> = values.iter().map(|v| Identifier::new(*v).unwrap()).collect();
+        ids.sort();
+        let mut sorted = values.to_vec();
+        sorted.sort();
+        assert_eq!(ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>(), sorted);
+        assert!(Identifier::new("a").unwrap() < Identifier::new("ab").unwrap());
+        assert!(Identifier::default() < Identifier::new("a").unwrap());
+    }
+
     #[test]
     fn should_convert_between_identifier_and_string() {
         let id = Identifier::new("foo").unwrap();
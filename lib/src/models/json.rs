@@ -5,6 +5,8 @@ use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::errors::JsonTypeError;
+
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// Similar to `serde_json::json!`, this allows you to create JSON literals.
@@ -143,6 +145,41 @@ where
     }
 }
 
+// The smallest float that's too large for a `u64`, i.e. 2^64.
+const U64_LIMIT: f64 = 18_446_744_073_709_551_616.0;
+
+fn canonicalize_number(value: &serde_json::Number) -> serde_json::Number {
+    match value.as_f64() {
+        Some(f) if !value.is_i64() && !value.is_u64() => {
+            if f.fract() == 0.0 && f >= i64::MIN as f64 && f < U64_LIMIT {
+                // Integral floats in range convert exactly; this also turns
+                // -0.0 into 0
+                if f < 0.0 {
+                    serde_json::Number::from(f as i64)
+                } else {
+                    serde_json::Number::from(f as u64)
+                }
+            } else {
+                value.clone()
+            }
+        }
+        _ => value.clone(),
+    }
+}
+
+fn canonicalize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Number(v) => serde_json::Value::Number(canonicalize_number(v)),
+        serde_json::Value::Array(v) => serde_json::Value::Array(v.iter().map(canonicalize).collect()),
+        serde_json::Value::Object(v) => {
+            let mut entries: Vec<(&String, &serde_json::Value)> = v.iter().collect();
+            entries.sort_by_key(|(k, _)| *k);
+            serde_json::Value::Object(entries.into_iter().map(|(k, v)| (k.clone(), canonicalize(v))).collect())
+        }
+        other => other.clone(),
+    }
+}
+
 /// Wraps `serde_json::Value` in an `Arc` to make it more cheaply cloneable, as
 /// well as implements extra traits useful for datastore storage and querying.
 #[derive(Clone, Eq, Debug)]
@@ -156,6 +193,82 @@ impl Json {
     pub fn new(value: serde_json::Value) -> Self {
         Self(Arc::new(value))
     }
+
+    /// Constructs a JSON string. This isn't named `from_str`, since that
+    /// parses JSON.
+    ///
+    /// # Arguments
+    /// * `value`: The string.
+    pub fn from_string<S: Into<String>>(value: S) -> Self {
+        Self::new(serde_json::Value::String(value.into()))
+    }
+
+    /// Constructs a JSON number. As JSON has no representation for them,
+    /// NaN and infinite values are converted to `null`.
+    ///
+    /// # Arguments
+    /// * `value`: The number.
+    pub fn from_number(value: f64) -> Self {
+        Self::new(serde_json::Value::from(value))
+    }
+
+    /// Constructs a JSON boolean.
+    ///
+    /// # Arguments
+    /// * `value`: The boolean.
+    pub fn from_bool(value: bool) -> Self {
+        Self::new(serde_json::Value::Bool(value))
+    }
+
+    /// Constructs a JSON object. If a key is repeated, the last value for it
+    /// is used.
+    ///
+    /// # Arguments
+    /// * `entries`: The keys and values of the object.
+    pub fn object<I, K>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, Json)>,
+        K: Into<String>,
+    {
+        let map = entries
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into_value()))
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+        Self::new(serde_json::Value::Object(map))
+    }
+
+    /// Constructs a JSON array.
+    ///
+    /// # Arguments
+    /// * `values`: The values of the array.
+    pub fn array<I: IntoIterator<Item = Json>>(values: I) -> Self {
+        Self::new(serde_json::Value::Array(
+            values.into_iter().map(Json::into_value).collect(),
+        ))
+    }
+
+    /// Gets the underlying JSON value, only cloning it if it's shared.
+    pub fn into_value(self) -> serde_json::Value {
+        Arc::try_unwrap(self.0).unwrap_or_else(|value| (*value).clone())
+    }
+
+    /// Gets the canonical form of the JSON value, which can be used as a map
+    /// key. See `CanonicalJson` for how values are canonicalized.
+    pub fn canonical(&self) -> CanonicalJson {
+        CanonicalJson(Json::new(canonicalize(&self.0)))
+    }
+
+    /// Gets the type of the JSON value.
+    pub fn json_type(&self) -> JsonType {
+        match *self.0 {
+            serde_json::Value::Null => JsonType::Null,
+            serde_json::Value::Bool(_) => JsonType::Bool,
+            serde_json::Value::Number(_) => JsonType::Number,
+            serde_json::Value::String(_) => JsonType::String,
+            serde_json::Value::Array(_) => JsonType::Array,
+            serde_json::Value::Object(_) => JsonType::Object,
+        }
+    }
 }
 
 impl From<serde_json::Value> for Json {
@@ -170,6 +283,78 @@ impl From<Arc<serde_json::Value>> for Json {
     }
 }
 
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Self::from_string(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Self::from_string(value)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Self::from_number(value)
+    }
+}
+
+impl From<i64> for Json {
+    fn from(value: i64) -> Self {
+        Self::new(serde_json::Value::from(value))
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Self::new(serde_json::Value::from(value))
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Self::from_bool(value)
+    }
+}
+
+impl TryFrom<Json> for String {
+    type Error = JsonTypeError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        match value.into_value() {
+            serde_json::Value::String(s) => Ok(s),
+            other => Err(JsonTypeError {
+                expected: JsonType::String,
+                actual: Json::new(other).json_type(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Json> for f64 {
+    type Error = JsonTypeError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or(JsonTypeError {
+            expected: JsonType::Number,
+            actual: value.json_type(),
+        })
+    }
+}
+
+impl TryFrom<Json> for bool {
+    type Error = JsonTypeError;
+
+    fn try_from(value: Json) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or(JsonTypeError {
+            expected: JsonType::Bool,
+            actual: value.json_type(),
+        })
+    }
+}
+
 impl Hash for Json {
     fn hash<H: Hasher>(&self, state: &mut H) {
         hash(&self.0, state);
@@ -223,17 +408,101 @@ impl FromStr for Json {
     }
 }
 
+/// A JSON value in canonical form, so that values that represent the same
+/// JSON are equal and hash the same, e.g. for grouping or deduplicating
+/// property values in a `HashMap` or `HashSet`. Created with
+/// `Json::canonical`.
+///
+/// Values are canonicalized as follows:
+/// * Object keys are sorted, bytewise, at every level of nesting.
+/// * Floats with no fractional part are converted to integers if they're
+///   within the range of an `i64` or `u64`, so `3.0` is the same as `3`,
+///   and `-0.0` is the same as `0`. Integral floats outside that range,
+///   e.g. `1e20`, stay floats.
+/// * Other floats are compared by their exact value, so `0.1 + 0.2` is
+///   different from `0.3`. JSON has no NaN or infinite values, so every
+///   float is equal to itself.
+/// * Integers are the same whether they were parsed as signed or unsigned.
+#[derive(Clone, Debug)]
+pub struct CanonicalJson(Json);
+
+impl CanonicalJson {
+    /// Gets the canonicalized JSON value.
+    pub fn into_json(self) -> Json {
+        self.0
+    }
+}
+
+impl Hash for CanonicalJson {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash(&self.0 .0, state);
+    }
+}
+
+impl PartialEq for CanonicalJson {
+    fn eq(&self, other: &Self) -> bool {
+        // Canonical values only have one representation, so they can be
+        // compared structurally
+        *self.0 .0 == *other.0 .0
+    }
+}
+
+impl Eq for CanonicalJson {}
+
+impl Deref for CanonicalJson {
+    type Target = Json;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<CanonicalJson> for Json {
+    fn from(value: CanonicalJson) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for CanonicalJson {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The type of a JSON value, e.g. the type expected of a property's values -
+/// see `Transaction::register_property_schema`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum JsonType {
+    /// `null`.
+    Null,
+    /// `true` or `false`.
+    Bool,
+    /// An integer or floating point number.
+    Number,
+    /// A string.
+    String,
+    /// An array.
+    Array,
+    /// An object.
+    Object,
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Json;
+    use super::{Json, JsonType};
+    use crate::JsonTypeError;
     use std::collections::HashSet;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
 
     fn json_u64() -> Json {
-        Json::new(serde_json::Value::Number(serde_json::Number::from(u64::max_value())))
+        Json::new(serde_json::Value::Number(serde_json::Number::from(u64::MAX)))
     }
 
     fn json_i64() -> Json {
-        Json::new(serde_json::Value::Number(serde_json::Number::from(i64::min_value())))
+        Json::new(serde_json::Value::Number(serde_json::Number::from(i64::MIN)))
     }
 
     #[test]
@@ -250,6 +519,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_hash_canonical() {
+        let canonical = |values: Vec<Json>| values.iter().map(Json::canonical).collect::<HashSet<_>>();
+        assert_eq!(canonical(vec![ijson!(3), ijson!(3.0), ijson!(3u64)]).len(), 1);
+        assert_eq!(canonical(vec![ijson!(0), ijson!(-0.0), ijson!(0.0)]).len(), 1);
+        assert_eq!(canonical(vec![ijson!(-3), ijson!(-3.0), ijson!(3.5)]).len(), 2);
+        assert_eq!(
+            canonical(vec![
+                Json::from_str(r#"{"b": [1.0], "a": {"d": 2, "c": null}}"#).unwrap(),
+                Json::from_str(r#"{"a": {"c": null, "d": 2.0}, "b": [1]}"#).unwrap(),
+            ])
+            .len(),
+            1
+        );
+        assert_eq!(ijson!(1e20).canonical().into_json(), ijson!(1e20));
+        assert_eq!(ijson!(-4.0).canonical().to_string(), "-4");
+    }
+
     #[test]
     fn should_compare() {
         assert!(ijson!("foo1") < ijson!("foo2"));
@@ -280,4 +567,51 @@ mod tests {
         assert!(ijson!({}) < ijson!({"key": "value"}));
         assert!(ijson!({"key": "value"}) > ijson!({}));
     }
+
+    #[test]
+    fn should_get_json_type() {
+        assert_eq!(ijson!(null).json_type(), JsonType::Null);
+        assert_eq!(ijson!(true).json_type(), JsonType::Bool);
+        assert_eq!(ijson!(3).json_type(), JsonType::Number);
+        assert_eq!(json_u64().json_type(), JsonType::Number);
+        assert_eq!(ijson!(3.5).json_type(), JsonType::Number);
+        assert_eq!(ijson!("foo").json_type(), JsonType::String);
+        assert_eq!(ijson!([1, 2]).json_type(), JsonType::Array);
+        assert_eq!(ijson!({"foo": 1}).json_type(), JsonType::Object);
+    }
+
+    #[test]
+    fn should_construct() {
+        assert_eq!(Json::from_string("foo"), ijson!("foo"));
+        assert_eq!(Json::from("foo".to_string()), ijson!("foo"));
+        assert_eq!(Json::from_number(3.5), ijson!(3.5));
+        assert_eq!(Json::from_number(f64::NAN), ijson!(null));
+        assert_eq!(Json::from(-3i64), ijson!(-3));
+        assert_eq!(Json::from_bool(true), ijson!(true));
+        assert_eq!(
+            Json::object([("foo", Json::from(1u64)), ("bar", Json::array([Json::from(true)]))]),
+            ijson!({"foo": 1, "bar": [true]})
+        );
+    }
+
+    #[test]
+    fn should_convert_to_rust_types() {
+        assert_eq!(String::try_from(ijson!("foo")), Ok("foo".to_string()));
+        assert_eq!(f64::try_from(ijson!(3)), Ok(3.0));
+        assert_eq!(bool::try_from(ijson!(false)), Ok(false));
+        assert_eq!(
+            String::try_from(ijson!(3)),
+            Err(JsonTypeError {
+                expected: JsonType::String,
+                actual: JsonType::Number
+            })
+        );
+        assert_eq!(
+            bool::try_from(ijson!(null)),
+            Err(JsonTypeError {
+                expected: JsonType::Bool,
+                actual: JsonType::Null
+            })
+        );
+    }
 }
@@ -1,15 +1,30 @@
+mod bulk_delete;
 mod bulk_insert;
+mod changes;
+mod cursor;
 mod edges;
 mod identifiers;
 mod json;
+mod neighborhood;
+mod plan;
 mod properties;
 mod queries;
+mod stats;
 mod vertices;
 
+pub use self::bulk_delete::BulkDeleteItem;
 pub use self::bulk_insert::BulkInsertItem;
+pub use self::changes::{ChangeEvent, ChangeFilter};
+pub use self::cursor::Cursor;
 pub use self::edges::Edge;
-pub use self::identifiers::Identifier;
-pub use self::json::Json;
+pub use self::identifiers::{
+    max_identifier_length, set_max_identifier_length, Identifier, DEFAULT_MAX_IDENTIFIER_LENGTH,
+    MAX_IDENTIFIER_LENGTH_CEILING,
+};
+pub use self::json::{CanonicalJson, Json, JsonType};
+pub use self::neighborhood::Neighborhood;
+pub use self::plan::{PlanAccess, QueryPlan};
 pub use self::properties::{EdgeProperties, EdgeProperty, NamedProperty, VertexProperties, VertexProperty};
 pub use self::queries::*;
+pub use self::stats::DatastoreStats;
 pub use self::vertices::Vertex;
@@ -0,0 +1,14 @@
+use super::{Edge, Vertex};
+
+/// A vertex along with its edges, e.g. for rendering it in a graph
+/// exploration UI. See `Transaction::vertex_neighborhood`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Neighborhood {
+    /// The vertex.
+    pub vertex: Vertex,
+    /// The vertex's outbound edges.
+    pub outbound: Vec<Edge>,
+    /// The vertex's inbound edges. These are as stored, i.e. their inbound
+    /// ID is the vertex's ID.
+    pub inbound: Vec<Edge>,
+}
@@ -0,0 +1,90 @@
+use crate::Identifier;
+
+/// How a stage of a query plan finds its results.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PlanAccess {
+    /// Reads every vertex or edge.
+    FullScan,
+    /// Reads vertices in ID order until the limit is reached, filtering out
+    /// the ones that don't match.
+    RangeScan,
+    /// Looks up specific vertices or edges.
+    Lookup,
+    /// Looks up vertices or edges via a property index.
+    PropertyIndex,
+    /// Needs a property index that doesn't exist, so running the query
+    /// fails with `Error::NotIndexed`.
+    MissingIndex,
+    /// Looks up vertices via an index other than a property index, e.g. of
+    /// labels or creation times.
+    Index,
+    /// Reads the output of the inner stage, e.g. to filter it or to follow
+    /// edges from it.
+    Pipe,
+    /// Reads a count maintained by the datastore, without scanning.
+    StoredCount,
+}
+
+/// A description of how a query would run, from `Transaction::explain`.
+/// Each stage of the plan corresponds to a query, with the stage of its
+/// inner query nested in it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryPlan {
+    /// The kind of query of the stage, e.g. `RangeVertex`.
+    pub stage: String,
+    /// How the stage finds its results.
+    pub access: PlanAccess,
+    /// The property the stage looks up or filters by, if any.
+    pub property: Option<Identifier>,
+    /// An upper bound on the number of results of the stage, or `None` if it
+    /// can't be estimated without running the query.
+    pub estimated_count: Option<u64>,
+    /// The plan of the inner query, for queries that wrap another one.
+    pub inner: Option<Box<QueryPlan>>,
+}
+
+impl QueryPlan {
+    /// Creates a new query plan stage, without a property or inner stage.
+    ///
+    /// # Arguments
+    /// * `stage`: The kind of query of the stage.
+    /// * `access`: How the stage finds its results.
+    /// * `estimated_count`: An upper bound on the number of results.
+    pub fn new<S: Into<String>>(stage: S, access: PlanAccess, estimated_count: Option<u64>) -> Self {
+        Self {
+            stage: stage.into(),
+            access,
+            property: None,
+            estimated_count,
+            inner: None,
+        }
+    }
+
+    /// Sets the property the stage looks up or filters by.
+    ///
+    /// # Arguments
+    /// * `property`: The property.
+    pub fn property(self, property: Identifier) -> Self {
+        Self {
+            property: Some(property),
+            ..self
+        }
+    }
+
+    /// Sets the plan of the inner query.
+    ///
+    /// # Arguments
+    /// * `inner`: The inner plan.
+    pub fn inner(self, inner: QueryPlan) -> Self {
+        Self {
+            inner: Some(Box::new(inner)),
+            ..self
+        }
+    }
+
+    /// Gets whether any stage of the plan needs a property index that
+    /// doesn't exist.
+    pub fn is_missing_index(&self) -> bool {
+        self.access == PlanAccess::MissingIndex || self.inner.as_ref().is_some_and(|inner| inner.is_missing_index())
+    }
+}
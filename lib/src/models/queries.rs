@@ -1,8 +1,10 @@
 use std::str::FromStr;
-use std::u32;
+use std::time::SystemTime;
 
 use crate::{errors, Edge, Identifier, Json};
 
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 macro_rules! into_query {
@@ -31,14 +33,18 @@ macro_rules! nestable_query {
 /// Edge and vertex queries can build off of one another via pipes - e.g. you
 /// can get the outbound edges of a set of vertices by piping from a vertex
 /// query to an edge query. `EdgeDirection`s are used to specify which
-/// end of things you want to pipe - either the outbound items or the inbound
-/// items.
-#[derive(Eq, PartialEq, Clone, Debug, Hash, Copy)]
+/// end of things you want to pipe - either the outbound items, the inbound
+/// items, or both.
+#[derive(Eq, PartialEq, Clone, Debug, Hash, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EdgeDirection {
     /// Outbound direction.
     Outbound,
     /// Inbound direction.
     Inbound,
+    /// Both directions. Edges are only returned once, even when both of their
+    /// ends are piped from, and self-loops are only returned once per vertex.
+    Both,
 }
 
 impl FromStr for EdgeDirection {
@@ -48,6 +54,7 @@ impl FromStr for EdgeDirection {
         match s {
             "outbound" => Ok(EdgeDirection::Outbound),
             "inbound" => Ok(EdgeDirection::Inbound),
+            "both" => Ok(EdgeDirection::Both),
             _ => Err(errors::ValidationError::InvalidValue),
         }
     }
@@ -58,12 +65,47 @@ impl From<EdgeDirection> for String {
         match d {
             EdgeDirection::Outbound => "outbound".to_string(),
             EdgeDirection::Inbound => "inbound".to_string(),
+            EdgeDirection::Both => "both".to_string(),
         }
     }
 }
 
+/// The order to get vertices from a range query in, by ID.
+#[derive(Eq, PartialEq, Clone, Debug, Hash, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeDirection {
+    /// Lowest IDs first.
+    #[default]
+    Ascending,
+    /// Highest IDs first.
+    Descending,
+}
+
 /// A query to get a set of values from the database.
-#[derive(Eq, PartialEq, Clone, Debug)]
+///
+/// Queries can be stored or sent as JSON via `to_json_str` and
+/// `from_json_str`. Each query is an object with a `type` field naming its
+/// variant in snake case, e.g. `"range_vertex"`, alongside the fields of the
+/// variant's struct. Nested queries are in the `inner` field, edge
+/// directions are `"outbound"`, `"inbound"` or `"both"`, and property
+/// predicates are objects with an `op` field, e.g.
+/// `{"op": "greater_than", "value": 5}`. Times are objects with
+/// `secs_since_epoch` and `nanos_since_epoch` fields.
+///
+/// # Examples
+/// ```
+/// use indradb::{Identifier, Query, QueryExt, RangeVertexQuery};
+/// let q: Query = RangeVertexQuery::new()
+///     .t(Identifier::new("user").unwrap())
+///     .limit(10)
+///     .outbound()
+///     .unwrap()
+///     .into();
+/// let json = q.to_json_str().unwrap();
+/// assert_eq!(Query::from_json_str(&json).unwrap(), q);
+/// ```
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Query {
     /// Gets all vertices.
     AllVertex,
@@ -75,9 +117,15 @@ pub enum Query {
     VertexWithPropertyPresence(VertexWithPropertyPresenceQuery),
     /// Gets vertices with a property equal to a given value.
     VertexWithPropertyValue(VertexWithPropertyValueQuery),
+    /// Gets vertices with a given label.
+    VertexWithLabel(VertexWithLabelQuery),
+    /// Gets vertices by when they were created.
+    CreatedRangeVertex(CreatedRangeVertexQuery),
 
     /// Gets all edges.
     AllEdge,
+    /// Gets a range of edges.
+    RangeEdge(RangeEdgeQuery),
     /// Gets a specific set of edges.
     SpecificEdge(SpecificEdgeQuery),
     /// Gets edges with or without a given property.
@@ -85,6 +133,9 @@ pub enum Query {
     /// Gets edges with a property equal to a given value.
     EdgeWithPropertyValue(EdgeWithPropertyValueQuery),
 
+    /// Gets both vertices and edges with a property equal to a given value.
+    WithPropertyValue(WithPropertyValueQuery),
+
     /// Gets the vertices associated with edges, or edges associated with
     /// vertices.
     Pipe(PipeQuery),
@@ -94,6 +145,11 @@ pub enum Query {
     PipeWithPropertyPresence(PipeWithPropertyPresenceQuery),
     /// Gets vertices or edges with a property equal to a given value.
     PipeWithPropertyValue(PipeWithPropertyValueQuery),
+    /// Gets vertices or edges with a property matching a predicate.
+    PipeWithPropertyPredicate(PipeWithPropertyPredicateQuery),
+    /// Gets the vertices reachable from a set of vertices within a number of
+    /// hops.
+    TraversalVertex(TraversalVertexQuery),
 
     /// Includes the results of a query in output.
     Include(IncludeQuery),
@@ -102,6 +158,75 @@ pub enum Query {
 }
 
 impl Query {
+    /// Serializes the query as JSON, in the shape described on `Query`. The
+    /// query is wrapped in an object with the version of the shape, as
+    /// `{"version": 1, "query": {...}}`, so that `from_json_str` can reject
+    /// queries written in an incompatible shape.
+    pub fn to_json_str(&self) -> errors::Result<String> {
+        let json = VersionedQueryRef {
+            version: QUERY_JSON_VERSION,
+            query: self,
+        };
+        Ok(serde_json::to_string(&json)?)
+    }
+
+    /// Parses a query serialized by `to_json_str`.
+    ///
+    /// # Arguments
+    /// * `s`: The JSON to parse.
+    ///
+    /// # Errors
+    /// Returns `ValidationError::InvalidValue` if the JSON is of an
+    /// unsupported version, or `ValidationError::InnerQuery` if it nests
+    /// queries in a way their constructors don't allow. Malformed JSON is
+    /// returned as an `Error::Datastore`.
+    pub fn from_json_str(s: &str) -> errors::Result<Query> {
+        let json: VersionedQuery = serde_json::from_str(s)?;
+        if json.version != QUERY_JSON_VERSION {
+            return Err(errors::ValidationError::InvalidValue.into());
+        }
+        let query: Query = serde_json::from_value(json.query)?;
+        query.validate()?;
+        Ok(query)
+    }
+
+    // Checks that nested queries are combined the way their constructors
+    // require, for queries that weren't built with them.
+    fn validate(&self) -> errors::ValidationResult<()> {
+        match self {
+            Query::Pipe(PipeQuery { inner, .. })
+            | Query::PipeProperty(PipePropertyQuery { inner, .. })
+            | Query::PipeWithPropertyPresence(PipeWithPropertyPresenceQuery { inner, .. })
+            | Query::PipeWithPropertyValue(PipeWithPropertyValueQuery { inner, .. })
+            | Query::PipeWithPropertyPredicate(PipeWithPropertyPredicateQuery { inner, .. }) => {
+                inner.validate()?;
+                match inner.output_type()? {
+                    QueryOutputValue::Vertices(_) | QueryOutputValue::Edges(_) => Ok(()),
+                    _ => Err(errors::ValidationError::InnerQuery),
+                }
+            }
+            Query::TraversalVertex(TraversalVertexQuery { inner, .. }) => {
+                inner.validate()?;
+                match inner.output_type()? {
+                    QueryOutputValue::Vertices(_) => Ok(()),
+                    _ => Err(errors::ValidationError::InnerQuery),
+                }
+            }
+            Query::Count(CountQuery { inner }) => {
+                inner.validate()?;
+                match inner.output_type()? {
+                    QueryOutputValue::Vertices(_)
+                    | QueryOutputValue::Edges(_)
+                    | QueryOutputValue::VertexProperties(_)
+                    | QueryOutputValue::EdgeProperties(_) => Ok(()),
+                    _ => Err(errors::ValidationError::InnerQuery),
+                }
+            }
+            Query::Include(IncludeQuery { inner }) => inner.validate(),
+            _ => Ok(()),
+        }
+    }
+
     /// Determines the number of output values the query will produce without
     /// running it, so we can allocate a `Vec` with the correct capacity
     /// ahead-of-time.
@@ -112,15 +237,21 @@ impl Query {
             | Query::SpecificVertex(_)
             | Query::VertexWithPropertyPresence(_)
             | Query::VertexWithPropertyValue(_)
+            | Query::VertexWithLabel(_)
+            | Query::CreatedRangeVertex(_)
             | Query::AllEdge
+            | Query::RangeEdge(_)
             | Query::SpecificEdge(_)
             | Query::EdgeWithPropertyPresence(_)
             | Query::EdgeWithPropertyValue(_)
             | Query::Count(_) => 1,
+            Query::WithPropertyValue(_) => 2,
+            Query::TraversalVertex(q) => q.inner.output_len(),
             Query::Pipe(q) => q.inner.output_len(),
             Query::PipeProperty(q) => q.inner.output_len(),
             Query::PipeWithPropertyPresence(q) => q.inner.output_len(),
             Query::PipeWithPropertyValue(q) => q.inner.output_len(),
+            Query::PipeWithPropertyPredicate(q) => q.inner.output_len(),
             Query::Include(q) => 1 + q.inner.output_len(),
         }
     }
@@ -134,12 +265,19 @@ impl Query {
             | Query::RangeVertex(_)
             | Query::SpecificVertex(_)
             | Query::VertexWithPropertyPresence(_)
-            | Query::VertexWithPropertyValue(_) => Ok(QueryOutputValue::Vertices(Vec::default())),
+            | Query::VertexWithPropertyValue(_)
+            | Query::VertexWithLabel(_)
+            | Query::CreatedRangeVertex(_)
+            | Query::TraversalVertex(_) => Ok(QueryOutputValue::Vertices(Vec::default())),
             Query::AllEdge
+            | Query::RangeEdge(_)
             | Query::SpecificEdge(_)
             | Query::EdgeWithPropertyPresence(_)
             | Query::EdgeWithPropertyValue(_) => Ok(QueryOutputValue::Edges(Vec::default())),
             Query::Count(_) => Ok(QueryOutputValue::Count(0)),
+            // This produces two values of different types, so it can't be
+            // piped from
+            Query::WithPropertyValue(_) => Err(errors::ValidationError::InnerQuery),
             Query::Pipe(q) => q.inner.output_type(),
             Query::PipeProperty(q) => match q.inner.output_type()? {
                 QueryOutputValue::Vertices(_) => Ok(QueryOutputValue::VertexProperties(Vec::default())),
@@ -148,9 +286,61 @@ impl Query {
             },
             Query::PipeWithPropertyPresence(q) => q.inner.output_type(),
             Query::PipeWithPropertyValue(q) => q.inner.output_type(),
+            Query::PipeWithPropertyPredicate(q) => q.inner.output_type(),
             Query::Include(q) => q.inner.output_type(),
         }
     }
+
+    /// Limits the query to at most one result where it supports a limit, so
+    /// running it stops as soon as a result is found. Other queries are
+    /// returned as-is.
+    pub fn first(self) -> Query {
+        match self {
+            Query::AllVertex => RangeVertexQuery::new().limit(1).into(),
+            Query::AllEdge => RangeEdgeQuery::new().limit(1).into(),
+            Query::RangeVertex(q) => {
+                let limit = q.limit.min(1);
+                q.limit(limit).into()
+            }
+            Query::CreatedRangeVertex(q) => {
+                let limit = q.limit.min(1);
+                q.limit(limit).into()
+            }
+            Query::RangeEdge(q) => {
+                let limit = q.limit.min(1);
+                q.limit(limit).into()
+            }
+            Query::Pipe(q) => {
+                let limit = q.limit.min(1);
+                q.limit(limit).into()
+            }
+            Query::TraversalVertex(q) => {
+                let limit = q.limit.min(1);
+                q.limit(limit).into()
+            }
+            Query::PipeWithPropertyPredicate(q) => {
+                let limit = q.limit.min(1);
+                q.limit(limit).into()
+            }
+            q => q,
+        }
+    }
+}
+
+// The version of the JSON shape of queries. Bump this on incompatible
+// changes to the shape.
+const QUERY_JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct VersionedQueryRef<'a> {
+    version: u32,
+    query: &'a Query,
+}
+
+#[derive(Deserialize)]
+struct VersionedQuery {
+    version: u32,
+    query: serde_json::Value,
 }
 
 /// Extension trait containing common functions for all query structs.
@@ -165,7 +355,15 @@ pub trait QueryExt: Into<Query> {
         PipeQuery::new(Box::new(self.into()), EdgeDirection::Inbound)
     }
 
-    /// Gets values with a property.
+    /// Gets both the outbound and inbound vertices or edges associated with
+    /// this query.
+    fn both(self) -> errors::ValidationResult<PipeQuery> {
+        PipeQuery::new(Box::new(self.into()), EdgeDirection::Both)
+    }
+
+    /// Gets values with a property, whatever its value. This uses the
+    /// property index if there is one, and otherwise looks up the property
+    /// of each value.
     ///
     /// # Arguments
     /// * `name`: The name of the property.
@@ -173,7 +371,8 @@ pub trait QueryExt: Into<Query> {
         PipeWithPropertyPresenceQuery::new(Box::new(self.into()), name, true)
     }
 
-    /// Gets values without a property.
+    /// Gets values without a property. Like `with_property`, this doesn't
+    /// need the property to be indexed.
     ///
     /// # Arguments
     /// * `name`: The name of the property.
@@ -207,6 +406,29 @@ pub trait QueryExt: Into<Query> {
         PipeWithPropertyValueQuery::new(Box::new(self.into()), name, value, false)
     }
 
+    /// Gets values with a property matching a predicate.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    /// * `predicate`: The predicate the property value must match.
+    fn with_property_matching<T: Into<Identifier>>(
+        self,
+        name: T,
+        predicate: PropertyPredicate,
+    ) -> errors::ValidationResult<PipeWithPropertyPredicateQuery> {
+        PipeWithPropertyPredicateQuery::new(Box::new(self.into()), name, predicate)
+    }
+
+    /// Gets the vertices reachable from this query's vertices within
+    /// `max_depth` hops.
+    ///
+    /// # Arguments
+    /// * `direction`: Which direction to follow edges in.
+    /// * `max_depth`: The maximum number of hops.
+    fn traverse(self, direction: EdgeDirection, max_depth: u32) -> errors::ValidationResult<TraversalVertexQuery> {
+        TraversalVertexQuery::new(Box::new(self.into()), direction, max_depth)
+    }
+
     /// Gets the properties associated with the query results.
     fn properties(self) -> errors::ValidationResult<PipePropertyQuery> {
         PipePropertyQuery::new(Box::new(self.into()))
@@ -226,7 +448,7 @@ pub trait CountQueryExt: Into<Query> {
 }
 
 /// Gets all vertices.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct AllVertexQuery;
 
 impl QueryExt for AllVertexQuery {}
@@ -240,17 +462,27 @@ impl Into<Query> for AllVertexQuery {
     }
 }
 
-/// Gets a range of vertices.
-#[derive(Eq, PartialEq, Clone, Debug)]
+/// Gets a range of vertices. Vertices are returned in order of their IDs,
+/// regardless of their types, so results are deterministic.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct RangeVertexQuery {
     /// Limits the number of vertices to get.
     pub limit: u32,
 
-    /// Filters the type of vertices returned.
-    pub t: Option<Identifier>,
+    /// Filters the type of vertices returned. Vertices matching any of the
+    /// types are returned; if empty, vertices of any type are returned.
+    pub t_filter: Vec<Identifier>,
 
-    /// Sets the lowest vertex ID to return.
+    /// Sets the first vertex ID to return - the lowest when ascending, or
+    /// the highest when descending.
     pub start_id: Option<Uuid>,
+
+    /// Filters the vertices returned to those with a property equal to a
+    /// given value.
+    pub property_filter: Option<(Identifier, Json)>,
+
+    /// The order to get vertices in.
+    pub direction: RangeDirection,
 }
 
 nestable_query!(RangeVertexQuery, RangeVertex);
@@ -265,9 +497,11 @@ impl RangeVertexQuery {
     /// Creates a new vertex range query.
     pub fn new() -> Self {
         Self {
-            limit: u32::max_value(),
-            t: None,
+            limit: u32::MAX,
+            t_filter: Vec::new(),
             start_id: None,
+            property_filter: None,
+            direction: RangeDirection::Ascending,
         }
     }
 
@@ -278,38 +512,81 @@ impl RangeVertexQuery {
     pub fn limit(self, limit: u32) -> Self {
         Self {
             limit,
-            t: self.t,
+            t_filter: self.t_filter,
             start_id: self.start_id,
+            property_filter: self.property_filter,
+            direction: self.direction,
         }
     }
 
-    /// Filter the type of vertices returned.
+    /// Filter the type of vertices returned. This can be called multiple
+    /// times to return vertices matching any of the given types.
     ///
     /// # Arguments
-    /// * `t`: Sets the type filter.
+    /// * `t`: Adds a type to the filter.
     pub fn t(self, t: Identifier) -> Self {
+        let mut t_filter = self.t_filter;
+        t_filter.push(t);
         Self {
             limit: self.limit,
-            t: Some(t),
+            t_filter,
             start_id: self.start_id,
+            property_filter: self.property_filter,
+            direction: self.direction,
         }
     }
 
-    /// Sets the lowest vertex ID to return.
+    /// Sets the first vertex ID to return - the lowest when ascending, or
+    /// the highest when descending. To continue paging from the last vertex
+    /// of a previous page, skip that vertex, since it's included again.
     ///
     /// # Arguments
-    /// * `start_id`: The lowest vertex ID to return.
+    /// * `start_id`: The first vertex ID to return.
     pub fn start_id(self, start_id: Uuid) -> Self {
         Self {
             limit: self.limit,
-            t: self.t,
+            t_filter: self.t_filter,
             start_id: Some(start_id),
+            property_filter: self.property_filter,
+            direction: self.direction,
+        }
+    }
+
+    /// Filter the vertices returned to those with a property equal to a
+    /// given value. If the property is indexed, the index will be used;
+    /// otherwise, vertices are scanned.
+    ///
+    /// # Arguments
+    /// * `name`: The property name.
+    /// * `value`: The property value.
+    pub fn property_filter<T: Into<Identifier>>(self, name: T, value: Json) -> Self {
+        Self {
+            limit: self.limit,
+            t_filter: self.t_filter,
+            start_id: self.start_id,
+            property_filter: Some((name.into(), value)),
+            direction: self.direction,
+        }
+    }
+
+    /// Sets the order to get vertices in. By default, they're returned in
+    /// ascending order of ID.
+    ///
+    /// # Arguments
+    /// * `direction`: The order to get vertices in.
+    pub fn direction(self, direction: RangeDirection) -> Self {
+        Self {
+            limit: self.limit,
+            t_filter: self.t_filter,
+            start_id: self.start_id,
+            property_filter: self.property_filter,
+            direction,
         }
     }
 }
 
 /// Gets a specific set of vertices.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct SpecificVertexQuery {
     /// The IDs of the vertices to get.
     pub ids: Vec<Uuid>,
@@ -336,8 +613,10 @@ impl SpecificVertexQuery {
     }
 }
 
-/// Gets vertices with or without a given property.
-#[derive(Eq, PartialEq, Clone, Debug)]
+/// Gets vertices with a given property. The property must be indexed; to
+/// filter on an unindexed property, pipe from another query via
+/// `QueryExt::with_property`, e.g. from `AllVertexQuery`.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct VertexWithPropertyPresenceQuery {
     /// The name of the property.
     pub name: Identifier,
@@ -356,7 +635,7 @@ impl VertexWithPropertyPresenceQuery {
 }
 
 /// Gets vertices with a property equal to a given value.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct VertexWithPropertyValueQuery {
     /// The name of the property.
     pub name: Identifier,
@@ -380,8 +659,109 @@ impl VertexWithPropertyValueQuery {
     }
 }
 
+/// Gets vertices with a given label. Labels are indexed, so this doesn't
+/// scan all vertices.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct VertexWithLabelQuery {
+    /// The label.
+    pub label: Identifier,
+}
+
+nestable_query!(VertexWithLabelQuery, VertexWithLabel);
+
+impl VertexWithLabelQuery {
+    /// Creates a new vertex with label query.
+    ///
+    /// # Arguments
+    /// * `label`: The label.
+    pub fn new<T: Into<Identifier>>(label: T) -> Self {
+        Self { label: label.into() }
+    }
+}
+
+/// Gets vertices by when they were created, oldest first. Creation times are
+/// indexed, so this doesn't scan all vertices. Vertices created before their
+/// datastore tracked creation times aren't returned.
+///
+/// # Examples
+/// ```
+/// use indradb::CreatedRangeVertexQuery;
+/// use std::time::{Duration, SystemTime};
+/// // A query to get up to 100 vertices created in the last day.
+/// let q = CreatedRangeVertexQuery::new()
+///     .low(SystemTime::now() - Duration::from_secs(86_400))
+///     .limit(100);
+/// ```
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CreatedRangeVertexQuery {
+    /// Limits the number of vertices to get.
+    pub limit: u32,
+
+    /// If set, only vertices created at or after this time are returned.
+    pub low: Option<SystemTime>,
+
+    /// If set, only vertices created before this time are returned.
+    pub high: Option<SystemTime>,
+}
+
+nestable_query!(CreatedRangeVertexQuery, CreatedRangeVertex);
+
+impl Default for CreatedRangeVertexQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CreatedRangeVertexQuery {
+    /// Creates a new vertex creation time range query, which gets vertices
+    /// created at any time.
+    pub fn new() -> Self {
+        Self {
+            limit: u32::MAX,
+            low: None,
+            high: None,
+        }
+    }
+
+    /// Sets the limit.
+    ///
+    /// # Arguments
+    /// * `limit`: Limits the number of returned results.
+    pub fn limit(self, limit: u32) -> Self {
+        Self {
+            limit,
+            low: self.low,
+            high: self.high,
+        }
+    }
+
+    /// Only returns vertices created at or after a time.
+    ///
+    /// # Arguments
+    /// * `low`: The earliest creation time, inclusive.
+    pub fn low(self, low: SystemTime) -> Self {
+        Self {
+            limit: self.limit,
+            low: Some(low),
+            high: self.high,
+        }
+    }
+
+    /// Only returns vertices created before a time.
+    ///
+    /// # Arguments
+    /// * `high`: The latest creation time, exclusive.
+    pub fn high(self, high: SystemTime) -> Self {
+        Self {
+            limit: self.limit,
+            low: self.low,
+            high: Some(high),
+        }
+    }
+}
+
 /// Gets all edges.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct AllEdgeQuery;
 
 impl QueryExt for AllEdgeQuery {}
@@ -395,8 +775,105 @@ impl Into<Query> for AllEdgeQuery {
     }
 }
 
+/// Gets a range of edges, in key order - i.e. by outbound vertex ID, then
+/// type, then inbound vertex ID. Unlike piping from a vertex, this can page
+/// through the edges of a vertex with any number of them, by continuing each
+/// page after the last edge of the previous one.
+///
+/// Continuing seeks straight to the edge in every datastore, rather than
+/// skipping over the edges before it, so every page is as fast to get as the
+/// first.
+///
+/// # Examples
+/// ```
+/// use indradb::{Edge, Identifier, RangeEdgeQuery};
+/// use uuid::Uuid;
+/// // A query to return the next 100 outbound edges of a vertex, after the
+/// // last edge of the previous page.
+/// let id = Uuid::default();
+/// let last = Edge::new(id, Identifier::new("follows").unwrap(), Uuid::default());
+/// let q = RangeEdgeQuery::new().outbound_id(id).after(last).limit(100);
+/// ```
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RangeEdgeQuery {
+    /// Limits the number of edges to get.
+    pub limit: u32,
+
+    /// Only returns edges from this vertex.
+    pub outbound_id: Option<Uuid>,
+
+    /// Filters the type of edges returned. Edges matching any of the types
+    /// are returned; if empty, edges of any type are returned.
+    pub t_filter: Vec<Identifier>,
+
+    /// Only returns edges after this one, exclusive. The edge doesn't need
+    /// to exist.
+    pub after: Option<Edge>,
+}
+
+nestable_query!(RangeEdgeQuery, RangeEdge);
+
+impl Default for RangeEdgeQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RangeEdgeQuery {
+    /// Creates a new edge range query.
+    pub fn new() -> Self {
+        Self {
+            limit: u32::MAX,
+            outbound_id: None,
+            t_filter: Vec::new(),
+            after: None,
+        }
+    }
+
+    /// Sets the limit.
+    ///
+    /// # Arguments
+    /// * `limit`: Limits the number of returned results.
+    pub fn limit(self, limit: u32) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// Only returns edges from a vertex.
+    ///
+    /// # Arguments
+    /// * `outbound_id`: The ID of the outbound vertex.
+    pub fn outbound_id(self, outbound_id: Uuid) -> Self {
+        Self {
+            outbound_id: Some(outbound_id),
+            ..self
+        }
+    }
+
+    /// Filter the type of edges returned. This can be called multiple times
+    /// to return edges matching any of the given types.
+    ///
+    /// # Arguments
+    /// * `t`: Adds a type to the filter.
+    pub fn t(self, t: Identifier) -> Self {
+        let mut t_filter = self.t_filter;
+        t_filter.push(t);
+        Self { t_filter, ..self }
+    }
+
+    /// Continues after an edge, e.g. the last edge of the previous page.
+    ///
+    /// # Arguments
+    /// * `after`: The edge to continue after, exclusive.
+    pub fn after(self, after: Edge) -> Self {
+        Self {
+            after: Some(after),
+            ..self
+        }
+    }
+}
+
 /// Gets a specific set of edges.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct SpecificEdgeQuery {
     /// The edges to get.
     pub edges: Vec<Edge>,
@@ -422,8 +899,10 @@ impl SpecificEdgeQuery {
     }
 }
 
-/// Gets edges with or without a given property.
-#[derive(Eq, PartialEq, Clone, Debug)]
+/// Gets edges with a given property. The property must be indexed; to
+/// filter on an unindexed property, pipe from another query via
+/// `QueryExt::with_property`, e.g. from `AllEdgeQuery`.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct EdgeWithPropertyPresenceQuery {
     /// The name of the property.
     pub name: Identifier,
@@ -442,7 +921,7 @@ impl EdgeWithPropertyPresenceQuery {
 }
 
 /// Gets edges with a property equal to a given value.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct EdgeWithPropertyValueQuery {
     /// The name of the property.
     pub name: Identifier,
@@ -466,12 +945,54 @@ impl EdgeWithPropertyValueQuery {
     }
 }
 
+/// Gets both vertices and edges with a property equal to a given value, e.g.
+/// to find everything with a given tag.
+///
+/// This outputs two values: the matching vertices, then the matching edges.
+/// Because of that, it can't be piped from or counted. The property must be
+/// indexed.
+///
+/// # Examples
+/// ```
+/// use indradb::{ijson, Identifier, WithPropertyValueQuery};
+/// let q = WithPropertyValueQuery::new(Identifier::new("source").unwrap(), ijson!("import-2024"));
+/// ```
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct WithPropertyValueQuery {
+    /// The name of the property.
+    pub name: Identifier,
+    /// The value of the property.
+    pub value: Json,
+}
+
+into_query!(WithPropertyValueQuery, WithPropertyValue);
+
+impl WithPropertyValueQuery {
+    /// Creates a new with property value query.
+    ///
+    /// # Arguments
+    /// * `name`: The property name.
+    /// * `value`: The property value.
+    pub fn new<T: Into<Identifier>>(name: T, value: Json) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
 /// Gets the vertices associated with edges, or edges associated with
 /// vertices.
 ///
+/// Piping from vertices to their edges scans the edges of all the vertices
+/// in one pass, with one range scan per vertex and edge type in the filter.
+/// For k such scans over a datastore with n edges, returning m of them, this
+/// takes O(k log n + m), so fanning out from a batch of vertices is linear
+/// in the number of edges returned rather than needing a query per vertex.
+///
 /// Generally, you shouldn't need to construct this directly, but rather call
 /// `.outbound()` or `.inbound()`.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct PipeQuery {
     /// The edge query to build off of.
     pub inner: Box<Query>,
@@ -482,8 +1003,9 @@ pub struct PipeQuery {
     /// Limits the number of values to get.
     pub limit: u32,
 
-    /// Filters the type of values returned.
-    pub t: Option<Identifier>,
+    /// Filters the type of values returned. Values matching any of the types
+    /// are returned; if empty, values of any type are returned.
+    pub t_filter: Vec<Identifier>,
 }
 
 nestable_query!(PipeQuery, Pipe);
@@ -503,8 +1025,8 @@ impl PipeQuery {
         Ok(Self {
             inner,
             direction,
-            limit: u32::max_value(),
-            t: None,
+            limit: u32::MAX,
+            t_filter: Vec::new(),
         })
     }
 
@@ -517,26 +1039,67 @@ impl PipeQuery {
             inner: self.inner,
             direction: self.direction,
             limit,
-            t: self.t,
+            t_filter: self.t_filter,
         }
     }
 
-    /// Filter the type of values returned.
+    /// Filter the type of values returned. This can be called multiple times
+    /// to return values matching any of the given types.
     ///
     /// # Arguments
-    /// * `t`: Sets the type filter.
+    /// * `t`: Adds a type to the filter.
     pub fn t(self, t: Identifier) -> Self {
+        let mut t_filter = self.t_filter;
+        t_filter.push(t);
         Self {
             inner: self.inner,
             direction: self.direction,
             limit: self.limit,
-            t: Some(t),
+            t_filter,
+        }
+    }
+
+    /// Constructs a query for the neighbors of a vertex, e.g. the vertices
+    /// that follow a user. This is a shorthand for piping the vertex's edges
+    /// to the vertices at their other ends, which is backed by the edge
+    /// indices of the datastore.
+    ///
+    /// The returned query can be limited via `limit`, and filtered by vertex
+    /// type via `t`.
+    ///
+    /// # Arguments
+    /// * `target`: The ID of the vertex to get the neighbors of.
+    /// * `t_filter`: Filters the type of edges followed; if empty, edges of
+    ///   any type are followed.
+    /// * `direction`: The direction of the edges, from the perspective of
+    ///   `target`: `Inbound` gets the vertices with an edge to `target`, and
+    ///   `Outbound` gets the vertices that `target` has an edge to. `Both`
+    ///   gets both, along with `target` itself if it has any edges.
+    pub fn vertices_with_edge_to(target: Uuid, t_filter: Vec<Identifier>, direction: EdgeDirection) -> Self {
+        // Piped edges are in their stored form, so the neighbors of inbound
+        // edges are their outbound vertices and vice versa
+        let neighbor_direction = match direction {
+            EdgeDirection::Outbound => EdgeDirection::Inbound,
+            EdgeDirection::Inbound => EdgeDirection::Outbound,
+            EdgeDirection::Both => EdgeDirection::Both,
+        };
+        let edges = Self {
+            inner: Box::new(SpecificVertexQuery::single(target).into()),
+            direction,
+            limit: u32::MAX,
+            t_filter,
+        };
+        Self {
+            inner: Box::new(edges.into()),
+            direction: neighbor_direction,
+            limit: u32::MAX,
+            t_filter: Vec::new(),
         }
     }
 }
 
 /// Returns the properties associated with a vertex or edge.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct PipePropertyQuery {
     /// The inner query.
     pub inner: Box<Query>,
@@ -572,8 +1135,11 @@ impl PipePropertyQuery {
     }
 }
 
-/// Gets vertices or edges with or without a property.
-#[derive(Eq, PartialEq, Clone, Debug)]
+/// Gets vertices or edges with or without a property, whatever its value.
+///
+/// This uses the property index if there is one. Otherwise, the property of
+/// each value is looked up, so the property doesn't need to be indexed.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct PipeWithPropertyPresenceQuery {
     /// The query to filter.
     pub inner: Box<Query>,
@@ -606,7 +1172,7 @@ impl PipeWithPropertyPresenceQuery {
 }
 
 /// Gets vertices or edges with a property equal to a given value.
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct PipeWithPropertyValueQuery {
     /// The query to filter.
     pub inner: Box<Query>,
@@ -647,6 +1213,267 @@ impl PipeWithPropertyValueQuery {
     }
 }
 
+/// A predicate to match property values against.
+///
+/// Ordering comparisons follow the ordering of `Json`: numbers are compared
+/// numerically, strings lexically, and arrays and objects element-wise.
+/// Values of different types are incomparable, and never match an ordering
+/// comparison. String matching predicates only match string values.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", content = "value", rename_all = "snake_case")]
+pub enum PropertyPredicate {
+    /// Matches values equal to the given value.
+    Equal(Json),
+    /// Matches values not equal to the given value.
+    NotEqual(Json),
+    /// Matches values greater than the given value.
+    GreaterThan(Json),
+    /// Matches values less than the given value.
+    LessThan(Json),
+    /// Matches values greater than or equal to the given value.
+    GreaterOrEqual(Json),
+    /// Matches values less than or equal to the given value.
+    LessOrEqual(Json),
+    /// Matches strings containing the given substring.
+    Contains(String),
+    /// Matches strings containing a match of the given regex, in the syntax
+    /// of the `regex` crate. Use `(?i)` for case-insensitive matching, and
+    /// anchors such as `^` to match prefixes.
+    Regex(String),
+}
+
+impl PropertyPredicate {
+    /// Checks whether a value matches the predicate. Regexes are compiled on
+    /// each call, so use `matcher` to check many values; invalid regexes
+    /// never match.
+    ///
+    /// # Arguments
+    /// * `value`: The value to check.
+    pub fn matches(&self, value: &Json) -> bool {
+        match self.matcher() {
+            Ok(matcher) => matcher.matches(value),
+            Err(_) => false,
+        }
+    }
+
+    /// Prepares the predicate for checking many values, compiling its regex
+    /// once up front.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidRegex` if the predicate has an invalid regex.
+    pub fn matcher(&self) -> errors::Result<PropertyMatcher<'_>> {
+        let regex = match self {
+            PropertyPredicate::Regex(pattern) => Some(Regex::new(pattern).map_err(errors::Error::InvalidRegex)?),
+            _ => None,
+        };
+        Ok(PropertyMatcher { predicate: self, regex })
+    }
+}
+
+/// A property predicate prepared for checking many values. See
+/// `PropertyPredicate::matcher`.
+#[derive(Clone, Debug)]
+pub struct PropertyMatcher<'a> {
+    predicate: &'a PropertyPredicate,
+    regex: Option<Regex>,
+}
+
+impl<'a> PropertyMatcher<'a> {
+    /// Checks whether a value matches the predicate.
+    ///
+    /// # Arguments
+    /// * `value`: The value to check.
+    pub fn matches(&self, value: &Json) -> bool {
+        match self.predicate {
+            PropertyPredicate::Equal(v) => value == v,
+            PropertyPredicate::NotEqual(v) => value != v,
+            PropertyPredicate::GreaterThan(v) => value > v,
+            PropertyPredicate::LessThan(v) => value < v,
+            PropertyPredicate::GreaterOrEqual(v) => value >= v,
+            PropertyPredicate::LessOrEqual(v) => value <= v,
+            PropertyPredicate::Contains(s) => value.as_str().is_some_and(|value| value.contains(s.as_str())),
+            PropertyPredicate::Regex(_) => match (value.as_str(), &self.regex) {
+                (Some(value), Some(regex)) => regex.is_match(value),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Gets vertices or edges with a property matching a predicate.
+///
+/// Unlike `PipeWithPropertyValueQuery`, this does not use property indexes,
+/// so the property does not need to be indexed. Values without the property
+/// are excluded. Filtering runs in the datastore, so it can follow a
+/// traversal without pulling the unfiltered values back to the client.
+///
+/// Limits are applied in order of nesting: a limit on the inner query caps
+/// the values that are filtered, so fewer than that many may match, whereas
+/// the limit on this query caps the values that match, and filtering stops
+/// once it's reached.
+///
+/// # Examples
+/// ```
+/// use indradb::{ijson, Identifier, PropertyPredicate, QueryExt, SpecificVertexQuery};
+/// use uuid::Uuid;
+/// // A query to return the first 10 vertices an outbound edge away from a
+/// // vertex with an age over 30.
+/// let q = SpecificVertexQuery::single(Uuid::default())
+///     .outbound()
+///     .unwrap()
+///     .inbound()
+///     .unwrap()
+///     .with_property_matching(Identifier::new("age").unwrap(), PropertyPredicate::GreaterThan(ijson!(30)))
+///     .unwrap()
+///     .limit(10);
+/// ```
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct PipeWithPropertyPredicateQuery {
+    /// The query to filter.
+    pub inner: Box<Query>,
+    /// The name of the property.
+    pub name: Identifier,
+    /// The predicate the property value must match.
+    pub predicate: PropertyPredicate,
+    /// Limits the number of matching values to get.
+    #[serde(default = "no_limit", skip_serializing_if = "is_no_limit")]
+    pub limit: u32,
+}
+
+nestable_query!(PipeWithPropertyPredicateQuery, PipeWithPropertyPredicate);
+
+impl PipeWithPropertyPredicateQuery {
+    /// Constructs a new pipe with property predicate query.
+    ///
+    /// # Arguments
+    /// * `inner`: The inner query.
+    /// * `name`: The property name to filter.
+    /// * `predicate`: The predicate the property value must match.
+    pub fn new<T: Into<Identifier>>(
+        inner: Box<Query>,
+        name: T,
+        predicate: PropertyPredicate,
+    ) -> errors::ValidationResult<Self> {
+        match inner.output_type()? {
+            QueryOutputValue::Vertices(_) | QueryOutputValue::Edges(_) => {}
+            _ => return Err(errors::ValidationError::InnerQuery),
+        }
+        Ok(Self {
+            inner,
+            name: name.into(),
+            predicate,
+            limit: u32::MAX,
+        })
+    }
+
+    /// Sets the limit, which is applied after filtering.
+    ///
+    /// # Arguments
+    /// * `limit`: Limits the number of returned results.
+    pub fn limit(self, limit: u32) -> Self {
+        Self { limit, ..self }
+    }
+}
+
+// Queries serialized before `limit` was added to a query have no limit.
+fn no_limit() -> u32 {
+    u32::MAX
+}
+
+// ...and queries without a limit serialize the same as they did then.
+fn is_no_limit(limit: &u32) -> bool {
+    *limit == u32::MAX
+}
+
+/// Gets the vertices reachable from a set of seed vertices within a number of
+/// hops, expanding breadth-first.
+///
+/// The seed vertices themselves are included in the output (they're reachable
+/// in zero hops.) Each vertex is returned at most once, so cycles in the
+/// graph are handled.
+///
+/// # Examples
+/// ```
+/// use indradb::{EdgeDirection, QueryExt, SpecificVertexQuery};
+/// use uuid::Uuid;
+/// // A query to return all vertices within two outbound hops of a vertex.
+/// let q = SpecificVertexQuery::single(Uuid::default()).traverse(EdgeDirection::Outbound, 2);
+/// ```
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TraversalVertexQuery {
+    /// The query for the seed vertices.
+    pub inner: Box<Query>,
+
+    /// Whether to follow outbound or inbound edges.
+    pub direction: EdgeDirection,
+
+    /// The maximum number of hops to take from the seed vertices.
+    pub max_depth: u32,
+
+    /// Limits the number of vertices to get.
+    pub limit: u32,
+
+    /// Filters the type of edges followed. Edges matching any of the types
+    /// are followed; if empty, edges of any type are followed.
+    pub t_filter: Vec<Identifier>,
+}
+
+nestable_query!(TraversalVertexQuery, TraversalVertex);
+
+impl TraversalVertexQuery {
+    /// Constructs a new traversal query.
+    ///
+    /// # Arguments
+    /// * `inner`: The query for the seed vertices.
+    /// * `direction`: Which direction to follow edges in.
+    /// * `max_depth`: The maximum number of hops.
+    pub fn new(inner: Box<Query>, direction: EdgeDirection, max_depth: u32) -> errors::ValidationResult<Self> {
+        match inner.output_type()? {
+            QueryOutputValue::Vertices(_) => {}
+            _ => return Err(errors::ValidationError::InnerQuery),
+        }
+
+        Ok(Self {
+            inner,
+            direction,
+            max_depth,
+            limit: u32::MAX,
+            t_filter: Vec::new(),
+        })
+    }
+
+    /// Sets the limit.
+    ///
+    /// # Arguments
+    /// * `limit`: Limits the number of returned results.
+    pub fn limit(self, limit: u32) -> Self {
+        Self {
+            inner: self.inner,
+            direction: self.direction,
+            max_depth: self.max_depth,
+            limit,
+            t_filter: self.t_filter,
+        }
+    }
+
+    /// Filter the type of edges followed. This can be called multiple times
+    /// to follow edges matching any of the given types.
+    ///
+    /// # Arguments
+    /// * `t`: Adds an edge type to the filter.
+    pub fn t(self, t: Identifier) -> Self {
+        let mut t_filter = self.t_filter;
+        t_filter.push(t);
+        Self {
+            inner: self.inner,
+            direction: self.direction,
+            max_depth: self.max_depth,
+            limit: self.limit,
+            t_filter,
+        }
+    }
+}
+
 /// Includes the results of a query in output.
 ///
 /// The outermost part of a query will always be explicitly included. This
@@ -662,7 +1489,7 @@ impl PipeWithPropertyValueQuery {
 /// // vertices are explicitly included as intermediate results.
 /// let q = AllVertexQuery.include().outbound();
 /// ```
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct IncludeQuery {
     /// The query to export.
     pub inner: Box<Query>,
@@ -688,7 +1515,7 @@ impl IncludeQuery {
 /// // A query to return the total number of vertices in the database.
 /// let q = AllVertexQuery.count();
 /// ```
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct CountQuery {
     /// The query to export.
     pub inner: Box<Query>,
@@ -731,15 +1558,18 @@ pub enum QueryOutputValue {
 #[cfg(test)]
 mod tests {
     use crate::{
-        ijson, AllVertexQuery, CountQuery, CountQueryExt, EdgeDirection, Identifier, PipePropertyQuery, PipeQuery,
-        PipeWithPropertyPresenceQuery, PipeWithPropertyValueQuery, Query, ValidationError,
+        ijson, AllEdgeQuery, AllVertexQuery, CountQuery, CountQueryExt, EdgeDirection, Identifier, PipePropertyQuery,
+        PipeQuery, PipeWithPropertyPredicateQuery, PipeWithPropertyPresenceQuery, PipeWithPropertyValueQuery,
+        PropertyPredicate, Query, QueryExt, RangeVertexQuery, SpecificVertexQuery, TraversalVertexQuery,
+        ValidationError,
     };
     use std::str::FromStr;
+    use std::time::{Duration, SystemTime};
 
     fn expect_inner_query_err<T: core::fmt::Debug>(result: Result<T, ValidationError>) {
         match result {
             Err(ValidationError::InnerQuery) => (),
-            _ => assert!(false, "unexpected result: {:?}", result),
+            _ => panic!("unexpected result: {result:?}"),
         }
     }
 
@@ -747,6 +1577,7 @@ mod tests {
     fn should_convert_str_to_edge_direction() {
         assert_eq!(EdgeDirection::from_str("outbound").unwrap(), EdgeDirection::Outbound);
         assert_eq!(EdgeDirection::from_str("inbound").unwrap(), EdgeDirection::Inbound);
+        assert_eq!(EdgeDirection::from_str("both").unwrap(), EdgeDirection::Both);
         assert!(EdgeDirection::from_str("foo").is_err());
     }
 
@@ -756,6 +1587,22 @@ mod tests {
         assert_eq!(s, "outbound".to_string());
         let s: String = EdgeDirection::Inbound.into();
         assert_eq!(s, "inbound".to_string());
+        let s: String = EdgeDirection::Both.into();
+        assert_eq!(s, "both".to_string());
+    }
+
+    #[test]
+    fn should_limit_query_to_first() {
+        let expected: Query = RangeVertexQuery::new().limit(1).into();
+        assert_eq!(Query::AllVertex.first(), expected);
+        let q: Query = RangeVertexQuery::new().limit(0).into();
+        assert_eq!(q.clone().first(), q);
+        let q = SpecificVertexQuery::new(vec![]).outbound().unwrap();
+        let expected: Query = q.clone().limit(1).into();
+        let q: Query = q.into();
+        assert_eq!(q.first(), expected);
+        let q: Query = SpecificVertexQuery::new(vec![]).into();
+        assert_eq!(q.clone().first(), q);
     }
 
     #[test]
@@ -775,5 +1622,152 @@ mod tests {
             ijson!("bar"),
             true,
         ));
+        expect_inner_query_err(PipeWithPropertyPredicateQuery::new(
+            Box::new(q.clone()),
+            Identifier::new("foo").unwrap(),
+            PropertyPredicate::Equal(ijson!("bar")),
+        ));
+        expect_inner_query_err(TraversalVertexQuery::new(
+            Box::new(q.clone()),
+            EdgeDirection::Outbound,
+            1,
+        ));
+    }
+
+    #[test]
+    fn should_fail_for_traversal_from_edges() {
+        let q: Query = AllEdgeQuery.into();
+        expect_inner_query_err(TraversalVertexQuery::new(Box::new(q), EdgeDirection::Outbound, 1));
+    }
+
+    #[test]
+    fn should_match_property_predicates() {
+        assert!(PropertyPredicate::Equal(ijson!(1)).matches(&ijson!(1)));
+        assert!(!PropertyPredicate::Equal(ijson!(1)).matches(&ijson!("1")));
+        assert!(PropertyPredicate::NotEqual(ijson!(1)).matches(&ijson!("1")));
+        assert!(PropertyPredicate::GreaterThan(ijson!(0.8)).matches(&ijson!(1)));
+        assert!(!PropertyPredicate::GreaterThan(ijson!(1)).matches(&ijson!(1)));
+        assert!(PropertyPredicate::GreaterOrEqual(ijson!(1)).matches(&ijson!(1.0)));
+        assert!(PropertyPredicate::LessThan(ijson!("b")).matches(&ijson!("a")));
+        assert!(PropertyPredicate::LessOrEqual(ijson!(-1)).matches(&ijson!(-2)));
+    }
+
+    #[test]
+    fn should_round_trip_queries_through_json() {
+        use crate::{
+            CreatedRangeVertexQuery, Edge, EdgeWithPropertyPresenceQuery, EdgeWithPropertyValueQuery, RangeDirection,
+            SpecificEdgeQuery, VertexWithLabelQuery, VertexWithPropertyPresenceQuery, VertexWithPropertyValueQuery,
+            WithPropertyValueQuery,
+        };
+        use uuid::Uuid;
+
+        let t = Identifier::new("foo").unwrap();
+        let id = Uuid::from_u128(1);
+        let queries: Vec<Query> = vec![
+            AllVertexQuery.into(),
+            RangeVertexQuery::new()
+                .t(t)
+                .limit(10)
+                .start_id(id)
+                .property_filter(t, ijson!({"a": [1, 2.5, null]}))
+                .direction(RangeDirection::Descending)
+                .into(),
+            SpecificVertexQuery::single(id).into(),
+            VertexWithPropertyPresenceQuery::new(t).into(),
+            VertexWithPropertyValueQuery::new(t, ijson!("bar")).into(),
+            VertexWithLabelQuery::new(t).into(),
+            CreatedRangeVertexQuery::new()
+                .low(SystemTime::UNIX_EPOCH + Duration::from_millis(1_500))
+                .into(),
+            AllEdgeQuery.into(),
+            SpecificEdgeQuery::single(Edge::new(id, t, id)).into(),
+            EdgeWithPropertyPresenceQuery::new(t).into(),
+            EdgeWithPropertyValueQuery::new(t, ijson!(true)).into(),
+            WithPropertyValueQuery::new(t, ijson!(1)).into(),
+            AllVertexQuery.include().both().unwrap().t(t).limit(5).into(),
+            AllEdgeQuery.properties().unwrap().name(t).count().unwrap().into(),
+            AllVertexQuery.without_property(t).unwrap().into(),
+            AllVertexQuery.with_property_not_equal_to(t, ijson!(2)).unwrap().into(),
+            AllEdgeQuery
+                .with_property_matching(t, PropertyPredicate::Regex("^a".to_string()))
+                .unwrap()
+                .into(),
+            SpecificVertexQuery::single(id)
+                .traverse(EdgeDirection::Inbound, 3)
+                .unwrap()
+                .t(t)
+                .into(),
+        ];
+
+        for q in queries {
+            let json = q.to_json_str().unwrap();
+            assert_eq!(Query::from_json_str(&json).unwrap(), q, "{json}");
+        }
+    }
+
+    #[test]
+    fn should_serialize_queries_in_a_stable_shape() {
+        let q: Query = AllVertexQuery
+            .outbound()
+            .unwrap()
+            .with_property_matching(
+                Identifier::new("age").unwrap(),
+                PropertyPredicate::GreaterThan(ijson!(5)),
+            )
+            .unwrap()
+            .into();
+        let json: serde_json::Value = serde_json::from_str(&q.to_json_str().unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "query": {
+                    "type": "pipe_with_property_predicate",
+                    "inner": {
+                        "type": "pipe",
+                        "inner": {"type": "all_vertex"},
+                        "direction": "outbound",
+                        "limit": u32::MAX,
+                        "t_filter": [],
+                    },
+                    "name": "age",
+                    "predicate": {"op": "greater_than", "value": 5},
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_parse_invalid_query_json() {
+        assert!(Query::from_json_str("{").is_err());
+        assert!(Query::from_json_str(r#"{"version": 2, "query": {"type": "all_vertex"}}"#).is_err());
+        assert!(Query::from_json_str(r#"{"version": 1, "query": {"type": "foo"}}"#).is_err());
+        // Counts can't be piped from, even though the JSON is well-formed
+        let json = r#"{"version": 1, "query": {"type": "pipe", "direction": "outbound", "limit": 1, "t_filter": [],
+            "inner": {"type": "count", "inner": {"type": "all_vertex"}}}}"#;
+        assert!(matches!(
+            Query::from_json_str(json),
+            Err(crate::Error::Invalid(ValidationError::InnerQuery))
+        ));
+    }
+
+    #[test]
+    fn should_not_match_incomparable_property_predicates() {
+        assert!(!PropertyPredicate::GreaterThan(ijson!(1)).matches(&ijson!("2")));
+        assert!(!PropertyPredicate::LessThan(ijson!(1)).matches(&ijson!("0")));
+        assert!(!PropertyPredicate::GreaterOrEqual(ijson!(null)).matches(&ijson!(true)));
+        assert!(!PropertyPredicate::LessOrEqual(ijson!([1])).matches(&ijson!({})));
+    }
+
+    #[test]
+    fn should_match_string_property_predicates() {
+        assert!(PropertyPredicate::Contains("li".to_string()).matches(&ijson!("alice")));
+        assert!(!PropertyPredicate::Contains("LI".to_string()).matches(&ijson!("alice")));
+        assert!(!PropertyPredicate::Contains("1".to_string()).matches(&ijson!(1)));
+        assert!(PropertyPredicate::Regex("^al".to_string()).matches(&ijson!("alice")));
+        assert!(PropertyPredicate::Regex("(?i)^AL".to_string()).matches(&ijson!("alice")));
+        assert!(!PropertyPredicate::Regex("^li".to_string()).matches(&ijson!("alice")));
+        assert!(!PropertyPredicate::Regex("1".to_string()).matches(&ijson!(1)));
+        assert!(!PropertyPredicate::Regex("(".to_string()).matches(&ijson!("(")));
     }
 }
@@ -0,0 +1,14 @@
+/// Statistics about a datastore, e.g. for monitoring. Datastores may
+/// estimate values that would be expensive to compute exactly.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DatastoreStats {
+    /// The number of vertices.
+    pub vertex_count: u64,
+    /// The number of edges.
+    pub edge_count: u64,
+    /// The number of indexed properties, including paths to nested fields.
+    pub index_count: u64,
+    /// The approximate size of the datastore in bytes, if known - on disk
+    /// for datastores that persist to disk, or in memory otherwise.
+    pub size_bytes: Option<u64>,
+}
@@ -0,0 +1,632 @@
+//! Instrumentation of transactions, e.g. to record per-operation latencies
+//! and error counts as metrics.
+//!
+//! Wrapping a datastore in an `ObservedDatastore` reports every call of a
+//! `Transaction` method to a `TransactionObserver`, named after the method
+//! so that names are consistent across datastores. Datastores that aren't
+//! wrapped aren't instrumented, so they have no overhead.
+
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::result::Result as StdResult;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::errors::{Error, Result};
+use crate::models::{
+    BulkInsertItem, ChangeFilter, DatastoreStats, Edge, EdgeDirection, Identifier, Json, JsonType, Neighborhood, Query,
+    QueryOutputValue, QueryPlan, Vertex,
+};
+use crate::watch::Watcher;
+use crate::{Datastore, DynIter, Transaction};
+
+use uuid::Uuid;
+
+/// Observes the operations run on transactions of an `ObservedDatastore`.
+pub trait TransactionObserver: Debug + Send + Sync {
+    /// Called after each operation. By default, this does nothing.
+    ///
+    /// For operations that return iterators, the duration only covers
+    /// creating the iterator, not reading from it.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the operation, which is the name of the
+    ///   `Transaction` method, e.g. `create_vertex`.
+    /// * `duration`: How long the operation took.
+    /// * `result`: Whether the operation succeeded.
+    fn on_operation(&self, _name: &'static str, _duration: Duration, _result: StdResult<(), &Error>) {}
+}
+
+/// An observer that ignores all operations.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopObserver;
+
+impl TransactionObserver for NoopObserver {}
+
+// Runs an operation, reporting it to the observer.
+fn observe<T, F: FnOnce() -> Result<T>>(observer: &dyn TransactionObserver, name: &'static str, f: F) -> Result<T> {
+    let start = Instant::now();
+    let result = f();
+    observer.on_operation(name, start.elapsed(), result.as_ref().map(|_| ()));
+    result
+}
+
+// Runs an operation that can't fail, reporting it to the observer.
+fn observe_infallible<T, F: FnOnce() -> T>(observer: &dyn TransactionObserver, name: &'static str, f: F) -> T {
+    let start = Instant::now();
+    let value = f();
+    observer.on_operation(name, start.elapsed(), Ok(()));
+    value
+}
+
+/// A datastore that reports the operations run on its transactions to an
+/// observer.
+///
+/// Every call of a `Transaction` method is reported once, so a single
+/// `Database` call, e.g. `Database::get`, may report several operations.
+#[derive(Clone, Debug)]
+pub struct ObservedDatastore<D: Datastore> {
+    datastore: D,
+    observer: Arc<dyn TransactionObserver>,
+}
+
+impl<D: Datastore> ObservedDatastore<D> {
+    /// Wraps a datastore.
+    ///
+    /// # Arguments
+    /// * `datastore`: The datastore to observe.
+    /// * `observer`: The observer to report operations to.
+    pub fn new<O: TransactionObserver + 'static>(datastore: D, observer: O) -> Self {
+        Self {
+            datastore,
+            observer: Arc::new(observer),
+        }
+    }
+
+    /// Gets the underlying datastore. Operations run on it directly aren't
+    /// observed.
+    pub fn inner(&self) -> &D {
+        &self.datastore
+    }
+
+    /// Unwraps the underlying datastore.
+    pub fn into_inner(self) -> D {
+        self.datastore
+    }
+}
+
+impl<D: Datastore> Datastore for ObservedDatastore<D> {
+    type Transaction<'a>
+        = ObservedTransaction<D::Transaction<'a>>
+    where
+        Self: 'a;
+
+    fn transaction(&self) -> Self::Transaction<'_> {
+        ObservedTransaction {
+            txn: self.datastore.transaction(),
+            observer: self.observer.clone(),
+        }
+    }
+
+    fn readonly_transaction(&self) -> Self::Transaction<'_> {
+        ObservedTransaction {
+            txn: self.datastore.readonly_transaction(),
+            observer: self.observer.clone(),
+        }
+    }
+
+    fn watch(&self, filter: ChangeFilter) -> Result<Watcher> {
+        self.datastore.watch(filter)
+    }
+
+    fn generate_vertex_id(&self, t: Identifier) -> Uuid {
+        self.datastore.generate_vertex_id(t)
+    }
+}
+
+/// A transaction of an `ObservedDatastore`.
+pub struct ObservedTransaction<T> {
+    txn: T,
+    observer: Arc<dyn TransactionObserver>,
+}
+
+impl<'a, T: Transaction<'a>> Transaction<'a> for ObservedTransaction<T> {
+    fn vertex_count(&self) -> u64 {
+        observe_infallible(&*self.observer, "vertex_count", || self.txn.vertex_count())
+    }
+
+    fn vertex_count_by_type(&self, t: Identifier) -> Result<u64> {
+        observe(&*self.observer, "vertex_count_by_type", || {
+            self.txn.vertex_count_by_type(t)
+        })
+    }
+
+    fn vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        observe(&*self.observer, "vertex_property_names", || {
+            self.txn.vertex_property_names()
+        })
+    }
+
+    fn edge_property_names(&self) -> Result<Vec<Identifier>> {
+        observe(&*self.observer, "edge_property_names", || {
+            self.txn.edge_property_names()
+        })
+    }
+
+    fn indexed_properties(&self) -> Result<Vec<Identifier>> {
+        observe(&*self.observer, "indexed_properties", || self.txn.indexed_properties())
+    }
+
+    fn property_schemas(&self) -> Result<Vec<(Identifier, JsonType)>> {
+        observe(&*self.observer, "property_schemas", || self.txn.property_schemas())
+    }
+
+    fn stats(&self) -> Result<DatastoreStats> {
+        observe(&*self.observer, "stats", || self.txn.stats())
+    }
+
+    fn all_vertices(&'a self) -> Result<DynIter<'a, Vertex>> {
+        observe(&*self.observer, "all_vertices", || self.txn.all_vertices())
+    }
+
+    fn range_vertices(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        observe(&*self.observer, "range_vertices", || self.txn.range_vertices(offset))
+    }
+
+    fn range_vertices_descending(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        observe(&*self.observer, "range_vertices_descending", || {
+            self.txn.range_vertices_descending(offset)
+        })
+    }
+
+    fn specific_vertices(&'a self, ids: Vec<Uuid>) -> Result<DynIter<'a, Vertex>> {
+        observe(&*self.observer, "specific_vertices", || self.txn.specific_vertices(ids))
+    }
+
+    fn get_vertex(&'a self, id: Uuid) -> Result<Option<Vertex>> {
+        observe(&*self.observer, "get_vertex", || self.txn.get_vertex(id))
+    }
+
+    fn contains_vertex(&'a self, id: Uuid) -> Result<bool> {
+        observe(&*self.observer, "contains_vertex", || self.txn.contains_vertex(id))
+    }
+
+    fn vertex_ids_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Uuid>>> {
+        observe(&*self.observer, "vertex_ids_with_property", || {
+            self.txn.vertex_ids_with_property(name)
+        })
+    }
+
+    fn vertex_ids_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Uuid>>> {
+        observe(&*self.observer, "vertex_ids_with_property_value", || {
+            self.txn.vertex_ids_with_property_value(name, value)
+        })
+    }
+
+    fn vertex_labels(&self, id: Uuid) -> Result<BTreeSet<Identifier>> {
+        observe(&*self.observer, "vertex_labels", || self.txn.vertex_labels(id))
+    }
+
+    fn vertex_ids_with_label(&'a self, label: Identifier) -> Result<DynIter<'a, Uuid>> {
+        observe(&*self.observer, "vertex_ids_with_label", || {
+            self.txn.vertex_ids_with_label(label)
+        })
+    }
+
+    fn vertex_created_at(&self, id: Uuid) -> Result<Option<SystemTime>> {
+        observe(&*self.observer, "vertex_created_at", || self.txn.vertex_created_at(id))
+    }
+
+    fn vertex_ids_created_in_range(
+        &'a self,
+        low: Option<SystemTime>,
+        high: Option<SystemTime>,
+    ) -> Result<DynIter<'a, Uuid>> {
+        observe(&*self.observer, "vertex_ids_created_in_range", || {
+            self.txn.vertex_ids_created_in_range(low, high)
+        })
+    }
+
+    fn stream_vertices(&'a self, q: Query) -> Result<DynIter<'a, Vertex>> {
+        observe(&*self.observer, "stream_vertices", || self.txn.stream_vertices(q))
+    }
+
+    fn get(&'a self, q: Query) -> Result<Vec<QueryOutputValue>> {
+        observe(&*self.observer, "get", || self.txn.get(q))
+    }
+
+    fn explain(&'a self, q: Query) -> Result<QueryPlan> {
+        observe(&*self.observer, "explain", || self.txn.explain(q))
+    }
+
+    fn edge_count(&self) -> u64 {
+        observe_infallible(&*self.observer, "edge_count", || self.txn.edge_count())
+    }
+
+    fn all_edges(&'a self) -> Result<DynIter<'a, Edge>> {
+        observe(&*self.observer, "all_edges", || self.txn.all_edges())
+    }
+
+    fn range_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>> {
+        observe(&*self.observer, "range_edges", || self.txn.range_edges(offset))
+    }
+
+    fn range_reversed_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>> {
+        observe(&*self.observer, "range_reversed_edges", || {
+            self.txn.range_reversed_edges(offset)
+        })
+    }
+
+    fn range_edges_for_vertices(
+        &'a self,
+        roots: Vec<(Uuid, Option<Identifier>)>,
+        reversed: bool,
+    ) -> Result<DynIter<'a, Edge>> {
+        observe(&*self.observer, "range_edges_for_vertices", || {
+            self.txn.range_edges_for_vertices(roots, reversed)
+        })
+    }
+
+    fn specific_edges(&'a self, edges: Vec<Edge>) -> Result<DynIter<'a, Edge>> {
+        observe(&*self.observer, "specific_edges", || self.txn.specific_edges(edges))
+    }
+
+    fn get_edge(&'a self, edge: &Edge) -> Result<Option<Edge>> {
+        observe(&*self.observer, "get_edge", || self.txn.get_edge(edge))
+    }
+
+    fn contains_edge(&'a self, edge: &Edge) -> Result<bool> {
+        observe(&*self.observer, "contains_edge", || self.txn.contains_edge(edge))
+    }
+
+    fn edges_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Edge>>> {
+        observe(&*self.observer, "edges_with_property", || {
+            self.txn.edges_with_property(name)
+        })
+    }
+
+    fn edges_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Edge>>> {
+        observe(&*self.observer, "edges_with_property_value", || {
+            self.txn.edges_with_property_value(name, value)
+        })
+    }
+
+    fn vertex_property(&self, vertex: &Vertex, name: Identifier) -> Result<Option<Json>> {
+        observe(&*self.observer, "vertex_property", || {
+            self.txn.vertex_property(vertex, name)
+        })
+    }
+
+    fn all_vertex_properties_for_vertex(&'a self, vertex: &Vertex) -> Result<DynIter<'a, (Identifier, Json)>> {
+        observe(&*self.observer, "all_vertex_properties_for_vertex", || {
+            self.txn.all_vertex_properties_for_vertex(vertex)
+        })
+    }
+
+    fn edge_property(&self, edge: &Edge, name: Identifier) -> Result<Option<Json>> {
+        observe(&*self.observer, "edge_property", || self.txn.edge_property(edge, name))
+    }
+
+    fn all_edge_properties_for_edge(&'a self, edge: &Edge) -> Result<DynIter<'a, (Identifier, Json)>> {
+        observe(&*self.observer, "all_edge_properties_for_edge", || {
+            self.txn.all_edge_properties_for_edge(edge)
+        })
+    }
+
+    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<u64> {
+        observe(&*self.observer, "delete_vertices", || {
+            self.txn.delete_vertices(vertices)
+        })
+    }
+
+    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<u64> {
+        observe(&*self.observer, "delete_edges", || self.txn.delete_edges(edges))
+    }
+
+    fn delete_vertex_properties(&mut self, props: Vec<(Uuid, Identifier)>) -> Result<()> {
+        observe(&*self.observer, "delete_vertex_properties", || {
+            self.txn.delete_vertex_properties(props)
+        })
+    }
+
+    fn delete_edge_properties(&mut self, props: Vec<(Edge, Identifier)>) -> Result<()> {
+        observe(&*self.observer, "delete_edge_properties", || {
+            self.txn.delete_edge_properties(props)
+        })
+    }
+
+    fn sync(&self) -> Result<()> {
+        observe(&*self.observer, "sync", || self.txn.sync())
+    }
+
+    fn compact(&self) -> Result<()> {
+        observe(&*self.observer, "compact", || self.txn.compact())
+    }
+
+    fn commit(self) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let txn = self.txn;
+        observe(&*self.observer, "commit", move || txn.commit())
+    }
+
+    fn rollback(self) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let txn = self.txn;
+        observe(&*self.observer, "rollback", move || txn.rollback())
+    }
+
+    fn create_vertex(&mut self, vertex: &Vertex) -> Result<bool> {
+        observe(&*self.observer, "create_vertex", || self.txn.create_vertex(vertex))
+    }
+
+    fn create_vertex_with_properties(&mut self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        observe(&*self.observer, "create_vertex_with_properties", || {
+            self.txn.create_vertex_with_properties(vertex, props)
+        })
+    }
+
+    fn create_vertex_with_labels(&mut self, vertex: &Vertex, labels: BTreeSet<Identifier>) -> Result<bool> {
+        observe(&*self.observer, "create_vertex_with_labels", || {
+            self.txn.create_vertex_with_labels(vertex, labels)
+        })
+    }
+
+    fn upsert_vertex(&mut self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        observe(&*self.observer, "upsert_vertex", || {
+            self.txn.upsert_vertex(vertex, props)
+        })
+    }
+
+    fn create_edge(&mut self, edge: &Edge) -> Result<bool> {
+        observe(&*self.observer, "create_edge", || self.txn.create_edge(edge))
+    }
+
+    fn create_edge_with_ttl(&mut self, edge: &Edge, ttl: Duration) -> Result<bool> {
+        observe(&*self.observer, "create_edge_with_ttl", || {
+            self.txn.create_edge_with_ttl(edge, ttl)
+        })
+    }
+
+    fn create_edge_if_absent(&mut self, edge: &Edge) -> Result<bool> {
+        observe(&*self.observer, "create_edge_if_absent", || {
+            self.txn.create_edge_if_absent(edge)
+        })
+    }
+
+    fn create_symmetric_edge(&mut self, edge: &Edge) -> Result<bool> {
+        observe(&*self.observer, "create_symmetric_edge", || {
+            self.txn.create_symmetric_edge(edge)
+        })
+    }
+
+    fn delete_symmetric_edge(&mut self, edge: &Edge) -> Result<u64> {
+        observe(&*self.observer, "delete_symmetric_edge", || {
+            self.txn.delete_symmetric_edge(edge)
+        })
+    }
+
+    fn bulk_insert(&mut self, items: Vec<BulkInsertItem>) -> Result<()> {
+        observe(&*self.observer, "bulk_insert", || self.txn.bulk_insert(items))
+    }
+
+    fn bulk_insert_checked(&mut self, items: Vec<BulkInsertItem>) -> Result<()>
+    where
+        Self: 'a,
+    {
+        observe(&*self.observer, "bulk_insert_checked", || {
+            self.txn.bulk_insert_checked(items)
+        })
+    }
+
+    fn shortest_path(
+        &'a self,
+        from: Uuid,
+        to: Uuid,
+        max_depth: u32,
+        t: Option<Identifier>,
+        direction: Option<EdgeDirection>,
+    ) -> Result<Option<Vec<Edge>>> {
+        observe(&*self.observer, "shortest_path", || {
+            self.txn.shortest_path(from, to, max_depth, t, direction)
+        })
+    }
+
+    fn vertex_neighborhood(
+        &'a self,
+        id: Uuid,
+        t_filter: Option<Identifier>,
+        limit: u32,
+    ) -> Result<Option<Neighborhood>> {
+        observe(&*self.observer, "vertex_neighborhood", || {
+            self.txn.vertex_neighborhood(id, t_filter, limit)
+        })
+    }
+
+    fn edge_counts(&'a self, id: Uuid, t_filter: Option<Identifier>) -> Result<(u64, u64)> {
+        observe(&*self.observer, "edge_counts", || self.txn.edge_counts(id, t_filter))
+    }
+
+    fn top_vertices_by_degree(
+        &'a self,
+        direction: EdgeDirection,
+        limit: usize,
+        t_filter: Option<Identifier>,
+    ) -> Result<Vec<(Uuid, u64)>> {
+        observe(&*self.observer, "top_vertices_by_degree", || {
+            self.txn.top_vertices_by_degree(direction, limit, t_filter)
+        })
+    }
+
+    fn index_property(&mut self, name: Identifier) -> Result<()> {
+        observe(&*self.observer, "index_property", || self.txn.index_property(name))
+    }
+
+    fn drop_index(&mut self, name: Identifier) -> Result<()> {
+        observe(&*self.observer, "drop_index", || self.txn.drop_index(name))
+    }
+
+    fn register_property_schema(&mut self, name: Identifier, expected: JsonType) -> Result<()> {
+        observe(&*self.observer, "register_property_schema", || {
+            self.txn.register_property_schema(name, expected)
+        })
+    }
+
+    fn clear(&mut self) -> Result<()>
+    where
+        Self: 'a,
+    {
+        observe(&*self.observer, "clear", || self.txn.clear())
+    }
+
+    fn set_vertex_properties(&mut self, vertices: Vec<Uuid>, name: Identifier, value: &Json) -> Result<()> {
+        observe(&*self.observer, "set_vertex_properties", || {
+            self.txn.set_vertex_properties(vertices, name, value)
+        })
+    }
+
+    fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()> {
+        observe(&*self.observer, "set_edge_properties", || {
+            self.txn.set_edge_properties(edges, name, value)
+        })
+    }
+
+    fn set_vertex_properties_batch(&mut self, vertices: Vec<Uuid>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        observe(&*self.observer, "set_vertex_properties_batch", || {
+            self.txn.set_vertex_properties_batch(vertices, props)
+        })
+    }
+
+    fn set_edge_properties_batch(&mut self, edges: Vec<Edge>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        observe(&*self.observer, "set_edge_properties_batch", || {
+            self.txn.set_edge_properties_batch(edges, props)
+        })
+    }
+
+    fn add_vertex_labels(&mut self, vertices: Vec<Uuid>, label: Identifier) -> Result<()> {
+        observe(&*self.observer, "add_vertex_labels", || {
+            self.txn.add_vertex_labels(vertices, label)
+        })
+    }
+
+    fn remove_vertex_labels(&mut self, vertices: Vec<Uuid>, label: Identifier) -> Result<()> {
+        observe(&*self.observer, "remove_vertex_labels", || {
+            self.txn.remove_vertex_labels(vertices, label)
+        })
+    }
+
+    fn increment_vertex_properties(&mut self, vertices: Vec<Vertex>, name: Identifier, delta: f64) -> Result<()> {
+        observe(&*self.observer, "increment_vertex_properties", || {
+            self.txn.increment_vertex_properties(vertices, name, delta)
+        })
+    }
+
+    fn merge_vertex_properties(&mut self, vertices: Vec<Vertex>, name: Identifier, patch: &Json) -> Result<()> {
+        observe(&*self.observer, "merge_vertex_properties", || {
+            self.txn.merge_vertex_properties(vertices, name, patch)
+        })
+    }
+
+    fn compare_and_set_vertex_properties(
+        &mut self,
+        vertices: Vec<Vertex>,
+        name: Identifier,
+        expected: Option<&Json>,
+        new: &Json,
+    ) -> Result<bool> {
+        observe(&*self.observer, "compare_and_set_vertex_properties", || {
+            self.txn
+                .compare_and_set_vertex_properties(vertices, name, expected, new)
+        })
+    }
+
+    fn copy_vertex_properties(&mut self, from: &Vertex, to: &Vertex, overwrite: bool) -> Result<()>
+    where
+        Self: 'a,
+    {
+        observe(&*self.observer, "copy_vertex_properties", || {
+            self.txn.copy_vertex_properties(from, to, overwrite)
+        })
+    }
+
+    fn delete_all_vertex_properties(&mut self, vertices: Vec<Vertex>) -> Result<u64>
+    where
+        Self: 'a,
+    {
+        observe(&*self.observer, "delete_all_vertex_properties", || {
+            self.txn.delete_all_vertex_properties(vertices)
+        })
+    }
+
+    fn delete_all_edge_properties(&mut self, edges: Vec<Edge>) -> Result<u64>
+    where
+        Self: 'a,
+    {
+        observe(&*self.observer, "delete_all_edge_properties", || {
+            self.txn.delete_all_edge_properties(edges)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "test-suite")]
+    use super::NoopObserver;
+    use super::{ObservedDatastore, TransactionObserver};
+    use crate::{ijson, Database, Datastore, Error, Identifier, MemoryDatastore, SpecificVertexQuery, Transaction};
+    use std::result::Result as StdResult;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    // Observing shouldn't change how the datastore behaves
+    #[cfg(feature = "test-suite")]
+    full_test_impl!(Database::new(ObservedDatastore::new(
+        MemoryDatastore::new_db().datastore,
+        NoopObserver
+    )));
+
+    #[derive(Debug, Default)]
+    struct RecordingObserver(Arc<Mutex<Vec<(&'static str, bool)>>>);
+
+    impl TransactionObserver for RecordingObserver {
+        fn on_operation(&self, name: &'static str, _duration: Duration, result: StdResult<(), &Error>) {
+            self.0.lock().unwrap().push((name, result.is_ok()));
+        }
+    }
+
+    #[test]
+    fn should_observe_operations() {
+        let operations = Arc::new(Mutex::new(Vec::new()));
+        let datastore = ObservedDatastore::new(
+            MemoryDatastore::new_db().datastore,
+            RecordingObserver(operations.clone()),
+        );
+        let db = Database::new(datastore);
+        let id = db.create_vertex_from_type(Identifier::default()).unwrap();
+        db.set_properties(SpecificVertexQuery::single(id), Identifier::default(), &ijson!(1))
+            .unwrap();
+        assert!(operations.lock().unwrap().contains(&("create_vertex", true)));
+        assert!(operations.lock().unwrap().contains(&("set_vertex_properties", true)));
+
+        // Failures are reported too
+        operations.lock().unwrap().clear();
+        let mut txn = db.datastore.readonly_transaction();
+        assert!(matches!(
+            txn.index_property(Identifier::default()),
+            Err(Error::ReadOnly)
+        ));
+        txn.commit().unwrap();
+        assert_eq!(
+            *operations.lock().unwrap(),
+            vec![("index_property", false), ("commit", true)]
+        );
+
+        // Operations on the underlying datastore aren't observed
+        operations.lock().unwrap().clear();
+        db.datastore.inner().transaction().vertex_count();
+        assert!(operations.lock().unwrap().is_empty());
+    }
+}
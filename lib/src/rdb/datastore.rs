@@ -1,17 +1,93 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::i32;
+use std::mem;
 use std::path::Path;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::{Duration, SystemTime};
 use std::u64;
 
 use super::managers::*;
-use crate::errors::Result;
-use crate::{BulkInsertItem, Database, Datastore, DynIter, Edge, Identifier, Json, Transaction, Vertex};
-
-use rocksdb::{DBCompactionStyle, Options, WriteBatch, DB};
+use crate::errors::{Error, Result};
+use crate::util::{self, increment_json};
+use crate::{
+    BulkInsertItem, Database, Datastore, DatastoreStats, DynIter, Edge, Identifier, Json, JsonType, Transaction,
+    UuidV1Generator, Vertex, VertexIdGenerator,
+};
+
+use rocksdb::{BlockBasedOptions, Cache, ColumnFamilyRef, DBCompactionStyle, IteratorMode, Options, DB};
 use uuid::Uuid;
 
-const CF_NAMES: [&str; 8] = [
+fn increment_count(counts: &mut HashMap<Identifier, u64>, key: Identifier) {
+    *counts.entry(key).or_insert(0) += 1;
+}
+
+fn decrement_count(counts: &mut HashMap<Identifier, u64>, key: Identifier) {
+    if let Some(count) = counts.get_mut(&key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(&key);
+        }
+    }
+}
+
+// Drops all but the last value for each property name, since property
+// writes in the same batch can't see each other.
+fn dedup_properties(props: Vec<(Identifier, Json)>) -> Vec<(Identifier, Json)> {
+    let mut seen = HashSet::new();
+    let mut deduped: Vec<(Identifier, Json)> = props.into_iter().rev().filter(|(name, _)| seen.insert(*name)).collect();
+    deduped.reverse();
+    deduped
+}
+
+// Loads the per-type vertex counts, computing them with a full scan for
+// databases created before they were tracked.
+fn load_vertex_type_counts(db: &DB, namespace: Option<Identifier>) -> Result<HashMap<Identifier, u64>> {
+    let view = DbView::new(db);
+    let metadata_manager = MetadataManager::new(&view, namespace);
+    if let Some(counts) = metadata_manager.get_vertex_type_counts()? {
+        return Ok(counts);
+    }
+
+    let vertex_manager = VertexManager::new(&view, namespace);
+    let mut counts = HashMap::new();
+    for item in vertex_manager.iterate_for_range(Uuid::default()) {
+        increment_count(&mut counts, item?.t);
+    }
+
+    let mut batch = Batch::default();
+    metadata_manager.set_vertex_type_counts(&mut batch, &counts)?;
+    view.write(batch);
+    view.commit(&column_families(db, namespace), false)?;
+    Ok(counts)
+}
+
+// Loads the per-name property counts, computing them with a full scan for
+// databases created before they were tracked.
+fn load_property_name_counts(db: &DB, namespace: Option<Identifier>) -> Result<PropertyNameCounts> {
+    let view = DbView::new(db);
+    let metadata_manager = MetadataManager::new(&view, namespace);
+    if let Some(counts) = metadata_manager.get_property_name_counts()? {
+        return Ok(counts);
+    }
+
+    let mut counts = PropertyNameCounts::default();
+    let vertex_property_manager = VertexPropertyManager::new(&view, namespace);
+    for item in vertex_property_manager.iterate_for_all_names() {
+        increment_count(&mut counts.vertex, item?);
+    }
+    let edge_property_manager = EdgePropertyManager::new(&view, namespace);
+    for item in edge_property_manager.iterate_for_all_names() {
+        increment_count(&mut counts.edge, item?);
+    }
+
+    let mut batch = Batch::default();
+    metadata_manager.set_property_name_counts(&mut batch, &counts)?;
+    view.write(batch);
+    view.commit(&column_families(db, namespace), false)?;
+    Ok(counts)
+}
+
+const CF_NAMES: [&str; 11] = [
     "vertices:v2",
     "edge_ranges:v2",
     "reversed_edge_ranges:v2",
@@ -20,11 +96,38 @@ const CF_NAMES: [&str; 8] = [
     "vertex_property_values:v2",
     "edge_property_values:v2",
     "metadata:v2",
+    "vertex_labels:v2",
+    "label_vertices:v2",
+    "created_vertices:v2",
 ];
 
+fn column_families(db: &DB, namespace: Option<Identifier>) -> Vec<ColumnFamilyRef<'_>> {
+    CF_NAMES
+        .iter()
+        .map(|name| db.cf_handle(&cf_name(namespace, name)).unwrap())
+        .collect()
+}
+
+// The metadata that's kept in memory, as of the last commit.
+#[derive(Clone, Debug, Default)]
+struct Metadata {
+    indexed_properties: HashSet<Identifier>,
+    property_schemas: HashMap<Identifier, JsonType>,
+    vertex_type_counts: HashMap<Identifier, u64>,
+    property_name_counts: PropertyNameCounts,
+}
+
 pub struct RocksdbTransaction<'a> {
     db: &'a DB,
-    indexed_properties: Arc<RwLock<HashSet<Identifier>>>,
+    view: DbView<'a>,
+    namespace: Option<Identifier>,
+    metadata: &'a RwLock<Metadata>,
+    // The transaction's copies of the metadata, which are published on
+    // commit
+    indexed_properties: RwLock<HashSet<Identifier>>,
+    property_schemas: RwLock<HashMap<Identifier, JsonType>>,
+    vertex_type_counts: Mutex<HashMap<Identifier, u64>>,
+    property_name_counts: Mutex<PropertyNameCounts>,
     vertex_manager: VertexManager<'a>,
     edge_manager: EdgeManager<'a>,
     edge_range_manager: EdgeRangeManager<'a>,
@@ -33,10 +136,48 @@ pub struct RocksdbTransaction<'a> {
     edge_property_manager: EdgePropertyManager<'a>,
     vertex_property_value_manager: VertexPropertyValueManager<'a>,
     edge_property_value_manager: EdgePropertyValueManager<'a>,
+    vertex_label_manager: VertexLabelManager<'a>,
     metadata_manager: MetadataManager<'a>,
+    column_families: Vec<ColumnFamilyRef<'a>>,
+    readonly: bool,
+    sync_writes: bool,
+    // Range deletes only leave tombstones, so these are compacted after
+    // commit to free the space
+    dropped_indexes: Mutex<Vec<Identifier>>,
+    cleared: Mutex<bool>,
+    // Writable transactions hold the datastore's write lock until they end,
+    // so that they can't see or overwrite each other's pending writes
+    _write_guard: Option<MutexGuard<'a, ()>>,
 }
 
 impl<'a> RocksdbTransaction<'a> {
+    fn check_writable(&self) -> Result<()> {
+        if self.readonly {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Adds a batch to the transaction's pending writes. Reads see them
+    // straight away, but they're only written to the database on commit.
+    fn write(&self, batch: Batch) -> Result<()> {
+        self.view.write(batch);
+        Ok(())
+    }
+
+    fn compact_all(&self) {
+        self.vertex_manager.compact();
+        self.edge_range_manager.compact();
+        self.reversed_edge_range_manager.compact();
+        self.vertex_property_manager.compact();
+        self.edge_property_manager.compact();
+        self.vertex_property_value_manager.compact();
+        self.edge_property_value_manager.compact();
+        self.vertex_label_manager.compact();
+        self.metadata_manager.compact();
+    }
+
     fn vertex_ids_from_property_value_iterator(
         &'a self,
         iter: impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a,
@@ -50,6 +191,48 @@ impl<'a> RocksdbTransaction<'a> {
             Err(err) => Some(Err(err)),
         })
     }
+
+    fn edges_from_property_value_iterator(
+        &'a self,
+        iter: impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a,
+    ) -> impl Iterator<Item = Result<Edge>> + 'a {
+        iter.filter_map(|item| match item {
+            Ok((_, _, edge)) => match self.edge_range_manager.contains(&edge) {
+                Ok(true) => Some(Ok(edge)),
+                Ok(false) => None,
+                Err(err) => Some(Err(err)),
+            },
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    fn uncount_vertex_properties(&self, counts: &mut PropertyNameCounts, id: Uuid) -> Result<()> {
+        for item in self.vertex_property_manager.iterate_for_owner(id)? {
+            let (_, name, _) = item?;
+            decrement_count(&mut counts.vertex, name);
+        }
+        Ok(())
+    }
+
+    fn uncount_edge_properties(&self, counts: &mut PropertyNameCounts, edge: &Edge) -> Result<()> {
+        for item in self.edge_property_manager.iterate_for_owner(edge)? {
+            let (_, name, _) = item?;
+            decrement_count(&mut counts.edge, name);
+        }
+        Ok(())
+    }
+
+    fn set_edge(&mut self, edge: &Edge, expires_at: Option<SystemTime>) -> Result<bool> {
+        self.check_writable()?;
+        if !self.vertex_manager.exists(edge.outbound_id)? || !self.vertex_manager.exists(edge.inbound_id)? {
+            Ok(false)
+        } else {
+            let mut batch = Batch::default();
+            self.edge_manager.set(&mut batch, edge, expires_at)?;
+            self.write(batch)?;
+            Ok(true)
+        }
+    }
 }
 
 impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
@@ -58,6 +241,72 @@ impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
         iter.count() as u64
     }
 
+    fn vertex_count_by_type(&self, t: Identifier) -> Result<u64> {
+        let vertex_type_counts = self.vertex_type_counts.lock().unwrap();
+        Ok(vertex_type_counts.get(&t).copied().unwrap_or(0))
+    }
+
+    fn vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        let property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut names: Vec<Identifier> = property_name_counts.vertex.keys().copied().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn edge_property_names(&self) -> Result<Vec<Identifier>> {
+        let property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut names: Vec<Identifier> = property_name_counts.edge.keys().copied().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn indexed_properties(&self) -> Result<Vec<Identifier>> {
+        // This mirrors the indexed properties persisted in the metadata
+        let indexed_properties = self.indexed_properties.read().unwrap();
+        let mut names: Vec<Identifier> = indexed_properties.iter().copied().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn property_schemas(&self) -> Result<Vec<(Identifier, JsonType)>> {
+        // This mirrors the property schemas persisted in the metadata
+        let property_schemas = self.property_schemas.read().unwrap();
+        let mut schemas: Vec<(Identifier, JsonType)> = property_schemas
+            .iter()
+            .map(|(name, expected)| (*name, *expected))
+            .collect();
+        schemas.sort_by_key(|(name, _)| *name);
+        Ok(schemas)
+    }
+
+    fn stats(&self) -> Result<DatastoreStats> {
+        let index_count = self.indexed_properties.read().unwrap().len() as u64;
+        let vertex_count = self.vertex_type_counts.lock().unwrap().values().sum();
+
+        // Counting edges exactly takes a full scan, so use RocksDB's estimate
+        let edge_ranges_cf = self.db.cf_handle(&cf_name(self.namespace, "edge_ranges:v2")).unwrap();
+        let edge_count = self
+            .db
+            .property_int_value_cf(edge_ranges_cf, "rocksdb.estimate-num-keys")?
+            .unwrap_or(0);
+
+        let mut size_bytes = 0;
+        for name in &CF_NAMES {
+            let cf = self.db.cf_handle(&cf_name(self.namespace, name)).unwrap();
+            size_bytes += self
+                .db
+                .property_int_value_cf(cf, "rocksdb.total-sst-files-size")?
+                .unwrap_or(0);
+        }
+
+        Ok(DatastoreStats {
+            vertex_count,
+            edge_count,
+            index_count,
+            size_bytes: Some(size_bytes),
+        })
+    }
+
     fn all_vertices(&'a self) -> Result<DynIter<'a, Vertex>> {
         let iter = self.vertex_manager.iterate_for_range(Uuid::default());
         Ok(Box::new(iter))
@@ -68,6 +317,11 @@ impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
         Ok(Box::new(iter))
     }
 
+    fn range_vertices_descending(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        let iter = self.vertex_manager.iterate_for_range_descending(offset);
+        Ok(Box::new(iter))
+    }
+
     fn specific_vertices(&'a self, ids: Vec<Uuid>) -> Result<DynIter<'a, Vertex>> {
         let iter = ids.into_iter().filter_map(move |id| match self.vertex_manager.get(id) {
             Ok(Some(t)) => Some(Ok(Vertex::with_id(id, t))),
@@ -78,6 +332,52 @@ impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
         Ok(Box::new(iter))
     }
 
+    fn get_vertex(&'a self, id: Uuid) -> Result<Option<Vertex>> {
+        Ok(self.vertex_manager.get(id)?.map(|t| Vertex::with_id(id, t)))
+    }
+
+    fn contains_vertex(&'a self, id: Uuid) -> Result<bool> {
+        self.vertex_manager.exists(id)
+    }
+
+    fn vertex_labels(&self, id: Uuid) -> Result<BTreeSet<Identifier>> {
+        self.vertex_label_manager.iterate_for_vertex(id).collect()
+    }
+
+    fn vertex_ids_with_label(&'a self, label: Identifier) -> Result<DynIter<'a, Uuid>> {
+        Ok(Box::new(self.vertex_label_manager.iterate_for_label(label)))
+    }
+
+    fn vertex_created_at(&self, id: Uuid) -> Result<Option<SystemTime>> {
+        Ok(self.vertex_manager.get_created_at(id)?.map(time_from_millis))
+    }
+
+    fn vertex_ids_created_in_range(
+        &'a self,
+        low: Option<SystemTime>,
+        high: Option<SystemTime>,
+    ) -> Result<DynIter<'a, Uuid>> {
+        let low = low.map_or(0, millis_since_epoch);
+        let high = high.map(millis_since_epoch);
+        let iter = self
+            .vertex_manager
+            .iterate_for_created(low)
+            .take_while(move |item| match (item, high) {
+                (Ok((created_at, _)), Some(high)) => *created_at < high,
+                _ => true,
+            })
+            .filter_map(move |item| match item {
+                // Skip stale index entries
+                Ok((created_at, id)) => match self.vertex_manager.get_created_at(id) {
+                    Ok(Some(actual)) if actual == created_at => Some(Ok(id)),
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                },
+                Err(err) => Some(Err(err)),
+            });
+        Ok(Box::new(iter))
+    }
+
     fn vertex_ids_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Uuid>>> {
         if self.indexed_properties.read().unwrap().contains(&name) {
             let iter = self.vertex_property_value_manager.iterate_for_name(name);
@@ -122,6 +422,19 @@ impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
         Ok(Box::new(iter))
     }
 
+    fn range_edges_for_vertices(
+        &'a self,
+        roots: Vec<(Uuid, Option<Identifier>)>,
+        reversed: bool,
+    ) -> Result<DynIter<'a, Edge>> {
+        let manager = if reversed {
+            &self.reversed_edge_range_manager
+        } else {
+            &self.edge_range_manager
+        };
+        Ok(Box::new(manager.iterate_for_roots(roots)))
+    }
+
     fn specific_edges(&'a self, edges: Vec<Edge>) -> Result<DynIter<'a, Edge>> {
         let iter = edges
             .into_iter()
@@ -134,15 +447,14 @@ impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
         Ok(Box::new(iter))
     }
 
+    fn contains_edge(&'a self, edge: &Edge) -> Result<bool> {
+        self.edge_range_manager.contains(edge)
+    }
+
     fn edges_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Edge>>> {
         if self.indexed_properties.read().unwrap().contains(&name) {
-            let iter = self
-                .edge_property_value_manager
-                .iterate_for_name(name)
-                .map(|r| match r {
-                    Ok((_, _, e)) => Ok(e),
-                    Err(err) => Err(err),
-                });
+            let iter = self.edge_property_value_manager.iterate_for_name(name);
+            let iter = self.edges_from_property_value_iterator(iter);
             Ok(Some(Box::new(iter)))
         } else {
             Ok(None)
@@ -151,13 +463,8 @@ impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
 
     fn edges_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Edge>>> {
         if self.indexed_properties.read().unwrap().contains(&name) {
-            let iter = self
-                .edge_property_value_manager
-                .iterate_for_value(name, value)
-                .map(|r| match r {
-                    Ok((_, _, e)) => Ok(e),
-                    Err(err) => Err(err),
-                });
+            let iter = self.edge_property_value_manager.iterate_for_value(name, value);
+            let iter = self.edges_from_property_value_iterator(iter);
             Ok(Some(Box::new(iter)))
         } else {
             Ok(None)
@@ -192,180 +499,766 @@ impl<'a> Transaction<'a> for RocksdbTransaction<'a> {
         Ok(Box::new(iter))
     }
 
-    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<()> {
+    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<u64> {
+        self.check_writable()?;
         let indexed_properties = self.indexed_properties.read().unwrap();
-        let mut batch = WriteBatch::default();
+        let mut vertex_type_counts = self.vertex_type_counts.lock().unwrap();
+        let mut new_vertex_type_counts = vertex_type_counts.clone();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        // Tracks what's been deleted in the batch, so that repeated vertices
+        // and edges between deleted vertices aren't counted twice
+        let mut deleted = HashSet::new();
+        let mut deleted_edges = HashSet::new();
 
         for vertex in vertices.into_iter() {
-            self.vertex_manager.delete(&mut batch, &indexed_properties, vertex.id)?;
+            if deleted.contains(&vertex.id) {
+                continue;
+            }
+            if let Some(t) = self.vertex_manager.get(vertex.id)? {
+                self.uncount_vertex_properties(&mut new_property_name_counts, vertex.id)?;
+                for item in self.edge_range_manager.iterate_for_root(vertex.id, None)? {
+                    deleted_edges.insert(item?);
+                }
+                for item in self.reversed_edge_range_manager.iterate_for_root(vertex.id, None)? {
+                    deleted_edges.insert(item?.reversed());
+                }
+                self.vertex_manager.delete(&mut batch, &indexed_properties, vertex.id)?;
+                decrement_count(&mut new_vertex_type_counts, t);
+                deleted.insert(vertex.id);
+            }
         }
 
-        self.db.write(batch)?;
-        Ok(())
+        for edge in deleted_edges.iter() {
+            self.uncount_edge_properties(&mut new_property_name_counts, edge)?;
+        }
+
+        self.metadata_manager
+            .set_vertex_type_counts(&mut batch, &new_vertex_type_counts)?;
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *vertex_type_counts = new_vertex_type_counts;
+        *property_name_counts = new_property_name_counts;
+        Ok(deleted.len() as u64)
     }
 
-    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<()> {
+    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<u64> {
+        self.check_writable()?;
         let indexed_properties = self.indexed_properties.read().unwrap();
-        let mut batch = WriteBatch::default();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut deleted = HashSet::new();
 
         for edge in edges.into_iter() {
-            if self.vertex_manager.get(edge.outbound_id)?.is_some() {
+            if !deleted.contains(&edge) && self.edge_range_manager.contains(&edge)? {
+                self.uncount_edge_properties(&mut new_property_name_counts, &edge)?;
                 self.edge_manager.delete(&mut batch, &indexed_properties, &edge)?;
+                deleted.insert(edge);
             };
         }
 
-        self.db.write(batch)?;
-        Ok(())
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
+        Ok(deleted.len() as u64)
     }
 
     fn delete_vertex_properties(&mut self, props: Vec<(Uuid, Identifier)>) -> Result<()> {
+        self.check_writable()?;
         let indexed_properties = self.indexed_properties.read().unwrap();
-        let mut batch = WriteBatch::default();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut deleted = HashSet::new();
 
         for (id, name) in props.into_iter() {
+            if !deleted.contains(&(id, name)) && self.vertex_property_manager.get(id, name)?.is_some() {
+                decrement_count(&mut new_property_name_counts.vertex, name);
+                deleted.insert((id, name));
+            }
             self.vertex_property_manager
                 .delete(&mut batch, &indexed_properties, id, name)?;
         }
 
-        self.db.write(batch)?;
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
         Ok(())
     }
 
     fn delete_edge_properties(&mut self, props: Vec<(Edge, Identifier)>) -> Result<()> {
+        self.check_writable()?;
         let indexed_properties = self.indexed_properties.read().unwrap();
-        let mut batch = WriteBatch::default();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut deleted = HashSet::new();
 
         for (edge, name) in props.into_iter() {
+            if !deleted.contains(&(edge.clone(), name)) && self.edge_property_manager.get(&edge, name)?.is_some() {
+                decrement_count(&mut new_property_name_counts.edge, name);
+                deleted.insert((edge.clone(), name));
+            }
             self.edge_property_manager
                 .delete(&mut batch, &indexed_properties, &edge, name)?;
         }
 
-        self.db.write(batch)?;
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
         Ok(())
     }
 
     fn sync(&self) -> Result<()> {
-        self.vertex_manager.compact();
-        self.edge_range_manager.compact();
-        self.edge_range_manager.compact();
-        self.vertex_property_manager.compact();
-        self.edge_property_manager.compact();
-        self.vertex_property_value_manager.compact();
-        self.edge_property_value_manager.compact();
-        self.metadata_manager.compact();
+        // Reap expired edges, since reads only skip over them
+        if !self.readonly {
+            let indexed_properties = self.indexed_properties.read().unwrap();
+            let mut property_name_counts = self.property_name_counts.lock().unwrap();
+            let mut new_property_name_counts = property_name_counts.clone();
+            let mut batch = Batch::default();
+            for item in self.edge_range_manager.iterate_for_expired() {
+                let edge = item?;
+                self.uncount_edge_properties(&mut new_property_name_counts, &edge)?;
+                self.edge_manager.delete(&mut batch, &indexed_properties, &edge)?;
+            }
+            self.metadata_manager
+                .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+            self.write(batch)?;
+            *property_name_counts = new_property_name_counts;
+        }
+
+        self.compact_all();
+        // Sync the write-ahead log, then flush memtables, so that every write
+        // made before this is durable
+        self.db.flush_wal(true)?;
         self.db.flush()?;
         Ok(())
     }
 
+    fn compact(&self) -> Result<()> {
+        self.compact_all();
+        Ok(())
+    }
+
+    fn commit(self) -> Result<()> {
+        if self.readonly {
+            return Ok(());
+        }
+
+        // Publish the metadata under its lock, so that read-only
+        // transactions see it change along with the database
+        let mut metadata = self.metadata.write().unwrap();
+        self.view.commit(&self.column_families, self.sync_writes)?;
+        *metadata = Metadata {
+            indexed_properties: mem::take(&mut *self.indexed_properties.write().unwrap()),
+            property_schemas: mem::take(&mut *self.property_schemas.write().unwrap()),
+            vertex_type_counts: mem::take(&mut *self.vertex_type_counts.lock().unwrap()),
+            property_name_counts: mem::take(&mut *self.property_name_counts.lock().unwrap()),
+        };
+        drop(metadata);
+
+        if *self.cleared.lock().unwrap() {
+            self.compact_all();
+        } else {
+            for name in self.dropped_indexes.lock().unwrap().iter() {
+                self.vertex_property_value_manager.compact_for_name(*name);
+                self.edge_property_value_manager.compact_for_name(*name);
+            }
+        }
+        Ok(())
+    }
+
+    fn rollback(self) -> Result<()> {
+        // Nothing is written until commit, so dropping the transaction
+        // discards everything
+        Ok(())
+    }
+
     fn create_vertex(&mut self, vertex: &Vertex) -> Result<bool> {
+        self.check_writable()?;
+        let mut vertex_type_counts = self.vertex_type_counts.lock().unwrap();
         if self.vertex_manager.exists(vertex.id)? {
             Ok(false)
         } else {
-            let mut batch = WriteBatch::default();
+            let mut new_vertex_type_counts = vertex_type_counts.clone();
+            increment_count(&mut new_vertex_type_counts, vertex.t);
+            let mut batch = Batch::default();
             self.vertex_manager.create(&mut batch, vertex)?;
-            self.db.write(batch)?;
+            self.metadata_manager
+                .set_vertex_type_counts(&mut batch, &new_vertex_type_counts)?;
+            self.write(batch)?;
+            *vertex_type_counts = new_vertex_type_counts;
             Ok(true)
         }
     }
 
+    fn create_vertex_with_properties(&mut self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        self.check_writable()?;
+        let props = dedup_properties(props);
+        let indexed_properties = self.indexed_properties.read().unwrap();
+        let property_schemas = self.property_schemas.read().unwrap();
+        for (name, value) in &props {
+            util::check_property_schema(&property_schemas, *name, value)?;
+        }
+        let mut vertex_type_counts = self.vertex_type_counts.lock().unwrap();
+        if self.vertex_manager.exists(vertex.id)? {
+            return Ok(false);
+        }
+
+        // Write the vertex and its properties in one batch, so that a
+        // failure can't leave the vertex half-initialized
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_vertex_type_counts = vertex_type_counts.clone();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        increment_count(&mut new_vertex_type_counts, vertex.t);
+        self.vertex_manager.create(&mut batch, vertex)?;
+        for (name, value) in &props {
+            increment_count(&mut new_property_name_counts.vertex, *name);
+            self.vertex_property_manager
+                .set(&mut batch, &indexed_properties, vertex.id, *name, value)?;
+        }
+        self.metadata_manager
+            .set_vertex_type_counts(&mut batch, &new_vertex_type_counts)?;
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *vertex_type_counts = new_vertex_type_counts;
+        *property_name_counts = new_property_name_counts;
+        Ok(true)
+    }
+
+    fn upsert_vertex(&mut self, vertex: &Vertex, props: Vec<(Identifier, Json)>) -> Result<bool> {
+        if self.create_vertex_with_properties(vertex, props.clone())? {
+            return Ok(true);
+        }
+        self.set_vertex_properties_batch(vec![vertex.id], props)?;
+        Ok(false)
+    }
+
+    fn create_vertex_with_labels(&mut self, vertex: &Vertex, labels: BTreeSet<Identifier>) -> Result<bool> {
+        self.check_writable()?;
+        let mut vertex_type_counts = self.vertex_type_counts.lock().unwrap();
+        if self.vertex_manager.exists(vertex.id)? {
+            return Ok(false);
+        }
+
+        let mut new_vertex_type_counts = vertex_type_counts.clone();
+        let mut batch = Batch::default();
+        increment_count(&mut new_vertex_type_counts, vertex.t);
+        self.vertex_manager.create(&mut batch, vertex)?;
+        for label in labels {
+            self.vertex_label_manager.set(&mut batch, vertex.id, label);
+        }
+        self.metadata_manager
+            .set_vertex_type_counts(&mut batch, &new_vertex_type_counts)?;
+        self.write(batch)?;
+        *vertex_type_counts = new_vertex_type_counts;
+        Ok(true)
+    }
+
     fn create_edge(&mut self, edge: &Edge) -> Result<bool> {
+        self.set_edge(edge, None)
+    }
+
+    fn create_edge_with_ttl(&mut self, edge: &Edge, ttl: Duration) -> Result<bool> {
+        self.set_edge(edge, Some(SystemTime::now() + ttl))
+    }
+
+    fn create_symmetric_edge(&mut self, edge: &Edge) -> Result<bool> {
+        self.check_writable()?;
         if !self.vertex_manager.exists(edge.outbound_id)? || !self.vertex_manager.exists(edge.inbound_id)? {
-            Ok(false)
-        } else {
-            let mut batch = WriteBatch::default();
-            self.edge_manager.set(&mut batch, edge)?;
-            self.db.write(batch)?;
-            Ok(true)
+            return Ok(false);
+        }
+        // Write both edges in one batch, so that only one can't be written
+        let mut batch = Batch::default();
+        self.edge_manager.set(&mut batch, edge, None)?;
+        self.edge_manager.set(&mut batch, &edge.reversed(), None)?;
+        self.write(batch)?;
+        Ok(true)
+    }
+
+    fn create_edge_if_absent(&mut self, edge: &Edge) -> Result<bool> {
+        self.check_writable()?;
+        if self.edge_range_manager.contains(edge)? {
+            return Ok(false);
         }
+        self.set_edge(edge, None)
     }
 
     // We override the default `bulk_insert` implementation because further
     // optimization can be done by using `WriteBatch`s.
     fn bulk_insert(&mut self, items: Vec<BulkInsertItem>) -> Result<()> {
+        self.check_writable()?;
         let indexed_properties = self.indexed_properties.read().unwrap();
-        let mut batch = WriteBatch::default();
+        let property_schemas = self.property_schemas.read().unwrap();
+        let mut vertex_type_counts = self.vertex_type_counts.lock().unwrap();
+        let mut new_vertex_type_counts = vertex_type_counts.clone();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        // Items inserted earlier in this batch aren't visible to the managers
+        // yet, so they're tracked here to keep counts accurate
+        let mut inserted_vertices = HashMap::new();
+        let mut inserted_vertex_properties = HashSet::new();
+        let mut inserted_edge_properties = HashSet::new();
 
         for item in items {
             match item {
                 BulkInsertItem::Vertex(ref vertex) => {
+                    let previous_t = match inserted_vertices.get(&vertex.id) {
+                        Some(t) => Some(*t),
+                        None => self.vertex_manager.get(vertex.id)?,
+                    };
+                    if let Some(previous_t) = previous_t {
+                        decrement_count(&mut new_vertex_type_counts, previous_t);
+                    }
+                    increment_count(&mut new_vertex_type_counts, vertex.t);
+                    inserted_vertices.insert(vertex.id, vertex.t);
                     self.vertex_manager.create(&mut batch, vertex)?;
                 }
                 BulkInsertItem::Edge(ref edge) => {
-                    self.edge_manager.set(&mut batch, edge)?;
+                    self.edge_manager.set(&mut batch, edge, None)?;
                 }
                 BulkInsertItem::VertexProperty(id, ref name, ref value) => {
+                    util::check_property_schema(&property_schemas, *name, value)?;
+                    if !inserted_vertex_properties.contains(&(id, *name))
+                        && self.vertex_property_manager.get(id, *name)?.is_none()
+                    {
+                        increment_count(&mut new_property_name_counts.vertex, *name);
+                    }
+                    inserted_vertex_properties.insert((id, *name));
                     self.vertex_property_manager
                         .set(&mut batch, &indexed_properties, id, *name, value)?;
                 }
                 BulkInsertItem::EdgeProperty(ref edge, ref name, ref value) => {
+                    util::check_property_schema(&property_schemas, *name, value)?;
+                    if !inserted_edge_properties.contains(&(edge.clone(), *name))
+                        && self.edge_property_manager.get(edge, *name)?.is_none()
+                    {
+                        increment_count(&mut new_property_name_counts.edge, *name);
+                    }
+                    inserted_edge_properties.insert((edge.clone(), *name));
                     self.edge_property_manager
                         .set(&mut batch, &indexed_properties, edge, *name, value)?;
                 }
             }
         }
 
-        self.db.write(batch)?;
+        if !inserted_vertices.is_empty() {
+            self.metadata_manager
+                .set_vertex_type_counts(&mut batch, &new_vertex_type_counts)?;
+        }
+        if !inserted_vertex_properties.is_empty() || !inserted_edge_properties.is_empty() {
+            self.metadata_manager
+                .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        }
+        self.write(batch)?;
+        *vertex_type_counts = new_vertex_type_counts;
+        *property_name_counts = new_property_name_counts;
         Ok(())
     }
 
     fn index_property(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
         let mut indexed_properties = self.indexed_properties.write().unwrap();
         if !indexed_properties.insert(name) {
             return Ok(());
         }
 
-        let mut batch = WriteBatch::default();
+        let mut batch = Batch::default();
         self.metadata_manager
             .set_indexed_properties(&mut batch, &indexed_properties)?;
 
+        // For paths, the value is nested in the property at the root of the path
+        let (property_name, fields) = name.split_path();
+
         for item in self.vertex_manager.iterate_for_range(Uuid::default()) {
             let vertex = item?;
-            if let Some(property_value) = self.vertex_property_manager.get(vertex.id, name)? {
-                self.vertex_property_value_manager
-                    .set(&mut batch, vertex.id, name, &property_value);
+            if let Some(property_value) = self.vertex_property_manager.get(vertex.id, property_name)? {
+                if let Some(property_value) = util::json_at_path(&property_value, &fields) {
+                    self.vertex_property_value_manager
+                        .set(&mut batch, vertex.id, name, &property_value);
+                }
             }
         }
 
         for item in self.edge_range_manager.iterate_for_all() {
             let edge = item?;
-            if let Some(property_value) = self.edge_property_manager.get(&edge, name)? {
-                self.edge_property_value_manager
-                    .set(&mut batch, &edge, name, &property_value);
+            if let Some(property_value) = self.edge_property_manager.get(&edge, property_name)? {
+                if let Some(property_value) = util::json_at_path(&property_value, &fields) {
+                    self.edge_property_value_manager
+                        .set(&mut batch, &edge, name, &property_value);
+                }
             }
         }
 
-        self.db.write(batch)?;
+        self.write(batch)?;
+        Ok(())
+    }
+
+    fn drop_index(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
+        let mut indexed_properties = self.indexed_properties.write().unwrap();
+        if !indexed_properties.remove(&name) {
+            return Ok(());
+        }
+
+        let mut batch = Batch::default();
+        self.metadata_manager
+            .set_indexed_properties(&mut batch, &indexed_properties)?;
+        self.vertex_property_value_manager.delete_for_name(&mut batch, name);
+        self.edge_property_value_manager.delete_for_name(&mut batch, name);
+        self.write(batch)?;
+        self.dropped_indexes.lock().unwrap().push(name);
+        Ok(())
+    }
+
+    fn register_property_schema(&mut self, name: Identifier, expected: JsonType) -> Result<()> {
+        self.check_writable()?;
+        let mut property_schemas = self.property_schemas.write().unwrap();
+        let mut new_property_schemas = property_schemas.clone();
+        new_property_schemas.insert(name, expected);
+        let mut batch = Batch::default();
+        self.metadata_manager
+            .set_property_schemas(&mut batch, &new_property_schemas)?;
+        self.write(batch)?;
+        *property_schemas = new_property_schemas;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.check_writable()?;
+        let mut indexed_properties = self.indexed_properties.write().unwrap();
+        let mut property_schemas = self.property_schemas.write().unwrap();
+        let mut vertex_type_counts = self.vertex_type_counts.lock().unwrap();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+
+        // Column families can't be dropped while transactions hold handles
+        // to them, so range delete everything up to and including the last
+        // key of each instead
+        let mut batch = Batch::default();
+        for name in &CF_NAMES {
+            let cf = self.db.cf_handle(&cf_name(self.namespace, name)).unwrap();
+            if let Some(item) = self.view.iterator_cf(&cf, IteratorMode::End).next() {
+                let (last_key, _) = item?;
+                batch.delete_range_cf(&cf, &[][..], &last_key[..]);
+                batch.delete_cf(&cf, &last_key);
+            }
+        }
+
+        self.metadata_manager
+            .set_indexed_properties(&mut batch, &HashSet::new())?;
+        self.metadata_manager
+            .set_property_schemas(&mut batch, &HashMap::new())?;
+        self.metadata_manager
+            .set_vertex_type_counts(&mut batch, &HashMap::new())?;
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &PropertyNameCounts::default())?;
+        self.write(batch)?;
+        indexed_properties.clear();
+        property_schemas.clear();
+        vertex_type_counts.clear();
+        *property_name_counts = PropertyNameCounts::default();
+        *self.cleared.lock().unwrap() = true;
         Ok(())
     }
 
     fn set_vertex_properties(&mut self, vertices: Vec<Uuid>, name: Identifier, value: &Json) -> Result<()> {
+        self.set_vertex_properties_batch(vertices, vec![(name, value.clone())])
+    }
+
+    fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()> {
+        self.set_edge_properties_batch(edges, vec![(name, value.clone())])
+    }
+
+    fn add_vertex_labels(&mut self, vertices: Vec<Uuid>, label: Identifier) -> Result<()> {
+        self.check_writable()?;
+        let mut batch = Batch::default();
+        for id in vertices {
+            if self.vertex_manager.exists(id)? {
+                self.vertex_label_manager.set(&mut batch, id, label);
+            }
+        }
+        self.write(batch)?;
+        Ok(())
+    }
+
+    fn remove_vertex_labels(&mut self, vertices: Vec<Uuid>, label: Identifier) -> Result<()> {
+        self.check_writable()?;
+        let mut batch = Batch::default();
+        for id in vertices {
+            self.vertex_label_manager.delete(&mut batch, id, label);
+        }
+        self.write(batch)?;
+        Ok(())
+    }
+
+    fn set_vertex_properties_batch(&mut self, vertices: Vec<Uuid>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        self.check_writable()?;
+        let props = dedup_properties(props);
         let indexed_properties = self.indexed_properties.read().unwrap();
-        let mut batch = WriteBatch::default();
+        let property_schemas = self.property_schemas.read().unwrap();
+        for (name, value) in &props {
+            util::check_property_schema(&property_schemas, *name, value)?;
+        }
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut created = HashSet::new();
         for id in vertices.into_iter() {
-            self.vertex_property_manager
-                .set(&mut batch, &indexed_properties, id, name, value)?;
+            for (name, value) in &props {
+                if !created.contains(&(id, *name)) && self.vertex_property_manager.get(id, *name)?.is_none() {
+                    increment_count(&mut new_property_name_counts.vertex, *name);
+                    created.insert((id, *name));
+                }
+                self.vertex_property_manager
+                    .set(&mut batch, &indexed_properties, id, *name, value)?;
+            }
         }
-        self.db.write(batch)?;
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
         Ok(())
     }
 
-    fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()> {
+    fn set_edge_properties_batch(&mut self, edges: Vec<Edge>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        self.check_writable()?;
+        let props = dedup_properties(props);
         let indexed_properties = self.indexed_properties.read().unwrap();
-        let mut batch = WriteBatch::default();
+        let property_schemas = self.property_schemas.read().unwrap();
+        for (name, value) in &props {
+            util::check_property_schema(&property_schemas, *name, value)?;
+        }
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut created = HashSet::new();
         for edge in edges.into_iter() {
-            self.edge_property_manager
-                .set(&mut batch, &indexed_properties, &edge, name, value)?;
+            for (name, value) in &props {
+                if !created.contains(&(edge.clone(), *name)) && self.edge_property_manager.get(&edge, *name)?.is_none()
+                {
+                    increment_count(&mut new_property_name_counts.edge, *name);
+                    created.insert((edge.clone(), *name));
+                }
+                self.edge_property_manager
+                    .set(&mut batch, &indexed_properties, &edge, *name, value)?;
+            }
+        }
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
+        Ok(())
+    }
+
+    fn increment_vertex_properties(&mut self, vertices: Vec<Vertex>, name: Identifier, delta: f64) -> Result<()> {
+        self.check_writable()?;
+        let indexed_properties = self.indexed_properties.read().unwrap();
+        let property_schemas = self.property_schemas.read().unwrap();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut created = HashSet::new();
+        for vertex in vertices.into_iter() {
+            let value = self.vertex_property_manager.get(vertex.id, name)?;
+            if value.is_none() && created.insert(vertex.id) {
+                increment_count(&mut new_property_name_counts.vertex, name);
+            }
+            let value = increment_json(value.as_ref(), delta)?;
+            util::check_property_schema(&property_schemas, name, &value)?;
+            self.vertex_property_manager
+                .set(&mut batch, &indexed_properties, vertex.id, name, &value)?;
         }
-        self.db.write(batch)?;
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
+        Ok(())
+    }
+
+    fn merge_vertex_properties(&mut self, vertices: Vec<Vertex>, name: Identifier, patch: &Json) -> Result<()> {
+        self.check_writable()?;
+        let indexed_properties = self.indexed_properties.read().unwrap();
+        let property_schemas = self.property_schemas.read().unwrap();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut created = HashSet::new();
+        for vertex in vertices.into_iter() {
+            let value = self.vertex_property_manager.get(vertex.id, name)?;
+            if value.is_none() && created.insert(vertex.id) {
+                increment_count(&mut new_property_name_counts.vertex, name);
+            }
+            let value = util::merge_json(value.as_ref(), patch)?;
+            util::check_property_schema(&property_schemas, name, &value)?;
+            self.vertex_property_manager
+                .set(&mut batch, &indexed_properties, vertex.id, name, &value)?;
+        }
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
         Ok(())
     }
+
+    fn compare_and_set_vertex_properties(
+        &mut self,
+        vertices: Vec<Vertex>,
+        name: Identifier,
+        expected: Option<&Json>,
+        new: &Json,
+    ) -> Result<bool> {
+        self.check_writable()?;
+        if vertices.is_empty() {
+            return Ok(false);
+        }
+        for vertex in &vertices {
+            if self.vertex_property_manager.get(vertex.id, name)?.as_ref() != expected {
+                return Ok(false);
+            }
+        }
+        self.set_vertex_properties(vertices.into_iter().map(|v| v.id).collect(), name, new)?;
+        Ok(true)
+    }
+
+    fn delete_all_vertex_properties(&mut self, vertices: Vec<Vertex>) -> Result<u64> {
+        self.check_writable()?;
+        let indexed_properties = self.indexed_properties.read().unwrap();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut seen = HashSet::new();
+        let mut count = 0;
+
+        // Scans each vertex's property key prefix, rather than looking up
+        // properties one name at a time
+        for vertex in vertices {
+            if !seen.insert(vertex.id) {
+                continue;
+            }
+            for item in self.vertex_property_manager.iterate_for_owner(vertex.id)? {
+                let (_, name, _) = item?;
+                decrement_count(&mut new_property_name_counts.vertex, name);
+                self.vertex_property_manager
+                    .delete(&mut batch, &indexed_properties, vertex.id, name)?;
+                count += 1;
+            }
+        }
+
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
+        Ok(count)
+    }
+
+    fn delete_all_edge_properties(&mut self, edges: Vec<Edge>) -> Result<u64> {
+        self.check_writable()?;
+        let indexed_properties = self.indexed_properties.read().unwrap();
+        let mut property_name_counts = self.property_name_counts.lock().unwrap();
+        let mut new_property_name_counts = property_name_counts.clone();
+        let mut batch = Batch::default();
+        let mut seen = HashSet::new();
+        let mut count = 0;
+
+        for edge in edges {
+            if seen.contains(&edge) {
+                continue;
+            }
+            for item in self.edge_property_manager.iterate_for_owner(&edge)? {
+                let (_, name, _) = item?;
+                decrement_count(&mut new_property_name_counts.edge, name);
+                self.edge_property_manager
+                    .delete(&mut batch, &indexed_properties, &edge, name)?;
+                count += 1;
+            }
+            seen.insert(edge);
+        }
+
+        self.metadata_manager
+            .set_property_name_counts(&mut batch, &new_property_name_counts)?;
+        self.write(batch)?;
+        *property_name_counts = new_property_name_counts;
+        Ok(count)
+    }
+}
+
+/// Typed tuning options for a rocksdb datastore, covering the options most
+/// worth adjusting for a workload. Anything unset keeps IndraDB's defaults,
+/// as in `RocksdbDatastore::get_options`. For anything not covered here,
+/// build `rocksdb::Options` directly and use
+/// `RocksdbDatastore::new_db_with_options`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RocksdbConfig {
+    /// The size of the LRU block cache, in bytes. If `None`, rocksdb's
+    /// default 8mb cache is used.
+    pub block_cache_size: Option<usize>,
+    /// The maximum number of files to have open. If `None`, rocksdb's
+    /// default is used.
+    pub max_open_files: Option<i32>,
+    /// The compaction style. Defaults to level compaction.
+    pub compaction_style: DBCompactionStyle,
+    /// Whether every write waits for the write-ahead log to be synced to
+    /// disk, so that it survives a machine crash as soon as it returns.
+    /// Otherwise, writes only survive a process crash until the next sync.
+    /// This makes writes much slower, since each one waits on an fsync, so
+    /// for bulk loads it's usually better to leave this off and call
+    /// `Database::sync` afterwards. Defaults to `false`.
+    pub sync_writes: bool,
+}
+
+impl Default for RocksdbConfig {
+    fn default() -> Self {
+        Self {
+            block_cache_size: None,
+            max_open_files: None,
+            compaction_style: DBCompactionStyle::Level,
+            sync_writes: false,
+        }
+    }
+}
+
+impl RocksdbConfig {
+    /// Builds the rocksdb options for this config.
+    pub fn to_options(&self) -> Options {
+        let mut opts = RocksdbDatastore::get_options(self.max_open_files);
+        opts.set_compaction_style(self.compaction_style);
+
+        if let Some(block_cache_size) = self.block_cache_size {
+            let cache = Cache::new_lru_cache(block_cache_size);
+            let mut block_opts = BlockBasedOptions::default();
+            block_opts.set_block_cache(&cache);
+            opts.set_block_based_table_factory(&block_opts);
+        }
+
+        opts
+    }
 }
 
 /// A datastore that is backed by rocksdb.
+///
+/// Transactions keep their writes in memory, and write them to the database
+/// in one atomic batch when they're committed. Rolling back or dropping a
+/// transaction discards them. Only one writable transaction can be open at
+/// a time, so starting another waits for it to end. Read-only transactions
+/// read from a snapshot, so they don't wait, and don't see writes committed
+/// after they start.
 #[derive(Debug)]
 pub struct RocksdbDatastore {
     db: Arc<DB>,
-    indexed_properties: Arc<RwLock<HashSet<Identifier>>>,
+    namespace: Option<Identifier>,
+    metadata: Arc<RwLock<Metadata>>,
+    write_lock: Arc<Mutex<()>>,
+    vertex_id_generator: Arc<dyn VertexIdGenerator>,
+    sync_writes: bool,
 }
 
 impl RocksdbDatastore {
@@ -374,32 +1267,36 @@ impl RocksdbDatastore {
     /// # Arguments
     /// * `path`: The file path to the rocksdb database.
     pub fn new_db<P: AsRef<Path>>(path: P) -> Result<Database<RocksdbDatastore>> {
-        let opts = RocksdbDatastore::get_options(None);
-        let path = path.as_ref();
-
-        let db = match DB::open_cf(&opts, path, CF_NAMES) {
-            Ok(db) => db,
-            Err(_) => {
-                let mut db = DB::open(&opts, path)?;
-
-                for cf_name in &CF_NAMES {
-                    db.create_cf(cf_name, &opts)?;
-                }
-
-                db
-            }
-        };
-
-        let metadata_manager = MetadataManager::new(&db);
-        let indexed_properties = metadata_manager.get_indexed_properties()?;
+        RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(None))
+    }
 
-        Ok(Database::new(RocksdbDatastore {
-            db: Arc::new(db),
-            indexed_properties: Arc::new(RwLock::new(indexed_properties)),
-        }))
+    /// Creates a new rocksdb datastore, tuned with the commonly adjusted
+    /// options in `config`.
+    ///
+    /// # Arguments
+    /// * `path`: The file path to the rocksdb database.
+    /// * `config`: The tuning options.
+    pub fn new_db_with_config<P: AsRef<Path>>(path: P, config: &RocksdbConfig) -> Result<Database<RocksdbDatastore>> {
+        let mut db = RocksdbDatastore::new_db_with_options(path, &config.to_options())?;
+        db.datastore.set_sync_writes(config.sync_writes);
+        Ok(db)
     }
 
-    /// Creates a new rocksdb datastore with user-tuned rocksdb Option.
+    /// Creates a new rocksdb datastore with user-tuned rocksdb Option. The
+    /// options are used for every column family. It's easiest to start from
+    /// `RocksdbDatastore::get_options` and adjust from there.
+    ///
+    /// IndraDB relies on some options keeping their defaults, and overriding
+    /// them can silently break queries or corrupt the datastore:
+    /// * The comparator must be the default bytewise one. Keys are encoded so
+    ///   that bytewise order matches the order of IDs and property values,
+    ///   and range and prefix scans depend on it.
+    /// * There must be no prefix extractor. Scans seek to a key and iterate
+    ///   forward across prefixes, which prefix seeks would cut short.
+    /// * There must be no merge operator or compaction filter, since values
+    ///   are expected to be exactly what IndraDB wrote.
+    ///
+    /// Also, `create_if_missing` must be set to open a new database.
     ///
     /// # Arguments
     /// * `path`: The file path to the rocksdb database.
@@ -410,23 +1307,114 @@ impl RocksdbDatastore {
         let db = match DB::open_cf(opts, path, CF_NAMES) {
             Ok(db) => db,
             Err(_) => {
-                let mut db = DB::open(opts, path)?;
+                // Either the database is new, or it was created before some
+                // of the column families existed
+                let existing_cf_names = DB::list_cf(opts, path).unwrap_or_default();
+                let mut db = DB::open_cf(opts, path, &existing_cf_names)?;
 
                 for cf_name in &CF_NAMES {
-                    db.create_cf(cf_name, opts)?;
+                    if !existing_cf_names.iter().any(|name| name == cf_name) {
+                        db.create_cf(cf_name, opts)?;
+                    }
                 }
 
                 db
             }
         };
 
-        let metadata_manager = MetadataManager::new(&db);
-        let indexed_properties = metadata_manager.get_indexed_properties()?;
+        Ok(Database::new(RocksdbDatastore::load(Arc::new(db), None)?))
+    }
 
-        Ok(Database::new(RocksdbDatastore {
-            db: Arc::new(db),
-            indexed_properties: Arc::new(RwLock::new(indexed_properties)),
-        }))
+    // Loads the state of a namespace that's kept in memory, for a datastore
+    // scoped to it.
+    fn load(db: Arc<DB>, namespace: Option<Identifier>) -> Result<RocksdbDatastore> {
+        let metadata_manager = MetadataManager::new(&DbView::new(&db), namespace);
+        let metadata = Metadata {
+            indexed_properties: metadata_manager.get_indexed_properties()?,
+            property_schemas: metadata_manager.get_property_schemas()?,
+            vertex_type_counts: load_vertex_type_counts(&db, namespace)?,
+            property_name_counts: load_property_name_counts(&db, namespace)?,
+        };
+
+        Ok(RocksdbDatastore {
+            db,
+            namespace,
+            metadata: Arc::new(RwLock::new(metadata)),
+            write_lock: Arc::new(Mutex::new(())),
+            vertex_id_generator: Arc::new(UuidV1Generator),
+            sync_writes: false,
+        })
+    }
+
+    /// Creates a namespace, if it doesn't exist yet. Each namespace is a
+    /// separate graph, with its own column families, in the same rocksdb
+    /// database. Namespaces persist, so this only needs to be called once
+    /// per namespace, but is a no-op for existing ones.
+    ///
+    /// Creating column families needs exclusive access to the underlying
+    /// database, so this must be called before getting any datastores
+    /// scoped to a namespace with `namespaced`, or they must be dropped
+    /// first.
+    ///
+    /// # Arguments
+    /// * `namespace`: The namespace to create.
+    pub fn create_namespace(&mut self, namespace: Identifier) -> Result<()> {
+        let opts = RocksdbDatastore::get_options(None);
+        let db = Arc::get_mut(&mut self.db)
+            .ok_or_else(|| Error::Datastore("can't create a namespace while the database is shared".into()))?;
+        for name in &CF_NAMES {
+            let name = cf_name(Some(namespace), name);
+            if db.cf_handle(&name).is_none() {
+                db.create_cf(&name, &opts)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets a datastore scoped to a namespace, which shares this one's
+    /// underlying rocksdb database. Vertices, edges, properties, indexes and
+    /// counts are all confined to the namespace, so queries, counts and
+    /// deletes on the returned datastore never see those of other
+    /// namespaces, or of the default namespace, which this datastore is
+    /// scoped to unless it was itself gotten with `namespaced`. Settings
+    /// such as whether writes are synced are copied over.
+    ///
+    /// # Arguments
+    /// * `namespace`: The namespace, which must have been created with
+    ///   `create_namespace`.
+    ///
+    /// # Errors
+    /// Returns an error if the namespace doesn't exist.
+    pub fn namespaced(&self, namespace: Identifier) -> Result<Database<RocksdbDatastore>> {
+        if self.db.cf_handle(&cf_name(Some(namespace), CF_NAMES[0])).is_none() {
+            return Err(Error::Datastore(
+                format!("namespace `{}` doesn't exist", namespace.as_str()).into(),
+            ));
+        }
+
+        let mut datastore = RocksdbDatastore::load(self.db.clone(), Some(namespace))?;
+        datastore.vertex_id_generator = self.vertex_id_generator.clone();
+        datastore.sync_writes = self.sync_writes;
+        Ok(Database::new(datastore))
+    }
+
+    /// Sets how the IDs of vertices created from just a type are generated.
+    /// By default, they're UUID v1s, which are ordered by creation time and
+    /// so keep recently created vertices close together on disk.
+    ///
+    /// # Arguments
+    /// * `generator`: The vertex ID generator.
+    pub fn set_vertex_id_generator<G: VertexIdGenerator + 'static>(&mut self, generator: G) {
+        self.vertex_id_generator = Arc::new(generator);
+    }
+
+    /// Sets whether every write waits for the write-ahead log to be synced
+    /// to disk. See `RocksdbConfig::sync_writes` for the tradeoff.
+    ///
+    /// # Arguments
+    /// * `sync_writes`: Whether to sync every write.
+    pub fn set_sync_writes(&mut self, sync_writes: bool) {
+        self.sync_writes = sync_writes;
     }
 
     /// Runs a repair operation on the rocksdb database.
@@ -470,21 +1458,71 @@ impl RocksdbDatastore {
     }
 }
 
-impl Datastore for RocksdbDatastore {
-    type Transaction<'a> = RocksdbTransaction<'a> where Self: 'a;
-    fn transaction(&'_ self) -> Self::Transaction<'_> {
+impl RocksdbDatastore {
+    fn new_transaction(&self, readonly: bool) -> RocksdbTransaction<'_> {
+        // Writable transactions wait for the write lock before copying the
+        // metadata, so that they start from the last commit
+        let write_guard = if readonly {
+            None
+        } else {
+            Some(self.write_lock.lock().unwrap())
+        };
+
+        // Snapshots are taken under the metadata lock, so that they match it
+        let metadata = self.metadata.read().unwrap();
+        let view = if readonly {
+            DbView::snapshot(&self.db)
+        } else {
+            DbView::new(&self.db)
+        };
+        let Metadata {
+            indexed_properties,
+            property_schemas,
+            vertex_type_counts,
+            property_name_counts,
+        } = metadata.clone();
+        drop(metadata);
+
         RocksdbTransaction {
             db: &self.db,
-            indexed_properties: self.indexed_properties.clone(),
-            vertex_manager: VertexManager::new(&self.db),
-            edge_manager: EdgeManager::new(&self.db),
-            edge_range_manager: EdgeRangeManager::new(&self.db),
-            reversed_edge_range_manager: EdgeRangeManager::new_reversed(&self.db),
-            vertex_property_manager: VertexPropertyManager::new(&self.db),
-            edge_property_manager: EdgePropertyManager::new(&self.db),
-            vertex_property_value_manager: VertexPropertyValueManager::new(&self.db),
-            edge_property_value_manager: EdgePropertyValueManager::new(&self.db),
-            metadata_manager: MetadataManager::new(&self.db),
+            namespace: self.namespace,
+            metadata: &self.metadata,
+            indexed_properties: RwLock::new(indexed_properties),
+            property_schemas: RwLock::new(property_schemas),
+            vertex_type_counts: Mutex::new(vertex_type_counts),
+            property_name_counts: Mutex::new(property_name_counts),
+            vertex_manager: VertexManager::new(&view, self.namespace),
+            edge_manager: EdgeManager::new(&view, self.namespace),
+            edge_range_manager: EdgeRangeManager::new(&view, self.namespace),
+            reversed_edge_range_manager: EdgeRangeManager::new_reversed(&view, self.namespace),
+            vertex_property_manager: VertexPropertyManager::new(&view, self.namespace),
+            edge_property_manager: EdgePropertyManager::new(&view, self.namespace),
+            vertex_property_value_manager: VertexPropertyValueManager::new(&view, self.namespace),
+            edge_property_value_manager: EdgePropertyValueManager::new(&view, self.namespace),
+            vertex_label_manager: VertexLabelManager::new(&view, self.namespace),
+            metadata_manager: MetadataManager::new(&view, self.namespace),
+            column_families: column_families(&self.db, self.namespace),
+            view,
+            readonly,
+            sync_writes: self.sync_writes,
+            dropped_indexes: Mutex::new(Vec::new()),
+            cleared: Mutex::new(false),
+            _write_guard: write_guard,
         }
     }
 }
+
+impl Datastore for RocksdbDatastore {
+    type Transaction<'a> = RocksdbTransaction<'a> where Self: 'a;
+    fn transaction(&'_ self) -> Self::Transaction<'_> {
+        self.new_transaction(false)
+    }
+
+    fn readonly_transaction(&'_ self) -> Self::Transaction<'_> {
+        self.new_transaction(true)
+    }
+
+    fn generate_vertex_id(&self, t: Identifier) -> Uuid {
+        self.vertex_id_generator.generate(t)
+    }
+}
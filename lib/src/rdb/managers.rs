@@ -1,14 +1,22 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Cursor;
-use std::ops::Deref;
+use std::mem;
+use std::ops::{Bound, Deref};
 use std::result::Result as StdResult;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::u8;
 
 use crate::errors::Result;
 use crate::models;
 use crate::util;
 
-use rocksdb::{ColumnFamilyRef, DBIterator, Direction, IteratorMode, WriteBatch, DB};
+use byteorder::{BigEndian, ByteOrder};
+use rocksdb::{
+    ColumnFamily, ColumnFamilyRef, DBIterator, Direction, IteratorMode, ReadOptions, Snapshot, WriteBatch,
+    WriteOptions, DB,
+};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub type OwnedPropertyItem = (Uuid, models::Identifier, models::Json);
@@ -16,10 +24,10 @@ pub type EdgePropertyItem = (models::Edge, models::Identifier, models::Json);
 pub type VertexPropertyValueKey = (models::Identifier, u64, Uuid);
 pub type EdgePropertyValueKey = (models::Identifier, u64, models::Edge);
 
-fn take_with_prefix(
-    iterator: DBIterator<'_>,
-    prefix: Vec<u8>,
-) -> impl Iterator<Item = StdResult<(Box<[u8]>, Box<[u8]>), rocksdb::Error>> + '_ {
+type KvBytes = (Box<[u8]>, Box<[u8]>);
+type KvResult = StdResult<KvBytes, rocksdb::Error>;
+
+fn take_with_prefix(iterator: ViewIterator<'_>, prefix: Vec<u8>) -> impl Iterator<Item = KvResult> + '_ {
     iterator.take_while(move |item| -> bool {
         if let Ok((ref k, _)) = *item {
             k.starts_with(&prefix)
@@ -29,16 +37,397 @@ fn take_with_prefix(
     })
 }
 
-pub(crate) struct VertexManager<'a> {
+fn cf_address(cf: &ColumnFamily) -> usize {
+    cf as *const ColumnFamily as usize
+}
+
+// Writes a transaction has made but not yet committed, for one column
+// family.
+#[derive(Default)]
+struct PendingColumnFamily {
+    // The latest value written to each key, or `None` if it was deleted
+    keys: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    // Deleted ranges, from an inclusive start to an exclusive end. Keys
+    // written after a range was deleted are in `keys`, which takes
+    // precedence.
+    ranges: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl PendingColumnFamily {
+    fn is_range_deleted(&self, key: &[u8]) -> bool {
+        self.ranges.iter().any(|(from, to)| key >= &from[..] && key < &to[..])
+    }
+
+    // Gets a key's pending value, or `None` if the key hasn't been written.
+    fn get(&self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        match self.keys.get(key) {
+            Some(value) => Some(value.clone()),
+            None if self.is_range_deleted(key) => Some(None),
+            None => None,
+        }
+    }
+
+    // Gets the first written key past `bound` in the direction of iteration.
+    fn next(&self, bound: &Bound<Vec<u8>>, forward: bool) -> Option<(Vec<u8>, Option<Vec<u8>>)> {
+        let entry = if forward {
+            self.keys.range::<Vec<u8>, _>((bound.as_ref(), Bound::Unbounded)).next()
+        } else {
+            self.keys
+                .range::<Vec<u8>, _>((Bound::Unbounded, bound.as_ref()))
+                .next_back()
+        };
+        entry.map(|(key, value)| (key.clone(), value.clone()))
+    }
+}
+
+// Pending writes, by column family address. Column families are recorded by
+// address rather than by reference, so that the writes can be kept without
+// borrowing the database for longer.
+type PendingWrites = HashMap<usize, PendingColumnFamily>;
+
+/// A batch of writes, which are applied to a transaction's pending writes
+/// together.
+#[derive(Default)]
+pub(crate) struct Batch {
+    writes: Vec<BatchWrite>,
+}
+
+enum BatchWrite {
+    Put(usize, Vec<u8>, Vec<u8>),
+    Delete(usize, Vec<u8>),
+    DeleteRange(usize, Vec<u8>, Vec<u8>),
+}
+
+impl Batch {
+    pub fn put_cf<K: AsRef<[u8]>, V: AsRef<[u8]>>(&mut self, cf: &ColumnFamilyRef<'_>, key: K, value: V) {
+        self.writes.push(BatchWrite::Put(
+            cf_address(cf),
+            key.as_ref().to_vec(),
+            value.as_ref().to_vec(),
+        ));
+    }
+
+    pub fn delete_cf<K: AsRef<[u8]>>(&mut self, cf: &ColumnFamilyRef<'_>, key: K) {
+        self.writes
+            .push(BatchWrite::Delete(cf_address(cf), key.as_ref().to_vec()));
+    }
+
+    pub fn delete_range_cf<K: AsRef<[u8]>>(&mut self, cf: &ColumnFamilyRef<'_>, from: K, to: K) {
+        self.writes.push(BatchWrite::DeleteRange(
+            cf_address(cf),
+            from.as_ref().to_vec(),
+            to.as_ref().to_vec(),
+        ));
+    }
+}
+
+/// The database as a transaction sees it: the database itself, or a
+/// snapshot of it for read-only transactions, with the transaction's pending
+/// writes on top. Nothing is written to the database until the transaction
+/// is committed. This is cheap to clone, and clones share pending writes.
+#[derive(Clone)]
+pub(crate) struct DbView<'a> {
     db: &'a DB,
+    snapshot: Option<Arc<Snapshot<'a>>>,
+    pending: Option<Arc<Mutex<PendingWrites>>>,
+}
+
+impl<'a> DbView<'a> {
+    /// Creates a writable view of the database.
+    pub fn new(db: &'a DB) -> Self {
+        DbView {
+            db,
+            snapshot: None,
+            pending: Some(Arc::new(Mutex::new(PendingWrites::default()))),
+        }
+    }
+
+    /// Creates a read-only view of a snapshot of the database, which doesn't
+    /// see anything written after it's created.
+    pub fn snapshot(db: &'a DB) -> Self {
+        DbView {
+            db,
+            snapshot: Some(Arc::new(db.snapshot())),
+            pending: None,
+        }
+    }
+
+    fn read_options(&self) -> ReadOptions {
+        let mut opts = ReadOptions::default();
+        if let Some(ref snapshot) = self.snapshot {
+            opts.set_snapshot(snapshot);
+        }
+        opts
+    }
+
+    pub fn cf_handle(&self, name: &str) -> Option<ColumnFamilyRef<'a>> {
+        self.db.cf_handle(name)
+    }
+
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamilyRef<'_>,
+        key: K,
+    ) -> StdResult<Option<Vec<u8>>, rocksdb::Error> {
+        if let Some(ref pending) = self.pending {
+            let pending = pending.lock().unwrap();
+            if let Some(value) = pending.get(&cf_address(cf)).and_then(|p| p.get(key.as_ref())) {
+                return Ok(value);
+            }
+        }
+        self.db.get_cf_opt(cf, key, &self.read_options())
+    }
+
+    // Like `DB::key_may_exist_cf`, this can have false positives, but not
+    // false negatives.
+    pub fn key_may_exist_cf<K: AsRef<[u8]>>(&self, cf: &ColumnFamilyRef<'_>, key: K) -> bool {
+        if let Some(ref pending) = self.pending {
+            let pending = pending.lock().unwrap();
+            if let Some(value) = pending.get(&cf_address(cf)).and_then(|p| p.get(key.as_ref())) {
+                return value.is_some();
+            }
+        }
+        // The database's filters don't know about what a snapshot still sees
+        self.snapshot.is_some() || self.db.key_may_exist_cf(cf, key)
+    }
+
+    pub fn iterator_cf(&self, cf: &ColumnFamilyRef<'_>, mode: IteratorMode) -> ViewIterator<'a> {
+        let base = self.db.iterator_cf_opt(cf, self.read_options(), mode);
+        let mut iter = ViewIterator {
+            base,
+            _snapshot: self.snapshot.clone(),
+            pending: self.pending.clone(),
+            address: cf_address(cf),
+            base_next: None,
+            base_done: false,
+            bound: Bound::Unbounded,
+            forward: true,
+        };
+        iter.set_bounds(mode);
+        iter
+    }
+
+    pub fn compact_range_cf<S: AsRef<[u8]>, E: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamilyRef<'_>,
+        start: Option<S>,
+        end: Option<E>,
+    ) {
+        self.db.compact_range_cf(cf, start, end);
+    }
+
+    /// Adds a batch to the pending writes.
+    ///
+    /// # Panics
+    /// Panics if the view is read-only.
+    pub fn write(&self, batch: Batch) {
+        let pending = self.pending.as_ref().expect("expected a writable view");
+        let mut pending = pending.lock().unwrap();
+        for write in batch.writes {
+            match write {
+                BatchWrite::Put(address, key, value) => {
+                    pending.entry(address).or_default().keys.insert(key, Some(value));
+                }
+                BatchWrite::Delete(address, key) => {
+                    pending.entry(address).or_default().keys.insert(key, None);
+                }
+                BatchWrite::DeleteRange(address, from, to) => {
+                    if from >= to {
+                        continue;
+                    }
+                    let pending_cf = pending.entry(address).or_default();
+                    for (_, value) in pending_cf
+                        .keys
+                        .range_mut::<[u8], _>((Bound::Included(&from[..]), Bound::Excluded(&to[..])))
+                    {
+                        *value = None;
+                    }
+                    pending_cf.ranges.push((from, to));
+                }
+            }
+        }
+    }
+
+    /// Writes the pending writes to the database in one atomic batch, and
+    /// clears them.
+    ///
+    /// # Arguments
+    /// * `cfs`: The column families written to.
+    /// * `sync`: Whether to wait for the write to be synced to disk.
+    pub fn commit(&self, cfs: &[ColumnFamilyRef<'_>], sync: bool) -> Result<()> {
+        let pending = match self.pending {
+            Some(ref pending) => mem::take(&mut *pending.lock().unwrap()),
+            None => return Ok(()),
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch = WriteBatch::default();
+        for cf in cfs {
+            if let Some(pending_cf) = pending.get(&cf_address(cf)) {
+                for (from, to) in &pending_cf.ranges {
+                    batch.delete_range_cf(cf, from, to);
+                }
+                for (key, value) in &pending_cf.keys {
+                    match value {
+                        Some(value) => batch.put_cf(cf, key, value),
+                        None => batch.delete_cf(cf, key),
+                    }
+                }
+            }
+        }
+
+        let mut opts = WriteOptions::default();
+        opts.set_sync(sync);
+        self.db.write_opt(batch, &opts)?;
+        Ok(())
+    }
+}
+
+/// Iterates over a column family as a transaction sees it, merging its
+/// pending writes into what's in the database.
+pub(crate) struct ViewIterator<'a> {
+    // Declared before the snapshot, so that it's dropped first
+    base: DBIterator<'a>,
+    _snapshot: Option<Arc<Snapshot<'a>>>,
+    pending: Option<Arc<Mutex<PendingWrites>>>,
+    address: usize,
+    // The next item from the database, if it's been read ahead
+    base_next: Option<KvBytes>,
+    base_done: bool,
+    // Where to look for the next pending write from
+    bound: Bound<Vec<u8>>,
+    forward: bool,
+}
+
+impl<'a> ViewIterator<'a> {
+    fn set_bounds(&mut self, mode: IteratorMode) {
+        self.base_next = None;
+        self.base_done = false;
+        (self.bound, self.forward) = match mode {
+            IteratorMode::Start => (Bound::Unbounded, true),
+            IteratorMode::End => (Bound::Unbounded, false),
+            IteratorMode::From(key, Direction::Forward) => (Bound::Included(key.to_vec()), true),
+            IteratorMode::From(key, Direction::Reverse) => (Bound::Included(key.to_vec()), false),
+        };
+    }
+
+    /// Seeks the iterator, as with `DBIterator::set_mode`.
+    pub fn set_mode(&mut self, mode: IteratorMode) {
+        self.base.set_mode(mode);
+        self.set_bounds(mode);
+    }
+}
+
+impl<'a> Iterator for ViewIterator<'a> {
+    type Item = KvResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(ref pending) = self.pending else {
+            return self.base.next();
+        };
+
+        loop {
+            if self.base_next.is_none() && !self.base_done {
+                match self.base.next() {
+                    Some(Ok(item)) => self.base_next = Some(item),
+                    Some(Err(err)) => return Some(Err(err)),
+                    None => self.base_done = true,
+                }
+            }
+
+            let pending = pending.lock().unwrap();
+            let pending_cf = pending.get(&self.address);
+            let pending_next = pending_cf.and_then(|p| p.next(&self.bound, self.forward));
+
+            // Take whichever of the two comes first, preferring the pending
+            // write if they're for the same key
+            let take_base = match (&self.base_next, &pending_next) {
+                (Some(_), None) => true,
+                (Some((base_key, _)), Some((pending_key, _))) => {
+                    if self.forward {
+                        base_key[..] < pending_key[..]
+                    } else {
+                        base_key[..] > pending_key[..]
+                    }
+                }
+                (None, _) => false,
+            };
+
+            if take_base {
+                let (key, value) = self.base_next.take().unwrap();
+                if pending_cf.is_some_and(|p| p.is_range_deleted(&key)) {
+                    continue;
+                }
+                return Some(Ok((key, value)));
+            }
+
+            let (key, value) = pending_next?;
+            if self
+                .base_next
+                .as_ref()
+                .is_some_and(|(base_key, _)| base_key[..] == key[..])
+            {
+                self.base_next = None;
+            }
+            self.bound = Bound::Excluded(key.clone());
+            if let Some(value) = value {
+                return Some(Ok((key.into_boxed_slice(), value.into_boxed_slice())));
+            }
+        }
+    }
+}
+
+// Edge ranges store the edge's expiration time, as milliseconds since the
+// UNIX epoch, in their value. Edges without an expiration have an empty value.
+pub(crate) fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+pub(crate) fn time_from_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+// Gets the name of a column family in a namespace. The default namespace
+// uses the bare names, so databases created before namespaces existed keep
+// working. Names don't contain slashes, so namespaces can't collide.
+pub(crate) fn cf_name(namespace: Option<models::Identifier>, name: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{}/{name}", namespace.as_str()),
+        None => name.to_string(),
+    }
+}
+
+fn is_expired(value: &[u8], now: u64) -> bool {
+    value.len() == 8 && BigEndian::read_u64(value) <= now
+}
+
+/// The number of vertex and edge properties with each name.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct PropertyNameCounts {
+    pub vertex: HashMap<models::Identifier, u64>,
+    pub edge: HashMap<models::Identifier, u64>,
+}
+
+// Vertices store their type, followed by their creation time, as
+// milliseconds since the UNIX epoch, in their value. Vertices created before
+// creation times were tracked only have their type. Creation times are also
+// indexed in a separate column family, keyed by time and then vertex ID.
+pub(crate) struct VertexManager<'a> {
+    db: DbView<'a>,
+    namespace: Option<models::Identifier>,
     cf: ColumnFamilyRef<'a>,
+    created_cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> VertexManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         VertexManager {
-            db,
-            cf: db.cf_handle("vertices:v2").unwrap(),
+            db: db.clone(),
+            namespace,
+            cf: db.cf_handle(&cf_name(namespace, "vertices:v2")).unwrap(),
+            created_cf: db.cf_handle(&cf_name(namespace, "created_vertices:v2")).unwrap(),
         }
     }
 
@@ -46,8 +435,20 @@ impl<'a> VertexManager<'a> {
         util::build(&[util::Component::Uuid(id)])
     }
 
+    fn created_key(&self, created_at: u64, id: Uuid) -> Vec<u8> {
+        let mut key = created_at.to_be_bytes().to_vec();
+        key.extend(util::build(&[util::Component::Uuid(id)]));
+        key
+    }
+
     pub fn exists(&self, id: Uuid) -> Result<bool> {
-        Ok(self.db.get_cf(&self.cf, self.key(id))?.is_some())
+        let key = self.key(id);
+        // `key_may_exist_cf` can have false positives, but not false
+        // negatives, so only the possible hits need confirming
+        if !self.db.key_may_exist_cf(&self.cf, &key) {
+            return Ok(false);
+        }
+        Ok(self.db.get_cf(&self.cf, key)?.is_some())
     }
 
     pub fn get(&self, id: Uuid) -> Result<Option<models::Identifier>> {
@@ -60,11 +461,52 @@ impl<'a> VertexManager<'a> {
         }
     }
 
-    pub fn iterate_for_range(&'a self, id: Uuid) -> impl Iterator<Item = Result<models::Vertex>> + 'a {
-        let low_key = util::build(&[util::Component::Uuid(id)]);
+    pub fn get_created_at(&self, id: Uuid) -> Result<Option<u64>> {
+        match self.db.get_cf(&self.cf, self.key(id))? {
+            Some(value_bytes) => {
+                let mut cursor = Cursor::new(value_bytes.deref());
+                util::read_identifier(&mut cursor);
+                let rest = &value_bytes[cursor.position() as usize..];
+                if rest.len() == 8 {
+                    Ok(Some(BigEndian::read_u64(rest)))
+                } else {
+                    Ok(None)
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Iterates over the index of creation times, starting from `low`. The
+    // index isn't updated when a vertex is overwritten in the same batch it
+    // was created in, so callers should check entries against
+    // `get_created_at`.
+    pub fn iterate_for_created(&'a self, low: u64) -> impl Iterator<Item = Result<(u64, Uuid)>> + 'a {
+        let key = low.to_be_bytes();
         let iter = self
             .db
-            .iterator_cf(&self.cf, IteratorMode::From(&low_key, Direction::Forward));
+            .iterator_cf(&self.created_cf, IteratorMode::From(&key, Direction::Forward));
+        iter.map(|item| -> Result<(u64, Uuid)> {
+            let (k, _) = item?;
+            let created_at = BigEndian::read_u64(&k[..8]);
+            let mut cursor = Cursor::new(&k[8..]);
+            Ok((created_at, util::read_uuid(&mut cursor)))
+        })
+    }
+
+    pub fn iterate_for_range(&'a self, id: Uuid) -> impl Iterator<Item = Result<models::Vertex>> + 'a {
+        self.iterate_from(id, Direction::Forward)
+    }
+
+    pub fn iterate_for_range_descending(&'a self, id: Uuid) -> impl Iterator<Item = Result<models::Vertex>> + 'a {
+        self.iterate_from(id, Direction::Reverse)
+    }
+
+    // Iterating in reverse starts from the last key less than or equal to
+    // `id`, rather than the first key greater than or equal to it.
+    fn iterate_from(&'a self, id: Uuid, direction: Direction) -> impl Iterator<Item = Result<models::Vertex>> + 'a {
+        let key = util::build(&[util::Component::Uuid(id)]);
+        let iter = self.db.iterator_cf(&self.cf, IteratorMode::From(&key, direction));
         iter.map(|item| -> Result<models::Vertex> {
             let (k, v) = item?;
 
@@ -80,21 +522,30 @@ impl<'a> VertexManager<'a> {
         })
     }
 
-    pub fn create(&self, batch: &mut WriteBatch, vertex: &models::Vertex) -> Result<()> {
-        let key = self.key(vertex.id);
-        batch.put_cf(&self.cf, &key, &util::build(&[util::Component::Identifier(vertex.t)]));
+    pub fn create(&self, batch: &mut Batch, vertex: &models::Vertex) -> Result<()> {
+        // Overwriting a vertex, e.g. via a bulk insert, keeps its original
+        // creation time
+        let created_at = match self.get_created_at(vertex.id)? {
+            Some(created_at) => created_at,
+            None => {
+                let created_at = millis_since_epoch(SystemTime::now());
+                batch.put_cf(&self.created_cf, self.created_key(created_at, vertex.id), []);
+                created_at
+            }
+        };
+        let mut value = util::build(&[util::Component::Identifier(vertex.t)]);
+        value.extend_from_slice(&created_at.to_be_bytes());
+        batch.put_cf(&self.cf, self.key(vertex.id), &value);
         Ok(())
     }
 
-    pub fn delete(
-        &self,
-        batch: &mut WriteBatch,
-        indexed_properties: &HashSet<models::Identifier>,
-        id: Uuid,
-    ) -> Result<()> {
+    pub fn delete(&self, batch: &mut Batch, indexed_properties: &HashSet<models::Identifier>, id: Uuid) -> Result<()> {
+        if let Some(created_at) = self.get_created_at(id)? {
+            batch.delete_cf(&self.created_cf, self.created_key(created_at, id));
+        }
         batch.delete_cf(&self.cf, self.key(id));
 
-        let vertex_property_manager = VertexPropertyManager::new(self.db);
+        let vertex_property_manager = VertexPropertyManager::new(&self.db, self.namespace);
         for item in vertex_property_manager.iterate_for_owner(id)? {
             let (vertex_property_owner_id, vertex_property_name, _) = item?;
             vertex_property_manager.delete(
@@ -105,10 +556,15 @@ impl<'a> VertexManager<'a> {
             )?;
         }
 
-        let edge_manager = EdgeManager::new(self.db);
+        let vertex_label_manager = VertexLabelManager::new(&self.db, self.namespace);
+        for item in vertex_label_manager.iterate_for_vertex(id) {
+            vertex_label_manager.delete(batch, id, item?);
+        }
+
+        let edge_manager = EdgeManager::new(&self.db, self.namespace);
 
         {
-            let edge_range_manager = EdgeRangeManager::new(self.db);
+            let edge_range_manager = EdgeRangeManager::new(&self.db, self.namespace);
             for item in edge_range_manager.iterate_for_root(id, None)? {
                 let edge = item?;
                 debug_assert_eq!(edge.outbound_id, id);
@@ -117,11 +573,11 @@ impl<'a> VertexManager<'a> {
         }
 
         {
-            let reversed_edge_range_manager = EdgeRangeManager::new_reversed(self.db);
+            let reversed_edge_range_manager = EdgeRangeManager::new_reversed(&self.db, self.namespace);
             for item in reversed_edge_range_manager.iterate_for_root(id, None)? {
-                let edge = item?;
-                debug_assert_eq!(edge.outbound_id, id);
-                edge_manager.delete(batch, indexed_properties, &edge)?;
+                let reversed_edge = item?;
+                debug_assert_eq!(reversed_edge.outbound_id, id);
+                edge_manager.delete(batch, indexed_properties, &reversed_edge.reversed())?;
             }
         }
 
@@ -131,21 +587,27 @@ impl<'a> VertexManager<'a> {
     pub fn compact(&self) {
         self.db
             .compact_range_cf(&self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+        self.db
+            .compact_range_cf(&self.created_cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
 
 pub(crate) struct EdgeManager<'a> {
-    db: &'a DB,
+    db: DbView<'a>,
+    namespace: Option<models::Identifier>,
 }
 
 impl<'a> EdgeManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
-        EdgeManager { db }
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
+        EdgeManager {
+            db: db.clone(),
+            namespace,
+        }
     }
 
-    pub fn set(&self, batch: &mut WriteBatch, edge: &models::Edge) -> Result<()> {
-        let edge_range_manager = EdgeRangeManager::new(self.db);
-        let reversed_edge_range_manager = EdgeRangeManager::new_reversed(self.db);
+    pub fn set(&self, batch: &mut Batch, edge: &models::Edge, expires_at: Option<SystemTime>) -> Result<()> {
+        let edge_range_manager = EdgeRangeManager::new(&self.db, self.namespace);
+        let reversed_edge_range_manager = EdgeRangeManager::new_reversed(&self.db, self.namespace);
         let reversed_edge = edge.reversed();
 
         if edge_range_manager.contains(edge)? {
@@ -153,24 +615,24 @@ impl<'a> EdgeManager<'a> {
             reversed_edge_range_manager.delete(batch, &reversed_edge)?;
         }
 
-        edge_range_manager.set(batch, edge)?;
-        reversed_edge_range_manager.set(batch, &reversed_edge)?;
+        edge_range_manager.set(batch, edge, expires_at)?;
+        reversed_edge_range_manager.set(batch, &reversed_edge, expires_at)?;
         Ok(())
     }
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         indexed_properties: &HashSet<models::Identifier>,
         edge: &models::Edge,
     ) -> Result<()> {
-        let edge_range_manager = EdgeRangeManager::new(self.db);
+        let edge_range_manager = EdgeRangeManager::new(&self.db, self.namespace);
         edge_range_manager.delete(batch, edge)?;
 
-        let reversed_edge_range_manager = EdgeRangeManager::new_reversed(self.db);
+        let reversed_edge_range_manager = EdgeRangeManager::new_reversed(&self.db, self.namespace);
         reversed_edge_range_manager.delete(batch, &edge.reversed())?;
 
-        let edge_property_manager = EdgePropertyManager::new(self.db);
+        let edge_property_manager = EdgePropertyManager::new(&self.db, self.namespace);
         for item in edge_property_manager.iterate_for_owner(edge)? {
             let (edge_property_edge, edge_property_name, _) = item?;
             edge_property_manager.delete(batch, indexed_properties, &edge_property_edge, edge_property_name)?;
@@ -181,22 +643,22 @@ impl<'a> EdgeManager<'a> {
 }
 
 pub(crate) struct EdgeRangeManager<'a> {
-    db: &'a DB,
+    db: DbView<'a>,
     cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> EdgeRangeManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         EdgeRangeManager {
-            db,
-            cf: db.cf_handle("edge_ranges:v2").unwrap(),
+            db: db.clone(),
+            cf: db.cf_handle(&cf_name(namespace, "edge_ranges:v2")).unwrap(),
         }
     }
 
-    pub fn new_reversed(db: &'a DB) -> Self {
+    pub fn new_reversed(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         EdgeRangeManager {
-            db,
-            cf: db.cf_handle("reversed_edge_ranges:v2").unwrap(),
+            db: db.clone(),
+            cf: db.cf_handle(&cf_name(namespace, "reversed_edge_ranges:v2")).unwrap(),
         }
     }
 
@@ -210,7 +672,7 @@ impl<'a> EdgeRangeManager<'a> {
 
     fn iterate<I>(&'a self, iterator: I) -> impl Iterator<Item = Result<models::Edge>> + 'a
     where
-        I: Iterator<Item = StdResult<(Box<[u8]>, Box<[u8]>), rocksdb::Error>> + 'a,
+        I: Iterator<Item = KvResult> + 'a,
     {
         iterator.map(move |item| -> Result<models::Edge> {
             let (k, _) = item?;
@@ -222,8 +684,27 @@ impl<'a> EdgeRangeManager<'a> {
         })
     }
 
+    fn iterate_live<I>(&'a self, iterator: I) -> impl Iterator<Item = Result<models::Edge>> + 'a
+    where
+        I: Iterator<Item = KvResult> + 'a,
+    {
+        let now = millis_since_epoch(SystemTime::now());
+        let iterator = iterator.filter(move |item| match item {
+            Ok((_, v)) => !is_expired(v, now),
+            Err(_) => true,
+        });
+        self.iterate(iterator)
+    }
+
     pub fn contains(&self, edge: &models::Edge) -> Result<bool> {
-        Ok(self.db.get_cf(&self.cf, self.key(edge))?.is_some())
+        let key = self.key(edge);
+        if !self.db.key_may_exist_cf(&self.cf, &key) {
+            return Ok(false);
+        }
+        match self.db.get_cf(&self.cf, key)? {
+            Some(value) => Ok(!is_expired(&value, millis_since_epoch(SystemTime::now()))),
+            None => Ok(false),
+        }
     }
 
     pub fn iterate_for_root(
@@ -253,6 +734,41 @@ impl<'a> EdgeRangeManager<'a> {
         Ok(Box::new(self.iterate(iter)))
     }
 
+    // Iterates over the edges of each root in turn. Rather than opening an
+    // iterator per root, one iterator is seeked to each of them.
+    pub fn iterate_for_roots(
+        &'a self,
+        roots: Vec<(Uuid, Option<models::Identifier>)>,
+    ) -> impl Iterator<Item = Result<models::Edge>> + 'a {
+        let mut roots = roots.into_iter();
+        let mut iter: Option<ViewIterator<'a>> = None;
+        let mut prefix: Option<Vec<u8>> = None;
+
+        let iter = std::iter::from_fn(move || loop {
+            if let (Some(iter), Some(prefix)) = (iter.as_mut(), prefix.as_ref()) {
+                match iter.next() {
+                    Some(Ok((k, v))) if k.starts_with(prefix) => return Some(Ok((k, v))),
+                    Some(Err(err)) => return Some(Err(err)),
+                    _ => {}
+                }
+            }
+
+            let (id, t) = roots.next()?;
+            let next_prefix = match t {
+                Some(t) => util::build(&[util::Component::Uuid(id), util::Component::Identifier(t)]),
+                None => util::build(&[util::Component::Uuid(id)]),
+            };
+            let mode = IteratorMode::From(&next_prefix, Direction::Forward);
+            match iter {
+                Some(ref mut iter) => iter.set_mode(mode),
+                None => iter = Some(self.db.iterator_cf(&self.cf, mode)),
+            }
+            prefix = Some(next_prefix);
+        });
+
+        self.iterate_live(iter)
+    }
+
     pub fn iterate_for_range(
         &'a self,
         first_id: Uuid,
@@ -267,21 +783,40 @@ impl<'a> EdgeRangeManager<'a> {
         let iter = self
             .db
             .iterator_cf(&self.cf, IteratorMode::From(&low_key, Direction::Forward));
-        Ok(Box::new(self.iterate(iter)))
+        Ok(Box::new(self.iterate_live(iter)))
     }
 
     pub fn iterate_for_all(&'a self) -> impl Iterator<Item = Result<models::Edge>> + 'a {
         let iterator = self.db.iterator_cf(&self.cf, IteratorMode::Start);
+        self.iterate_live(iterator)
+    }
+
+    pub fn iterate_for_expired(&'a self) -> impl Iterator<Item = Result<models::Edge>> + 'a {
+        let now = millis_since_epoch(SystemTime::now());
+        let iterator = self
+            .db
+            .iterator_cf(&self.cf, IteratorMode::Start)
+            .filter(move |item| match item {
+                Ok((_, v)) => is_expired(v, now),
+                Err(_) => true,
+            });
         self.iterate(iterator)
     }
 
-    pub fn set(&self, batch: &mut WriteBatch, edge: &models::Edge) -> Result<()> {
+    pub fn set(&self, batch: &mut Batch, edge: &models::Edge, expires_at: Option<SystemTime>) -> Result<()> {
         let key = self.key(edge);
-        batch.put_cf(&self.cf, &key, []);
+        match expires_at {
+            Some(expires_at) => {
+                let mut value = [0; 8];
+                BigEndian::write_u64(&mut value, millis_since_epoch(expires_at));
+                batch.put_cf(&self.cf, &key, value);
+            }
+            None => batch.put_cf(&self.cf, &key, []),
+        }
         Ok(())
     }
 
-    pub fn delete(&self, batch: &mut WriteBatch, edge: &models::Edge) -> Result<()> {
+    pub fn delete(&self, batch: &mut Batch, edge: &models::Edge) -> Result<()> {
         batch.delete_cf(&self.cf, self.key(edge));
         Ok(())
     }
@@ -293,15 +828,17 @@ impl<'a> EdgeRangeManager<'a> {
 }
 
 pub(crate) struct VertexPropertyManager<'a> {
-    db: &'a DB,
+    db: DbView<'a>,
+    namespace: Option<models::Identifier>,
     cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> VertexPropertyManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         VertexPropertyManager {
-            db,
-            cf: db.cf_handle("vertex_properties:v2").unwrap(),
+            db: db.clone(),
+            namespace,
+            cf: db.cf_handle(&cf_name(namespace, "vertex_properties:v2")).unwrap(),
         }
     }
 
@@ -336,6 +873,17 @@ impl<'a> VertexPropertyManager<'a> {
         }))
     }
 
+    pub fn iterate_for_all_names(&'a self) -> impl Iterator<Item = Result<models::Identifier>> + 'a {
+        let iterator = self.db.iterator_cf(&self.cf, IteratorMode::Start);
+        iterator.map(|item| -> Result<models::Identifier> {
+            let (k, _) = item?;
+            let mut cursor = Cursor::new(k);
+            util::read_uuid(&mut cursor);
+            let name_str = util::read_fixed_length_string(&mut cursor);
+            Ok(unsafe { models::Identifier::new_unchecked(name_str) })
+        })
+    }
+
     pub fn get(&self, vertex_id: Uuid, name: models::Identifier) -> Result<Option<models::Json>> {
         match self.db.get_cf(&self.cf, self.key(vertex_id, name))? {
             Some(value_bytes) => Ok(Some(serde_json::from_slice(&value_bytes)?)),
@@ -345,13 +893,13 @@ impl<'a> VertexPropertyManager<'a> {
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         indexed_properties: &HashSet<models::Identifier>,
         vertex_id: Uuid,
         name: models::Identifier,
         value: &models::Json,
     ) -> Result<()> {
-        let is_indexed = indexed_properties.contains(&name);
+        let is_indexed = util::is_indexed(indexed_properties, name);
         let key = self.key(vertex_id, name);
         if is_indexed {
             self.delete(batch, indexed_properties, vertex_id, name)?;
@@ -359,23 +907,27 @@ impl<'a> VertexPropertyManager<'a> {
         let value_json = serde_json::to_vec(value)?;
         batch.put_cf(&self.cf, &key, &value_json);
         if is_indexed {
-            let vertex_property_value_manager = VertexPropertyValueManager::new(self.db);
-            vertex_property_value_manager.set(batch, vertex_id, name, value);
+            let vertex_property_value_manager = VertexPropertyValueManager::new(&self.db, self.namespace);
+            for (index_name, index_value) in util::indexed_values(indexed_properties, name, value) {
+                vertex_property_value_manager.set(batch, vertex_id, index_name, &index_value);
+            }
         }
         Ok(())
     }
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         indexed_properties: &HashSet<models::Identifier>,
         vertex_id: Uuid,
         name: models::Identifier,
     ) -> Result<()> {
-        if indexed_properties.contains(&name) {
+        if util::is_indexed(indexed_properties, name) {
             if let Some(value) = self.get(vertex_id, name)? {
-                let vertex_property_value_manager = VertexPropertyValueManager::new(self.db);
-                vertex_property_value_manager.delete(batch, vertex_id, name, &value);
+                let vertex_property_value_manager = VertexPropertyValueManager::new(&self.db, self.namespace);
+                for (index_name, index_value) in util::indexed_values(indexed_properties, name, &value) {
+                    vertex_property_value_manager.delete(batch, vertex_id, index_name, &index_value);
+                }
             }
         }
         batch.delete_cf(&self.cf, self.key(vertex_id, name));
@@ -388,16 +940,93 @@ impl<'a> VertexPropertyManager<'a> {
     }
 }
 
+// Stores each vertex's labels, along with the same pairs keyed by label
+// first, so that vertices can be looked up by label without a scan.
+pub(crate) struct VertexLabelManager<'a> {
+    db: DbView<'a>,
+    cf: ColumnFamilyRef<'a>,
+    reversed_cf: ColumnFamilyRef<'a>,
+}
+
+impl<'a> VertexLabelManager<'a> {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
+        VertexLabelManager {
+            db: db.clone(),
+            cf: db.cf_handle(&cf_name(namespace, "vertex_labels:v2")).unwrap(),
+            reversed_cf: db.cf_handle(&cf_name(namespace, "label_vertices:v2")).unwrap(),
+        }
+    }
+
+    fn key(&self, vertex_id: Uuid, label: models::Identifier) -> Vec<u8> {
+        util::build(&[
+            util::Component::Uuid(vertex_id),
+            util::Component::FixedLengthString(&label.0),
+        ])
+    }
+
+    fn reversed_key(&self, vertex_id: Uuid, label: models::Identifier) -> Vec<u8> {
+        util::build(&[util::Component::Identifier(label), util::Component::Uuid(vertex_id)])
+    }
+
+    pub fn iterate_for_vertex(&'a self, vertex_id: Uuid) -> impl Iterator<Item = Result<models::Identifier>> + 'a {
+        let prefix = util::build(&[util::Component::Uuid(vertex_id)]);
+        let iterator = self
+            .db
+            .iterator_cf(&self.cf, IteratorMode::From(&prefix, Direction::Forward));
+
+        take_with_prefix(iterator, prefix).map(|item| -> Result<models::Identifier> {
+            let (k, _) = item?;
+            let mut cursor = Cursor::new(k);
+            util::read_uuid(&mut cursor);
+            let label_str = util::read_fixed_length_string(&mut cursor);
+            Ok(unsafe { models::Identifier::new_unchecked(label_str) })
+        })
+    }
+
+    pub fn iterate_for_label(&'a self, label: models::Identifier) -> impl Iterator<Item = Result<Uuid>> + 'a {
+        let prefix = util::build(&[util::Component::Identifier(label)]);
+        let iterator = self
+            .db
+            .iterator_cf(&self.reversed_cf, IteratorMode::From(&prefix, Direction::Forward));
+
+        take_with_prefix(iterator, prefix).map(|item| -> Result<Uuid> {
+            let (k, _) = item?;
+            let mut cursor = Cursor::new(k);
+            util::read_identifier(&mut cursor);
+            Ok(util::read_uuid(&mut cursor))
+        })
+    }
+
+    pub fn set(&self, batch: &mut Batch, vertex_id: Uuid, label: models::Identifier) {
+        batch.put_cf(&self.cf, self.key(vertex_id, label), []);
+        batch.put_cf(&self.reversed_cf, self.reversed_key(vertex_id, label), []);
+    }
+
+    pub fn delete(&self, batch: &mut Batch, vertex_id: Uuid, label: models::Identifier) {
+        batch.delete_cf(&self.cf, self.key(vertex_id, label));
+        batch.delete_cf(&self.reversed_cf, self.reversed_key(vertex_id, label));
+    }
+
+    pub fn compact(&self) {
+        self.db
+            .compact_range_cf(&self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+        self.db
+            .compact_range_cf(&self.reversed_cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
 pub(crate) struct EdgePropertyManager<'a> {
-    db: &'a DB,
+    db: DbView<'a>,
+    namespace: Option<models::Identifier>,
     cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> EdgePropertyManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         EdgePropertyManager {
-            db,
-            cf: db.cf_handle("edge_properties:v2").unwrap(),
+            db: db.clone(),
+            namespace,
+            cf: db.cf_handle(&cf_name(namespace, "edge_properties:v2")).unwrap(),
         }
     }
 
@@ -450,6 +1079,19 @@ impl<'a> EdgePropertyManager<'a> {
         Ok(Box::new(mapped))
     }
 
+    pub fn iterate_for_all_names(&'a self) -> impl Iterator<Item = Result<models::Identifier>> + 'a {
+        let iterator = self.db.iterator_cf(&self.cf, IteratorMode::Start);
+        iterator.map(|item| -> Result<models::Identifier> {
+            let (k, _) = item?;
+            let mut cursor = Cursor::new(k);
+            util::read_uuid(&mut cursor);
+            util::read_identifier(&mut cursor);
+            util::read_uuid(&mut cursor);
+            let name_str = util::read_fixed_length_string(&mut cursor);
+            Ok(unsafe { models::Identifier::new_unchecked(name_str) })
+        })
+    }
+
     pub fn get(&self, edge: &models::Edge, name: models::Identifier) -> Result<Option<models::Json>> {
         match self.db.get_cf(&self.cf, self.key(edge, name))? {
             Some(value_bytes) => Ok(Some(serde_json::from_slice(&value_bytes)?)),
@@ -459,13 +1101,13 @@ impl<'a> EdgePropertyManager<'a> {
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         indexed_properties: &HashSet<models::Identifier>,
         edge: &models::Edge,
         name: models::Identifier,
         value: &models::Json,
     ) -> Result<()> {
-        let is_indexed = indexed_properties.contains(&name);
+        let is_indexed = util::is_indexed(indexed_properties, name);
         let key = self.key(edge, name);
         if is_indexed {
             self.delete(batch, indexed_properties, edge, name)?;
@@ -473,23 +1115,27 @@ impl<'a> EdgePropertyManager<'a> {
         let value_json = serde_json::to_vec(value)?;
         batch.put_cf(&self.cf, &key, &value_json);
         if is_indexed {
-            let edge_property_value_manager = EdgePropertyValueManager::new(self.db);
-            edge_property_value_manager.set(batch, edge, name, value);
+            let edge_property_value_manager = EdgePropertyValueManager::new(&self.db, self.namespace);
+            for (index_name, index_value) in util::indexed_values(indexed_properties, name, value) {
+                edge_property_value_manager.set(batch, edge, index_name, &index_value);
+            }
         }
         Ok(())
     }
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         indexed_properties: &HashSet<models::Identifier>,
         edge: &models::Edge,
         name: models::Identifier,
     ) -> Result<()> {
-        if indexed_properties.contains(&name) {
+        if util::is_indexed(indexed_properties, name) {
             if let Some(value) = self.get(edge, name)? {
-                let edge_property_value_manager = EdgePropertyValueManager::new(self.db);
-                edge_property_value_manager.delete(batch, edge, name, &value);
+                let edge_property_value_manager = EdgePropertyValueManager::new(&self.db, self.namespace);
+                for (index_name, index_value) in util::indexed_values(indexed_properties, name, &value) {
+                    edge_property_value_manager.delete(batch, edge, index_name, &index_value);
+                }
             }
         }
         batch.delete_cf(&self.cf, self.key(edge, name));
@@ -502,16 +1148,26 @@ impl<'a> EdgePropertyManager<'a> {
     }
 }
 
+// Gets the range of keys for a property name in the property value column
+// families. Identifiers are UTF-8, which never contains a 0xff byte, so
+// bumping the last byte of the prefix gives an exclusive upper bound.
+fn property_value_name_range(property_name: models::Identifier) -> (Vec<u8>, Vec<u8>) {
+    let from = util::build(&[util::Component::Identifier(property_name)]);
+    let mut to = from.clone();
+    *to.last_mut().unwrap() += 1;
+    (from, to)
+}
+
 pub(crate) struct VertexPropertyValueManager<'a> {
-    db: &'a DB,
+    db: DbView<'a>,
     cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> VertexPropertyValueManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         VertexPropertyValueManager {
-            db,
-            cf: db.cf_handle("vertex_property_values:v2").unwrap(),
+            db: db.clone(),
+            cf: db.cf_handle(&cf_name(namespace, "vertex_property_values:v2")).unwrap(),
         }
     }
 
@@ -525,7 +1181,7 @@ impl<'a> VertexPropertyValueManager<'a> {
 
     fn iterate(
         &'a self,
-        iterator: DBIterator<'a>,
+        iterator: ViewIterator<'a>,
         prefix: Vec<u8>,
     ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
         let filtered = take_with_prefix(iterator, prefix);
@@ -568,7 +1224,7 @@ impl<'a> VertexPropertyValueManager<'a> {
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         vertex_id: Uuid,
         property_name: models::Identifier,
         property_value: &models::Json,
@@ -579,7 +1235,7 @@ impl<'a> VertexPropertyValueManager<'a> {
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         vertex_id: Uuid,
         property_name: models::Identifier,
         property_value: &models::Json,
@@ -588,6 +1244,16 @@ impl<'a> VertexPropertyValueManager<'a> {
         batch.delete_cf(&self.cf, key);
     }
 
+    pub fn delete_for_name(&self, batch: &mut Batch, property_name: models::Identifier) {
+        let (from, to) = property_value_name_range(property_name);
+        batch.delete_range_cf(&self.cf, from, to);
+    }
+
+    pub fn compact_for_name(&self, property_name: models::Identifier) {
+        let (from, to) = property_value_name_range(property_name);
+        self.db.compact_range_cf(&self.cf, Some(from), Some(to));
+    }
+
     pub fn compact(&self) {
         self.db
             .compact_range_cf(&self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
@@ -595,15 +1261,15 @@ impl<'a> VertexPropertyValueManager<'a> {
 }
 
 pub(crate) struct EdgePropertyValueManager<'a> {
-    db: &'a DB,
+    db: DbView<'a>,
     cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> EdgePropertyValueManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         EdgePropertyValueManager {
-            db,
-            cf: db.cf_handle("edge_property_values:v2").unwrap(),
+            db: db.clone(),
+            cf: db.cf_handle(&cf_name(namespace, "edge_property_values:v2")).unwrap(),
         }
     }
 
@@ -619,7 +1285,7 @@ impl<'a> EdgePropertyValueManager<'a> {
 
     fn iterate(
         &'a self,
-        iterator: DBIterator<'a>,
+        iterator: ViewIterator<'a>,
         prefix: Vec<u8>,
     ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
         let filtered = take_with_prefix(iterator, prefix);
@@ -664,7 +1330,7 @@ impl<'a> EdgePropertyValueManager<'a> {
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         edge: &models::Edge,
         property_name: models::Identifier,
         property_value: &models::Json,
@@ -675,7 +1341,7 @@ impl<'a> EdgePropertyValueManager<'a> {
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         edge: &models::Edge,
         property_name: models::Identifier,
         property_value: &models::Json,
@@ -684,6 +1350,16 @@ impl<'a> EdgePropertyValueManager<'a> {
         batch.delete_cf(&self.cf, key);
     }
 
+    pub fn delete_for_name(&self, batch: &mut Batch, property_name: models::Identifier) {
+        let (from, to) = property_value_name_range(property_name);
+        batch.delete_range_cf(&self.cf, from, to);
+    }
+
+    pub fn compact_for_name(&self, property_name: models::Identifier) {
+        let (from, to) = property_value_name_range(property_name);
+        self.db.compact_range_cf(&self.cf, Some(from), Some(to));
+    }
+
     pub fn compact(&self) {
         self.db
             .compact_range_cf(&self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
@@ -691,15 +1367,15 @@ impl<'a> EdgePropertyValueManager<'a> {
 }
 
 pub(crate) struct MetadataManager<'a> {
-    db: &'a DB,
+    db: DbView<'a>,
     cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> MetadataManager<'a> {
-    pub fn new(db: &'a DB) -> Self {
+    pub fn new(db: &DbView<'a>, namespace: Option<models::Identifier>) -> Self {
         MetadataManager {
-            db,
-            cf: db.cf_handle("metadata:v2").unwrap(),
+            db: db.clone(),
+            cf: db.cf_handle(&cf_name(namespace, "metadata:v2")).unwrap(),
         }
     }
 
@@ -710,12 +1386,55 @@ impl<'a> MetadataManager<'a> {
         }
     }
 
-    pub fn set_indexed_properties(&self, batch: &mut WriteBatch, indices: &HashSet<models::Identifier>) -> Result<()> {
+    pub fn set_indexed_properties(&self, batch: &mut Batch, indices: &HashSet<models::Identifier>) -> Result<()> {
         let value_bytes = bincode::serialize(&indices)?;
         batch.put_cf(&self.cf, "indexed_properties", &value_bytes);
         Ok(())
     }
 
+    pub fn get_property_schemas(&self) -> Result<HashMap<models::Identifier, models::JsonType>> {
+        match self.db.get_cf(&self.cf, "property_schemas")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(HashMap::default()),
+        }
+    }
+
+    pub fn set_property_schemas(
+        &self,
+        batch: &mut Batch,
+        schemas: &HashMap<models::Identifier, models::JsonType>,
+    ) -> Result<()> {
+        let value_bytes = bincode::serialize(&schemas)?;
+        batch.put_cf(&self.cf, "property_schemas", &value_bytes);
+        Ok(())
+    }
+
+    pub fn get_vertex_type_counts(&self) -> Result<Option<HashMap<models::Identifier, u64>>> {
+        match self.db.get_cf(&self.cf, "vertex_type_counts")? {
+            Some(value_bytes) => Ok(Some(bincode::deserialize(&value_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_vertex_type_counts(&self, batch: &mut Batch, counts: &HashMap<models::Identifier, u64>) -> Result<()> {
+        let value_bytes = bincode::serialize(&counts)?;
+        batch.put_cf(&self.cf, "vertex_type_counts", &value_bytes);
+        Ok(())
+    }
+
+    pub fn get_property_name_counts(&self) -> Result<Option<PropertyNameCounts>> {
+        match self.db.get_cf(&self.cf, "property_name_counts")? {
+            Some(value_bytes) => Ok(Some(bincode::deserialize(&value_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_property_name_counts(&self, batch: &mut Batch, counts: &PropertyNameCounts) -> Result<()> {
+        let value_bytes = bincode::serialize(&counts)?;
+        batch.put_cf(&self.cf, "property_name_counts", &value_bytes);
+        Ok(())
+    }
+
     pub fn compact(&self) {
         self.db
             .compact_range_cf(&self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
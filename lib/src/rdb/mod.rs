@@ -3,7 +3,7 @@
 mod datastore;
 mod managers;
 
-pub use self::datastore::RocksdbDatastore;
+pub use self::datastore::{RocksdbConfig, RocksdbDatastore};
 
 #[cfg(feature = "bench-suite")]
 full_bench_impl!({
@@ -28,6 +28,60 @@ mod tests {
         RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(Some(1))).unwrap()
     });
 
+    #[cfg(feature = "test-suite")]
+    define_test!(should_not_get_expired_edges, {
+        use super::RocksdbDatastore;
+        use tempfile::tempdir;
+
+        let path = tempdir().unwrap().into_path();
+        RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(Some(1))).unwrap()
+    });
+
+    #[cfg(feature = "test-suite")]
+    define_test!(should_not_create_an_invalid_edge_with_ttl, {
+        use super::RocksdbDatastore;
+        use tempfile::tempdir;
+
+        let path = tempdir().unwrap().into_path();
+        RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(Some(1))).unwrap()
+    });
+
+    #[cfg(feature = "test-suite")]
+    define_test!(should_index_property_path, {
+        use super::RocksdbDatastore;
+        use tempfile::tempdir;
+
+        let path = tempdir().unwrap().into_path();
+        RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(Some(1))).unwrap()
+    });
+
+    #[cfg(feature = "test-suite")]
+    define_test!(should_validate_property_schemas, {
+        use super::RocksdbDatastore;
+        use tempfile::tempdir;
+
+        let path = tempdir().unwrap().into_path();
+        RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(Some(1))).unwrap()
+    });
+
+    #[cfg(feature = "test-suite")]
+    define_test!(should_add_and_remove_vertex_labels, {
+        use super::RocksdbDatastore;
+        use tempfile::tempdir;
+
+        let path = tempdir().unwrap().into_path();
+        RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(Some(1))).unwrap()
+    });
+
+    #[cfg(feature = "test-suite")]
+    define_test!(should_get_vertices_by_creation_time, {
+        use super::RocksdbDatastore;
+        use tempfile::tempdir;
+
+        let path = tempdir().unwrap().into_path();
+        RocksdbDatastore::new_db_with_options(path, &RocksdbDatastore::get_options(Some(1))).unwrap()
+    });
+
     #[test]
     fn should_repair() {
         use super::RocksdbDatastore;
@@ -42,6 +96,142 @@ mod tests {
         RocksdbDatastore::repair(dir.path(), &RocksdbDatastore::get_options(Some(1))).unwrap();
     }
 
+    #[test]
+    fn should_open_with_config() {
+        use super::{RocksdbConfig, RocksdbDatastore};
+        use rocksdb::DBCompactionStyle;
+
+        let dir = tempdir().unwrap();
+        let config = RocksdbConfig {
+            block_cache_size: Some(1_048_576),
+            max_open_files: Some(1),
+            compaction_style: DBCompactionStyle::Universal,
+            sync_writes: true,
+        };
+        let db = RocksdbDatastore::new_db_with_config(dir.path(), &config).unwrap();
+        let id = db.create_vertex_from_type(crate::Identifier::default()).unwrap();
+        drop(db);
+
+        // Reopening with the defaults should see the same data
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let vertices = crate::util::extract_vertices(db.get(crate::SpecificVertexQuery::single(id)).unwrap()).unwrap();
+        assert_eq!(vertices.len(), 1);
+    }
+
+    #[test]
+    fn should_persist_vertex_created_at() {
+        use super::RocksdbDatastore;
+        use crate::{CreatedRangeVertexQuery, Identifier};
+
+        let dir = tempdir().unwrap();
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let id = db.create_vertex_from_type(Identifier::default()).unwrap();
+        let created_at = db.get_vertex_created_at(id).unwrap();
+        assert!(created_at.is_some());
+        drop(db);
+
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        assert_eq!(db.get_vertex_created_at(id).unwrap(), created_at);
+        let vertices = crate::util::extract_vertices(db.get(CreatedRangeVertexQuery::new()).unwrap()).unwrap();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].id, id);
+    }
+
+    #[test]
+    fn should_persist_property_schemas() {
+        use super::RocksdbDatastore;
+        use crate::{Identifier, JsonType};
+
+        let dir = tempdir().unwrap();
+        let name = Identifier::new("age").unwrap();
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        db.register_property_schema(name, JsonType::Number).unwrap();
+        drop(db);
+
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        assert_eq!(db.get_property_schemas().unwrap(), vec![(name, JsonType::Number)]);
+        let id = db.create_vertex_from_type(Identifier::default()).unwrap();
+        let result = db.set_properties(crate::SpecificVertexQuery::single(id), name, &crate::ijson!("thirty"));
+        assert!(matches!(result, Err(crate::Error::SchemaViolation)));
+    }
+
+    #[test]
+    fn should_persist_long_identifiers() {
+        use super::RocksdbDatastore;
+        use crate::{Edge, Identifier, SpecificEdgeQuery, SpecificVertexQuery};
+
+        let dir = tempdir().unwrap();
+        // Created unchecked rather than by raising the process-wide maximum
+        // length, which would affect other tests
+        let t = unsafe { Identifier::new_unchecked("t".repeat(300)) };
+        let exact_t = unsafe { Identifier::new_unchecked("e".repeat(255)) };
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let outbound_id = db.create_vertex_from_type(t).unwrap();
+        let inbound_id = db.create_vertex_from_type(exact_t).unwrap();
+        let edge = Edge::new(outbound_id, t, inbound_id);
+        db.create_edge(&edge).unwrap();
+        db.set_properties(SpecificEdgeQuery::single(edge.clone()), exact_t, &crate::ijson!(true))
+            .unwrap();
+        drop(db);
+
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let vertices =
+            crate::util::extract_vertices(db.get(SpecificVertexQuery::new(vec![outbound_id, inbound_id])).unwrap())
+                .unwrap();
+        assert_eq!(vertices.len(), 2);
+        assert!(vertices.iter().any(|v| v.id == outbound_id && v.t == t));
+        assert!(vertices.iter().any(|v| v.id == inbound_id && v.t == exact_t));
+        let edges = crate::util::extract_edges(
+            db.get(SpecificVertexQuery::single(outbound_id).outbound().unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(edges, vec![edge.clone()]);
+        let props = crate::util::extract_edge_properties(
+            db.get(SpecificEdgeQuery::single(edge).properties().unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(props[0].props[0].name, exact_t);
+    }
+
+    #[test]
+    fn should_namespace_graphs() {
+        use super::RocksdbDatastore;
+        use crate::{AllVertexQuery, Datastore, Identifier, Transaction};
+
+        let dir = tempdir().unwrap();
+        let ns = Identifier::new("tenant").unwrap();
+        let t = Identifier::new("test_vertex_type").unwrap();
+        let mut db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        assert!(db.datastore.namespaced(ns).is_err());
+        db.datastore.create_namespace(ns).unwrap();
+
+        let id = db.create_vertex_from_type(t).unwrap();
+        let namespaced_db = db.datastore.namespaced(ns).unwrap();
+        let namespaced_id = namespaced_db.create_vertex_from_type(t).unwrap();
+
+        // Each namespace only sees its own vertices
+        let vertices = crate::util::extract_vertices(db.get(AllVertexQuery).unwrap()).unwrap();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].id, id);
+        let vertices = crate::util::extract_vertices(namespaced_db.get(AllVertexQuery).unwrap()).unwrap();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].id, namespaced_id);
+
+        // Deletes are confined to the namespace too
+        assert_eq!(namespaced_db.delete(AllVertexQuery).unwrap(), 1);
+        assert_eq!(db.get_vertex_count_by_type(t).unwrap(), 1);
+        assert_eq!(namespaced_db.get_vertex_count_by_type(t).unwrap(), 0);
+        drop(namespaced_db);
+        drop(db);
+
+        // Namespaces persist across reopening
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let namespaced_db = db.datastore.namespaced(ns).unwrap();
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 1);
+        assert_eq!(namespaced_db.datastore.readonly_transaction().vertex_count(), 0);
+    }
+
     // Tests for a regression where reversed range queries were incorrect.
     // See https://github.com/indradb/indradb/issues/280
     #[test]
@@ -73,4 +263,194 @@ mod tests {
             assert_eq!(edge.outbound_id, out_v.id);
         }
     }
+
+    #[test]
+    fn should_read_from_snapshot() {
+        use super::RocksdbDatastore;
+        use crate::{Datastore, Identifier, Transaction};
+
+        let dir = tempdir().unwrap();
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let t = Identifier::new("test_vertex_type").unwrap();
+        db.create_vertex_from_type(t).unwrap();
+
+        // Read-only transactions don't see writes committed after they start
+        let txn = db.datastore.readonly_transaction();
+        db.create_vertex_from_type(t).unwrap();
+        assert_eq!(txn.vertex_count(), 1);
+        assert_eq!(txn.vertex_count_by_type(t).unwrap(), 1);
+        drop(txn);
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 2);
+    }
+
+    #[test]
+    fn should_only_write_on_commit() {
+        use super::RocksdbDatastore;
+        use crate::{Datastore, Edge, Identifier, Transaction, Vertex};
+
+        let dir = tempdir().unwrap();
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let t = Identifier::new("test_vertex_type").unwrap();
+        let outbound_v = Vertex::new(t);
+        let inbound_v = Vertex::new(t);
+        let edge = Edge::new(outbound_v.id, Identifier::new("test_edge_type").unwrap(), inbound_v.id);
+
+        // Pending writes are visible to later reads in the same transaction,
+        // but not to other transactions
+        let mut txn = db.datastore.transaction();
+        assert!(txn.create_vertex(&outbound_v).unwrap());
+        assert!(!txn.create_vertex(&outbound_v).unwrap());
+        assert!(txn.create_vertex(&inbound_v).unwrap());
+        assert!(txn.create_edge(&edge).unwrap());
+        assert!(!txn
+            .create_edge(&Edge::new(outbound_v.id, t, Vertex::new(t).id))
+            .unwrap());
+        assert!(txn.contains_edge(&edge).unwrap());
+        assert_eq!(txn.vertex_count(), 2);
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 0);
+        assert_eq!(db.get_vertex_count_by_type(t).unwrap(), 0);
+        txn.commit().unwrap();
+        assert_eq!(db.datastore.readonly_transaction().edge_count(), 1);
+        assert_eq!(db.get_vertex_count_by_type(t).unwrap(), 2);
+
+        // Dropping the transaction discards its writes
+        let mut txn = db.datastore.transaction();
+        assert!(txn.create_vertex(&Vertex::new(t)).unwrap());
+        txn.delete_vertices(vec![outbound_v]).unwrap();
+        drop(txn);
+        assert_eq!(db.get_vertex_count_by_type(t).unwrap(), 2);
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 2);
+        assert!(db.contains_edge(&edge).unwrap());
+    }
+
+    #[test]
+    fn should_iterate_over_pending_writes() {
+        use super::RocksdbDatastore;
+        use crate::{ijson, Datastore, Identifier, Transaction, Vertex};
+        use uuid::Uuid;
+
+        let dir = tempdir().unwrap();
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let t = Identifier::new("test_vertex_type").unwrap();
+        let name = Identifier::new("name").unwrap();
+        let mut committed: Vec<Vertex> = (0..3).map(|_| Vertex::new(t)).collect();
+        for vertex in &committed {
+            db.create_vertex(vertex).unwrap();
+        }
+        db.index_property(name).unwrap();
+
+        let mut txn = db.datastore.transaction();
+        let created: Vec<Vertex> = (0..2).map(|_| Vertex::new(t)).collect();
+        for vertex in &created {
+            assert!(txn.create_vertex(vertex).unwrap());
+        }
+        txn.delete_vertices(vec![committed.remove(1)]).unwrap();
+        let mut expected: Vec<Vertex> = committed.into_iter().chain(created).collect();
+        expected.sort_by_key(|vertex| vertex.id);
+
+        let vertices: Vec<Vertex> = txn.all_vertices().unwrap().map(|item| item.unwrap()).collect();
+        assert_eq!(vertices, expected);
+        let vertices: Vec<Vertex> = txn
+            .range_vertices_descending(Uuid::from_u128(u128::MAX))
+            .unwrap()
+            .map(|item| item.unwrap())
+            .collect();
+        expected.reverse();
+        assert_eq!(vertices, expected);
+
+        // Writes made after a range delete are seen over it
+        txn.set_vertex_properties(vec![expected[0].id], name, &ijson!("alice"))
+            .unwrap();
+        txn.drop_index(name).unwrap();
+        txn.index_property(name).unwrap();
+        let ids: Vec<Uuid> = txn
+            .vertex_ids_with_property_value(name, &ijson!("alice"))
+            .unwrap()
+            .unwrap()
+            .map(|item| item.unwrap())
+            .collect();
+        assert_eq!(ids, vec![expected[0].id]);
+    }
+
+    #[test]
+    fn should_create_vertices_from_concurrent_transactions() {
+        use super::RocksdbDatastore;
+        use crate::{Datastore, Identifier, Transaction, Vertex};
+        use std::sync::Barrier;
+        use std::thread;
+
+        let dir = tempdir().unwrap();
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let types = [Identifier::new("a").unwrap(), Identifier::new("b").unwrap()];
+
+        // Two transactions create each vertex, with different types, so only
+        // one of them can succeed
+        for _ in 0..10 {
+            let id = Vertex::new(types[0]).id;
+            let barrier = Barrier::new(types.len());
+            let created: Vec<bool> = thread::scope(|s| {
+                let handles: Vec<_> = types
+                    .iter()
+                    .map(|t| {
+                        let (db, barrier) = (&db, &barrier);
+                        s.spawn(move || {
+                            barrier.wait();
+                            let mut txn = db.datastore.transaction();
+                            let created = txn.create_vertex(&Vertex::with_id(id, *t)).unwrap();
+                            txn.commit().unwrap();
+                            created
+                        })
+                    })
+                    .collect();
+                handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+            });
+            assert_eq!(created.iter().filter(|created| **created).count(), 1);
+        }
+
+        let counts: Vec<u64> = types.iter().map(|t| db.get_vertex_count_by_type(*t).unwrap()).collect();
+        assert_eq!(counts.iter().sum::<u64>(), 10);
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 10);
+    }
+
+    #[test]
+    fn should_roll_back_transaction() {
+        use super::RocksdbDatastore;
+        use crate::util::{extract_count, extract_vertices};
+        use crate::{
+            ijson, AllEdgeQuery, CountQueryExt, Datastore, Edge, Identifier, SpecificVertexQuery, Transaction, Vertex,
+            VertexWithPropertyValueQuery,
+        };
+
+        let dir = tempdir().unwrap();
+        let db = RocksdbDatastore::new_db(dir.path()).unwrap();
+        let name = Identifier::new("name").unwrap();
+        let t = Identifier::new("test_vertex_type").unwrap();
+        db.index_property(name).unwrap();
+        let id = db.create_vertex_from_type(t).unwrap();
+        db.set_properties(SpecificVertexQuery::single(id), name, &ijson!("alice"))
+            .unwrap();
+
+        let mut txn = db.datastore.transaction();
+        let other = Vertex::new(t);
+        let edge = Edge::new(id, t, other.id);
+        assert!(txn.create_vertex(&other).unwrap());
+        assert!(txn.create_edge(&edge).unwrap());
+        txn.set_vertex_properties(vec![id, other.id], name, &ijson!("bob"))
+            .unwrap();
+        txn.delete_vertices(vec![Vertex::with_id(id, t)]).unwrap();
+        txn.drop_index(name).unwrap();
+        txn.rollback().unwrap();
+
+        assert!(db.get_vertex(id).unwrap().is_some());
+        assert!(db.get_vertex(other.id).unwrap().is_none());
+        assert_eq!(db.get_vertex_count_by_type(t).unwrap(), 1);
+        assert_eq!(extract_count(db.get(AllEdgeQuery.count().unwrap()).unwrap()), Some(0));
+        assert_eq!(db.get_indexed_properties().unwrap(), vec![name]);
+        let vertices = extract_vertices(
+            db.get(VertexWithPropertyValueQuery::new(name, ijson!("alice")))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vertices, vec![Vertex::with_id(id, t)]);
+    }
 }
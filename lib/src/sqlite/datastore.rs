@@ -0,0 +1,615 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::errors::{Error, Result};
+use crate::{BulkInsertItem, Database, Datastore, DynIter, Edge, Identifier, Json, Transaction, Vertex};
+
+use rusqlite::{params, Connection, OptionalExtension, Params};
+use uuid::Uuid;
+
+// Vertex and edge keys are stored as blobs and identifiers as text, which
+// SQLite compares bytewise. That matches the ordering of `Uuid` and
+// `Identifier`, so range queries return results in the same order as the
+// other datastores.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS vertices (
+        id BLOB NOT NULL PRIMARY KEY,
+        t TEXT NOT NULL
+    ) WITHOUT ROWID;
+
+    CREATE INDEX IF NOT EXISTS ix_vertices_t ON vertices (t);
+
+    CREATE TABLE IF NOT EXISTS edges (
+        outbound_id BLOB NOT NULL,
+        t TEXT NOT NULL,
+        inbound_id BLOB NOT NULL,
+        PRIMARY KEY (outbound_id, t, inbound_id)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS ix_edges_reversed ON edges (inbound_id, t, outbound_id);
+
+    CREATE TABLE IF NOT EXISTS vertex_properties (
+        owner_id BLOB NOT NULL,
+        name TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (owner_id, name)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS ix_vertex_properties_name_value ON vertex_properties (name, value);
+
+    CREATE TABLE IF NOT EXISTS edge_properties (
+        outbound_id BLOB NOT NULL,
+        t TEXT NOT NULL,
+        inbound_id BLOB NOT NULL,
+        name TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (outbound_id, t, inbound_id, name)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS ix_edge_properties_name_value ON edge_properties (name, value);
+    CREATE INDEX IF NOT EXISTS ix_edge_properties_inbound_id ON edge_properties (inbound_id);
+
+    CREATE TABLE IF NOT EXISTS indexed_properties (
+        name TEXT NOT NULL PRIMARY KEY
+    ) WITHOUT ROWID;
+";
+
+fn json_to_sql(value: &Json) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+fn json_from_sql(value: &str) -> Result<Json> {
+    Ok(serde_json::from_str(value)?)
+}
+
+fn create_vertex(conn: &Connection, vertex: &Vertex) -> Result<bool> {
+    let changed = conn.execute(
+        "INSERT OR IGNORE INTO vertices (id, t) VALUES (?1, ?2)",
+        params![vertex.id, vertex.t.as_str()],
+    )?;
+    Ok(changed > 0)
+}
+
+fn create_edge(conn: &Connection, edge: &Edge) -> Result<bool> {
+    Ok(insert_edge(conn, edge)?.is_some())
+}
+
+/// Inserts an edge if both of its vertices exist. Returns `None` if a vertex
+/// is missing, and otherwise whether a new row was inserted.
+fn insert_edge(conn: &Connection, edge: &Edge) -> Result<Option<bool>> {
+    let mut stmt = conn.prepare_cached("SELECT EXISTS(SELECT 1 FROM vertices WHERE id = ?1)")?;
+    for id in [edge.outbound_id, edge.inbound_id] {
+        if !stmt.query_row(params![id], |row| row.get::<_, bool>(0))? {
+            return Ok(None);
+        }
+    }
+
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO edges (outbound_id, t, inbound_id) VALUES (?1, ?2, ?3)",
+        params![edge.outbound_id, edge.t.as_str(), edge.inbound_id],
+    )?;
+    Ok(Some(inserted > 0))
+}
+
+fn set_vertex_property(conn: &Connection, id: Uuid, name: Identifier, value: &str) -> Result<()> {
+    conn.prepare_cached("INSERT OR REPLACE INTO vertex_properties (owner_id, name, value) VALUES (?1, ?2, ?3)")?
+        .execute(params![id, name.as_str(), value])?;
+    Ok(())
+}
+
+fn set_edge_property(conn: &Connection, edge: &Edge, name: Identifier, value: &str) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO edge_properties (outbound_id, t, inbound_id, name, value) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?
+    .execute(params![
+        edge.outbound_id,
+        edge.t.as_str(),
+        edge.inbound_id,
+        name.as_str(),
+        value
+    ])?;
+    Ok(())
+}
+
+pub struct SqliteTransaction<'a> {
+    conn: MutexGuard<'a, Connection>,
+    readonly: bool,
+}
+
+impl<'a> SqliteTransaction<'a> {
+    fn check_writable(&self) -> Result<()> {
+        if self.readonly {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn is_indexed(&self, name: Identifier) -> Result<bool> {
+        let indexed = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM indexed_properties WHERE name = ?1)",
+            params![name.as_str()],
+            |row| row.get(0),
+        )?;
+        Ok(indexed)
+    }
+
+    // Counts rows for the infallible count methods, which can't return the
+    // error, so it's logged and the count is reported as 0.
+    fn count(&self, sql: &str) -> u64 {
+        match self.conn.query_row(sql, [], |row| row.get::<_, i64>(0)) {
+            Ok(count) => count as u64,
+            Err(err) => {
+                log::error!("failed to count rows with `{sql}`: {err}");
+                0
+            }
+        }
+    }
+
+    fn query_ids<P: Params>(&self, sql: &str, params: P) -> Result<Vec<Uuid>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let ids = stmt
+            .query_map(params, |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<Uuid>>>()?;
+        Ok(ids)
+    }
+
+    fn query_names(&self, sql: &str) -> Result<Vec<Identifier>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut names = Vec::new();
+        for row in rows {
+            names.push(Identifier::new(row?)?);
+        }
+        Ok(names)
+    }
+
+    fn query_vertices<P: Params>(&self, sql: &str, params: P) -> Result<Vec<Vertex>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let rows = stmt.query_map(params, |row| Ok((row.get::<_, Uuid>(0)?, row.get::<_, String>(1)?)))?;
+        let mut vertices = Vec::new();
+        for row in rows {
+            let (id, t) = row?;
+            vertices.push(Vertex::with_id(id, Identifier::new(t)?));
+        }
+        Ok(vertices)
+    }
+
+    // Builds edges from rows of `(outbound_id, t, inbound_id)`. Selecting the
+    // columns in the opposite order yields reversed edges.
+    fn query_edges<P: Params>(&self, sql: &str, params: P) -> Result<Vec<Edge>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            Ok((row.get::<_, Uuid>(0)?, row.get::<_, String>(1)?, row.get::<_, Uuid>(2)?))
+        })?;
+        let mut edges = Vec::new();
+        for row in rows {
+            let (outbound_id, t, inbound_id) = row?;
+            edges.push(Edge::new(outbound_id, Identifier::new(t)?, inbound_id));
+        }
+        Ok(edges)
+    }
+
+    fn query_property<P: Params>(&self, sql: &str, params: P) -> Result<Option<Json>> {
+        let value: Option<String> = self
+            .conn
+            .prepare_cached(sql)?
+            .query_row(params, |row| row.get(0))
+            .optional()?;
+        match value {
+            Some(value) => Ok(Some(json_from_sql(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn query_properties<P: Params>(&self, sql: &str, params: P) -> Result<Vec<(Identifier, Json)>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let rows = stmt.query_map(params, |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut properties = Vec::new();
+        for row in rows {
+            let (name, value) = row?;
+            properties.push((Identifier::new(name)?, json_from_sql(&value)?));
+        }
+        Ok(properties)
+    }
+}
+
+impl<'a> Transaction<'a> for SqliteTransaction<'a> {
+    fn vertex_count(&self) -> u64 {
+        self.count("SELECT COUNT(*) FROM vertices")
+    }
+
+    fn vertex_count_by_type(&self, t: Identifier) -> Result<u64> {
+        let count: i64 = self
+            .conn
+            .prepare_cached("SELECT COUNT(*) FROM vertices WHERE t = ?1")?
+            .query_row(params![t.as_str()], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+
+    fn vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        self.query_names("SELECT DISTINCT name FROM vertex_properties ORDER BY name")
+    }
+
+    fn edge_property_names(&self) -> Result<Vec<Identifier>> {
+        self.query_names("SELECT DISTINCT name FROM edge_properties ORDER BY name")
+    }
+
+    fn indexed_properties(&self) -> Result<Vec<Identifier>> {
+        self.query_names("SELECT name FROM indexed_properties ORDER BY name")
+    }
+
+    fn all_vertices(&'a self) -> Result<DynIter<'a, Vertex>> {
+        let vertices = self.query_vertices("SELECT id, t FROM vertices ORDER BY id", [])?;
+        Ok(Box::new(vertices.into_iter().map(Ok)))
+    }
+
+    fn range_vertices(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        let vertices = self.query_vertices("SELECT id, t FROM vertices WHERE id >= ?1 ORDER BY id", params![offset])?;
+        Ok(Box::new(vertices.into_iter().map(Ok)))
+    }
+
+    fn range_vertices_descending(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        let vertices = self.query_vertices(
+            "SELECT id, t FROM vertices WHERE id <= ?1 ORDER BY id DESC",
+            params![offset],
+        )?;
+        Ok(Box::new(vertices.into_iter().map(Ok)))
+    }
+
+    // This runs a SELECT per ID, rather than one with a huge `IN` list, and
+    // reads every result into memory before returning.
+    fn specific_vertices(&'a self, ids: Vec<Uuid>) -> Result<DynIter<'a, Vertex>> {
+        let mut vertices = Vec::with_capacity(ids.len());
+        for id in ids {
+            vertices.extend(self.query_vertices("SELECT id, t FROM vertices WHERE id = ?1", params![id])?);
+        }
+        Ok(Box::new(vertices.into_iter().map(Ok)))
+    }
+
+    fn vertex_ids_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Uuid>>> {
+        if !self.is_indexed(name)? {
+            return Ok(None);
+        }
+        let ids = self.query_ids(
+            "SELECT owner_id FROM vertex_properties WHERE name = ?1",
+            params![name.as_str()],
+        )?;
+        Ok(Some(Box::new(ids.into_iter().map(Ok))))
+    }
+
+    fn vertex_ids_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Uuid>>> {
+        if !self.is_indexed(name)? {
+            return Ok(None);
+        }
+        let ids = self.query_ids(
+            "SELECT owner_id FROM vertex_properties WHERE name = ?1 AND value = ?2",
+            params![name.as_str(), json_to_sql(value)?],
+        )?;
+        Ok(Some(Box::new(ids.into_iter().map(Ok))))
+    }
+
+    fn edge_count(&self) -> u64 {
+        self.count("SELECT COUNT(*) FROM edges")
+    }
+
+    fn all_edges(&'a self) -> Result<DynIter<'a, Edge>> {
+        let edges = self.query_edges(
+            "SELECT outbound_id, t, inbound_id FROM edges ORDER BY outbound_id, t, inbound_id",
+            [],
+        )?;
+        Ok(Box::new(edges.into_iter().map(Ok)))
+    }
+
+    fn range_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>> {
+        let edges = self.query_edges(
+            "SELECT outbound_id, t, inbound_id FROM edges
+            WHERE (outbound_id, t, inbound_id) >= (?1, ?2, ?3)
+            ORDER BY outbound_id, t, inbound_id",
+            params![offset.outbound_id, offset.t.as_str(), offset.inbound_id],
+        )?;
+        Ok(Box::new(edges.into_iter().map(Ok)))
+    }
+
+    fn range_reversed_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>> {
+        let edges = self.query_edges(
+            "SELECT inbound_id, t, outbound_id FROM edges
+            WHERE (inbound_id, t, outbound_id) >= (?1, ?2, ?3)
+            ORDER BY inbound_id, t, outbound_id",
+            params![offset.outbound_id, offset.t.as_str(), offset.inbound_id],
+        )?;
+        Ok(Box::new(edges.into_iter().map(Ok)))
+    }
+
+    // Like `specific_vertices`, this runs a SELECT per edge, and reads every
+    // result into memory before returning.
+    fn specific_edges(&'a self, edges: Vec<Edge>) -> Result<DynIter<'a, Edge>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT EXISTS(SELECT 1 FROM edges WHERE outbound_id = ?1 AND t = ?2 AND inbound_id = ?3)",
+        )?;
+        let mut existing = Vec::with_capacity(edges.len());
+        for edge in edges {
+            if stmt.query_row(params![edge.outbound_id, edge.t.as_str(), edge.inbound_id], |row| {
+                row.get::<_, bool>(0)
+            })? {
+                existing.push(edge);
+            }
+        }
+        Ok(Box::new(existing.into_iter().map(Ok)))
+    }
+
+    fn edges_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Edge>>> {
+        if !self.is_indexed(name)? {
+            return Ok(None);
+        }
+        let edges = self.query_edges(
+            "SELECT outbound_id, t, inbound_id FROM edge_properties WHERE name = ?1",
+            params![name.as_str()],
+        )?;
+        Ok(Some(Box::new(edges.into_iter().map(Ok))))
+    }
+
+    fn edges_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Edge>>> {
+        if !self.is_indexed(name)? {
+            return Ok(None);
+        }
+        let edges = self.query_edges(
+            "SELECT outbound_id, t, inbound_id FROM edge_properties WHERE name = ?1 AND value = ?2",
+            params![name.as_str(), json_to_sql(value)?],
+        )?;
+        Ok(Some(Box::new(edges.into_iter().map(Ok))))
+    }
+
+    fn vertex_property(&self, vertex: &Vertex, name: Identifier) -> Result<Option<Json>> {
+        self.query_property(
+            "SELECT value FROM vertex_properties WHERE owner_id = ?1 AND name = ?2",
+            params![vertex.id, name.as_str()],
+        )
+    }
+
+    fn all_vertex_properties_for_vertex(&'a self, vertex: &Vertex) -> Result<DynIter<'a, (Identifier, Json)>> {
+        let properties = self.query_properties(
+            "SELECT name, value FROM vertex_properties WHERE owner_id = ?1 ORDER BY name",
+            params![vertex.id],
+        )?;
+        Ok(Box::new(properties.into_iter().map(Ok)))
+    }
+
+    fn edge_property(&self, edge: &Edge, name: Identifier) -> Result<Option<Json>> {
+        self.query_property(
+            "SELECT value FROM edge_properties WHERE outbound_id = ?1 AND t = ?2 AND inbound_id = ?3 AND name = ?4",
+            params![edge.outbound_id, edge.t.as_str(), edge.inbound_id, name.as_str()],
+        )
+    }
+
+    fn all_edge_properties_for_edge(&'a self, edge: &Edge) -> Result<DynIter<'a, (Identifier, Json)>> {
+        let properties = self.query_properties(
+            "SELECT name, value FROM edge_properties
+            WHERE outbound_id = ?1 AND t = ?2 AND inbound_id = ?3
+            ORDER BY name",
+            params![edge.outbound_id, edge.t.as_str(), edge.inbound_id],
+        )?;
+        Ok(Box::new(properties.into_iter().map(Ok)))
+    }
+
+    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<u64> {
+        self.check_writable()?;
+        let tx = self.conn.savepoint()?;
+        let mut count = 0;
+        for vertex in vertices {
+            tx.execute("DELETE FROM vertex_properties WHERE owner_id = ?1", params![vertex.id])?;
+            tx.execute(
+                "DELETE FROM edge_properties WHERE outbound_id = ?1 OR inbound_id = ?1",
+                params![vertex.id],
+            )?;
+            tx.execute(
+                "DELETE FROM edges WHERE outbound_id = ?1 OR inbound_id = ?1",
+                params![vertex.id],
+            )?;
+            count += tx.execute("DELETE FROM vertices WHERE id = ?1", params![vertex.id])? as u64;
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<u64> {
+        self.check_writable()?;
+        let tx = self.conn.savepoint()?;
+        let mut count = 0;
+        for edge in edges {
+            tx.execute(
+                "DELETE FROM edge_properties WHERE outbound_id = ?1 AND t = ?2 AND inbound_id = ?3",
+                params![edge.outbound_id, edge.t.as_str(), edge.inbound_id],
+            )?;
+            count += tx.execute(
+                "DELETE FROM edges WHERE outbound_id = ?1 AND t = ?2 AND inbound_id = ?3",
+                params![edge.outbound_id, edge.t.as_str(), edge.inbound_id],
+            )? as u64;
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    fn delete_vertex_properties(&mut self, props: Vec<(Uuid, Identifier)>) -> Result<()> {
+        self.check_writable()?;
+        let tx = self.conn.savepoint()?;
+        for (id, name) in props {
+            tx.execute(
+                "DELETE FROM vertex_properties WHERE owner_id = ?1 AND name = ?2",
+                params![id, name.as_str()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_edge_properties(&mut self, props: Vec<(Edge, Identifier)>) -> Result<()> {
+        self.check_writable()?;
+        let tx = self.conn.savepoint()?;
+        for (edge, name) in props {
+            tx.execute(
+                "DELETE FROM edge_properties WHERE outbound_id = ?1 AND t = ?2 AND inbound_id = ?3 AND name = ?4",
+                params![edge.outbound_id, edge.t.as_str(), edge.inbound_id, name.as_str()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn sync(&self) -> Result<()> {
+        // Writes are durable as soon as their SQLite transaction commits, so
+        // there's nothing to flush.
+        Ok(())
+    }
+
+    fn commit(self) -> Result<()> {
+        self.conn.execute_batch("COMMIT")?;
+        Ok(())
+    }
+
+    fn rollback(self) -> Result<()> {
+        self.conn.execute_batch("ROLLBACK")?;
+        Ok(())
+    }
+
+    fn create_vertex(&mut self, vertex: &Vertex) -> Result<bool> {
+        self.check_writable()?;
+        create_vertex(&self.conn, vertex)
+    }
+
+    fn create_edge(&mut self, edge: &Edge) -> Result<bool> {
+        self.check_writable()?;
+        create_edge(&self.conn, edge)
+    }
+
+    fn create_edge_if_absent(&mut self, edge: &Edge) -> Result<bool> {
+        self.check_writable()?;
+        Ok(insert_edge(&self.conn, edge)? == Some(true))
+    }
+
+    fn bulk_insert(&mut self, items: Vec<BulkInsertItem>) -> Result<()> {
+        self.check_writable()?;
+        let tx = self.conn.savepoint()?;
+        for item in items {
+            match item {
+                BulkInsertItem::Vertex(vertex) => {
+                    create_vertex(&tx, &vertex)?;
+                }
+                BulkInsertItem::Edge(edge) => {
+                    create_edge(&tx, &edge)?;
+                }
+                BulkInsertItem::VertexProperty(id, name, value) => {
+                    set_vertex_property(&tx, id, name, &json_to_sql(&value)?)?;
+                }
+                BulkInsertItem::EdgeProperty(edge, name, value) => {
+                    set_edge_property(&tx, &edge, name, &json_to_sql(&value)?)?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn index_property(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
+        // Properties are queried directly rather than through an index, so
+        // there's nothing to extract nested values into
+        if !name.split_path().1.is_empty() {
+            return Err(Error::Unsupported);
+        }
+        self.conn.execute(
+            "INSERT OR IGNORE INTO indexed_properties (name) VALUES (?1)",
+            params![name.as_str()],
+        )?;
+        Ok(())
+    }
+
+    fn drop_index(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
+        self.conn
+            .execute("DELETE FROM indexed_properties WHERE name = ?1", params![name.as_str()])?;
+        Ok(())
+    }
+
+    fn set_vertex_properties(&mut self, vertices: Vec<Uuid>, name: Identifier, value: &Json) -> Result<()> {
+        self.check_writable()?;
+        let value = json_to_sql(value)?;
+        let tx = self.conn.savepoint()?;
+        for id in vertices {
+            set_vertex_property(&tx, id, name, &value)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()> {
+        self.check_writable()?;
+        let value = json_to_sql(value)?;
+        let tx = self.conn.savepoint()?;
+        for edge in edges {
+            set_edge_property(&tx, &edge, name, &value)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+impl<'a> Drop for SqliteTransaction<'a> {
+    fn drop(&mut self) {
+        // Discard anything that wasn't committed. There's no way to report
+        // an error from here, but a failed rollback still ends the SQLite
+        // transaction.
+        if !self.conn.is_autocommit() {
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+    }
+}
+
+/// A datastore that is backed by SQLite.
+///
+/// Each transaction runs in one SQLite transaction, which is committed by
+/// `commit`, and rolled back by `rollback` or by dropping it. Transactions
+/// share one connection, so they run one at a time. Query results are read
+/// into memory before they're returned, rather than streamed.
+#[derive(Debug, Clone)]
+pub struct SqliteDatastore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteDatastore {
+    /// Creates a new SQLite datastore, or opens an existing one.
+    ///
+    /// # Arguments
+    /// * `path`: The file path to the SQLite database.
+    pub fn new_db<P: AsRef<Path>>(path: P) -> Result<Database<SqliteDatastore>> {
+        Self::new_db_from_connection(Connection::open(path)?)
+    }
+
+    /// Creates a new SQLite datastore that only lives in memory.
+    pub fn new_in_memory_db() -> Result<Database<SqliteDatastore>> {
+        Self::new_db_from_connection(Connection::open_in_memory()?)
+    }
+
+    fn new_db_from_connection(conn: Connection) -> Result<Database<SqliteDatastore>> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Database::new(SqliteDatastore {
+            conn: Arc::new(Mutex::new(conn)),
+        }))
+    }
+
+    fn new_transaction(&self, readonly: bool) -> SqliteTransaction<'_> {
+        let conn = self.conn.lock().unwrap();
+        // A deferred BEGIN doesn't touch the database, so it can only fail if
+        // a transaction is already open, and dropping the last one ended it
+        conn.execute_batch("BEGIN").unwrap();
+        SqliteTransaction { conn, readonly }
+    }
+}
+
+impl Datastore for SqliteDatastore {
+    type Transaction<'a> = SqliteTransaction<'a>;
+    fn transaction(&'_ self) -> Self::Transaction<'_> {
+        self.new_transaction(false)
+    }
+
+    fn readonly_transaction(&'_ self) -> Self::Transaction<'_> {
+        self.new_transaction(true)
+    }
+}
@@ -0,0 +1,71 @@
+//! The SQLite datastore implementation. This is a good fit for embedded or
+//! single-node deployments, where pulling in rocksdb's C++ build isn't
+//! desirable.
+
+mod datastore;
+
+pub use self::datastore::SqliteDatastore;
+
+#[cfg(feature = "bench-suite")]
+full_bench_impl!({
+    use super::SqliteDatastore;
+    use tempfile::tempdir;
+    let path = tempdir().unwrap().into_path().join("indradb.sqlite3");
+    SqliteDatastore::new_db(path).unwrap()
+});
+
+#[cfg(feature = "test-suite")]
+#[cfg(test)]
+mod tests {
+    use super::SqliteDatastore;
+    use crate::{AllVertexQuery, CountQueryExt, Datastore, Error, Identifier, Transaction, Vertex};
+    use tempfile::tempdir;
+
+    full_test_impl!(SqliteDatastore::new_in_memory_db().unwrap());
+
+    #[test]
+    fn should_reopen_db() {
+        let path = tempdir().unwrap().into_path().join("indradb.sqlite3");
+
+        let db = SqliteDatastore::new_db(&path).unwrap();
+        let id = db.create_vertex_from_type(Identifier::new("person").unwrap()).unwrap();
+        drop(db);
+
+        let db = SqliteDatastore::new_db(&path).unwrap();
+        let vertices = crate::util::extract_vertices(db.get(AllVertexQuery).unwrap()).unwrap();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].id, id);
+        let count = crate::util::extract_count(db.get(AllVertexQuery.count().unwrap()).unwrap());
+        assert_eq!(count, Some(1));
+    }
+
+    #[test]
+    fn should_roll_back_transaction() {
+        let db = SqliteDatastore::new_in_memory_db().unwrap();
+        let t = Identifier::new("person").unwrap();
+
+        let mut txn = db.datastore.transaction();
+        assert!(txn.create_vertex(&Vertex::new(t)).unwrap());
+        assert_eq!(txn.vertex_count(), 1);
+        txn.rollback().unwrap();
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 0);
+
+        // Dropping a transaction without committing it also rolls it back
+        let mut txn = db.datastore.transaction();
+        assert!(txn.create_vertex(&Vertex::new(t)).unwrap());
+        drop(txn);
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 0);
+
+        let mut txn = db.datastore.transaction();
+        assert!(txn.create_vertex(&Vertex::new(t)).unwrap());
+        txn.commit().unwrap();
+        assert_eq!(db.datastore.readonly_transaction().vertex_count(), 1);
+    }
+
+    #[test]
+    fn should_not_index_property_path() {
+        let db = SqliteDatastore::new_in_memory_db().unwrap();
+        let path = Identifier::new_path("addr.city").unwrap();
+        assert!(matches!(db.index_property(path), Err(Error::Unsupported)));
+    }
+}
@@ -0,0 +1,63 @@
+use super::util;
+use crate::{
+    expect_err, ijson, AllEdgeQuery, BulkDeleteItem, CountQueryExt, Database, Datastore, Edge, Error, Identifier,
+    QueryExt, SpecificEdgeQuery, SpecificVertexQuery,
+};
+
+use uuid::Uuid;
+
+pub fn should_bulk_delete<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let (outbound_id, inbound_ids) = util::create_edges(db)?;
+    let edge_t = Identifier::new("test_edge_type")?;
+    let name = Identifier::new("foo")?;
+    db.set_properties(SpecificVertexQuery::single(outbound_id), name, &ijson!(true))?;
+    db.set_properties(AllEdgeQuery, name, &ijson!(true))?;
+
+    db.bulk_delete(vec![
+        BulkDeleteItem::Vertex(inbound_ids[0]),
+        BulkDeleteItem::Edge(Edge::new(outbound_id, edge_t, inbound_ids[1])),
+        BulkDeleteItem::Properties(SpecificVertexQuery::single(outbound_id).into(), name),
+        BulkDeleteItem::Properties(
+            SpecificEdgeQuery::single(Edge::new(outbound_id, edge_t, inbound_ids[2])).into(),
+            name,
+        ),
+        // Missing items should be ignored
+        BulkDeleteItem::Vertex(Uuid::default()),
+        BulkDeleteItem::Edge(Edge::new(outbound_id, edge_t, Uuid::default())),
+    ])?;
+
+    let vertices = util::get_vertices(db, SpecificVertexQuery::new(vec![outbound_id, inbound_ids[0]]))?;
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, outbound_id);
+
+    let edges = util::get_edges(db, SpecificVertexQuery::single(outbound_id).outbound()?)?;
+    assert_eq!(edges.len(), 3);
+    assert!(!edges
+        .iter()
+        .any(|e| e.inbound_id == inbound_ids[0] || e.inbound_id == inbound_ids[1]));
+
+    let props = util::get_vertex_properties(db, SpecificVertexQuery::single(outbound_id).properties()?.name(name))?;
+    assert_eq!(props.len(), 0);
+    let props = util::get_edge_properties(
+        db,
+        SpecificEdgeQuery::single(Edge::new(outbound_id, edge_t, inbound_ids[2]))
+            .properties()?
+            .name(name),
+    )?;
+    assert_eq!(props.len(), 0);
+    let props = util::get_edge_properties(
+        db,
+        SpecificEdgeQuery::single(Edge::new(outbound_id, edge_t, inbound_ids[3]))
+            .properties()?
+            .name(name),
+    )?;
+    assert_eq!(props.len(), 1);
+    Ok(())
+}
+
+pub fn should_not_bulk_delete_properties_of_a_count<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let q = SpecificVertexQuery::single(Uuid::default()).count()?;
+    let result = db.bulk_delete(vec![BulkDeleteItem::Properties(q.into(), Identifier::new("foo")?)]);
+    expect_err!(result, Error::OperationOnQuery);
+    Ok(())
+}
@@ -99,3 +99,39 @@ pub fn should_bulk_insert_an_invalid_edge<D: Datastore>(db: &Database<D>) -> Res
     assert!(db.bulk_insert(items).is_ok());
     Ok(())
 }
+
+pub fn should_bulk_insert_checked<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = Identifier::new("test_vertex_type")?;
+    let v1 = Vertex::new(vertex_t);
+    let v2 = Vertex::new(vertex_t);
+    assert!(db.create_vertex(&v1)?);
+    let edge = Edge::new(v1.id, Identifier::new("test_edge_type")?, v2.id);
+
+    // The inbound vertex is inserted in the same batch, before the edge
+    let items = vec![BulkInsertItem::Vertex(v2.clone()), BulkInsertItem::Edge(edge.clone())];
+    db.bulk_insert_checked(items)?;
+
+    let edges = util::get_edges(db, SpecificEdgeQuery::single(edge.clone()))?;
+    assert_eq!(edges, vec![edge]);
+    Ok(())
+}
+
+pub fn should_not_bulk_insert_checked_a_dangling_edge<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = Identifier::new("test_vertex_type")?;
+    let v1 = Vertex::new(vertex_t);
+    let v2 = Vertex::new(vertex_t);
+    assert!(db.create_vertex(&v1)?);
+    let edge = Edge::new(v1.id, Identifier::new("test_edge_type")?, v2.id);
+
+    // The inbound vertex only comes after the edge
+    let items = vec![BulkInsertItem::Edge(edge.clone()), BulkInsertItem::Vertex(v2.clone())];
+    let result = db.bulk_insert_checked(items);
+    assert!(matches!(result, Err(Error::DanglingEdge { ref key }) if *key == edge));
+
+    // Nothing was inserted
+    let vertices = util::get_vertices(db, SpecificVertexQuery::single(v2.id))?;
+    assert_eq!(vertices.len(), 0);
+    let edges = util::get_edges(db, SpecificEdgeQuery::single(edge))?;
+    assert_eq!(edges.len(), 0);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use super::util;
+use crate::util::extract_count;
+use crate::{
+    expect_err, ijson, AllEdgeQuery, CountQueryExt, Database, Datastore, Edge, Error, Identifier, SpecificEdgeQuery,
+    SpecificVertexQuery, VertexWithPropertyPresenceQuery,
+};
+
+pub fn should_clear<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(t)?;
+    let inbound_id = db.create_vertex_from_type(t)?;
+    let edge = Edge::new(outbound_id, Identifier::new("test_edge_type")?, inbound_id);
+    db.create_edge(&edge)?;
+    let name = Identifier::new("foo")?;
+    db.index_property(name)?;
+    db.set_properties(SpecificVertexQuery::single(outbound_id), name, &ijson!(true))?;
+    db.set_properties(SpecificEdgeQuery::single(edge), name, &ijson!(true))?;
+
+    db.clear()?;
+    assert_eq!(util::get_vertex_count(db)?, 0);
+    assert_eq!(extract_count(db.get(AllEdgeQuery.count()?)?), Some(0));
+    assert_eq!(db.get_vertex_count_by_type(t)?, 0);
+    assert!(db.get_vertex_property_names()?.is_empty());
+    assert!(db.get_edge_property_names()?.is_empty());
+    assert!(db.get_indexed_properties()?.is_empty());
+    expect_err!(db.get(VertexWithPropertyPresenceQuery::new(name)), Error::NotIndexed);
+
+    // The database is still usable afterwards
+    let id = db.create_vertex_from_type(t)?;
+    db.set_properties(SpecificVertexQuery::single(id), name, &ijson!(true))?;
+    assert_eq!(util::get_vertex_count(db)?, 1);
+    assert_eq!(db.get_vertex_property_names()?, vec![name]);
+    Ok(())
+}
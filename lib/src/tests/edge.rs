@@ -1,9 +1,11 @@
 use std::collections::HashSet;
+use std::time::Duration;
 
 use super::util;
+use crate::util::extract_count;
 use crate::{
-    ijson, models, AllEdgeQuery, Database, Datastore, Edge, EdgeDirection, EdgeWithPropertyValueQuery, Error,
-    Identifier, QueryExt, SpecificEdgeQuery, SpecificVertexQuery,
+    ijson, models, AllEdgeQuery, CountQueryExt, Database, Datastore, Edge, EdgeDirection, EdgeWithPropertyValueQuery,
+    Error, Identifier, QueryExt, QueryOutputValue, RangeEdgeQuery, SpecificEdgeQuery, SpecificVertexQuery,
 };
 
 use uuid::Uuid;
@@ -38,6 +40,37 @@ pub fn should_get_a_valid_edge<D: Datastore>(db: &Database<D>) -> Result<(), Err
     Ok(())
 }
 
+pub fn should_get_edge<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(vertex_t)?;
+    let inbound_id = db.create_vertex_from_type(vertex_t)?;
+    let edge_t = models::Identifier::new("test_edge_type")?;
+    let edge = models::Edge::new(outbound_id, edge_t, inbound_id);
+    assert_eq!(db.get_edge(&edge)?, None);
+
+    db.create_edge(&edge)?;
+    assert_eq!(db.get_edge(&edge)?, Some(edge.clone()));
+    assert_eq!(db.get_edge(&edge.reversed())?, None);
+    Ok(())
+}
+
+pub fn should_check_edge_exists<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(vertex_t)?;
+    let inbound_id = db.create_vertex_from_type(vertex_t)?;
+    let edge_t = models::Identifier::new("test_edge_type")?;
+    let edge = models::Edge::new(outbound_id, edge_t, inbound_id);
+    assert!(!db.contains_edge(&edge)?);
+
+    db.create_edge(&edge)?;
+    assert!(db.contains_edge(&edge)?);
+    assert!(!db.contains_edge(&edge.reversed())?);
+
+    db.delete(SpecificEdgeQuery::single(edge.clone()))?;
+    assert!(!db.contains_edge(&edge)?);
+    Ok(())
+}
+
 pub fn should_not_get_an_invalid_edge<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let vertex_t = models::Identifier::new("test_vertex_type")?;
     let outbound_id = db.create_vertex_from_type(vertex_t)?;
@@ -98,6 +131,49 @@ pub fn should_not_create_an_invalid_edge<D: Datastore>(db: &Database<D>) -> Resu
     Ok(())
 }
 
+pub fn should_create_an_edge_if_absent<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(vertex_t)?;
+    let inbound_id = db.create_vertex_from_type(vertex_t)?;
+    let edge_t = models::Identifier::new("test_edge_type")?;
+    let edge = models::Edge::new(outbound_id, edge_t, inbound_id);
+    assert!(db.create_edge_if_absent(&edge)?);
+    assert!(!db.create_edge_if_absent(&edge)?);
+    let e = util::get_edges(db, SpecificVertexQuery::single(outbound_id).outbound()?)?;
+    assert_eq!(e, vec![edge]);
+
+    let invalid_edge = models::Edge::new(outbound_id, edge_t, Uuid::default());
+    assert!(!db.create_edge_if_absent(&invalid_edge)?);
+    Ok(())
+}
+
+pub fn should_create_and_delete_symmetric_edges<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(vertex_t)?;
+    let inbound_id = db.create_vertex_from_type(vertex_t)?;
+    let edge_t = models::Identifier::new("test_edge_type")?;
+    let edge = models::Edge::new(outbound_id, edge_t, inbound_id);
+    assert!(db.create_symmetric_edge(&edge)?);
+    let e = util::get_edges(db, SpecificVertexQuery::single(outbound_id).outbound()?)?;
+    assert_eq!(e, vec![edge.clone()]);
+    let e = util::get_edges(db, SpecificVertexQuery::single(inbound_id).outbound()?)?;
+    assert_eq!(e, vec![edge.reversed()]);
+
+    // Deleting from either direction deletes both
+    assert_eq!(db.delete_symmetric_edge(&edge.reversed())?, 2);
+    assert!(util::get_edges(db, AllEdgeQuery)?.is_empty());
+
+    // Self-loops are their own reverse
+    let self_loop = models::Edge::new(outbound_id, edge_t, outbound_id);
+    assert!(db.create_symmetric_edge(&self_loop)?);
+    assert_eq!(db.delete_symmetric_edge(&self_loop)?, 1);
+
+    let invalid_edge = models::Edge::new(outbound_id, edge_t, Uuid::default());
+    assert!(!db.create_symmetric_edge(&invalid_edge)?);
+    assert!(util::get_edges(db, AllEdgeQuery)?.is_empty());
+    Ok(())
+}
+
 pub fn should_delete_a_valid_edge<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let vertex_t = models::Identifier::new("test_edge_type")?;
     let outbound_id = db.create_vertex_from_type(vertex_t)?;
@@ -110,7 +186,7 @@ pub fn should_delete_a_valid_edge<D: Datastore>(db: &Database<D>) -> Result<(),
     let q = SpecificEdgeQuery::single(edge);
     db.set_properties(q.clone(), models::Identifier::new("foo")?, &ijson!(true))?;
 
-    db.delete(q.clone())?;
+    assert_eq!(db.delete(q.clone())?, 1);
     let e = util::get_edges(db, q)?;
     assert_eq!(e.len(), 0);
     Ok(())
@@ -121,11 +197,12 @@ pub fn should_not_delete_an_invalid_edge<D: Datastore>(db: &Database<D>) -> Resu
     let outbound_v = models::Vertex::new(vertex_t);
     db.create_vertex(&outbound_v)?;
     let edge_t = models::Identifier::new("test_edge_type")?;
-    db.delete(SpecificEdgeQuery::single(Edge::new(
+    let count = db.delete(SpecificEdgeQuery::single(Edge::new(
         outbound_v.id,
         edge_t,
         Uuid::default(),
     )))?;
+    assert_eq!(count, 0);
     Ok(())
 }
 
@@ -137,6 +214,30 @@ pub fn should_get_an_edge_count<D: Datastore>(db: &Database<D>) -> Result<(), Er
     Ok(())
 }
 
+pub fn should_get_an_edge_count_with_limit<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let (outbound_id, _) = util::create_edges(db)?;
+    let q = SpecificVertexQuery::single(outbound_id).outbound()?.limit(3).count()?;
+    let count = extract_count(db.get(q)?).unwrap();
+    assert_eq!(count, 3);
+    Ok(())
+}
+
+pub fn should_get_an_edge_count_with_included_vertices<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let (outbound_id, _) = util::create_edges(db)?;
+    let q = SpecificVertexQuery::single(outbound_id).include().outbound()?.count()?;
+    let output = db.get(q)?;
+    assert_eq!(output.len(), 2);
+    assert_eq!(output[1], QueryOutputValue::Count(5));
+    match output[0] {
+        QueryOutputValue::Vertices(ref vertices) => {
+            assert_eq!(vertices.len(), 1);
+            assert_eq!(vertices[0].id, outbound_id);
+        }
+        _ => panic!("unexpected output: {:?}", output[0]),
+    }
+    Ok(())
+}
+
 pub fn should_get_an_edge_count_with_no_type<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let (outbound_id, _) = util::create_edges(db)?;
     let count = util::get_edge_count(db, outbound_id, None, EdgeDirection::Outbound)?;
@@ -173,6 +274,37 @@ pub fn should_get_edge_range<D: Datastore>(db: &Database<D>) -> Result<(), Error
     Ok(())
 }
 
+pub fn should_get_edges_with_multiple_types<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(vertex_t)?;
+    let inbound_id = db.create_vertex_from_type(vertex_t)?;
+    let t1 = models::Identifier::new("test_edge_type_1")?;
+    let t2 = models::Identifier::new("test_edge_type_2")?;
+    let t3 = models::Identifier::new("test_edge_type_3")?;
+    for t in [t1, t2, t3] {
+        db.create_edge(&Edge::new(outbound_id, t, inbound_id))?;
+    }
+
+    let q = SpecificVertexQuery::single(outbound_id).outbound()?;
+    let range = util::get_edges(db, q.clone().t(t1).t(t3))?;
+    assert_eq!(
+        range.into_iter().map(|e| e.t).collect::<HashSet<Identifier>>(),
+        HashSet::from([t1, t3])
+    );
+    let range = util::get_edges(db, q.clone().t(t3).t(t1).limit(1))?;
+    assert_eq!(range.len(), 1);
+    let range = util::get_edges(db, q)?;
+    assert_eq!(range.len(), 3);
+
+    let q = SpecificVertexQuery::single(inbound_id).inbound()?.t(t2).t(t3);
+    let range = util::get_edges(db, q)?;
+    assert_eq!(
+        range.into_iter().map(|e| e.t).collect::<HashSet<Identifier>>(),
+        HashSet::from([t2, t3])
+    );
+    Ok(())
+}
+
 pub fn should_get_edges<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let (outbound_id, inbound_ids) = util::create_edges(db)?;
     let t = models::Identifier::new("test_edge_type")?;
@@ -221,6 +353,144 @@ pub fn should_get_edges_piped<D: Datastore>(db: &Database<D>) -> Result<(), Erro
     Ok(())
 }
 
+pub fn should_get_edges_in_both_directions<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let edge_t = models::Identifier::new("test_edge_type")?;
+    let x = models::Vertex::new(vertex_t);
+    let y = models::Vertex::new(vertex_t);
+    let z = models::Vertex::new(vertex_t);
+    db.create_vertex(&x)?;
+    db.create_vertex(&y)?;
+    db.create_vertex(&z)?;
+    let x_y = Edge::new(x.id, edge_t, y.id);
+    let z_x = Edge::new(z.id, edge_t, x.id);
+    let x_x = Edge::new(x.id, edge_t, x.id);
+    db.create_edge(&x_y)?;
+    db.create_edge(&z_x)?;
+    db.create_edge(&x_x)?;
+
+    // The self-loop is only returned once, in its stored orientation
+    let edges: HashSet<Edge> = util::get_edges(db, SpecificVertexQuery::single(x.id).both()?)?
+        .into_iter()
+        .collect();
+    assert_eq!(edges, HashSet::from([x_y.clone(), z_x.clone(), x_x.clone()]));
+    assert_eq!(util::get_edge_count(db, x.id, None, EdgeDirection::Both)?, 3);
+    assert_eq!(util::get_edge_count(db, y.id, None, EdgeDirection::Both)?, 1);
+
+    // Edges between the piped vertices are only returned once
+    let q = SpecificVertexQuery::new(vec![x.id, y.id]).both()?;
+    let edges = util::get_edges(db, q.clone())?;
+    assert_eq!(edges.len(), 3);
+    assert_eq!(edges.into_iter().collect::<HashSet<Edge>>().len(), 3);
+    let count = extract_count(db.get(q.count()?)?).unwrap();
+    assert_eq!(count, 3);
+
+    // Both ends of the edges are piped to, once each
+    let vertices = util::get_vertices(db, SpecificEdgeQuery::new(vec![x_y, z_x]).both()?)?;
+    assert_eq!(vertices.len(), 3);
+    assert_eq!(
+        vertices.into_iter().map(|v| v.id).collect::<HashSet<Uuid>>(),
+        HashSet::from([x.id, y.id, z.id])
+    );
+    Ok(())
+}
+
+pub fn should_page_through_edges<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let (outbound_id, _) = util::create_edges(db)?;
+    // Edges of other vertices shouldn't be paged through
+    util::create_edges(db)?;
+    let expected = util::get_edges(db, SpecificVertexQuery::single(outbound_id).outbound()?)?;
+    assert_eq!(expected.len(), 5);
+
+    let mut edges = Vec::new();
+    let mut q = RangeEdgeQuery::new().outbound_id(outbound_id).limit(2);
+    loop {
+        let page = util::get_edges(db, q.clone())?;
+        assert!(page.len() <= 2);
+        match page.last() {
+            Some(last) => q = q.after(last.clone()),
+            None => break,
+        }
+        edges.extend(page);
+    }
+    assert_eq!(edges, expected);
+
+    // The edge to continue after doesn't need to exist
+    let t = Identifier::new("test_edge_type")?;
+    let q = RangeEdgeQuery::new()
+        .outbound_id(outbound_id)
+        .after(Edge::new(outbound_id, t, Uuid::nil()));
+    assert_eq!(util::get_edges(db, q)?, expected);
+
+    // Without a vertex, all edges are paged through
+    let all = util::get_edges(db, AllEdgeQuery)?;
+    assert_eq!(all.len(), 10);
+    let page = util::get_edges(db, RangeEdgeQuery::new().after(all[3].clone()).limit(3))?;
+    assert_eq!(page, all[4..7].to_vec());
+
+    // Edges of other types are skipped without counting towards the limit
+    let other_t = Identifier::new("other_edge_type")?;
+    let other = Edge::new(outbound_id, other_t, outbound_id);
+    db.create_edge(&other)?;
+    let q = RangeEdgeQuery::new().outbound_id(outbound_id).t(other_t).limit(2);
+    assert_eq!(util::get_edges(db, q)?, vec![other]);
+    let q = RangeEdgeQuery::new().outbound_id(outbound_id).t(t).limit(2);
+    assert_eq!(util::get_edges(db, q)?, expected[..2].to_vec());
+    Ok(())
+}
+
+pub fn should_get_edges_from_many_vertices<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let follows_t = models::Identifier::new("follows")?;
+    let likes_t = models::Identifier::new("likes")?;
+    let blocks_t = models::Identifier::new("blocks")?;
+
+    let mut ids = Vec::new();
+    for _ in 0..10 {
+        ids.push(db.create_vertex_from_type(vertex_t)?);
+    }
+    // Each vertex follows the next one, and every other vertex likes the
+    // one after that
+    let mut edges = HashSet::new();
+    for i in 0..ids.len() {
+        let follows = Edge::new(ids[i], follows_t, ids[(i + 1) % ids.len()]);
+        db.create_edge(&follows)?;
+        edges.insert(follows);
+        if i % 2 == 0 {
+            let likes = Edge::new(ids[i], likes_t, ids[(i + 2) % ids.len()]);
+            db.create_edge(&likes)?;
+            edges.insert(likes);
+        }
+    }
+
+    // Pipe from the vertices in reverse order, including one without edges
+    let lonely_id = db.create_vertex_from_type(vertex_t)?;
+    let mut piped_ids = ids.clone();
+    piped_ids.push(lonely_id);
+    piped_ids.reverse();
+
+    let outbound: HashSet<Edge> = util::get_edges(db, SpecificVertexQuery::new(piped_ids.clone()).outbound()?)?
+        .into_iter()
+        .collect();
+    assert_eq!(outbound, edges);
+    let inbound: HashSet<Edge> = util::get_edges(db, SpecificVertexQuery::new(piped_ids.clone()).inbound()?)?
+        .into_iter()
+        .collect();
+    assert_eq!(inbound, edges);
+    let both = util::get_edges(db, SpecificVertexQuery::new(piped_ids.clone()).both()?)?;
+    assert_eq!(both.len(), edges.len());
+    assert_eq!(both.into_iter().collect::<HashSet<Edge>>(), edges);
+
+    // Only edges of the filtered types are returned, including when some
+    // of the types don't have any edges
+    let q = SpecificVertexQuery::new(piped_ids).outbound()?.t(likes_t).t(blocks_t);
+    let likes: HashSet<Edge> = util::get_edges(db, q.clone())?.into_iter().collect();
+    assert_eq!(likes.len(), 5);
+    assert!(likes.iter().all(|e| e.t == likes_t));
+    assert_eq!(extract_count(db.get(q.count()?)?).unwrap(), 5);
+    Ok(())
+}
+
 /// Test for a regression, see
 /// https://github.com/indradb/indradb/issues/278#issuecomment-1515797381
 pub fn should_delete_indexed_edge_with_property_value<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
@@ -229,6 +499,46 @@ pub fn should_delete_indexed_edge_with_property_value<D: Datastore>(db: &Databas
     Ok(())
 }
 
+pub fn should_not_get_expired_edges<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let (outbound_id, inbound_ids) = util::create_edges(db)?;
+    let t = models::Identifier::new("test_edge_type")?;
+    let expired_edge = Edge::new(outbound_id, t, inbound_ids[0]);
+    let live_edge = Edge::new(outbound_id, t, inbound_ids[1]);
+    assert!(db.create_edge_with_ttl(&expired_edge, Duration::from_secs(0))?);
+    assert!(db.create_edge_with_ttl(&live_edge, Duration::from_secs(3600))?);
+
+    let e = util::get_edges(
+        db,
+        SpecificEdgeQuery::new(vec![expired_edge.clone(), live_edge.clone()]),
+    )?;
+    assert_eq!(e, vec![live_edge]);
+    let e = util::get_edges(db, AllEdgeQuery)?;
+    assert_eq!(e.len(), 4);
+    assert!(!e.contains(&expired_edge));
+    let e = util::get_edges(db, SpecificVertexQuery::single(inbound_ids[0]).inbound()?)?;
+    assert_eq!(e.len(), 0);
+
+    let count = util::get_edge_count(db, outbound_id, Some(t), EdgeDirection::Outbound)?;
+    assert_eq!(count, 4);
+    let count = extract_count(db.get(AllEdgeQuery.count()?)?).unwrap();
+    assert_eq!(count, 4);
+
+    // Recreating the edge without a TTL should bring it back for good
+    assert!(db.create_edge(&expired_edge)?);
+    let count = extract_count(db.get(AllEdgeQuery.count()?)?).unwrap();
+    assert_eq!(count, 5);
+    Ok(())
+}
+
+pub fn should_not_create_an_invalid_edge_with_ttl<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(vertex_t)?;
+    let edge_t = models::Identifier::new("test_edge_type")?;
+    let edge = Edge::new(outbound_id, edge_t, Uuid::default());
+    assert!(!db.create_edge_with_ttl(&edge, Duration::from_secs(3600))?);
+    Ok(())
+}
+
 fn check_edge_range(range: &[models::Edge], expected_outbound_id: Uuid, expected_length: usize) -> Result<(), Error> {
     assert_eq!(range.len(), expected_length);
     let mut covered_ids: HashSet<Uuid> = HashSet::new();
@@ -243,3 +553,114 @@ fn check_edge_range(range: &[models::Edge], expected_outbound_id: Uuid, expected
 
     Ok(())
 }
+
+pub fn should_get_top_vertices_by_degree<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let follows_t = models::Identifier::new("follows")?;
+    let likes_t = models::Identifier::new("likes")?;
+    // Sequential IDs, so that ties are broken predictably
+    let [a, b, c, d] = [1, 2, 3, 4].map(Uuid::from_u128);
+    for id in [a, b, c, d] {
+        db.create_vertex(&models::Vertex::with_id(id, vertex_t))?;
+    }
+    for (outbound_id, t, inbound_id) in [
+        (a, follows_t, b),
+        (a, follows_t, c),
+        (a, follows_t, d),
+        (b, follows_t, c),
+        (c, follows_t, c),
+        (d, likes_t, a),
+    ] {
+        db.create_edge(&Edge::new(outbound_id, t, inbound_id))?;
+    }
+
+    assert_eq!(
+        db.top_vertices_by_degree(EdgeDirection::Outbound, 10, None)?,
+        vec![(a, 3), (b, 1), (c, 1), (d, 1)]
+    );
+    assert_eq!(
+        db.top_vertices_by_degree(EdgeDirection::Outbound, 2, None)?,
+        vec![(a, 3), (b, 1)]
+    );
+    assert_eq!(
+        db.top_vertices_by_degree(EdgeDirection::Inbound, 10, None)?,
+        vec![(c, 3), (a, 1), (b, 1), (d, 1)]
+    );
+    // The self-loop on c only counts once
+    assert_eq!(
+        db.top_vertices_by_degree(EdgeDirection::Both, 10, Some(follows_t))?,
+        vec![(a, 3), (c, 3), (b, 2), (d, 1)]
+    );
+    assert_eq!(
+        db.top_vertices_by_degree(EdgeDirection::Outbound, 10, Some(likes_t))?,
+        vec![(d, 1)]
+    );
+    assert_eq!(db.top_vertices_by_degree(EdgeDirection::Outbound, 0, None)?, vec![]);
+    Ok(())
+}
+
+pub fn should_get_vertex_neighborhood<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = Identifier::new("test_vertex_type")?;
+    let edge_t = Identifier::new("test_edge_type")?;
+    let other_edge_t = Identifier::new("test_other_edge_type")?;
+    let id = db.create_vertex_from_type(vertex_t)?;
+    let other_ids = [
+        db.create_vertex_from_type(vertex_t)?,
+        db.create_vertex_from_type(vertex_t)?,
+        db.create_vertex_from_type(vertex_t)?,
+    ];
+    let outbound = Edge::new(id, edge_t, other_ids[0]);
+    let other_outbound = Edge::new(id, other_edge_t, other_ids[1]);
+    let inbound = Edge::new(other_ids[2], edge_t, id);
+    for edge in [&outbound, &other_outbound, &inbound] {
+        db.create_edge(edge)?;
+    }
+
+    let neighborhood = db.get_vertex_neighborhood(id, None, u32::MAX)?.unwrap();
+    assert_eq!(neighborhood.vertex.id, id);
+    let outbound_edges: HashSet<Edge> = neighborhood.outbound.into_iter().collect();
+    assert_eq!(outbound_edges, HashSet::from([outbound.clone(), other_outbound]));
+    assert_eq!(neighborhood.inbound, vec![inbound.clone()]);
+
+    let neighborhood = db.get_vertex_neighborhood(id, Some(edge_t), u32::MAX)?.unwrap();
+    assert_eq!(neighborhood.outbound, vec![outbound]);
+    assert_eq!(neighborhood.inbound, vec![inbound]);
+
+    let neighborhood = db.get_vertex_neighborhood(id, None, 1)?.unwrap();
+    assert_eq!(neighborhood.outbound.len(), 1);
+    assert_eq!(neighborhood.inbound.len(), 1);
+    let neighborhood = db
+        .get_vertex_neighborhood(other_ids[0], Some(other_edge_t), u32::MAX)?
+        .unwrap();
+    assert!(neighborhood.outbound.is_empty() && neighborhood.inbound.is_empty());
+
+    assert_eq!(db.get_vertex_neighborhood(Uuid::default(), None, u32::MAX)?, None);
+    Ok(())
+}
+
+pub fn should_get_edge_counts<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = Identifier::new("test_vertex_type")?;
+    let edge_t = Identifier::new("test_edge_type")?;
+    let other_edge_t = Identifier::new("test_other_edge_type")?;
+    let id = db.create_vertex_from_type(vertex_t)?;
+    let other_ids = [
+        db.create_vertex_from_type(vertex_t)?,
+        db.create_vertex_from_type(vertex_t)?,
+        db.create_vertex_from_type(vertex_t)?,
+    ];
+    for edge in [
+        Edge::new(id, edge_t, other_ids[0]),
+        Edge::new(id, other_edge_t, other_ids[1]),
+        Edge::new(other_ids[2], edge_t, id),
+        Edge::new(id, edge_t, id),
+    ] {
+        db.create_edge(&edge)?;
+    }
+
+    assert_eq!(db.get_edge_counts(id, None)?, (3, 2));
+    assert_eq!(db.get_edge_counts(id, Some(edge_t))?, (2, 2));
+    assert_eq!(db.get_edge_counts(id, Some(other_edge_t))?, (1, 0));
+    assert_eq!(db.get_edge_counts(other_ids[0], None)?, (0, 1));
+    assert_eq!(db.get_edge_counts(Uuid::default(), None)?, (0, 0));
+    Ok(())
+}
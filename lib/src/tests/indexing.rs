@@ -1,5 +1,5 @@
 use super::util;
-use crate::{expect_err, ijson, models, Database, Datastore, Error, QueryExt};
+use crate::{expect_err, ijson, models, CountQueryExt, Database, Datastore, Error, QueryExt, ValidationError};
 use uuid::Uuid;
 
 fn setup_vertex_with_indexed_property<D: Datastore>(
@@ -70,9 +70,10 @@ pub fn should_index_existing_vertex_property<D: Datastore>(db: &Database<D>) ->
     let result = util::get_vertices(db, q.clone().without_property(property_name)?)?;
     assert!(result.is_empty());
 
-    // Check against another property
-    let result = util::get_vertices(db, q.clone().without_property(other_property_name)?);
-    expect_err!(result, Error::NotIndexed);
+    // Check against another property, which works with or without an index
+    let result = util::get_vertices(db, q.clone().without_property(other_property_name)?)?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, id);
     db.index_property(other_property_name.clone())?;
     let result = util::get_vertices(db, q.without_property(other_property_name)?)?;
     assert_eq!(result.len(), 1);
@@ -109,9 +110,9 @@ pub fn should_index_existing_edge_property<D: Datastore>(db: &Database<D>) -> Re
     let result = util::get_edges(db, q.clone().without_property(property_name)?)?;
     assert!(result.is_empty());
 
-    // Check against another property
-    let result = util::get_edges(db, q.clone().without_property(other_property_name)?);
-    expect_err!(result, Error::NotIndexed);
+    // Check against another property, which works with or without an index
+    let result = util::get_edges(db, q.clone().without_property(other_property_name)?)?;
+    assert_eq!(result, vec![edge.clone()]);
     db.index_property(other_property_name)?;
     let result = util::get_edges(db, q.without_property(other_property_name)?)?;
     assert_eq!(result.len(), 1);
@@ -274,3 +275,254 @@ pub fn should_pipe_not_indexed_errors<D: Datastore>(db: &Database<D>) -> Result<
 
     Ok(())
 }
+
+pub fn should_get_vertices_and_edges_with_property_value<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let property_name = models::Identifier::new("source")?;
+    let q = models::WithPropertyValueQuery::new(property_name, ijson!("import-2024"));
+    expect_err!(db.get(q.clone()), Error::NotIndexed);
+
+    let id = setup_vertex_with_indexed_property(db, property_name)?;
+    let edge = setup_edge_with_indexed_property(db, property_name)?;
+    db.set_properties(
+        models::SpecificVertexQuery::single(id),
+        property_name,
+        &ijson!("import-2024"),
+    )?;
+    db.set_properties(
+        models::SpecificEdgeQuery::single(edge.clone()),
+        property_name,
+        &ijson!("import-2024"),
+    )?;
+
+    let output = db.get(q)?;
+    assert_eq!(output.len(), 2);
+    assert_eq!(
+        output[0],
+        models::QueryOutputValue::Vertices(vec![models::Vertex::with_id(
+            id,
+            models::Identifier::new("test_vertex_type")?
+        )])
+    );
+    assert_eq!(output[1], models::QueryOutputValue::Edges(vec![edge]));
+
+    // Values are matched exactly
+    let output = db.get(models::WithPropertyValueQuery::new(property_name, ijson!(true)))?;
+    assert_eq!(
+        output,
+        vec![
+            models::QueryOutputValue::Vertices(Vec::new()),
+            models::QueryOutputValue::Edges(Vec::new())
+        ]
+    );
+
+    // It can't be piped from
+    expect_err!(
+        models::CountQuery::new(Box::new(
+            models::WithPropertyValueQuery::new(property_name, ijson!(true)).into()
+        )),
+        ValidationError::InnerQuery
+    );
+    Ok(())
+}
+
+pub fn should_index_property_path<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let property_name = models::Identifier::new("addr")?;
+    let path = models::Identifier::new_path("addr.city")?;
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let existing_id = db.create_vertex_from_type(vertex_t)?;
+    let new_id = db.create_vertex_from_type(vertex_t)?;
+    let missing_id = db.create_vertex_from_type(vertex_t)?;
+    let non_object_id = db.create_vertex_from_type(vertex_t)?;
+    let edge = models::Edge::new(existing_id, models::Identifier::new("test_edge_type")?, new_id);
+    db.create_edge(&edge)?;
+
+    // Values set before the path is indexed should be picked up
+    db.set_properties(
+        models::SpecificVertexQuery::single(existing_id),
+        property_name,
+        &ijson!({"city": "NYC"}),
+    )?;
+    db.index_property(path)?;
+    db.set_properties(
+        models::SpecificVertexQuery::single(new_id),
+        property_name,
+        &ijson!({"city": "NYC", "zip": "10001"}),
+    )?;
+    db.set_properties(
+        models::SpecificEdgeQuery::single(edge.clone()),
+        property_name,
+        &ijson!({"city": "SF"}),
+    )?;
+    // Neither of these have the field, so they shouldn't be indexed
+    db.set_properties(
+        models::SpecificVertexQuery::single(missing_id),
+        property_name,
+        &ijson!({"zip": "10001"}),
+    )?;
+    db.set_properties(
+        models::SpecificVertexQuery::single(non_object_id),
+        property_name,
+        &ijson!("NYC"),
+    )?;
+
+    let mut result = util::get_vertices(db, models::VertexWithPropertyValueQuery::new(path, ijson!("NYC")))?;
+    result.sort_by_key(|v| v.id);
+    let mut expected = vec![existing_id, new_id];
+    expected.sort();
+    assert_eq!(result.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+    let result = util::get_vertices(db, models::VertexWithPropertyPresenceQuery::new(path))?;
+    assert_eq!(result.len(), 2);
+    let result = util::get_edges(db, models::EdgeWithPropertyValueQuery::new(path, ijson!("SF")))?;
+    assert_eq!(result, vec![edge]);
+
+    // Updating or deleting the property should update the index
+    db.set_properties(
+        models::SpecificVertexQuery::single(existing_id),
+        property_name,
+        &ijson!({"city": "SF"}),
+    )?;
+    db.delete(
+        models::SpecificVertexQuery::single(new_id)
+            .properties()?
+            .name(property_name),
+    )?;
+    let result = util::get_vertices(db, models::VertexWithPropertyValueQuery::new(path, ijson!("NYC")))?;
+    assert!(result.is_empty());
+    let result = util::get_vertices(db, models::VertexWithPropertyValueQuery::new(path, ijson!("SF")))?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, existing_id);
+
+    // The property itself isn't indexed
+    let result = util::get_vertices(db, models::VertexWithPropertyPresenceQuery::new(property_name));
+    expect_err!(result, Error::NotIndexed);
+    Ok(())
+}
+
+pub fn should_drop_index<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let property_name = models::Identifier::new("droppable-property")?;
+    let id = setup_vertex_with_indexed_property(db, property_name)?;
+    let q = models::VertexWithPropertyValueQuery::new(property_name, ijson!(true));
+    assert_eq!(util::get_vertices(db, q.clone())?.len(), 1);
+
+    db.drop_index(property_name)?;
+    let result = util::get_vertices(db, q.clone());
+    expect_err!(result, Error::NotIndexed);
+    // Dropping it again is a no-op
+    db.drop_index(property_name)?;
+
+    // Re-indexing shouldn't pick up stale values from the old index
+    db.set_properties(models::SpecificVertexQuery::single(id), property_name, &ijson!(false))?;
+    db.index_property(property_name)?;
+    assert!(util::get_vertices(db, q)?.is_empty());
+    let result = util::get_vertices(
+        db,
+        models::VertexWithPropertyValueQuery::new(property_name, ijson!(false)),
+    )?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, id);
+    Ok(())
+}
+
+pub fn should_get_indexed_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    assert!(db.get_indexed_properties()?.is_empty());
+    let first = models::Identifier::new("first-indexed-property")?;
+    let second = models::Identifier::new("second-indexed-property")?;
+    db.index_property(second)?;
+    db.index_property(first)?;
+    // Indexing an already indexed property shouldn't duplicate it
+    db.index_property(first)?;
+    assert_eq!(db.get_indexed_properties()?, vec![first, second]);
+    db.drop_index(second)?;
+    assert_eq!(db.get_indexed_properties()?, vec![first]);
+    Ok(())
+}
+
+pub fn should_filter_on_property_presence_without_index<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("presence-vertex-type")?;
+    let edge_t = models::Identifier::new("presence-edge-type")?;
+    let email = models::Identifier::new("presence-email")?;
+    let with_id = db.create_vertex_from_type(vertex_t)?;
+    let null_id = db.create_vertex_from_type(vertex_t)?;
+    let without_id = db.create_vertex_from_type(vertex_t)?;
+    db.set_properties(models::SpecificVertexQuery::single(with_id), email, &ijson!("a@b.c"))?;
+    // A null value still counts as the property being set
+    db.set_properties(models::SpecificVertexQuery::single(null_id), email, &ijson!(null))?;
+    let edge = models::Edge::new(with_id, edge_t, without_id);
+    let other_edge = models::Edge::new(null_id, edge_t, without_id);
+    db.create_edge(&edge)?;
+    db.create_edge(&other_edge)?;
+    db.set_properties(models::SpecificEdgeQuery::single(edge.clone()), email, &ijson!(1))?;
+
+    let vertices = models::RangeVertexQuery::new().t(vertex_t);
+    let edges = models::SpecificEdgeQuery::new(vec![edge.clone(), other_edge.clone()]);
+    for _ in 0..2 {
+        let mut ids: Vec<Uuid> = util::get_vertices(db, vertices.clone().with_property(email)?)?
+            .into_iter()
+            .map(|v| v.id)
+            .collect();
+        ids.sort();
+        let mut expected_ids = vec![with_id, null_id];
+        expected_ids.sort();
+        assert_eq!(ids, expected_ids);
+        let result = util::get_vertices(db, vertices.clone().without_property(email)?)?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, without_id);
+
+        assert_eq!(
+            util::get_edges(db, edges.clone().with_property(email)?)?,
+            vec![edge.clone()]
+        );
+        assert_eq!(
+            util::get_edges(db, edges.clone().without_property(email)?)?,
+            vec![other_edge.clone()]
+        );
+
+        // Indexing the property gives the same results
+        db.index_property(email)?;
+    }
+    Ok(())
+}
+
+pub fn should_explain_query<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let indexed = models::Identifier::new("explain-indexed")?;
+    let unindexed = models::Identifier::new("explain-unindexed")?;
+    setup_vertex_with_indexed_property(db, indexed)?;
+    db.create_vertex_from_type(models::Identifier::new("test_vertex_type")?)?;
+
+    let plan = db.explain(models::VertexWithPropertyPresenceQuery::new(indexed))?;
+    assert_eq!(plan.stage, "VertexWithPropertyPresence");
+    assert_eq!(plan.access, models::PlanAccess::PropertyIndex);
+    assert_eq!(plan.property, Some(indexed));
+    assert_eq!(plan.estimated_count, Some(1));
+    assert!(!plan.is_missing_index());
+
+    let plan = db.explain(models::VertexWithPropertyPresenceQuery::new(unindexed))?;
+    assert_eq!(plan.access, models::PlanAccess::MissingIndex);
+    assert_eq!(plan.estimated_count, None);
+
+    let plan = db.explain(models::AllVertexQuery.with_property(unindexed)?)?;
+    assert_eq!(plan.access, models::PlanAccess::Pipe);
+    assert!(!plan.is_missing_index());
+
+    let plan = db.explain(
+        models::AllVertexQuery
+            .with_property_equal_to(unindexed, ijson!(true))?
+            .count()?,
+    )?;
+    assert_eq!(plan.stage, "Count");
+    assert_eq!(plan.estimated_count, Some(1));
+    assert!(plan.is_missing_index());
+    let inner = plan.inner.unwrap();
+    assert_eq!(inner.access, models::PlanAccess::MissingIndex);
+    assert_eq!(inner.property, Some(unindexed));
+    let inner = inner.inner.unwrap();
+    assert_eq!(inner.access, models::PlanAccess::FullScan);
+    assert_eq!(inner.estimated_count, Some(2));
+    assert_eq!(inner.inner, None);
+
+    // Counting all vertices doesn't scan them
+    let plan = db.explain(models::AllVertexQuery.count()?)?;
+    assert_eq!(plan.access, models::PlanAccess::StoredCount);
+    assert_eq!(plan.inner, None);
+    Ok(())
+}
@@ -21,52 +21,90 @@ macro_rules! define_test {
     };
 }
 
-/// Use this macro to enable the entire standard test suite.
+/// Use this macro to enable the entire standard test suite. The expression
+/// constructing the database is evaluated once per test; to make failures
+/// reproducible, it can seed vertex IDs from `test_seed`.
 #[macro_export]
 macro_rules! full_test_impl {
     ($code:expr) => {
         // Sync
         define_test!(should_sync, $code);
+        define_test!(should_compact, $code);
+        // Clear
+        define_test!(should_clear, $code);
 
         // Bulk insert
         define_test!(should_bulk_insert, $code);
         define_test!(should_bulk_insert_a_redundant_vertex, $code);
         define_test!(should_bulk_insert_an_invalid_edge, $code);
+        define_test!(should_bulk_insert_checked, $code);
+        define_test!(should_not_bulk_insert_checked_a_dangling_edge, $code);
+
+        // Bulk delete
+        define_test!(should_bulk_delete, $code);
+        define_test!(should_not_bulk_delete_properties_of_a_count, $code);
 
         // Vertices
         define_test!(should_create_vertex_from_type, $code);
+        define_test!(should_create_vertex_with_properties, $code);
+        define_test!(should_upsert_vertex, $code);
         define_test!(should_get_all_vertices, $code);
         define_test!(should_get_range_vertices, $code);
+        define_test!(should_get_range_vertices_descending, $code);
+        define_test!(should_get_vertex_pages, $code);
         define_test!(should_get_no_vertices_with_zero_limit, $code);
         define_test!(should_get_range_vertices_out_of_range, $code);
         define_test!(should_get_no_vertices_with_type_filter, $code);
+        define_test!(should_get_range_vertices_with_multiple_types, $code);
+        define_test!(should_get_range_vertices_with_property_filter, $code);
+        define_test!(should_get_range_vertices_with_indexed_property_filter, $code);
         define_test!(should_get_single_vertex, $code);
         define_test!(should_get_single_vertex_nonexisting, $code);
+        define_test!(should_get_vertex, $code);
+        define_test!(should_check_vertex_exists, $code);
+        define_test!(should_get_one, $code);
+        define_test!(should_get_batch, $code);
         define_test!(should_get_vertices, $code);
         define_test!(should_get_vertices_piped, $code);
         define_test!(should_get_a_vertex_count, $code);
+        define_test!(should_get_a_vertex_count_by_type, $code);
+        define_test!(should_get_stats, $code);
         define_test!(should_delete_a_valid_outbound_vertex, $code);
         define_test!(should_delete_a_valid_inbound_vertex, $code);
         define_test!(should_not_delete_an_invalid_vertex, $code);
         define_test!(should_not_delete_on_vertex_count, $code);
         define_test!(should_not_pipe_on_vertex_count, $code);
+        define_test!(should_stream_vertices, $code);
 
         // Edges
         define_test!(should_get_all_edges, $code);
         define_test!(should_get_a_valid_edge, $code);
+        define_test!(should_get_edge, $code);
+        define_test!(should_check_edge_exists, $code);
         define_test!(should_not_get_an_invalid_edge, $code);
         define_test!(should_create_a_valid_edge, $code);
         define_test!(should_not_create_an_invalid_edge, $code);
+        define_test!(should_create_an_edge_if_absent, $code);
+        define_test!(should_create_and_delete_symmetric_edges, $code);
         define_test!(should_delete_a_valid_edge, $code);
         define_test!(should_not_delete_an_invalid_edge, $code);
         define_test!(should_get_an_edge_count, $code);
+        define_test!(should_get_an_edge_count_with_limit, $code);
+        define_test!(should_get_an_edge_count_with_included_vertices, $code);
         define_test!(should_get_an_edge_count_with_no_type, $code);
         define_test!(should_get_an_edge_count_for_an_invalid_edge, $code);
         define_test!(should_get_an_inbound_edge_count, $code);
         define_test!(should_get_edges_with_no_type, $code);
         define_test!(should_get_edge_range, $code);
+        define_test!(should_get_edges_with_multiple_types, $code);
         define_test!(should_get_edges, $code);
         define_test!(should_get_edges_piped, $code);
+        define_test!(should_get_edges_in_both_directions, $code);
+        define_test!(should_get_edges_from_many_vertices, $code);
+        define_test!(should_page_through_edges, $code);
+        define_test!(should_get_top_vertices_by_degree, $code);
+        define_test!(should_get_vertex_neighborhood, $code);
+        define_test!(should_get_edge_counts, $code);
         define_test!(should_delete_indexed_edge_with_property_value, $code);
 
         // Include queries
@@ -79,14 +117,19 @@ macro_rules! full_test_impl {
         define_test!(should_not_query_unindexed_edge_property, $code);
         define_test!(should_index_existing_vertex_property, $code);
         define_test!(should_index_existing_edge_property, $code);
+        define_test!(should_filter_on_property_presence_without_index, $code);
         define_test!(should_delete_indexed_vertex_property, $code);
         define_test!(should_delete_indexed_edge_property, $code);
+        define_test!(should_drop_index, $code);
+        define_test!(should_get_indexed_properties, $code);
         define_test!(should_update_indexed_vertex_property, $code);
         define_test!(should_update_indexed_edge_property, $code);
         define_test!(should_query_indexed_vertex_property_empty, $code);
         define_test!(should_query_indexed_edge_property_empty, $code);
         define_test!(should_get_vertex_with_property_value_empty, $code);
         define_test!(should_pipe_not_indexed_errors, $code);
+        define_test!(should_get_vertices_and_edges_with_property_value, $code);
+        define_test!(should_explain_query, $code);
 
         // Properties
         define_test!(should_handle_vertex_properties, $code);
@@ -99,8 +142,35 @@ macro_rules! full_test_impl {
         define_test!(should_get_all_edge_properties, $code);
         define_test!(should_get_an_edge_properties_count, $code);
         define_test!(should_get_a_vertex_properties_count, $code);
+        define_test!(should_set_properties_batch, $code);
+        define_test!(should_copy_vertex_properties, $code);
+        define_test!(should_delete_all_properties, $code);
         define_test!(should_not_set_properties_on_count, $code);
         define_test!(should_not_pipe_properties_on_vertex_count, $code);
         define_test!(should_not_pipe_property_presence_on_vertex_count, $code);
+        define_test!(should_get_vertices_with_property_predicate, $code);
+        define_test!(should_get_vertices_with_string_property_predicate, $code);
+        define_test!(should_get_edges_with_property_predicate, $code);
+        define_test!(should_limit_property_predicate_after_filtering, $code);
+        define_test!(should_increment_vertex_properties, $code);
+        define_test!(should_get_property_names, $code);
+        define_test!(should_group_count_by_property, $code);
+        define_test!(should_not_increment_non_numeric_vertex_properties, $code);
+        define_test!(should_merge_vertex_properties, $code);
+        define_test!(should_not_merge_non_object_vertex_properties, $code);
+        define_test!(should_compare_and_set_vertex_properties, $code);
+
+        // Traversal
+        define_test!(should_traverse_outbound, $code);
+        define_test!(should_traverse_inbound, $code);
+        define_test!(should_traverse_with_type_filter, $code);
+        define_test!(should_traverse_with_limit, $code);
+        define_test!(should_get_vertices_with_edge_to, $code);
+        define_test!(should_find_shortest_path, $code);
+        define_test!(should_not_find_shortest_path, $code);
+
+        // Read-only transactions
+        define_test!(should_not_write_in_readonly_transaction, $code);
+        define_test!(should_read_in_readonly_transaction, $code);
     };
 }
@@ -4,22 +4,32 @@
 //! `indradb` crate can reuse them. Generally you can use the convenience macro
 //! `full_test_impl`.
 
+mod bulk_delete;
 mod bulk_insert;
+mod clear;
 mod edge;
 mod include_query;
 mod indexing;
 #[macro_use]
 mod macros;
 mod properties;
+mod readonly;
+mod seed;
 mod sync;
+mod traversal;
 mod util;
 mod vertex;
 
+pub use self::bulk_delete::*;
 pub use self::bulk_insert::*;
+pub use self::clear::*;
 pub use self::edge::*;
 pub use self::include_query::*;
 pub use self::indexing::*;
 pub use self::macros::*;
 pub use self::properties::*;
+pub use self::readonly::*;
+pub use self::seed::*;
 pub use self::sync::*;
+pub use self::traversal::*;
 pub use self::vertex::*;
@@ -1,8 +1,9 @@
 use super::util;
 use crate::util::extract_count;
 use crate::{
-    errors, expect_err, ijson, AllVertexQuery, CountQueryExt, Database, Datastore, Edge, Error, Identifier,
-    PipePropertyQuery, PipeWithPropertyPresenceQuery, QueryExt, SpecificEdgeQuery, SpecificVertexQuery,
+    errors, expect_err, ijson, AllVertexQuery, BulkInsertItem, CountQueryExt, Database, Datastore, Edge, Error,
+    Identifier, Json, JsonType, NamedProperty, PipePropertyQuery, PipeWithPropertyPresenceQuery, PropertyPredicate,
+    QueryExt, SpecificEdgeQuery, SpecificVertexQuery, VertexWithPropertyPresenceQuery,
 };
 use uuid::Uuid;
 
@@ -217,6 +218,123 @@ pub fn should_get_a_vertex_properties_count<D: Datastore>(db: &Database<D>) -> R
     Ok(())
 }
 
+pub fn should_set_properties_batch<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(t)?;
+    let inbound_id = db.create_vertex_from_type(t)?;
+    let edge = Edge::new(outbound_id, Identifier::new("test_edge_type")?, inbound_id);
+    db.create_edge(&edge)?;
+    let a = Identifier::new("a")?;
+    let b = Identifier::new("b")?;
+
+    db.set_properties(SpecificVertexQuery::single(outbound_id), a, &ijson!(0))?;
+    db.set_properties_batch(
+        SpecificVertexQuery::new(vec![outbound_id, inbound_id]),
+        vec![(a, ijson!(1)), (b, ijson!("foo")), (a, ijson!(2))],
+    )?;
+    let result = util::get_all_vertex_properties(db, SpecificVertexQuery::single(outbound_id))?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].props.len(), 2);
+    assert_eq!(result[0].props[0].name, a);
+    assert_eq!(result[0].props[0].value, ijson!(2));
+    assert_eq!(result[0].props[1].name, b);
+    assert_eq!(result[0].props[1].value, ijson!("foo"));
+    let result = util::get_all_vertex_properties(db, SpecificVertexQuery::single(inbound_id))?;
+    assert_eq!(result[0].props.len(), 2);
+    assert_eq!(result[0].props[0].value, ijson!(2));
+
+    db.set_properties_batch(
+        SpecificEdgeQuery::single(edge.clone()),
+        vec![(a, ijson!(true)), (b, ijson!(false))],
+    )?;
+    let result = util::get_all_edge_properties(db, SpecificEdgeQuery::single(edge))?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].props.len(), 2);
+    assert_eq!(result[0].props[0].value, ijson!(true));
+    assert_eq!(result[0].props[1].value, ijson!(false));
+    Ok(())
+}
+
+pub fn should_copy_vertex_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let a = Identifier::new("a")?;
+    let b = Identifier::new("b")?;
+    let c = Identifier::new("c")?;
+    let from = db.create_vertex_with_properties(t, vec![(a, ijson!(1)), (b, ijson!(2))])?;
+    let to = db.create_vertex_with_properties(t, vec![(b, ijson!(3)), (c, ijson!(4))])?;
+    let other = db.create_vertex_from_type(t)?;
+
+    let get_props = |id: Uuid| -> Result<Vec<NamedProperty>, Error> {
+        let result = util::get_all_vertex_properties(db, SpecificVertexQuery::single(id))?;
+        Ok(result.into_iter().flat_map(|vp| vp.props).collect())
+    };
+
+    assert!(db.copy_vertex_properties(from, to, false)?);
+    assert_eq!(
+        get_props(to)?,
+        vec![
+            NamedProperty::new(a, ijson!(1)),
+            NamedProperty::new(b, ijson!(3)),
+            NamedProperty::new(c, ijson!(4)),
+        ]
+    );
+    assert!(db.copy_vertex_properties(from, to, true)?);
+    assert_eq!(
+        get_props(to)?,
+        vec![
+            NamedProperty::new(a, ijson!(1)),
+            NamedProperty::new(b, ijson!(2)),
+            NamedProperty::new(c, ijson!(4)),
+        ]
+    );
+    assert_eq!(
+        get_props(from)?,
+        vec![NamedProperty::new(a, ijson!(1)), NamedProperty::new(b, ijson!(2))]
+    );
+
+    // Nothing is copied if either vertex doesn't exist
+    assert!(!db.copy_vertex_properties(Uuid::default(), other, true)?);
+    assert!(!db.copy_vertex_properties(from, Uuid::default(), true)?);
+    assert!(get_props(other)?.is_empty());
+    Ok(())
+}
+
+pub fn should_delete_all_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let a = Identifier::new("a")?;
+    let b = Identifier::new("b")?;
+    db.index_property(a)?;
+    let v1 = db.create_vertex_with_properties(t, vec![(a, ijson!(1)), (b, ijson!(2))])?;
+    let v2 = db.create_vertex_with_properties(t, vec![(a, ijson!(3))])?;
+    let edge = Edge::new(v1, Identifier::new("test_edge_type")?, v2);
+    db.create_edge(&edge)?;
+    db.set_properties_batch(
+        SpecificEdgeQuery::single(edge.clone()),
+        vec![(a, ijson!(4)), (b, ijson!(5))],
+    )?;
+
+    assert_eq!(db.delete_all_properties(SpecificVertexQuery::single(v1))?, 2);
+    assert!(util::get_all_vertex_properties(db, SpecificVertexQuery::single(v1))?.is_empty());
+    // Indexes are updated too, and other vertices are left as is
+    let result = util::get_vertices(db, VertexWithPropertyPresenceQuery::new(a))?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, v2);
+    assert_eq!(
+        util::get_all_edge_properties(db, SpecificEdgeQuery::single(edge.clone()))?.len(),
+        1
+    );
+
+    assert_eq!(db.delete_all_properties(SpecificEdgeQuery::single(edge.clone()))?, 2);
+    assert!(util::get_all_edge_properties(db, SpecificEdgeQuery::single(edge))?.is_empty());
+
+    // Deleting again is a no-op
+    assert_eq!(db.delete_all_properties(SpecificVertexQuery::single(v1))?, 0);
+
+    let result = db.delete_all_properties(AllVertexQuery.count()?);
+    expect_err!(result, errors::Error::OperationOnQuery);
+    Ok(())
+}
+
 pub fn should_not_set_properties_on_count<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let result = db.set_properties(AllVertexQuery.count()?, Identifier::new("foo")?, &ijson!(true));
     expect_err!(result, errors::Error::OperationOnQuery);
@@ -247,3 +365,363 @@ pub fn should_not_pipe_property_presence_on_vertex_count<D: Datastore>(db: &Data
     expect_err!(result, errors::Error::OperationOnQuery);
     Ok(())
 }
+
+pub fn should_get_vertices_with_property_predicate<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let name = Identifier::new("score")?;
+    let v1 = db.create_vertex_from_type(t)?;
+    let v2 = db.create_vertex_from_type(t)?;
+    let v3 = db.create_vertex_from_type(t)?;
+    let v4 = db.create_vertex_from_type(t)?;
+    db.set_properties(SpecificVertexQuery::single(v1), name, &ijson!(0.5))?;
+    db.set_properties(SpecificVertexQuery::single(v2), name, &ijson!(0.8))?;
+    db.set_properties(SpecificVertexQuery::single(v3), name, &ijson!(1))?;
+    db.set_properties(SpecificVertexQuery::single(v4), name, &ijson!("high"))?;
+    let q = SpecificVertexQuery::new(vec![v1, v2, v3, v4]);
+
+    let check = |predicate: PropertyPredicate, expected: Vec<Uuid>| -> Result<(), Error> {
+        let vertices = util::get_vertices(db, q.clone().with_property_matching(name, predicate)?)?;
+        assert_eq!(vertices.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+        Ok(())
+    };
+
+    check(PropertyPredicate::GreaterThan(ijson!(0.8)), vec![v3])?;
+    check(PropertyPredicate::GreaterOrEqual(ijson!(0.8)), vec![v2, v3])?;
+    check(PropertyPredicate::LessThan(ijson!(0.8)), vec![v1])?;
+    check(PropertyPredicate::LessOrEqual(ijson!(0.8)), vec![v1, v2])?;
+    check(PropertyPredicate::Equal(ijson!(1)), vec![v3])?;
+    check(PropertyPredicate::NotEqual(ijson!(1)), vec![v1, v2, v4])?;
+    check(PropertyPredicate::GreaterThan(ijson!("a")), vec![v4])?;
+
+    // Vertices without the property are excluded
+    let vertices = util::get_vertices(
+        db,
+        q.with_property_matching(Identifier::new("foo")?, PropertyPredicate::NotEqual(ijson!(1)))?,
+    )?;
+    assert!(vertices.is_empty());
+    Ok(())
+}
+
+pub fn should_get_vertices_with_string_property_predicate<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let name = Identifier::new("name")?;
+    let v1 = db.create_vertex_from_type(t)?;
+    let v2 = db.create_vertex_from_type(t)?;
+    let v3 = db.create_vertex_from_type(t)?;
+    db.set_properties(SpecificVertexQuery::single(v1), name, &ijson!("Alice"))?;
+    db.set_properties(SpecificVertexQuery::single(v2), name, &ijson!("Malik"))?;
+    db.set_properties(SpecificVertexQuery::single(v3), name, &ijson!(["Ali"]))?;
+    let q = SpecificVertexQuery::new(vec![v1, v2, v3]);
+
+    let check = |predicate: PropertyPredicate, expected: Vec<Uuid>| -> Result<(), Error> {
+        let vertices = util::get_vertices(db, q.clone().with_property_matching(name, predicate)?)?;
+        assert_eq!(vertices.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+        Ok(())
+    };
+
+    check(PropertyPredicate::Contains("li".to_string()), vec![v1, v2])?;
+    check(PropertyPredicate::Contains("Ali".to_string()), vec![v1])?;
+    check(PropertyPredicate::Regex("^Ali".to_string()), vec![v1])?;
+    check(PropertyPredicate::Regex("(?i)^mal".to_string()), vec![v2])?;
+    check(PropertyPredicate::Regex("e$".to_string()), vec![v1])?;
+
+    let result = db.get(q.with_property_matching(name, PropertyPredicate::Regex("[".to_string()))?);
+    expect_err!(result, errors::Error::InvalidRegex(_));
+    Ok(())
+}
+
+pub fn should_get_edges_with_property_predicate<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let (outbound_id, inbound_ids) = util::create_edges(db)?;
+    let name = Identifier::new("weight")?;
+    let edge_t = Identifier::new("test_edge_type")?;
+    for (i, inbound_id) in inbound_ids.iter().enumerate() {
+        let q = SpecificEdgeQuery::single(Edge::new(outbound_id, edge_t, *inbound_id));
+        db.set_properties(q, name, &ijson!(i))?;
+    }
+
+    let q = SpecificVertexQuery::single(outbound_id)
+        .outbound()?
+        .with_property_matching(name, PropertyPredicate::GreaterOrEqual(ijson!(3)))?;
+    let edges = util::get_edges(db, q)?;
+    assert_eq!(
+        edges,
+        vec![
+            Edge::new(outbound_id, edge_t, inbound_ids[3]),
+            Edge::new(outbound_id, edge_t, inbound_ids[4]),
+        ]
+    );
+    Ok(())
+}
+
+pub fn should_limit_property_predicate_after_filtering<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let (outbound_id, inbound_ids) = util::create_edges(db)?;
+    let name = Identifier::new("score")?;
+    for (i, inbound_id) in inbound_ids.iter().enumerate() {
+        db.set_properties(SpecificVertexQuery::single(*inbound_id), name, &ijson!(i))?;
+    }
+    let q = SpecificVertexQuery::single(outbound_id).outbound()?;
+
+    // The limit caps the matching vertices, rather than the ones filtered
+    let predicate = PropertyPredicate::GreaterOrEqual(ijson!(1));
+    let vertices = util::get_vertices(
+        db,
+        q.clone()
+            .inbound()?
+            .with_property_matching(name, predicate.clone())?
+            .limit(3),
+    )?;
+    assert_eq!(vertices.len(), 3);
+    assert!(vertices.iter().all(|v| inbound_ids[1..].contains(&v.id)));
+    let vertices = util::get_vertices(db, q.clone().inbound()?.with_property_matching(name, predicate)?)?;
+    assert_eq!(vertices.len(), 4);
+
+    // A limit on the inner query is applied before filtering
+    let vertices = util::get_vertices(
+        db,
+        q.inbound()?
+            .limit(2)
+            .with_property_matching(name, PropertyPredicate::GreaterOrEqual(ijson!(0)))?,
+    )?;
+    assert_eq!(vertices.len(), 2);
+    Ok(())
+}
+
+pub fn should_increment_vertex_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let id = db.create_vertex_from_type(t)?;
+    let q = SpecificVertexQuery::single(id);
+    let name = Identifier::new("counter")?;
+
+    // Missing values are treated as 0
+    db.increment_property(q.clone(), name, 1.0)?;
+    let result = util::get_vertex_properties(db, q.clone().properties()?.name(name))?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].value, ijson!(1));
+
+    // Integers stay integers
+    db.set_properties(q.clone(), name, &ijson!(42))?;
+    db.increment_property(q.clone(), name, 1.0)?;
+    let result = util::get_vertex_properties(db, q.clone().properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!(43));
+
+    // Fractional deltas produce floats
+    db.increment_property(q.clone(), name, -0.5)?;
+    let result = util::get_vertex_properties(db, q.clone().properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!(42.5));
+
+    Ok(())
+}
+
+pub fn should_not_increment_non_numeric_vertex_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let numeric_id = db.create_vertex_from_type(t)?;
+    let non_numeric_id = db.create_vertex_from_type(t)?;
+    let name = Identifier::new("counter")?;
+    db.set_properties(SpecificVertexQuery::single(numeric_id), name, &ijson!(1))?;
+    db.set_properties(SpecificVertexQuery::single(non_numeric_id), name, &ijson!("foo"))?;
+
+    let q = SpecificVertexQuery::new(vec![numeric_id, non_numeric_id]);
+    let result = db.increment_property(q, name, 1.0);
+    expect_err!(result, errors::Error::NotNumeric);
+
+    // Neither value should have changed
+    let result = util::get_vertex_properties(db, SpecificVertexQuery::single(numeric_id).properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!(1));
+    let result = util::get_vertex_properties(db, SpecificVertexQuery::single(non_numeric_id).properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!("foo"));
+
+    let result = db.increment_property(AllVertexQuery.count()?, name, 1.0);
+    expect_err!(result, errors::Error::OperationOnQuery);
+    Ok(())
+}
+
+pub fn should_merge_vertex_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let id = db.create_vertex_from_type(t)?;
+    let q = SpecificVertexQuery::single(id);
+    let name = Identifier::new("metadata")?;
+
+    // Missing values are created from the patch
+    db.merge_properties(q.clone(), name, &ijson!({"a": 1, "b": {"c": 2}}))?;
+    let result = util::get_vertex_properties(db, q.clone().properties()?.name(name))?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].value, ijson!({"a": 1, "b": {"c": 2}}));
+
+    // Nested objects are merged, and other values are overwritten
+    db.merge_properties(q.clone(), name, &ijson!({"b": {"d": [3]}, "e": 4}))?;
+    db.merge_properties(q.clone(), name, &ijson!({"a": [5], "b": {"d": [6]}}))?;
+    let result = util::get_vertex_properties(db, q.clone().properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!({"a": [5], "b": {"c": 2, "d": [6]}, "e": 4}));
+
+    Ok(())
+}
+
+pub fn should_not_merge_non_object_vertex_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let object_id = db.create_vertex_from_type(t)?;
+    let non_object_id = db.create_vertex_from_type(t)?;
+    let name = Identifier::new("metadata")?;
+    db.set_properties(SpecificVertexQuery::single(object_id), name, &ijson!({"a": 1}))?;
+    db.set_properties(SpecificVertexQuery::single(non_object_id), name, &ijson!([1]))?;
+
+    let q = SpecificVertexQuery::new(vec![object_id, non_object_id]);
+    let result = db.merge_properties(q, name, &ijson!({"b": 2}));
+    expect_err!(result, errors::Error::NotObject);
+
+    // Neither value should have changed
+    let result = util::get_vertex_properties(db, SpecificVertexQuery::single(object_id).properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!({"a": 1}));
+    let result = util::get_vertex_properties(db, SpecificVertexQuery::single(non_object_id).properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!([1]));
+
+    let result = db.merge_properties(SpecificVertexQuery::single(object_id), name, &ijson!(2));
+    expect_err!(result, errors::Error::NotObject);
+
+    let result = db.merge_properties(AllVertexQuery.count()?, name, &ijson!({"b": 2}));
+    expect_err!(result, errors::Error::OperationOnQuery);
+    Ok(())
+}
+
+pub fn should_compare_and_set_vertex_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let ids = [db.create_vertex_from_type(t)?, db.create_vertex_from_type(t)?];
+    let q = SpecificVertexQuery::new(ids.to_vec());
+    let name = Identifier::new("version")?;
+    let get_values = || -> Result<Vec<Option<Json>>, Error> {
+        let mut values = Vec::new();
+        for id in ids {
+            let result = util::get_vertex_properties(db, SpecificVertexQuery::single(id).properties()?.name(name))?;
+            values.push(result.into_iter().next().map(|prop| prop.value));
+        }
+        Ok(values)
+    };
+
+    // A missing property is expected with `None`
+    assert!(!db.compare_and_set_property(q.clone(), name, Some(&ijson!(0)), &ijson!(1))?);
+    assert_eq!(get_values()?, vec![None, None]);
+    assert!(db.compare_and_set_property(q.clone(), name, None, &ijson!(1))?);
+    assert_eq!(get_values()?, vec![Some(ijson!(1)), Some(ijson!(1))]);
+    assert!(!db.compare_and_set_property(q.clone(), name, None, &ijson!(2))?);
+
+    // Either every vertex is set, or none of them are
+    db.set_properties(SpecificVertexQuery::single(ids[0]), name, &ijson!(2))?;
+    assert!(!db.compare_and_set_property(q.clone(), name, Some(&ijson!(1)), &ijson!(3))?);
+    assert_eq!(get_values()?, vec![Some(ijson!(2)), Some(ijson!(1))]);
+    assert!(db.compare_and_set_property(SpecificVertexQuery::single(ids[1]), name, Some(&ijson!(1)), &ijson!(2))?);
+    assert!(db.compare_and_set_property(q, name, Some(&ijson!(2)), &ijson!(3))?);
+    assert_eq!(get_values()?, vec![Some(ijson!(3)), Some(ijson!(3))]);
+
+    // Nothing is set without any vertices
+    assert!(!db.compare_and_set_property(SpecificVertexQuery::new(vec![]), name, None, &ijson!(1))?);
+    Ok(())
+}
+
+pub fn should_get_property_names<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = Identifier::new("test_vertex_type")?;
+    let edge_t = Identifier::new("test_edge_type")?;
+    let outbound_id = db.create_vertex_from_type(vertex_t)?;
+    let inbound_id = db.create_vertex_from_type(vertex_t)?;
+    let edge = Edge::new(outbound_id, edge_t, inbound_id);
+    db.create_edge(&edge)?;
+    assert_eq!(db.get_vertex_property_names()?, Vec::<Identifier>::new());
+    assert_eq!(db.get_edge_property_names()?, Vec::<Identifier>::new());
+
+    let a = Identifier::new("a")?;
+    let b = Identifier::new("b")?;
+    let c = Identifier::new("c")?;
+    db.set_properties(
+        SpecificVertexQuery::new(vec![outbound_id, inbound_id]),
+        b,
+        &ijson!(true),
+    )?;
+    db.set_properties(SpecificVertexQuery::single(outbound_id), a, &ijson!(true))?;
+    db.set_properties(SpecificEdgeQuery::single(edge.clone()), c, &ijson!(true))?;
+    assert_eq!(db.get_vertex_property_names()?, vec![a, b]);
+    assert_eq!(db.get_edge_property_names()?, vec![c]);
+
+    // Names should stick around until the last property using them is gone
+    db.delete(SpecificVertexQuery::single(outbound_id).properties()?.name(b))?;
+    assert_eq!(db.get_vertex_property_names()?, vec![a, b]);
+    db.delete(SpecificVertexQuery::single(outbound_id).properties()?.name(a))?;
+    assert_eq!(db.get_vertex_property_names()?, vec![b]);
+
+    // Deleting a vertex deletes its properties, and the properties of its
+    // edges
+    db.delete(SpecificVertexQuery::single(inbound_id))?;
+    assert_eq!(db.get_vertex_property_names()?, Vec::<Identifier>::new());
+    assert_eq!(db.get_edge_property_names()?, Vec::<Identifier>::new());
+    Ok(())
+}
+
+pub fn should_group_count_by_property<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let status = Identifier::new("status")?;
+    let mut ids = Vec::new();
+    for _ in 0..6 {
+        ids.push(db.create_vertex_from_type(t)?);
+    }
+    db.set_properties(SpecificVertexQuery::new(ids[0..3].to_vec()), status, &ijson!("active"))?;
+    db.set_properties(SpecificVertexQuery::new(ids[3..5].to_vec()), status, &ijson!("idle"))?;
+
+    // The last vertex has no status, so it shouldn't be counted
+    let counts = db.group_count_by_property(SpecificVertexQuery::new(ids.clone()), status)?;
+    assert_eq!(counts, vec![(ijson!("active"), 3), (ijson!("idle"), 2)]);
+
+    // Only the query's results should be counted
+    let counts = db.group_count_by_property(SpecificVertexQuery::new(ids[2..4].to_vec()), status)?;
+    assert_eq!(counts, vec![(ijson!("active"), 1), (ijson!("idle"), 1)]);
+
+    let result = db.group_count_by_property(AllVertexQuery.count()?, status);
+    expect_err!(result, Error::OperationOnQuery);
+    Ok(())
+}
+
+pub fn should_validate_property_schemas<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(t)?;
+    let inbound_id = db.create_vertex_from_type(t)?;
+    let edge = Edge::new(outbound_id, Identifier::new("test_edge_type")?, inbound_id);
+    db.create_edge(&edge)?;
+    let name = Identifier::new("age")?;
+    let q = SpecificVertexQuery::single(outbound_id);
+
+    assert_eq!(db.get_property_schemas()?, vec![]);
+    db.register_property_schema(name, JsonType::Number)?;
+    assert_eq!(db.get_property_schemas()?, vec![(name, JsonType::Number)]);
+
+    db.set_properties(q.clone(), name, &ijson!(30))?;
+    expect_err!(
+        db.set_properties(q.clone(), name, &ijson!("thirty")),
+        errors::Error::SchemaViolation
+    );
+    expect_err!(
+        db.set_properties(SpecificEdgeQuery::single(edge.clone()), name, &ijson!(null)),
+        errors::Error::SchemaViolation
+    );
+    expect_err!(
+        db.set_properties_batch(
+            q.clone(),
+            vec![(Identifier::new("nickname")?, ijson!("al")), (name, ijson!([30]))]
+        ),
+        errors::Error::SchemaViolation
+    );
+    expect_err!(
+        db.bulk_insert(vec![BulkInsertItem::VertexProperty(inbound_id, name, ijson!(true))]),
+        errors::Error::SchemaViolation
+    );
+
+    // The rejected writes shouldn't have changed anything
+    let result = util::get_vertex_properties(db, q.clone().properties()?.name(name))?;
+    assert_eq!(result[0].value, ijson!(30));
+    let result = util::get_edge_properties(db, SpecificEdgeQuery::single(edge).properties()?.name(name))?;
+    assert_eq!(result.len(), 0);
+
+    // Properties without a schema can still be set to anything
+    db.set_properties(q.clone(), Identifier::new("nickname")?, &ijson!(30))?;
+
+    // Registering again replaces the expected type
+    db.register_property_schema(name, JsonType::String)?;
+    assert_eq!(db.get_property_schemas()?, vec![(name, JsonType::String)]);
+    db.set_properties(q, name, &ijson!("thirty"))?;
+    Ok(())
+}
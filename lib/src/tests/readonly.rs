@@ -0,0 +1,64 @@
+use crate::{
+    ijson, BulkInsertItem, Database, Datastore, Edge, Error, Identifier, SpecificVertexQuery, Transaction, Vertex,
+};
+
+pub fn should_not_write_in_readonly_transaction<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let outbound_id = db.create_vertex_from_type(t)?;
+    let inbound_id = db.create_vertex_from_type(t)?;
+    let edge = Edge::new(outbound_id, Identifier::new("test_edge_type")?, inbound_id);
+    db.create_edge(&edge)?;
+    let vertex = Vertex::with_id(outbound_id, t);
+    let name = Identifier::new("foo")?;
+
+    {
+        let mut txn = db.datastore.readonly_transaction();
+        expect_err!(txn.create_vertex(&Vertex::new(t)), Error::ReadOnly);
+        expect_err!(
+            txn.create_edge(&Edge::new(inbound_id, edge.t, outbound_id)),
+            Error::ReadOnly
+        );
+        expect_err!(
+            txn.bulk_insert(vec![BulkInsertItem::Vertex(Vertex::new(t))]),
+            Error::ReadOnly
+        );
+        expect_err!(
+            txn.set_vertex_properties(vec![outbound_id], name, &ijson!(true)),
+            Error::ReadOnly
+        );
+        expect_err!(
+            txn.set_edge_properties(vec![edge.clone()], name, &ijson!(true)),
+            Error::ReadOnly
+        );
+        expect_err!(txn.delete_vertex_properties(vec![(outbound_id, name)]), Error::ReadOnly);
+        expect_err!(txn.delete_edge_properties(vec![(edge.clone(), name)]), Error::ReadOnly);
+        expect_err!(txn.delete_edges(vec![edge.clone()]), Error::ReadOnly);
+        expect_err!(txn.delete_vertices(vec![vertex.clone()]), Error::ReadOnly);
+        expect_err!(txn.index_property(name), Error::ReadOnly);
+        expect_err!(txn.clear(), Error::ReadOnly);
+
+        // Nothing should have changed
+        assert_eq!(txn.vertex_count(), 2);
+        assert_eq!(txn.edge_count(), 1);
+        assert_eq!(txn.vertex_property(&vertex, name)?, None);
+        assert_eq!(txn.edge_property(&edge, name)?, None);
+    }
+
+    // Regular transactions should still be writable afterwards
+    let mut txn = db.datastore.transaction();
+    assert!(txn.create_vertex(&Vertex::new(t))?);
+    txn.commit()
+}
+
+pub fn should_read_in_readonly_transaction<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = Identifier::new("test_vertex_type")?;
+    let id = db.create_vertex_from_type(t)?;
+    let vertex = Vertex::with_id(id, t);
+    let name = Identifier::new("foo")?;
+    db.set_properties(SpecificVertexQuery::single(id), name, &ijson!(true))?;
+
+    let txn = db.datastore.readonly_transaction();
+    assert_eq!(txn.vertex_count(), 1);
+    assert_eq!(txn.vertex_property(&vertex, name)?, Some(ijson!(true)));
+    Ok(())
+}
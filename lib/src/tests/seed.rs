@@ -0,0 +1,19 @@
+use std::env;
+
+/// The environment variable that seeds the IDs of vertices created in tests.
+/// Set it to an unsigned integer to re-run a failing test with the same IDs.
+pub const TEST_SEED_VAR: &str = "INDRADB_TEST_SEED";
+
+/// Gets the seed set via `INDRADB_TEST_SEED`, if any. Test datastores that
+/// support it should generate vertex IDs from the seed, e.g. via
+/// `MemoryDatastore::new_seeded_db`.
+///
+/// # Panics
+/// If the seed isn't an unsigned integer.
+pub fn test_seed() -> Option<u64> {
+    let seed = env::var(TEST_SEED_VAR).ok()?;
+    match seed.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => panic!("invalid {}: {:?}", TEST_SEED_VAR, seed),
+    }
+}
@@ -4,3 +4,8 @@ pub fn should_sync<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     // just make sure that it runs fine
     db.sync()
 }
+
+pub fn should_compact<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    // just make sure that it runs fine
+    db.compact()
+}
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+
+use super::util;
+use crate::{Database, Datastore, Edge, EdgeDirection, Error, Identifier, PipeQuery, QueryExt, SpecificVertexQuery};
+
+use uuid::Uuid;
+
+// Creates the graph `a -> b -> c -> a`, along with `b -> d` using a different
+// edge type.
+fn create_cycle<D: Datastore>(db: &Database<D>) -> Result<[Uuid; 4], Error> {
+    let vertex_t = Identifier::new("test_vertex_type")?;
+    let ids = [
+        db.create_vertex_from_type(vertex_t)?,
+        db.create_vertex_from_type(vertex_t)?,
+        db.create_vertex_from_type(vertex_t)?,
+        db.create_vertex_from_type(vertex_t)?,
+    ];
+    let edge_t = Identifier::new("test_edge_type")?;
+    let other_edge_t = Identifier::new("test_other_edge_type")?;
+    db.create_edge(&Edge::new(ids[0], edge_t, ids[1]))?;
+    db.create_edge(&Edge::new(ids[1], edge_t, ids[2]))?;
+    db.create_edge(&Edge::new(ids[2], edge_t, ids[0]))?;
+    db.create_edge(&Edge::new(ids[1], other_edge_t, ids[3]))?;
+    Ok(ids)
+}
+
+fn get_traversed_ids<D: Datastore, Q: QueryExt>(
+    db: &Database<D>,
+    q: Q,
+    direction: EdgeDirection,
+    max_depth: u32,
+    t: Option<Identifier>,
+) -> Result<HashSet<Uuid>, Error> {
+    let mut q = q.traverse(direction, max_depth)?;
+    if let Some(t) = t {
+        q = q.t(t);
+    }
+    let vertices = util::get_vertices(db, q)?;
+    let len = vertices.len();
+    let ids: HashSet<Uuid> = vertices.into_iter().map(|v| v.id).collect();
+    // ensure there are no duplicates
+    assert_eq!(ids.len(), len);
+    Ok(ids)
+}
+
+pub fn should_traverse_outbound<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let ids = create_cycle(db)?;
+    let q = SpecificVertexQuery::single(ids[0]);
+    assert_eq!(
+        get_traversed_ids(db, q.clone(), EdgeDirection::Outbound, 0, None)?,
+        HashSet::from([ids[0]])
+    );
+    assert_eq!(
+        get_traversed_ids(db, q.clone(), EdgeDirection::Outbound, 1, None)?,
+        HashSet::from([ids[0], ids[1]])
+    );
+    assert_eq!(
+        get_traversed_ids(db, q.clone(), EdgeDirection::Outbound, 2, None)?,
+        HashSet::from([ids[0], ids[1], ids[2], ids[3]])
+    );
+    assert_eq!(
+        get_traversed_ids(db, q, EdgeDirection::Outbound, 10, None)?,
+        HashSet::from([ids[0], ids[1], ids[2], ids[3]])
+    );
+    Ok(())
+}
+
+pub fn should_traverse_inbound<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let ids = create_cycle(db)?;
+    let q = SpecificVertexQuery::single(ids[3]);
+    assert_eq!(
+        get_traversed_ids(db, q.clone(), EdgeDirection::Inbound, 1, None)?,
+        HashSet::from([ids[3], ids[1]])
+    );
+    assert_eq!(
+        get_traversed_ids(db, q, EdgeDirection::Inbound, 10, None)?,
+        HashSet::from([ids[0], ids[1], ids[2], ids[3]])
+    );
+    Ok(())
+}
+
+pub fn should_traverse_with_type_filter<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let ids = create_cycle(db)?;
+    let q = SpecificVertexQuery::single(ids[0]);
+    let t = Identifier::new("test_edge_type")?;
+    assert_eq!(
+        get_traversed_ids(db, q.clone(), EdgeDirection::Outbound, 10, Some(t))?,
+        HashSet::from([ids[0], ids[1], ids[2]])
+    );
+    let t = Identifier::new("test_other_edge_type")?;
+    assert_eq!(
+        get_traversed_ids(db, q, EdgeDirection::Outbound, 10, Some(t))?,
+        HashSet::from([ids[0]])
+    );
+    Ok(())
+}
+
+pub fn should_get_vertices_with_edge_to<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let ids = create_cycle(db)?;
+    let get_ids = |q: PipeQuery| -> Result<HashSet<Uuid>, Error> {
+        Ok(util::get_vertices(db, q)?.into_iter().map(|v| v.id).collect())
+    };
+    assert_eq!(
+        get_ids(PipeQuery::vertices_with_edge_to(ids[1], vec![], EdgeDirection::Inbound))?,
+        HashSet::from([ids[0]])
+    );
+    assert_eq!(
+        get_ids(PipeQuery::vertices_with_edge_to(
+            ids[1],
+            vec![],
+            EdgeDirection::Outbound
+        ))?,
+        HashSet::from([ids[2], ids[3]])
+    );
+    assert_eq!(
+        get_ids(PipeQuery::vertices_with_edge_to(ids[0], vec![], EdgeDirection::Both))?,
+        HashSet::from([ids[0], ids[1], ids[2]])
+    );
+    let t = Identifier::new("test_other_edge_type")?;
+    assert_eq!(
+        get_ids(PipeQuery::vertices_with_edge_to(
+            ids[1],
+            vec![t],
+            EdgeDirection::Outbound
+        ))?,
+        HashSet::from([ids[3]])
+    );
+    let q = PipeQuery::vertices_with_edge_to(ids[1], vec![], EdgeDirection::Outbound).limit(1);
+    assert_eq!(util::get_vertices(db, q)?.len(), 1);
+    Ok(())
+}
+
+pub fn should_traverse_with_limit<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let ids = create_cycle(db)?;
+    let q = SpecificVertexQuery::single(ids[0]).traverse(EdgeDirection::Outbound, 10)?;
+    let vertices = util::get_vertices(db, q.clone().limit(2))?;
+    assert_eq!(
+        vertices.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(),
+        vec![ids[0], ids[1]]
+    );
+    let vertices = util::get_vertices(db, q.limit(0))?;
+    assert!(vertices.is_empty());
+    Ok(())
+}
+
+pub fn should_find_shortest_path<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let ids = create_cycle(db)?;
+    let edge_t = Identifier::new("test_edge_type")?;
+    let other_edge_t = Identifier::new("test_other_edge_type")?;
+
+    let path = db.shortest_path(ids[0], ids[3], 10, None, Some(EdgeDirection::Outbound))?;
+    assert_eq!(
+        path,
+        Some(vec![
+            Edge::new(ids[0], edge_t, ids[1]),
+            Edge::new(ids[1], other_edge_t, ids[3]),
+        ])
+    );
+
+    // Following edges inbound returns the edges in their stored orientation
+    let path = db.shortest_path(ids[0], ids[2], 10, None, Some(EdgeDirection::Inbound))?;
+    assert_eq!(path, Some(vec![Edge::new(ids[2], edge_t, ids[0])]));
+
+    // Following edges in both directions can take shortcuts against the
+    // direction of the cycle
+    let path = db.shortest_path(ids[2], ids[3], 10, None, None)?;
+    assert_eq!(
+        path,
+        Some(vec![
+            Edge::new(ids[1], edge_t, ids[2]),
+            Edge::new(ids[1], other_edge_t, ids[3]),
+        ])
+    );
+    let path = db.shortest_path(ids[3], ids[0], 10, None, None)?;
+    assert_eq!(
+        path,
+        Some(vec![
+            Edge::new(ids[1], other_edge_t, ids[3]),
+            Edge::new(ids[0], edge_t, ids[1]),
+        ])
+    );
+
+    let path = db.shortest_path(ids[0], ids[0], 10, None, None)?;
+    assert_eq!(path, Some(vec![]));
+
+    // Longer paths are searched from both ends, and joined up in order
+    let vertex_t = Identifier::new("test_vertex_type")?;
+    let chain = (0..6)
+        .map(|_| db.create_vertex_from_type(vertex_t))
+        .collect::<Result<Vec<Uuid>, Error>>()?;
+    let edges: Vec<Edge> = chain.windows(2).map(|ids| Edge::new(ids[0], edge_t, ids[1])).collect();
+    for edge in &edges {
+        db.create_edge(edge)?;
+    }
+    let path = db.shortest_path(chain[0], chain[5], 5, None, Some(EdgeDirection::Outbound))?;
+    assert_eq!(path, Some(edges.clone()));
+    let path = db.shortest_path(chain[0], chain[5], 4, None, Some(EdgeDirection::Outbound))?;
+    assert_eq!(path, None);
+    Ok(())
+}
+
+pub fn should_not_find_shortest_path<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let ids = create_cycle(db)?;
+    let edge_t = Identifier::new("test_edge_type")?;
+    assert_eq!(
+        db.shortest_path(ids[0], ids[2], 1, None, Some(EdgeDirection::Outbound))?,
+        None
+    );
+    assert_eq!(
+        db.shortest_path(ids[3], ids[0], 10, None, Some(EdgeDirection::Outbound))?,
+        None
+    );
+    assert_eq!(
+        db.shortest_path(ids[0], ids[3], 10, Some(edge_t), Some(EdgeDirection::Outbound))?,
+        None
+    );
+    assert_eq!(db.shortest_path(ids[0], Uuid::default(), 10, None, None)?, None);
+    Ok(())
+}
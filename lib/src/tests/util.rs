@@ -55,6 +55,7 @@ pub(crate) fn get_edge_count<D: Datastore>(
     let q = match direction {
         models::EdgeDirection::Outbound => q.outbound().unwrap(),
         models::EdgeDirection::Inbound => q.inbound().unwrap(),
+        models::EdgeDirection::Both => q.both().unwrap(),
     };
 
     let q: models::Query = if let Some(t) = t {
@@ -1,11 +1,14 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::error::Error as StdError;
+use std::thread;
+use std::time::{Duration, SystemTime};
 
 use super::util;
-use crate::util::extract_count;
+use crate::util::{extract_count, extract_vertices};
 use crate::{
-    errors, expect_err, ijson, models, AllVertexQuery, CountQueryExt, Database, Datastore, Error, QueryExt,
-    RangeVertexQuery, SpecificVertexQuery,
+    errors, expect_err, ijson, models, AllEdgeQuery, AllVertexQuery, BulkInsertItem, CountQueryExt,
+    CreatedRangeVertexQuery, Cursor, Database, Datastore, Error, QueryExt, RangeDirection, RangeVertexQuery,
+    SpecificVertexQuery, VertexWithLabelQuery,
 };
 
 use uuid::Uuid;
@@ -16,6 +19,62 @@ pub fn should_create_vertex_from_type<D: Datastore>(db: &Database<D>) -> Result<
     Ok(())
 }
 
+pub fn should_create_vertex_with_properties<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = models::Identifier::new("test_vertex_type")?;
+    let name = models::Identifier::new("name")?;
+    let age = models::Identifier::new("age")?;
+    db.index_property(name)?;
+    let id = db.create_vertex_with_properties(t, vec![(name, ijson!("alice")), (age, ijson!(30))])?;
+
+    assert_eq!(db.get_vertex(id)?.map(|v| v.t), Some(t));
+    let props = util::get_all_vertex_properties(db, SpecificVertexQuery::single(id))?;
+    assert_eq!(props.len(), 1);
+    assert_eq!(
+        props[0].props,
+        vec![
+            models::NamedProperty::new(age, ijson!(30)),
+            models::NamedProperty::new(name, ijson!("alice")),
+        ]
+    );
+    let vertices = util::get_vertices(db, models::VertexWithPropertyValueQuery::new(name, ijson!("alice")))?;
+    assert_eq!(vertices.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), vec![id]);
+    assert_eq!(db.get_vertex_property_names()?, vec![age, name]);
+    Ok(())
+}
+
+pub fn should_upsert_vertex<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = models::Identifier::new("test_vertex_type")?;
+    let name = models::Identifier::new("name")?;
+    let age = models::Identifier::new("age")?;
+    db.index_property(name)?;
+    let vertex = models::Vertex::new(t);
+    let q = SpecificVertexQuery::single(vertex.id);
+
+    assert!(db.upsert_vertex(&vertex, vec![(name, ijson!("alice")), (age, ijson!(30))])?);
+    assert_eq!(db.get_vertex(vertex.id)?, Some(vertex.clone()));
+
+    // Upserting again only sets the given properties, and keeps the type
+    let other_t = models::Identifier::new("other_vertex_type")?;
+    assert!(!db.upsert_vertex(
+        &models::Vertex::with_id(vertex.id, other_t),
+        vec![(name, ijson!("bob"))]
+    )?);
+    assert_eq!(db.get_vertex(vertex.id)?, Some(vertex));
+    let props = util::get_all_vertex_properties(db, q)?;
+    assert_eq!(props.len(), 1);
+    assert_eq!(
+        props[0].props,
+        vec![
+            models::NamedProperty::new(age, ijson!(30)),
+            models::NamedProperty::new(name, ijson!("bob")),
+        ]
+    );
+    let vertices = util::get_vertices(db, models::VertexWithPropertyValueQuery::new(name, ijson!("alice")))?;
+    assert!(vertices.is_empty());
+    assert_eq!(db.get_vertex_count_by_type(t)?, 1);
+    Ok(())
+}
+
 pub fn should_get_all_vertices<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let inserted_ids = create_vertices(db)?;
     let range = util::get_vertices(db, AllVertexQuery)?;
@@ -30,6 +89,58 @@ pub fn should_get_range_vertices<D: Datastore>(db: &Database<D>) -> Result<(), E
     Ok(())
 }
 
+pub fn should_get_range_vertices_descending<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let mut ids = create_vertices(db)?;
+    ids.sort();
+    ids.reverse();
+
+    let q = RangeVertexQuery::new().direction(RangeDirection::Descending);
+    let range = util::get_vertices(db, q.clone())?;
+    assert_eq!(range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), ids);
+    // The start ID is the highest one returned
+    let range = util::get_vertices(db, q.clone().start_id(ids[2]).limit(2))?;
+    assert_eq!(
+        range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(),
+        vec![ids[2], ids[3]]
+    );
+    let range = util::get_vertices(db, q.start_id(Uuid::nil()))?;
+    assert_eq!(range.len(), 0);
+    Ok(())
+}
+
+pub fn should_get_vertex_pages<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let mut ids = create_vertices(db)?;
+    ids.sort();
+
+    for direction in [RangeDirection::Ascending, RangeDirection::Descending] {
+        let q = RangeVertexQuery::new().direction(direction).limit(2);
+        let mut paged_ids = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = db.get_vertex_page(q.clone(), cursor.as_ref())?;
+            assert!(page.len() <= 2);
+            paged_ids.extend(page.into_iter().map(|v| v.id));
+            match next {
+                // Cursors survive being handed out as strings
+                Some(next) => cursor = Some(next.to_string().parse::<Cursor>()?),
+                None => break,
+            }
+        }
+        if direction == RangeDirection::Descending {
+            paged_ids.reverse();
+        }
+        assert_eq!(paged_ids, ids);
+    }
+
+    let (_, cursor) = db.get_vertex_page(RangeVertexQuery::new().limit(1), None)?;
+    let other_q = RangeVertexQuery::new().t(models::Identifier::new("other_vertex_type")?);
+    expect_err!(
+        db.get_vertex_page(other_q.clone(), cursor.as_ref()),
+        Error::InvalidCursor
+    );
+    Ok(())
+}
+
 pub fn should_get_no_vertices_with_zero_limit<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     create_vertices(db)?;
     let range = util::get_vertices(db, RangeVertexQuery::new().limit(0))?;
@@ -55,6 +166,33 @@ pub fn should_get_no_vertices_with_type_filter<D: Datastore>(db: &Database<D>) -
     Ok(())
 }
 
+pub fn should_get_range_vertices_with_multiple_types<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t1 = models::Identifier::new("test_vertex_type_1")?;
+    let t2 = models::Identifier::new("test_vertex_type_2")?;
+    let t3 = models::Identifier::new("test_vertex_type_3")?;
+    let v1 = db.create_vertex_from_type(t1)?;
+    db.create_vertex_from_type(t2)?;
+    let v3 = db.create_vertex_from_type(t3)?;
+
+    // Vertices are ordered by ID rather than type
+    let range = util::get_vertices(db, RangeVertexQuery::new().t(t3).t(t1))?;
+    let mut expected = vec![v1, v3];
+    expected.sort();
+    assert_eq!(range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+    let range = util::get_vertices(db, RangeVertexQuery::new().t(t1).t(models::Identifier::new("foo")?))?;
+    assert_eq!(range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), vec![v1]);
+    Ok(())
+}
+
+pub fn should_get_range_vertices_with_property_filter<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    check_range_vertices_with_property_filter(db)
+}
+
+pub fn should_get_range_vertices_with_indexed_property_filter<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    db.index_property(models::Identifier::new("status")?)?;
+    check_range_vertices_with_property_filter(db)
+}
+
 pub fn should_get_single_vertex<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let vertex_t = models::Identifier::new("test_vertex_type")?;
     let id = db.create_vertex_from_type(vertex_t)?;
@@ -73,6 +211,93 @@ pub fn should_get_single_vertex_nonexisting<D: Datastore>(db: &Database<D>) -> R
     Ok(())
 }
 
+pub fn should_get_vertex<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let id = db.create_vertex_from_type(vertex_t)?;
+    assert_eq!(db.get_vertex(id)?, Some(models::Vertex::with_id(id, vertex_t)));
+    assert_eq!(db.get_vertex(Uuid::default())?, None);
+
+    db.delete(SpecificVertexQuery::single(id))?;
+    assert_eq!(db.get_vertex(id)?, None);
+    Ok(())
+}
+
+pub fn should_check_vertex_exists<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let id = db.create_vertex_from_type(vertex_t)?;
+    assert!(db.contains_vertex(id)?);
+    assert!(!db.contains_vertex(Uuid::default())?);
+
+    db.delete(SpecificVertexQuery::single(id))?;
+    assert!(!db.contains_vertex(id)?);
+    Ok(())
+}
+
+pub fn should_get_one<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    assert_eq!(db.get_one(AllVertexQuery)?, None);
+    assert_eq!(db.get_one(AllEdgeQuery)?, None);
+
+    let vertex_t = models::Identifier::new("test_vertex_type")?;
+    let ids = create_vertices(db)?;
+    let edge = models::Edge::new(ids[0], models::Identifier::new("test_edge_type")?, ids[1]);
+    db.create_edge(&edge)?;
+
+    match db.get_one(AllVertexQuery)? {
+        Some(models::QueryOutputValue::Vertices(vertices)) => {
+            assert_eq!(vertices.len(), 1);
+            assert!(ids.contains(&vertices[0].id));
+            assert_eq!(vertices[0].t, vertex_t);
+        }
+        value => panic!("unexpected value: {value:?}"),
+    }
+    assert_eq!(
+        db.get_one(AllEdgeQuery)?,
+        Some(models::QueryOutputValue::Edges(vec![edge.clone()]))
+    );
+    assert_eq!(
+        db.get_one(SpecificVertexQuery::single(ids[0]).outbound()?)?,
+        Some(models::QueryOutputValue::Edges(vec![edge]))
+    );
+    assert_eq!(
+        db.get_one(AllVertexQuery.count()?)?,
+        Some(models::QueryOutputValue::Count(ids.len() as u64))
+    );
+    assert_eq!(db.get_one(SpecificVertexQuery::single(ids[1]).outbound()?)?, None);
+    Ok(())
+}
+
+pub fn should_get_batch<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    assert!(db.get_batch(Vec::new())?.is_empty());
+
+    let ids = create_vertices(db)?;
+    let edge = models::Edge::new(ids[0], models::Identifier::new("test_edge_type")?, ids[1]);
+    db.create_edge(&edge)?;
+
+    let results = db.get_batch(vec![
+        SpecificVertexQuery::single(ids[0]).into(),
+        SpecificVertexQuery::single(ids[0]).outbound()?.count()?.into(),
+        SpecificVertexQuery::single(ids[1]).include().outbound()?.into(),
+    ])?;
+    assert_eq!(results.len(), 3);
+    let vertices = extract_vertices(results[0].clone()).unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, ids[0]);
+    assert_eq!(results[1], vec![models::QueryOutputValue::Count(1)]);
+    assert_eq!(results[2].len(), 2);
+    assert_eq!(results[2][1], models::QueryOutputValue::Edges(Vec::new()));
+
+    // One failing query fails the whole batch
+    let q = models::PipeQuery {
+        inner: Box::new(AllVertexQuery.count()?.into()),
+        direction: models::EdgeDirection::Outbound,
+        limit: 1,
+        t_filter: Vec::new(),
+    };
+    let result = db.get_batch(vec![AllVertexQuery.into(), q.into()]);
+    expect_err!(result, Error::OperationOnQuery);
+    Ok(())
+}
+
 pub fn should_get_vertices<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let mut inserted_ids = create_vertices(db)?;
 
@@ -151,7 +376,7 @@ pub fn should_delete_a_valid_outbound_vertex<D: Datastore>(db: &Database<D>) ->
     let (outbound_id, _) = util::create_edges(db)?;
     let q = SpecificVertexQuery::single(outbound_id);
     db.set_properties(q.clone(), models::Identifier::new("foo")?, &ijson!(true))?;
-    db.delete(q.clone())?;
+    assert_eq!(db.delete(q.clone())?, 1);
     let v = util::get_vertices(db, q)?;
     assert_eq!(v.len(), 0);
     let t = models::Identifier::new("test_edge_type")?;
@@ -174,7 +399,8 @@ pub fn should_delete_a_valid_inbound_vertex<D: Datastore>(db: &Database<D>) -> R
 }
 
 pub fn should_not_delete_an_invalid_vertex<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
-    db.delete(SpecificVertexQuery::single(Uuid::default()))
+    assert_eq!(db.delete(SpecificVertexQuery::single(Uuid::default()))?, 0);
+    Ok(())
 }
 
 pub fn should_get_a_vertex_count<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
@@ -187,6 +413,49 @@ pub fn should_get_a_vertex_count<D: Datastore>(db: &Database<D>) -> Result<(), E
     Ok(())
 }
 
+pub fn should_get_a_vertex_count_by_type<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t1 = models::Identifier::new("test_vertex_type_1")?;
+    let t2 = models::Identifier::new("test_vertex_type_2")?;
+    let id = db.create_vertex_from_type(t1)?;
+    db.create_vertex_from_type(t1)?;
+    db.create_vertex_from_type(t2)?;
+    assert_eq!(db.get_vertex_count_by_type(t1)?, 2);
+    assert_eq!(db.get_vertex_count_by_type(t2)?, 1);
+    assert_eq!(db.get_vertex_count_by_type(models::Identifier::new("foo")?)?, 0);
+
+    // Re-creating an existing vertex shouldn't change the counts
+    assert!(!db.create_vertex(&models::Vertex::with_id(id, t1))?);
+    db.bulk_insert(vec![BulkInsertItem::Vertex(models::Vertex::with_id(id, t1))])?;
+    assert_eq!(db.get_vertex_count_by_type(t1)?, 2);
+
+    db.delete(SpecificVertexQuery::single(id))?;
+    assert_eq!(db.get_vertex_count_by_type(t1)?, 1);
+    db.delete(AllVertexQuery)?;
+    assert_eq!(db.get_vertex_count_by_type(t1)?, 0);
+    assert_eq!(db.get_vertex_count_by_type(t2)?, 0);
+    Ok(())
+}
+
+pub fn should_get_stats<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let stats = db.get_stats()?;
+    assert_eq!(stats.vertex_count, 0);
+    assert_eq!(stats.index_count, 0);
+
+    // Edge counts may be estimated, so only vertex and index counts are
+    // checked exactly
+    let t = models::Identifier::new("test_vertex_type")?;
+    let id = db.create_vertex_from_type(t)?;
+    db.create_vertex_from_type(t)?;
+    db.index_property(models::Identifier::new("foo")?)?;
+    let stats = db.get_stats()?;
+    assert_eq!(stats.vertex_count, 2);
+    assert_eq!(stats.index_count, 1);
+
+    db.delete(SpecificVertexQuery::single(id))?;
+    assert_eq!(db.get_stats()?.vertex_count, 1);
+    Ok(())
+}
+
 pub fn should_not_delete_on_vertex_count<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
     let result = db.delete(AllVertexQuery.count()?);
     expect_err!(result, errors::Error::OperationOnQuery);
@@ -200,13 +469,52 @@ pub fn should_not_pipe_on_vertex_count<D: Datastore>(db: &Database<D>) -> Result
         inner: Box::new(AllVertexQuery.count()?.into()),
         direction: models::EdgeDirection::Outbound,
         limit: 1,
-        t: None,
+        t_filter: Vec::new(),
     };
     let result = db.get(q);
     expect_err!(result, errors::Error::OperationOnQuery);
     Ok(())
 }
 
+pub fn should_stream_vertices<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let inserted_ids = create_vertices(db)?;
+    let other_id = db.create_vertex_from_type(models::Identifier::new("other_vertex_type")?)?;
+    let stream = |q: models::Query| -> Result<Vec<models::Vertex>, Error> {
+        let mut vertices = Vec::new();
+        db.stream_vertices(q, |v| {
+            vertices.push(v);
+            Ok(())
+        })?;
+        Ok(vertices)
+    };
+
+    let range = stream(AllVertexQuery.into())?;
+    check_has_all_vertices(range, inserted_ids.clone());
+
+    let q = RangeVertexQuery::new()
+        .t(models::Identifier::new("test_vertex_type")?)
+        .limit(3);
+    let range = stream(q.into())?;
+    assert_eq!(range.len(), 3);
+    assert!(range.iter().all(|v| v.id != other_id));
+
+    // Queries that aren't streamed are run in full
+    let q = SpecificVertexQuery::new(inserted_ids).outbound()?.inbound()?;
+    assert_eq!(stream(q.into())?.len(), 0);
+
+    assert!(matches!(stream(AllEdgeQuery.into()), Err(Error::OperationOnQuery)));
+
+    // Errors from the callback stop the stream
+    let mut seen = 0;
+    let result = db.stream_vertices(AllVertexQuery, |_| {
+        seen += 1;
+        Err(Error::Unsupported)
+    });
+    assert!(matches!(result, Err(Error::Unsupported)));
+    assert_eq!(seen, 1);
+    Ok(())
+}
+
 fn check_has_all_vertices(range: Vec<models::Vertex>, mut inserted_ids: Vec<Uuid>) {
     assert!(range.len() >= 5);
     let mut covered_ids: HashSet<Uuid> = HashSet::new();
@@ -221,6 +529,110 @@ fn check_has_all_vertices(range: Vec<models::Vertex>, mut inserted_ids: Vec<Uuid
     }
 }
 
+fn check_range_vertices_with_property_filter<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let mut ids = create_vertices(db)?;
+    ids.sort();
+    let name = models::Identifier::new("status")?;
+    let q = SpecificVertexQuery::new(vec![ids[0], ids[2], ids[4]]);
+    db.set_properties(q, name, &ijson!("active"))?;
+    db.set_properties(SpecificVertexQuery::single(ids[1]), name, &ijson!("inactive"))?;
+
+    let q = RangeVertexQuery::new().property_filter(name, ijson!("active"));
+    let range = util::get_vertices(db, q.clone())?;
+    assert_eq!(
+        range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(),
+        vec![ids[0], ids[2], ids[4]]
+    );
+    let range = util::get_vertices(db, q.clone().start_id(ids[1]))?;
+    assert_eq!(
+        range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(),
+        vec![ids[2], ids[4]]
+    );
+    let range = util::get_vertices(db, q.clone().limit(1))?;
+    assert_eq!(range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(), vec![ids[0]]);
+    let range = util::get_vertices(db, q.clone().direction(RangeDirection::Descending).start_id(ids[3]))?;
+    assert_eq!(
+        range.into_iter().map(|v| v.id).collect::<Vec<Uuid>>(),
+        vec![ids[2], ids[0]]
+    );
+    let range = util::get_vertices(db, q.t(models::Identifier::new("foo")?))?;
+    assert_eq!(range.len(), 0);
+
+    let q = RangeVertexQuery::new().property_filter(name, ijson!("missing"));
+    let range = util::get_vertices(db, q)?;
+    assert_eq!(range.len(), 0);
+    Ok(())
+}
+
+pub fn should_add_and_remove_vertex_labels<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = models::Identifier::new("person")?;
+    let admin = models::Identifier::new("admin")?;
+    let staff = models::Identifier::new("staff")?;
+    let labels: BTreeSet<models::Identifier> = vec![admin, staff].into_iter().collect();
+    let id = db.create_vertex_with_labels(t, labels.clone())?;
+    let other_id = db.create_vertex_from_type(t)?;
+    assert_eq!(db.get_vertex_labels(id)?, labels);
+    assert!(db.get_vertex_labels(other_id)?.is_empty());
+
+    db.add_label(SpecificVertexQuery::new(vec![other_id, Uuid::default()]), staff)?;
+    let staff_ids: HashSet<Uuid> = util::get_vertices(db, VertexWithLabelQuery::new(staff))?
+        .into_iter()
+        .map(|v| v.id)
+        .collect();
+    assert_eq!(staff_ids, vec![id, other_id].into_iter().collect());
+    // Labels are separate from the vertex type
+    assert!(util::get_vertices(db, VertexWithLabelQuery::new(t))?.is_empty());
+
+    db.remove_label(SpecificVertexQuery::single(id), staff)?;
+    assert_eq!(db.get_vertex_labels(id)?, vec![admin].into_iter().collect());
+    let staff_vertices = util::get_vertices(db, VertexWithLabelQuery::new(staff))?;
+    assert_eq!(staff_vertices.len(), 1);
+    assert_eq!(staff_vertices[0].id, other_id);
+
+    // Deleting a vertex deletes its labels
+    db.delete(SpecificVertexQuery::single(id))?;
+    assert!(db.get_vertex_labels(id)?.is_empty());
+    assert!(util::get_vertices(db, VertexWithLabelQuery::new(admin))?.is_empty());
+
+    expect_err!(db.add_label(AllEdgeQuery, admin), Error::OperationOnQuery);
+    Ok(())
+}
+
+pub fn should_get_vertices_by_creation_time<D: Datastore>(db: &Database<D>) -> Result<(), Error> {
+    let t = models::Identifier::new("test_vertex_type")?;
+    let before = SystemTime::now() - Duration::from_secs(1);
+    let first_id = db.create_vertex_from_type(t)?;
+    // Sleep so that the creation times are distinct, even when they're
+    // stored with millisecond precision
+    thread::sleep(Duration::from_millis(5));
+    let middle = SystemTime::now();
+    thread::sleep(Duration::from_millis(5));
+    let second_id = db.create_vertex_from_type(t)?;
+
+    let first_created_at = db.get_vertex_created_at(first_id)?.unwrap();
+    assert!(before <= first_created_at && first_created_at <= middle);
+    assert!(db.get_vertex_created_at(second_id)?.unwrap() >= middle);
+    assert_eq!(db.get_vertex_created_at(Uuid::default())?, None);
+
+    let get_ids = |q: CreatedRangeVertexQuery| -> Result<Vec<Uuid>, Error> {
+        Ok(util::get_vertices(db, q)?.into_iter().map(|v| v.id).collect())
+    };
+    assert_eq!(get_ids(CreatedRangeVertexQuery::new())?, vec![first_id, second_id]);
+    assert_eq!(get_ids(CreatedRangeVertexQuery::new().low(middle))?, vec![second_id]);
+    assert_eq!(get_ids(CreatedRangeVertexQuery::new().high(middle))?, vec![first_id]);
+    assert_eq!(get_ids(CreatedRangeVertexQuery::new().limit(1))?, vec![first_id]);
+
+    // Overwriting a vertex keeps its creation time
+    db.bulk_insert(vec![BulkInsertItem::Vertex(models::Vertex::with_id(first_id, t))])?;
+    assert_eq!(db.get_vertex_created_at(first_id)?, Some(first_created_at));
+    assert_eq!(get_ids(CreatedRangeVertexQuery::new())?, vec![first_id, second_id]);
+
+    db.delete(SpecificVertexQuery::single(first_id))?;
+    assert_eq!(db.get_vertex_created_at(first_id)?, None);
+    assert_eq!(get_ids(CreatedRangeVertexQuery::new())?, vec![second_id]);
+    Ok(())
+}
+
 fn create_vertices<D: Datastore>(db: &Database<D>) -> Result<Vec<Uuid>, Error> {
     let t = models::Identifier::new("test_vertex_type")?;
     let mut ids = Vec::with_capacity(5);
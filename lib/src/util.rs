@@ -2,20 +2,36 @@
 //! that implement Datastore.
 
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
 use std::hash::{Hash, Hasher};
 use std::io::{Cursor, Error as IoError, Read, Write};
 use std::{str, u8};
 
-use crate::errors::{ValidationError, ValidationResult};
+use crate::errors::{Error, Result as DbResult, ValidationError, ValidationResult};
 use crate::models;
+use crate::Transaction;
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use once_cell::sync::Lazy;
 use uuid::v1::{Context, Timestamp};
 use uuid::Uuid;
 
+mod csv;
+mod graphml;
+mod jsonl;
+
+pub use self::csv::{export_edges_csv, export_vertices_csv, import_edges_csv, import_vertices_csv, CsvRowError};
+pub use self::graphml::export_graphml;
+pub use self::jsonl::{dump, restore};
+
 const NODE_ID: [u8; 6] = [0, 0, 0, 0, 0, 0];
 
+// Identifiers are prefixed with their length as a single byte. Identifiers
+// longer than 255 bytes instead get two `0xFF` bytes followed by the length
+// as a `u16`. This doesn't collide with an identifier of exactly 255 bytes,
+// as `0xFF` never starts a UTF-8 character.
+const LONG_IDENTIFIER_MARKER: u8 = u8::MAX;
+
 static CONTEXT: Lazy<Context> = Lazy::new(|| Context::new(0));
 
 /// A byte-serializable value, frequently employed in the keys of key/value
@@ -38,6 +54,7 @@ impl<'a> Component<'a> {
         match *self {
             Component::Uuid(_) => 16,
             Component::FixedLengthString(s) => s.len(),
+            Component::Identifier(t) if t.0.len() > LONG_IDENTIFIER_MARKER as usize => t.0.len() + 4,
             Component::Identifier(t) => t.0.len() + 1,
             Component::Json(_) => 8,
         }
@@ -49,7 +66,12 @@ impl<'a> Component<'a> {
             Component::Uuid(uuid) => cursor.write_all(uuid.as_bytes()),
             Component::FixedLengthString(s) => cursor.write_all(s.as_bytes()),
             Component::Identifier(i) => {
-                cursor.write_all(&[i.0.len() as u8])?;
+                if i.0.len() > LONG_IDENTIFIER_MARKER as usize {
+                    cursor.write_all(&[LONG_IDENTIFIER_MARKER, LONG_IDENTIFIER_MARKER])?;
+                    cursor.write_u16::<BigEndian>(i.0.len() as u16)?;
+                } else {
+                    cursor.write_all(&[i.0.len() as u8])?;
+                }
                 cursor.write_all(i.0.as_bytes())
             }
             Component::Json(json) => {
@@ -94,15 +116,28 @@ pub fn read_uuid<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> Uuid {
 /// # Arguments
 /// * `cursor`: The bytes to read from.
 pub fn read_identifier<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> models::Identifier {
-    let t_len = {
-        let mut buf: [u8; 1] = [0; 1];
-        cursor.read_exact(&mut buf).unwrap();
-        buf[0] as usize
+    let buf = match cursor.read_u8().unwrap() {
+        LONG_IDENTIFIER_MARKER => match cursor.read_u8().unwrap() {
+            LONG_IDENTIFIER_MARKER => {
+                let t_len = cursor.read_u16::<BigEndian>().unwrap() as usize;
+                let mut buf = vec![0u8; t_len];
+                cursor.read_exact(&mut buf).unwrap();
+                buf
+            }
+            // An identifier of exactly 255 bytes, of which this is the first
+            first => {
+                let mut buf = vec![first; LONG_IDENTIFIER_MARKER as usize];
+                cursor.read_exact(&mut buf[1..]).unwrap();
+                buf
+            }
+        },
+        t_len => {
+            let mut buf = vec![0u8; t_len as usize];
+            cursor.read_exact(&mut buf).unwrap();
+            buf
+        }
     };
 
-    let mut buf = vec![0u8; t_len];
-    cursor.read_exact(&mut buf).unwrap();
-
     unsafe {
         let s = str::from_utf8_unchecked(&buf).to_string();
         models::Identifier::new_unchecked(s)
@@ -156,6 +191,200 @@ pub fn next_uuid(uuid: Uuid) -> ValidationResult<Uuid> {
     Err(ValidationError::CannotIncrementUuid)
 }
 
+/// Adds `delta` to a numeric JSON value, treating a missing value as 0.
+/// Integers stay integers when `delta` is a whole number and the result
+/// doesn't overflow; otherwise the result is a float.
+///
+/// # Arguments
+/// * `value`: The current value, if any.
+/// * `delta`: The amount to add.
+///
+/// # Errors
+/// Returns `Error::NotNumeric` if the current value is not a number, or
+/// `ValidationError::InvalidValue` if the result is not a finite number.
+pub fn increment_json(value: Option<&models::Json>, delta: f64) -> DbResult<models::Json> {
+    let current = match value {
+        None => serde_json::Number::from(0),
+        Some(value) => match **value {
+            serde_json::Value::Number(ref n) => n.clone(),
+            _ => return Err(Error::NotNumeric),
+        },
+    };
+
+    if delta.fract() == 0.0 && delta.abs() < i64::MAX as f64 {
+        if let Some(result) = current.as_i64().and_then(|n| n.checked_add(delta as i64)) {
+            return Ok(models::Json::new(serde_json::Value::from(result)));
+        }
+    }
+
+    let result = current.as_f64().unwrap() + delta;
+    match serde_json::Number::from_f64(result) {
+        Some(result) => Ok(models::Json::new(serde_json::Value::Number(result))),
+        None => Err(ValidationError::InvalidValue.into()),
+    }
+}
+
+/// Deep-merges a JSON object patch into the current value of a property.
+/// Nested objects are merged recursively, while any other values in the
+/// patch - including arrays - overwrite the current ones.
+///
+/// # Arguments
+/// * `value`: The current value, if any. If missing, the result is the
+///   patch.
+/// * `patch`: The object to merge in.
+///
+/// # Errors
+/// Returns `Error::NotObject` if either the current value or the patch is
+/// not an object.
+pub fn merge_json(value: Option<&models::Json>, patch: &models::Json) -> DbResult<models::Json> {
+    if !patch.is_object() {
+        return Err(Error::NotObject);
+    }
+
+    let mut current = match value {
+        None => serde_json::Value::Object(serde_json::Map::new()),
+        Some(value) if value.is_object() => (**value).clone(),
+        Some(_) => return Err(Error::NotObject),
+    };
+
+    merge_json_value(&mut current, patch);
+    Ok(models::Json::new(current))
+}
+
+fn merge_json_value(current: &mut serde_json::Value, patch: &serde_json::Value) {
+    match (current, patch) {
+        (serde_json::Value::Object(current), serde_json::Value::Object(patch)) => {
+            for (key, patch_value) in patch {
+                match current.get_mut(key) {
+                    Some(current_value) if current_value.is_object() && patch_value.is_object() => {
+                        merge_json_value(current_value, patch_value);
+                    }
+                    _ => {
+                        current.insert(key.clone(), patch_value.clone());
+                    }
+                }
+            }
+        }
+        (current, patch) => *current = patch.clone(),
+    }
+}
+
+/// Gets the names of all vertex properties in use. For datastores that don't
+/// track them, this scans every vertex's properties.
+pub(crate) fn all_vertex_property_names<'a>(txn: &(dyn Transaction<'a> + 'a)) -> DbResult<Vec<models::Identifier>> {
+    let txn_ptr = txn as *const dyn Transaction<'a>;
+    match txn.vertex_property_names() {
+        Err(Error::Unsupported) => {
+            let mut names = BTreeSet::new();
+            for vertex in unsafe { (*txn_ptr).all_vertices()? } {
+                for property in unsafe { (*txn_ptr).all_vertex_properties_for_vertex(&vertex?)? } {
+                    names.insert(property?.0);
+                }
+            }
+            Ok(names.into_iter().collect())
+        }
+        result => result,
+    }
+}
+
+/// Gets the names of all edge properties in use. For datastores that don't
+/// track them, this scans every edge's properties.
+pub(crate) fn all_edge_property_names<'a>(txn: &(dyn Transaction<'a> + 'a)) -> DbResult<Vec<models::Identifier>> {
+    let txn_ptr = txn as *const dyn Transaction<'a>;
+    match txn.edge_property_names() {
+        Err(Error::Unsupported) => {
+            let mut names = BTreeSet::new();
+            for edge in unsafe { (*txn_ptr).all_edges()? } {
+                for property in unsafe { (*txn_ptr).all_edge_properties_for_edge(&edge?)? } {
+                    names.insert(property?.0);
+                }
+            }
+            Ok(names.into_iter().collect())
+        }
+        result => result,
+    }
+}
+
+/// Gets the value of a nested field, or `None` if it's missing or a value
+/// along the way isn't an object.
+///
+/// # Arguments
+/// * `value`: The JSON value.
+/// * `fields`: The names of the nested fields. If empty, this is `value`
+///   itself.
+pub(crate) fn json_at_path(value: &models::Json, fields: &[&str]) -> Option<models::Json> {
+    let mut current: &serde_json::Value = value;
+    for field in fields {
+        current = current.as_object()?.get(*field)?;
+    }
+    Some(models::Json::new(current.clone()))
+}
+
+/// Checks a property value against the type registered for the property in
+/// its schema, if there is one.
+///
+/// # Arguments
+/// * `property_schemas`: The expected value types of properties with a
+///   registered schema.
+/// * `name`: The property name.
+/// * `value`: The property value.
+///
+/// # Errors
+/// Returns `Error::SchemaViolation` if the value isn't of the expected type.
+pub(crate) fn check_property_schema(
+    property_schemas: &HashMap<models::Identifier, models::JsonType>,
+    name: models::Identifier,
+    value: &models::Json,
+) -> DbResult<()> {
+    match property_schemas.get(&name) {
+        Some(expected) if *expected != value.json_type() => Err(Error::SchemaViolation),
+        _ => Ok(()),
+    }
+}
+
+/// Gets whether a property is covered by any index, either on the property
+/// itself or on a path into it.
+///
+/// # Arguments
+/// * `indexed_properties`: The names of indexed properties and paths.
+/// * `name`: The property name.
+#[cfg(feature = "rocksdb-datastore")]
+pub(crate) fn is_indexed<'a, I>(indexed_properties: I, name: models::Identifier) -> bool
+where
+    I: IntoIterator<Item = &'a models::Identifier>,
+{
+    indexed_properties
+        .into_iter()
+        .any(|indexed_name| indexed_name.split_path().0 == name)
+}
+
+/// Gets the values a property should be indexed under, as pairs of index
+/// name and indexed value.
+///
+/// # Arguments
+/// * `indexed_properties`: The names of indexed properties and paths.
+/// * `name`: The property name.
+/// * `value`: The property value.
+pub(crate) fn indexed_values<'a, I>(
+    indexed_properties: I,
+    name: models::Identifier,
+    value: &models::Json,
+) -> Vec<(models::Identifier, models::Json)>
+where
+    I: IntoIterator<Item = &'a models::Identifier>,
+{
+    indexed_properties
+        .into_iter()
+        .filter_map(|indexed_name| {
+            let (property_name, fields) = indexed_name.split_path();
+            if property_name != name {
+                return None;
+            }
+            json_at_path(value, &fields).map(|indexed_value| (*indexed_name, indexed_value))
+        })
+        .collect()
+}
+
 /// Extracts vertices from the last query output value, or `None`.
 ///
 /// # Arguments
@@ -216,15 +445,58 @@ pub fn extract_edge_properties(mut output: Vec<models::QueryOutputValue>) -> Opt
     }
 }
 
+/// Extracts the first item from the last query output value, or `None` if it
+/// has no items. Counts are returned as-is.
+///
+/// # Arguments
+/// * `output`: The query output.
+pub fn extract_first(mut output: Vec<models::QueryOutputValue>) -> Option<models::QueryOutputValue> {
+    match output.pop()? {
+        models::QueryOutputValue::Vertices(vertices) => vertices
+            .into_iter()
+            .next()
+            .map(|vertex| models::QueryOutputValue::Vertices(vec![vertex])),
+        models::QueryOutputValue::Edges(edges) => edges
+            .into_iter()
+            .next()
+            .map(|edge| models::QueryOutputValue::Edges(vec![edge])),
+        models::QueryOutputValue::Count(count) => Some(models::QueryOutputValue::Count(count)),
+        models::QueryOutputValue::VertexProperties(props) => props
+            .into_iter()
+            .next()
+            .map(|props| models::QueryOutputValue::VertexProperties(vec![props])),
+        models::QueryOutputValue::EdgeProperties(props) => props
+            .into_iter()
+            .next()
+            .map(|props| models::QueryOutputValue::EdgeProperties(vec![props])),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        extract_count, extract_edge_properties, extract_edges, extract_vertex_properties, extract_vertices,
-        generate_uuid_v1, next_uuid,
+        build, extract_count, extract_edge_properties, extract_edges, extract_first, extract_vertex_properties,
+        extract_vertices, generate_uuid_v1, increment_json, indexed_values, json_at_path, merge_json, next_uuid,
+        read_identifier, Component,
     };
+    use crate::{ijson, Error, Identifier, QueryOutputValue, Vertex};
     use core::str::FromStr;
+    use std::io::Cursor;
     use uuid::Uuid;
 
+    #[test]
+    fn should_round_trip_identifiers_of_any_length() {
+        for len in [0, 1, 254, 255, 256, 1000, u16::MAX as usize] {
+            let t = unsafe { Identifier::new_unchecked("x".repeat(len)) };
+            let component = Component::Identifier(t);
+            let bytes = build(&[component, Component::Uuid(Uuid::default())]);
+            assert_eq!(bytes.len(), Component::Identifier(t).byte_len() + 16);
+            let mut cursor = Cursor::new(bytes);
+            assert_eq!(read_identifier(&mut cursor), t);
+            assert_eq!(cursor.position() as usize, Component::Identifier(t).byte_len());
+        }
+    }
+
     #[test]
     fn should_generate_new_uuid_v1() {
         let first = generate_uuid_v1();
@@ -245,6 +517,57 @@ mod tests {
         assert!(next_uuid(from_uuid).is_err());
     }
 
+    #[test]
+    fn should_increment_json() {
+        assert_eq!(increment_json(None, 2.0).unwrap(), ijson!(2));
+        assert_eq!(increment_json(Some(&ijson!(42)), 1.0).unwrap(), ijson!(43));
+        assert_eq!(increment_json(Some(&ijson!(42)), -50.0).unwrap(), ijson!(-8));
+        assert_eq!(increment_json(Some(&ijson!(1.5)), 0.25).unwrap(), ijson!(1.75));
+        assert_eq!(increment_json(Some(&ijson!(1)), 0.5).unwrap(), ijson!(1.5));
+        assert_eq!(
+            increment_json(Some(&ijson!(u64::MAX)), 1.0).unwrap(),
+            ijson!(u64::MAX as f64 + 1.0)
+        );
+    }
+
+    #[test]
+    fn should_not_increment_non_numeric_json() {
+        assert!(matches!(
+            increment_json(Some(&ijson!("1")), 1.0),
+            Err(Error::NotNumeric)
+        ));
+        assert!(matches!(
+            increment_json(Some(&ijson!(null)), 1.0),
+            Err(Error::NotNumeric)
+        ));
+        assert!(matches!(
+            increment_json(Some(&ijson!(1)), f64::NAN),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn should_merge_json() {
+        assert_eq!(merge_json(None, &ijson!({"a": 1})).unwrap(), ijson!({"a": 1}));
+        assert_eq!(
+            merge_json(
+                Some(&ijson!({"a": 1, "b": {"c": 2, "d": [1, 2]}, "e": {"f": 3}})),
+                &ijson!({"b": {"c": 4, "d": [3]}, "e": 5, "g": {"h": 6}})
+            )
+            .unwrap(),
+            ijson!({"a": 1, "b": {"c": 4, "d": [3]}, "e": 5, "g": {"h": 6}})
+        );
+    }
+
+    #[test]
+    fn should_not_merge_non_object_json() {
+        assert!(matches!(
+            merge_json(Some(&ijson!([1])), &ijson!({"a": 1})),
+            Err(Error::NotObject)
+        ));
+        assert!(matches!(merge_json(None, &ijson!(1)), Err(Error::NotObject)));
+    }
+
     #[test]
     fn should_not_extract_vertices_on_empty() {
         assert_eq!(extract_vertices(vec![]), None);
@@ -269,4 +592,46 @@ mod tests {
     fn should_not_extract_edge_properties_on_empty() {
         assert_eq!(extract_edge_properties(vec![]), None);
     }
+
+    #[test]
+    fn should_extract_first() {
+        assert_eq!(extract_first(vec![]), None);
+        assert_eq!(extract_first(vec![QueryOutputValue::Vertices(vec![])]), None);
+        assert_eq!(
+            extract_first(vec![QueryOutputValue::Count(2)]),
+            Some(QueryOutputValue::Count(2))
+        );
+        let t = Identifier::new("foo").unwrap();
+        let (first, second) = (Vertex::new(t), Vertex::new(t));
+        assert_eq!(
+            extract_first(vec![QueryOutputValue::Vertices(vec![first.clone(), second])]),
+            Some(QueryOutputValue::Vertices(vec![first]))
+        );
+    }
+
+    #[test]
+    fn should_get_json_at_path() {
+        let value = ijson!({"addr": {"city": "NYC"}});
+        assert_eq!(json_at_path(&value, &[]), Some(value.clone()));
+        assert_eq!(json_at_path(&value, &["addr", "city"]), Some(ijson!("NYC")));
+        assert_eq!(json_at_path(&value, &["addr", "zip"]), None);
+        assert_eq!(json_at_path(&value, &["addr", "city", "name"]), None);
+    }
+
+    #[test]
+    fn should_get_indexed_values() {
+        let name = Identifier::new("addr").unwrap();
+        let path = Identifier::new_path("addr.city").unwrap();
+        let other_path = Identifier::new_path("other.city").unwrap();
+        let indexed_properties = vec![name, path, other_path];
+        let value = ijson!({"city": "NYC"});
+        assert_eq!(
+            indexed_values(&indexed_properties, name, &value),
+            vec![(name, value), (path, ijson!("NYC"))]
+        );
+        assert_eq!(
+            indexed_values(&indexed_properties, name, &ijson!("NYC")),
+            vec![(name, ijson!("NYC"))]
+        );
+    }
 }
@@ -0,0 +1,475 @@
+//! Importing and exporting vertices and edges as CSV.
+//!
+//! Vertex files have `id` and `type` columns, and edge files have
+//! `outbound_id`, `type` and `inbound_id` columns. Every other column is a
+//! property, named by its header. Property cells hold JSON values, except
+//! that strings are written as-is when they can't be mistaken for another
+//! JSON value, so that files are easy to work with in a spreadsheet. Empty
+//! cells are missing properties.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Error as IoError, ErrorKind as IoErrorKind, Read, Write};
+use std::str::FromStr;
+
+use crate::errors::{Error, Result};
+use crate::{BulkInsertItem, Edge, Identifier, Json, Transaction, Vertex};
+
+use uuid::Uuid;
+
+// How many rows to insert at a time when importing.
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// A row that couldn't be imported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvRowError {
+    /// The line the row starts on, counting from 1 for the header.
+    pub line: u64,
+    /// A description of what's wrong with the row.
+    pub message: String,
+}
+
+fn io_error(err: IoError) -> Error {
+    Error::Datastore(Box::new(err))
+}
+
+fn invalid_csv(message: String) -> Error {
+    io_error(IoError::new(IoErrorKind::InvalidData, message))
+}
+
+// Reads a record, which may span multiple lines if a quoted field contains
+// newlines. Returns `None` at the end of the input.
+fn read_record<R: BufRead>(reader: &mut R, line: &mut u64) -> Result<Option<Vec<String>>> {
+    let mut buf = String::new();
+    if reader.read_line(&mut buf).map_err(io_error)? == 0 {
+        return Ok(None);
+    }
+    *line += 1;
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    loop {
+        let mut chars = buf.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (in_quotes, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => in_quotes = false,
+                (true, c) => field.push(c),
+                (false, '"') => in_quotes = true,
+                (false, ',') => fields.push(std::mem::take(&mut field)),
+                (false, '\r') | (false, '\n') => {}
+                (false, c) => field.push(c),
+            }
+        }
+
+        if !in_quotes {
+            break;
+        }
+
+        buf.clear();
+        if reader.read_line(&mut buf).map_err(io_error)? == 0 {
+            return Err(invalid_csv(format!("unterminated quoted field on line {line}")));
+        }
+        *line += 1;
+    }
+
+    fields.push(field);
+    Ok(Some(fields))
+}
+
+fn write_record<W: Write>(writer: &mut W, fields: &[String]) -> Result<()> {
+    let mut record = String::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            record.push(',');
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            record.push('"');
+            record.push_str(&field.replace('"', "\"\""));
+            record.push('"');
+        } else {
+            record.push_str(field);
+        }
+    }
+    record.push('\n');
+    writer.write_all(record.as_bytes()).map_err(io_error)
+}
+
+fn json_to_cell(value: &Json) -> String {
+    match **value {
+        // Strings that would parse as JSON are encoded so they read back
+        // as strings
+        serde_json::Value::String(ref s) if !s.is_empty() && serde_json::from_str::<serde_json::Value>(s).is_err() => {
+            s.clone()
+        }
+        ref value => value.to_string(),
+    }
+}
+
+fn cell_to_json(cell: &str) -> Json {
+    match serde_json::from_str(cell) {
+        Ok(value) => Json::new(value),
+        Err(_) => Json::new(serde_json::Value::String(cell.to_string())),
+    }
+}
+
+// Maps the header to the positions of the required columns, and the names
+// of the property columns.
+struct Header {
+    required: Vec<usize>,
+    properties: Vec<(usize, Identifier)>,
+    len: usize,
+}
+
+fn read_header<R: BufRead>(reader: &mut R, line: &mut u64, required: &[&str]) -> Result<Header> {
+    let columns = match read_record(reader, line)? {
+        Some(columns) => columns,
+        None => return Err(invalid_csv("missing header".to_string())),
+    };
+
+    let mut positions = Vec::with_capacity(required.len());
+    for name in required {
+        match columns.iter().position(|column| column == name) {
+            Some(position) => positions.push(position),
+            None => return Err(invalid_csv(format!("missing column '{name}'"))),
+        }
+    }
+
+    let mut properties = Vec::new();
+    for (i, column) in columns.iter().enumerate() {
+        if !required.contains(&column.as_str()) {
+            let name = Identifier::new(column.as_str())
+                .map_err(|err| invalid_csv(format!("invalid property column '{column}': {err}")))?;
+            properties.push((i, name));
+        }
+    }
+
+    Ok(Header {
+        required: positions,
+        properties,
+        len: columns.len(),
+    })
+}
+
+// Imports rows, using `parse_row` to turn each one into the items to insert.
+fn import_csv<'a, R, F>(
+    txn: &mut (dyn Transaction<'a> + 'a),
+    reader: R,
+    required: &[&str],
+    parse_row: F,
+) -> Result<Vec<CsvRowError>>
+where
+    R: Read,
+    F: Fn(&[&str], Vec<(Identifier, Json)>) -> std::result::Result<Vec<BulkInsertItem>, String>,
+{
+    let mut reader = BufReader::new(reader);
+    let mut line = 0;
+    let header = read_header(&mut reader, &mut line, required)?;
+    let mut errors = Vec::new();
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+
+    loop {
+        let row_line = line + 1;
+        let record = match read_record(&mut reader, &mut line)? {
+            Some(record) => record,
+            None => break,
+        };
+        // Skip blank lines
+        if record.len() == 1 && record[0].is_empty() {
+            continue;
+        }
+        if record.len() != header.len {
+            errors.push(CsvRowError {
+                line: row_line,
+                message: format!("expected {} columns, got {}", header.len, record.len()),
+            });
+            continue;
+        }
+
+        let required: Vec<&str> = header.required.iter().map(|i| record[*i].as_str()).collect();
+        let properties: Vec<(Identifier, Json)> = header
+            .properties
+            .iter()
+            .filter(|(i, _)| !record[*i].is_empty())
+            .map(|(i, name)| (*name, cell_to_json(&record[*i])))
+            .collect();
+        match parse_row(&required, properties) {
+            Ok(items) => batch.extend(items),
+            Err(message) => errors.push(CsvRowError {
+                line: row_line,
+                message,
+            }),
+        }
+
+        if batch.len() >= IMPORT_BATCH_SIZE {
+            txn.bulk_insert(std::mem::take(&mut batch))?;
+        }
+    }
+
+    if !batch.is_empty() {
+        txn.bulk_insert(batch)?;
+    }
+
+    Ok(errors)
+}
+
+fn parse_uuid(column: &str, value: &str) -> std::result::Result<Uuid, String> {
+    Uuid::from_str(value).map_err(|err| format!("invalid {column} '{value}': {err}"))
+}
+
+fn parse_identifier(column: &str, value: &str) -> std::result::Result<Identifier, String> {
+    Identifier::new(value).map_err(|err| format!("invalid {column} '{value}': {err}"))
+}
+
+/// Imports vertices and their properties from CSV, via `bulk_insert`.
+/// Vertices with an empty `id` are given a new one. Rows that can't be
+/// imported are skipped, and returned along with why.
+///
+/// # Arguments
+/// * `txn`: The transaction to import into.
+/// * `reader`: The CSV to read.
+///
+/// # Errors
+/// Returns an error if the header is malformed, the CSV can't be read, or
+/// inserting fails.
+pub fn import_vertices_csv<'a, R: Read>(txn: &mut (dyn Transaction<'a> + 'a), reader: R) -> Result<Vec<CsvRowError>> {
+    import_csv(txn, reader, &["id", "type"], |required, properties| {
+        let t = parse_identifier("type", required[1])?;
+        let vertex = if required[0].is_empty() {
+            Vertex::new(t)
+        } else {
+            Vertex::with_id(parse_uuid("id", required[0])?, t)
+        };
+        let mut items = vec![BulkInsertItem::Vertex(vertex.clone())];
+        for (name, value) in properties {
+            items.push(BulkInsertItem::VertexProperty(vertex.id, name, value));
+        }
+        Ok(items)
+    })
+}
+
+/// Imports edges and their properties from CSV, via `bulk_insert`. As with
+/// `bulk_insert`, datastores may or may not check that the edges' vertices
+/// exist. Rows that can't be imported are skipped, and returned along with
+/// why.
+///
+/// # Arguments
+/// * `txn`: The transaction to import into.
+/// * `reader`: The CSV to read.
+///
+/// # Errors
+/// Returns an error if the header is malformed, the CSV can't be read, or
+/// inserting fails.
+pub fn import_edges_csv<'a, R: Read>(txn: &mut (dyn Transaction<'a> + 'a), reader: R) -> Result<Vec<CsvRowError>> {
+    import_csv(
+        txn,
+        reader,
+        &["outbound_id", "type", "inbound_id"],
+        |required, properties| {
+            let edge = Edge::new(
+                parse_uuid("outbound_id", required[0])?,
+                parse_identifier("type", required[1])?,
+                parse_uuid("inbound_id", required[2])?,
+            );
+            let mut items = vec![BulkInsertItem::Edge(edge.clone())];
+            for (name, value) in properties {
+                items.push(BulkInsertItem::EdgeProperty(edge.clone(), name, value));
+            }
+            Ok(items)
+        },
+    )
+}
+
+fn property_cells(names: &[Identifier], properties: Vec<(Identifier, Json)>) -> Vec<String> {
+    let mut properties: HashMap<Identifier, Json> = properties.into_iter().collect();
+    names
+        .iter()
+        .map(|name| match properties.remove(name) {
+            Some(value) => json_to_cell(&value),
+            None => String::new(),
+        })
+        .collect()
+}
+
+/// Exports all vertices and their properties as CSV. Rows are written as
+/// vertices are read, rather than buffering the whole graph.
+///
+/// # Arguments
+/// * `txn`: The transaction to export from.
+/// * `writer`: Where to write the CSV.
+pub fn export_vertices_csv<'a, W: Write>(txn: &(dyn Transaction<'a> + 'a), mut writer: W) -> Result<()> {
+    let txn_ptr = txn as *const dyn Transaction<'a>;
+    let names = super::all_vertex_property_names(txn)?;
+
+    let mut header = vec!["id".to_string(), "type".to_string()];
+    header.extend(names.iter().map(|name| name.as_str().to_string()));
+    write_record(&mut writer, &header)?;
+
+    for vertex in unsafe { (*txn_ptr).all_vertices()? } {
+        let vertex = vertex?;
+        let properties = unsafe { (*txn_ptr).all_vertex_properties_for_vertex(&vertex)? };
+        let properties = properties.collect::<Result<Vec<(Identifier, Json)>>>()?;
+        let mut record = vec![vertex.id.to_string(), vertex.t.as_str().to_string()];
+        record.extend(property_cells(&names, properties));
+        write_record(&mut writer, &record)?;
+    }
+
+    writer.flush().map_err(io_error)
+}
+
+/// Exports all edges and their properties as CSV. Rows are written as edges
+/// are read, rather than buffering the whole graph.
+///
+/// # Arguments
+/// * `txn`: The transaction to export from.
+/// * `writer`: Where to write the CSV.
+pub fn export_edges_csv<'a, W: Write>(txn: &(dyn Transaction<'a> + 'a), mut writer: W) -> Result<()> {
+    let txn_ptr = txn as *const dyn Transaction<'a>;
+    let names = super::all_edge_property_names(txn)?;
+
+    let mut header = vec!["outbound_id".to_string(), "type".to_string(), "inbound_id".to_string()];
+    header.extend(names.iter().map(|name| name.as_str().to_string()));
+    write_record(&mut writer, &header)?;
+
+    for edge in unsafe { (*txn_ptr).all_edges()? } {
+        let edge = edge?;
+        let properties = unsafe { (*txn_ptr).all_edge_properties_for_edge(&edge)? };
+        let properties = properties.collect::<Result<Vec<(Identifier, Json)>>>()?;
+        let mut record = vec![
+            edge.outbound_id.to_string(),
+            edge.t.as_str().to_string(),
+            edge.inbound_id.to_string(),
+        ];
+        record.extend(property_cells(&names, properties));
+        write_record(&mut writer, &record)?;
+    }
+
+    writer.flush().map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cell_to_json, export_edges_csv, export_vertices_csv, import_edges_csv, import_vertices_csv, json_to_cell,
+        read_record, write_record, CsvRowError,
+    };
+    use crate::util::{extract_edges, extract_vertices};
+    use crate::{
+        ijson, AllEdgeQuery, AllVertexQuery, Datastore, Edge, Identifier, MemoryDatastore, Transaction, Vertex,
+    };
+    use std::io::Cursor;
+
+    #[test]
+    fn should_round_trip_records() {
+        let records = vec![
+            vec!["a".to_string(), "b,c".to_string(), "".to_string()],
+            vec!["\"quoted\"".to_string(), "multi\nline".to_string(), "d".to_string()],
+        ];
+        let mut buf = Vec::new();
+        for record in &records {
+            write_record(&mut buf, record).unwrap();
+        }
+
+        let mut reader = Cursor::new(buf);
+        let mut line = 0;
+        assert_eq!(read_record(&mut reader, &mut line).unwrap().unwrap(), records[0]);
+        assert_eq!(read_record(&mut reader, &mut line).unwrap().unwrap(), records[1]);
+        assert_eq!(read_record(&mut reader, &mut line).unwrap(), None);
+        assert_eq!(line, 3);
+    }
+
+    #[test]
+    fn should_not_read_unterminated_record() {
+        let mut reader = Cursor::new("a,\"b\n");
+        let mut line = 0;
+        assert!(read_record(&mut reader, &mut line).is_err());
+    }
+
+    #[test]
+    fn should_round_trip_cells() {
+        for value in [
+            ijson!("foo"),
+            ijson!("123"),
+            ijson!("true"),
+            ijson!(""),
+            ijson!(123),
+            ijson!(null),
+            ijson!({"a": [1]}),
+        ] {
+            assert_eq!(cell_to_json(&json_to_cell(&value)), value);
+        }
+        assert_eq!(json_to_cell(&ijson!("foo")), "foo");
+        assert_eq!(json_to_cell(&ijson!("123")), "\"123\"");
+    }
+
+    #[test]
+    fn should_import_and_export_csv() {
+        let db = MemoryDatastore::new_db();
+        let t = Identifier::new("user").unwrap();
+        let outbound = Vertex::new(t);
+        let inbound = Vertex::new(t);
+        let vertices_csv = format!(
+            "type,id,name,age\nuser,{},alice,30\nuser,{},\"{{\"\"a\"\": 1}}\",\nuser,not-a-uuid,bob,\nuser,{}\n",
+            outbound.id, inbound.id, inbound.id
+        );
+        let edges_csv = format!(
+            "outbound_id,type,inbound_id,weight\n{},follows,{},0.5\n{},bad type,{},\n",
+            outbound.id, inbound.id, outbound.id, inbound.id
+        );
+
+        {
+            let mut txn = db.datastore.transaction();
+            let errors = import_vertices_csv(&mut txn, vertices_csv.as_bytes()).unwrap();
+            assert_eq!(errors.len(), 2);
+            assert_eq!(errors[0].line, 4);
+            assert_eq!(
+                errors[1],
+                CsvRowError {
+                    line: 5,
+                    message: "expected 4 columns, got 2".to_string()
+                }
+            );
+            let errors = import_edges_csv(&mut txn, edges_csv.as_bytes()).unwrap();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].line, 3);
+            txn.commit().unwrap();
+        }
+
+        let vertices = extract_vertices(db.get(AllVertexQuery).unwrap()).unwrap();
+        assert_eq!(vertices.len(), 2);
+        let edges = extract_edges(db.get(AllEdgeQuery).unwrap()).unwrap();
+        assert_eq!(
+            edges,
+            vec![Edge::new(outbound.id, Identifier::new("follows").unwrap(), inbound.id)]
+        );
+
+        // Exporting and re-importing should produce the same graph
+        let txn = db.datastore.readonly_transaction();
+        let mut exported_vertices = Vec::new();
+        export_vertices_csv(&txn, &mut exported_vertices).unwrap();
+        let mut exported_edges = Vec::new();
+        export_edges_csv(&txn, &mut exported_edges).unwrap();
+        drop(txn);
+
+        let other_db = MemoryDatastore::new_db();
+        {
+            let mut txn = other_db.datastore.transaction();
+            assert_eq!(
+                import_vertices_csv(&mut txn, exported_vertices.as_slice()).unwrap(),
+                vec![]
+            );
+            assert_eq!(import_edges_csv(&mut txn, exported_edges.as_slice()).unwrap(), vec![]);
+            txn.commit().unwrap();
+        }
+        let txn = other_db.datastore.readonly_transaction();
+        let mut reexported_vertices = Vec::new();
+        export_vertices_csv(&txn, &mut reexported_vertices).unwrap();
+        let mut reexported_edges = Vec::new();
+        export_edges_csv(&txn, &mut reexported_edges).unwrap();
+        assert_eq!(reexported_vertices, exported_vertices);
+        assert_eq!(reexported_edges, exported_edges);
+    }
+}
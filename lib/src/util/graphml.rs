@@ -0,0 +1,186 @@
+//! Exporting the graph as GraphML, e.g. for visualizing it in Gephi or yEd.
+
+use std::io::{Error as IoError, Write};
+
+use crate::errors::{Error, Result};
+use crate::{Identifier, Json, Transaction};
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">
+"#;
+
+// The keys for vertex and edge types. Property keys are numbered, so they
+// can't collide with these.
+const VERTEX_TYPE_KEY: &str = "vt";
+const EDGE_TYPE_KEY: &str = "et";
+
+fn io_error(err: IoError) -> Error {
+    Error::Datastore(Box::new(err))
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Strings are written as-is, and everything else as JSON.
+fn json_to_text(value: &Json) -> String {
+    match **value {
+        serde_json::Value::String(ref s) => s.clone(),
+        ref value => value.to_string(),
+    }
+}
+
+fn write_key<W: Write>(writer: &mut W, id: &str, target: &str, name: &str) -> Result<()> {
+    writeln!(
+        writer,
+        r#"  <key id="{}" for="{}" attr.name="{}" attr.type="string"/>"#,
+        id,
+        target,
+        escape(name)
+    )
+    .map_err(io_error)
+}
+
+fn write_data<W: Write>(writer: &mut W, key: &str, value: &str) -> Result<()> {
+    writeln!(writer, r#"      <data key="{}">{}</data>"#, key, escape(value)).map_err(io_error)
+}
+
+fn write_properties<W: Write>(
+    writer: &mut W,
+    prefix: char,
+    names: &[Identifier],
+    properties: Vec<(Identifier, Json)>,
+) -> Result<()> {
+    for (name, value) in properties {
+        // Properties may have been set since the names were read
+        if let Ok(i) = names.binary_search(&name) {
+            write_data(writer, &format!("{prefix}{i}"), &json_to_text(&value))?;
+        }
+    }
+    Ok(())
+}
+
+/// Exports the whole graph as a GraphML document. Vertices become nodes,
+/// and vertex and edge types and properties become `<data>` elements, with
+/// a `<key>` declared for each property name in use. Property values that
+/// aren't strings are written as JSON.
+///
+/// The document is written as vertices and edges are read, rather than
+/// buffering the whole graph.
+///
+/// # Arguments
+/// * `txn`: The transaction to export from.
+/// * `writer`: Where to write the document.
+pub fn export_graphml<'a, W: Write>(txn: &(dyn Transaction<'a> + 'a), mut writer: W) -> Result<()> {
+    let txn_ptr = txn as *const dyn Transaction<'a>;
+    let vertex_property_names = super::all_vertex_property_names(txn)?;
+    let edge_property_names = super::all_edge_property_names(txn)?;
+
+    writer.write_all(HEADER.as_bytes()).map_err(io_error)?;
+    write_key(&mut writer, VERTEX_TYPE_KEY, "node", "type")?;
+    for (i, name) in vertex_property_names.iter().enumerate() {
+        write_key(&mut writer, &format!("v{i}"), "node", name)?;
+    }
+    write_key(&mut writer, EDGE_TYPE_KEY, "edge", "type")?;
+    for (i, name) in edge_property_names.iter().enumerate() {
+        write_key(&mut writer, &format!("e{i}"), "edge", name)?;
+    }
+    writeln!(writer, r#"  <graph id="G" edgedefault="directed">"#).map_err(io_error)?;
+
+    for vertex in unsafe { (*txn_ptr).all_vertices()? } {
+        let vertex = vertex?;
+        writeln!(writer, r#"    <node id="{}">"#, vertex.id).map_err(io_error)?;
+        write_data(&mut writer, VERTEX_TYPE_KEY, &vertex.t)?;
+        let properties = unsafe { (*txn_ptr).all_vertex_properties_for_vertex(&vertex)? };
+        write_properties(
+            &mut writer,
+            'v',
+            &vertex_property_names,
+            properties.collect::<Result<Vec<(Identifier, Json)>>>()?,
+        )?;
+        writeln!(writer, "    </node>").map_err(io_error)?;
+    }
+
+    for edge in unsafe { (*txn_ptr).all_edges()? } {
+        let edge = edge?;
+        writeln!(
+            writer,
+            r#"    <edge source="{}" target="{}">"#,
+            edge.outbound_id, edge.inbound_id
+        )
+        .map_err(io_error)?;
+        write_data(&mut writer, EDGE_TYPE_KEY, &edge.t)?;
+        let properties = unsafe { (*txn_ptr).all_edge_properties_for_edge(&edge)? };
+        write_properties(
+            &mut writer,
+            'e',
+            &edge_property_names,
+            properties.collect::<Result<Vec<(Identifier, Json)>>>()?,
+        )?;
+        writeln!(writer, "    </edge>").map_err(io_error)?;
+    }
+
+    writeln!(writer, "  </graph>\n</graphml>").map_err(io_error)?;
+    writer.flush().map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, export_graphml};
+    use crate::{ijson, Datastore, Edge, Identifier, MemoryDatastore, SpecificEdgeQuery, SpecificVertexQuery};
+
+    #[test]
+    fn should_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn should_export_graphml() {
+        let db = MemoryDatastore::new_db();
+        let t = Identifier::new("user").unwrap();
+        let outbound_id = db.create_vertex_from_type(t).unwrap();
+        let inbound_id = db.create_vertex_from_type(t).unwrap();
+        let edge = Edge::new(outbound_id, Identifier::new("follows").unwrap(), inbound_id);
+        db.create_edge(&edge).unwrap();
+        let name = Identifier::new("name").unwrap();
+        db.set_properties(SpecificVertexQuery::single(outbound_id), name, &ijson!("<alice>"))
+            .unwrap();
+        db.set_properties(
+            SpecificEdgeQuery::single(edge),
+            Identifier::new("meta").unwrap(),
+            &ijson!({"since": 2020}),
+        )
+        .unwrap();
+
+        let txn = db.datastore.readonly_transaction();
+        let mut buf = Vec::new();
+        export_graphml(&txn, &mut buf).unwrap();
+        let document = String::from_utf8(buf).unwrap();
+
+        assert!(document.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(document.contains(r#"<key id="v0" for="node" attr.name="name" attr.type="string"/>"#));
+        assert!(document.contains(r#"<key id="e0" for="edge" attr.name="meta" attr.type="string"/>"#));
+        assert!(document.contains(&format!(r#"<node id="{outbound_id}">"#)));
+        assert!(document.contains(&format!(r#"<node id="{inbound_id}">"#)));
+        assert!(document.contains(r#"<data key="vt">user</data>"#));
+        assert!(document.contains(r#"<data key="v0">&lt;alice&gt;</data>"#));
+        assert!(document.contains(&format!(r#"<edge source="{outbound_id}" target="{inbound_id}">"#)));
+        assert!(document.contains(r#"<data key="et">follows</data>"#));
+        assert!(document.contains(r#"<data key="e0">{&quot;since&quot;:2020}</data>"#));
+        assert!(document.ends_with("</graph>\n</graphml>\n"));
+    }
+}
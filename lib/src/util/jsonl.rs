@@ -0,0 +1,237 @@
+//! Dumping and restoring the whole graph as JSON lines, e.g. for backups or
+//! for moving data between datastores.
+//!
+//! Each line is a JSON object with a `kind` of `vertex`, `edge`,
+//! `vertex_property` or `edge_property`. Vertices and their properties are
+//! written before edges, so that restoring creates vertices before the edges
+//! between them.
+
+use std::io::{BufRead, BufReader, Error as IoError, ErrorKind as IoErrorKind, Read, Write};
+
+use crate::errors::{Error, Result};
+use crate::{BulkInsertItem, Edge, Identifier, Json, Transaction, Vertex};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// How many records to insert at a time when restoring.
+const RESTORE_BATCH_SIZE: usize = 1000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Record {
+    Vertex {
+        id: Uuid,
+        t: Identifier,
+    },
+    Edge {
+        outbound_id: Uuid,
+        t: Identifier,
+        inbound_id: Uuid,
+    },
+    VertexProperty {
+        id: Uuid,
+        name: Identifier,
+        value: Json,
+    },
+    EdgeProperty {
+        outbound_id: Uuid,
+        t: Identifier,
+        inbound_id: Uuid,
+        name: Identifier,
+        value: Json,
+    },
+}
+
+impl From<Record> for BulkInsertItem {
+    fn from(record: Record) -> Self {
+        match record {
+            Record::Vertex { id, t } => BulkInsertItem::Vertex(Vertex::with_id(id, t)),
+            Record::Edge {
+                outbound_id,
+                t,
+                inbound_id,
+            } => BulkInsertItem::Edge(Edge::new(outbound_id, t, inbound_id)),
+            Record::VertexProperty { id, name, value } => BulkInsertItem::VertexProperty(id, name, value),
+            Record::EdgeProperty {
+                outbound_id,
+                t,
+                inbound_id,
+                name,
+                value,
+            } => BulkInsertItem::EdgeProperty(Edge::new(outbound_id, t, inbound_id), name, value),
+        }
+    }
+}
+
+fn io_error(err: IoError) -> Error {
+    Error::Datastore(Box::new(err))
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &Record) -> Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n").map_err(io_error)
+}
+
+/// Dumps the whole graph as JSON lines, with one line per vertex, edge and
+/// property. Lines are written as vertices and edges are read, rather than
+/// buffering the whole graph.
+///
+/// # Arguments
+/// * `txn`: The transaction to dump from.
+/// * `writer`: Where to write the dump.
+pub fn dump<'a, W: Write>(txn: &(dyn Transaction<'a> + 'a), mut writer: W) -> Result<()> {
+    let txn_ptr = txn as *const dyn Transaction<'a>;
+
+    for vertex in unsafe { (*txn_ptr).all_vertices()? } {
+        let vertex = vertex?;
+        write_record(
+            &mut writer,
+            &Record::Vertex {
+                id: vertex.id,
+                t: vertex.t,
+            },
+        )?;
+        for property in unsafe { (*txn_ptr).all_vertex_properties_for_vertex(&vertex)? } {
+            let (name, value) = property?;
+            write_record(
+                &mut writer,
+                &Record::VertexProperty {
+                    id: vertex.id,
+                    name,
+                    value,
+                },
+            )?;
+        }
+    }
+
+    for edge in unsafe { (*txn_ptr).all_edges()? } {
+        let edge = edge?;
+        write_record(
+            &mut writer,
+            &Record::Edge {
+                outbound_id: edge.outbound_id,
+                t: edge.t,
+                inbound_id: edge.inbound_id,
+            },
+        )?;
+        for property in unsafe { (*txn_ptr).all_edge_properties_for_edge(&edge)? } {
+            let (name, value) = property?;
+            write_record(
+                &mut writer,
+                &Record::EdgeProperty {
+                    outbound_id: edge.outbound_id,
+                    t: edge.t,
+                    inbound_id: edge.inbound_id,
+                    name,
+                    value,
+                },
+            )?;
+        }
+    }
+
+    writer.flush().map_err(io_error)
+}
+
+/// Restores a dump written by `dump`, via `bulk_insert`. Restoring
+/// overwrites vertices, edges and properties that already exist with the
+/// dumped ones, so re-running an interrupted restore is safe.
+///
+/// # Arguments
+/// * `txn`: The transaction to restore into.
+/// * `reader`: The dump to read.
+///
+/// # Errors
+/// Returns an error if a line is malformed, the dump can't be read, or
+/// inserting fails. Batches inserted before the error are still part of the
+/// transaction.
+pub fn restore<'a, R: Read>(txn: &mut (dyn Transaction<'a> + 'a), reader: R) -> Result<()> {
+    let mut batch = Vec::with_capacity(RESTORE_BATCH_SIZE);
+
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        // Skip blank lines
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record = serde_json::from_str(&line).map_err(|err| {
+            io_error(IoError::new(
+                IoErrorKind::InvalidData,
+                format!("invalid record on line {}: {err}", i + 1),
+            ))
+        })?;
+        batch.push(record.into());
+
+        if batch.len() >= RESTORE_BATCH_SIZE {
+            txn.bulk_insert(std::mem::take(&mut batch))?;
+        }
+    }
+
+    if !batch.is_empty() {
+        txn.bulk_insert(batch)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dump, restore};
+    use crate::{
+        ijson, Database, Datastore, Edge, Identifier, MemoryDatastore, SpecificEdgeQuery, SpecificVertexQuery,
+        Transaction,
+    };
+
+    fn dump_db(db: &Database<MemoryDatastore>) -> String {
+        let txn = db.datastore.readonly_transaction();
+        let mut buf = Vec::new();
+        dump(&txn, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn restore_db(db: &Database<MemoryDatastore>, dumped: &str) {
+        let mut txn = db.datastore.transaction();
+        restore(&mut txn, dumped.as_bytes()).unwrap();
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn should_dump_and_restore() {
+        let db = MemoryDatastore::new_db();
+        let t = Identifier::new("user").unwrap();
+        let outbound_id = db.create_vertex_from_type(t).unwrap();
+        let inbound_id = db.create_vertex_from_type(t).unwrap();
+        let edge = Edge::new(outbound_id, Identifier::new("follows").unwrap(), inbound_id);
+        db.create_edge(&edge).unwrap();
+        db.set_properties(
+            SpecificVertexQuery::single(outbound_id),
+            Identifier::new("name").unwrap(),
+            &ijson!("alice"),
+        )
+        .unwrap();
+        db.set_properties(
+            SpecificEdgeQuery::single(edge),
+            Identifier::new("meta").unwrap(),
+            &ijson!({"since": 2020}),
+        )
+        .unwrap();
+
+        let dumped = dump_db(&db);
+        assert_eq!(dumped.lines().count(), 5);
+        assert!(dumped.lines().all(|line| line.starts_with(r#"{"kind":"#)));
+
+        // Restoring twice is the same as restoring once
+        let other_db = MemoryDatastore::new_db();
+        restore_db(&other_db, &dumped);
+        restore_db(&other_db, &dumped);
+        assert_eq!(dump_db(&other_db), dumped);
+    }
+
+    #[test]
+    fn should_not_restore_malformed_dump() {
+        let db = MemoryDatastore::new_db();
+        let mut txn = db.datastore.transaction();
+        let err = restore(&mut txn, "\n{\"kind\": \"vertex\"}\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{ChangeEvent, ChangeFilter};
+
+/// The default number of events buffered for each watcher.
+pub const DEFAULT_WATCH_CAPACITY: usize = 1024;
+
+#[derive(Debug)]
+struct Subscriber {
+    filter: ChangeFilter,
+    sender: SyncSender<ChangeEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Fans change events out to watchers. Datastores that support watching
+/// hold one of these, and publish events to it as mutations are committed.
+///
+/// Delivery is best-effort: each watcher has a bounded buffer, and if a
+/// watcher falls far enough behind that its buffer is full, new events are
+/// dropped for it - rather than blocking writers - until it catches up.
+/// Watchers can check `Watcher::dropped_count` to find out whether they've
+/// missed anything.
+#[derive(Debug)]
+pub struct ChangeBroadcaster {
+    subscribers: Mutex<Vec<Subscriber>>,
+    capacity: usize,
+}
+
+impl ChangeBroadcaster {
+    /// Creates a new broadcaster.
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of events to buffer for each watcher.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+            capacity,
+        }
+    }
+
+    /// Creates a new watcher that receives events passing `filter`.
+    ///
+    /// # Arguments
+    /// * `filter`: Which events to receive.
+    pub fn subscribe(&self, filter: ChangeFilter) -> Watcher {
+        let (sender, receiver) = sync_channel(self.capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.subscribers.lock().unwrap().push(Subscriber {
+            filter,
+            sender,
+            dropped: dropped.clone(),
+        });
+        Watcher { receiver, dropped }
+    }
+
+    /// Returns whether there are any watchers. Datastores can use this to
+    /// avoid collecting events when nobody is listening.
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.lock().unwrap().is_empty()
+    }
+
+    /// Publishes events to all watchers whose filters they pass. This never
+    /// blocks on a slow watcher.
+    ///
+    /// # Arguments
+    /// * `events`: The events to publish, in the order they happened.
+    pub fn publish(&self, events: Vec<ChangeEvent>) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| {
+            for event in events.iter().filter(|event| subscriber.filter.matches(event)) {
+                match subscriber.sender.try_send(event.clone()) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        subscriber.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    // The watcher was dropped, so stop tracking it
+                    Err(TrySendError::Disconnected(_)) => return false,
+                }
+            }
+            true
+        });
+    }
+}
+
+impl Default for ChangeBroadcaster {
+    fn default() -> Self {
+        Self::new(DEFAULT_WATCH_CAPACITY)
+    }
+}
+
+/// Receives change events from a datastore. See `ChangeBroadcaster` for the
+/// delivery guarantees.
+///
+/// Iterating over a watcher blocks until the next event, and ends once the
+/// datastore is dropped.
+#[derive(Debug)]
+pub struct Watcher {
+    receiver: Receiver<ChangeEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Watcher {
+    /// Gets the next event, waiting for up to `timeout` for one.
+    ///
+    /// # Arguments
+    /// * `timeout`: How long to wait.
+    ///
+    /// # Errors
+    /// Returns `RecvTimeoutError::Timeout` if there were no events in time,
+    /// or `RecvTimeoutError::Disconnected` if the datastore was dropped.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<ChangeEvent, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Gets the next event if one is buffered, without waiting.
+    pub fn try_recv(&self) -> Option<ChangeEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Gets the number of events dropped so far because this watcher fell
+    /// behind.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Iterator for Watcher {
+    type Item = ChangeEvent;
+
+    fn next(&mut self) -> Option<ChangeEvent> {
+        self.receiver.recv().ok()
+    }
+}
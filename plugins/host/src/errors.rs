@@ -17,6 +17,8 @@ pub enum Error {
     InvalidArgument(String),
     /// Any other kind of error.
     Other(Box<dyn StdError + Send + Sync>),
+    /// A map operation was cancelled before it finished.
+    Cancelled,
 }
 
 impl StdError for Error {
@@ -37,6 +39,7 @@ impl fmt::Display for Error {
             Error::IndraDB(ref err) => write!(f, "IndraDB error: {err}"),
             Error::InvalidArgument(ref msg) => write!(f, "{msg}"),
             Error::Other(ref err) => write!(f, "{err}"),
+            Error::Cancelled => write!(f, "cancelled"),
         }
     }
 }
@@ -1,11 +1,18 @@
 use std::cmp::max;
+use std::collections::HashMap;
+use std::iter;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crate::errors::Error;
 
+use indradb::QueryExt;
 use threadpool::ThreadPool;
 
 const DEFAULT_NUM_THREADS: usize = 8;
+const PREFETCH_PAGE_SIZE: usize = 1000;
+const DEFAULT_QUERY_LIMIT: u32 = 1000;
 
 /// Trait for running an operation on all vertices in a datastore.
 pub trait VertexMapper: Send + Sync + 'static {
@@ -13,40 +20,331 @@ pub trait VertexMapper: Send + Sync + 'static {
     fn num_threads(&self) -> usize {
         DEFAULT_NUM_THREADS
     }
+    /// The names of vertex properties to fetch ahead of the map operation. If
+    /// non-empty, `map_with_properties` is called instead of `map`.
+    fn prefetch_properties(&self) -> Vec<indradb::Identifier> {
+        Vec::new()
+    }
+    /// A flag that cancels the map operation once set, e.g. when the user
+    /// hits Ctrl-C. Vertices that haven't been mapped yet are skipped, and
+    /// `map` returns `Error::Cancelled`. If `None`, the operation can't be
+    /// cancelled.
+    fn cancellation(&self) -> Option<Arc<AtomicBool>> {
+        None
+    }
+    /// Called with the number of vertices processed so far, each time one
+    /// has been mapped. This is called from the worker threads, so counts
+    /// may arrive slightly out of order. By default, this does nothing.
+    fn progress(&self, _processed: u64) {}
     /// The map operation.
     fn map(&self, vertex: indradb::Vertex) -> Result<(), Error>;
+    /// The map operation, for when properties are prefetched. Properties
+    /// listed in `prefetch_properties` that the vertex doesn't have are
+    /// absent from `properties`. By default, this ignores the properties and
+    /// calls `map`.
+    fn map_with_properties(
+        &self,
+        vertex: indradb::Vertex,
+        _properties: HashMap<indradb::Identifier, indradb::Json>,
+    ) -> Result<(), Error> {
+        self.map(vertex)
+    }
+}
+
+/// Trait for running an operation on all vertices in a datastore, and
+/// collecting its results - see `map_collect`.
+pub trait VertexCollector: Send + Sync + 'static {
+    /// The result of the map operation for each vertex.
+    type Output: Send + 'static;
+    /// The number of threads that should execute the map operation.
+    fn num_threads(&self) -> usize {
+        DEFAULT_NUM_THREADS
+    }
+    /// A flag that cancels the map operation once set. Vertices that haven't
+    /// been mapped yet are skipped, and `map_collect` returns
+    /// `Error::Cancelled`. If `None`, the operation can't be cancelled.
+    fn cancellation(&self) -> Option<Arc<AtomicBool>> {
+        None
+    }
+    /// Called with the number of vertices processed so far, each time one
+    /// has been mapped. This is called from the worker threads, so counts
+    /// may arrive slightly out of order. By default, this does nothing.
+    fn progress(&self, _processed: u64) {}
+    /// The map operation.
+    fn map(&self, vertex: indradb::Vertex) -> Result<Self::Output, Error>;
+}
+
+/// Trait for running an operation on all edges in a datastore.
+pub trait EdgeMapper: Send + Sync + 'static {
+    /// The number of threads that should execute the map operation.
+    fn num_threads(&self) -> usize {
+        DEFAULT_NUM_THREADS
+    }
+    /// The edge types to run the map operation on. If empty, it's run on
+    /// edges of any type.
+    fn t_filter(&self) -> Vec<indradb::Identifier> {
+        Vec::new()
+    }
+    /// The number of edges to read at a time.
+    fn query_limit(&self) -> u32 {
+        DEFAULT_QUERY_LIMIT
+    }
+    /// A flag that cancels the map operation once set. Edges that haven't
+    /// been mapped yet are skipped, and `map_edges` returns
+    /// `Error::Cancelled`. If `None`, the operation can't be cancelled.
+    fn cancellation(&self) -> Option<Arc<AtomicBool>> {
+        None
+    }
+    /// Called with the number of edges processed so far, each time one has
+    /// been mapped. This is called from the worker threads, so counts may
+    /// arrive slightly out of order. By default, this does nothing.
+    fn progress(&self, _processed: u64) {}
+    /// The map operation.
+    fn map(&self, edge: indradb::Edge) -> Result<(), Error>;
 }
 
 /// Runs an operation on all vertices in the datastore.
 ///
 /// # Arguments
+/// * `txn`: The transaction to read vertices from.
 /// * `mapper`: Specified options and the map operation to run.
-/// * `database`: The database.
+///
+/// # Errors
+/// Returns `Error::Cancelled` if the mapper's cancellation flag was set
+/// before all vertices were mapped.
 pub fn map<'a, M: VertexMapper>(txn: &(dyn indradb::Transaction<'a> + 'a), mapper: Arc<M>) -> Result<(), Error> {
-    let first_err: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
-    let pool = ThreadPool::new(max(mapper.num_threads(), 1));
     let txn_ptr = txn as *const dyn indradb::Transaction<'a>;
+    let vertices = unsafe { (*txn_ptr).all_vertices()? };
+    let num_threads = mapper.num_threads();
+    let prefetch_properties = mapper.prefetch_properties();
+    let cancellation = mapper.cancellation();
+
+    if prefetch_properties.is_empty() {
+        let progress_mapper = mapper.clone();
+        return dispatch(
+            vertices,
+            num_threads,
+            cancellation,
+            move |vertex| mapper.map(vertex),
+            move |processed| progress_mapper.progress(processed),
+        );
+    }
+
+    // Properties are fetched on this thread, since the transaction can't be
+    // shared with the workers. Vertices are read a page at a time, so each
+    // property only takes one query per page rather than one per vertex.
+    let mut vertices = vertices;
+    let items = iter::from_fn(move || {
+        let mut page = Vec::with_capacity(PREFETCH_PAGE_SIZE);
+        for vertex in vertices.by_ref().take(PREFETCH_PAGE_SIZE) {
+            match vertex {
+                Ok(vertex) => page.push(vertex),
+                Err(err) => return Some(vec![Err(err)]),
+            }
+        }
+        if page.is_empty() {
+            return None;
+        }
+        match unsafe { prefetch_page(&*txn_ptr, page, &prefetch_properties) } {
+            Ok(items) => Some(items.into_iter().map(Ok).collect()),
+            Err(err) => Some(vec![Err(err)]),
+        }
+    })
+    .flatten();
+    let progress_mapper = mapper.clone();
+    dispatch(
+        items,
+        num_threads,
+        cancellation,
+        move |(vertex, properties)| mapper.map_with_properties(vertex, properties),
+        move |processed| progress_mapper.progress(processed),
+    )
+}
+
+/// Fetches the given properties for a page of vertices, with one query per
+/// property.
+fn prefetch_page<'a>(
+    txn: &'a (dyn indradb::Transaction<'a> + 'a),
+    page: Vec<indradb::Vertex>,
+    names: &[indradb::Identifier],
+) -> indradb::Result<Vec<(indradb::Vertex, HashMap<indradb::Identifier, indradb::Json>)>> {
+    let ids: Vec<_> = page.iter().map(|vertex| vertex.id).collect();
+    let mut page_properties: HashMap<_, HashMap<_, _>> = HashMap::with_capacity(page.len());
+    for name in names {
+        let q = indradb::SpecificVertexQuery::new(ids.clone()).properties()?.name(*name);
+        let output = txn.get(q.into())?;
+        for vertex_properties in indradb::util::extract_vertex_properties(output).unwrap_or_default() {
+            let properties = page_properties.entry(vertex_properties.vertex.id).or_default();
+            for property in vertex_properties.props {
+                properties.insert(property.name, property.value);
+            }
+        }
+    }
+    Ok(page
+        .into_iter()
+        .map(|vertex| {
+            let properties = page_properties.remove(&vertex.id).unwrap_or_default();
+            (vertex, properties)
+        })
+        .collect())
+}
+
+/// Runs an operation on all vertices in the datastore, and collects its
+/// results. Vertices are mapped in parallel, but the results are returned in
+/// the order the vertices were scanned in, so they can be correlated with
+/// the vertices without querying them again.
+///
+/// # Arguments
+/// * `txn`: The transaction to read vertices from.
+/// * `collector`: Specified options and the map operation to run.
+///
+/// # Errors
+/// Returns the first error returned by the map operation, or
+/// `Error::Cancelled` if the collector's cancellation flag was set before
+/// all vertices were mapped.
+pub fn map_collect<'a, C: VertexCollector>(
+    txn: &(dyn indradb::Transaction<'a> + 'a),
+    collector: Arc<C>,
+) -> Result<Vec<C::Output>, Error> {
+    let txn_ptr = txn as *const dyn indradb::Transaction<'a>;
+    let vertices = unsafe { (*txn_ptr).all_vertices()? };
+    let items = vertices
+        .enumerate()
+        .map(|(index, vertex)| vertex.map(|vertex| (index, vertex)));
+    let num_threads = collector.num_threads();
+    let cancellation = collector.cancellation();
+
+    // Workers finish in any order, so results are tagged with the index of
+    // their vertex and sorted afterwards
+    let results = Arc::new(Mutex::new(Vec::<(usize, C::Output)>::new()));
+    let worker_results = results.clone();
+    let progress_collector = collector.clone();
+    dispatch(
+        items,
+        num_threads,
+        cancellation,
+        move |(index, vertex)| {
+            let output = collector.map(vertex)?;
+            worker_results.lock().unwrap().push((index, output));
+            Ok(())
+        },
+        move |processed| progress_collector.progress(processed),
+    )?;
+
+    let mut results = mem::take(&mut *results.lock().unwrap());
+    results.sort_unstable_by_key(|(index, _)| *index);
+    Ok(results.into_iter().map(|(_, output)| output).collect())
+}
+
+/// Runs an operation on all edges in the datastore. Edges are read a page
+/// at a time, with `query_limit` edges per page.
+///
+/// # Arguments
+/// * `txn`: The transaction to read edges from.
+/// * `mapper`: Specified options and the map operation to run.
+///
+/// # Errors
+/// Returns `Error::Cancelled` if the mapper's cancellation flag was set
+/// before all edges were mapped.
+pub fn map_edges<'a, M: EdgeMapper>(txn: &(dyn indradb::Transaction<'a> + 'a), mapper: Arc<M>) -> Result<(), Error> {
+    let txn_ptr = txn as *const dyn indradb::Transaction<'a>;
+    let query_limit = max(mapper.query_limit(), 1);
+    let mut q = indradb::RangeEdgeQuery::new().limit(query_limit);
+    q.t_filter = mapper.t_filter();
+    let mut done = false;
+    let edges = iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        match unsafe { (*txn_ptr).get(q.clone().into()) } {
+            Ok(output) => {
+                let page = indradb::util::extract_edges(output).unwrap_or_default();
+                done = page.len() < query_limit as usize;
+                if let Some(last) = page.last() {
+                    q = q.clone().after(last.clone());
+                }
+                Some(page.into_iter().map(Ok).collect::<Vec<_>>())
+            }
+            Err(err) => {
+                done = true;
+                Some(vec![Err(err)])
+            }
+        }
+    })
+    .flatten();
+    let num_threads = mapper.num_threads();
+    let cancellation = mapper.cancellation();
+    let progress_mapper = mapper.clone();
+    dispatch(
+        edges,
+        num_threads,
+        cancellation,
+        move |edge| mapper.map(edge),
+        move |processed| progress_mapper.progress(processed),
+    )
+}
+
+fn is_cancelled(cancellation: &Option<Arc<AtomicBool>>) -> bool {
+    cancellation
+        .as_ref()
+        .is_some_and(|cancellation| cancellation.load(Ordering::Relaxed))
+}
+
+/// Runs `f` on each item across a thread pool, stopping early and returning
+/// the first error encountered. `progress` is called with the number of
+/// items processed so far after each one. If `cancellation` is set, the
+/// remaining items are skipped, the in-flight ones are waited on, and
+/// `Error::Cancelled` is returned.
+fn dispatch<T, I, F, P>(
+    items: I,
+    num_threads: usize,
+    cancellation: Option<Arc<AtomicBool>>,
+    f: F,
+    progress: P,
+) -> Result<(), Error>
+where
+    T: Send + 'static,
+    I: Iterator<Item = indradb::Result<T>>,
+    F: Fn(T) -> Result<(), Error> + Send + Sync + 'static,
+    P: Fn(u64) + Send + Sync + 'static,
+{
+    let first_err: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
+    let pool = ThreadPool::new(max(num_threads, 1));
+    let f = Arc::new(f);
+    let progress = Arc::new(progress);
+    let processed = Arc::new(AtomicU64::new(0));
     let mut i = 0;
 
-    for vertex in unsafe { (*txn_ptr).all_vertices()? } {
+    for item in items {
+        if is_cancelled(&cancellation) {
+            break;
+        }
         i += 1;
         if i % 1000 == 0 && first_err.lock().unwrap().is_some() {
             // Break on error, but also only check every once in a while since
             // the error is behind a mutex.
             break;
         }
-        match vertex {
-            Ok(vertex) => {
-                let vertex: indradb::Vertex = vertex;
-                let mapper = mapper.clone();
+        match item {
+            Ok(item) => {
+                let f = f.clone();
+                let progress = progress.clone();
+                let processed = processed.clone();
+                let cancellation = cancellation.clone();
                 let first_err = first_err.clone();
                 pool.execute(move || {
-                    if let Err(err) = mapper.map(vertex) {
+                    // Items may already be queued when the map is cancelled,
+                    // so skip them rather than waiting for them to run
+                    if is_cancelled(&cancellation) {
+                        return;
+                    }
+                    if let Err(err) = f(item) {
                         let mut first_err = first_err.lock().unwrap();
                         if first_err.is_none() {
                             *first_err = Some(err);
                         }
                     }
+                    progress(processed.fetch_add(1, Ordering::Relaxed) + 1);
                 });
             }
             Err(err) => {
@@ -60,6 +358,10 @@ pub fn map<'a, M: VertexMapper>(txn: &(dyn indradb::Transaction<'a> + 'a), mappe
 
     pool.join();
 
+    if is_cancelled(&cancellation) {
+        return Err(Error::Cancelled);
+    }
+
     let mut first_err = first_err.lock().unwrap();
     if first_err.is_some() {
         Err(first_err.take().unwrap())
@@ -67,3 +369,254 @@ pub fn map<'a, M: VertexMapper>(txn: &(dyn indradb::Transaction<'a> + 'a), mappe
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{dispatch, map, map_collect, map_edges, EdgeMapper, VertexCollector, VertexMapper};
+    use crate::errors::Error;
+    use indradb::{ijson, Database, Datastore, Edge, Identifier, Json, MemoryDatastore, SpecificVertexQuery, Vertex};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    fn create_vertices(db: &Database<MemoryDatastore>, count: usize) -> Vec<Uuid> {
+        (0..count)
+            .map(|_| db.create_vertex_from_type(Identifier::new("test").unwrap()).unwrap())
+            .collect()
+    }
+
+    struct IdCollector;
+
+    impl VertexCollector for IdCollector {
+        type Output = Uuid;
+
+        fn num_threads(&self) -> usize {
+            4
+        }
+
+        fn map(&self, vertex: Vertex) -> Result<Uuid, Error> {
+            Ok(vertex.id)
+        }
+    }
+
+    struct FailingCollector;
+
+    impl VertexCollector for FailingCollector {
+        type Output = ();
+
+        fn map(&self, _vertex: Vertex) -> Result<(), Error> {
+            Err(Error::InvalidArgument("failed".to_string()))
+        }
+    }
+
+    #[test]
+    fn should_collect_in_scan_order() {
+        let db = MemoryDatastore::new_db();
+        // Vertices are scanned in id order
+        let mut ids = create_vertices(&db, 2500);
+        ids.sort();
+        let txn = db.datastore.readonly_transaction();
+        assert_eq!(map_collect(&txn, Arc::new(IdCollector)).unwrap(), ids);
+    }
+
+    #[test]
+    fn should_fail_collect_when_map_fails() {
+        let db = MemoryDatastore::new_db();
+        create_vertices(&db, 10);
+        let txn = db.datastore.readonly_transaction();
+        assert!(matches!(
+            map_collect(&txn, Arc::new(FailingCollector)),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn should_return_first_error() {
+        // With one thread, items are mapped in order
+        let result = dispatch(
+            (0..10).map(Ok),
+            1,
+            None,
+            |item: u64| {
+                if item >= 3 {
+                    Err(Error::InvalidArgument(item.to_string()))
+                } else {
+                    Ok(())
+                }
+            },
+            |_| {},
+        );
+        assert!(matches!(result, Err(Error::InvalidArgument(ref msg)) if msg == "3"));
+
+        // Errors from the iterator count too
+        let items = vec![Ok(0), Err(indradb::Error::Unsupported), Ok(2)];
+        let result = dispatch(items.into_iter(), 1, None, |_: u64| Ok(()), |_| {});
+        assert!(matches!(result, Err(Error::IndraDB(indradb::Error::Unsupported))));
+    }
+
+    #[test]
+    fn should_drain_queued_items_when_cancelled() {
+        let cancellation = Arc::new(AtomicBool::new(false));
+        let worker_cancellation = cancellation.clone();
+        let calls = Arc::new(AtomicU64::new(0));
+        let worker_calls = calls.clone();
+        let progress_calls = Arc::new(AtomicU64::new(0));
+        let worker_progress_calls = progress_calls.clone();
+
+        // The first item cancels the map, so everything queued behind it on
+        // the only worker is skipped
+        let result = dispatch(
+            (0..1000).map(Ok),
+            1,
+            Some(cancellation),
+            move |_: u64| {
+                worker_calls.fetch_add(1, Ordering::SeqCst);
+                worker_cancellation.store(true, Ordering::SeqCst);
+                Ok(())
+            },
+            move |_| {
+                worker_progress_calls.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(progress_calls.load(Ordering::SeqCst), 1);
+    }
+
+    struct CancelledMapper {
+        calls: AtomicU64,
+    }
+
+    impl VertexMapper for CancelledMapper {
+        fn cancellation(&self) -> Option<Arc<AtomicBool>> {
+            Some(Arc::new(AtomicBool::new(true)))
+        }
+
+        fn map(&self, _vertex: Vertex) -> Result<(), Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_not_map_when_already_cancelled() {
+        let db = MemoryDatastore::new_db();
+        create_vertices(&db, 10);
+        let txn = db.datastore.readonly_transaction();
+        let mapper = Arc::new(CancelledMapper {
+            calls: AtomicU64::new(0),
+        });
+        assert!(matches!(map(&txn, mapper.clone()), Err(Error::Cancelled)));
+        assert_eq!(mapper.calls.load(Ordering::SeqCst), 0);
+    }
+
+    struct PropertyMapper {
+        name: Identifier,
+        seen: Mutex<HashMap<Uuid, Option<Json>>>,
+    }
+
+    impl VertexMapper for PropertyMapper {
+        fn prefetch_properties(&self) -> Vec<Identifier> {
+            vec![self.name]
+        }
+
+        fn map(&self, _vertex: Vertex) -> Result<(), Error> {
+            unreachable!()
+        }
+
+        fn map_with_properties(&self, vertex: Vertex, properties: HashMap<Identifier, Json>) -> Result<(), Error> {
+            let value = properties.get(&self.name).cloned();
+            self.seen.lock().unwrap().insert(vertex.id, value);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_map_with_prefetched_properties() {
+        let db = MemoryDatastore::new_db();
+        let name = Identifier::new("value").unwrap();
+        // Enough vertices to span several prefetch pages
+        let ids = create_vertices(&db, 2500);
+        for (i, id) in ids.iter().enumerate().filter(|(i, _)| i % 2 == 0) {
+            db.set_properties(SpecificVertexQuery::single(*id), name, &ijson!(i))
+                .unwrap();
+        }
+
+        let txn = db.datastore.readonly_transaction();
+        let mapper = Arc::new(PropertyMapper {
+            name,
+            seen: Mutex::new(HashMap::new()),
+        });
+        map(&txn, mapper.clone()).unwrap();
+        let seen = mapper.seen.lock().unwrap();
+        assert_eq!(seen.len(), ids.len());
+        for (i, id) in ids.iter().enumerate() {
+            let expected = if i % 2 == 0 { Some(ijson!(i)) } else { None };
+            assert_eq!(seen[id], expected);
+        }
+    }
+
+    struct EdgeRecorder {
+        t_filter: Vec<Identifier>,
+        query_limit: u32,
+        edges: Mutex<Vec<Edge>>,
+    }
+
+    impl EdgeMapper for EdgeRecorder {
+        fn t_filter(&self) -> Vec<Identifier> {
+            self.t_filter.clone()
+        }
+
+        fn query_limit(&self) -> u32 {
+            self.query_limit
+        }
+
+        fn map(&self, edge: Edge) -> Result<(), Error> {
+            self.edges.lock().unwrap().push(edge);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_map_edges() {
+        let db = MemoryDatastore::new_db();
+        let ids = create_vertices(&db, 2);
+        let t1 = Identifier::new("t1").unwrap();
+        let t2 = Identifier::new("t2").unwrap();
+        let edges = vec![
+            Edge::new(ids[0], t1, ids[1]),
+            Edge::new(ids[1], t1, ids[0]),
+            Edge::new(ids[0], t2, ids[1]),
+        ];
+        for edge in &edges {
+            db.create_edge(edge).unwrap();
+        }
+        let txn = db.datastore.readonly_transaction();
+
+        let mapper = Arc::new(EdgeRecorder {
+            t_filter: Vec::new(),
+            query_limit: 2,
+            edges: Mutex::new(Vec::new()),
+        });
+        map_edges(&txn, mapper.clone()).unwrap();
+        let mut mapped = mapper.edges.lock().unwrap().clone();
+        mapped.sort();
+        let mut expected = edges.clone();
+        expected.sort();
+        assert_eq!(mapped, expected);
+
+        // Only edges of the filtered types are mapped
+        let mapper = Arc::new(EdgeRecorder {
+            t_filter: vec![t1],
+            query_limit: 2,
+            edges: Mutex::new(Vec::new()),
+        });
+        map_edges(&txn, mapper.clone()).unwrap();
+        let mut mapped = mapper.edges.lock().unwrap().clone();
+        mapped.sort();
+        let mut expected = edges[..2].to_vec();
+        expected.sort();
+        assert_eq!(mapped, expected);
+    }
+}
@@ -1,36 +1,71 @@
+use std::cmp::{max, min};
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeSet;
 use std::convert::TryInto;
 use std::error::Error as StdError;
 use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 use crate::ConversionError;
 
-use tokio::sync::mpsc;
+use indradb::QueryExt;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
-use tokio_stream::StreamExt;
+use tokio_stream::{Stream, StreamExt};
+#[cfg(feature = "compression")]
+use tonic::codec::CompressionEncoding;
+use tonic::metadata::{Ascii, MetadataValue};
+#[cfg(feature = "tls")]
+use tonic::transport::ClientTlsConfig;
 use tonic::transport::{Channel, Endpoint, Error as TonicTransportError};
-use tonic::{Request, Status};
+use tonic::{Code, Request, Status};
 use uuid::Uuid;
 
 const CHANNEL_CAPACITY: usize = 100;
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// The error returned if a client operation failed.
 #[derive(Debug)]
 pub enum ClientError {
     /// Conversion between an IndraDB and its protobuf equivalent failed.
     Conversion { inner: ConversionError },
+    /// An error from IndraDB on the server, e.g. `Error::NotIndexed`.
+    /// Errors that wrap other errors, e.g. from the underlying datastore,
+    /// are returned as `Grpc` errors with a message instead.
+    Indradb { inner: indradb::Error },
     /// A gRPC error.
     Grpc { inner: Status },
     /// A transport error.
     Transport { inner: TonicTransportError },
     /// The gRPC channel has been closed.
     ChannelClosed,
+    /// The request didn't complete within the client's timeout.
+    Timeout,
+    /// The auth token contains characters that can't be sent in a header.
+    InvalidAuthToken,
+    /// A pagination cursor came from a different query.
+    InvalidCursor,
+    /// A query returned a different kind of value than expected, e.g. edges
+    /// from `Client::get_vertices`.
+    UnexpectedOutput,
+    /// The server truncated the output of a query to fit its response size
+    /// limit. Large outputs can be paged through instead, e.g. with
+    /// `Client::get_vertex_properties_page`.
+    Truncated,
+    /// The client has been shut down, so it doesn't accept new requests.
+    ShutDown,
 }
 
 impl StdError for ClientError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
             ClientError::Conversion { ref inner } => Some(inner),
+            ClientError::Indradb { ref inner } => Some(inner),
             ClientError::Grpc { ref inner } => Some(inner),
             ClientError::Transport { ref inner } => Some(inner),
             _ => None,
@@ -42,9 +77,16 @@ impl fmt::Display for ClientError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             ClientError::Conversion { ref inner } => inner.fmt(f),
+            ClientError::Indradb { ref inner } => inner.fmt(f),
             ClientError::Grpc { ref inner } => write!(f, "grpc error: {inner}"),
             ClientError::Transport { ref inner } => write!(f, "transport error: {inner}"),
             ClientError::ChannelClosed => write!(f, "failed to send request: channel closed"),
+            ClientError::Timeout => write!(f, "request timed out"),
+            ClientError::InvalidAuthToken => write!(f, "invalid auth token"),
+            ClientError::InvalidCursor => write!(f, "invalid cursor"),
+            ClientError::UnexpectedOutput => write!(f, "unexpected query output"),
+            ClientError::Truncated => write!(f, "query output truncated to fit the server's response size limit"),
+            ClientError::ShutDown => write!(f, "client shut down"),
         }
     }
 }
@@ -57,7 +99,15 @@ impl From<ConversionError> for ClientError {
 
 impl From<Status> for ClientError {
     fn from(err: Status) -> Self {
-        ClientError::Grpc { inner: err }
+        let indradb_err = err
+            .metadata()
+            .get(crate::ERROR_KIND_METADATA_KEY)
+            .and_then(|kind| kind.to_str().ok())
+            .and_then(crate::error_from_kind);
+        match indradb_err {
+            Some(inner) => ClientError::Indradb { inner },
+            None => ClientError::Grpc { inner: err },
+        }
     }
 }
 
@@ -73,6 +123,92 @@ impl<T> From<mpsc::error::SendError<T>> for ClientError {
     }
 }
 
+impl ClientError {
+    /// Whether the error is likely transient, such that the request could
+    /// succeed if retried.
+    pub fn is_retryable(&self) -> bool {
+        match *self {
+            ClientError::Transport { .. } => true,
+            ClientError::Grpc { ref inner } => inner.code() == Code::Unavailable,
+            _ => false,
+        }
+    }
+}
+
+/// Configures how a client retries requests that fail with a retryable
+/// error. Backoff between attempts is exponential, with jitter.
+#[derive(Clone, Debug)]
+pub struct RetryConfig {
+    /// The maximum number of attempts to make, including the first one.
+    pub max_attempts: u32,
+    /// The backoff before the first retry.
+    pub initial_backoff: Duration,
+    /// The maximum backoff between retries.
+    pub max_backoff: Duration,
+    /// Whether to also retry requests that aren't idempotent, e.g. creating
+    /// a vertex. A retried request may end up being applied more than once.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+// Picks a random duration between half of `backoff` and all of it, so that
+// clients retrying at the same time spread out.
+fn jitter(backoff: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let half = backoff / 2;
+    half + Duration::from_nanos(random % (half.as_nanos() as u64 + 1))
+}
+
+// Tracks the requests of a client and its clones, so that shutting down can
+// wait for them.
+#[derive(Default)]
+struct Lifecycle {
+    shut_down: AtomicBool,
+    stopping: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+impl Lifecycle {
+    // Counts a request as in flight until the returned guard is dropped, or
+    // fails if the client has been shut down.
+    fn start(&self) -> Result<InFlight<'_>, ClientError> {
+        // Increment before checking, so that `shutdown` either sees this
+        // request or this request sees the shutdown
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(self);
+        if self.shut_down.load(Ordering::SeqCst) {
+            Err(ClientError::ShutDown)
+        } else {
+            Ok(guard)
+        }
+    }
+
+    fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+}
+
+struct InFlight<'a>(&'a Lifecycle);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_one();
+        }
+    }
+}
+
 /// A higher-level client implementation.
 ///
 /// This should be better suited than the low-level client auto-generated by
@@ -80,7 +216,15 @@ impl<T> From<mpsc::error::SendError<T>> for ClientError {
 /// translating between protobuf types and their IndraDB equivalents. The
 /// interface is designed to resemble `indradb::Database`, but async.
 #[derive(Clone)]
-pub struct Client(crate::ProtoClient<Channel>);
+pub struct Client {
+    inner: crate::ProtoClient<Channel>,
+    retry: Option<RetryConfig>,
+    timeout: Option<Duration>,
+    authorization: Option<MetadataValue<Ascii>>,
+    bulk_insert_capacity: usize,
+    shutdown_timeout: Duration,
+    lifecycle: Arc<Lifecycle>,
+}
 
 impl Client {
     /// Creates a new client.
@@ -88,21 +232,274 @@ impl Client {
     /// # Arguments
     /// * `endpoint`: The server endpoint.
     pub async fn new(endpoint: Endpoint) -> Result<Self, ClientError> {
-        let client = crate::ProtoClient::connect(endpoint).await?;
-        Ok(Client(client))
+        let inner = crate::ProtoClient::connect(endpoint).await?;
+        Ok(Client {
+            inner,
+            retry: None,
+            timeout: None,
+            authorization: None,
+            bulk_insert_capacity: CHANNEL_CAPACITY,
+            shutdown_timeout: SHUTDOWN_TIMEOUT,
+            lifecycle: Arc::new(Lifecycle::default()),
+        })
+    }
+
+    /// Creates a new client that connects over TLS. Certificate problems,
+    /// e.g. an untrusted server certificate, are returned as
+    /// `ClientError::Transport`, with the underlying cause available via
+    /// `source()`.
+    ///
+    /// The server needs to be hosted with TLS enabled for this to work. The
+    /// standalone server doesn't support TLS, but `Server` can be hosted with
+    /// tonic directly:
+    ///
+    /// ```ignore
+    /// let identity = Identity::from_pem(server_cert_pem, server_key_pem);
+    /// let mut tls = ServerTlsConfig::new().identity(identity);
+    /// // Only required for mTLS
+    /// tls = tls.client_ca_root(Certificate::from_pem(client_ca_pem));
+    /// tonic::transport::Server::builder()
+    ///     .tls_config(tls)?
+    ///     .add_service(indradb_proto::indra_db_server::IndraDbServer::new(server))
+    ///     .serve(addr)
+    ///     .await?;
+    /// ```
+    ///
+    /// # Arguments
+    /// * `endpoint`: The server endpoint.
+    /// * `tls`: The TLS config. This should include the CA certificate to
+    ///   verify the server with, and for mTLS, the client's certificate and
+    ///   key as an identity. The domain name can be overridden if it
+    ///   differs from the endpoint's host.
+    #[cfg(feature = "tls")]
+    pub async fn new_tls(endpoint: Endpoint, tls: ClientTlsConfig) -> Result<Self, ClientError> {
+        Self::new(endpoint.tls_config(tls)?).await
+    }
+
+    /// Creates a new client that compresses requests, and accepts compressed
+    /// responses. See `Client::set_compression`.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The server endpoint.
+    /// * `compression`: The compression to use.
+    #[cfg(feature = "compression")]
+    pub async fn with_compression(endpoint: Endpoint, compression: CompressionEncoding) -> Result<Self, ClientError> {
+        let mut client = Self::new(endpoint).await?;
+        client.set_compression(compression);
+        Ok(client)
+    }
+
+    /// Creates a new client that retries requests which fail with a
+    /// retryable error. Only idempotent requests are retried, unless
+    /// `retry_non_idempotent` is set in the config. Bulk inserts are never
+    /// retried. Connecting is retried as well.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The server endpoint.
+    /// * `config`: How to retry requests.
+    pub async fn with_retry(endpoint: Endpoint, config: RetryConfig) -> Result<Self, ClientError> {
+        let inner = retry(&config, || crate::ProtoClient::connect(endpoint.clone())).await?;
+        Ok(Client {
+            inner,
+            retry: Some(config),
+            timeout: None,
+            authorization: None,
+            bulk_insert_capacity: CHANNEL_CAPACITY,
+            shutdown_timeout: SHUTDOWN_TIMEOUT,
+            lifecycle: Arc::new(Lifecycle::default()),
+        })
+    }
+
+    /// Sets how long each request may take before failing with
+    /// `ClientError::Timeout`. For requests that stream, this bounds the
+    /// whole request rather than each message. If retries are enabled, each
+    /// attempt gets the full timeout. If `None`, requests don't time out.
+    ///
+    /// # Arguments
+    /// * `timeout`: The request timeout.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Sets the compression to compress requests with, and to accept for
+    /// responses. The server only compresses responses that are large
+    /// enough to benefit, e.g. the output of a `get` of many vertices; see
+    /// `Server::set_compression_min_bytes`.
+    ///
+    /// # Arguments
+    /// * `compression`: The compression to use.
+    #[cfg(feature = "compression")]
+    pub fn set_compression(&mut self, compression: CompressionEncoding) {
+        self.inner = self
+            .inner
+            .clone()
+            .send_compressed(compression)
+            .accept_compressed(compression);
+    }
+
+    /// Sets how many items bulk inserts buffer while they're waiting to be
+    /// streamed to the server. A larger buffer stalls the producer less
+    /// often on high-throughput loads, at the cost of memory. Defaults to
+    /// 100; a capacity of 0 is treated as 1.
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of items to buffer.
+    pub fn set_bulk_insert_capacity(&mut self, capacity: usize) {
+        self.bulk_insert_capacity = max(capacity, 1);
+    }
+
+    /// Sets how long `shutdown` waits for requests in flight to finish.
+    /// Defaults to 30 seconds.
+    ///
+    /// # Arguments
+    /// * `timeout`: The shutdown timeout.
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
+    }
+
+    /// Sets a bearer token to send in the `authorization` header of every
+    /// request. This can be called at any time to rotate the token; requests
+    /// made afterwards use the new one. If `None`, no header is sent.
+    ///
+    /// # Arguments
+    /// * `token`: The bearer token.
+    ///
+    /// # Errors
+    /// Returns `ClientError::InvalidAuthToken` if the token can't be sent in
+    /// a header, e.g. because it contains non-ASCII characters.
+    pub fn set_auth_token(&mut self, token: Option<String>) -> Result<(), ClientError> {
+        self.authorization = match token {
+            // Header values may also hold other bytes, but servers can't read
+            // them as text
+            Some(token) if !token.is_ascii() => return Err(ClientError::InvalidAuthToken),
+            Some(token) => {
+                let value = format!("Bearer {token}")
+                    .parse()
+                    .map_err(|_| ClientError::InvalidAuthToken)?;
+                Some(value)
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    // Wraps a message in a request, attaching any auth metadata.
+    fn new_request<T>(&self, message: T) -> Request<T> {
+        let mut req = Request::new(message);
+        if let Some(ref authorization) = self.authorization {
+            req.metadata_mut().insert("authorization", authorization.clone());
+        }
+        req
+    }
+
+    // Runs a request, retrying it if configured to do so.
+    async fn request<T, F, Fut>(&self, idempotent: bool, mut f: F) -> Result<T, ClientError>
+    where
+        F: FnMut(crate::ProtoClient<Channel>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let _in_flight = self.lifecycle.start()?;
+        match self.retry {
+            Some(ref config) if idempotent || config.retry_non_idempotent => {
+                retry(config, || with_timeout(self.timeout, f(self.inner.clone()))).await
+            }
+            _ => with_timeout(self.timeout, f(self.inner.clone())).await,
+        }
+    }
+
+    // Streams items to the server from a separate task. The task stops
+    // early if the client shuts down before the items are all sent. Errors
+    // are stored in `last_err`.
+    fn spawn_producer<I, T>(
+        &self,
+        items: Vec<I>,
+        capacity: usize,
+        last_err: Arc<Mutex<Option<ClientError>>>,
+    ) -> (ReceiverStream<T>, JoinHandle<()>)
+    where
+        I: Into<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(capacity);
+        let lifecycle = self.lifecycle.clone();
+        let producer = tokio::spawn(async move {
+            for item in items.into_iter() {
+                if lifecycle.is_stopping() {
+                    *last_err.lock().unwrap() = Some(ClientError::ShutDown);
+                    return;
+                }
+                if let Err(err) = tx.send(item.into()).await {
+                    *last_err.lock().unwrap() = Some(err.into());
+                    return;
+                }
+            }
+        });
+        (ReceiverStream::new(rx), producer)
+    }
+
+    /// Shuts down the client, along with its clones. New requests fail with
+    /// `ClientError::ShutDown`, while requests already in flight are given
+    /// up to the shutdown timeout to finish. Bulk requests that are still
+    /// streaming after that are stopped, so the server only receives the
+    /// items sent so far, and the requests fail with
+    /// `ClientError::ShutDown`. The connection closes once the last clone of
+    /// the client is dropped.
+    pub async fn shutdown(self) {
+        let lifecycle = self.lifecycle.clone();
+        lifecycle.shut_down.store(true, Ordering::SeqCst);
+
+        let drain = async {
+            while lifecycle.in_flight.load(Ordering::SeqCst) > 0 {
+                lifecycle.idle.notified().await;
+            }
+        };
+        let _ = tokio::time::timeout(self.shutdown_timeout, drain).await;
+
+        lifecycle.stopping.store(true, Ordering::SeqCst);
+        // Wake up any clones that are shutting down too
+        lifecycle.idle.notify_one();
+        drop(self);
     }
 
     /// Pings the server.
     pub async fn ping(&mut self) -> Result<(), ClientError> {
-        self.0.ping(()).await?;
-        Ok(())
+        self.request(true, |mut client| {
+            let req = self.new_request(());
+            async move {
+                client.ping(req).await?;
+                Ok(())
+            }
+        })
+        .await
     }
 
-    /// Syncs persisted content. Depending on the datastore implementation,
-    /// this has different meanings - including potentially being a no-op.
+    /// Syncs persisted content. What this guarantees depends on the server's
+    /// datastore; see `indradb::Database::sync`. With rocksdb, every write
+    /// made before the call is durable on disk once it returns.
     pub async fn sync(&mut self) -> Result<(), ClientError> {
-        self.0.sync(()).await?;
-        Ok(())
+        self.request(true, |mut client| {
+            let req = self.new_request(());
+            async move {
+                client.sync(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Compacts the datastore's storage, e.g. to clear out what's left behind
+    /// by large deletes. This can be expensive, so it's intended for off-peak
+    /// maintenance. Depending on the datastore implementation, this may be a
+    /// no-op.
+    pub async fn compact(&mut self) -> Result<(), ClientError> {
+        self.request(true, |mut client| {
+            let req = self.new_request(());
+            async move {
+                client.compact(req).await?;
+                Ok(())
+            }
+        })
+        .await
     }
 
     /// Creates a new vertex. Returns whether the vertex was successfully
@@ -113,8 +510,14 @@ impl Client {
     /// * `vertex`: The vertex to create.
     pub async fn create_vertex(&mut self, vertex: &indradb::Vertex) -> Result<bool, ClientError> {
         let vertex: crate::Vertex = vertex.clone().into();
-        let res = self.0.create_vertex(vertex).await?;
-        Ok(res.into_inner().created)
+        self.request(false, |mut client| {
+            let req = self.new_request(vertex.clone());
+            async move {
+                let res = client.create_vertex(req).await?;
+                Ok(res.into_inner().created)
+            }
+        })
+        .await
     }
 
     /// Creates a new vertex with just a type specification. As opposed to
@@ -125,8 +528,82 @@ impl Client {
     /// * `t`: The type of the vertex to create.
     pub async fn create_vertex_from_type(&mut self, t: indradb::Identifier) -> Result<Uuid, ClientError> {
         let t: crate::Identifier = t.into();
-        let res = self.0.create_vertex_from_type(t).await?;
-        Ok(res.into_inner().try_into()?)
+        self.request(false, |mut client| {
+            let req = self.new_request(t.clone());
+            async move {
+                let res = client.create_vertex_from_type(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
+    }
+
+    /// Creates a new vertex of a given type along with its initial
+    /// properties, in one transaction. Returns the new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    /// * `props`: The property names and values.
+    pub async fn create_vertex_with_properties(
+        &mut self,
+        t: indradb::Identifier,
+        props: Vec<(indradb::Identifier, indradb::Json)>,
+    ) -> Result<Uuid, ClientError> {
+        let req: crate::CreateVertexWithPropertiesRequest = (t, props).into();
+        self.request(false, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.create_vertex_with_properties(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
+    }
+
+    /// Atomically creates a vertex along with its properties if it doesn't
+    /// exist, or sets the properties on it if it does. Returns whether the
+    /// vertex was created. An existing vertex keeps its type, and its other
+    /// properties.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create or update.
+    /// * `props`: The property names and values.
+    pub async fn upsert_vertex(
+        &mut self,
+        vertex: &indradb::Vertex,
+        props: Vec<(indradb::Identifier, indradb::Json)>,
+    ) -> Result<bool, ClientError> {
+        let req: crate::UpsertVertexRequest = (vertex.clone(), props).into();
+        self.request(false, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.upsert_vertex(req).await?;
+                Ok(res.into_inner().created)
+            }
+        })
+        .await
+    }
+
+    /// Creates a new vertex of a given type along with its labels, in one
+    /// transaction. Returns the new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    /// * `labels`: The labels.
+    pub async fn create_vertex_with_labels(
+        &mut self,
+        t: indradb::Identifier,
+        labels: BTreeSet<indradb::Identifier>,
+    ) -> Result<Uuid, ClientError> {
+        let req: crate::CreateVertexWithLabelsRequest = (t, labels).into();
+        self.request(false, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.create_vertex_with_labels(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
     }
 
     /// Creates a new edge. If the edge already exists, this will update it
@@ -138,32 +615,516 @@ impl Client {
     /// * `edge`: The edge to create.
     pub async fn create_edge(&mut self, edge: &indradb::Edge) -> Result<bool, ClientError> {
         let edge: crate::Edge = edge.clone().into();
-        let res = self.0.create_edge(edge).await?;
-        Ok(res.into_inner().created)
+        self.request(false, |mut client| {
+            let req = self.new_request(edge.clone());
+            async move {
+                let res = client.create_edge(req).await?;
+                Ok(res.into_inner().created)
+            }
+        })
+        .await
+    }
+
+    /// Creates a new edge, but only if it doesn't already exist. Returns
+    /// whether the edge was newly created - if this is false, it's because
+    /// either the edge already exists, or one of the specified vertices is
+    /// missing.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to create.
+    pub async fn create_edge_if_absent(&mut self, edge: &indradb::Edge) -> Result<bool, ClientError> {
+        let edge: crate::Edge = edge.clone().into();
+        self.request(false, |mut client| {
+            let req = self.new_request(edge.clone());
+            async move {
+                let res = client.create_edge_if_absent(req).await?;
+                Ok(res.into_inner().created)
+            }
+        })
+        .await
     }
 
     /// Gets values specified by a query.
     ///
     /// # Arguments
     /// * `q`: The query to run.
+    ///
+    /// # Errors
+    /// Returns `ClientError::Truncated` if the server truncated the output to
+    /// fit its response size limit - see `Server::set_max_response_bytes`.
     pub async fn get<Q: Into<indradb::Query>>(&mut self, q: Q) -> Result<Vec<indradb::QueryOutputValue>, ClientError> {
-        let q: crate::Query = q.into().into();
-        let mut output = Vec::<indradb::QueryOutputValue>::new();
-        let mut res = self.0.get(q).await?.into_inner();
-        while let Some(res) = res.next().await {
-            output.push(res?.try_into()?);
+        self.get_with_options(q.into(), false).await
+    }
+
+    /// Gets values specified by a query in a read-only transaction, which
+    /// doesn't contend with writers.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    ///
+    /// # Errors
+    /// Returns `ClientError::Truncated` if the server truncated the output to
+    /// fit its response size limit - see `Server::set_max_response_bytes`.
+    pub async fn get_readonly<Q: Into<indradb::Query>>(
+        &mut self,
+        q: Q,
+    ) -> Result<Vec<indradb::QueryOutputValue>, ClientError> {
+        self.get_with_options(q.into(), true).await
+    }
+
+    async fn get_with_options(
+        &mut self,
+        q: indradb::Query,
+        readonly: bool,
+    ) -> Result<Vec<indradb::QueryOutputValue>, ClientError> {
+        match self.get_truncatable(q, readonly).await? {
+            (_, true) => Err(ClientError::Truncated),
+            (output, false) => Ok(output),
         }
-        Ok(output)
     }
 
-    /// Deletes values specified by a query.
+    // Gets values specified by a query, along with whether the server
+    // truncated any of them to fit its response size limit.
+    async fn get_truncatable(
+        &mut self,
+        q: indradb::Query,
+        readonly: bool,
+    ) -> Result<(Vec<indradb::QueryOutputValue>, bool), ClientError> {
+        let q = crate::Query { readonly, ..q.into() };
+        self.request(true, |mut client| {
+            let req = self.new_request(q.clone());
+            async move {
+                let mut output = Vec::<indradb::QueryOutputValue>::new();
+                let mut truncated = false;
+                let mut res = client.get(req).await?.into_inner();
+                while let Some(res) = res.next().await {
+                    let res = res?;
+                    truncated |= res.truncated;
+                    output.push(res.try_into()?);
+                }
+                Ok((output, truncated))
+            }
+        })
+        .await
+    }
+
+    /// Gets the vertices specified by a query. If the query includes
+    /// intermediate results, only its final vertices are returned.
     ///
     /// # Arguments
     /// * `q`: The query to run.
-    pub async fn delete<Q: Into<indradb::Query>>(&mut self, q: Q) -> Result<(), ClientError> {
+    ///
+    /// # Errors
+    /// Returns `ClientError::UnexpectedOutput` if the query doesn't output
+    /// vertices.
+    pub async fn get_vertices<Q: Into<indradb::Query>>(&mut self, q: Q) -> Result<Vec<indradb::Vertex>, ClientError> {
+        let output = self.get(q).await?;
+        indradb::util::extract_vertices(output).ok_or(ClientError::UnexpectedOutput)
+    }
+
+    /// Gets the edges specified by a query. If the query includes
+    /// intermediate results, only its final edges are returned.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    ///
+    /// # Errors
+    /// Returns `ClientError::UnexpectedOutput` if the query doesn't output
+    /// edges.
+    pub async fn get_edges<Q: Into<indradb::Query>>(&mut self, q: Q) -> Result<Vec<indradb::Edge>, ClientError> {
+        let output = self.get(q).await?;
+        indradb::util::extract_edges(output).ok_or(ClientError::UnexpectedOutput)
+    }
+
+    /// Gets values specified by several queries in one request, all from one
+    /// transaction. The values of each query are returned in the same
+    /// position as the query. If any query fails, the whole batch fails.
+    ///
+    /// # Arguments
+    /// * `qs`: The queries to run.
+    pub async fn get_batch(
+        &mut self,
+        qs: Vec<indradb::Query>,
+    ) -> Result<Vec<Vec<indradb::QueryOutputValue>>, ClientError> {
+        self.get_batch_with_options(qs, false).await
+    }
+
+    /// Gets values specified by several queries in one request, all from one
+    /// read-only transaction, which doesn't contend with writers.
+    ///
+    /// # Arguments
+    /// * `qs`: The queries to run.
+    pub async fn get_batch_readonly(
+        &mut self,
+        qs: Vec<indradb::Query>,
+    ) -> Result<Vec<Vec<indradb::QueryOutputValue>>, ClientError> {
+        self.get_batch_with_options(qs, true).await
+    }
+
+    async fn get_batch_with_options(
+        &mut self,
+        qs: Vec<indradb::Query>,
+        readonly: bool,
+    ) -> Result<Vec<Vec<indradb::QueryOutputValue>>, ClientError> {
+        let req = crate::QueryBatch { readonly, ..qs.into() };
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.get_batch(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
+    }
+
+    /// Describes how a query would run without running it, e.g. to check
+    /// whether it uses a property index.
+    ///
+    /// # Arguments
+    /// * `q`: The query to describe.
+    pub async fn explain<Q: Into<indradb::Query>>(&mut self, q: Q) -> Result<indradb::QueryPlan, ClientError> {
         let q: crate::Query = q.into().into();
-        self.0.delete(q).await?;
-        Ok(())
+        self.request(true, |mut client| {
+            let req = self.new_request(q.clone());
+            async move {
+                let res = client.explain(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
+    }
+
+    /// Gets the first value specified by a query, or `None` if there are no
+    /// values. Where the query supports a limit, it's limited to one value
+    /// before being sent to the server. Counts are returned as-is.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn get_one<Q: Into<indradb::Query>>(
+        &mut self,
+        q: Q,
+    ) -> Result<Option<indradb::QueryOutputValue>, ClientError> {
+        let output = self.get(q.into().first()).await?;
+        Ok(indradb::util::extract_first(output))
+    }
+
+    /// Gets a page of vertices from a range query, along with an opaque
+    /// cursor for getting the next page, or `None` if there are no more
+    /// vertices.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run. Its limit is the page size.
+    /// * `cursor`: Where to continue from, or `None` for the first page.
+    pub async fn get_vertex_page(
+        &mut self,
+        q: indradb::RangeVertexQuery,
+        cursor: Option<&indradb::Cursor>,
+    ) -> Result<(Vec<indradb::Vertex>, Option<indradb::Cursor>), ClientError> {
+        let q = match cursor {
+            Some(cursor) => cursor.apply(q).map_err(|_| ClientError::InvalidCursor)?,
+            None => q,
+        };
+        let (output, truncated) = self.get_truncatable(q.clone().into(), false).await?;
+        let vertices = indradb::util::extract_vertices(output).unwrap_or_default();
+        let next = if truncated {
+            vertices.last().and_then(|last| indradb::Cursor::continuing(&q, last))
+        } else {
+            indradb::Cursor::after(&q, &vertices)
+        };
+        Ok((vertices, next))
+    }
+
+    /// Gets the properties of a page of vertices from a range query, along
+    /// with a cursor for getting the next page, or `None` if there are no
+    /// more vertices. Vertices without properties are skipped, so pages can
+    /// be smaller than the query's limit, or empty.
+    ///
+    /// If the server has a response size limit, pages are also cut short to
+    /// fit it, so this can page through properties that are too large to
+    /// get at once - see `Server::set_max_response_bytes`.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run. Its limit is the most vertices per page.
+    /// * `cursor`: Where to continue from, or `None` for the first page.
+    pub async fn get_vertex_properties_page(
+        &mut self,
+        q: indradb::RangeVertexQuery,
+        cursor: Option<&indradb::Cursor>,
+    ) -> Result<(Vec<indradb::VertexProperties>, Option<indradb::Cursor>), ClientError> {
+        let q = match cursor {
+            Some(cursor) => cursor.apply(q).map_err(|_| ClientError::InvalidCursor)?,
+            None => q,
+        };
+        // The vertices are included to tell whether the page is full
+        let properties_q = q
+            .clone()
+            .include()
+            .properties()
+            .map_err(|err| ClientError::Conversion { inner: err.into() })?;
+        let (output, truncated) = self.get_truncatable(properties_q.into(), false).await?;
+        let mut output = output.into_iter();
+        let vertices = match output.next() {
+            Some(indradb::QueryOutputValue::Vertices(vertices)) => vertices,
+            _ => return Err(ClientError::UnexpectedOutput),
+        };
+        let properties = match output.next() {
+            Some(indradb::QueryOutputValue::VertexProperties(properties)) => properties,
+            _ => return Err(ClientError::UnexpectedOutput),
+        };
+        let next = if truncated {
+            // Continue after the last vertex that made it into the page
+            match properties.last() {
+                Some(last) => indradb::Cursor::continuing(&q, &last.vertex),
+                None => vertices.last().and_then(|last| indradb::Cursor::continuing(&q, last)),
+            }
+        } else {
+            indradb::Cursor::after(&q, &vertices)
+        };
+        Ok((properties, next))
+    }
+
+    /// Gets a vertex if it exists, or `None` otherwise.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex to get.
+    pub async fn get_vertex(&mut self, id: Uuid) -> Result<Option<indradb::Vertex>, ClientError> {
+        let output = self.get(indradb::SpecificVertexQuery::single(id)).await?;
+        Ok(indradb::util::extract_vertices(output).and_then(|vertices| vertices.into_iter().next()))
+    }
+
+    /// Checks whether a vertex exists.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex to check.
+    pub async fn contains_vertex(&mut self, id: Uuid) -> Result<bool, ClientError> {
+        Ok(self.get_vertex(id).await?.is_some())
+    }
+
+    /// Gets an edge if it exists, or `None` otherwise.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to get.
+    pub async fn get_edge(&mut self, edge: &indradb::Edge) -> Result<Option<indradb::Edge>, ClientError> {
+        let output = self.get(indradb::SpecificEdgeQuery::single(edge.clone())).await?;
+        Ok(indradb::util::extract_edges(output).and_then(|edges| edges.into_iter().next()))
+    }
+
+    /// Checks whether an edge exists.
+    ///
+    /// # Arguments
+    /// * `edge`: The edge to check.
+    pub async fn contains_edge(&mut self, edge: &indradb::Edge) -> Result<bool, ClientError> {
+        Ok(self.get_edge(edge).await?.is_some())
+    }
+
+    /// Gets the number of vertices of a given type.
+    ///
+    /// # Arguments
+    /// * `t`: The vertex type to count.
+    pub async fn get_vertex_count_by_type(&mut self, t: indradb::Identifier) -> Result<u64, ClientError> {
+        let t: crate::Identifier = t.into();
+        self.request(true, |mut client| {
+            let req = self.new_request(t.clone());
+            async move {
+                let res = client.get_vertex_count_by_type(req).await?;
+                Ok(res.into_inner().count)
+            }
+        })
+        .await
+    }
+
+    /// Gets a vertex along with its outbound and inbound edges, or `None` if
+    /// the vertex doesn't exist, all in one request.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only get edges of this type, if set.
+    /// * `limit`: The maximum number of edges to get in each direction.
+    pub async fn get_vertex_neighborhood(
+        &mut self,
+        id: Uuid,
+        t_filter: Option<indradb::Identifier>,
+        limit: u32,
+    ) -> Result<Option<indradb::Neighborhood>, ClientError> {
+        let req: crate::GetVertexNeighborhoodRequest = (id, t_filter, limit).into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.get_vertex_neighborhood(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
+    }
+
+    /// Gets the number of outbound and inbound edges of a vertex, as
+    /// `(outbound, inbound)`, in one request.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    /// * `t_filter`: Only count edges of this type, if set.
+    pub async fn get_edge_counts(
+        &mut self,
+        id: Uuid,
+        t_filter: Option<indradb::Identifier>,
+    ) -> Result<(u64, u64), ClientError> {
+        let req: crate::GetEdgeCountsRequest = (id, t_filter).into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.get_edge_counts(req).await?;
+                Ok(res.into_inner().into())
+            }
+        })
+        .await
+    }
+
+    /// Gets the labels of a vertex, which are empty if the vertex doesn't
+    /// exist.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    pub async fn get_vertex_labels(&mut self, id: Uuid) -> Result<BTreeSet<indradb::Identifier>, ClientError> {
+        let req: crate::Uuid = id.into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.get_vertex_labels(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
+    }
+
+    /// Gets when a vertex was created, or `None` if it doesn't exist or was
+    /// created before its datastore tracked creation times.
+    ///
+    /// # Arguments
+    /// * `id`: The ID of the vertex.
+    pub async fn get_vertex_created_at(&mut self, id: Uuid) -> Result<Option<SystemTime>, ClientError> {
+        let req: crate::Uuid = id.into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.get_vertex_created_at(req).await?;
+                Ok(res.into_inner().try_into()?)
+            }
+        })
+        .await
+    }
+
+    /// Gets the distinct vertex and edge property names currently in use.
+    /// Returns a tuple of the vertex property names and the edge property
+    /// names, each in sorted order.
+    pub async fn get_property_names(
+        &mut self,
+    ) -> Result<(Vec<indradb::Identifier>, Vec<indradb::Identifier>), ClientError> {
+        self.request(true, |mut client| {
+            let req = self.new_request(());
+            async move {
+                let res = client.get_property_names(req).await?.into_inner();
+                let vertex_property_names: Result<Vec<indradb::Identifier>, ConversionError> = res
+                    .vertex_property_names
+                    .into_iter()
+                    .map(|name| name.try_into())
+                    .collect();
+                let edge_property_names: Result<Vec<indradb::Identifier>, ConversionError> = res
+                    .edge_property_names
+                    .into_iter()
+                    .map(|name| name.try_into())
+                    .collect();
+                Ok((vertex_property_names?, edge_property_names?))
+            }
+        })
+        .await
+    }
+
+    /// Gets the names of indexed properties, including paths to nested
+    /// fields, in sorted order.
+    pub async fn get_indexed_properties(&mut self) -> Result<Vec<indradb::Identifier>, ClientError> {
+        self.request(true, |mut client| {
+            let req = self.new_request(());
+            async move {
+                let res = client.get_indexed_properties(req).await?.into_inner();
+                let names: Result<Vec<indradb::Identifier>, ConversionError> =
+                    res.names.into_iter().map(|name| name.try_into()).collect();
+                Ok(names?)
+            }
+        })
+        .await
+    }
+
+    /// Gets statistics about the datastore, e.g. for monitoring. This is
+    /// cheaper than counting with queries, but some datastores estimate the
+    /// counts.
+    pub async fn get_stats(&mut self) -> Result<indradb::DatastoreStats, ClientError> {
+        self.request(true, |mut client| {
+            let req = self.new_request(());
+            async move {
+                let res = client.get_stats(req).await?.into_inner();
+                Ok(res.into())
+            }
+        })
+        .await
+    }
+
+    /// Counts the vertices or edges returned by a query, grouped by the value
+    /// of a property. Returns each distinct value with its count, most common
+    /// first. Vertices or edges without the property aren't counted.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name to group by.
+    pub async fn group_count_by_property<Q: Into<indradb::Query>>(
+        &mut self,
+        q: Q,
+        name: indradb::Identifier,
+    ) -> Result<Vec<(indradb::Json, u64)>, ClientError> {
+        let req: crate::GroupCountByPropertyRequest = (q.into(), name).into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.group_count_by_property(req).await?.into_inner();
+                let counts: Result<Vec<(indradb::Json, u64)>, ConversionError> =
+                    res.counts.into_iter().map(|count| count.try_into()).collect();
+                Ok(counts?)
+            }
+        })
+        .await
+    }
+
+    /// Deletes values specified by a query. Returns the number of vertices,
+    /// edges or properties deleted.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn delete<Q: Into<indradb::Query>>(&mut self, q: Q) -> Result<u64, ClientError> {
+        let q: crate::Query = q.into().into();
+        self.request(true, |mut client| {
+            let req = self.new_request(q.clone());
+            async move {
+                let res = client.delete(req).await?;
+                Ok(res.into_inner().count)
+            }
+        })
+        .await
+    }
+
+    /// Deletes every property of the vertices or edges specified by a query,
+    /// in one transaction. Returns the number of properties deleted.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn delete_all_properties<Q: Into<indradb::Query>>(&mut self, q: Q) -> Result<u64, ClientError> {
+        let q: crate::Query = q.into().into();
+        self.request(true, |mut client| {
+            let req = self.new_request(q.clone());
+            async move {
+                let res = client.delete_all_properties(req).await?;
+                Ok(res.into_inner().count)
+            }
+        })
+        .await
     }
 
     /// Sets properties.
@@ -180,13 +1141,112 @@ impl Client {
     ) -> Result<(), ClientError> {
         let name: crate::Identifier = name.into();
         let value: crate::Json = value.clone().into();
-        let req = Request::new(crate::SetPropertiesRequest {
+        let req = crate::SetPropertiesRequest {
             q: Some(q.into().into()),
             name: name.into(),
             value: value.clone().into(),
-        });
-        self.0.set_properties(req).await?;
-        Ok(())
+        };
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                client.set_properties(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Adds a label to the vertices returned by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `label`: The label.
+    pub async fn add_label<Q: Into<indradb::Query>>(
+        &mut self,
+        q: Q,
+        label: indradb::Identifier,
+    ) -> Result<(), ClientError> {
+        let req: crate::LabelRequest = (q.into(), label).into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                client.add_label(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Removes a label from the vertices returned by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `label`: The label.
+    pub async fn remove_label<Q: Into<indradb::Query>>(
+        &mut self,
+        q: Q,
+        label: indradb::Identifier,
+    ) -> Result<(), ClientError> {
+        let req: crate::LabelRequest = (q.into(), label).into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                client.remove_label(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Sets several properties in one round trip and transaction. If a name
+    /// appears more than once, the last value wins.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `props`: The property names and values.
+    pub async fn set_properties_batch<Q: Into<indradb::Query>>(
+        &mut self,
+        q: Q,
+        props: Vec<(indradb::Identifier, indradb::Json)>,
+    ) -> Result<(), ClientError> {
+        let req: crate::SetPropertiesBatchRequest = (q.into(), props).into();
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                client.set_properties_batch(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Atomically sets a vertex property to a new value, but only if its
+    /// current value is equal to an expected one. Returns whether the
+    /// property was set. This isn't retried, since a retry after a lost
+    /// response would report that the property wasn't set.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `name`: The property name.
+    /// * `expected`: The expected current value, or `None` if the property
+    ///   is expected to be missing.
+    /// * `new`: The value to set.
+    pub async fn compare_and_set_property<Q: Into<indradb::Query>>(
+        &mut self,
+        q: Q,
+        name: indradb::Identifier,
+        expected: Option<&indradb::Json>,
+        new: &indradb::Json,
+    ) -> Result<bool, ClientError> {
+        let req: crate::CompareAndSetPropertyRequest = (q.into(), name, expected.cloned(), new.clone()).into();
+        self.request(false, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.compare_and_set_property(req).await?;
+                Ok(res.into_inner().swapped)
+            }
+        })
+        .await
     }
 
     /// Bulk inserts many vertices, edges, and/or properties.
@@ -203,22 +1263,98 @@ impl Client {
     /// # Arguments
     /// * `items`: The items to insert.
     pub async fn bulk_insert(&mut self, items: Vec<indradb::BulkInsertItem>) -> Result<(), ClientError> {
-        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let _in_flight = self.lifecycle.start()?;
         let last_err: Arc<Mutex<Option<ClientError>>> = Arc::new(Mutex::new(None));
+        let (stream, producer) =
+            self.spawn_producer::<_, crate::BulkInsertItem>(items, self.bulk_insert_capacity, last_err.clone());
 
-        {
-            let last_err = last_err.clone();
-            tokio::spawn(async move {
-                for item in items.into_iter() {
-                    if let Err(err) = tx.send(item.into()).await {
-                        *last_err.lock().unwrap() = Some(err.into());
-                        return;
-                    }
-                }
-            });
+        let mut client = self.inner.clone();
+        let req = self.new_request(stream);
+        let res = with_timeout(self.timeout, async move {
+            client.bulk_insert(req).await?;
+            Ok(())
+        })
+        .await;
+        // The producer ends once the request has consumed or dropped the
+        // stream
+        let _ = producer.await;
+        res?;
+
+        let mut last_err = last_err.lock().unwrap();
+        if last_err.is_some() {
+            Err(last_err.take().unwrap())
+        } else {
+            Ok(())
         }
+    }
 
-        self.0.bulk_insert(Request::new(ReceiverStream::new(rx))).await?;
+    /// Bulk inserts many vertices, edges, and/or properties. As opposed to
+    /// `bulk_insert`, items that fail don't abort the whole request; the
+    /// rest of the items are still inserted, and the failures are returned
+    /// along with each item's index in `items`. The same safeguard vs
+    /// performance tradeoffs as `bulk_insert` apply.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    pub async fn bulk_insert_lenient(
+        &mut self,
+        items: Vec<indradb::BulkInsertItem>,
+    ) -> Result<Vec<(usize, ClientError)>, ClientError> {
+        let _in_flight = self.lifecycle.start()?;
+        let last_err: Arc<Mutex<Option<ClientError>>> = Arc::new(Mutex::new(None));
+        let (stream, producer) =
+            self.spawn_producer::<_, crate::BulkInsertItem>(items, self.bulk_insert_capacity, last_err.clone());
+
+        let mut client = self.inner.clone();
+        let req = self.new_request(stream);
+        let res = with_timeout(self.timeout, async move {
+            let mut failures: Vec<(usize, ClientError)> = Vec::new();
+            let mut res = client.bulk_insert_lenient(req).await?.into_inner();
+            while let Some(failure) = res.next().await {
+                let failure = failure?;
+                let status = Status::new(Code::from(failure.code), failure.message);
+                failures.push((failure.index as usize, ClientError::from(status)));
+            }
+            Ok(failures)
+        })
+        .await;
+        let _ = producer.await;
+        let failures = res?;
+
+        let mut last_err = last_err.lock().unwrap();
+        if last_err.is_some() {
+            Err(last_err.take().unwrap())
+        } else {
+            Ok(failures)
+        }
+    }
+
+    /// Bulk deletes many vertices, edges, and/or properties. The server
+    /// deletes items in batches as they're streamed in, so if this fails,
+    /// some of the items may have already been deleted.
+    ///
+    /// As with `bulk_insert`, datastores have discretion on how to approach
+    /// safeguard vs performance tradeoffs; e.g., a disk-backed datastore may
+    /// or may not flush before returning. If you want maximum protection, use
+    /// `delete`, which will provide more safeguards.
+    ///
+    /// # Arguments
+    /// * `items`: The items to delete.
+    pub async fn bulk_delete(&mut self, items: Vec<indradb::BulkDeleteItem>) -> Result<(), ClientError> {
+        let _in_flight = self.lifecycle.start()?;
+        let last_err: Arc<Mutex<Option<ClientError>>> = Arc::new(Mutex::new(None));
+        let (stream, producer) =
+            self.spawn_producer::<_, crate::BulkDeleteItem>(items, CHANNEL_CAPACITY, last_err.clone());
+
+        let mut client = self.inner.clone();
+        let req = self.new_request(stream);
+        let res = with_timeout(self.timeout, async move {
+            client.bulk_delete(req).await?;
+            Ok(())
+        })
+        .await;
+        let _ = producer.await;
+        res?;
 
         let mut last_err = last_err.lock().unwrap();
         if last_err.is_some() {
@@ -228,23 +1364,156 @@ impl Client {
         }
     }
 
+    /// Enables indexing on a specified property. A nested field of a property
+    /// can be indexed by passing a path created with
+    /// `indradb::Identifier::new_path`, e.g. `addr.city`.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to index.
     pub async fn index_property(&mut self, name: indradb::Identifier) -> Result<(), ClientError> {
-        let request = Request::new(crate::IndexPropertyRequest {
+        let req = crate::IndexPropertyRequest {
             name: Some(name.into()),
-        });
-        self.0.index_property(request).await?;
-        Ok(())
+        };
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                client.index_property(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Drops the index on a specified property. This is a no-op if the
+    /// property isn't indexed.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property, or path to the field, to stop
+    ///   indexing.
+    pub async fn drop_index(&mut self, name: indradb::Identifier) -> Result<(), ClientError> {
+        let req = crate::DropIndexRequest {
+            name: Some(name.into()),
+        };
+        self.request(true, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                client.drop_index(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Removes all vertices, edges, properties and indexes, returning the
+    /// database to an empty state. This fails if the server has disabled
+    /// clearing.
+    pub async fn clear(&mut self) -> Result<(), ClientError> {
+        self.request(true, |mut client| {
+            let req = self.new_request(());
+            async move {
+                client.clear(req).await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+
+    /// Watches for changes to the graph. The returned stream yields events
+    /// as mutations are committed, and ends if the connection is lost; it
+    /// isn't retried.
+    ///
+    /// Delivery is best-effort. If the stream isn't consumed quickly enough,
+    /// the server drops events rather than slowing down writers, so a
+    /// consumer that needs to stay exactly in sync should periodically
+    /// reconcile against the datastore.
+    ///
+    /// # Arguments
+    /// * `filter`: Which changes to watch for.
+    pub async fn watch(
+        &mut self,
+        filter: indradb::ChangeFilter,
+    ) -> Result<impl Stream<Item = Result<indradb::ChangeEvent, ClientError>> + Unpin, ClientError> {
+        let req: crate::WatchRequest = filter.into();
+        let res = self
+            .request(true, |mut client| {
+                let req = self.new_request(req.clone());
+                async move { Ok(client.watch(req).await?.into_inner()) }
+            })
+            .await?;
+        Ok(res.map(|event| -> Result<indradb::ChangeEvent, ClientError> { Ok(event?.try_into()?) }))
     }
 
     pub async fn execute_plugin(&mut self, name: &str, arg: indradb::Json) -> Result<indradb::Json, ClientError> {
-        let req = Request::new(crate::ExecutePluginRequest {
+        self.execute_plugin_with_options(name, arg, false).await
+    }
+
+    pub async fn execute_readonly_plugin(
+        &mut self,
+        name: &str,
+        arg: indradb::Json,
+    ) -> Result<indradb::Json, ClientError> {
+        self.execute_plugin_with_options(name, arg, true).await
+    }
+
+    async fn execute_plugin_with_options(
+        &mut self,
+        name: &str,
+        arg: indradb::Json,
+        readonly: bool,
+    ) -> Result<indradb::Json, ClientError> {
+        let req = crate::ExecutePluginRequest {
             name: name.to_string(),
             arg: Some(arg.into()),
-        });
-        let res = self.0.execute_plugin(req).await?;
-        match res.into_inner().value {
-            Some(value) => Ok(value.try_into()?),
-            None => Ok(indradb::Json::new(serde_json::Value::Null)),
+            readonly,
+        };
+        // Read-only plugins can't have side effects, so are safe to retry
+        self.request(readonly, |mut client| {
+            let req = self.new_request(req.clone());
+            async move {
+                let res = client.execute_plugin(req).await?;
+                match res.into_inner().value {
+                    Some(value) => Ok(value.try_into()?),
+                    None => Ok(indradb::Json::new(serde_json::Value::Null)),
+                }
+            }
+        })
+        .await
+    }
+}
+
+// Runs `f`, failing with `ClientError::Timeout` if it takes longer than
+// `timeout`.
+async fn with_timeout<T, Fut>(timeout: Option<Duration>, f: Fut) -> Result<T, ClientError>
+where
+    Fut: Future<Output = Result<T, ClientError>>,
+{
+    match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, f).await {
+            Ok(result) => result,
+            Err(_) => Err(ClientError::Timeout),
+        },
+        None => f.await,
+    }
+}
+
+// Runs `f` until it succeeds, fails with an error that isn't retryable, or
+// runs out of attempts. The last error is returned on failure.
+async fn retry<T, E, F, Fut>(config: &RetryConfig, mut f: F) -> Result<T, ClientError>
+where
+    E: Into<ClientError>,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut backoff = config.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match f().await.map_err(Into::into) {
+            Err(err) if err.is_retryable() && attempt < config.max_attempts => {
+                tokio::time::sleep(jitter(backoff)).await;
+                backoff = min(backoff.saturating_mul(2), config.max_backoff);
+                attempt += 1;
+            }
+            result => return result,
         }
     }
 }
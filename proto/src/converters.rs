@@ -1,9 +1,11 @@
 //! Trait implementations for conveniently converting between protobuf and
 //! native IndraDB models.
 
+use std::collections::BTreeSet;
 use std::convert::TryInto;
 use std::error::Error as StdError;
 use std::fmt;
+use std::time::SystemTime;
 
 use indradb::ValidationError;
 use serde_json::Error as SerdeJsonError;
@@ -61,12 +63,116 @@ impl From<ValidationError> for ConversionError {
     }
 }
 
+/// The metadata key that identifies which IndraDB error a gRPC status came
+/// from, so that clients can reconstruct the error.
+pub const ERROR_KIND_METADATA_KEY: &str = "indradb-error";
+
+// Gets the identifier of an error sent in status metadata. Errors that wrap
+// other errors, e.g. from the underlying datastore, are only sent as
+// messages.
+#[cfg(feature = "server")]
+pub(crate) fn error_kind(err: &indradb::Error) -> Option<&'static str> {
+    match err {
+        indradb::Error::UuidTaken => Some("uuid_taken"),
+        indradb::Error::NotIndexed => Some("not_indexed"),
+        indradb::Error::Unsupported => Some("unsupported"),
+        indradb::Error::Invalid(indradb::ValidationError::InvalidValue) => Some("invalid_value"),
+        indradb::Error::Invalid(indradb::ValidationError::ValueTooLong) => Some("value_too_long"),
+        indradb::Error::Invalid(indradb::ValidationError::CannotIncrementUuid) => Some("cannot_increment_uuid"),
+        indradb::Error::Invalid(indradb::ValidationError::InnerQuery) => Some("inner_query"),
+        indradb::Error::OperationOnQuery => Some("operation_on_query"),
+        indradb::Error::NotNumeric => Some("not_numeric"),
+        indradb::Error::NotObject => Some("not_object"),
+        indradb::Error::ReadOnly => Some("read_only"),
+        indradb::Error::LimitExceeded => Some("limit_exceeded"),
+        indradb::Error::SchemaViolation => Some("schema_violation"),
+        indradb::Error::SerializationConflict => Some("serialization_conflict"),
+        indradb::Error::InvalidCursor => Some("invalid_cursor"),
+        _ => None,
+    }
+}
+
+// The inverse of `error_kind`.
+#[cfg(feature = "client")]
+pub(crate) fn error_from_kind(kind: &str) -> Option<indradb::Error> {
+    match kind {
+        "uuid_taken" => Some(indradb::Error::UuidTaken),
+        "not_indexed" => Some(indradb::Error::NotIndexed),
+        "unsupported" => Some(indradb::Error::Unsupported),
+        "invalid_value" => Some(indradb::Error::Invalid(indradb::ValidationError::InvalidValue)),
+        "value_too_long" => Some(indradb::Error::Invalid(indradb::ValidationError::ValueTooLong)),
+        "cannot_increment_uuid" => Some(indradb::Error::Invalid(indradb::ValidationError::CannotIncrementUuid)),
+        "inner_query" => Some(indradb::Error::Invalid(indradb::ValidationError::InnerQuery)),
+        "operation_on_query" => Some(indradb::Error::OperationOnQuery),
+        "not_numeric" => Some(indradb::Error::NotNumeric),
+        "not_object" => Some(indradb::Error::NotObject),
+        "read_only" => Some(indradb::Error::ReadOnly),
+        "limit_exceeded" => Some(indradb::Error::LimitExceeded),
+        "schema_violation" => Some(indradb::Error::SchemaViolation),
+        "serialization_conflict" => Some(indradb::Error::SerializationConflict),
+        "invalid_cursor" => Some(indradb::Error::InvalidCursor),
+        _ => None,
+    }
+}
+
 fn required_field<T>(field_name: &str, value: Option<T>) -> Result<T, ConversionError> {
     value.ok_or_else(|| ConversionError::NoneField {
         name: field_name.to_string(),
     })
 }
 
+// Type filters are sent as `t_filter`, with `t` also set for older servers
+// when there is only one type.
+fn t_filter_into_proto(t_filter: Vec<indradb::Identifier>) -> (Option<crate::Identifier>, Vec<crate::Identifier>) {
+    let t = match t_filter.as_slice() {
+        [t] => Some((*t).into()),
+        _ => None,
+    };
+    (t, t_filter.into_iter().map(|t| t.into()).collect())
+}
+
+// Older clients only send `t`, so it's merged into `t_filter`.
+fn t_filter_from_proto(
+    t: Option<crate::Identifier>,
+    t_filter: Vec<crate::Identifier>,
+) -> Result<Vec<indradb::Identifier>, ConversionError> {
+    let mut t_filter = t_filter
+        .into_iter()
+        .map(|t| t.try_into())
+        .collect::<Result<Vec<indradb::Identifier>, ConversionError>>()?;
+    if let Some(t) = t {
+        let t = t.try_into()?;
+        if !t_filter.contains(&t) {
+            t_filter.push(t);
+        }
+    }
+    Ok(t_filter)
+}
+
+// Fails for timestamps that are out of range for `SystemTime`.
+fn time_from_proto(time: prost_types::Timestamp) -> Result<SystemTime, ConversionError> {
+    SystemTime::try_from(time).map_err(|_| ValidationError::InvalidValue.into())
+}
+
+fn properties_into_proto(props: Vec<(indradb::Identifier, indradb::Json)>) -> Vec<crate::NamedProperty> {
+    props
+        .into_iter()
+        .map(|(name, value)| indradb::NamedProperty::new(name, value).into())
+        .collect()
+}
+
+fn properties_from_proto(
+    props: Vec<crate::NamedProperty>,
+) -> Result<Vec<(indradb::Identifier, indradb::Json)>, ConversionError> {
+    props
+        .into_iter()
+        .map(|prop| {
+            let prop: indradb::NamedProperty = prop.try_into()?;
+            Ok((prop.name, prop.value))
+        })
+        .collect()
+}
+
 impl From<Uuid> for crate::Uuid {
     fn from(uuid: Uuid) -> Self {
         crate::Uuid {
@@ -93,7 +199,9 @@ impl TryInto<indradb::Identifier> for crate::Identifier {
     type Error = ConversionError;
 
     fn try_into(self) -> Result<indradb::Identifier, Self::Error> {
-        Ok(indradb::Identifier::new(self.value)?)
+        // Paths are a superset of plain identifiers, and are accepted so that
+        // nested properties can be indexed and queried
+        Ok(indradb::Identifier::new_path(self.value)?)
     }
 }
 
@@ -160,11 +268,21 @@ impl From<indradb::Query> for crate::Query {
         crate::Query {
             query: Some(match q {
                 indradb::Query::AllVertex => crate::QueryVariant::AllVertex(()),
-                indradb::Query::RangeVertex(q) => crate::QueryVariant::RangeVertex(crate::RangeVertexQuery {
-                    limit: q.limit,
-                    t: q.t.map(|t| t.into()),
-                    start_id: q.start_id.map(|start_id| start_id.into()),
-                }),
+                indradb::Query::RangeVertex(q) => {
+                    let (t, t_filter) = t_filter_into_proto(q.t_filter);
+                    let mut proto_q = crate::RangeVertexQuery {
+                        limit: q.limit,
+                        t,
+                        t_filter,
+                        start_id: q.start_id.map(|start_id| start_id.into()),
+                        property_filter: q
+                            .property_filter
+                            .map(|(name, value)| indradb::NamedProperty::new(name, value).into()),
+                        direction: 0,
+                    };
+                    proto_q.set_direction(q.direction.into());
+                    crate::QueryVariant::RangeVertex(proto_q)
+                }
                 indradb::Query::SpecificVertex(q) => crate::QueryVariant::SpecificVertex(crate::SpecificVertexQuery {
                     ids: q.ids.into_iter().map(|id| id.into()).collect(),
                 }),
@@ -179,8 +297,26 @@ impl From<indradb::Query> for crate::Query {
                         value: Some(q.value.into()),
                     })
                 }
+                indradb::Query::VertexWithLabel(q) => {
+                    crate::QueryVariant::VertexWithLabel(crate::VertexWithLabelQuery {
+                        label: Some(q.label.into()),
+                    })
+                }
+                indradb::Query::CreatedRangeVertex(q) => {
+                    crate::QueryVariant::CreatedRangeVertex(crate::CreatedRangeVertexQuery {
+                        limit: q.limit,
+                        low: q.low.map(|low| low.into()),
+                        high: q.high.map(|high| high.into()),
+                    })
+                }
 
                 indradb::Query::AllEdge => crate::QueryVariant::AllEdge(()),
+                indradb::Query::RangeEdge(q) => crate::QueryVariant::RangeEdge(crate::RangeEdgeQuery {
+                    limit: q.limit,
+                    outbound_id: q.outbound_id.map(|outbound_id| outbound_id.into()),
+                    after: q.after.map(|after| after.into()),
+                    t_filter: q.t_filter.into_iter().map(|t| t.into()).collect(),
+                }),
                 indradb::Query::SpecificEdge(q) => crate::QueryVariant::SpecificEdge(crate::SpecificEdgeQuery {
                     edges: q.edges.into_iter().map(|id| id.into()).collect(),
                 }),
@@ -196,12 +332,21 @@ impl From<indradb::Query> for crate::Query {
                     })
                 }
 
+                indradb::Query::WithPropertyValue(q) => {
+                    crate::QueryVariant::WithPropertyValue(crate::WithPropertyValueQuery {
+                        name: Some(q.name.into()),
+                        value: Some(q.value.into()),
+                    })
+                }
+
                 indradb::Query::Pipe(q) => {
+                    let (t, t_filter) = t_filter_into_proto(q.t_filter);
                     let mut proto_q = crate::PipeQuery {
                         inner: Some(Box::new((*q.inner).into())),
                         direction: 0,
                         limit: q.limit,
-                        t: q.t.map(|t| t.into()),
+                        t,
+                        t_filter,
                     };
                     proto_q.set_direction(q.direction.into());
                     crate::QueryVariant::Pipe(Box::new(proto_q))
@@ -230,6 +375,26 @@ impl From<indradb::Query> for crate::Query {
                     };
                     crate::QueryVariant::PipeWithPropertyValue(Box::new(proto_q))
                 }
+                indradb::Query::PipeWithPropertyPredicate(q) => {
+                    let proto_q = crate::PipeWithPropertyPredicateQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        name: Some(q.name.into()),
+                        predicate: Some(q.predicate.into()),
+                        limit: Some(q.limit),
+                    };
+                    crate::QueryVariant::PipeWithPropertyPredicate(Box::new(proto_q))
+                }
+                indradb::Query::TraversalVertex(q) => {
+                    let mut proto_q = crate::TraversalVertexQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        direction: 0,
+                        max_depth: q.max_depth,
+                        limit: q.limit,
+                        t_filter: q.t_filter.into_iter().map(|t| t.into()).collect(),
+                    };
+                    proto_q.set_direction(q.direction.into());
+                    crate::QueryVariant::TraversalVertex(Box::new(proto_q))
+                }
 
                 indradb::Query::Include(q) => {
                     let proto_q = crate::IncludeQuery {
@@ -244,6 +409,7 @@ impl From<indradb::Query> for crate::Query {
                     crate::QueryVariant::Count(Box::new(proto_q))
                 }
             }),
+            readonly: false,
         }
     }
 }
@@ -254,11 +420,22 @@ impl TryInto<indradb::Query> for crate::Query {
     fn try_into(self) -> Result<indradb::Query, Self::Error> {
         Ok(match required_field("query", self.query)? {
             crate::QueryVariant::AllVertex(_q) => indradb::Query::AllVertex,
-            crate::QueryVariant::RangeVertex(q) => indradb::Query::RangeVertex(indradb::RangeVertexQuery {
-                limit: q.limit,
-                t: q.t.map(|t| t.try_into()).transpose()?,
-                start_id: q.start_id.map(|start_id| start_id.try_into()).transpose()?,
-            }),
+            crate::QueryVariant::RangeVertex(q) => {
+                let direction = q.direction().into();
+                indradb::Query::RangeVertex(indradb::RangeVertexQuery {
+                    limit: q.limit,
+                    t_filter: t_filter_from_proto(q.t, q.t_filter)?,
+                    start_id: q.start_id.map(|start_id| start_id.try_into()).transpose()?,
+                    property_filter: q
+                        .property_filter
+                        .map(|p| {
+                            let p: indradb::NamedProperty = p.try_into()?;
+                            Ok::<_, ConversionError>((p.name, p.value))
+                        })
+                        .transpose()?,
+                    direction,
+                })
+            }
             crate::QueryVariant::SpecificVertex(q) => {
                 let ids: Result<Vec<Uuid>, ConversionError> = q.ids.into_iter().map(|id| id.try_into()).collect();
                 indradb::Query::SpecificVertex(indradb::SpecificVertexQuery { ids: ids? })
@@ -277,8 +454,31 @@ impl TryInto<indradb::Query> for crate::Query {
                     value: value.try_into()?,
                 })
             }
+            crate::QueryVariant::VertexWithLabel(q) => {
+                let label = required_field("label", q.label)?;
+                indradb::Query::VertexWithLabel(indradb::VertexWithLabelQuery {
+                    label: label.try_into()?,
+                })
+            }
+            crate::QueryVariant::CreatedRangeVertex(q) => {
+                indradb::Query::CreatedRangeVertex(indradb::CreatedRangeVertexQuery {
+                    limit: q.limit,
+                    low: q.low.map(time_from_proto).transpose()?,
+                    high: q.high.map(time_from_proto).transpose()?,
+                })
+            }
 
             crate::QueryVariant::AllEdge(_q) => indradb::Query::AllEdge,
+            crate::QueryVariant::RangeEdge(q) => indradb::Query::RangeEdge(indradb::RangeEdgeQuery {
+                limit: q.limit,
+                outbound_id: q.outbound_id.map(|outbound_id| outbound_id.try_into()).transpose()?,
+                t_filter: q
+                    .t_filter
+                    .into_iter()
+                    .map(|t| t.try_into())
+                    .collect::<Result<Vec<indradb::Identifier>, ConversionError>>()?,
+                after: q.after.map(|after| after.try_into()).transpose()?,
+            }),
             crate::QueryVariant::SpecificEdge(q) => {
                 let edges: Result<Vec<indradb::Edge>, ConversionError> =
                     q.edges.into_iter().map(|id| id.try_into()).collect();
@@ -299,15 +499,24 @@ impl TryInto<indradb::Query> for crate::Query {
                 })
             }
 
+            crate::QueryVariant::WithPropertyValue(q) => {
+                let name = required_field("name", q.name)?;
+                let value = required_field("value", q.value)?;
+                indradb::Query::WithPropertyValue(indradb::WithPropertyValueQuery {
+                    name: name.try_into()?,
+                    value: value.try_into()?,
+                })
+            }
+
             crate::QueryVariant::Pipe(q) => {
                 let direction = q.direction().into();
                 let limit = q.limit;
-                let t = q.t.map(|t| t.try_into()).transpose()?;
+                let t_filter = t_filter_from_proto(q.t, q.t_filter)?;
                 let inner = required_field("inner", q.inner)?;
                 indradb::Query::Pipe(indradb::PipeQuery {
                     direction,
                     limit,
-                    t,
+                    t_filter,
                     inner: Box::new((*inner).try_into()?),
                 })
             }
@@ -339,6 +548,30 @@ impl TryInto<indradb::Query> for crate::Query {
                     equal: q.equal,
                 })
             }
+            crate::QueryVariant::PipeWithPropertyPredicate(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let name = required_field("name", q.name)?;
+                let predicate = required_field("predicate", q.predicate)?;
+                indradb::Query::PipeWithPropertyPredicate(indradb::PipeWithPropertyPredicateQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    name: name.try_into()?,
+                    predicate: predicate.try_into()?,
+                    limit: q.limit.unwrap_or(u32::MAX),
+                })
+            }
+            crate::QueryVariant::TraversalVertex(q) => {
+                let direction = q.direction().into();
+                let t_filter: Result<Vec<indradb::Identifier>, ConversionError> =
+                    q.t_filter.into_iter().map(|t| t.try_into()).collect();
+                let inner = required_field("inner", q.inner)?;
+                indradb::Query::TraversalVertex(indradb::TraversalVertexQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    direction,
+                    max_depth: q.max_depth,
+                    limit: q.limit,
+                    t_filter: t_filter?,
+                })
+            }
 
             crate::QueryVariant::Include(q) => {
                 let inner = required_field("inner", q.inner)?;
@@ -356,6 +589,44 @@ impl TryInto<indradb::Query> for crate::Query {
     }
 }
 
+impl From<indradb::PropertyPredicate> for crate::PropertyPredicate {
+    fn from(predicate: indradb::PropertyPredicate) -> Self {
+        let variant = match predicate {
+            indradb::PropertyPredicate::Equal(v) => crate::PropertyPredicateVariant::Equal(v.into()),
+            indradb::PropertyPredicate::NotEqual(v) => crate::PropertyPredicateVariant::NotEqual(v.into()),
+            indradb::PropertyPredicate::GreaterThan(v) => crate::PropertyPredicateVariant::GreaterThan(v.into()),
+            indradb::PropertyPredicate::LessThan(v) => crate::PropertyPredicateVariant::LessThan(v.into()),
+            indradb::PropertyPredicate::GreaterOrEqual(v) => crate::PropertyPredicateVariant::GreaterOrEqual(v.into()),
+            indradb::PropertyPredicate::LessOrEqual(v) => crate::PropertyPredicateVariant::LessOrEqual(v.into()),
+            indradb::PropertyPredicate::Contains(s) => crate::PropertyPredicateVariant::Contains(s),
+            indradb::PropertyPredicate::Regex(s) => crate::PropertyPredicateVariant::Regex(s),
+        };
+
+        crate::PropertyPredicate {
+            predicate: Some(variant),
+        }
+    }
+}
+
+impl TryInto<indradb::PropertyPredicate> for crate::PropertyPredicate {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::PropertyPredicate, Self::Error> {
+        Ok(match required_field("predicate", self.predicate)? {
+            crate::PropertyPredicateVariant::Equal(v) => indradb::PropertyPredicate::Equal(v.try_into()?),
+            crate::PropertyPredicateVariant::NotEqual(v) => indradb::PropertyPredicate::NotEqual(v.try_into()?),
+            crate::PropertyPredicateVariant::GreaterThan(v) => indradb::PropertyPredicate::GreaterThan(v.try_into()?),
+            crate::PropertyPredicateVariant::LessThan(v) => indradb::PropertyPredicate::LessThan(v.try_into()?),
+            crate::PropertyPredicateVariant::GreaterOrEqual(v) => {
+                indradb::PropertyPredicate::GreaterOrEqual(v.try_into()?)
+            }
+            crate::PropertyPredicateVariant::LessOrEqual(v) => indradb::PropertyPredicate::LessOrEqual(v.try_into()?),
+            crate::PropertyPredicateVariant::Contains(s) => indradb::PropertyPredicate::Contains(s),
+            crate::PropertyPredicateVariant::Regex(s) => indradb::PropertyPredicate::Regex(s),
+        })
+    }
+}
+
 impl From<indradb::QueryOutputValue> for crate::QueryOutputValue {
     fn from(output: indradb::QueryOutputValue) -> Self {
         let variant = match output {
@@ -380,7 +651,10 @@ impl From<indradb::QueryOutputValue> for crate::QueryOutputValue {
             }
         };
 
-        crate::QueryOutputValue { value: Some(variant) }
+        crate::QueryOutputValue {
+            value: Some(variant),
+            truncated: false,
+        }
     }
 }
 
@@ -425,6 +699,7 @@ impl From<indradb::EdgeDirection> for crate::EdgeDirection {
         match direction {
             indradb::EdgeDirection::Outbound => crate::EdgeDirection::Outbound,
             indradb::EdgeDirection::Inbound => crate::EdgeDirection::Inbound,
+            indradb::EdgeDirection::Both => crate::EdgeDirection::Both,
         }
     }
 }
@@ -434,6 +709,25 @@ impl From<crate::EdgeDirection> for indradb::EdgeDirection {
         match direction {
             crate::EdgeDirection::Outbound => indradb::EdgeDirection::Outbound,
             crate::EdgeDirection::Inbound => indradb::EdgeDirection::Inbound,
+            crate::EdgeDirection::Both => indradb::EdgeDirection::Both,
+        }
+    }
+}
+
+impl From<indradb::RangeDirection> for crate::RangeDirection {
+    fn from(direction: indradb::RangeDirection) -> Self {
+        match direction {
+            indradb::RangeDirection::Ascending => crate::RangeDirection::Ascending,
+            indradb::RangeDirection::Descending => crate::RangeDirection::Descending,
+        }
+    }
+}
+
+impl From<crate::RangeDirection> for indradb::RangeDirection {
+    fn from(direction: crate::RangeDirection) -> Self {
+        match direction {
+            crate::RangeDirection::Ascending => indradb::RangeDirection::Ascending,
+            crate::RangeDirection::Descending => indradb::RangeDirection::Descending,
         }
     }
 }
@@ -588,6 +882,38 @@ impl TryInto<indradb::BulkInsertItem> for crate::BulkInsertItem {
     }
 }
 
+impl From<indradb::BulkDeleteItem> for crate::BulkDeleteItem {
+    fn from(item: indradb::BulkDeleteItem) -> Self {
+        crate::BulkDeleteItem {
+            item: Some(match item {
+                indradb::BulkDeleteItem::Vertex(id) => crate::BulkDeleteItemVariant::Vertex(id.into()),
+                indradb::BulkDeleteItem::Edge(edge) => crate::BulkDeleteItemVariant::Edge(edge.into()),
+                indradb::BulkDeleteItem::Properties(q, name) => {
+                    crate::BulkDeleteItemVariant::Properties(crate::PropertiesBulkDeleteItem {
+                        q: Some(q.into()),
+                        name: Some(name.into()),
+                    })
+                }
+            }),
+        }
+    }
+}
+
+impl TryInto<indradb::BulkDeleteItem> for crate::BulkDeleteItem {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::BulkDeleteItem, Self::Error> {
+        Ok(match required_field("item", self.item)? {
+            crate::BulkDeleteItemVariant::Vertex(id) => indradb::BulkDeleteItem::Vertex(id.try_into()?),
+            crate::BulkDeleteItemVariant::Edge(edge) => indradb::BulkDeleteItem::Edge(edge.try_into()?),
+            crate::BulkDeleteItemVariant::Properties(item) => indradb::BulkDeleteItem::Properties(
+                required_field("q", item.q)?.try_into()?,
+                required_field("name", item.name)?.try_into()?,
+            ),
+        })
+    }
+}
+
 impl TryInto<indradb::Identifier> for crate::IndexPropertyRequest {
     type Error = ConversionError;
 
@@ -597,6 +923,106 @@ impl TryInto<indradb::Identifier> for crate::IndexPropertyRequest {
     }
 }
 
+impl TryInto<indradb::Identifier> for crate::DropIndexRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Identifier, Self::Error> {
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok(name)
+    }
+}
+
+impl TryInto<(indradb::Query, indradb::Identifier)> for crate::GroupCountByPropertyRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Query, indradb::Identifier), Self::Error> {
+        let q = required_field("q", self.q)?.try_into()?;
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok((q, name))
+    }
+}
+
+impl From<(indradb::Query, indradb::Identifier)> for crate::GroupCountByPropertyRequest {
+    fn from(value: (indradb::Query, indradb::Identifier)) -> Self {
+        crate::GroupCountByPropertyRequest {
+            q: Some(value.0.into()),
+            name: Some(value.1.into()),
+        }
+    }
+}
+
+impl
+    TryInto<(
+        indradb::Query,
+        indradb::Identifier,
+        Option<indradb::Json>,
+        indradb::Json,
+    )> for crate::CompareAndSetPropertyRequest
+{
+    type Error = ConversionError;
+
+    fn try_into(
+        self,
+    ) -> Result<
+        (
+            indradb::Query,
+            indradb::Identifier,
+            Option<indradb::Json>,
+            indradb::Json,
+        ),
+        Self::Error,
+    > {
+        let q = required_field("q", self.q)?.try_into()?;
+        let name = required_field("name", self.name)?.try_into()?;
+        let expected = self.expected.map(|expected| expected.try_into()).transpose()?;
+        let new = required_field("new", self.new)?.try_into()?;
+        Ok((q, name, expected, new))
+    }
+}
+
+impl
+    From<(
+        indradb::Query,
+        indradb::Identifier,
+        Option<indradb::Json>,
+        indradb::Json,
+    )> for crate::CompareAndSetPropertyRequest
+{
+    fn from(
+        value: (
+            indradb::Query,
+            indradb::Identifier,
+            Option<indradb::Json>,
+            indradb::Json,
+        ),
+    ) -> Self {
+        crate::CompareAndSetPropertyRequest {
+            q: Some(value.0.into()),
+            name: Some(value.1.into()),
+            expected: value.2.map(|expected| expected.into()),
+            new: Some(value.3.into()),
+        }
+    }
+}
+
+impl TryInto<(indradb::Json, u64)> for crate::GroupCount {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Json, u64), Self::Error> {
+        let value = required_field("value", self.value)?.try_into()?;
+        Ok((value, self.count))
+    }
+}
+
+impl From<(indradb::Json, u64)> for crate::GroupCount {
+    fn from(value: (indradb::Json, u64)) -> Self {
+        crate::GroupCount {
+            value: Some(value.0.into()),
+            count: value.1,
+        }
+    }
+}
+
 impl TryInto<(indradb::Query, indradb::Identifier, indradb::Json)> for crate::SetPropertiesRequest {
     type Error = ConversionError;
 
@@ -617,3 +1043,418 @@ impl From<(indradb::Query, indradb::Identifier, indradb::Json)> for crate::SetPr
         }
     }
 }
+
+impl TryInto<(indradb::Query, Vec<(indradb::Identifier, indradb::Json)>)> for crate::SetPropertiesBatchRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Query, Vec<(indradb::Identifier, indradb::Json)>), Self::Error> {
+        let q = required_field("q", self.q)?.try_into()?;
+        Ok((q, properties_from_proto(self.properties)?))
+    }
+}
+
+impl From<(indradb::Query, Vec<(indradb::Identifier, indradb::Json)>)> for crate::SetPropertiesBatchRequest {
+    fn from(value: (indradb::Query, Vec<(indradb::Identifier, indradb::Json)>)) -> Self {
+        crate::SetPropertiesBatchRequest {
+            q: Some(value.0.into()),
+            properties: properties_into_proto(value.1),
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, Vec<(indradb::Identifier, indradb::Json)>)>
+    for crate::CreateVertexWithPropertiesRequest
+{
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, Vec<(indradb::Identifier, indradb::Json)>), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        Ok((t, properties_from_proto(self.properties)?))
+    }
+}
+
+impl From<(indradb::Identifier, Vec<(indradb::Identifier, indradb::Json)>)>
+    for crate::CreateVertexWithPropertiesRequest
+{
+    fn from(value: (indradb::Identifier, Vec<(indradb::Identifier, indradb::Json)>)) -> Self {
+        crate::CreateVertexWithPropertiesRequest {
+            t: Some(value.0.into()),
+            properties: properties_into_proto(value.1),
+        }
+    }
+}
+
+impl TryInto<(indradb::Vertex, Vec<(indradb::Identifier, indradb::Json)>)> for crate::UpsertVertexRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Vertex, Vec<(indradb::Identifier, indradb::Json)>), Self::Error> {
+        let vertex = required_field("vertex", self.vertex)?.try_into()?;
+        Ok((vertex, properties_from_proto(self.properties)?))
+    }
+}
+
+impl From<(indradb::Vertex, Vec<(indradb::Identifier, indradb::Json)>)> for crate::UpsertVertexRequest {
+    fn from(value: (indradb::Vertex, Vec<(indradb::Identifier, indradb::Json)>)) -> Self {
+        crate::UpsertVertexRequest {
+            vertex: Some(value.0.into()),
+            properties: properties_into_proto(value.1),
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, BTreeSet<indradb::Identifier>)> for crate::CreateVertexWithLabelsRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, BTreeSet<indradb::Identifier>), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        let labels = self
+            .labels
+            .into_iter()
+            .map(|label| label.try_into())
+            .collect::<Result<BTreeSet<indradb::Identifier>, ConversionError>>()?;
+        Ok((t, labels))
+    }
+}
+
+impl From<(indradb::Identifier, BTreeSet<indradb::Identifier>)> for crate::CreateVertexWithLabelsRequest {
+    fn from(value: (indradb::Identifier, BTreeSet<indradb::Identifier>)) -> Self {
+        crate::CreateVertexWithLabelsRequest {
+            t: Some(value.0.into()),
+            labels: value.1.into_iter().map(|label| label.into()).collect(),
+        }
+    }
+}
+
+impl TryInto<(indradb::Query, indradb::Identifier)> for crate::LabelRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Query, indradb::Identifier), Self::Error> {
+        let q = required_field("q", self.q)?.try_into()?;
+        let label = required_field("label", self.label)?.try_into()?;
+        Ok((q, label))
+    }
+}
+
+impl From<(indradb::Query, indradb::Identifier)> for crate::LabelRequest {
+    fn from(value: (indradb::Query, indradb::Identifier)) -> Self {
+        crate::LabelRequest {
+            q: Some(value.0.into()),
+            label: Some(value.1.into()),
+        }
+    }
+}
+
+impl From<BTreeSet<indradb::Identifier>> for crate::VertexLabels {
+    fn from(labels: BTreeSet<indradb::Identifier>) -> Self {
+        crate::VertexLabels {
+            labels: labels.into_iter().map(|label| label.into()).collect(),
+        }
+    }
+}
+
+impl TryInto<BTreeSet<indradb::Identifier>> for crate::VertexLabels {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<BTreeSet<indradb::Identifier>, Self::Error> {
+        self.labels.into_iter().map(|label| label.try_into()).collect()
+    }
+}
+
+impl From<Option<SystemTime>> for crate::VertexCreatedAt {
+    fn from(created_at: Option<SystemTime>) -> Self {
+        crate::VertexCreatedAt {
+            created_at: created_at.map(|created_at| created_at.into()),
+        }
+    }
+}
+
+impl TryInto<Option<SystemTime>> for crate::VertexCreatedAt {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Option<SystemTime>, Self::Error> {
+        self.created_at.map(time_from_proto).transpose()
+    }
+}
+
+impl TryInto<Vec<indradb::Query>> for crate::QueryBatch {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<indradb::Query>, Self::Error> {
+        self.queries.into_iter().map(|q| q.try_into()).collect()
+    }
+}
+
+impl From<Vec<indradb::Query>> for crate::QueryBatch {
+    fn from(qs: Vec<indradb::Query>) -> Self {
+        crate::QueryBatch {
+            queries: qs.into_iter().map(|q| q.into()).collect(),
+            readonly: false,
+        }
+    }
+}
+
+impl From<Vec<Vec<indradb::QueryOutputValue>>> for crate::QueryBatchOutputs {
+    fn from(outputs: Vec<Vec<indradb::QueryOutputValue>>) -> Self {
+        crate::QueryBatchOutputs {
+            outputs: outputs
+                .into_iter()
+                .map(|values| crate::QueryBatchOutput {
+                    values: values.into_iter().map(|value| value.into()).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryInto<Vec<Vec<indradb::QueryOutputValue>>> for crate::QueryBatchOutputs {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<Vec<indradb::QueryOutputValue>>, Self::Error> {
+        self.outputs
+            .into_iter()
+            .map(|output| output.values.into_iter().map(|value| value.try_into()).collect())
+            .collect()
+    }
+}
+
+impl From<indradb::DatastoreStats> for crate::DatastoreStats {
+    fn from(stats: indradb::DatastoreStats) -> Self {
+        crate::DatastoreStats {
+            vertex_count: stats.vertex_count,
+            edge_count: stats.edge_count,
+            index_count: stats.index_count,
+            size_bytes: stats.size_bytes,
+        }
+    }
+}
+
+impl From<crate::DatastoreStats> for indradb::DatastoreStats {
+    fn from(stats: crate::DatastoreStats) -> Self {
+        indradb::DatastoreStats {
+            vertex_count: stats.vertex_count,
+            edge_count: stats.edge_count,
+            index_count: stats.index_count,
+            size_bytes: stats.size_bytes,
+        }
+    }
+}
+
+impl From<indradb::PlanAccess> for crate::PlanAccess {
+    fn from(access: indradb::PlanAccess) -> Self {
+        match access {
+            indradb::PlanAccess::FullScan => crate::PlanAccess::FullScan,
+            indradb::PlanAccess::RangeScan => crate::PlanAccess::RangeScan,
+            indradb::PlanAccess::Lookup => crate::PlanAccess::Lookup,
+            indradb::PlanAccess::PropertyIndex => crate::PlanAccess::PropertyIndex,
+            indradb::PlanAccess::MissingIndex => crate::PlanAccess::MissingIndex,
+            indradb::PlanAccess::Index => crate::PlanAccess::Index,
+            indradb::PlanAccess::Pipe => crate::PlanAccess::Pipe,
+            indradb::PlanAccess::StoredCount => crate::PlanAccess::StoredCount,
+        }
+    }
+}
+
+impl From<crate::PlanAccess> for indradb::PlanAccess {
+    fn from(access: crate::PlanAccess) -> Self {
+        match access {
+            crate::PlanAccess::FullScan => indradb::PlanAccess::FullScan,
+            crate::PlanAccess::RangeScan => indradb::PlanAccess::RangeScan,
+            crate::PlanAccess::Lookup => indradb::PlanAccess::Lookup,
+            crate::PlanAccess::PropertyIndex => indradb::PlanAccess::PropertyIndex,
+            crate::PlanAccess::MissingIndex => indradb::PlanAccess::MissingIndex,
+            crate::PlanAccess::Index => indradb::PlanAccess::Index,
+            crate::PlanAccess::Pipe => indradb::PlanAccess::Pipe,
+            crate::PlanAccess::StoredCount => indradb::PlanAccess::StoredCount,
+        }
+    }
+}
+
+impl From<indradb::QueryPlan> for crate::QueryPlan {
+    fn from(plan: indradb::QueryPlan) -> Self {
+        let mut proto_plan = crate::QueryPlan {
+            stage: plan.stage,
+            access: 0,
+            property: plan.property.map(|name| name.into()),
+            estimated_count: plan.estimated_count,
+            inner: plan.inner.map(|inner| Box::new((*inner).into())),
+        };
+        proto_plan.set_access(plan.access.into());
+        proto_plan
+    }
+}
+
+impl TryInto<indradb::QueryPlan> for crate::QueryPlan {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::QueryPlan, Self::Error> {
+        let access = self.access().into();
+        Ok(indradb::QueryPlan {
+            stage: self.stage,
+            access,
+            property: self.property.map(|name| name.try_into()).transpose()?,
+            estimated_count: self.estimated_count,
+            inner: self.inner.map(|inner| (*inner).try_into().map(Box::new)).transpose()?,
+        })
+    }
+}
+
+impl TryInto<(Uuid, Option<indradb::Identifier>, u32)> for crate::GetVertexNeighborhoodRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(Uuid, Option<indradb::Identifier>, u32), Self::Error> {
+        let id = required_field("id", self.id)?.try_into()?;
+        let t = self.t.map(|t| t.try_into()).transpose()?;
+        Ok((id, t, self.limit))
+    }
+}
+
+impl From<(Uuid, Option<indradb::Identifier>, u32)> for crate::GetVertexNeighborhoodRequest {
+    fn from(value: (Uuid, Option<indradb::Identifier>, u32)) -> Self {
+        crate::GetVertexNeighborhoodRequest {
+            id: Some(value.0.into()),
+            t: value.1.map(|t| t.into()),
+            limit: value.2,
+        }
+    }
+}
+
+impl From<Option<indradb::Neighborhood>> for crate::VertexNeighborhood {
+    fn from(neighborhood: Option<indradb::Neighborhood>) -> Self {
+        match neighborhood {
+            Some(neighborhood) => crate::VertexNeighborhood {
+                vertex: Some(neighborhood.vertex.into()),
+                outbound: neighborhood.outbound.into_iter().map(|edge| edge.into()).collect(),
+                inbound: neighborhood.inbound.into_iter().map(|edge| edge.into()).collect(),
+            },
+            None => crate::VertexNeighborhood::default(),
+        }
+    }
+}
+
+impl TryInto<Option<indradb::Neighborhood>> for crate::VertexNeighborhood {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Option<indradb::Neighborhood>, Self::Error> {
+        let vertex = match self.vertex {
+            Some(vertex) => vertex.try_into()?,
+            None => return Ok(None),
+        };
+        let outbound: Result<Vec<indradb::Edge>, ConversionError> =
+            self.outbound.into_iter().map(|edge| edge.try_into()).collect();
+        let inbound: Result<Vec<indradb::Edge>, ConversionError> =
+            self.inbound.into_iter().map(|edge| edge.try_into()).collect();
+        Ok(Some(indradb::Neighborhood {
+            vertex,
+            outbound: outbound?,
+            inbound: inbound?,
+        }))
+    }
+}
+
+impl TryInto<(Uuid, Option<indradb::Identifier>)> for crate::GetEdgeCountsRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(Uuid, Option<indradb::Identifier>), Self::Error> {
+        let id = required_field("id", self.id)?.try_into()?;
+        let t = self.t.map(|t| t.try_into()).transpose()?;
+        Ok((id, t))
+    }
+}
+
+impl From<(Uuid, Option<indradb::Identifier>)> for crate::GetEdgeCountsRequest {
+    fn from(value: (Uuid, Option<indradb::Identifier>)) -> Self {
+        crate::GetEdgeCountsRequest {
+            id: Some(value.0.into()),
+            t: value.1.map(|t| t.into()),
+        }
+    }
+}
+
+impl From<(u64, u64)> for crate::EdgeCounts {
+    fn from(value: (u64, u64)) -> Self {
+        crate::EdgeCounts {
+            outbound: value.0,
+            inbound: value.1,
+        }
+    }
+}
+
+impl From<crate::EdgeCounts> for (u64, u64) {
+    fn from(counts: crate::EdgeCounts) -> Self {
+        (counts.outbound, counts.inbound)
+    }
+}
+
+impl From<indradb::ChangeFilter> for crate::WatchRequest {
+    fn from(filter: indradb::ChangeFilter) -> Self {
+        crate::WatchRequest {
+            vertex_types: filter.vertex_types.into_iter().map(|t| t.into()).collect(),
+            edge_types: filter.edge_types.into_iter().map(|t| t.into()).collect(),
+        }
+    }
+}
+
+impl TryInto<indradb::ChangeFilter> for crate::WatchRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::ChangeFilter, Self::Error> {
+        let vertex_types: Result<Vec<indradb::Identifier>, ConversionError> =
+            self.vertex_types.into_iter().map(|t| t.try_into()).collect();
+        let edge_types: Result<Vec<indradb::Identifier>, ConversionError> =
+            self.edge_types.into_iter().map(|t| t.try_into()).collect();
+        Ok(indradb::ChangeFilter {
+            vertex_types: vertex_types?,
+            edge_types: edge_types?,
+        })
+    }
+}
+
+impl From<indradb::ChangeEvent> for crate::ChangeEvent {
+    fn from(event: indradb::ChangeEvent) -> Self {
+        crate::ChangeEvent {
+            event: Some(match event {
+                indradb::ChangeEvent::VertexCreated(vertex) => crate::ChangeEventVariant::VertexCreated(vertex.into()),
+                indradb::ChangeEvent::VertexDeleted(vertex) => crate::ChangeEventVariant::VertexDeleted(vertex.into()),
+                indradb::ChangeEvent::EdgeCreated(edge) => crate::ChangeEventVariant::EdgeCreated(edge.into()),
+                indradb::ChangeEvent::EdgeDeleted(edge) => crate::ChangeEventVariant::EdgeDeleted(edge.into()),
+                indradb::ChangeEvent::VertexPropertySet(vertex, name, value) => {
+                    crate::ChangeEventVariant::VertexPropertySet(crate::VertexPropertySetEvent {
+                        vertex: Some(vertex.into()),
+                        name: Some(name.into()),
+                        value: Some(value.into()),
+                    })
+                }
+                indradb::ChangeEvent::EdgePropertySet(edge, name, value) => {
+                    crate::ChangeEventVariant::EdgePropertySet(crate::EdgePropertySetEvent {
+                        edge: Some(edge.into()),
+                        name: Some(name.into()),
+                        value: Some(value.into()),
+                    })
+                }
+            }),
+        }
+    }
+}
+
+impl TryInto<indradb::ChangeEvent> for crate::ChangeEvent {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::ChangeEvent, Self::Error> {
+        Ok(match required_field("event", self.event)? {
+            crate::ChangeEventVariant::VertexCreated(vertex) => indradb::ChangeEvent::VertexCreated(vertex.try_into()?),
+            crate::ChangeEventVariant::VertexDeleted(vertex) => indradb::ChangeEvent::VertexDeleted(vertex.try_into()?),
+            crate::ChangeEventVariant::EdgeCreated(edge) => indradb::ChangeEvent::EdgeCreated(edge.try_into()?),
+            crate::ChangeEventVariant::EdgeDeleted(edge) => indradb::ChangeEvent::EdgeDeleted(edge.try_into()?),
+            crate::ChangeEventVariant::VertexPropertySet(event) => indradb::ChangeEvent::VertexPropertySet(
+                required_field("vertex", event.vertex)?.try_into()?,
+                required_field("name", event.name)?.try_into()?,
+                required_field("value", event.value)?.try_into()?,
+            ),
+            crate::ChangeEventVariant::EdgePropertySet(event) => indradb::ChangeEvent::EdgePropertySet(
+                required_field("edge", event.edge)?.try_into()?,
+                required_field("name", event.name)?.try_into()?,
+                required_field("value", event.value)?.try_into()?,
+            ),
+        })
+    }
+}
@@ -4,8 +4,11 @@ extern crate indradb;
 
 tonic::include_proto!("indradb");
 
+pub use bulk_delete_item::Item as BulkDeleteItemVariant;
 pub use bulk_insert_item::Item as BulkInsertItemVariant;
+pub use change_event::Event as ChangeEventVariant;
 pub use indra_db_client::IndraDbClient as ProtoClient;
+pub use property_predicate::Predicate as PropertyPredicateVariant;
 pub use query::Query as QueryVariant;
 pub use query_output_value::Value as QueryOutputValueVariant;
 
@@ -15,7 +18,13 @@ pub use converters::*;
 #[cfg(feature = "client")]
 mod client;
 #[cfg(feature = "client")]
-pub use client::{Client, ClientError};
+pub use client::{Client, ClientError, RetryConfig};
+#[cfg(feature = "client")]
+mod pool;
+#[cfg(feature = "client")]
+pub use pool::ClientPool;
+#[cfg(feature = "tls")]
+pub use tonic::transport::{Certificate, ClientTlsConfig, Identity};
 
 #[cfg(feature = "server")]
 mod server;
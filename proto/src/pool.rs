@@ -0,0 +1,141 @@
+use std::cmp::max;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Client, ClientError, RetryConfig};
+
+#[cfg(feature = "compression")]
+use tonic::codec::CompressionEncoding;
+#[cfg(feature = "tls")]
+use tonic::transport::ClientTlsConfig;
+use tonic::transport::Endpoint;
+
+/// A pool of clients, each with its own connection to the server.
+///
+/// A `Client` multiplexes all of its requests over one HTTP/2 connection, so
+/// under high concurrency, requests can queue up behind the connection's
+/// stream limit. The pool spreads requests over several connections instead:
+/// `client` hands out clients bound to each connection in turn, and those
+/// have the full `Client` interface. Clones of the pool share its
+/// connections.
+#[derive(Clone)]
+pub struct ClientPool {
+    clients: Vec<Client>,
+    next: Arc<AtomicUsize>,
+}
+
+impl ClientPool {
+    /// Creates a new pool.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The server endpoint.
+    /// * `size`: The number of connections to open. A size of 0 is treated
+    ///   as 1.
+    pub async fn new(endpoint: Endpoint, size: usize) -> Result<Self, ClientError> {
+        let mut clients = Vec::with_capacity(max(size, 1));
+        for _ in 0..max(size, 1) {
+            clients.push(Client::new(endpoint.clone()).await?);
+        }
+        Ok(Self::from_clients(clients))
+    }
+
+    /// Creates a new pool that connects over TLS. See `Client::new_tls`.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The server endpoint.
+    /// * `size`: The number of connections to open. A size of 0 is treated
+    ///   as 1.
+    /// * `tls`: The TLS config.
+    #[cfg(feature = "tls")]
+    pub async fn new_tls(endpoint: Endpoint, size: usize, tls: ClientTlsConfig) -> Result<Self, ClientError> {
+        Self::new(endpoint.tls_config(tls)?, size).await
+    }
+
+    /// Creates a new pool of clients that retry requests. See
+    /// `Client::with_retry`.
+    ///
+    /// # Arguments
+    /// * `endpoint`: The server endpoint.
+    /// * `size`: The number of connections to open. A size of 0 is treated
+    ///   as 1.
+    /// * `config`: How to retry requests.
+    pub async fn with_retry(endpoint: Endpoint, size: usize, config: RetryConfig) -> Result<Self, ClientError> {
+        let mut clients = Vec::with_capacity(max(size, 1));
+        for _ in 0..max(size, 1) {
+            clients.push(Client::with_retry(endpoint.clone(), config.clone()).await?);
+        }
+        Ok(Self::from_clients(clients))
+    }
+
+    fn from_clients(clients: Vec<Client>) -> Self {
+        Self {
+            clients,
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Gets the number of connections in the pool.
+    pub fn size(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Gets a client, bound to the next connection in the pool. Clients are
+    /// cheap to get, so a new one can be gotten for every request.
+    pub fn client(&self) -> Client {
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len();
+        self.clients[i].clone()
+    }
+
+    /// Sets the request timeout of every client in the pool. See
+    /// `Client::set_timeout`.
+    ///
+    /// # Arguments
+    /// * `timeout`: The request timeout.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        for client in &mut self.clients {
+            client.set_timeout(timeout);
+        }
+    }
+
+    /// Sets the compression of every client in the pool. See
+    /// `Client::set_compression`.
+    ///
+    /// # Arguments
+    /// * `compression`: The compression to use.
+    #[cfg(feature = "compression")]
+    pub fn set_compression(&mut self, compression: CompressionEncoding) {
+        for client in &mut self.clients {
+            client.set_compression(compression);
+        }
+    }
+
+    /// Sets how many items bulk inserts buffer on every client in the pool.
+    /// See `Client::set_bulk_insert_capacity`.
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of items to buffer.
+    pub fn set_bulk_insert_capacity(&mut self, capacity: usize) {
+        for client in &mut self.clients {
+            client.set_bulk_insert_capacity(capacity);
+        }
+    }
+
+    /// Sets the bearer token of every client in the pool. See
+    /// `Client::set_auth_token`.
+    ///
+    /// # Arguments
+    /// * `token`: The bearer token.
+    ///
+    /// # Errors
+    /// Returns `ClientError::InvalidAuthToken` if the token can't be sent in
+    /// a header, in which case no client is changed.
+    pub fn set_auth_token(&mut self, token: Option<String>) -> Result<(), ClientError> {
+        let mut clients = self.clients.clone();
+        for client in &mut clients {
+            client.set_auth_token(token.clone())?;
+        }
+        self.clients = clients;
+        Ok(())
+    }
+}
@@ -4,26 +4,86 @@ use std::error::Error as StdError;
 use std::fmt;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
+use indradb::Transaction;
 use libloading::Library;
+use prost::encoding::encoded_len_varint;
+use prost::Message;
 use tokio::net::TcpListener;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
 use tokio_stream::{Stream, StreamExt};
+#[cfg(feature = "compression")]
+use tonic::codec::CompressionEncoding;
+use tonic::metadata::MetadataValue;
 use tonic::transport::{Error as TonicTransportError, Server as TonicServer};
 use tonic::{Request, Response, Status, Streaming};
 
 const CHANNEL_CAPACITY: usize = 100;
+const BULK_BATCH_SIZE: usize = 1000;
+// How often watch streams check whether the client has gone away.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+// An upper bound on the bytes an output value takes up other than its items,
+// e.g. for its truncated flag.
+const OUTPUT_VALUE_OVERHEAD: usize = 16;
+const COMPRESSION_MIN_BYTES: usize = 1024;
+
+// Drops items from the end of a list until it fits in `budget` bytes once
+// encoded, other than the first item. Returns whether any were dropped.
+fn truncate_items<T: Message>(items: &mut Vec<T>, budget: usize) -> bool {
+    let mut total = 0;
+    let mut len = 0;
+    for item in items.iter() {
+        // Each item is encoded with a one byte tag and its length
+        let item_len = item.encoded_len();
+        total += 1 + encoded_len_varint(item_len as u64) + item_len;
+        if total > budget && len > 0 {
+            break;
+        }
+        len += 1;
+    }
+    if len < items.len() {
+        items.truncate(len);
+        true
+    } else {
+        false
+    }
+}
 
-fn send(tx: &mpsc::Sender<Result<crate::QueryOutputValue, Status>>, result: Result<crate::QueryOutputValue, Status>) {
-    if let Err(err) = tx.blocking_send(result) {
-        eprintln!("could not send message to client: {err}");
+// Truncates an output value so that it fits in `max_bytes` once encoded, and
+// marks it as truncated if so.
+fn truncate_output(value: &mut crate::QueryOutputValue, max_bytes: usize) {
+    if value.encoded_len() <= max_bytes {
+        return;
     }
+    let budget = max_bytes.saturating_sub(OUTPUT_VALUE_OVERHEAD);
+    value.truncated = match value.value {
+        Some(crate::QueryOutputValueVariant::Vertices(ref mut v)) => truncate_items(&mut v.vertices, budget),
+        Some(crate::QueryOutputValueVariant::Edges(ref mut e)) => truncate_items(&mut e.edges, budget),
+        Some(crate::QueryOutputValueVariant::VertexProperties(ref mut p)) => {
+            truncate_items(&mut p.vertex_properties, budget)
+        }
+        Some(crate::QueryOutputValueVariant::EdgeProperties(ref mut p)) => {
+            truncate_items(&mut p.edge_properties, budget)
+        }
+        Some(crate::QueryOutputValueVariant::Count(_)) | None => false,
+    };
 }
 
 fn map_indradb_result<T>(res: Result<T, indradb::Error>) -> Result<T, Status> {
-    res.map_err(|err| Status::internal(format!("{err}")))
+    res.map_err(|err| {
+        let mut status = Status::internal(format!("{err}"));
+        if let Some(kind) = crate::error_kind(&err) {
+            status
+                .metadata_mut()
+                .insert(crate::ERROR_KIND_METADATA_KEY, MetadataValue::from_static(kind));
+        }
+        status
+    })
 }
 
 fn map_conversion_result<T>(res: Result<T, crate::ConversionError>) -> Result<T, Status> {
@@ -35,6 +95,43 @@ fn map_jh_indra_result<T>(res: Result<Result<T, indradb::Error>, tokio::task::Jo
     map_indradb_result(jh_res)
 }
 
+fn bulk_insert_failure(index: u64, status: Status) -> crate::BulkInsertFailure {
+    crate::BulkInsertFailure {
+        index,
+        code: status.code() as i32,
+        message: status.message().to_string(),
+    }
+}
+
+// Inserts a batch of items, returning the ones that failed.
+fn bulk_insert_lenient_batch<D: indradb::Datastore>(
+    db: &indradb::Database<D>,
+    batch: Vec<(u64, indradb::BulkInsertItem)>,
+) -> Vec<crate::BulkInsertFailure> {
+    let items = batch.iter().map(|(_, item)| item.clone()).collect();
+    if db.bulk_insert(items).is_ok() {
+        return Vec::new();
+    }
+
+    // Something in the batch failed, so insert the items one at a time to
+    // find out which. Inserts are idempotent, so this is safe even if the
+    // datastore partially applied the batch.
+    batch
+        .into_iter()
+        .filter_map(|(index, item)| match map_indradb_result(db.bulk_insert(vec![item])) {
+            Ok(()) => None,
+            Err(status) => Some(bulk_insert_failure(index, status)),
+        })
+        .collect()
+}
+
+async fn bulk_delete_batch<D: indradb::Datastore + Send + Sync + 'static>(
+    db: Arc<indradb::Database<D>>,
+    items: Vec<indradb::BulkDeleteItem>,
+) -> Result<(), Status> {
+    map_jh_indra_result(tokio::task::spawn_blocking(move || db.bulk_delete(items)).await)
+}
+
 /// An error that occurred while initializing the server with plugins enabled.
 #[derive(Debug)]
 pub enum InitError {
@@ -127,6 +224,9 @@ struct Plugins {
 pub struct Server<D: indradb::Datastore + Send + Sync + 'static> {
     db: Arc<indradb::Database<D>>,
     plugins: Arc<Plugins>,
+    clear_enabled: bool,
+    max_response_bytes: Option<usize>,
+    compression_min_bytes: usize,
 }
 
 impl<D: indradb::Datastore + Send + Sync + 'static> Server<D> {
@@ -138,9 +238,65 @@ impl<D: indradb::Datastore + Send + Sync + 'static> Server<D> {
         Self {
             db,
             plugins: Arc::new(Plugins::default()),
+            clear_enabled: true,
+            max_response_bytes: None,
+            compression_min_bytes: COMPRESSION_MIN_BYTES,
         }
     }
 
+    /// Sets whether clients can clear the database. This is enabled by
+    /// default; disable it in production to prevent accidental data loss.
+    ///
+    /// # Arguments
+    /// * `enabled`: Whether clearing is enabled.
+    pub fn set_clear_enabled(&mut self, enabled: bool) {
+        self.clear_enabled = enabled;
+    }
+
+    /// Sets the most bytes that each output value streamed by `Get` may take
+    /// up once encoded, so that responses stay within the client's message
+    /// size limit - 4MiB by default in tonic. Values that would exceed it
+    /// have items dropped from the end, and are marked as truncated. This
+    /// applies alongside the item limits of queries; whichever is hit first
+    /// wins. An item that's larger than the limit on its own is still sent,
+    /// since items can't be split. If `None`, which is the default, output
+    /// values aren't limited.
+    ///
+    /// # Arguments
+    /// * `max_bytes`: The most bytes per output value.
+    pub fn set_max_response_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_response_bytes = max_bytes;
+    }
+
+    /// Sets the fewest bytes a response must take up once encoded to be
+    /// compressed, for clients that accept compressed responses. Smaller
+    /// responses are sent uncompressed, since compressing them costs more
+    /// than it saves. For streamed responses, this applies to the whole
+    /// stream. Defaults to 1KiB.
+    ///
+    /// # Arguments
+    /// * `min_bytes`: The fewest bytes to compress.
+    pub fn set_compression_min_bytes(&mut self, min_bytes: usize) {
+        self.compression_min_bytes = min_bytes;
+    }
+
+    // Wraps a message in a response, disabling compression if the message
+    // is smaller than the compression threshold.
+    fn respond<T: Message>(&self, message: T) -> Response<T> {
+        let len = message.encoded_len();
+        self.respond_with_len(message, len)
+    }
+
+    // Like `respond`, but for messages whose length is found separately,
+    // e.g. streams.
+    fn respond_with_len<T>(&self, message: T, len: usize) -> Response<T> {
+        let mut res = Response::new(message);
+        if len < self.compression_min_bytes {
+            disable_compression(&mut res);
+        }
+        res
+    }
+
     /// Creates a new server with plugins enabled.
     ///
     /// # Arguments
@@ -187,6 +343,9 @@ impl<D: indradb::Datastore + Send + Sync + 'static> Server<D> {
                 _libraries: libraries,
                 entries: plugin_entries,
             }),
+            clear_enabled: true,
+            max_response_bytes: None,
+            compression_min_bytes: COMPRESSION_MIN_BYTES,
         })
     }
 }
@@ -194,20 +353,26 @@ impl<D: indradb::Datastore + Send + Sync + 'static> Server<D> {
 #[tonic::async_trait]
 impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::IndraDb for Server<D> {
     async fn ping(&self, _: Request<()>) -> Result<Response<()>, Status> {
-        Ok(Response::new(()))
+        Ok(self.respond(()))
     }
 
     async fn sync(&self, _: Request<()>) -> Result<Response<()>, Status> {
         let db = self.db.clone();
         map_jh_indra_result(tokio::task::spawn_blocking(move || db.sync()).await)?;
-        Ok(Response::new(()))
+        Ok(self.respond(()))
+    }
+
+    async fn compact(&self, _: Request<()>) -> Result<Response<()>, Status> {
+        let db = self.db.clone();
+        map_jh_indra_result(tokio::task::spawn_blocking(move || db.compact()).await)?;
+        Ok(self.respond(()))
     }
 
     async fn create_vertex(&self, request: Request<crate::Vertex>) -> Result<Response<crate::CreateResponse>, Status> {
         let db = self.db.clone();
         let vertex = map_conversion_result(request.into_inner().try_into())?;
         let res = map_jh_indra_result(tokio::task::spawn_blocking(move || db.create_vertex(&vertex)).await)?;
-        Ok(Response::new(crate::CreateResponse { created: res }))
+        Ok(self.respond(crate::CreateResponse { created: res }))
     }
 
     async fn create_vertex_from_type(
@@ -217,45 +382,271 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         let db = self.db.clone();
         let t = map_conversion_result(request.into_inner().try_into())?;
         let res = map_jh_indra_result(tokio::task::spawn_blocking(move || db.create_vertex_from_type(t)).await)?;
-        Ok(Response::new(res.into()))
+        Ok(self.respond(res.into()))
+    }
+
+    async fn create_vertex_with_properties(
+        &self,
+        request: Request<crate::CreateVertexWithPropertiesRequest>,
+    ) -> Result<Response<crate::Uuid>, Status> {
+        let db = self.db.clone();
+        let (t, props) = map_conversion_result(request.into_inner().try_into())?;
+        let res =
+            map_jh_indra_result(tokio::task::spawn_blocking(move || db.create_vertex_with_properties(t, props)).await)?;
+        Ok(self.respond(res.into()))
+    }
+
+    async fn create_vertex_with_labels(
+        &self,
+        request: Request<crate::CreateVertexWithLabelsRequest>,
+    ) -> Result<Response<crate::Uuid>, Status> {
+        let db = self.db.clone();
+        let (t, labels) = map_conversion_result(request.into_inner().try_into())?;
+        let res =
+            map_jh_indra_result(tokio::task::spawn_blocking(move || db.create_vertex_with_labels(t, labels)).await)?;
+        Ok(self.respond(res.into()))
+    }
+
+    async fn upsert_vertex(
+        &self,
+        request: Request<crate::UpsertVertexRequest>,
+    ) -> Result<Response<crate::CreateResponse>, Status> {
+        let db = self.db.clone();
+        let (vertex, props) = map_conversion_result(request.into_inner().try_into())?;
+        let res = map_jh_indra_result(tokio::task::spawn_blocking(move || db.upsert_vertex(&vertex, props)).await)?;
+        Ok(self.respond(crate::CreateResponse { created: res }))
     }
 
     async fn create_edge(&self, request: Request<crate::Edge>) -> Result<Response<crate::CreateResponse>, Status> {
         let db = self.db.clone();
         let edge = map_conversion_result(request.into_inner().try_into())?;
         let res = map_jh_indra_result(tokio::task::spawn_blocking(move || db.create_edge(&edge)).await)?;
-        Ok(Response::new(crate::CreateResponse { created: res }))
+        Ok(self.respond(crate::CreateResponse { created: res }))
+    }
+
+    async fn create_edge_if_absent(
+        &self,
+        request: Request<crate::Edge>,
+    ) -> Result<Response<crate::CreateResponse>, Status> {
+        let db = self.db.clone();
+        let edge = map_conversion_result(request.into_inner().try_into())?;
+        let res = map_jh_indra_result(tokio::task::spawn_blocking(move || db.create_edge_if_absent(&edge)).await)?;
+        Ok(self.respond(crate::CreateResponse { created: res }))
     }
 
     type GetStream = Pin<Box<dyn Stream<Item = Result<crate::QueryOutputValue, Status>> + Send + Sync + 'static>>;
     async fn get(&self, request: Request<crate::Query>) -> Result<Response<Self::GetStream>, Status> {
+        let db = self.db.clone();
+        let request = request.into_inner();
+        let readonly = request.readonly;
+        let q: indradb::Query = map_conversion_result(request.try_into())?;
+        let max_response_bytes = self.max_response_bytes;
+        // The whole output is gotten before responding, so that compression
+        // can be skipped for small outputs
+        let results: Vec<Result<crate::QueryOutputValue, Status>> = tokio::task::spawn_blocking(move || {
+            let output = if readonly {
+                db.get(q)
+            } else {
+                indradb::get_in_txn(&db.datastore.transaction(), q)
+            };
+            match map_indradb_result(output) {
+                Ok(output) => output
+                    .into_iter()
+                    .map(|output| {
+                        let mut output: crate::QueryOutputValue = output.into();
+                        if let Some(max_bytes) = max_response_bytes {
+                            truncate_output(&mut output, max_bytes);
+                        }
+                        Ok(output)
+                    })
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            }
+        })
+        .await
+        .map_err(|err| Status::internal(format!("{err}")))?;
+
+        let len = results
+            .iter()
+            .map(|result| result.as_ref().map_or(0, Message::encoded_len))
+            .sum();
+        let stream: Self::GetStream = Box::pin(tokio_stream::iter(results));
+        Ok(self.respond_with_len(stream, len))
+    }
+
+    async fn explain(&self, request: Request<crate::Query>) -> Result<Response<crate::QueryPlan>, Status> {
         let db = self.db.clone();
         let q: indradb::Query = map_conversion_result(request.into_inner().try_into())?;
-        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
-        tokio::task::spawn_blocking(move || match map_indradb_result(db.get(q)) {
-            Ok(output) => {
-                for output in output.into_iter() {
-                    send(&tx, Ok(output.into()));
+        let plan = map_jh_indra_result(tokio::task::spawn_blocking(move || db.explain(q)).await)?;
+        Ok(self.respond(plan.into()))
+    }
+
+    async fn get_batch(
+        &self,
+        request: Request<crate::QueryBatch>,
+    ) -> Result<Response<crate::QueryBatchOutputs>, Status> {
+        let db = self.db.clone();
+        let request = request.into_inner();
+        let readonly = request.readonly;
+        let qs: Vec<indradb::Query> = map_conversion_result(request.try_into())?;
+        let outputs = map_jh_indra_result(
+            tokio::task::spawn_blocking(move || {
+                if readonly {
+                    db.get_batch(qs)
+                } else {
+                    indradb::get_batch_in_txn(&db.datastore.transaction(), qs)
                 }
-            }
-            Err(err) => send(&tx, Err(err)),
-        });
+            })
+            .await,
+        )?;
+        Ok(self.respond(outputs.into()))
+    }
 
-        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    async fn get_vertex_count_by_type(
+        &self,
+        request: Request<crate::Identifier>,
+    ) -> Result<Response<crate::CountResponse>, Status> {
+        let db = self.db.clone();
+        let t = map_conversion_result(request.into_inner().try_into())?;
+        let count = map_jh_indra_result(tokio::task::spawn_blocking(move || db.get_vertex_count_by_type(t)).await)?;
+        Ok(self.respond(crate::CountResponse { count }))
+    }
+
+    async fn get_vertex_neighborhood(
+        &self,
+        request: Request<crate::GetVertexNeighborhoodRequest>,
+    ) -> Result<Response<crate::VertexNeighborhood>, Status> {
+        let db = self.db.clone();
+        let (id, t, limit) = map_conversion_result(request.into_inner().try_into())?;
+        let neighborhood =
+            map_jh_indra_result(tokio::task::spawn_blocking(move || db.get_vertex_neighborhood(id, t, limit)).await)?;
+        Ok(self.respond(neighborhood.into()))
+    }
+
+    async fn get_edge_counts(
+        &self,
+        request: Request<crate::GetEdgeCountsRequest>,
+    ) -> Result<Response<crate::EdgeCounts>, Status> {
+        let db = self.db.clone();
+        let (id, t) = map_conversion_result(request.into_inner().try_into())?;
+        let counts = map_jh_indra_result(tokio::task::spawn_blocking(move || db.get_edge_counts(id, t)).await)?;
+        Ok(self.respond(counts.into()))
+    }
+
+    async fn get_vertex_labels(&self, request: Request<crate::Uuid>) -> Result<Response<crate::VertexLabels>, Status> {
+        let db = self.db.clone();
+        let id = map_conversion_result(request.into_inner().try_into())?;
+        let labels = map_jh_indra_result(tokio::task::spawn_blocking(move || db.get_vertex_labels(id)).await)?;
+        Ok(self.respond(labels.into()))
+    }
+
+    async fn get_vertex_created_at(
+        &self,
+        request: Request<crate::Uuid>,
+    ) -> Result<Response<crate::VertexCreatedAt>, Status> {
+        let db = self.db.clone();
+        let id = map_conversion_result(request.into_inner().try_into())?;
+        let created_at = map_jh_indra_result(tokio::task::spawn_blocking(move || db.get_vertex_created_at(id)).await)?;
+        Ok(self.respond(created_at.into()))
+    }
+
+    async fn get_property_names(&self, _: Request<()>) -> Result<Response<crate::PropertyNames>, Status> {
+        let db = self.db.clone();
+        let (vertex_property_names, edge_property_names) = map_jh_indra_result(
+            tokio::task::spawn_blocking(move || Ok((db.get_vertex_property_names()?, db.get_edge_property_names()?)))
+                .await,
+        )?;
+        Ok(self.respond(crate::PropertyNames {
+            vertex_property_names: vertex_property_names.into_iter().map(|name| name.into()).collect(),
+            edge_property_names: edge_property_names.into_iter().map(|name| name.into()).collect(),
+        }))
     }
 
-    async fn delete(&self, request: Request<crate::Query>) -> Result<Response<()>, Status> {
+    async fn get_indexed_properties(&self, _: Request<()>) -> Result<Response<crate::IndexedProperties>, Status> {
+        let db = self.db.clone();
+        let names = map_jh_indra_result(tokio::task::spawn_blocking(move || db.get_indexed_properties()).await)?;
+        Ok(self.respond(crate::IndexedProperties {
+            names: names.into_iter().map(|name| name.into()).collect(),
+        }))
+    }
+
+    async fn get_stats(&self, _: Request<()>) -> Result<Response<crate::DatastoreStats>, Status> {
+        let db = self.db.clone();
+        let stats = map_jh_indra_result(tokio::task::spawn_blocking(move || db.get_stats()).await)?;
+        Ok(self.respond(stats.into()))
+    }
+
+    async fn group_count_by_property(
+        &self,
+        request: Request<crate::GroupCountByPropertyRequest>,
+    ) -> Result<Response<crate::GroupCountByPropertyResponse>, Status> {
+        let db = self.db.clone();
+        let (q, name) = map_conversion_result(request.into_inner().try_into())?;
+        let counts =
+            map_jh_indra_result(tokio::task::spawn_blocking(move || db.group_count_by_property(q, name)).await)?;
+        Ok(self.respond(crate::GroupCountByPropertyResponse {
+            counts: counts.into_iter().map(|count| count.into()).collect(),
+        }))
+    }
+
+    async fn delete(&self, request: Request<crate::Query>) -> Result<Response<crate::DeleteResponse>, Status> {
         let db = self.db.clone();
         let q: indradb::Query = map_conversion_result(request.into_inner().try_into())?;
-        map_jh_indra_result(tokio::task::spawn_blocking(move || db.delete(q)).await)?;
-        Ok(Response::new(()))
+        let count = map_jh_indra_result(tokio::task::spawn_blocking(move || db.delete(q)).await)?;
+        Ok(self.respond(crate::DeleteResponse { count }))
+    }
+
+    async fn delete_all_properties(
+        &self,
+        request: Request<crate::Query>,
+    ) -> Result<Response<crate::DeleteResponse>, Status> {
+        let db = self.db.clone();
+        let q: indradb::Query = map_conversion_result(request.into_inner().try_into())?;
+        let count = map_jh_indra_result(tokio::task::spawn_blocking(move || db.delete_all_properties(q)).await)?;
+        Ok(self.respond(crate::DeleteResponse { count }))
     }
 
     async fn set_properties(&self, request: Request<crate::SetPropertiesRequest>) -> Result<Response<()>, Status> {
         let db = self.db.clone();
         let (q, name, value) = map_conversion_result(request.into_inner().try_into())?;
         map_jh_indra_result(tokio::task::spawn_blocking(move || db.set_properties(q, name, &value)).await)?;
-        Ok(Response::new(()))
+        Ok(self.respond(()))
+    }
+
+    async fn add_label(&self, request: Request<crate::LabelRequest>) -> Result<Response<()>, Status> {
+        let db = self.db.clone();
+        let (q, label) = map_conversion_result(request.into_inner().try_into())?;
+        map_jh_indra_result(tokio::task::spawn_blocking(move || db.add_label(q, label)).await)?;
+        Ok(self.respond(()))
+    }
+
+    async fn remove_label(&self, request: Request<crate::LabelRequest>) -> Result<Response<()>, Status> {
+        let db = self.db.clone();
+        let (q, label) = map_conversion_result(request.into_inner().try_into())?;
+        map_jh_indra_result(tokio::task::spawn_blocking(move || db.remove_label(q, label)).await)?;
+        Ok(self.respond(()))
+    }
+
+    async fn set_properties_batch(
+        &self,
+        request: Request<crate::SetPropertiesBatchRequest>,
+    ) -> Result<Response<()>, Status> {
+        let db = self.db.clone();
+        let (q, props) = map_conversion_result(request.into_inner().try_into())?;
+        map_jh_indra_result(tokio::task::spawn_blocking(move || db.set_properties_batch(q, props)).await)?;
+        Ok(self.respond(()))
+    }
+
+    async fn compare_and_set_property(
+        &self,
+        request: Request<crate::CompareAndSetPropertyRequest>,
+    ) -> Result<Response<crate::CompareAndSetPropertyResponse>, Status> {
+        let db = self.db.clone();
+        let (q, name, expected, new) = map_conversion_result(request.into_inner().try_into())?;
+        let swapped = map_jh_indra_result(
+            tokio::task::spawn_blocking(move || db.compare_and_set_property(q, name, expected.as_ref(), &new)).await,
+        )?;
+        Ok(self.respond(crate::CompareAndSetPropertyResponse { swapped }))
     }
 
     async fn bulk_insert(&self, request: Request<Streaming<crate::BulkInsertItem>>) -> Result<Response<()>, Status> {
@@ -273,7 +664,88 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         };
 
         map_jh_indra_result(tokio::task::spawn_blocking(move || db.bulk_insert(items)).await)?;
-        Ok(Response::new(()))
+        Ok(self.respond(()))
+    }
+
+    type BulkInsertLenientStream =
+        Pin<Box<dyn Stream<Item = Result<crate::BulkInsertFailure, Status>> + Send + Sync + 'static>>;
+    async fn bulk_insert_lenient(
+        &self,
+        request: Request<Streaming<crate::BulkInsertItem>>,
+    ) -> Result<Response<Self::BulkInsertLenientStream>, Status> {
+        let db = self.db.clone();
+        let mut stream = request.into_inner();
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+        tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(BULK_BATCH_SIZE);
+            let mut index = 0;
+            loop {
+                let next = stream.next().await;
+                let done = next.is_none();
+                match next {
+                    Some(Ok(item)) => {
+                        match map_conversion_result(item.try_into()) {
+                            Ok(item) => batch.push((index, item)),
+                            Err(status) => {
+                                if tx.send(Ok(bulk_insert_failure(index, status))).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        index += 1;
+                    }
+                    Some(Err(status)) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                    None => {}
+                }
+
+                if batch.len() >= BULK_BATCH_SIZE || (done && !batch.is_empty()) {
+                    let db = db.clone();
+                    let batch = std::mem::take(&mut batch);
+                    let failures =
+                        match tokio::task::spawn_blocking(move || bulk_insert_lenient_batch(&db, batch)).await {
+                            Ok(failures) => failures,
+                            Err(err) => {
+                                let _ = tx.send(Err(Status::internal(format!("{err}")))).await;
+                                return;
+                            }
+                        };
+                    for failure in failures {
+                        if tx.send(Ok(failure)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+
+                if done {
+                    return;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn bulk_delete(&self, request: Request<Streaming<crate::BulkDeleteItem>>) -> Result<Response<()>, Status> {
+        let mut stream = request.into_inner();
+        let mut items = Vec::<indradb::BulkDeleteItem>::with_capacity(BULK_BATCH_SIZE);
+
+        // Items are deleted in batches as they come in, rather than buffering
+        // the whole stream
+        while let Some(request) = stream.next().await {
+            items.push(map_conversion_result(request?.try_into())?);
+            if items.len() >= BULK_BATCH_SIZE {
+                bulk_delete_batch(self.db.clone(), std::mem::take(&mut items)).await?;
+            }
+        }
+        if !items.is_empty() {
+            bulk_delete_batch(self.db.clone(), items).await?;
+        }
+
+        Ok(self.respond(()))
     }
 
     async fn index_property(&self, request: Request<crate::IndexPropertyRequest>) -> Result<Response<()>, Status> {
@@ -281,7 +753,52 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
 
         let name: indradb::Identifier = map_conversion_result(request.into_inner().try_into())?;
         map_jh_indra_result(tokio::task::spawn_blocking(move || db.index_property(name)).await)?;
-        Ok(Response::new(()))
+        Ok(self.respond(()))
+    }
+
+    async fn drop_index(&self, request: Request<crate::DropIndexRequest>) -> Result<Response<()>, Status> {
+        let db = self.db.clone();
+
+        let name: indradb::Identifier = map_conversion_result(request.into_inner().try_into())?;
+        map_jh_indra_result(tokio::task::spawn_blocking(move || db.drop_index(name)).await)?;
+        Ok(self.respond(()))
+    }
+
+    async fn clear(&self, _: Request<()>) -> Result<Response<()>, Status> {
+        if !self.clear_enabled {
+            return Err(Status::permission_denied("clearing the database is disabled"));
+        }
+        let db = self.db.clone();
+        map_jh_indra_result(tokio::task::spawn_blocking(move || db.clear()).await)?;
+        Ok(self.respond(()))
+    }
+
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<crate::ChangeEvent, Status>> + Send + Sync + 'static>>;
+    async fn watch(&self, request: Request<crate::WatchRequest>) -> Result<Response<Self::WatchStream>, Status> {
+        let filter: indradb::ChangeFilter = map_conversion_result(request.into_inner().try_into())?;
+        let watcher = map_indradb_result(self.db.watch(filter))?;
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+        // Watches are long-lived, so they get their own thread rather than
+        // tying up one of tokio's blocking threads. If the client is slow,
+        // this blocks on sending, and the watcher drops events in turn.
+        thread::spawn(move || loop {
+            match watcher.recv_timeout(WATCH_POLL_INTERVAL) {
+                Ok(event) => {
+                    if tx.blocking_send(Ok(event.into())).is_err() {
+                        return;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if tx.is_closed() {
+                        return;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
 
     async fn execute_plugin(
@@ -297,13 +814,18 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
 
         if let Some(plugin) = self.plugins.entries.get(&request.name) {
             let db = self.db.clone();
-            let mut txn = db.datastore.transaction();
+            let mut txn = if request.readonly {
+                db.datastore.readonly_transaction()
+            } else {
+                db.datastore.transaction()
+            };
             let response = {
                 plugin
                     .call(&mut txn, arg)
                     .map_err(|err| Status::internal(format!("{err}")))?
             };
-            Ok(Response::new(crate::ExecutePluginResponse {
+            map_indradb_result(txn.commit())?;
+            Ok(self.respond(crate::ExecutePluginResponse {
                 value: Some(response.into()),
             }))
         } else {
@@ -312,6 +834,40 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
     }
 }
 
+// Enables compressed requests and responses on the service. Compressed
+// responses are only sent to clients that accept them.
+#[cfg(feature = "compression")]
+pub(crate) fn compressed<D>(
+    service: crate::indra_db_server::IndraDbServer<Server<D>>,
+) -> crate::indra_db_server::IndraDbServer<Server<D>>
+where
+    D: indradb::Datastore + Send + Sync + 'static,
+{
+    service
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip)
+}
+
+#[cfg(not(feature = "compression"))]
+pub(crate) fn compressed<D>(
+    service: crate::indra_db_server::IndraDbServer<Server<D>>,
+) -> crate::indra_db_server::IndraDbServer<Server<D>>
+where
+    D: indradb::Datastore + Send + Sync + 'static,
+{
+    service
+}
+
+// Sends a response uncompressed, even if the client accepts compressed
+// responses.
+#[cfg(feature = "compression")]
+fn disable_compression<T>(res: &mut Response<T>) {
+    res.disable_compression();
+}
+
+#[cfg(not(feature = "compression"))]
+fn disable_compression<T>(_res: &mut Response<T>) {}
+
 /// Runs the IndraDB server.
 ///
 /// # Arguments
@@ -325,7 +881,7 @@ pub async fn run<D>(db: Arc<indradb::Database<D>>, listener: TcpListener) -> Res
 where
     D: indradb::Datastore + Send + Sync + 'static,
 {
-    let service = crate::indra_db_server::IndraDbServer::new(Server::new(db));
+    let service = compressed(crate::indra_db_server::IndraDbServer::new(Server::new(db)));
     let incoming = TcpListenerStream::new(listener);
     TonicServer::builder()
         .add_service(service)
@@ -364,7 +920,7 @@ where
     }
 
     let server = Server::new_with_plugins(db, plugin_paths)?;
-    let service = crate::indra_db_server::IndraDbServer::new(server);
+    let service = compressed(crate::indra_db_server::IndraDbServer::new(server));
     let incoming = TcpListenerStream::new(listener);
     TonicServer::builder()
         .add_service(service)
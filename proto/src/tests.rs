@@ -13,27 +13,20 @@ use std::result::Result as StdResult;
 use std::time::Duration;
 
 use indradb::{
-    util, AllEdgeQuery, AllVertexQuery, BulkInsertItem, CountQueryExt, Datastore, DynIter, Edge,
-    EdgeWithPropertyPresenceQuery, EdgeWithPropertyValueQuery, Error, Identifier, Json, Query, QueryExt,
-    QueryOutputValue, RangeVertexQuery, Result, SpecificEdgeQuery, SpecificVertexQuery, Transaction, Vertex,
-    VertexWithPropertyPresenceQuery, VertexWithPropertyValueQuery,
+    util, AllEdgeQuery, AllVertexQuery, BulkInsertItem, CountQueryExt, Datastore, DatastoreStats, DynIter, Edge,
+    EdgeWithPropertyPresenceQuery, EdgeWithPropertyValueQuery, Error, Identifier, Json, Neighborhood, Query, QueryExt,
+    QueryOutputValue, QueryPlan, RangeDirection, RangeVertexQuery, Result, SpecificEdgeQuery, SpecificVertexQuery,
+    Transaction, Vertex, VertexWithPropertyPresenceQuery, VertexWithPropertyValueQuery,
 };
 
 use tokio::runtime::Runtime;
-use tokio::time::sleep;
 use tonic::transport::Endpoint;
 use uuid::Uuid;
 
 fn map_client_result<T>(result: StdResult<T, crate::ClientError>) -> Result<T> {
     result.map_err(|err| {
         match err {
-            // this is the only error variant we need to handle for testing
-            crate::ClientError::Grpc { inner }
-                if inner.code() == tonic::Code::Internal
-                    && inner.message() == "query attempted on a property that isn't indexed" =>
-            {
-                Error::NotIndexed
-            }
+            crate::ClientError::Indradb { inner } => inner,
             // unexpected error variant
             _ => panic!("{}", err),
         }
@@ -43,14 +36,29 @@ fn map_client_result<T>(result: StdResult<T, crate::ClientError>) -> Result<T> {
 pub struct ClientTransaction {
     client: Rc<RefCell<crate::Client>>,
     exec: Rc<RefCell<Runtime>>,
+    readonly: bool,
 }
 
 impl<'a> ClientTransaction {
+    fn check_writable(&self) -> Result<()> {
+        if self.readonly {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
     fn get<Q: Into<Query>>(&self, q: Q) -> Result<Vec<QueryOutputValue>> {
-        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().get(q)))
+        let mut client = self.client.borrow_mut();
+        let result = if self.readonly {
+            self.exec.borrow_mut().block_on(client.get_readonly(q))
+        } else {
+            self.exec.borrow_mut().block_on(client.get(q))
+        };
+        map_client_result(result)
     }
 
-    fn delete<Q: Into<Query>>(&self, q: Q) -> Result<()> {
+    fn delete<Q: Into<Query>>(&self, q: Q) -> Result<u64> {
         map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().delete(q)))
     }
 
@@ -62,17 +70,29 @@ impl<'a> ClientTransaction {
         )
     }
 
+    fn set_properties_batch<Q: Into<Query>>(&self, q: Q, props: Vec<(Identifier, Json)>) -> Result<()> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().set_properties_batch(q, props)),
+        )
+    }
+
     fn get_count<Q: Into<Query>>(&self, q: Q) -> u64 {
         util::extract_count(self.get(q).unwrap()).unwrap()
     }
 
     fn get_vertices<Q: Into<Query>>(&'a self, q: Q) -> Result<DynIter<'a, Vertex>> {
-        let vertices = util::extract_vertices(self.get(q)?).ok_or(Error::Unsupported)?;
+        let vertices = map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_vertices(q)),
+        )?;
         Ok(Box::new(vertices.into_iter().map(Ok)))
     }
 
     fn get_edges<Q: Into<Query>>(&'a self, q: Q) -> Result<DynIter<'a, Edge>> {
-        let edges = util::extract_edges(self.get(q)?).ok_or(Error::Unsupported)?;
+        let edges = map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().get_edges(q)))?;
         Ok(Box::new(edges.into_iter().map(Ok)))
     }
 }
@@ -82,6 +102,69 @@ impl<'a> Transaction<'a> for ClientTransaction {
         self.get_count(AllVertexQuery.count().unwrap())
     }
 
+    fn vertex_property_names(&self) -> Result<Vec<Identifier>> {
+        let (names, _) = map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_property_names()),
+        )?;
+        Ok(names)
+    }
+
+    fn edge_property_names(&self) -> Result<Vec<Identifier>> {
+        let (_, names) = map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_property_names()),
+        )?;
+        Ok(names)
+    }
+
+    fn indexed_properties(&self) -> Result<Vec<Identifier>> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_indexed_properties()),
+        )
+    }
+
+    fn stats(&self) -> Result<DatastoreStats> {
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().get_stats()))
+    }
+
+    fn explain(&'a self, q: Query) -> Result<QueryPlan> {
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().explain(q)))
+    }
+
+    fn vertex_count_by_type(&self, t: Identifier) -> Result<u64> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_vertex_count_by_type(t)),
+        )
+    }
+
+    fn vertex_neighborhood(
+        &'a self,
+        id: Uuid,
+        t_filter: Option<Identifier>,
+        limit: u32,
+    ) -> Result<Option<Neighborhood>> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_vertex_neighborhood(id, t_filter, limit)),
+        )
+    }
+
+    fn edge_counts(&'a self, id: Uuid, t_filter: Option<Identifier>) -> Result<(u64, u64)> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_edge_counts(id, t_filter)),
+        )
+    }
+
     fn all_vertices(&'a self) -> Result<DynIter<'a, Vertex>> {
         self.get_vertices(AllVertexQuery)
     }
@@ -90,19 +173,35 @@ impl<'a> Transaction<'a> for ClientTransaction {
         self.get_vertices(RangeVertexQuery::default().start_id(offset))
     }
 
+    fn range_vertices_descending(&'a self, offset: Uuid) -> Result<DynIter<'a, Vertex>> {
+        self.get_vertices(
+            RangeVertexQuery::default()
+                .start_id(offset)
+                .direction(RangeDirection::Descending),
+        )
+    }
+
     fn specific_vertices(&'a self, ids: Vec<Uuid>) -> Result<DynIter<'a, Vertex>> {
         self.get_vertices(SpecificVertexQuery::new(ids))
     }
 
     fn vertex_ids_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Uuid>>> {
         let q = VertexWithPropertyPresenceQuery::new(name);
-        let vertices = util::extract_vertices(self.get(q)?).unwrap();
+        let vertices = match self.get(q) {
+            Ok(output) => util::extract_vertices(output).unwrap(),
+            Err(Error::NotIndexed) => return Ok(None),
+            Err(err) => return Err(err),
+        };
         Ok(Some(Box::new(vertices.into_iter().map(|v| Ok(v.id)))))
     }
 
     fn vertex_ids_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Uuid>>> {
         let q = VertexWithPropertyValueQuery::new(name, value.clone());
-        let vertices = util::extract_vertices(self.get(q)?).unwrap();
+        let vertices = match self.get(q) {
+            Ok(output) => util::extract_vertices(output).unwrap(),
+            Err(Error::NotIndexed) => return Ok(None),
+            Err(err) => return Err(err),
+        };
         Ok(Some(Box::new(vertices.into_iter().map(|v| Ok(v.id)))))
     }
 
@@ -122,12 +221,13 @@ impl<'a> Transaction<'a> for ClientTransaction {
 
     fn range_reversed_edges(&'a self, offset: Edge) -> Result<DynIter<'a, Edge>> {
         let edges = util::extract_edges(self.get(AllEdgeQuery)?).unwrap();
-        let iter = edges
+        let mut edges: Vec<Edge> = edges
             .into_iter()
             .map(|e| e.reversed())
             .filter(move |e| e >= &offset)
-            .map(Ok);
-        Ok(Box::new(iter))
+            .collect();
+        edges.sort();
+        Ok(Box::new(edges.into_iter().map(Ok)))
     }
 
     fn specific_edges(&'a self, edges: Vec<Edge>) -> Result<DynIter<'a, Edge>> {
@@ -136,13 +236,21 @@ impl<'a> Transaction<'a> for ClientTransaction {
 
     fn edges_with_property(&'a self, name: Identifier) -> Result<Option<DynIter<'a, Edge>>> {
         let q = EdgeWithPropertyPresenceQuery::new(name);
-        let edges = util::extract_edges(self.get(q)?).unwrap();
+        let edges = match self.get(q) {
+            Ok(output) => util::extract_edges(output).unwrap(),
+            Err(Error::NotIndexed) => return Ok(None),
+            Err(err) => return Err(err),
+        };
         Ok(Some(Box::new(edges.into_iter().map(Ok))))
     }
 
     fn edges_with_property_value(&'a self, name: Identifier, value: &Json) -> Result<Option<DynIter<'a, Edge>>> {
         let q = EdgeWithPropertyValueQuery::new(name, value.clone());
-        let edges = util::extract_edges(self.get(q)?).unwrap();
+        let edges = match self.get(q) {
+            Ok(output) => util::extract_edges(output).unwrap(),
+            Err(Error::NotIndexed) => return Ok(None),
+            Err(err) => return Err(err),
+        };
         Ok(Some(Box::new(edges.into_iter().map(Ok))))
     }
 
@@ -202,15 +310,18 @@ impl<'a> Transaction<'a> for ClientTransaction {
         }
     }
 
-    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<()> {
+    fn delete_vertices(&mut self, vertices: Vec<Vertex>) -> Result<u64> {
+        self.check_writable()?;
         self.delete(SpecificVertexQuery::new(vertices.into_iter().map(|v| v.id).collect()))
     }
 
-    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<()> {
+    fn delete_edges(&mut self, edges: Vec<Edge>) -> Result<u64> {
+        self.check_writable()?;
         self.delete(SpecificEdgeQuery::new(edges))
     }
 
     fn delete_vertex_properties(&mut self, props: Vec<(Uuid, Identifier)>) -> Result<()> {
+        self.check_writable()?;
         for (id, name) in props {
             self.delete(SpecificVertexQuery::single(id).properties().unwrap().name(name))?;
         }
@@ -218,6 +329,7 @@ impl<'a> Transaction<'a> for ClientTransaction {
     }
 
     fn delete_edge_properties(&mut self, props: Vec<(Edge, Identifier)>) -> Result<()> {
+        self.check_writable()?;
         for (edge, name) in props {
             self.delete(SpecificEdgeQuery::single(edge).properties().unwrap().name(name))?;
         }
@@ -228,7 +340,12 @@ impl<'a> Transaction<'a> for ClientTransaction {
         map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().sync()))
     }
 
+    fn compact(&self) -> Result<()> {
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().compact()))
+    }
+
     fn create_vertex(&mut self, vertex: &Vertex) -> Result<bool> {
+        self.check_writable()?;
         map_client_result(
             self.exec
                 .borrow_mut()
@@ -237,6 +354,7 @@ impl<'a> Transaction<'a> for ClientTransaction {
     }
 
     fn create_edge(&mut self, edge: &Edge) -> Result<bool> {
+        self.check_writable()?;
         map_client_result(
             self.exec
                 .borrow_mut()
@@ -244,7 +362,17 @@ impl<'a> Transaction<'a> for ClientTransaction {
         )
     }
 
+    fn create_edge_if_absent(&mut self, edge: &Edge) -> Result<bool> {
+        self.check_writable()?;
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().create_edge_if_absent(edge)),
+        )
+    }
+
     fn bulk_insert(&mut self, items: Vec<BulkInsertItem>) -> Result<()> {
+        self.check_writable()?;
         map_client_result(
             self.exec
                 .borrow_mut()
@@ -253,6 +381,7 @@ impl<'a> Transaction<'a> for ClientTransaction {
     }
 
     fn index_property(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
         map_client_result(
             self.exec
                 .borrow_mut()
@@ -260,13 +389,57 @@ impl<'a> Transaction<'a> for ClientTransaction {
         )
     }
 
+    fn drop_index(&mut self, name: Identifier) -> Result<()> {
+        self.check_writable()?;
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().drop_index(name)),
+        )
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.check_writable()?;
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().clear()))
+    }
+
     fn set_vertex_properties(&mut self, vertex_ids: Vec<Uuid>, name: Identifier, value: &Json) -> Result<()> {
+        self.check_writable()?;
         self.set_properties(SpecificVertexQuery::new(vertex_ids), name, value)
     }
 
     fn set_edge_properties(&mut self, edges: Vec<Edge>, name: Identifier, value: &Json) -> Result<()> {
+        self.check_writable()?;
         self.set_properties(SpecificEdgeQuery::new(edges), name, value)
     }
+
+    fn compare_and_set_vertex_properties(
+        &mut self,
+        vertices: Vec<Vertex>,
+        name: Identifier,
+        expected: Option<&Json>,
+        new: &Json,
+    ) -> Result<bool> {
+        self.check_writable()?;
+        let q = SpecificVertexQuery::new(vertices.into_iter().map(|v| v.id).collect());
+        map_client_result(
+            self.exec.borrow_mut().block_on(
+                self.client
+                    .borrow_mut()
+                    .compare_and_set_property(q, name, expected, new),
+            ),
+        )
+    }
+
+    fn set_vertex_properties_batch(&mut self, vertex_ids: Vec<Uuid>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        self.check_writable()?;
+        self.set_properties_batch(SpecificVertexQuery::new(vertex_ids), props)
+    }
+
+    fn set_edge_properties_batch(&mut self, edges: Vec<Edge>, props: Vec<(Identifier, Json)>) -> Result<()> {
+        self.check_writable()?;
+        self.set_properties_batch(SpecificEdgeQuery::new(edges), props)
+    }
 }
 
 pub struct ClientDatastore {
@@ -278,20 +451,23 @@ impl ClientDatastore {
     pub fn new(port: u16, exec: Runtime) -> Self {
         let endpoint: Endpoint = format!("http://127.0.0.1:{port}").try_into().unwrap();
 
-        for _ in 0..5 {
-            if let Ok(mut client) = exec.block_on(crate::Client::new(endpoint.clone())) {
-                if exec.block_on(client.ping()).is_ok() {
-                    return Self {
-                        client: Rc::new(RefCell::new(client)),
-                        exec: Rc::new(RefCell::new(exec)),
-                    };
-                }
-            }
-
-            exec.block_on(sleep(Duration::from_secs(1)));
+        // The server may still be starting up, so retry for a few seconds
+        let config = crate::RetryConfig {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(1),
+            retry_non_idempotent: false,
+        };
+        let mut client = exec
+            .block_on(crate::Client::with_retry(endpoint, config))
+            .expect("Could not connect to the server after a few seconds");
+        exec.block_on(client.ping())
+            .expect("Could not connect to the server after a few seconds");
+
+        Self {
+            client: Rc::new(RefCell::new(client)),
+            exec: Rc::new(RefCell::new(exec)),
         }
-
-        panic!("Could not connect to the server after a few seconds");
     }
 }
 
@@ -301,6 +477,15 @@ impl Datastore for ClientDatastore {
         ClientTransaction {
             client: self.client.clone(),
             exec: self.exec.clone(),
+            readonly: false,
+        }
+    }
+
+    fn readonly_transaction(&'_ self) -> Self::Transaction<'_> {
+        ClientTransaction {
+            client: self.client.clone(),
+            exec: self.exec.clone(),
+            readonly: true,
         }
     }
 }
@@ -323,3 +508,211 @@ full_test_impl!({
 
     Database::new(ClientDatastore::new(port as u16, rt))
 });
+
+#[cfg(test)]
+mod client {
+    use std::convert::TryInto;
+    use std::net::ToSocketAddrs;
+    use std::sync::Arc;
+    #[cfg(feature = "compression")]
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{Client, ClientError, ClientPool};
+    use indradb::{util, AllVertexQuery, CountQueryExt, Identifier, MemoryDatastore};
+    #[cfg(feature = "compression")]
+    use indradb::{BulkInsertItem, Vertex};
+    use tokio::net::TcpListener;
+    use tokio::runtime::Runtime;
+    use tokio_stream::wrappers::TcpListenerStream;
+    #[cfg(feature = "compression")]
+    use tonic::codec::CompressionEncoding;
+    use tonic::service::interceptor::InterceptedService;
+    use tonic::service::Interceptor;
+    use tonic::transport::{Endpoint, Server as TonicServer};
+    use tonic::{Code, Request, Status};
+
+    fn bind(rt: &Runtime) -> (TcpListener, Endpoint) {
+        let addr = "127.0.0.1:0".to_socket_addrs().unwrap().next().unwrap();
+        let listener = rt.block_on(TcpListener::bind(&addr)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let endpoint = format!("http://127.0.0.1:{port}").try_into().unwrap();
+        (listener, endpoint)
+    }
+
+    // Runs the standard server on a random port.
+    fn serve(rt: &Runtime) -> Endpoint {
+        let (listener, endpoint) = bind(rt);
+        rt.spawn(crate::run_server(Arc::new(MemoryDatastore::new_db()), listener));
+        endpoint
+    }
+
+    // Runs a server on a random port, like `serve`, that passes each request
+    // through `interceptor` first.
+    fn serve_with_interceptor<I>(rt: &Runtime, interceptor: I) -> Endpoint
+    where
+        I: Interceptor + Clone + Send + 'static,
+    {
+        let (listener, endpoint) = bind(rt);
+        let server = crate::Server::new(Arc::new(MemoryDatastore::new_db()));
+        let service = crate::server::compressed(crate::indra_db_server::IndraDbServer::new(server));
+        let service = InterceptedService::new(service, interceptor);
+        rt.spawn(
+            TonicServer::builder()
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        endpoint
+    }
+
+    fn slow(req: Request<()>) -> Result<Request<()>, Status> {
+        thread::sleep(Duration::from_millis(500));
+        Ok(req)
+    }
+
+    fn check_auth(req: Request<()>) -> Result<Request<()>, Status> {
+        match req.metadata().get("authorization") {
+            Some(authorization) if authorization == "Bearer secret" => Ok(req),
+            _ => Err(Status::unauthenticated("invalid token")),
+        }
+    }
+
+    fn is_unauthenticated<T>(result: Result<T, ClientError>) -> bool {
+        matches!(result, Err(ClientError::Grpc { inner }) if inner.code() == Code::Unauthenticated)
+    }
+
+    #[test]
+    fn should_time_out() {
+        // The server blocks while handling requests, so it gets a runtime of
+        // its own to keep it from stalling the client's timer
+        let server_rt = Runtime::new().unwrap();
+        let endpoint = serve_with_interceptor(&server_rt, slow);
+        let rt = Runtime::new().unwrap();
+        let mut client = rt.block_on(Client::new(endpoint)).unwrap();
+
+        client.set_timeout(Some(Duration::from_millis(50)));
+        assert!(matches!(rt.block_on(client.ping()), Err(ClientError::Timeout)));
+        client.set_timeout(None);
+        rt.block_on(client.ping()).unwrap();
+    }
+
+    #[test]
+    fn should_set_auth_token() {
+        let rt = Runtime::new().unwrap();
+        let endpoint = serve_with_interceptor(&rt, check_auth);
+        let mut client = rt.block_on(Client::new(endpoint)).unwrap();
+        assert!(is_unauthenticated(rt.block_on(client.ping())));
+
+        client.set_auth_token(Some("secret".to_string())).unwrap();
+        rt.block_on(client.ping()).unwrap();
+
+        // Rotating the token applies to the next request
+        client.set_auth_token(Some("other".to_string())).unwrap();
+        assert!(is_unauthenticated(rt.block_on(client.ping())));
+        client.set_auth_token(None).unwrap();
+        assert!(is_unauthenticated(rt.block_on(client.ping())));
+
+        assert!(matches!(
+            client.set_auth_token(Some("sécret".to_string())),
+            Err(ClientError::InvalidAuthToken)
+        ));
+    }
+
+    #[test]
+    fn should_share_database_across_pool() {
+        let rt = Runtime::new().unwrap();
+        let endpoint = serve(&rt);
+        let pool = rt.block_on(ClientPool::new(endpoint.clone(), 0)).unwrap();
+        assert_eq!(pool.size(), 1);
+
+        let pool = rt.block_on(ClientPool::new(endpoint, 3)).unwrap();
+        assert_eq!(pool.size(), 3);
+        let t = Identifier::new("test").unwrap();
+        for _ in 0..6 {
+            rt.block_on(pool.client().create_vertex_from_type(t)).unwrap();
+        }
+        // Each connection sees every vertex, whichever one created it
+        for _ in 0..3 {
+            let output = rt.block_on(pool.client().get(AllVertexQuery.count().unwrap())).unwrap();
+            assert_eq!(util::extract_count(output), Some(6));
+        }
+    }
+
+    #[test]
+    fn should_set_pool_auth_token() {
+        let rt = Runtime::new().unwrap();
+        let endpoint = serve_with_interceptor(&rt, check_auth);
+        let mut pool = rt.block_on(ClientPool::new(endpoint, 2)).unwrap();
+        assert!(is_unauthenticated(rt.block_on(pool.client().ping())));
+
+        pool.set_auth_token(Some("secret".to_string())).unwrap();
+        for _ in 0..2 {
+            rt.block_on(pool.client().ping()).unwrap();
+        }
+
+        // An invalid token leaves every client as it was
+        assert!(matches!(
+            pool.set_auth_token(Some("sécret".to_string())),
+            Err(ClientError::InvalidAuthToken)
+        ));
+        for _ in 0..2 {
+            rt.block_on(pool.client().ping()).unwrap();
+        }
+    }
+
+    #[test]
+    fn should_shutdown() {
+        let server_rt = Runtime::new().unwrap();
+        let endpoint = serve_with_interceptor(&server_rt, slow);
+        let rt = Runtime::new().unwrap();
+        let client = rt.block_on(Client::new(endpoint)).unwrap();
+        let mut other = client.clone();
+        let mut in_flight = client.clone();
+        let request = rt.spawn(async move { in_flight.ping().await });
+
+        rt.block_on(async move {
+            // Give the request time to start before shutting down
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            client.shutdown().await;
+
+            // Requests in flight get to finish, but new ones are refused
+            assert!(request.await.unwrap().is_ok());
+            assert!(matches!(other.ping().await, Err(ClientError::ShutDown)));
+        });
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn should_compress() {
+        let rt = Runtime::new().unwrap();
+        let encodings = Arc::new(Mutex::new(Vec::new()));
+        let recorded_encodings = encodings.clone();
+        let endpoint = serve_with_interceptor(&rt, move |req: Request<()>| {
+            let encoding = req.metadata().get("grpc-encoding").cloned();
+            recorded_encodings.lock().unwrap().push(encoding);
+            Ok(req)
+        });
+        let mut client = rt
+            .block_on(Client::with_compression(endpoint.clone(), CompressionEncoding::Gzip))
+            .unwrap();
+
+        // Enough vertices for the request and response to be compressed
+        let t = Identifier::new("test").unwrap();
+        let items: Vec<BulkInsertItem> = (0..1000).map(|_| BulkInsertItem::Vertex(Vertex::new(t))).collect();
+        rt.block_on(client.bulk_insert(items)).unwrap();
+        assert_eq!(rt.block_on(client.get_vertices(AllVertexQuery)).unwrap().len(), 1000);
+        // Small responses are sent uncompressed
+        rt.block_on(client.ping()).unwrap();
+        assert!(encodings
+            .lock()
+            .unwrap()
+            .drain(..)
+            .all(|encoding| encoding.is_some_and(|encoding| encoding == "gzip")));
+
+        // Clients without compression can still use the server
+        let mut client = rt.block_on(Client::new(endpoint)).unwrap();
+        assert_eq!(rt.block_on(client.get_vertices(AllVertexQuery)).unwrap().len(), 1000);
+        assert!(encodings.lock().unwrap().iter().all(Option::is_none));
+    }
+}
@@ -96,4 +96,11 @@ pub async fn plugins() {
         client.execute_plugin("naive_vertex_count", ijson!(null)).await.unwrap(),
         ijson!(11)
     );
+    assert_eq!(
+        client
+            .execute_readonly_plugin("naive_vertex_count", ijson!(null))
+            .await
+            .unwrap(),
+        ijson!(11)
+    );
 }